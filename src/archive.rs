@@ -0,0 +1,416 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+// External Libraries
+use zip::{CompressionMethod, ZipArchive};
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::runrefs::{FABRIC_NOTEBOOK_SUFFIX, display_name};
+
+
+// ====================================================
+//  Constants
+// ====================================================
+// Separates the archive from a member in `export.zip!Sales.Notebook/notebook-content.py`.
+pub const ARCHIVE_SEPARATOR: char = '!';
+pub const MAX_MEMBER_BYTES: u64 = 16 * 1024 * 1024;
+pub const MAX_ARCHIVE_BYTES: u64 = 256 * 1024 * 1024;
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Default)]
+pub struct Archive {
+    pub path: PathBuf,
+    pub members: BTreeMap<String, Vec<u8>>,
+    pub skipped: Vec<String>,
+}
+
+// ====================================================
+//  Archive Paths
+// ====================================================
+pub fn is_archive_path(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("zip"))
+}
+
+
+pub fn split_archive_input(input: &str) -> Option<(PathBuf, Option<String>)> {
+
+    // Splits an input into its archive and, after `!`, the member or folder
+    // inside it. Returns `None` for an input that is not a `.zip`.
+
+    let (archive, inner) = match input.split_once(ARCHIVE_SEPARATOR) {
+        Some((archive, inner)) => (archive, Some(inner.trim_matches('/').to_string()).filter(|i| !i.is_empty())),
+        None => (input, None),
+    };
+    is_archive_path(Path::new(archive)).then(|| (PathBuf::from(archive), inner))
+}
+
+
+pub fn member_path(archive: &Path, member: &str) -> PathBuf {
+
+    // Names a member the way it is written on the command line; this is the
+    // notebook path in reports, the run log and the manifest.
+
+    PathBuf::from(format!("{}{ARCHIVE_SEPARATOR}{member}", archive.display()))
+}
+
+
+pub fn split_member_path(path: &Path) -> Option<(PathBuf, String)> {
+
+    // Reverses [`member_path`].
+
+    let text = path.to_str()?;
+    let (archive, member) = text.split_once(ARCHIVE_SEPARATOR)?;
+    is_archive_path(Path::new(archive)).then(|| (PathBuf::from(archive), member.to_string()))
+}
+
+
+pub fn member_doc_path(member: &str, docs_dir: &Path) -> PathBuf {
+
+    // Returns the document path of an archive member below `docs_dir`.
+    //
+    // # Description
+    // The member's folders are kept, so notebooks with the same name in
+    // different folders do not overwrite each other. A Fabric item
+    // `Sales.Notebook/notebook-content.py` becomes `Sales.md` in the folder
+    // that holds the item.
+
+    let member = Path::new(member);
+    let mut dir = member.parent().unwrap_or(Path::new("")).to_path_buf();
+    if dir.file_name().is_some_and(|n| n.to_string_lossy().ends_with(FABRIC_NOTEBOOK_SUFFIX)) {
+        dir.pop();
+    }
+    docs_dir.join(dir).join(format!("{}.md", display_name(member)))
+}
+
+
+fn is_safe_member(name: &str) -> bool {
+
+    // Refuses absolute names and `..`, so a member cannot place its document
+    // outside the docs directory.
+
+    let path = Path::new(name);
+    !name.starts_with('/') && !name.contains('\\') && path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+
+// ====================================================
+//  Reading
+// ====================================================
+impl Archive {
+
+    pub fn read(&self, member: &str) -> Option<&[u8]> {
+        self.members.get(member).map(Vec::as_slice)
+    }
+}
+
+
+pub fn open_archive<F>(path: &Path, select: F) -> Result<Archive, DoxcerError>
+where
+    F: Fn(&str) -> bool,
+{
+
+    // Reads the selected members of a ZIP archive into memory with the `zip`
+    // crate.
+    //
+    // # Description
+    // Only the central directory and the selected members are read; members
+    // may be stored or deflated. A member over `MAX_MEMBER_BYTES` is skipped,
+    // checked while decompressing as well as in the header, and one holding
+    // more than its header declares is corrupt; the selected members together
+    // over `MAX_ARCHIVE_BYTES` stop the read. Names
+    // that are not UTF-8 or point outside the archive, and members with
+    // another compression method, are skipped as well; every skipped member is
+    // listed in `skipped` with the reason.
+    //
+    // # Parameters
+    // * `select` – Chooses the members to load by name.
+    //
+    // # Returns
+    // * `Ok(Archive)` with the loaded members by name.
+    // * `Err(DoxcerError)` when the file is not a readable ZIP, a selected member is
+    //   password-protected or corrupt, or the members are too large together.
+
+    open_archive_with(path, select, MAX_MEMBER_BYTES, MAX_ARCHIVE_BYTES)
+}
+
+
+fn open_archive_with<F>(path: &Path, select: F, member_limit: u64, archive_limit: u64) -> Result<Archive, DoxcerError>
+where
+    F: Fn(&str) -> bool,
+{
+
+    // [`open_archive`] with the size limits passed in.
+
+    let label = path.display();
+    let file = File::open(path).map_err(|e| DoxcerError::Input(format!("Failed to open archive {label}: {e}")))?;
+    let mut zip = ZipArchive::new(file).map_err(|e| DoxcerError::Input(format!("{label} is not a readable ZIP archive: {e}")))?;
+
+    let mut archive = Archive { path: path.to_path_buf(), ..Archive::default() };
+    let mut total: u64 = 0;
+    for index in 0..zip.len() {
+        let entry = zip.by_index_raw(index).map_err(|e| DoxcerError::Input(format!("{label} is not a readable ZIP archive: {e}")))?;
+        let Ok(name) = String::from_utf8(entry.name_raw().to_vec()) else {
+            archive.skipped.push(format!("{}: member name is not UTF-8", String::from_utf8_lossy(entry.name_raw())));
+            continue;
+        };
+        if entry.is_dir() || !select(&name) {
+            continue;
+        }
+        if !is_safe_member(&name) {
+            archive.skipped.push(format!("{name}: member path leaves the archive"));
+            continue;
+        }
+        if entry.encrypted() {
+            return Err(DoxcerError::Input(format!("{label}{ARCHIVE_SEPARATOR}{name} is password-protected; encrypted archives are not supported, unpack it first")));
+        }
+        let method = entry.compression();
+        if method != CompressionMethod::Stored && method != CompressionMethod::Deflated {
+            archive.skipped.push(format!("{name}: compression method {method} is not supported"));
+            continue;
+        }
+        if entry.size() > member_limit {
+            archive.skipped.push(format!("{name}: member is {} bytes, over the limit of {member_limit} bytes", entry.size()));
+            continue;
+        }
+        drop(entry);
+
+        let bytes = match read_member(&mut zip, index, member_limit) {
+            Ok(bytes) => bytes,
+            Err(DoxcerError::Limit(reason)) => {
+                archive.skipped.push(format!("{name}: {reason}"));
+                continue;
+            }
+            Err(e) => return Err(DoxcerError::Input(format!("{label}{ARCHIVE_SEPARATOR}{name}: {e}"))),
+        };
+        total += bytes.len() as u64;
+        if total > archive_limit {
+            return Err(DoxcerError::Input(format!("{label}: the selected members exceed the limit of {archive_limit} bytes in total")));
+        }
+        archive.members.insert(name, bytes);
+    }
+    Ok(archive)
+}
+
+
+fn read_member(zip: &mut ZipArchive<File>, index: usize, limit: u64) -> Result<Vec<u8>, DoxcerError> {
+
+    // Decompresses one member, reading at most one byte past `limit`. A
+    // member over `limit` is a `DoxcerError::Limit`; the zip crate refuses
+    // data past the declared size and checks the CRC at the end.
+
+    let member = zip.by_index(index).map_err(|e| DoxcerError::Input(e.to_string()))?;
+    let mut bytes = Vec::new();
+    member
+        .take(limit + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| DoxcerError::Input(format!("cannot decompress: {e}; the archive is corrupt")))?;
+    if bytes.len() as u64 > limit {
+        return Err(DoxcerError::Limit(format!("decompressed data exceeds {limit} bytes")));
+    }
+    Ok(bytes)
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+    use tempfile::TempDir;
+    use zip::ZipWriter;
+    use zip::unstable::write::FileOptionsExt;
+    use zip::write::SimpleFileOptions;
+
+    fn write_zip(members: &[(&str, CompressionMethod, &[u8])]) -> (TempDir, PathBuf) {
+
+        // Writes a real archive with the zip crate, one member per entry.
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.zip");
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        for (name, method, data) in members {
+            zip.start_file(*name, SimpleFileOptions::default().compression_method(*method)).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+        (dir, path)
+    }
+
+
+    fn patch(path: &Path, from: &[u8], to: &[u8]) {
+
+        // Replaces every occurrence of `from` in the archive file, to corrupt
+        // a name, a size or the member data the way a broken file would.
+
+        let bytes = fs::read(path).unwrap();
+        let mut out = Vec::new();
+        let mut at = 0;
+        while at < bytes.len() {
+            if bytes[at..].starts_with(from) {
+                out.extend_from_slice(to);
+                at += from.len();
+            } else {
+                out.push(bytes[at]);
+                at += 1;
+            }
+        }
+        fs::write(path, out).unwrap();
+    }
+
+
+    #[test]
+    fn stored_and_deflated_members_are_read() {
+        let runs = vec![b'a'; 2581];
+        let (_dir, path) = write_zip(&[
+            ("ws/orders.py", CompressionMethod::Stored, b"x = 1\n"),
+            ("ws/Sales.Notebook/notebook-content.py", CompressionMethod::Deflated, b"y = 2\n"),
+            ("ws/runs.py", CompressionMethod::Deflated, &runs),
+            ("ws/readme.md", CompressionMethod::Deflated, b"not selected"),
+        ]);
+        let archive = open_archive(&path, |name| name.ends_with(".py")).unwrap();
+        assert_eq!(archive.read("ws/orders.py"), Some(&b"x = 1\n"[..]));
+        assert_eq!(archive.read("ws/Sales.Notebook/notebook-content.py"), Some(&b"y = 2\n"[..]));
+        assert_eq!(archive.read("ws/runs.py"), Some(&runs[..]));
+        assert_eq!(archive.members.len(), 3);
+        assert!(archive.skipped.is_empty());
+    }
+
+
+    #[test]
+    fn a_file_that_is_not_a_zip_is_an_input_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.zip");
+        fs::write(&path, "x = 1\n").unwrap();
+        let error = open_archive(&path, |_| true).unwrap_err();
+        assert!(matches!(error, DoxcerError::Input(_)));
+        assert!(error.to_string().contains("export.zip is not a readable ZIP archive"), "{error}");
+    }
+
+
+    #[test]
+    fn a_crc_mismatch_is_an_error() {
+        let (_dir, path) = write_zip(&[("ws/orders.py", CompressionMethod::Stored, b"x = 1\n")]);
+        patch(&path, b"x = 1\n", b"x = 2\n");
+        let error = open_archive(&path, |_| true).unwrap_err();
+        assert!(error.to_string().contains("export.zip!ws/orders.py: cannot decompress"), "{error}");
+        assert!(error.to_string().ends_with("the archive is corrupt"), "{error}");
+    }
+
+
+    #[test]
+    fn a_bomb_that_understates_its_size_is_refused() {
+        let (_dir, path) = write_zip(&[("ws/bomb.py", CompressionMethod::Deflated, &[b'a'; 2581])]);
+        patch(&path, &2581u32.to_le_bytes(), &100u32.to_le_bytes());
+        let error = open_archive_with(&path, |_| true, 1000, 1 << 20).unwrap_err();
+        assert!(error.to_string().contains("export.zip!ws/bomb.py: cannot decompress"), "{error}");
+        assert!(error.to_string().ends_with("the archive is corrupt"), "{error}");
+    }
+
+
+    #[test]
+    fn a_member_whose_header_is_over_the_limit_is_skipped() {
+        let (_dir, path) = write_zip(&[("ws/large.py", CompressionMethod::Deflated, &[b'a'; 5000])]);
+        let archive = open_archive_with(&path, |_| true, 1000, 1 << 20).unwrap();
+        assert_eq!(archive.skipped, vec!["ws/large.py: member is 5000 bytes, over the limit of 1000 bytes".to_string()]);
+        assert!(archive.members.is_empty());
+    }
+
+
+    #[test]
+    fn members_over_the_total_limit_stop_the_read() {
+        let (_dir, path) = write_zip(&[
+            ("ws/a.py", CompressionMethod::Stored, b"x = 1\n"),
+            ("ws/b.py", CompressionMethod::Stored, b"y = 2\n"),
+        ]);
+        let error = open_archive_with(&path, |_| true, 1000, 10).unwrap_err();
+        assert!(error.to_string().ends_with("the selected members exceed the limit of 10 bytes in total"), "{error}");
+    }
+
+
+    #[test]
+    fn an_encrypted_member_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.zip");
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        zip.start_file("ws/orders.py", SimpleFileOptions::default().with_deprecated_encryption(b"secret").unwrap()).unwrap();
+        zip.write_all(b"x = 1\n").unwrap();
+        zip.finish().unwrap();
+
+        let error = open_archive(&path, |_| true).unwrap_err();
+        assert!(error.to_string().contains("export.zip!ws/orders.py is password-protected"), "{error}");
+        assert!(open_archive(&path, |name| name != "ws/orders.py").is_ok());
+    }
+
+
+    #[test]
+    fn names_that_leave_the_archive_are_skipped() {
+        let (_dir, path) = write_zip(&[
+            ("../evil.py", CompressionMethod::Stored, b"x"),
+            ("/etc/evil.py", CompressionMethod::Stored, b"x"),
+            ("ws/orders.py", CompressionMethod::Stored, b"x = 1\n"),
+        ]);
+        let archive = open_archive(&path, |_| true).unwrap();
+        assert_eq!(archive.skipped, vec![
+            "../evil.py: member path leaves the archive".to_string(),
+            "/etc/evil.py: member path leaves the archive".to_string(),
+        ]);
+        assert_eq!(archive.members.len(), 1);
+    }
+
+
+    #[test]
+    fn safe_member_names() {
+        assert!(is_safe_member("ws/Sales.Notebook/notebook-content.py"));
+        assert!(is_safe_member("./ws/a.py"));
+        assert!(!is_safe_member("ws/../../a.py"));
+        assert!(!is_safe_member("..\\a.py"));
+        assert!(!is_safe_member("/a.py"));
+    }
+
+
+    #[test]
+    fn a_name_that_is_not_utf8_is_skipped() {
+        let (_dir, path) = write_zip(&[
+            ("ws/~sales.py", CompressionMethod::Stored, b"x = 1\n"),
+            ("ws/b.py", CompressionMethod::Stored, b"y"),
+        ]);
+        patch(&path, b"ws/~sales.py", b"ws/\xffsales.py");
+        let archive = open_archive(&path, |_| true).unwrap();
+        assert_eq!(archive.skipped, vec!["ws/\u{fffd}sales.py: member name is not UTF-8".to_string()]);
+        assert_eq!(archive.read("ws/b.py"), Some(&b"y"[..]));
+    }
+
+
+    #[test]
+    fn a_zip64_member_is_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.zip");
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        zip.start_file("ws/first.py", SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"first\n").unwrap();
+        zip.start_file("ws/orders.py", SimpleFileOptions::default().large_file(true)).unwrap();
+        zip.write_all(b"x = 1\n").unwrap();
+        zip.finish().unwrap();
+
+        let archive = open_archive(&path, |name| name == "ws/orders.py").unwrap();
+        assert_eq!(archive.read("ws/orders.py"), Some(&b"x = 1\n"[..]));
+        assert_eq!(archive.members.len(), 1);
+    }
+}
@@ -0,0 +1,331 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// External Libraries
+use serde::{Deserialize, Serialize};
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::locale::{Locale, format_decimal, format_percent, format_size_in};
+use crate::provenance::content_hash;
+use crate::runlog::RunRecord;
+use crate::timefmt::age_of;
+use crate::workspace::DEFAULT_CACHE_DIR;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const RESPONSES_DIR: &str = "responses";
+pub const ENTRY_EXTENSION: &str = "json";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub key: String,
+    pub model: String,
+    pub template_hash: String,
+    pub source_hash: String,
+    pub notebook: String,
+    pub created_at: String,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub entry: CacheEntry,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    pub dir: PathBuf,
+    // Entries older than this are treated as missing (`--cache-ttl`).
+    pub ttl: Option<Duration>,
+    // Whether `get` reads entries at all; off with `--no-cache`, so every
+    // request is sent and its answer replaces the entry.
+    pub read: bool,
+}
+
+
+// ====================================================
+//  Cache Keys
+// ====================================================
+pub fn default_cache_dir() -> PathBuf {
+
+    // Returns the directory holding cached responses (`.doxcer/cache/responses`).
+
+    Path::new(DEFAULT_CACHE_DIR).join(RESPONSES_DIR)
+}
+
+
+pub fn cache_key(request_json: &str) -> String {
+
+    // Derives the cache key of a request.
+    //
+    // # Description
+    // The key is the SHA-256 of the serialized request, which contains the model
+    // and the fully rendered prompt (template plus notebook). Any change to the
+    // notebook, the template, the settings or the model that was actually used
+    // therefore gives a different key.
+
+    let hash = content_hash(request_json.as_bytes());
+    hash.split_once(':').map(|(_, h)| h.to_string()).unwrap_or(hash)
+}
+
+
+// ====================================================
+//  Cache IO
+// ====================================================
+impl ResponseCache {
+
+    pub fn new(dir: &Path) -> ResponseCache {
+
+        // Opens the cache in `dir`; the directory is created on the first write.
+
+        ResponseCache { dir: dir.to_path_buf(), ttl: None, read: true }
+    }
+
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+
+        // Returns the file of the entry for `key`.
+
+        self.dir.join(format!("{key}.{ENTRY_EXTENSION}"))
+    }
+
+
+    pub fn get(&self, key: &str) -> Option<CacheEntry> {
+
+        // Returns the cached entry for a key.
+        //
+        // # Returns
+        // * `None` when there is no entry, the entry cannot be read or parsed,
+        //   it is older than `ttl`, or reading is off.
+
+        if !self.read {
+            return None;
+        }
+        let raw = fs::read_to_string(self.entry_path(key)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&raw).ok()?;
+        match self.ttl {
+            Some(ttl) if age_of(&entry.created_at).is_none_or(|age| age > ttl) => None,
+            _ => Some(entry),
+        }
+    }
+
+
+    pub fn put(&self, entry: &CacheEntry) -> Result<(), DoxcerError> {
+
+        // Stores an entry atomically.
+        //
+        // # Description
+        // The entry is written to a `.partial` file and renamed into place, so a
+        // concurrent reader sees either the complete entry or none at all.
+
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| DoxcerError::Input(format!("Failed to create cache {}: {e}", self.dir.display())))?;
+        let path = self.entry_path(&entry.key);
+        let tmp = path.with_extension(format!("{ENTRY_EXTENSION}.partial"));
+        let json = serde_json::to_string_pretty(entry)
+            .map_err(|e| DoxcerError::Input(format!("Failed to serialize cache entry: {e}")))?;
+        fs::write(&tmp, json).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", tmp.display())))?;
+        fs::rename(&tmp, &path).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", path.display())))
+    }
+
+
+    pub fn entries(&self) -> Result<Vec<CacheFile>, DoxcerError> {
+
+        // Lists all complete cache entries.
+        //
+        // # Description
+        // `.partial` files and entries that cannot be read or parsed (for example
+        // because a running batch is writing them) are skipped. A missing cache
+        // directory has no entries.
+
+        let dir = match fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(DoxcerError::Input(format!("Failed to read cache {}: {e}", self.dir.display()))),
+        };
+
+        let mut files: Vec<CacheFile> = dir
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == ENTRY_EXTENSION))
+            .filter_map(|path| {
+                let size = fs::metadata(&path).ok()?.len();
+                let entry = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+                Some(CacheFile { path, size, entry })
+            })
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+
+    pub fn remove(&self, file: &CacheFile) -> Result<bool, DoxcerError> {
+
+        // Removes an entry; an entry that is already gone is not an error.
+        //
+        // # Returns
+        // * `Ok(true)` if this call removed the file, `Ok(false)` if it was gone.
+
+        match fs::remove_file(&file.path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(DoxcerError::Input(format!("Failed to remove {}: {e}", file.path.display()))),
+        }
+    }
+}
+
+
+// ====================================================
+//  Statistics
+// ====================================================
+pub fn render_stats(files: &[CacheFile], records: &[RunRecord], locale: Locale) -> String {
+
+    // Renders `doxcer cache stats`.
+    //
+    // # Description
+    // Shows the number of entries and their total size, the hit-rate over all
+    // run-log records that consulted the cache, and a breakdown by model and by
+    // template hash. Numbers follow the `lang` locale.
+
+    let total: u64 = files.iter().map(|f| f.size).sum();
+    let mut out = format!("Entries: {}\nSize: {}\n", files.len(), format_size_in(total as usize, locale));
+
+    let consulted: Vec<bool> = records.iter().filter_map(|r| r.cache_hit).collect();
+    let hits = consulted.iter().filter(|h| **h).count();
+    if consulted.is_empty() {
+        out.push_str("Hit-rate: - (no cached runs in the run log)\n");
+    } else {
+        out.push_str(&format!(
+            "Hit-rate: {} ({} of {} lookups)\n",
+            format_percent(hits as f64 * 100.0 / consulted.len() as f64, 1, locale),
+            format_decimal(hits as f64, 0, locale),
+            format_decimal(consulted.len() as f64, 0, locale)
+        ));
+    }
+
+    let breakdown = |label: &str, key: fn(&CacheEntry) -> &str| {
+        let mut groups: BTreeMap<&str, (usize, u64)> = BTreeMap::new();
+        for f in files {
+            let group = groups.entry(key(&f.entry)).or_default();
+            group.0 += 1;
+            group.1 += f.size;
+        }
+        let mut text = format!("By {label}:\n");
+        for (name, (count, size)) in groups {
+            text.push_str(&format!("  {name}  {count} entries, {}\n", format_size_in(size as usize, locale)));
+        }
+        text
+    };
+    if !files.is_empty() {
+        out.push_str(&breakdown("model", |e| &e.model));
+        out.push_str(&breakdown("template", |e| &e.template_hash));
+    }
+    out
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn entry(key: &str, age: Duration) -> CacheEntry {
+        CacheEntry {
+            key: key.to_string(),
+            model: "m".to_string(),
+            template_hash: "sha256:t".to_string(),
+            source_hash: "sha256:s".to_string(),
+            notebook: "orders.py".to_string(),
+            created_at: (Utc::now() - chrono::Duration::from_std(age).unwrap()).to_rfc3339(),
+            text: format!("# {key}\n"),
+            tags: BTreeMap::new(),
+        }
+    }
+
+
+    #[test]
+    fn a_stored_entry_is_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(&dir.path().join("responses"));
+        assert!(cache.get("a").is_none());
+        cache.put(&entry("a", Duration::ZERO)).unwrap();
+        assert_eq!(cache.get("a").unwrap().text, "# a\n");
+        assert_eq!(cache_key("{}"), cache_key("{}"));
+        assert_ne!(cache_key("{}"), cache_key("{ }"));
+    }
+
+
+    #[test]
+    fn an_entry_older_than_the_ttl_is_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache = ResponseCache::new(dir.path());
+        cache.put(&entry("old", Duration::from_secs(2 * 3600))).unwrap();
+        let mut undated = entry("undated", Duration::ZERO);
+        undated.created_at = "yesterday".to_string();
+        cache.put(&undated).unwrap();
+
+        assert!(cache.get("old").is_some() && cache.get("undated").is_some(), "no ttl, no expiry");
+        cache.ttl = Some(Duration::from_secs(3600));
+        assert!(cache.get("old").is_none());
+        assert!(cache.get("undated").is_none(), "an unreadable date counts as expired");
+        cache.ttl = Some(Duration::from_secs(3 * 3600));
+        assert!(cache.get("old").is_some());
+        assert_eq!(cache.entries().unwrap().len(), 2, "expired entries stay until pruned");
+    }
+
+
+    #[test]
+    fn no_cache_skips_reads_but_still_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache { read: false, ..ResponseCache::new(dir.path()) };
+        cache.put(&entry("a", Duration::ZERO)).unwrap();
+        assert!(cache.get("a").is_none());
+
+        let mut fresh = entry("a", Duration::ZERO);
+        fresh.text = "# fresh\n".to_string();
+        cache.put(&fresh).unwrap();
+        assert_eq!(ResponseCache::new(dir.path()).get("a").unwrap().text, "# fresh\n");
+    }
+
+
+    #[test]
+    fn a_corrupt_entry_is_a_miss_and_is_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(dir.path());
+        fs::write(dir.path().join("a.json"), "{\"key\": \"a\", \"text\": ").unwrap();
+        fs::write(dir.path().join("b.json.partial"), "{").unwrap();
+        assert!(cache.get("a").is_none());
+        assert!(cache.entries().unwrap().is_empty());
+
+        cache.put(&entry("a", Duration::ZERO)).unwrap();
+        assert_eq!(cache.get("a").unwrap().text, "# a\n");
+        let files = cache.entries().unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(cache.remove(&files[0]).unwrap());
+        assert!(!cache.remove(&files[0]).unwrap());
+    }
+}
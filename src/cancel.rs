@@ -0,0 +1,380 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(unix)]
+use std::sync::atomic::AtomicUsize;
+use std::sync::{mpsc, Arc, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// External Libraries
+use reqwest::blocking::{RequestBuilder, Response};
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+const READ_CHUNK: usize = 8192;
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    flag: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+// A response body read on a worker thread, so a read that waits on a silent
+// server can be given up on; see [`CancellableReader::new`].
+pub struct CancellableReader {
+    token: CancelToken,
+    chunks: mpsc::Receiver<io::Result<Vec<u8>>>,
+    pending: Vec<u8>,
+    offset: usize,
+    done: bool,
+}
+
+impl CancelToken {
+
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+
+    pub fn with_deadline(&self, deadline: Instant) -> CancelToken {
+
+        // Returns a token that shares this token's flag and is also cancelled
+        // once `deadline` passes (or the earlier deadline it already had).
+
+        let deadline = self.deadline.map_or(deadline, |own| own.min(deadline));
+        CancelToken { flag: Arc::clone(&self.flag), deadline: Some(deadline) }
+    }
+
+
+    pub fn cancel(&self) {
+
+        // Cancels the token and every clone of it.
+
+        self.flag.store(true, Ordering::SeqCst);
+    }
+
+
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::SeqCst) || self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+
+    pub fn timed_out(&self) -> bool {
+
+        // Whether the token is cancelled only because its deadline passed,
+        // not through `cancel`.
+
+        !self.flag.load(Ordering::SeqCst) && self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+
+    pub fn remaining(&self) -> Option<Duration> {
+
+        // The time left until the token's deadline; `None` without one.
+
+        self.deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+
+    pub fn sleep(&self, duration: Duration) -> bool {
+
+        // Sleeps for `duration` (a retry delay) or until the token is
+        // cancelled, and returns whether the whole duration passed.
+
+        let until = Instant::now() + duration;
+        while !self.is_cancelled() {
+            let left = until.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return true;
+            }
+            thread::sleep(left.min(POLL_INTERVAL));
+        }
+        false
+    }
+}
+
+impl CancellableReader {
+
+    pub fn new(token: &CancelToken, mut reader: impl Read + Send + 'static) -> CancellableReader {
+
+        // Starts reading `reader` on its own thread.
+        //
+        // # Description
+        // Reads block until the next chunk arrives or the token is cancelled;
+        // a cancelled read fails with `ErrorKind::TimedOut`, so callers can
+        // tell it from a connection error with [`CancelToken::is_cancelled`].
+        // The thread is left to finish on its own once the reader is dropped.
+
+        let (sender, chunks) = mpsc::sync_channel(1);
+        thread::spawn(move || {
+            let mut buffer = vec![0u8; READ_CHUNK];
+            loop {
+                let chunk = match reader.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(n) => Ok(buffer[..n].to_vec()),
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => Err(e),
+                };
+                let failed = chunk.is_err();
+                if sender.send(chunk).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        CancellableReader { token: token.clone(), chunks, pending: Vec::new(), offset: 0, done: false }
+    }
+}
+
+impl Read for CancellableReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.offset == self.pending.len() && !self.done {
+            if self.token.is_cancelled() {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "cancelled"));
+            }
+            match self.chunks.recv_timeout(POLL_INTERVAL) {
+                Ok(chunk) => {
+                    self.pending = chunk?;
+                    self.offset = 0;
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => self.done = true,
+            }
+        }
+        let n = buf.len().min(self.pending.len() - self.offset);
+        buf[..n].copy_from_slice(&self.pending[self.offset..self.offset + n]);
+        self.offset += n;
+        Ok(n)
+    }
+}
+
+
+// ====================================================
+//  Running
+// ====================================================
+pub fn run_cancellable<T, F>(token: &CancelToken, work: F) -> Option<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+
+    // Runs blocking work (an HTTP request) until it finishes or the token is
+    // cancelled.
+    //
+    // # Description
+    // The work runs on its own thread while the caller polls the token, so a
+    // request that never answers can still be given up on. A cancelled worker
+    // is left to finish on its own; its result is dropped. The work must
+    // therefore end by itself: [`send`] relies on the client's timeouts.
+    //
+    // # Returns
+    // * `Some(T)` – the work finished first.
+    // * `None` – the token was cancelled (or its deadline passed) first.
+
+    if token.is_cancelled() {
+        return None;
+    }
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(work());
+    });
+    loop {
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(result) => return Some(result),
+            Err(mpsc::RecvTimeoutError::Timeout) if token.is_cancelled() => return None,
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => return None,
+        }
+    }
+}
+
+
+pub fn send(token: &CancelToken, request: RequestBuilder) -> Result<Response, DoxcerError> {
+
+    // Sends a request and waits for the response headers, giving up when the
+    // token is cancelled. The body is read separately, e.g. through a
+    // [`CancellableReader`] on the same token.
+    //
+    // # Description
+    // A request given up on keeps its worker thread and connection until the
+    // client's timeout ends it. When the token has a deadline, the request
+    // times out at that deadline as well, so `--file-timeout` leaves nothing
+    // running behind it.
+    //
+    // # Returns
+    // * `Ok(Response)` – the server answered, with any status.
+    // * `Err(DoxcerError::Cancelled)` – the token was cancelled first.
+    // * `Err(DoxcerError::Provider)` – the request could not be sent.
+
+    let request = match token.remaining() {
+        Some(left) => request.timeout(left),
+        None => request,
+    };
+    match run_cancellable(token, move || request.send()) {
+        Some(Ok(response)) => Ok(response),
+        Some(Err(_)) if token.is_cancelled() => Err(DoxcerError::Cancelled { partial: None }),
+        Some(Err(e)) => Err(DoxcerError::Provider(format!("Request error: {e}"))),
+        None => Err(DoxcerError::Cancelled { partial: None }),
+    }
+}
+
+
+// ====================================================
+//  Interrupts
+// ====================================================
+static INTERRUPT_TOKEN: OnceLock<CancelToken> = OnceLock::new();
+
+#[cfg(unix)]
+static INTERRUPTS: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(unix)]
+extern "C" fn on_interrupt(_signal: libc::c_int) {
+
+    // The first signal cancels the token; a second one exits at once. Only
+    // atomics are touched here, as a signal handler must.
+
+    if INTERRUPTS.fetch_add(1, Ordering::SeqCst) > 0 {
+        // SAFETY: `_exit` is async-signal-safe.
+        unsafe { libc::_exit(130) };
+    }
+    if let Some(token) = INTERRUPT_TOKEN.get() {
+        token.cancel();
+    }
+}
+
+
+pub fn interrupt_token() -> CancelToken {
+
+    // Returns the token that Ctrl-C (`SIGINT`) and `SIGTERM` cancel,
+    // installing the handler on first use.
+    //
+    // # Description
+    // The request in flight is abandoned and the run ends with exit code 130;
+    // a second signal exits immediately. On other platforms the default
+    // handling (exit at once) is kept and the token is never cancelled.
+
+    INTERRUPT_TOKEN
+        .get_or_init(|| {
+            #[cfg(unix)]
+            {
+                let handler = on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t;
+                // SAFETY: the handler only touches atomics and calls `_exit`.
+                unsafe {
+                    libc::signal(libc::SIGINT, handler);
+                    libc::signal(libc::SIGTERM, handler);
+                }
+            }
+            CancelToken::new()
+        })
+        .clone()
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::net::TcpListener;
+
+    fn silent_server() -> String {
+
+        // Accepts connections and reads the request, but never answers.
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let mut open = Vec::new();
+            for stream in listener.incoming().flatten() {
+                open.push(stream);
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn cancelling_gives_up_on_a_server_that_never_answers() {
+        let token = CancelToken::new();
+        let canceller = token.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            canceller.cancel();
+        });
+        let started = Instant::now();
+        let request = reqwest::blocking::Client::new().post(silent_server()).body("{}");
+        assert_eq!(send(&token, request).unwrap_err(), DoxcerError::Cancelled { partial: None });
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+
+    #[test]
+    fn a_deadline_times_out_without_cancelling_the_shared_flag() {
+        let token = CancelToken::new();
+        let file = token.with_deadline(Instant::now() + Duration::from_millis(100));
+        let request = reqwest::blocking::Client::new().get(silent_server());
+        assert!(send(&file, request).is_err());
+        assert!(file.timed_out());
+        assert!(!token.is_cancelled());
+        assert!(!file.sleep(Duration::from_secs(5)));
+    }
+
+
+    #[test]
+    fn a_deadline_also_ends_the_abandoned_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (closed, closes) = mpsc::channel();
+        thread::spawn(move || {
+            let mut stream = listener.incoming().flatten().next().unwrap();
+            let mut buffer = [0u8; 1024];
+            while stream.read(&mut buffer).is_ok_and(|n| n > 0) {}
+            let _ = closed.send(());
+        });
+        let file = CancelToken::new().with_deadline(Instant::now() + Duration::from_millis(200));
+        let request = reqwest::blocking::Client::new().get(url);
+        assert_eq!(send(&file, request).unwrap_err(), DoxcerError::Cancelled { partial: None });
+        // The worker's request hits the same deadline and closes its connection.
+        assert!(closes.recv_timeout(Duration::from_secs(5)).is_ok());
+    }
+
+
+    #[test]
+    fn a_cancelled_reader_stops_a_body_that_stalls() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let mut stream = listener.incoming().flatten().next().unwrap();
+            stream.write_all(b"data: first\n\n").unwrap();
+            thread::sleep(Duration::from_secs(30));
+        });
+        let stream = std::net::TcpStream::connect(address).unwrap();
+        let token = CancelToken::new();
+        let mut reader = CancellableReader::new(&token, stream);
+
+        let mut first = [0u8; 13];
+        reader.read_exact(&mut first).unwrap();
+        assert_eq!(&first, b"data: first\n\n");
+        token.cancel();
+        assert_eq!(reader.read(&mut first).unwrap_err().kind(), io::ErrorKind::TimedOut);
+    }
+}
@@ -0,0 +1,223 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fmt;
+use std::mem;
+use std::path::Path;
+use std::str::FromStr;
+
+// External Libraries
+use serde::Deserialize;
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::excerpt::{Cell, split_cells};
+use crate::oversize::{CHARS_PER_TOKEN, count_tokens};
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChunkStrategy {
+    // Whole cells per chunk; only a cell that is too large by itself is cut.
+    #[default]
+    Cells,
+    // Whole lines per chunk, regardless of the cell markers.
+    Lines,
+    // Fixed windows of the token budget, cut anywhere.
+    Tokens,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    // Position of the chunk, starting at 1.
+    pub index: usize,
+    // First and last cell of the chunk; `None` when the strategy ignores cells.
+    pub cells: Option<(usize, usize)>,
+    pub text: String,
+}
+
+impl fmt::Display for ChunkStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkStrategy::Cells => write!(f, "cells"),
+            ChunkStrategy::Lines => write!(f, "lines"),
+            ChunkStrategy::Tokens => write!(f, "tokens"),
+        }
+    }
+}
+
+impl FromStr for ChunkStrategy {
+    type Err = DoxcerError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "cells" => Ok(ChunkStrategy::Cells),
+            "lines" => Ok(ChunkStrategy::Lines),
+            "tokens" => Ok(ChunkStrategy::Tokens),
+            other => Err(DoxcerError::Usage(format!("expected cells, lines or tokens, got {other:?}"))),
+        }
+    }
+}
+
+
+// ====================================================
+//  Splitting
+// ====================================================
+pub fn split_chunks(path: &Path, notebook: &str, strategy: ChunkStrategy, budget: usize, model: &str) -> Vec<Chunk> {
+
+    // Splits a notebook into chunks of at most `budget` tokens, counted for
+    // `model` (see [`count_tokens`]).
+    //
+    // # Description
+    // `cells` packs consecutive cells into a chunk until the next one would not
+    // fit; a single cell over the budget is cut by lines. `lines` does the same
+    // with lines and `tokens` cuts windows of `budget` tokens. A `.ipynb`
+    // notebook is split by the sources of its cells, not by its JSON.
+
+    let budget = budget.max(1);
+    let cells = split_cells(path, notebook).unwrap_or_default();
+    let source = match path.extension().is_some_and(|e| e == "ipynb") {
+        true => cells.iter().map(|c| c.text.as_str()).collect::<Vec<_>>().join("\n"),
+        false => notebook.to_string(),
+    };
+    let texts: Vec<(Option<(usize, usize)>, String)> = match strategy {
+        ChunkStrategy::Cells if !cells.is_empty() => pack_cells(&cells, budget, model),
+        ChunkStrategy::Cells | ChunkStrategy::Lines => pack_lines(&source, budget, model).into_iter().map(|t| (None, t)).collect(),
+        ChunkStrategy::Tokens => token_windows(&source, budget).into_iter().map(|t| (None, t)).collect(),
+    };
+    texts
+        .into_iter()
+        .filter(|(_, text)| !text.trim().is_empty())
+        .enumerate()
+        .map(|(i, (cells, text))| Chunk { index: i + 1, cells, text })
+        .collect()
+}
+
+
+fn pack_cells(cells: &[Cell], budget: usize, model: &str) -> Vec<(Option<(usize, usize)>, String)> {
+
+    // Packs whole cells into chunks; see `split_chunks`.
+
+    let mut chunks: Vec<(Option<(usize, usize)>, String)> = Vec::new();
+    let mut current: Option<(usize, usize, String)> = None;
+    let mut used = 0;
+    for cell in cells {
+        let tokens = count_tokens(&cell.text, model);
+        if tokens > budget {
+            if let Some((first, last, text)) = current.take() {
+                chunks.push((Some((first, last)), text));
+            }
+            chunks.extend(pack_lines(&cell.text, budget, model).into_iter().map(|t| (Some((cell.index, cell.index)), t)));
+            continue;
+        }
+        match current.as_mut() {
+            Some((_, last, text)) if used + 1 + tokens <= budget => {
+                text.push('\n');
+                text.push_str(&cell.text);
+                *last = cell.index;
+                used += 1 + tokens;
+            }
+            _ => {
+                if let Some((first, last, text)) = current.take() {
+                    chunks.push((Some((first, last)), text));
+                }
+                current = Some((cell.index, cell.index, cell.text.clone()));
+                used = tokens;
+            }
+        }
+    }
+    if let Some((first, last, text)) = current {
+        chunks.push((Some((first, last)), text));
+    }
+    chunks
+}
+
+
+fn pack_lines(text: &str, budget: usize, model: &str) -> Vec<String> {
+
+    // Packs whole lines into chunks of at most `budget` tokens; a line over the
+    // budget by itself is cut into token windows.
+
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut used = 0;
+    for line in text.split_inclusive('\n') {
+        let tokens = count_tokens(line, model);
+        if tokens > budget {
+            if !current.is_empty() {
+                chunks.push(mem::take(&mut current));
+            }
+            chunks.extend(token_windows(line, budget));
+            used = 0;
+            continue;
+        }
+        if !current.is_empty() && used + tokens > budget {
+            chunks.push(mem::take(&mut current));
+            used = 0;
+        }
+        current.push_str(line);
+        used += tokens;
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+
+fn token_windows(text: &str, budget: usize) -> Vec<String> {
+
+    // Cuts `text` into windows of `budget` estimated tokens.
+
+    let chars: Vec<char> = text.chars().collect();
+    chars.chunks(budget * CHARS_PER_TOKEN).map(|window| window.iter().collect()).collect()
+}
+
+
+// ====================================================
+//  Prompts
+// ====================================================
+pub fn chunk_banner(name: &str, chunk: &Chunk, total: usize) -> String {
+
+    // Returns the note put above one chunk of a notebook, so the model documents
+    // only that part.
+
+    let cells = match chunk.cells {
+        Some((first, last)) if first == last => format!(" (cel {first})"),
+        Some((first, last)) => format!(" (cellen {first}-{last})"),
+        None => String::new(),
+    };
+    format!(
+        "Let op: {name} is te groot om in één keer te versturen en is in {total} delen gesplitst. \
+         Hieronder staat deel {}{cells}. Documenteer alleen wat hieronder staat; de delen worden \
+         daarna samengevoegd tot één document.",
+        chunk.index
+    )
+}
+
+
+pub fn merge_input(name: &str, parts: &[(Chunk, String)]) -> String {
+
+    // Returns the input of the final merge prompt: the documentation of every
+    // chunk, in order, with the instruction to turn them into one document.
+
+    let mut input = format!(
+        "Let op: hieronder staat geen notebook, maar de documentatie van de {} delen van {name}, \
+         in volgorde. Voeg ze samen tot één samenhangend document volgens de instructies: elke \
+         sectie één keer, zonder herhalingen en zonder te vermelden dat het notebook in delen \
+         is gedocumenteerd.",
+        parts.len()
+    );
+    for (chunk, doc) in parts {
+        input.push_str(&format!("\n\n### Deel {}\n\n{}", chunk.index, doc.trim()));
+    }
+    input
+}
@@ -0,0 +1,65 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Clipboard
+// ====================================================
+#[cfg(feature = "clipboard")]
+pub fn copy_to_clipboard(text: &str) -> Result<(), DoxcerError> {
+
+    // Places the given text on the system clipboard.
+    //
+    // # Description
+    // Uses `arboard`, which talks to the platform clipboard directly. On headless
+    // machines (no display server) opening the clipboard fails; that is returned
+    // as an error so callers can degrade to a warning. On Linux the contents are
+    // handed over to the clipboard manager when the handle is dropped.
+    //
+    // # Returns
+    // * `Ok(())` once the clipboard holds the text.
+    // * `Err(DoxcerError)` if no clipboard is available or the copy failed.
+
+    let mut clipboard = arboard::Clipboard::new()
+        .map_err(|e| DoxcerError::Input(format!("No clipboard available: {e}")))?;
+    clipboard
+        .set_text(text.to_string())
+        .map_err(|e| DoxcerError::Input(format!("Failed to copy to clipboard: {e}")))
+}
+
+
+#[cfg(not(feature = "clipboard"))]
+pub fn copy_to_clipboard(_text: &str) -> Result<(), DoxcerError> {
+
+    // Fallback for builds without the `clipboard` feature.
+
+    Err(DoxcerError::Input("doxcer was built without clipboard support (rebuild with `--features clipboard`)".to_string()))
+}
+
+
+// ====================================================
+//  Formatting
+// ====================================================
+pub fn format_size(bytes: usize) -> String {
+
+    // Formats a byte count for humans, e.g. `512 B`, `4.2 KB`, `1.3 MB`.
+
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+
+    let b = bytes as f64;
+    if b >= MB {
+        format!("{:.1} MB", b / MB)
+    } else if b >= KB {
+        format!("{:.1} KB", b / KB)
+    } else {
+        format!("{bytes} B")
+    }
+}
@@ -0,0 +1,742 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// External Libraries
+use serde::Deserialize;
+
+// Internal Modules
+use crate::chunk::ChunkStrategy;
+use crate::contextfiles::DEFAULT_MAX_CONTEXT_TOKENS;
+use crate::cost::Price;
+use crate::error::DoxcerError;
+use crate::granularity::Granularity;
+use crate::limits::DEFAULT_PROVIDER;
+use crate::links::LinkPolicy;
+use crate::oversize::OversizePolicy;
+use crate::pii::PiiPolicy;
+use crate::posthook::DEFAULT_POST_HOOK_TIMEOUT_SECS;
+use crate::profile::ProfileConfig;
+use crate::provider::ReasoningEffort;
+use crate::retry::{DEFAULT_RETRY_BASE_DELAY_MS, DEFAULT_RETRY_MAX_ATTEMPTS};
+use crate::routing::{ModelRouting, describe_routing};
+use crate::secrets::{SecretPatternConfig, merge_secret_patterns};
+use crate::summary::DEFAULT_SUMMARY_MAX_CHARS;
+use crate::templateregistry::{TEMPLATE_ROUTE_TABLE, TemplateRoute, validate_template_routes};
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const DEFAULT_MODEL: &str = "gpt-5-mini";
+pub const DEFAULT_CONFIG_FILE: &str = "doxcer.toml";
+pub const DEFAULT_MAX_OUTPUT_BYTES: u64 = 1024 * 1024;
+pub const RUN_PROFILE_TABLE: &str = "profile";
+pub const INHERITS_KEY: &str = "inherits";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    Template,
+    ConfigFile,
+    Profile,
+    Env,
+    Directive,
+    Cli,
+}
+
+#[derive(Debug, Clone)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SettingsLayer {
+    pub provider: Option<String>,
+    pub provider_endpoint: Option<String>,
+    pub model: Option<String>,
+    pub fallback_models: Option<Vec<String>>,
+    pub max_output_tokens: Option<u32>,
+    pub temperature: Option<f64>,
+    pub top_p: Option<f64>,
+    pub reasoning_effort: Option<ReasoningEffort>,
+    pub max_output_bytes: Option<u64>,
+    pub required_sections: Option<Vec<String>>,
+    pub lang: Option<String>,
+    pub role_split_marker: Option<String>,
+    pub single_input: Option<bool>,
+    pub profiles: Option<BTreeMap<String, ProfileConfig>>,
+    pub secret_patterns: Option<BTreeMap<String, SecretPatternConfig>>,
+    pub summary_max_chars: Option<usize>,
+    pub link_policy: Option<LinkPolicy>,
+    pub link_allowlist: Option<Vec<String>>,
+    pub model_routing: Option<ModelRouting>,
+    pub pii: Option<PiiPolicy>,
+    pub fabric_workspace: Option<String>,
+    pub post_hooks: Option<Vec<String>>,
+    pub post_hook_timeout: Option<u64>,
+    pub context_files: Option<Vec<String>>,
+    pub max_context_tokens: Option<usize>,
+    pub max_total_tokens: Option<usize>,
+    pub oversize_policy: Option<OversizePolicy>,
+    pub chunk_strategy: Option<ChunkStrategy>,
+    pub granularity: Option<Granularity>,
+    pub retry_max_attempts: Option<u32>,
+    pub retry_base_delay_ms: Option<u64>,
+    pub file_timeout: Option<u64>,
+    pub prices: Option<BTreeMap<String, Price>>,
+    pub max_cost_eur: Option<f64>,
+    pub include_outputs: Option<bool>,
+    pub redact_literals: Option<bool>,
+    // `[vars]`: template variables, such as `author`.
+    pub vars: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub provider: Setting<String>,
+    pub provider_endpoint: Setting<Option<String>>,
+    pub model: Setting<String>,
+    pub fallback_models: Setting<Vec<String>>,
+    pub max_output_tokens: Setting<Option<u32>>,
+    pub temperature: Setting<Option<f64>>,
+    pub top_p: Setting<Option<f64>>,
+    pub reasoning_effort: Setting<Option<ReasoningEffort>>,
+    pub max_output_bytes: Setting<u64>,
+    pub required_sections: Setting<Vec<String>>,
+    pub lang: Setting<Option<String>>,
+    pub role_split_marker: Setting<Option<String>>,
+    pub single_input: Setting<bool>,
+    pub profiles: Setting<BTreeMap<String, ProfileConfig>>,
+    pub secret_patterns: Setting<BTreeMap<String, SecretPatternConfig>>,
+    pub summary_max_chars: Setting<usize>,
+    pub link_policy: Setting<LinkPolicy>,
+    pub link_allowlist: Setting<Vec<String>>,
+    pub model_routing: Setting<ModelRouting>,
+    pub pii: Setting<PiiPolicy>,
+    pub fabric_workspace: Setting<Option<String>>,
+    pub post_hooks: Setting<Vec<String>>,
+    pub post_hook_timeout: Setting<u64>,
+    pub context_files: Setting<Vec<String>>,
+    pub max_context_tokens: Setting<usize>,
+    pub max_total_tokens: Setting<Option<usize>>,
+    pub oversize_policy: Setting<OversizePolicy>,
+    pub chunk_strategy: Setting<ChunkStrategy>,
+    pub granularity: Setting<Granularity>,
+    pub retry_max_attempts: Setting<u32>,
+    pub retry_base_delay_ms: Setting<u64>,
+    pub file_timeout: Setting<Option<u64>>,
+    pub prices: Setting<BTreeMap<String, Price>>,
+    pub max_cost_eur: Setting<Option<f64>>,
+    pub include_outputs: Setting<bool>,
+    pub redact_literals: Setting<bool>,
+    pub vars: Setting<BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RunProfile {
+    pub inherits: Option<String>,
+    pub settings: SettingsLayer,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFile {
+    pub settings: SettingsLayer,
+    pub run_profiles: BTreeMap<String, RunProfile>,
+    // The `[[templates]]` entries, in file order; see `route_template`.
+    pub template_routes: Vec<TemplateRoute>,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::Template => write!(f, "template"),
+            Source::ConfigFile => write!(f, "config"),
+            Source::Profile => write!(f, "profile"),
+            Source::Env => write!(f, "env"),
+            Source::Directive => write!(f, "directive"),
+            Source::Cli => write!(f, "cli"),
+        }
+    }
+}
+
+
+// ====================================================
+//  Resolution
+// ====================================================
+impl Settings {
+
+    pub fn resolve(layers: &[(Source, &SettingsLayer)]) -> Settings {
+
+        // Resolves the effective settings from layers in increasing precedence.
+        //
+        // # Description
+        // Starts from the built-in defaults and lets every later layer override the
+        // values it sets. The usual order is template < config < env < cli, so a
+        // template's front-matter only fills in what nothing else specified.
+        //
+        // # Parameters
+        // * `layers` – `(source, layer)` pairs, lowest precedence first.
+        //
+        // # Returns
+        // * The resolved `Settings`, remembering which source set each value.
+
+        let mut settings = Settings {
+            provider: Setting { value: DEFAULT_PROVIDER.to_string(), source: Source::Default },
+            provider_endpoint: Setting { value: None, source: Source::Default },
+            model: Setting { value: DEFAULT_MODEL.to_string(), source: Source::Default },
+            fallback_models: Setting { value: Vec::new(), source: Source::Default },
+            max_output_tokens: Setting { value: None, source: Source::Default },
+            temperature: Setting { value: None, source: Source::Default },
+            top_p: Setting { value: None, source: Source::Default },
+            reasoning_effort: Setting { value: None, source: Source::Default },
+            max_output_bytes: Setting { value: DEFAULT_MAX_OUTPUT_BYTES, source: Source::Default },
+            required_sections: Setting { value: Vec::new(), source: Source::Default },
+            lang: Setting { value: None, source: Source::Default },
+            role_split_marker: Setting { value: None, source: Source::Default },
+            single_input: Setting { value: false, source: Source::Default },
+            profiles: Setting { value: BTreeMap::new(), source: Source::Default },
+            secret_patterns: Setting { value: BTreeMap::new(), source: Source::Default },
+            summary_max_chars: Setting { value: DEFAULT_SUMMARY_MAX_CHARS, source: Source::Default },
+            link_policy: Setting { value: LinkPolicy::default(), source: Source::Default },
+            link_allowlist: Setting { value: Vec::new(), source: Source::Default },
+            model_routing: Setting { value: ModelRouting::default(), source: Source::Default },
+            pii: Setting { value: PiiPolicy::default(), source: Source::Default },
+            fabric_workspace: Setting { value: None, source: Source::Default },
+            post_hooks: Setting { value: Vec::new(), source: Source::Default },
+            post_hook_timeout: Setting { value: DEFAULT_POST_HOOK_TIMEOUT_SECS, source: Source::Default },
+            context_files: Setting { value: Vec::new(), source: Source::Default },
+            max_context_tokens: Setting { value: DEFAULT_MAX_CONTEXT_TOKENS, source: Source::Default },
+            max_total_tokens: Setting { value: None, source: Source::Default },
+            oversize_policy: Setting { value: OversizePolicy::default(), source: Source::Default },
+            chunk_strategy: Setting { value: ChunkStrategy::default(), source: Source::Default },
+            granularity: Setting { value: Granularity::default(), source: Source::Default },
+            retry_max_attempts: Setting { value: DEFAULT_RETRY_MAX_ATTEMPTS, source: Source::Default },
+            retry_base_delay_ms: Setting { value: DEFAULT_RETRY_BASE_DELAY_MS, source: Source::Default },
+            file_timeout: Setting { value: None, source: Source::Default },
+            prices: Setting { value: BTreeMap::new(), source: Source::Default },
+            max_cost_eur: Setting { value: None, source: Source::Default },
+            include_outputs: Setting { value: false, source: Source::Default },
+            redact_literals: Setting { value: false, source: Source::Default },
+            vars: Setting { value: BTreeMap::new(), source: Source::Default },
+        };
+
+        for (source, layer) in layers {
+            let source = *source;
+            if let Some(v) = &layer.provider {
+                settings.provider = Setting { value: v.clone(), source };
+            }
+            if let Some(v) = &layer.provider_endpoint {
+                settings.provider_endpoint = Setting { value: Some(v.clone()), source };
+            }
+            if let Some(v) = &layer.model {
+                settings.model = Setting { value: v.clone(), source };
+            }
+            if let Some(v) = &layer.fallback_models {
+                settings.fallback_models = Setting { value: v.clone(), source };
+            }
+            if let Some(v) = layer.max_output_tokens {
+                settings.max_output_tokens = Setting { value: Some(v), source };
+            }
+            if let Some(v) = layer.temperature {
+                settings.temperature = Setting { value: Some(v), source };
+            }
+            if let Some(v) = layer.top_p {
+                settings.top_p = Setting { value: Some(v), source };
+            }
+            if let Some(v) = layer.reasoning_effort {
+                settings.reasoning_effort = Setting { value: Some(v), source };
+            }
+            if let Some(v) = layer.max_output_bytes {
+                settings.max_output_bytes = Setting { value: v, source };
+            }
+            if let Some(v) = &layer.required_sections {
+                settings.required_sections = Setting { value: v.clone(), source };
+            }
+            if let Some(v) = &layer.lang {
+                settings.lang = Setting { value: Some(v.clone()), source };
+            }
+            if let Some(v) = &layer.role_split_marker {
+                settings.role_split_marker = Setting { value: Some(v.clone()), source };
+            }
+            if let Some(v) = layer.single_input {
+                settings.single_input = Setting { value: v, source };
+            }
+            if let Some(v) = &layer.profiles {
+                settings.profiles = Setting { value: v.clone(), source };
+            }
+            if let Some(v) = &layer.secret_patterns {
+                merge_secret_patterns(&mut settings.secret_patterns.value, v);
+                settings.secret_patterns.source = source;
+            }
+            if let Some(v) = layer.summary_max_chars {
+                settings.summary_max_chars = Setting { value: v, source };
+            }
+            if let Some(v) = layer.link_policy {
+                settings.link_policy = Setting { value: v, source };
+            }
+            if let Some(v) = &layer.link_allowlist {
+                settings.link_allowlist = Setting { value: v.clone(), source };
+            }
+            if let Some(v) = &layer.model_routing {
+                settings.model_routing = Setting { value: v.clone(), source };
+            }
+            if let Some(v) = layer.pii {
+                settings.pii = Setting { value: v, source };
+            }
+            if let Some(v) = &layer.fabric_workspace {
+                settings.fabric_workspace = Setting { value: Some(v.clone()), source };
+            }
+            if let Some(v) = &layer.post_hooks {
+                settings.post_hooks = Setting { value: v.clone(), source };
+            }
+            if let Some(v) = layer.post_hook_timeout {
+                settings.post_hook_timeout = Setting { value: v, source };
+            }
+            if let Some(v) = &layer.context_files {
+                settings.context_files = Setting { value: v.clone(), source };
+            }
+            if let Some(v) = layer.max_context_tokens {
+                settings.max_context_tokens = Setting { value: v, source };
+            }
+            if let Some(v) = layer.max_total_tokens {
+                settings.max_total_tokens = Setting { value: Some(v), source };
+            }
+            if let Some(v) = layer.oversize_policy {
+                settings.oversize_policy = Setting { value: v, source };
+            }
+            if let Some(v) = layer.chunk_strategy {
+                settings.chunk_strategy = Setting { value: v, source };
+            }
+            if let Some(v) = layer.granularity {
+                settings.granularity = Setting { value: v, source };
+            }
+            if let Some(v) = layer.retry_max_attempts {
+                settings.retry_max_attempts = Setting { value: v, source };
+            }
+            if let Some(v) = layer.retry_base_delay_ms {
+                settings.retry_base_delay_ms = Setting { value: v, source };
+            }
+            if let Some(v) = layer.file_timeout {
+                settings.file_timeout = Setting { value: Some(v), source };
+            }
+            if let Some(v) = &layer.prices {
+                settings.prices.value.extend(v.iter().map(|(model, price)| (model.clone(), *price)));
+                settings.prices.source = source;
+            }
+            if let Some(v) = layer.max_cost_eur {
+                settings.max_cost_eur = Setting { value: Some(v), source };
+            }
+            if let Some(v) = layer.include_outputs {
+                settings.include_outputs = Setting { value: v, source };
+            }
+            if let Some(v) = layer.redact_literals {
+                settings.redact_literals = Setting { value: v, source };
+            }
+            if let Some(v) = &layer.vars {
+                settings.vars.value.extend(v.iter().map(|(name, value)| (name.clone(), value.clone())));
+                settings.vars.source = source;
+            }
+        }
+        settings
+    }
+
+
+    pub fn describe(&self) -> Vec<(&'static str, String, Source)> {
+
+        // Lists every setting as `(name, rendered value, source)` for display.
+
+        let opt = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".to_string());
+        vec![
+            ("provider", self.provider.value.clone(), self.provider.source),
+            ("provider_endpoint", opt(&self.provider_endpoint.value), self.provider_endpoint.source),
+            ("model", self.model.value.clone(), self.model.source),
+            (
+                "fallback_models",
+                format!("[{}]", self.fallback_models.value.join(", ")),
+                self.fallback_models.source,
+            ),
+            (
+                "max_output_tokens",
+                self.max_output_tokens.value.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string()),
+                self.max_output_tokens.source,
+            ),
+            ("temperature", self.temperature.value.map_or_else(|| "-".to_string(), |v| v.to_string()), self.temperature.source),
+            ("top_p", self.top_p.value.map_or_else(|| "-".to_string(), |v| v.to_string()), self.top_p.source),
+            (
+                "reasoning_effort",
+                self.reasoning_effort.value.map_or_else(|| "-".to_string(), |v| v.to_string()),
+                self.reasoning_effort.source,
+            ),
+            ("max_output_bytes", self.max_output_bytes.value.to_string(), self.max_output_bytes.source),
+            (
+                "required_sections",
+                format!("[{}]", self.required_sections.value.join(", ")),
+                self.required_sections.source,
+            ),
+            ("lang", opt(&self.lang.value), self.lang.source),
+            ("role_split_marker", opt(&self.role_split_marker.value), self.role_split_marker.source),
+            ("single_input", self.single_input.value.to_string(), self.single_input.source),
+            (
+                "profiles",
+                format!("[{}]", self.profiles.value.keys().cloned().collect::<Vec<_>>().join(", ")),
+                self.profiles.source,
+            ),
+            (
+                "secret_patterns",
+                format!("[{}]", self.secret_patterns.value.keys().cloned().collect::<Vec<_>>().join(", ")),
+                self.secret_patterns.source,
+            ),
+            ("summary_max_chars", self.summary_max_chars.value.to_string(), self.summary_max_chars.source),
+            ("link_policy", self.link_policy.value.to_string(), self.link_policy.source),
+            (
+                "link_allowlist",
+                format!("[{}]", self.link_allowlist.value.join(", ")),
+                self.link_allowlist.source,
+            ),
+            ("model_routing", describe_routing(&self.model_routing.value), self.model_routing.source),
+            ("pii", self.pii.value.to_string(), self.pii.source),
+            ("fabric_workspace", opt(&self.fabric_workspace.value), self.fabric_workspace.source),
+            ("post_hooks", format!("[{}]", self.post_hooks.value.join(", ")), self.post_hooks.source),
+            ("post_hook_timeout", format!("{}s", self.post_hook_timeout.value), self.post_hook_timeout.source),
+            ("context_files", format!("[{}]", self.context_files.value.join(", ")), self.context_files.source),
+            ("max_context_tokens", self.max_context_tokens.value.to_string(), self.max_context_tokens.source),
+            (
+                "max_total_tokens",
+                self.max_total_tokens.value.map_or_else(|| "-".to_string(), |v| v.to_string()),
+                self.max_total_tokens.source,
+            ),
+            ("oversize_policy", self.oversize_policy.value.to_string(), self.oversize_policy.source),
+            ("chunk_strategy", self.chunk_strategy.value.to_string(), self.chunk_strategy.source),
+            ("granularity", self.granularity.value.to_string(), self.granularity.source),
+            ("retry_max_attempts", self.retry_max_attempts.value.to_string(), self.retry_max_attempts.source),
+            ("retry_base_delay_ms", format!("{}ms", self.retry_base_delay_ms.value), self.retry_base_delay_ms.source),
+            (
+                "file_timeout",
+                self.file_timeout.value.map_or_else(|| "-".to_string(), |v| format!("{v}s")),
+                self.file_timeout.source,
+            ),
+            (
+                "prices",
+                format!("[{}]", self.prices.value.keys().cloned().collect::<Vec<_>>().join(", ")),
+                self.prices.source,
+            ),
+            (
+                "max_cost_eur",
+                self.max_cost_eur.value.map_or_else(|| "-".to_string(), |v| format!("{v:.2}")),
+                self.max_cost_eur.source,
+            ),
+            ("include_outputs", self.include_outputs.value.to_string(), self.include_outputs.source),
+            ("redact_literals", self.redact_literals.value.to_string(), self.redact_literals.source),
+            (
+                "vars",
+                format!("[{}]", self.vars.value.keys().cloned().collect::<Vec<_>>().join(", ")),
+                self.vars.source,
+            ),
+        ]
+    }
+}
+
+
+// ====================================================
+//  Layer Loading
+// ====================================================
+pub fn env_layer() -> Result<SettingsLayer, DoxcerError> {
+
+    // Builds a settings layer from `DOXCER_*` environment variables.
+    //
+    // # Description
+    // Reads `DOXCER_PROVIDER`, `DOXCER_PROVIDER_ENDPOINT`, `DOXCER_MODEL`,
+    // `DOXCER_FALLBACK_MODELS` (comma-separated),
+    // `DOXCER_MAX_OUTPUT_TOKENS`, `DOXCER_TEMPERATURE`, `DOXCER_TOP_P`,
+    // `DOXCER_REASONING_EFFORT`, `DOXCER_MAX_OUTPUT_BYTES`,
+    // `DOXCER_REQUIRED_SECTIONS` (comma-separated), `DOXCER_LANG`,
+    // `DOXCER_ROLE_SPLIT_MARKER`, `DOXCER_SINGLE_INPUT` (`true`/`false`),
+    // `DOXCER_SUMMARY_MAX_CHARS`,
+    // `DOXCER_LINK_POLICY`, `DOXCER_LINK_ALLOWLIST` (comma-separated),
+    // `DOXCER_PII`, `DOXCER_FABRIC_WORKSPACE`, `DOXCER_POST_HOOK_TIMEOUT`
+    // (seconds), `DOXCER_MAX_TOTAL_TOKENS`, `DOXCER_OVERSIZE_POLICY`,
+    // `DOXCER_CHUNK_STRATEGY`, `DOXCER_GRANULARITY`, `DOXCER_RETRY_MAX_ATTEMPTS`,
+    // `DOXCER_RETRY_BASE_DELAY_MS`, `DOXCER_FILE_TIMEOUT` (seconds), `DOXCER_MAX_COST_EUR`,
+    // `DOXCER_INCLUDE_OUTPUTS` and `DOXCER_REDACT_LITERALS` (`true`/`false`).
+    //
+    // # Returns
+    // * `Ok(SettingsLayer)` with the variables that are set.
+    // * `Err(DoxcerError)` if a numeric or policy variable cannot be parsed.
+
+    let var = |name: &str| env::var(name).ok().filter(|v| !v.trim().is_empty());
+
+    let max_output_tokens = match var("DOXCER_MAX_OUTPUT_TOKENS") {
+        Some(v) => Some(v.trim().parse::<u32>()
+            .map_err(|_| DoxcerError::Config(format!("DOXCER_MAX_OUTPUT_TOKENS must be a positive integer, got {v:?}")))?),
+        None => None,
+    };
+    let temperature = match var("DOXCER_TEMPERATURE") {
+        Some(v) => Some(v.trim().parse::<f64>()
+            .map_err(|_| DoxcerError::Config(format!("DOXCER_TEMPERATURE must be a number, got {v:?}")))?),
+        None => None,
+    };
+    let top_p = match var("DOXCER_TOP_P") {
+        Some(v) => Some(v.trim().parse::<f64>()
+            .map_err(|_| DoxcerError::Config(format!("DOXCER_TOP_P must be a number, got {v:?}")))?),
+        None => None,
+    };
+    let reasoning_effort = match var("DOXCER_REASONING_EFFORT") {
+        Some(v) => Some(v.trim().parse::<ReasoningEffort>().map_err(|e| DoxcerError::Config(format!("DOXCER_REASONING_EFFORT: {e}")))?),
+        None => None,
+    };
+    let max_output_bytes = match var("DOXCER_MAX_OUTPUT_BYTES") {
+        Some(v) => Some(v.trim().parse::<u64>()
+            .map_err(|_| DoxcerError::Config(format!("DOXCER_MAX_OUTPUT_BYTES must be a positive integer, got {v:?}")))?),
+        None => None,
+    };
+    let single_input = match var("DOXCER_SINGLE_INPUT") {
+        Some(v) => Some(v.trim().parse::<bool>()
+            .map_err(|_| DoxcerError::Config(format!("DOXCER_SINGLE_INPUT must be true or false, got {v:?}")))?),
+        None => None,
+    };
+    let summary_max_chars = match var("DOXCER_SUMMARY_MAX_CHARS") {
+        Some(v) => Some(v.trim().parse::<usize>()
+            .map_err(|_| DoxcerError::Config(format!("DOXCER_SUMMARY_MAX_CHARS must be a positive integer, got {v:?}")))?),
+        None => None,
+    };
+    let post_hook_timeout = match var("DOXCER_POST_HOOK_TIMEOUT") {
+        Some(v) => Some(v.trim().parse::<u64>()
+            .map_err(|_| DoxcerError::Config(format!("DOXCER_POST_HOOK_TIMEOUT must be a positive integer, got {v:?}")))?),
+        None => None,
+    };
+    let max_total_tokens = match var("DOXCER_MAX_TOTAL_TOKENS") {
+        Some(v) => Some(v.trim().parse::<usize>().ok().filter(|n| *n > 0)
+            .ok_or_else(|| DoxcerError::Config(format!("DOXCER_MAX_TOTAL_TOKENS must be a positive integer, got {v:?}")))?),
+        None => None,
+    };
+    let oversize_policy = match var("DOXCER_OVERSIZE_POLICY") {
+        Some(v) => Some(v.trim().parse::<OversizePolicy>().map_err(|e| DoxcerError::Config(format!("DOXCER_OVERSIZE_POLICY: {e}")))?),
+        None => None,
+    };
+    let granularity = match var("DOXCER_GRANULARITY") {
+        Some(v) => Some(v.trim().parse::<Granularity>().map_err(|e| DoxcerError::Config(format!("DOXCER_GRANULARITY: {e}")))?),
+        None => None,
+    };
+    let chunk_strategy = match var("DOXCER_CHUNK_STRATEGY") {
+        Some(v) => Some(v.trim().parse::<ChunkStrategy>().map_err(|e| DoxcerError::Config(format!("DOXCER_CHUNK_STRATEGY: {e}")))?),
+        None => None,
+    };
+    let retry_max_attempts = match var("DOXCER_RETRY_MAX_ATTEMPTS") {
+        Some(v) => Some(v.trim().parse::<u32>().ok().filter(|n| *n > 0)
+            .ok_or_else(|| DoxcerError::Config(format!("DOXCER_RETRY_MAX_ATTEMPTS must be a positive integer, got {v:?}")))?),
+        None => None,
+    };
+    let retry_base_delay_ms = match var("DOXCER_RETRY_BASE_DELAY_MS") {
+        Some(v) => Some(v.trim().parse::<u64>()
+            .map_err(|_| DoxcerError::Config(format!("DOXCER_RETRY_BASE_DELAY_MS must be a non-negative integer, got {v:?}")))?),
+        None => None,
+    };
+    let file_timeout = match var("DOXCER_FILE_TIMEOUT") {
+        Some(v) => Some(v.trim().parse::<u64>().ok().filter(|n| *n > 0)
+            .ok_or_else(|| DoxcerError::Config(format!("DOXCER_FILE_TIMEOUT must be a positive integer, got {v:?}")))?),
+        None => None,
+    };
+    let max_cost_eur = match var("DOXCER_MAX_COST_EUR") {
+        Some(v) => Some(v.trim().parse::<f64>().ok().filter(|n| *n > 0.0)
+            .ok_or_else(|| DoxcerError::Config(format!("DOXCER_MAX_COST_EUR must be a positive number, got {v:?}")))?),
+        None => None,
+    };
+    let include_outputs = match var("DOXCER_INCLUDE_OUTPUTS") {
+        Some(v) => Some(v.trim().parse::<bool>()
+            .map_err(|_| DoxcerError::Config(format!("DOXCER_INCLUDE_OUTPUTS must be true or false, got {v:?}")))?),
+        None => None,
+    };
+    let redact_literals = match var("DOXCER_REDACT_LITERALS") {
+        Some(v) => Some(v.trim().parse::<bool>()
+            .map_err(|_| DoxcerError::Config(format!("DOXCER_REDACT_LITERALS must be true or false, got {v:?}")))?),
+        None => None,
+    };
+    let link_policy = match var("DOXCER_LINK_POLICY") {
+        Some(v) => Some(v.trim().parse::<LinkPolicy>().map_err(|e| DoxcerError::Config(format!("DOXCER_LINK_POLICY: {e}")))?),
+        None => None,
+    };
+    let pii = match var("DOXCER_PII") {
+        Some(v) => Some(v.trim().parse::<PiiPolicy>().map_err(|e| DoxcerError::Config(format!("DOXCER_PII: {e}")))?),
+        None => None,
+    };
+
+    Ok(SettingsLayer {
+        provider: var("DOXCER_PROVIDER"),
+        provider_endpoint: var("DOXCER_PROVIDER_ENDPOINT"),
+        model: var("DOXCER_MODEL"),
+        fallback_models: var("DOXCER_FALLBACK_MODELS").map(|v| split_list(&v)),
+        max_output_tokens,
+        temperature,
+        top_p,
+        reasoning_effort,
+        max_output_bytes,
+        required_sections: var("DOXCER_REQUIRED_SECTIONS").map(|v| split_list(&v)),
+        lang: var("DOXCER_LANG"),
+        role_split_marker: var("DOXCER_ROLE_SPLIT_MARKER"),
+        single_input,
+        profiles: None,
+        secret_patterns: None,
+        summary_max_chars,
+        link_policy,
+        link_allowlist: var("DOXCER_LINK_ALLOWLIST").map(|v| split_list(&v)),
+        model_routing: None,
+        pii,
+        fabric_workspace: var("DOXCER_FABRIC_WORKSPACE"),
+        post_hooks: None,
+        post_hook_timeout,
+        context_files: None,
+        max_context_tokens: None,
+        max_total_tokens,
+        oversize_policy,
+        chunk_strategy,
+        granularity,
+        retry_max_attempts,
+        retry_base_delay_ms,
+        file_timeout,
+        prices: None,
+        max_cost_eur,
+        include_outputs,
+        redact_literals,
+        vars: None,
+    })
+}
+
+
+pub fn split_list(value: &str) -> Vec<String> {
+
+    // Splits a comma-separated list value, dropping empty items.
+
+    value.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+}
+
+
+pub fn resolve_config_path(explicit: Option<&Path>) -> Option<PathBuf> {
+
+    // Determines which config file to load.
+    //
+    // # Description
+    // Uses the explicit `--config` path, then `DOXCER_CONFIG`, then `./doxcer.toml`
+    // if it exists. Explicit paths are returned even when missing, so loading can
+    // report the error.
+
+    if let Some(p) = explicit {
+        return Some(p.to_path_buf());
+    }
+    if let Ok(p) = env::var("DOXCER_CONFIG") {
+        return Some(PathBuf::from(p));
+    }
+    let default = PathBuf::from(DEFAULT_CONFIG_FILE);
+    default.exists().then_some(default)
+}
+
+
+pub fn load_config_file(path: &Path) -> Result<ConfigFile, DoxcerError> {
+
+    // Loads a `doxcer.toml` config file into a settings layer and its run profiles.
+    //
+    // # Description
+    // Every `[profile.<name>]` table is a run profile: any subset of the normal
+    // keys, plus `inherits = "<other profile>"`.
+    // Every `[[templates]]` table maps a path pattern to a named template.
+    //
+    // # Returns
+    // * `Ok(ConfigFile)` with the keys set in the file.
+    // * `Err(DoxcerError)` if the file is missing, unreadable or contains unknown keys.
+
+    let raw = fs::read_to_string(path)
+        .map_err(|e| DoxcerError::Config(format!("Failed to read config {}: {e}", path.display())))?;
+    let invalid = |e: &dyn fmt::Display| DoxcerError::Config(format!("Invalid config {}: {e}", path.display()));
+    let mut table: toml::Table = toml::from_str(&raw).map_err(|e| invalid(&e))?;
+
+    let mut run_profiles = BTreeMap::new();
+    if let Some(profiles) = table.remove(RUN_PROFILE_TABLE) {
+        let toml::Value::Table(profiles) = profiles else {
+            return Err(invalid(&format!("`{RUN_PROFILE_TABLE}` must be a table of [{RUN_PROFILE_TABLE}.<name>] sections")));
+        };
+        for (name, profile) in profiles {
+            let toml::Value::Table(mut profile) = profile else {
+                return Err(invalid(&format!("[{RUN_PROFILE_TABLE}.{name}] must be a table")));
+            };
+            let inherits = match profile.remove(INHERITS_KEY) {
+                Some(toml::Value::String(parent)) => Some(parent),
+                Some(_) => return Err(invalid(&format!("[{RUN_PROFILE_TABLE}.{name}] `{INHERITS_KEY}` must be a profile name"))),
+                None => None,
+            };
+            let settings = toml::Value::Table(profile)
+                .try_into()
+                .map_err(|e| invalid(&format!("[{RUN_PROFILE_TABLE}.{name}]: {e}")))?;
+            run_profiles.insert(name, RunProfile { inherits, settings });
+        }
+    }
+    let template_routes = match table.remove(TEMPLATE_ROUTE_TABLE) {
+        Some(routes) => routes.try_into().map_err(|e| invalid(&format!("[[{TEMPLATE_ROUTE_TABLE}]]: {e}")))?,
+        None => Vec::new(),
+    };
+    validate_template_routes(&template_routes).map_err(|e| invalid(&e))?;
+    let settings = toml::Value::Table(table).try_into().map_err(|e| invalid(&e))?;
+    Ok(ConfigFile { settings, run_profiles, template_routes })
+}
+
+
+pub fn selected_run_profile(cli: Option<&str>) -> Option<String> {
+
+    // Returns the run profile to use: `--run-profile`, else `DOXCER_RUN_PROFILE`.
+
+    cli.map(str::to_string)
+        .or_else(|| env::var("DOXCER_RUN_PROFILE").ok())
+        .filter(|name| !name.trim().is_empty())
+}
+
+
+impl ConfigFile {
+
+    pub fn profile_chain(&self, name: &str) -> Result<Vec<(&str, &SettingsLayer)>, DoxcerError> {
+
+        // Resolves a run profile and the profiles it inherits from.
+        //
+        // # Returns
+        // * `Ok(Vec)` – `(name, layer)` pairs, the root of the chain first, so
+        //   the selected profile overrides what it inherits.
+        // * `Err(DoxcerError)` – an unknown profile (listing the available ones) or an
+        //   inheritance cycle.
+
+        let mut chain: Vec<(&str, &SettingsLayer)> = Vec::new();
+        let mut next = Some(name);
+        while let Some(current) = next {
+            let Some((key, profile)) = self.run_profiles.get_key_value(current) else {
+                let available: Vec<&str> = self.run_profiles.keys().map(String::as_str).collect();
+                let available = if available.is_empty() { "no [profile.<name>] sections are defined".to_string() } else { format!("available: {}", available.join(", ")) };
+                return Err(DoxcerError::Config(match chain.last() {
+                    Some((child, _)) => format!("run profile `{child}` inherits unknown profile `{current}`; {available}"),
+                    None => format!("unknown run profile `{current}`; {available}"),
+                }));
+            };
+            if chain.iter().any(|(seen, _)| *seen == current) {
+                let cycle: Vec<&str> = chain.iter().map(|(n, _)| *n).chain([current]).collect();
+                return Err(DoxcerError::Config(format!("run profiles inherit in a cycle: {}", cycle.join(" -> "))));
+            }
+            chain.push((key.as_str(), &profile.settings));
+            next = profile.inherits.as_deref();
+        }
+        chain.reverse();
+        Ok(chain)
+    }
+}
@@ -0,0 +1,100 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fs;
+use std::path::Path;
+
+// Internal Modules
+use crate::config::Source;
+use crate::error::DoxcerError;
+use crate::oversize::count_tokens;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const DEFAULT_MAX_CONTEXT_TOKENS: usize = 8_000;
+pub const MAX_CONTEXT_FILE_BYTES: u64 = 256 * 1024;
+// Only this much of a file is inspected for NUL bytes.
+pub const BINARY_SNIFF_BYTES: usize = 8_000;
+
+
+// ====================================================
+//  Detection
+// ====================================================
+pub fn is_binary(bytes: &[u8]) -> bool {
+
+    // Treats a file as binary when its head contains a NUL byte or the whole
+    // file is not valid UTF-8.
+
+    bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0) || std::str::from_utf8(bytes).is_err()
+}
+
+
+fn origin(source: Source) -> &'static str {
+
+    // Names where a list of context files was set, for error messages.
+
+    match source {
+        Source::Cli => "--context",
+        Source::ConfigFile => "context_files in doxcer.toml",
+        Source::Profile => "context_files in the run profile",
+        Source::Template => "context_files in the template front-matter",
+        Source::Env | Source::Directive | Source::Default => "context_files",
+    }
+}
+
+
+// ====================================================
+//  Loading
+// ====================================================
+pub fn load_context(files: &[String], source: Source, max_tokens: usize, model: &str) -> Result<String, DoxcerError> {
+
+    // Reads the context files and renders `{{context}}`.
+    //
+    // # Description
+    // Each file becomes a `### <path>` section with its text, in the order
+    // given. A file larger than `MAX_CONTEXT_FILE_BYTES` or binary is refused,
+    // as is a context over `max_tokens` in total, counted for `model` (see
+    // `oversize::count_tokens`). Paths are relative to the working directory.
+    //
+    // # Parameters
+    // * `source` – Where the list was set; named in the errors.
+    //
+    // # Returns
+    // * `Ok(String)` with the rendered context, empty without files.
+    // * `Err(DoxcerError)` naming the file and `source` when a file is missing,
+    //   too large or binary, or when the total is over `max_tokens`.
+
+    let from = origin(source);
+    let mut sections = Vec::new();
+    for file in files {
+        let path = Path::new(file);
+        let size = fs::metadata(path)
+            .map_err(|e| DoxcerError::Config(format!("context file {file} (from {from}) cannot be read: {e}")))?
+            .len();
+        if size > MAX_CONTEXT_FILE_BYTES {
+            return Err(DoxcerError::Config(format!("context file {file} (from {from}) is {size} bytes, over the limit of {MAX_CONTEXT_FILE_BYTES}")));
+        }
+        let bytes = fs::read(path).map_err(|e| DoxcerError::Config(format!("context file {file} (from {from}) cannot be read: {e}")))?;
+        if is_binary(&bytes) {
+            return Err(DoxcerError::Config(format!("context file {file} (from {from}) is binary; only text files can be used as context")));
+        }
+        let text = String::from_utf8(bytes).unwrap_or_default();
+        sections.push(format!("### {file}\n\n{}", text.trim_end()));
+    }
+
+    let context = sections.join("\n\n");
+    let tokens = count_tokens(&context, model);
+    if tokens > max_tokens {
+        return Err(DoxcerError::Config(format!(
+            "the context files (from {from}) are about {tokens} tokens, over --max-context-tokens {max_tokens}"
+        )));
+    }
+    Ok(context)
+}
@@ -0,0 +1,316 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+// External Libraries
+use serde::{Deserialize, Serialize};
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::locale::{Currency, DEFAULT_CURRENCY, Locale, format_decimal, format_money};
+use crate::oversize::DEFAULT_OUTPUT_RESERVE_TOKENS;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+// List prices in USD per million input and output tokens, matched on the
+// longest prefix of the model name. `[prices]` in `doxcer.toml` overrides them,
+// and they only apply while costs are shown in USD.
+pub const BUILTIN_PRICES: [(&str, f64, f64); 12] = [
+    ("gpt-5", 1.25, 10.00),
+    ("gpt-5-mini", 0.25, 2.00),
+    ("gpt-5-nano", 0.05, 0.40),
+    ("gpt-4.1", 2.00, 8.00),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1-nano", 0.10, 0.40),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("o3", 2.00, 8.00),
+    ("o4-mini", 1.10, 4.40),
+    ("claude-sonnet-4", 3.00, 15.00),
+    ("claude-haiku-4-5", 1.00, 5.00),
+];
+pub const TOKENS_PER_PRICE_UNIT: f64 = 1_000_000.0;
+pub const DEFAULT_COST_REPORT: &str = "doxcer-cost.json";
+// Status of a notebook that was not sent because `--max-cost` was reached.
+pub const OVER_BUDGET_STATUS: &str = "over-budget";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Price {
+    // Price per million input tokens.
+    pub input: f64,
+    // Price per million output tokens.
+    pub output: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostLine {
+    pub notebook: String,
+    pub model: String,
+    pub cached: bool,
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+    // `None` when the model has no price, or the API reported no usage.
+    pub cost: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CostTotals {
+    pub files: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost: f64,
+    // Files that were sent but could not be priced; their cost is missing from `cost`.
+    pub unpriced: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostReport {
+    pub currency: String,
+    pub files: Vec<CostLine>,
+    pub total: CostTotals,
+}
+
+// The spending cap of a run (`--max-cost`), shared by the workers of a batch.
+#[derive(Debug, Clone)]
+pub struct Budget {
+    // The cap in EUR.
+    pub cap: f64,
+    // EUR per unit of the currency the prices are in.
+    rate: f64,
+    // Whether the built-in USD prices apply, see `cost_line`.
+    builtin: bool,
+    state: Arc<Mutex<BudgetState>>,
+}
+
+#[derive(Debug, Default)]
+struct BudgetState {
+    spent: f64,
+    // Estimates of the requests in flight.
+    reserved: f64,
+    answers: u64,
+    output_tokens: u64,
+    // Set by the first request that did not fit; later requests are refused too.
+    exhausted: bool,
+}
+
+
+// ====================================================
+//  Pricing
+// ====================================================
+pub fn price_for(model: &str, prices: &BTreeMap<String, Price>, builtin: bool) -> Option<Price> {
+
+    // Returns the price of a model: the longest matching prefix in the
+    // configured table, else (with `builtin`) in `BUILTIN_PRICES`.
+
+    let model = model.rsplit('/').next().unwrap_or(model);
+    let configured = prices
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, price)| *price);
+    configured.or_else(|| {
+        builtin.then_some(())?;
+        BUILTIN_PRICES
+            .iter()
+            .filter(|(prefix, _, _)| model.starts_with(prefix))
+            .max_by_key(|(prefix, _, _)| prefix.len())
+            .map(|(_, input, output)| Price { input: *input, output: *output })
+    })
+}
+
+
+pub fn cost_line(notebook: &str, model: &str, cached: bool, tokens: (Option<u64>, Option<u64>), prices: &BTreeMap<String, Price>, currency: &Currency) -> CostLine {
+
+    // Prices one generated document.
+    //
+    // # Description
+    // An answer from the response cache sent no request, so it costs nothing
+    // in this run; its tokens are still shown. The built-in prices are in USD,
+    // so with another `DOXCER_CURRENCY` only the configured ones are used.
+
+    let (input_tokens, output_tokens) = tokens;
+    let cost = match cached {
+        true => Some(0.0),
+        false if input_tokens.is_none() && output_tokens.is_none() => None,
+        false => price_for(model, prices, currency.code == DEFAULT_CURRENCY).map(|price| {
+            (input_tokens.unwrap_or(0) as f64 * price.input + output_tokens.unwrap_or(0) as f64 * price.output) / TOKENS_PER_PRICE_UNIT
+        }),
+    };
+    CostLine { notebook: notebook.to_string(), model: model.to_string(), cached, input_tokens, output_tokens, cost }
+}
+
+
+pub fn cost_report(files: Vec<CostLine>, currency: &Currency) -> CostReport {
+
+    // Adds up the documents of a run.
+
+    let mut total = CostTotals { files: files.len(), ..CostTotals::default() };
+    for line in &files {
+        total.input_tokens += line.input_tokens.unwrap_or(0);
+        total.output_tokens += line.output_tokens.unwrap_or(0);
+        match line.cost {
+            Some(cost) => total.cost += cost,
+            None => total.unpriced += 1,
+        }
+    }
+    CostReport { currency: currency.code.clone(), files, total }
+}
+
+
+// ====================================================
+//  Budget
+// ====================================================
+impl Budget {
+
+    pub fn new(cap: f64, currency: &Currency) -> Result<Budget, DoxcerError> {
+
+        // Creates the budget of a run. The cap is in EUR; prices in another
+        // currency are converted with `DOXCER_FX_RATE`.
+
+        if cap.is_nan() || cap <= 0.0 {
+            return Err(DoxcerError::Config(format!("--max-cost must be a positive amount in EUR, got {cap}")));
+        }
+        let rate = match (currency.code.as_str(), currency.fx_rate) {
+            ("EUR", _) => 1.0,
+            (_, Some(rate)) => rate,
+            (code, None) => {
+                return Err(DoxcerError::Config(format!("--max-cost is in EUR but prices are in {code}; set DOXCER_FX_RATE to convert them")));
+            }
+        };
+        Ok(Budget { cap, rate, builtin: currency.code == DEFAULT_CURRENCY, state: Arc::new(Mutex::new(BudgetState::default())) })
+    }
+
+
+    pub fn estimate(&self, model: &str, input_tokens: u64, max_output_tokens: Option<u32>, prices: &BTreeMap<String, Price>) -> Option<f64> {
+
+        // Estimates what a request will cost in EUR, before it is sent.
+        //
+        // # Description
+        // The output is expected to be as long as the average answer of this
+        // run so far; before the first answer, `max_output_tokens` (or the
+        // default output reserve) is assumed. `None` when the model has no price.
+
+        let output_tokens = {
+            let state = self.state();
+            match state.answers {
+                0 => max_output_tokens.map_or(DEFAULT_OUTPUT_RESERVE_TOKENS as u64, u64::from),
+                answers => state.output_tokens.div_ceil(answers),
+            }
+        };
+        self.cost(model, input_tokens, output_tokens, prices)
+    }
+
+
+    pub fn cost(&self, model: &str, input_tokens: u64, output_tokens: u64, prices: &BTreeMap<String, Price>) -> Option<f64> {
+
+        // Returns the cost of the given tokens in EUR, or `None` when the model
+        // has no price.
+
+        let price = price_for(model, prices, self.builtin)?;
+        Some((input_tokens as f64 * price.input + output_tokens as f64 * price.output) / TOKENS_PER_PRICE_UNIT * self.rate)
+    }
+
+
+    pub fn reserve(&self, estimate: f64) -> bool {
+
+        // Reserves the estimated cost of a request before it is sent. Returns
+        // false, and refuses every later request, when the spent and reserved
+        // amounts plus `estimate` would exceed the cap.
+
+        let mut state = self.state();
+        if state.exhausted || state.spent + state.reserved + estimate > self.cap {
+            state.exhausted = true;
+            return false;
+        }
+        state.reserved += estimate;
+        true
+    }
+
+
+    pub fn settle(&self, estimate: f64, cost: f64, output_tokens: Option<u64>) {
+
+        // Replaces the reservation of a finished request by what it cost; a
+        // failed request passes a cost of 0.
+
+        let mut state = self.state();
+        state.reserved = (state.reserved - estimate).max(0.0);
+        state.spent += cost;
+        if let Some(tokens) = output_tokens {
+            state.answers += 1;
+            state.output_tokens += tokens;
+        }
+    }
+
+
+    pub fn spent(&self) -> f64 {
+        self.state().spent
+    }
+
+
+    pub fn exhausted(&self) -> bool {
+        self.state().exhausted
+    }
+
+
+    fn state(&self) -> MutexGuard<'_, BudgetState> {
+
+        // Locks the state; a panic while it was held leaves the budget usable.
+
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+
+// ====================================================
+//  Rendering
+// ====================================================
+pub fn render_cost_summary(report: &CostReport, currency: &Currency, locale: Locale) -> String {
+
+    // Renders the cost summary printed at the end of a run: one line per
+    // document and the total.
+
+    let tokens = |value: Option<u64>| value.map_or_else(|| "-".to_string(), |v| format_decimal(v as f64, 0, locale));
+    let width = report.files.iter().map(|line| line.notebook.chars().count()).max().unwrap_or(0);
+    let mut out = String::from("Cost per file:\n");
+    for line in &report.files {
+        let cost = match (line.cost, line.cached) {
+            (Some(_), true) => "cached".to_string(),
+            (Some(cost), false) => format_money(cost, currency, locale),
+            (None, _) => "no price".to_string(),
+        };
+        out.push_str(&format!(
+            "  {:<width$}  {}  {} in / {} out  {cost}\n",
+            line.notebook,
+            line.model,
+            tokens(line.input_tokens),
+            tokens(line.output_tokens)
+        ));
+    }
+    let total = &report.total;
+    out.push_str(&format!(
+        "Total: {} file(s), {} in / {} out tokens, {}",
+        total.files,
+        format_decimal(total.input_tokens as f64, 0, locale),
+        format_decimal(total.output_tokens as f64, 0, locale),
+        format_money(total.cost, currency, locale)
+    ));
+    if total.unpriced > 0 {
+        out.push_str(&format!(" ({} file(s) not priced: no usage reported, or no model entry under [prices] in doxcer.toml)", total.unpriced));
+    }
+    out.push('\n');
+    out
+}
@@ -0,0 +1,163 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+// External Libraries
+use serde::Serialize;
+
+// Internal Modules
+use crate::manifest::ManifestEntry;
+use crate::provenance::read_provenance;
+use crate::timefmt::age_of;
+use crate::verify::{DocStatus, VerifyResult};
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const STALE_DAYS: u64 = 30;
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverageBucket {
+    UpToDate,
+    StaleRecent,
+    StaleOld,
+    Never,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CoverageCounts {
+    pub notebooks: usize,
+    pub documented: usize,
+    pub up_to_date: usize,
+    pub stale_under_30d: usize,
+    pub stale_over_30d: usize,
+    pub never: usize,
+    pub coverage: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CoverageReport {
+    pub total: CoverageCounts,
+    pub folders: BTreeMap<String, CoverageCounts>,
+}
+
+impl fmt::Display for CoverageBucket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoverageBucket::UpToDate => write!(f, "up-to-date"),
+            CoverageBucket::StaleRecent => write!(f, "stale <{STALE_DAYS}d"),
+            CoverageBucket::StaleOld => write!(f, "stale >{STALE_DAYS}d"),
+            CoverageBucket::Never => write!(f, "never"),
+        }
+    }
+}
+
+impl CoverageCounts {
+
+    pub fn add(&mut self, bucket: CoverageBucket) {
+
+        // Counts one notebook; `coverage` is the documented fraction (1.0 when
+        // there are no notebooks at all).
+
+        self.notebooks += 1;
+        match bucket {
+            CoverageBucket::UpToDate => self.up_to_date += 1,
+            CoverageBucket::StaleRecent => self.stale_under_30d += 1,
+            CoverageBucket::StaleOld => self.stale_over_30d += 1,
+            CoverageBucket::Never => self.never += 1,
+        }
+        self.documented = self.notebooks - self.never;
+        self.coverage = self.documented as f64 / self.notebooks as f64;
+    }
+}
+
+
+// ====================================================
+//  Classification
+// ====================================================
+pub fn doc_age(doc_path: &Path, entry: Option<&ManifestEntry>) -> Option<Duration> {
+
+    // Returns how old a document is: from its `generated_at` front-matter, else
+    // the manifest, else the file's modification time.
+
+    let doc = fs::read_to_string(doc_path).ok()?;
+    read_provenance(&doc)
+        .and_then(|p| p.generated_at)
+        .or_else(|| entry.and_then(|e| e.generated_at.clone()))
+        .and_then(|at| age_of(&at))
+        .or_else(|| fs::metadata(doc_path).and_then(|m| m.modified()).ok().and_then(|t| SystemTime::now().duration_since(t).ok()))
+}
+
+
+pub fn bucket(status: DocStatus, age: Option<Duration>) -> CoverageBucket {
+
+    // Puts a notebook in its staleness bucket. A stale document of unknown age
+    // counts as older than `STALE_DAYS`.
+
+    match status {
+        DocStatus::Ok => CoverageBucket::UpToDate,
+        DocStatus::Missing => CoverageBucket::Never,
+        DocStatus::Stale if age.is_some_and(|age| age < Duration::from_secs(STALE_DAYS * 24 * 3600)) => CoverageBucket::StaleRecent,
+        DocStatus::Stale => CoverageBucket::StaleOld,
+    }
+}
+
+
+pub fn coverage_report(results: &[(VerifyResult, CoverageBucket)]) -> CoverageReport {
+
+    // Totals the buckets overall and per folder of the notebooks.
+
+    let mut report = CoverageReport::default();
+    for (result, bucket) in results {
+        let folder = result
+            .notebook
+            .parent()
+            .map(|p| p.display().to_string().replace('\\', "/"))
+            .filter(|p| !p.is_empty())
+            .unwrap_or_else(|| ".".to_string());
+        report.total.add(*bucket);
+        report.folders.entry(folder).or_default().add(*bucket);
+    }
+    report
+}
+
+
+// ====================================================
+//  Reporting
+// ====================================================
+pub fn render_coverage(report: &CoverageReport) -> String {
+
+    // Renders the per-folder rollup and the total as an aligned table.
+
+    let width = report.folders.keys().map(String::len).chain([6]).max().unwrap_or(0);
+    let row = |name: &str, c: &CoverageCounts| {
+        format!(
+            "{name:<width$} {:>9} {:>10} {:>10} {:>9} {:>9} {:>6} {:>8.1}%\n",
+            c.notebooks, c.documented, c.up_to_date, c.stale_under_30d, c.stale_over_30d, c.never, c.coverage * 100.0
+        )
+    };
+    let mut out = format!(
+        "{:<width$} {:>9} {:>10} {:>10} {:>9} {:>9} {:>6} {:>9}\n",
+        "FOLDER", "NOTEBOOKS", "DOCUMENTED", "UP-TO-DATE", "STALE<30D", "STALE>30D", "NEVER", "COVERAGE"
+    );
+    for (folder, counts) in &report.folders {
+        out.push_str(&row(folder, counts));
+    }
+    out.push_str(&row("TOTAL", &report.total));
+    out
+}
@@ -0,0 +1,297 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::path::Path;
+
+// External Libraries
+use serde_json::{Map, Value};
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+// Fabric stores a pipeline in git as `<display name>.DataPipeline/pipeline-content.json`.
+pub const FABRIC_PIPELINE_SUFFIX: &str = ".DataPipeline";
+pub const PIPELINE_CONTENT_FILE: &str = "pipeline-content.json";
+// Properties that hold nested activities, rendered below their container.
+pub const NESTED_ACTIVITY_KEYS: [&str; 5] = ["activities", "ifTrueActivities", "ifFalseActivities", "defaultActivities", "cases"];
+// Properties that only identify items in a workspace; they say nothing about
+// what the pipeline does.
+pub const NOISE_KEYS: [&str; 5] = ["workspaceId", "artifactId", "connection", "externalReferences", "annotations"];
+pub const VALUE_LIMIT: usize = 300;
+pub const PROPERTY_DEPTH: usize = 4;
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataPipeline {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    // `(name, type, default)` of every pipeline parameter.
+    pub parameters: Vec<(String, String, Option<String>)>,
+    pub variables: Vec<(String, String, Option<String>)>,
+    pub activities: Vec<Activity>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Activity {
+    pub name: String,
+    pub kind: String,
+    pub description: Option<String>,
+    // `(activity, conditions)` this one waits for.
+    pub depends_on: Vec<(String, Vec<String>)>,
+    // Flattened `typeProperties` and `policy`, as `(path, value)`.
+    pub properties: Vec<(String, String)>,
+    // Nested activities per branch, e.g. `activities`, `ifTrueActivities` or `case a`.
+    pub branches: Vec<(String, Vec<Activity>)>,
+}
+
+
+// ====================================================
+//  Detection
+// ====================================================
+pub fn is_json_path(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("json"))
+}
+
+
+pub fn is_pipeline_path(path: &Path) -> bool {
+    pipeline_name(path).is_some()
+}
+
+
+pub fn pipeline_name(path: &Path) -> Option<String> {
+
+    // Returns the display name of a Fabric pipeline item,
+    // `<name>.DataPipeline/pipeline-content.json`.
+
+    let parent = path.parent()?.file_name()?.to_string_lossy().to_string();
+    (path.file_name()? == PIPELINE_CONTENT_FILE).then_some(())?;
+    parent.strip_suffix(FABRIC_PIPELINE_SUFFIX).map(str::to_string)
+}
+
+
+// ====================================================
+//  Parsing
+// ====================================================
+pub fn parse_pipeline(text: &str) -> Result<DataPipeline, DoxcerError> {
+
+    // Parses a Fabric (or Azure Data Factory) pipeline definition.
+    //
+    // # Description
+    // The definition is the JSON of `pipeline-content.json`: the pipeline
+    // sits under `properties` (or at the root) with its `activities`,
+    // `parameters` and `variables`. Without an `activities` list the JSON is
+    // not a pipeline.
+
+    let json: Value = serde_json::from_str(text).map_err(|e| DoxcerError::Input(format!("not a pipeline definition: {e}")))?;
+    let properties = json.get("properties").filter(|p| p.get("activities").is_some()).unwrap_or(&json);
+    let activities = properties
+        .get("activities")
+        .and_then(Value::as_array)
+        .ok_or_else(|| DoxcerError::Input("not a pipeline definition: no `activities` list".to_string()))?;
+    Ok(DataPipeline {
+        name: json.get("name").and_then(Value::as_str).map(str::to_string),
+        description: properties.get("description").and_then(Value::as_str).map(str::to_string),
+        parameters: declarations(properties.get("parameters")),
+        variables: declarations(properties.get("variables")),
+        activities: activities.iter().filter_map(parse_activity).collect(),
+    })
+}
+
+
+fn declarations(value: Option<&Value>) -> Vec<(String, String, Option<String>)> {
+
+    // Reads the `{ name: { type, defaultValue } }` map of parameters or variables.
+
+    let Some(map) = value.and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    map.iter()
+        .map(|(name, declaration)| {
+            let kind = declaration.get("type").and_then(Value::as_str).unwrap_or("-").to_string();
+            let default = declaration.get("defaultValue").map(scalar).filter(|v| !v.is_empty());
+            (name.clone(), kind, default)
+        })
+        .collect()
+}
+
+
+fn parse_activity(value: &Value) -> Option<Activity> {
+
+    // Reads one activity with its dependencies, properties and nested
+    // activities.
+
+    let name = value.get("name")?.as_str()?.to_string();
+    let kind = value.get("type").and_then(Value::as_str).unwrap_or("-").to_string();
+    let depends_on = value
+        .get("dependsOn")
+        .and_then(Value::as_array)
+        .map(|deps| {
+            deps.iter()
+                .filter_map(|dep| {
+                    let activity = dep.get("activity")?.as_str()?.to_string();
+                    let conditions = dep
+                        .get("dependencyConditions")
+                        .and_then(Value::as_array)
+                        .map(|c| c.iter().filter_map(Value::as_str).map(str::to_string).collect())
+                        .unwrap_or_default();
+                    Some((activity, conditions))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut properties = Vec::new();
+    let mut branches = Vec::new();
+    if let Some(type_properties) = value.get("typeProperties").and_then(Value::as_object) {
+        for (key, nested) in type_properties.iter().filter(|(key, _)| NESTED_ACTIVITY_KEYS.contains(&key.as_str())) {
+            match (key.as_str(), nested) {
+                ("cases", Value::Array(cases)) => {
+                    for case in cases {
+                        let label = format!("case {}", case.get("value").map(scalar).unwrap_or_default());
+                        branches.push((label, nested_activities(case.get("activities"))));
+                    }
+                }
+                _ => branches.push((key.clone(), nested_activities(Some(nested)))),
+            }
+        }
+        flatten(type_properties, "", 0, &mut properties);
+    }
+    if let Some(policy) = value.get("policy").and_then(Value::as_object) {
+        flatten(policy, "policy.", 0, &mut properties);
+    }
+    Some(Activity {
+        name,
+        kind,
+        description: value.get("description").and_then(Value::as_str).map(str::to_string),
+        depends_on,
+        properties,
+        branches,
+    })
+}
+
+
+fn nested_activities(value: Option<&Value>) -> Vec<Activity> {
+    value.and_then(Value::as_array).map(|list| list.iter().filter_map(parse_activity).collect()).unwrap_or_default()
+}
+
+
+fn flatten(map: &Map<String, Value>, prefix: &str, depth: usize, out: &mut Vec<(String, String)>) {
+
+    // Flattens properties into `(path, value)` pairs, skipping nested
+    // activities and workspace identifiers. An expression
+    // (`{ "value": "@...", "type": "Expression" }`) is shown as its text, and
+    // a typed value such as a notebook parameter as `value (type)`.
+
+    for (key, value) in map {
+        if NESTED_ACTIVITY_KEYS.contains(&key.as_str()) || NOISE_KEYS.contains(&key.as_str()) {
+            continue;
+        }
+        let path = format!("{prefix}{key}");
+        match value {
+            Value::Object(inner) if inner.get("type").and_then(Value::as_str) == Some("Expression") => {
+                out.push((path, inner.get("value").map(scalar).unwrap_or_default()));
+            }
+            Value::Object(inner) if inner.len() == 2 && inner.contains_key("value") && inner.get("type").is_some_and(Value::is_string) => {
+                let kind = inner.get("type").map(scalar).unwrap_or_default();
+                out.push((path, format!("{} ({kind})", inner.get("value").map(scalar).unwrap_or_default())));
+            }
+            Value::Object(inner) if depth + 1 < PROPERTY_DEPTH => flatten(inner, &format!("{path}."), depth + 1, out),
+            Value::Null => {}
+            Value::Array(items) if items.is_empty() => {}
+            Value::Object(inner) if inner.is_empty() => {}
+            other => out.push((path, scalar(other))),
+        }
+    }
+}
+
+
+fn scalar(value: &Value) -> String {
+
+    // Renders a value on one line, cut at `VALUE_LIMIT` characters.
+
+    let text = match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(VALUE_LIMIT) {
+        Some((cut, _)) => format!("{}…", &text[..cut]),
+        None => text,
+    }
+}
+
+
+// ====================================================
+//  Rendering
+// ====================================================
+impl DataPipeline {
+
+    pub fn render(&self, fallback_name: &str) -> String {
+
+        // Renders the pipeline for a prompt: its parameters and variables,
+        // then every activity in order with what it waits for and its
+        // properties, nested activities indented below their container.
+
+        let mut out = format!("Pipeline: {}\n", self.name.as_deref().unwrap_or(fallback_name));
+        if let Some(description) = &self.description {
+            out.push_str(&format!("Beschrijving: {description}\n"));
+        }
+        for (label, declarations) in [("Parameters", &self.parameters), ("Variabelen", &self.variables)] {
+            if declarations.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n{label}:\n"));
+            for (name, kind, default) in declarations {
+                let default = default.as_ref().map(|d| format!(", standaard {d}")).unwrap_or_default();
+                out.push_str(&format!("- {name} ({kind}{default})\n"));
+            }
+        }
+        out.push_str("\nActiviteiten:\n");
+        render_activities(&self.activities, 0, &mut out);
+        out
+    }
+}
+
+
+fn render_activities(activities: &[Activity], depth: usize, out: &mut String) {
+    let indent = "   ".repeat(depth);
+    for (i, activity) in activities.iter().enumerate() {
+        let after = match activity.depends_on.is_empty() {
+            true => String::new(),
+            false => {
+                let deps: Vec<String> = activity
+                    .depends_on
+                    .iter()
+                    .map(|(name, conditions)| match conditions.is_empty() {
+                        true => name.clone(),
+                        false => format!("{name} ({})", conditions.join(", ")),
+                    })
+                    .collect();
+                format!(" — na {}", deps.join(", "))
+            }
+        };
+        out.push_str(&format!("{indent}{}. {} ({}){after}\n", i + 1, activity.name, activity.kind));
+        if let Some(description) = &activity.description {
+            out.push_str(&format!("{indent}   beschrijving: {description}\n"));
+        }
+        for (path, value) in &activity.properties {
+            out.push_str(&format!("{indent}   {path}: {value}\n"));
+        }
+        for (label, nested) in &activity.branches {
+            out.push_str(&format!("{indent}   {label}:\n"));
+            render_activities(nested, depth + 2, out);
+        }
+    }
+}
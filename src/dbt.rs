@@ -0,0 +1,193 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::frontmatter::split_front_matter;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const PROJECT_FILE: &str = "dbt_project.yml";
+pub const DEFAULT_MODEL_PATH: &str = "models";
+pub const SCHEMA_EXTENSIONS: [&str; 2] = ["yml", "yaml"];
+
+
+// ====================================================
+//  Project
+// ====================================================
+pub fn model_paths(project: &str) -> Vec<String> {
+
+    // Returns the model directories of a `dbt_project.yml`, relative to the
+    // project: `model-paths` (or the older `source-paths`) as a flow list
+    // (`["models"]`) or a block list, else `models`.
+    //
+    // # Description
+    // Only this one key is read, so no YAML parser is needed; anything more
+    // involved than a list of plain strings falls back to the default.
+
+    let lines: Vec<&str> = project.lines().collect();
+    for (i, line) in lines.iter().enumerate() {
+        let Some(value) = ["model-paths:", "source-paths:"].iter().find_map(|key| line.strip_prefix(key)) else {
+            continue;
+        };
+        let value = value.split(" #").next().unwrap_or_default().trim();
+        let paths: Vec<String> = match value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            Some(items) => items.split(',').map(unquote).filter(|p| !p.is_empty()).collect(),
+            None if value.is_empty() => lines[i + 1..]
+                .iter()
+                .take_while(|l| l.trim().is_empty() || l.starts_with([' ', '\t', '-']))
+                .filter_map(|l| l.trim().strip_prefix('-').map(unquote))
+                .filter(|p| !p.is_empty())
+                .collect(),
+            None => vec![unquote(value)],
+        };
+        if !paths.is_empty() {
+            return paths;
+        }
+    }
+    vec![DEFAULT_MODEL_PATH.to_string()]
+}
+
+
+pub fn schema_files(dirs: &[PathBuf]) -> Vec<PathBuf> {
+
+    // Returns the `.yml` and `.yaml` files below the model directories, sorted;
+    // hidden directories are skipped.
+
+    let mut files = Vec::new();
+    let mut pending: Vec<PathBuf> = dirs.to_vec();
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for path in entries.filter_map(|entry| entry.ok().map(|e| e.path())) {
+            let hidden = path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.'));
+            if path.is_dir() && !hidden {
+                pending.push(path);
+            } else if path.extension().and_then(|e| e.to_str()).is_some_and(|e| SCHEMA_EXTENSIONS.contains(&e)) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches(['"', '\'']).trim().to_string()
+}
+
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+
+// ====================================================
+//  Descriptions
+// ====================================================
+pub fn model_description(markdown: &str) -> Option<String> {
+
+    // Returns the first prose paragraph of a generated document, on one line,
+    // as the `description` of its model. Headings, tables, lists, quotes and
+    // code blocks are skipped.
+
+    let (_, body) = split_front_matter(markdown);
+    let mut fence = false;
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in body.lines().map(str::trim) {
+        if line.starts_with("```") {
+            fence = !fence;
+            continue;
+        }
+        let prose = !fence && !line.is_empty() && !line.starts_with(['#', '|', '-', '>', '<', '!']) && line != "---";
+        match (prose, paragraph.is_empty()) {
+            (true, _) => paragraph.push(line),
+            (false, false) => break,
+            (false, true) => {}
+        }
+    }
+    (!paragraph.is_empty()).then(|| paragraph.join(" ").replace("**", ""))
+}
+
+
+pub fn set_model_description(yaml: &str, model: &str, description: &str) -> Option<String> {
+
+    // Sets the `description` of one model in a dbt properties file
+    // (`schema.yml`) and returns the new text, or `None` when the file has no
+    // entry for `model` under its top-level `models:`.
+    //
+    // # Description
+    // The file is edited line by line, so comments, key order and the other
+    // entries stay as they are. An existing description (also a multi-line
+    // block scalar) is replaced by one double-quoted line; without one, the
+    // key is added right below `name:`. Column descriptions are not touched.
+
+    let lines: Vec<&str> = yaml.lines().collect();
+    let start = lines.iter().position(|l| l.trim_end() == "models:")? + 1;
+    let end = lines[start..]
+        .iter()
+        .position(|l| !l.trim().is_empty() && !l.starts_with([' ', '\t', '-', '#']))
+        .map_or(lines.len(), |i| start + i);
+
+    // The list items of `models:` all start at the indent of the first one.
+    let item_indent = lines[start..end].iter().find(|l| l.trim_start().starts_with('-')).map(|l| indent_of(l))?;
+    let (name_line, key_indent) = (start..end).find_map(|i| {
+        let line = lines[i];
+        let item = line.get(item_indent..)?.strip_prefix('-')?;
+        let key = item.trim_start();
+        let name = key.strip_prefix("name:")?;
+        (indent_of(line) == item_indent && unquote(name) == model).then(|| (i, item_indent + 1 + item.len() - key.len()))
+    })?;
+    let entry_end = (name_line + 1..end)
+        .find(|i| {
+            let line = lines[*i];
+            !line.trim().is_empty() && !line.trim_start().starts_with('#') && indent_of(line) <= item_indent
+        })
+        .unwrap_or(end);
+
+    let quoted = serde_json::to_string(description).ok()?;
+    let new_line = format!("{}description: {quoted}", " ".repeat(key_indent));
+    let existing = (name_line + 1..entry_end).find(|i| {
+        let line = lines[*i];
+        indent_of(line) == key_indent && line.trim_start().starts_with("description:")
+    });
+    let mut out: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+    match existing {
+        Some(at) => {
+            // The value runs on while lines are indented deeper than the key.
+            let value_end = (at + 1..entry_end)
+                .find(|i| !lines[*i].trim().is_empty() && indent_of(lines[*i]) <= key_indent)
+                .unwrap_or(entry_end);
+            let last = (at..value_end).rev().find(|i| !lines[*i].trim().is_empty()).unwrap_or(at);
+            out.splice(at..=last, [new_line]);
+        }
+        None => out.insert(name_line + 1, new_line),
+    }
+    let mut text = out.join("\n");
+    if yaml.ends_with('\n') {
+        text.push('\n');
+    }
+    Some(text)
+}
+
+
+pub fn write_schema_file(path: &Path, text: &str) -> Result<(), DoxcerError> {
+
+    // Replaces a properties file through a `.partial` sibling and a rename.
+
+    let tmp = path.with_extension(format!("{}.partial", path.extension().and_then(|e| e.to_str()).unwrap_or("yml")));
+    fs::write(&tmp, text).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", tmp.display())))?;
+    fs::rename(&tmp, path).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", path.display())))
+}
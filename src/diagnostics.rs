@@ -0,0 +1,231 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fmt;
+
+// Internal Modules
+use crate::provenance::content_hash;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const ALLOWED_PREFIXES: [&str; 4] = ["DOXCER_", "OPENAI_", "AZURE_OPENAI_", "ANTHROPIC_"];
+pub const ALLOWED_NAMES: [&str; 3] = ["ENCRYPTION_PASSWORD", "NO_COLOR", "OLLAMA_HOST"];
+pub const SECRET_MARKERS: [&str; 4] = ["KEY", "TOKEN", "PASSWORD", "SECRET"];
+pub const SECRET_SUFFIXES: [&str; 2] = ["_ENC", "_AUTH_HEADER"];
+pub const PUBLIC_NAMES: [&str; 1] = ["DOXCER_MAX_OUTPUT_TOKENS"];
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvValue {
+    Plain(String),
+    Redacted { chars: usize, fingerprint: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvEntry {
+    pub name: String,
+    pub value: EnvValue,
+}
+
+impl fmt::Display for EnvValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvValue::Plain(v) => write!(f, "{v}"),
+            EnvValue::Redacted { chars, fingerprint } => {
+                write!(f, "set, {chars} chars, fingerprint {fingerprint}")
+            }
+        }
+    }
+}
+
+
+// ====================================================
+//  Environment Snapshot
+// ====================================================
+pub fn is_allowed_name(name: &str) -> bool {
+
+    // Checks whether a variable belongs in the snapshot at all.
+
+    ALLOWED_PREFIXES.iter().any(|p| name.starts_with(p)) || ALLOWED_NAMES.contains(&name)
+}
+
+
+pub fn is_secret_name(name: &str) -> bool {
+
+    // Checks whether a variable's value must never be shown.
+    //
+    // # Description
+    // Matches `*KEY*`, `*TOKEN*`, `*PASSWORD*`, `*SECRET*`, `*_ENC` and `*_AUTH_HEADER`,
+    // case-insensitively, so `OPENAI_API_KEY_ENC` and `doxcer_token` are both
+    // redacted. Doxcer's own settings that merely contain a marker (such as
+    // `DOXCER_MAX_OUTPUT_TOKENS`) are listed in `PUBLIC_NAMES` and shown.
+
+    let upper = name.to_ascii_uppercase();
+    if PUBLIC_NAMES.contains(&upper.as_str()) {
+        return false;
+    }
+    SECRET_MARKERS.iter().any(|m| upper.contains(m)) || SECRET_SUFFIXES.iter().any(|s| upper.ends_with(s))
+}
+
+
+pub fn fingerprint(value: &str) -> String {
+
+    // Returns a short, non-reversible fingerprint of a secret value.
+    //
+    // # Description
+    // The first 8 hex characters of its SHA-256, enough to tell whether two
+    // machines use the same secret without revealing it.
+
+    let hash = content_hash(value.as_bytes());
+    let hex = hash.split_once(':').map(|(_, h)| h).unwrap_or(&hash);
+    hex.chars().take(8).collect()
+}
+
+
+pub fn env_snapshot<I>(vars: I) -> Vec<EnvEntry>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+
+    // Filters and redacts environment variables for diagnostics.
+    //
+    // # Description
+    // Keeps only variables on the allowlist (`DOXCER_*`, the provider
+    // prefixes `OPENAI_*`, `AZURE_OPENAI_*` and `ANTHROPIC_*`, and a few known
+    // names such as `OLLAMA_HOST`). Values of secret-looking names are replaced by their length
+    // and fingerprint. Pure: pass `std::env::vars()` or any other list.
+    //
+    // # Returns
+    // * The kept variables, sorted by name.
+
+    let mut entries: Vec<EnvEntry> = vars
+        .into_iter()
+        .filter(|(name, _)| is_allowed_name(name))
+        .map(|(name, value)| {
+            let value = if is_secret_name(&name) {
+                EnvValue::Redacted { chars: value.chars().count(), fingerprint: fingerprint(&value) }
+            } else {
+                EnvValue::Plain(value)
+            };
+            EnvEntry { name, value }
+        })
+        .collect();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    entries
+}
+
+
+pub fn render_env_snapshot(entries: &[EnvEntry]) -> String {
+
+    // Renders a snapshot as `NAME = value` lines.
+
+    if entries.is_empty() {
+        return "  (no DOXCER_* or provider variables set)\n".to_string();
+    }
+    entries.iter().map(|e| format!("  {} = {}\n", e.name, e.value)).collect()
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect()
+    }
+
+
+    #[test]
+    fn keeps_only_allowlisted_variables() {
+        let snapshot = env_snapshot(vars(&[
+            ("PATH", "/usr/bin"),
+            ("HOME", "/home/user"),
+            ("DOXCER_MODEL", "gpt-5-mini"),
+            ("OPENAI_BASE_URL", "https://api.openai.com"),
+            ("NO_COLOR", "1"),
+        ]));
+        let names: Vec<&str> = snapshot.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["DOXCER_MODEL", "NO_COLOR", "OPENAI_BASE_URL"]);
+        assert_eq!(snapshot[0].value, EnvValue::Plain("gpt-5-mini".to_string()));
+    }
+
+
+    #[test]
+    fn never_shows_secret_values() {
+        let secrets = [
+            ("OPENAI_API_KEY", "sk-live-abcdefghijklmnopqrstuvwxyz"),
+            ("OPENAI_API_KEY_ENC", "gAAAAABlongtoken"),
+            ("ENCRYPTION_PASSWORD", "c2VjcmV0LWtleS1tYXRlcmlhbA=="),
+            ("DOXCER_FABRIC_TOKEN", "eyJhbGciOi"),
+            ("DOXCER_db_password", "hunter2"),
+            ("DOXCER_CLIENT_SECRET", "s3cr3t"),
+            ("DOXCER_SOURCE_AUTH_HEADER", "Bearer abc"),
+            ("DOXCER_THING_ENC", "gAAAAAB"),
+        ];
+        let snapshot = env_snapshot(vars(&secrets));
+        assert_eq!(snapshot.len(), secrets.len());
+        let rendered = render_env_snapshot(&snapshot);
+        for (name, value) in secrets {
+            let entry = snapshot.iter().find(|e| e.name == name).unwrap();
+            assert_eq!(entry.value, EnvValue::Redacted { chars: value.chars().count(), fingerprint: fingerprint(value) });
+            assert!(!rendered.contains(value), "{name} leaked into {rendered}");
+        }
+    }
+
+
+    #[test]
+    fn keeps_the_settings_of_every_provider() {
+        let snapshot = env_snapshot(vars(&[
+            ("AZURE_OPENAI_ENDPOINT", "https://contoso.openai.azure.com"),
+            ("AZURE_OPENAI_DEPLOYMENT", "gpt-4o"),
+            ("AZURE_OPENAI_API_KEY", "azure-secret-value"),
+            ("ANTHROPIC_BASE_URL", "https://gateway.example/v1"),
+            ("ANTHROPIC_API_KEY", "sk-ant-secret-value"),
+            ("OLLAMA_HOST", "http://localhost:11434"),
+            ("AZURE_CLIENT_ID", "not-a-doxcer-setting"),
+        ]));
+        let names: Vec<&str> = snapshot.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["ANTHROPIC_API_KEY", "ANTHROPIC_BASE_URL", "AZURE_OPENAI_API_KEY", "AZURE_OPENAI_DEPLOYMENT", "AZURE_OPENAI_ENDPOINT", "OLLAMA_HOST"]);
+        let rendered = render_env_snapshot(&snapshot);
+        assert!(rendered.contains("AZURE_OPENAI_ENDPOINT = https://contoso.openai.azure.com"), "{rendered}");
+        assert!(rendered.contains("OLLAMA_HOST = http://localhost:11434"), "{rendered}");
+        for secret in ["azure-secret-value", "sk-ant-secret-value"] {
+            assert!(!rendered.contains(secret), "{secret} leaked into {rendered}");
+        }
+    }
+
+
+    #[test]
+    fn public_names_with_a_marker_are_shown() {
+        let snapshot = env_snapshot(vars(&[("DOXCER_MAX_OUTPUT_TOKENS", "4000")]));
+        assert_eq!(snapshot[0].value, EnvValue::Plain("4000".to_string()));
+    }
+
+
+    #[test]
+    fn redacted_values_render_length_and_fingerprint() {
+        let snapshot = env_snapshot(vars(&[("OPENAI_API_KEY", "abcd")]));
+        let fingerprint = fingerprint("abcd");
+        assert_eq!(fingerprint.len(), 8);
+        assert_eq!(render_env_snapshot(&snapshot), format!("  OPENAI_API_KEY = set, 4 chars, fingerprint {fingerprint}\n"));
+    }
+
+
+    #[test]
+    fn empty_snapshot_says_so() {
+        assert_eq!(render_env_snapshot(&env_snapshot(vars(&[("PATH", "/bin")]))), "  (no DOXCER_* or provider variables set)\n");
+    }
+}
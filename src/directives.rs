@@ -0,0 +1,73 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::BTreeMap;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const DIRECTIVE_PREFIX: &str = "# doxcer:";
+
+
+// ====================================================
+//  Directive Parsing
+// ====================================================
+pub fn parse_directives(content: &str) -> BTreeMap<String, String> {
+
+    // Collects inline `# doxcer: key=value` directives from a notebook.
+    //
+    // # Description
+    // A directive line contains `# doxcer:` followed by one or more `key=value`
+    // pairs separated by commas or whitespace. The prefix may appear inside a JSON
+    // string (as in `.ipynb` sources); the directive then ends at the first quote
+    // or escaped newline. Later occurrences of a key override earlier ones.
+    //
+    // # Parameters
+    // * `content` – The raw notebook contents.
+    //
+    // # Returns
+    // * A map of directive keys to their (trimmed) values.
+
+    let mut directives = BTreeMap::new();
+    for line in content.lines() {
+        let Some(start) = line.find(DIRECTIVE_PREFIX) else {
+            continue;
+        };
+        let rest = &line[start + DIRECTIVE_PREFIX.len()..];
+        let rest = match rest.find(['"', '\\']) {
+            Some(end) => &rest[..end],
+            None => rest,
+        };
+        for pair in rest.split([',', ' ', '\t']).filter(|p| !p.is_empty()) {
+            if let Some((key, value)) = pair.split_once('=') {
+                directives.insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+    }
+    directives
+}
+
+
+pub fn is_skipped(directives: &BTreeMap<String, String>) -> bool {
+
+    // Checks whether the directives ask doxcer to skip this notebook (`skip=true`).
+
+    directives
+        .get("skip")
+        .is_some_and(|v| v.eq_ignore_ascii_case("true"))
+}
+
+
+pub fn pinned_model(directives: &BTreeMap<String, String>) -> Option<&str> {
+
+    // Returns the model a notebook pins with `model=<name>`, which takes it out
+    // of `model_routing`.
+
+    directives.get("model").map(String::as_str).filter(|m| !m.is_empty())
+}
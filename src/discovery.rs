@@ -0,0 +1,466 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+// External Libraries
+use ignore::Match;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+// Internal Modules
+use crate::datapipeline::is_pipeline_path;
+use crate::directives::{is_skipped, parse_directives};
+use crate::error::DoxcerError;
+use crate::kind::is_sql_path;
+use crate::notebook::DATABRICKS_HEADER;
+use crate::semanticmodel::is_semantic_model_path;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const NOTEBOOK_EXTENSIONS: [&str; 2] = ["py", "ipynb"];
+pub const IGNORE_FILE_NAME: &str = ".doxcerignore";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Default)]
+pub struct DiscoveryOptions {
+    pub excludes: Vec<String>,
+    pub includes: Vec<String>,
+    // With `--kind sql`, every `.sql` file is collected, not only Databricks exports.
+    pub sql_scripts: bool,
+    // With `--kind pipeline`, Fabric Data Pipelines (`pipeline-content.json`) are collected.
+    pub pipelines: bool,
+    // With `--kind semantic-model`, `model.bim` and TMDL `definition/model.tmdl` files are collected.
+    pub semantic_models: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExclusionRule {
+    IgnoreFile { file: PathBuf, pattern: String },
+    CliExclude { pattern: String },
+    NotIncluded,
+    Directive,
+}
+
+#[derive(Debug, Clone)]
+pub struct Excluded {
+    pub path: PathBuf,
+    pub rule: ExclusionRule,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Discovery {
+    pub notebooks: Vec<PathBuf>,
+    pub excluded: Vec<Excluded>,
+}
+
+struct Rules {
+    sql_scripts: bool,
+    pipelines: bool,
+    semantic_models: bool,
+    cli: Gitignore,
+    include: Option<Gitignore>,
+    files: Vec<Gitignore>,
+}
+
+impl fmt::Display for ExclusionRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExclusionRule::IgnoreFile { file, pattern } => write!(f, "{pattern} ({})", file.display()),
+            ExclusionRule::CliExclude { pattern } => write!(f, "{pattern} (--exclude)"),
+            ExclusionRule::NotIncluded => write!(f, "no --include pattern matches"),
+            ExclusionRule::Directive => write!(f, "# doxcer: skip=true"),
+        }
+    }
+}
+
+
+// ====================================================
+//  Notebook Detection
+// ====================================================
+pub fn is_notebook_path(path: &Path) -> bool {
+
+    // Checks whether a path has one of the supported notebook extensions.
+
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| NOTEBOOK_EXTENSIONS.contains(&e))
+}
+
+
+pub fn is_databricks_sql(path: &Path) -> bool {
+
+    // Checks whether a `.sql` file is a Databricks notebook export, by its
+    // `-- Databricks notebook source` first line. Other SQL files are not
+    // notebooks and are left out when a directory is walked.
+
+    if !is_sql_path(path) {
+        return false;
+    }
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+    let mut first = String::new();
+    BufReader::new(file).read_line(&mut first).is_ok()
+        && first.trim_start_matches('\u{feff}').strip_prefix("--").is_some_and(|rest| rest.trim() == DATABRICKS_HEADER)
+}
+
+
+// ====================================================
+//  Discovery
+// ====================================================
+pub fn discover(inputs: &[PathBuf], options: &DiscoveryOptions) -> Result<Discovery, DoxcerError> {
+
+    // Expands a list of input paths into the notebooks they refer to.
+    //
+    // # Description
+    // Files are taken as-is. Directories are walked recursively and every file with
+    // a notebook extension is collected, and `.sql` files that are Databricks
+    // exports (every `.sql` file with `sql_scripts`, the Fabric
+    // `pipeline-content.json` files with `pipelines`, and `model.bim` and
+    // `definition/model.tmdl` with `semantic_models`); hidden directories (such
+    // as `.git` and `.doxcer`) are skipped. While walking, files and folders are
+    // excluded by:
+    // 1. `--exclude` patterns (gitignore syntax, relative to the working directory)
+    // 2. `.doxcerignore` files in the scanned directory and its ancestors up to the
+    //    repository root (deeper files take precedence, `!pattern` re-includes)
+    // 3. An inline `# doxcer: skip=true` directive inside the notebook
+    //
+    // With `--include` patterns, a notebook found while walking must also match
+    // one of them (gitignore syntax, matched against the file itself).
+    //
+    // Results per directory are sorted so runs are reproducible.
+    //
+    // # Parameters
+    // * `inputs` – Files and/or directories given on the command line.
+    // * `options` – Extra exclusion and inclusion patterns.
+    //
+    // # Returns
+    // * `Ok(Discovery)` with the included notebooks and the excluded paths.
+    // * `Err(DoxcerError)` if an input does not exist or an ignore file is invalid.
+
+    let cli = cli_rules(options)?;
+    let include = include_rules(options)?;
+    let mut discovery = Discovery::default();
+    for input in inputs {
+        if input.is_dir() {
+            let rules = Rules { sql_scripts: options.sql_scripts, pipelines: options.pipelines, semantic_models: options.semantic_models, cli: cli.clone(), include: include.clone(), files: ignore_files_for(input)? };
+            walk_dir(input, &rules, &mut discovery)?;
+        } else if input.is_file() {
+            discovery.notebooks.push(input.clone());
+        } else {
+            return Err(DoxcerError::Input(format!("Input path does not exist: {}", input.display())));
+        }
+    }
+    Ok(discovery)
+}
+
+
+pub fn discover_listed<R>(inputs: &[PathBuf], options: &DiscoveryOptions, listed: &[PathBuf], read: R) -> Result<Discovery, DoxcerError>
+where
+    R: Fn(&Path) -> Option<String>,
+{
+
+    // Expands input paths into notebooks from a list of files instead of the
+    // working tree.
+    //
+    // # Description
+    // `verify --at-ref` passes the files of a commit (see `git::ref_files`), so
+    // notebooks deleted from the working tree since then are found as well.
+    // The exclusions are those of [`discover`]: hidden directories, `--exclude`,
+    // the `.doxcerignore` files of the working tree, and `skip=true`, with the
+    // notebook read through `read`.
+    //
+    // # Parameters
+    // * `listed` – The candidate files, relative to the working directory.
+    // * `read` – Reads a notebook's text; `None` when it cannot be read.
+
+    let cli = cli_rules(options)?;
+    let include = include_rules(options)?;
+    let mut discovery = Discovery::default();
+    for input in inputs {
+        let base = input.strip_prefix(".").unwrap_or(input);
+        if listed.iter().any(|p| p == base) {
+            discovery.notebooks.push(base.to_path_buf());
+            continue;
+        }
+        let files: Vec<&PathBuf> = listed.iter().filter(|p| p.starts_with(base)).collect();
+        if files.is_empty() {
+            return Err(DoxcerError::Input(format!("Input path does not exist at the ref: {}", input.display())));
+        }
+        let rules = Rules { sql_scripts: options.sql_scripts, pipelines: options.pipelines, semantic_models: options.semantic_models, cli: cli.clone(), include: include.clone(), files: ignore_files_for(input)? };
+        'files: for path in files {
+            let relative = path.strip_prefix(base).unwrap_or(path);
+            let dirs: Vec<_> = relative.parent().map(|p| p.components().collect()).unwrap_or_default();
+            if dirs.iter().any(|c| c.as_os_str().to_string_lossy().starts_with('.')) || !(is_notebook_path(path) || rules.sql_scripts && is_sql_path(path) || rules.pipelines && is_pipeline_path(path) || rules.semantic_models && is_semantic_model_path(path)) {
+                continue;
+            }
+            let mut dir = base.to_path_buf();
+            for component in dirs {
+                dir.push(component);
+                if let Some(rule) = rules.check(&absolute(&dir), true) {
+                    if !discovery.excluded.iter().any(|e| e.path == dir) {
+                        discovery.excluded.push(Excluded { path: dir, rule });
+                    }
+                    continue 'files;
+                }
+            }
+            if let Some(rule) = rules.check(&absolute(path), false).or_else(|| rules.not_included(&absolute(path))) {
+                discovery.excluded.push(Excluded { path: path.clone(), rule });
+            } else if read(path).is_some_and(|c| is_skipped(&parse_directives(&c))) {
+                discovery.excluded.push(Excluded { path: path.clone(), rule: ExclusionRule::Directive });
+            } else {
+                discovery.notebooks.push(path.clone());
+            }
+        }
+    }
+    Ok(discovery)
+}
+
+
+fn cli_rules(options: &DiscoveryOptions) -> Result<Gitignore, DoxcerError> {
+
+    // Builds the matcher for `--exclude` patterns, relative to the working directory.
+
+    let cwd = absolute(Path::new("."));
+    let mut cli = GitignoreBuilder::new(&cwd);
+    for pattern in &options.excludes {
+        cli.add_line(None, pattern)
+            .map_err(|e| DoxcerError::Input(format!("Invalid --exclude pattern {pattern:?}: {e}")))?;
+    }
+    cli.build().map_err(|e| DoxcerError::Input(format!("Invalid --exclude pattern: {e}")))
+}
+
+
+fn include_rules(options: &DiscoveryOptions) -> Result<Option<Gitignore>, DoxcerError> {
+
+    // Builds the matcher for `--include` patterns; `None` when there are none,
+    // so every notebook is included.
+
+    if options.includes.is_empty() {
+        return Ok(None);
+    }
+    let mut include = GitignoreBuilder::new(absolute(Path::new(".")));
+    for pattern in &options.includes {
+        include.add_line(None, pattern)
+            .map_err(|e| DoxcerError::Input(format!("Invalid --include pattern {pattern:?}: {e}")))?;
+    }
+    include.build().map(Some).map_err(|e| DoxcerError::Input(format!("Invalid --include pattern: {e}")))
+}
+
+
+fn walk_dir(dir: &Path, rules: &Rules, discovery: &mut Discovery) -> Result<(), DoxcerError> {
+
+    // Recursively collects notebooks below `dir` in sorted order.
+
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| DoxcerError::Input(format!("Failed to read directory {}: {e}", dir.display())))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        let hidden = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with('.'));
+        let is_dir = path.is_dir();
+        let sql = is_sql_path(&path) && (rules.sql_scripts || is_databricks_sql(&path));
+        let item = rules.pipelines && is_pipeline_path(&path) || rules.semantic_models && is_semantic_model_path(&path);
+        if hidden && is_dir || !is_dir && !is_notebook_path(&path) && !sql && !item {
+            continue;
+        }
+
+        if let Some(rule) = rules.check(&absolute(&path), is_dir) {
+            discovery.excluded.push(Excluded { path, rule });
+        } else if is_dir {
+            walk_dir(&path, rules, discovery)?;
+        } else if let Some(rule) = rules.not_included(&absolute(&path)) {
+            discovery.excluded.push(Excluded { path, rule });
+        } else if fs::read_to_string(&path).is_ok_and(|c| is_skipped(&parse_directives(&c))) {
+            discovery.excluded.push(Excluded { path, rule: ExclusionRule::Directive });
+        } else {
+            discovery.notebooks.push(path);
+        }
+    }
+    Ok(())
+}
+
+
+// ====================================================
+//  Ignore Rules
+// ====================================================
+impl Rules {
+
+    fn check(&self, path: &Path, is_dir: bool) -> Option<ExclusionRule> {
+
+        // Returns the rule that excludes `path`, if any.
+        //
+        // # Description
+        // CLI patterns win over ignore files; among ignore files the deepest one
+        // with a matching pattern decides, including `!pattern` whitelists.
+
+        match self.cli.matched(path, is_dir) {
+            Match::Ignore(glob) => {
+                return Some(ExclusionRule::CliExclude { pattern: glob.original().to_string() });
+            }
+            Match::Whitelist(_) => return None,
+            Match::None => {}
+        }
+
+        for file in &self.files {
+            match file.matched(path, is_dir) {
+                Match::Ignore(glob) => {
+                    return Some(ExclusionRule::IgnoreFile {
+                        file: glob.from().map(Path::to_path_buf)
+                            .unwrap_or_else(|| file.path().join(IGNORE_FILE_NAME)),
+                        pattern: glob.original().to_string(),
+                    });
+                }
+                Match::Whitelist(_) => return None,
+                Match::None => {}
+            }
+        }
+        None
+    }
+
+
+    fn not_included(&self, path: &Path) -> Option<ExclusionRule> {
+
+        // Returns `NotIncluded` when `--include` patterns are given and none
+        // of them matches the notebook at `path`.
+
+        let include = self.include.as_ref()?;
+        (!include.matched(path, false).is_ignore()).then_some(ExclusionRule::NotIncluded)
+    }
+}
+
+
+fn ignore_files_for(dir: &Path) -> Result<Vec<Gitignore>, DoxcerError> {
+
+    // Loads the `.doxcerignore` files that apply to a scanned directory.
+    //
+    // # Description
+    // Looks in `dir` itself and, when `dir` lives inside a git repository, in every
+    // ancestor up to and including the repository root. Outside a repository only
+    // the scanned directory is considered. Returned deepest first.
+
+    let dir = absolute(dir);
+    let in_repo = dir.ancestors().any(|a| a.join(".git").exists());
+
+    let mut matchers = Vec::new();
+    for ancestor in dir.ancestors() {
+        let file = ancestor.join(IGNORE_FILE_NAME);
+        if file.is_file() {
+            let mut builder = GitignoreBuilder::new(ancestor);
+            if let Some(e) = builder.add(&file) {
+                return Err(DoxcerError::Input(format!("Invalid {}: {e}", file.display())));
+            }
+            let matcher = builder
+                .build()
+                .map_err(|e| DoxcerError::Input(format!("Invalid {}: {e}", file.display())))?;
+            matchers.push(matcher);
+        }
+        if !in_repo || ancestor.join(".git").exists() {
+            break;
+        }
+    }
+    Ok(matchers)
+}
+
+
+fn absolute(path: &Path) -> PathBuf {
+
+    // Resolves a path against the working directory without touching symlinks.
+
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(files: &[&str], ignore: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for file in files {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "print('hello')\n").unwrap();
+        }
+        fs::write(dir.path().join(IGNORE_FILE_NAME), ignore).unwrap();
+        dir
+    }
+
+    fn names(dir: &Path, paths: &[PathBuf]) -> Vec<String> {
+        paths.iter().map(|p| p.strip_prefix(dir).unwrap().to_string_lossy().replace('\\', "/")).collect()
+    }
+
+
+    #[test]
+    fn negated_pattern_re_includes_a_file() {
+        let dir = workspace(&["scratch_a.py", "scratch_keep.py", "load.py"], "scratch_*.py\n!scratch_keep.py\n");
+        let discovery = discover(&[dir.path().to_path_buf()], &DiscoveryOptions::default()).unwrap();
+        assert_eq!(names(dir.path(), &discovery.notebooks), ["load.py", "scratch_keep.py"]);
+        let excluded: Vec<PathBuf> = discovery.excluded.iter().map(|e| e.path.clone()).collect();
+        assert_eq!(names(dir.path(), &excluded), ["scratch_a.py"]);
+        assert!(matches!(&discovery.excluded[0].rule, ExclusionRule::IgnoreFile { pattern, .. } if pattern == "scratch_*.py"));
+    }
+
+
+    #[test]
+    fn negation_order_decides() {
+        // The last matching pattern wins, so a later exclude undoes an
+        // earlier `!pattern`.
+        let dir = workspace(&["keep.py", "other.py"], "!keep.py\n*.py\n");
+        let discovery = discover(&[dir.path().to_path_buf()], &DiscoveryOptions::default()).unwrap();
+        assert!(discovery.notebooks.is_empty());
+        assert_eq!(discovery.excluded.len(), 2);
+    }
+
+
+    #[test]
+    fn deeper_ignore_file_re_includes() {
+        // Ancestor ignore files only apply inside a repository.
+        let dir = workspace(&["archive/old.py", "archive/kept.py"], "archive/*.py\n");
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join("archive").join(IGNORE_FILE_NAME), "!kept.py\n").unwrap();
+        let discovery = discover(&[dir.path().join("archive")], &DiscoveryOptions::default()).unwrap();
+        assert_eq!(names(dir.path(), &discovery.notebooks), ["archive/kept.py"]);
+    }
+
+
+    #[test]
+    fn cli_negation_wins_over_ignore_file() {
+        let dir = workspace(&["keep.py", "drop.py"], "*.py\n");
+        let options = DiscoveryOptions { excludes: vec!["drop.py".to_string(), "!keep.py".to_string()], ..DiscoveryOptions::default() };
+        let discovery = discover(&[dir.path().to_path_buf()], &options).unwrap();
+        assert_eq!(names(dir.path(), &discovery.notebooks), ["keep.py"]);
+        assert!(matches!(&discovery.excluded[0].rule, ExclusionRule::CliExclude { pattern } if pattern == "drop.py"));
+    }
+
+
+    #[test]
+    fn excluded_directory_is_not_walked() {
+        let dir = workspace(&["archive/old.py", "archive/nested/older.py", "live.py"], "archive/\n");
+        let discovery = discover(&[dir.path().to_path_buf()], &DiscoveryOptions::default()).unwrap();
+        assert_eq!(names(dir.path(), &discovery.notebooks), ["live.py"]);
+        let excluded: Vec<PathBuf> = discovery.excluded.iter().map(|e| e.path.clone()).collect();
+        assert_eq!(names(dir.path(), &excluded), ["archive"]);
+    }
+}
@@ -0,0 +1,123 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::summary::DocMode;
+use crate::verify::expected_doc_path;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const BACKUP_EXTENSION: &str = "bak";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DocTarget {
+    // `--output <dir>`: one file per notebook and mode below the directory.
+    Dir(PathBuf),
+    // `--output-file <path>`: the one document of the run.
+    File(PathBuf),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExistingPolicy {
+    // An existing file is left alone and the document fails.
+    #[default]
+    Refuse,
+    // `--overwrite`: the existing file is replaced.
+    Overwrite,
+    // `--backup`: the existing file is kept as `<file>.bak` first.
+    Backup,
+}
+
+impl DocTarget {
+
+    pub fn path(&self, notebook: &Path, mode: DocMode) -> PathBuf {
+
+        // Returns the file a notebook's document is written to.
+        //
+        // # Description
+        // Below a directory the full document follows the `docs/<stem>.md`
+        // convention of `verify` (see [`expected_doc_path`]), so the files
+        // can be checked later; the summary goes next to it as
+        // `<stem>.summary.md`.
+
+        match (self, mode) {
+            (DocTarget::File(path), _) => path.clone(),
+            (DocTarget::Dir(dir), DocMode::Full) => expected_doc_path(notebook, dir),
+            (DocTarget::Dir(dir), DocMode::Summary) => expected_doc_path(notebook, dir).with_extension("summary.md"),
+        }
+    }
+}
+
+
+// ====================================================
+//  Writing
+// ====================================================
+pub fn check_writable(path: &Path, policy: ExistingPolicy) -> Result<(), DoxcerError> {
+
+    // Fails when `path` exists and the policy does not allow replacing it,
+    // so a run can stop before spending an API request on it.
+
+    if policy == ExistingPolicy::Refuse && path.exists() {
+        return Err(DoxcerError::Input(format!("{} already exists; pass --overwrite or --backup to replace it", path.display())));
+    }
+    Ok(())
+}
+
+
+pub fn write_document(path: &Path, contents: &str, policy: ExistingPolicy) -> Result<Option<PathBuf>, DoxcerError> {
+
+    // Writes a generated document through a `.partial` sibling and a rename.
+    //
+    // # Description
+    // The existing file is only touched once the new content is on disk:
+    // with `Backup` it is renamed to `<file>.bak` (replacing an older backup)
+    // right before the rename, with `Overwrite` the rename replaces it.
+    //
+    // # Returns
+    // * `Ok(Some(path))` – the path of the backup that was made.
+    // * `Ok(None)` – no backup was needed.
+    // * `Err(DoxcerError)` – the file exists under `Refuse`, or writing failed.
+
+    check_writable(path, policy)?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| DoxcerError::Input(format!("Failed to create {}: {e}", parent.display())))?;
+    }
+    let tmp = path.with_extension("md.partial");
+    fs::write(&tmp, contents).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", tmp.display())))?;
+    let mut backup = None;
+    if policy == ExistingPolicy::Backup && path.exists() {
+        let target = backup_path(path);
+        if let Err(e) = fs::rename(path, &target) {
+            let _ = fs::remove_file(&tmp);
+            return Err(DoxcerError::Input(format!("Failed to back up {} to {}: {e}", path.display(), target.display())));
+        }
+        backup = Some(target);
+    }
+    fs::rename(&tmp, path).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", path.display())))?;
+    Ok(backup)
+}
+
+
+pub fn backup_path(path: &Path) -> PathBuf {
+
+    // Returns where `--backup` keeps the previous version of a file.
+
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{BACKUP_EXTENSION}"));
+    path.with_file_name(name)
+}
@@ -0,0 +1,396 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::frontmatter::split_front_matter;
+use crate::provenance::content_hash;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const SNAPSHOT_DIR: &str = ".doxcer/sources";
+pub const DIFF_CONTEXT: usize = 3;
+pub const DEFAULT_MAX_DIFF_LINES: usize = 400;
+// Above this many cells the line table of a diff gets too big to build; the
+// run then falls back to full regeneration.
+pub const MAX_DIFF_CELLS: usize = 4_000_000;
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditPlan {
+    Edit { prior_doc: String, diff: String },
+    Full(String),
+}
+
+
+// ====================================================
+//  Diff
+// ====================================================
+pub fn line_diff<'a>(old: &'a str, new: &'a str) -> Option<Vec<DiffLine<'a>>> {
+
+    // Compares two texts line by line (longest common subsequence).
+    //
+    // # Description
+    // The common head and tail are matched first, so a small change in a large
+    // notebook only costs a table over the changed middle.
+    //
+    // # Returns
+    // * `None` when the changed middle is over `MAX_DIFF_CELLS`.
+
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let head = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let tail = a[head..].iter().rev().zip(b[head..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let (mid_a, mid_b) = (&a[head..a.len() - tail], &b[head..b.len() - tail]);
+    if (mid_a.len() + 1) * (mid_b.len() + 1) > MAX_DIFF_CELLS {
+        return None;
+    }
+
+    // lcs[i][j]: length of the common subsequence of mid_a[i..] and mid_b[j..].
+    let width = mid_b.len() + 1;
+    let mut lcs = vec![0u32; (mid_a.len() + 1) * width];
+    for i in (0..mid_a.len()).rev() {
+        for j in (0..mid_b.len()).rev() {
+            lcs[i * width + j] = if mid_a[i] == mid_b[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut lines: Vec<DiffLine> = a[..head].iter().map(|l| DiffLine::Same(l)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < mid_a.len() || j < mid_b.len() {
+        if i < mid_a.len() && j < mid_b.len() && mid_a[i] == mid_b[j] {
+            lines.push(DiffLine::Same(mid_a[i]));
+            i += 1;
+            j += 1;
+        } else if i < mid_a.len() && (j == mid_b.len() || lcs[(i + 1) * width + j] >= lcs[i * width + j + 1]) {
+            lines.push(DiffLine::Removed(mid_a[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(mid_b[j]));
+            j += 1;
+        }
+    }
+    lines.extend(a[a.len() - tail..].iter().map(|l| DiffLine::Same(l)));
+    Some(lines)
+}
+
+
+pub fn unified_diff(old: &str, new: &str, label: &str) -> Option<String> {
+
+    // Renders a unified diff (`---`/`+++` header, `@@` hunks with three lines of
+    // context). Empty when the texts have the same lines; `None` when the diff
+    // is too large to compute (see [`line_diff`]).
+
+    let lines = line_diff(old, new)?;
+    let changed = |k: usize| !matches!(lines[k], DiffLine::Same(_));
+    if !(0..lines.len()).any(changed) {
+        return Some(String::new());
+    }
+
+    // Line numbers in the old and new text before each diff line.
+    let mut positions = Vec::with_capacity(lines.len() + 1);
+    let (mut old_pos, mut new_pos) = (0, 0);
+    for line in &lines {
+        positions.push((old_pos, new_pos));
+        match line {
+            DiffLine::Same(_) => (old_pos, new_pos) = (old_pos + 1, new_pos + 1),
+            DiffLine::Removed(_) => old_pos += 1,
+            DiffLine::Added(_) => new_pos += 1,
+        }
+    }
+    positions.push((old_pos, new_pos));
+
+    let mut out = format!("--- a/{label}\n+++ b/{label}\n");
+    let mut k = 0;
+    while k < lines.len() {
+        if !changed(k) {
+            k += 1;
+            continue;
+        }
+        let start = k.saturating_sub(DIFF_CONTEXT);
+        let mut last = k;
+        let mut scan = k;
+        while scan < lines.len() && scan - last <= 2 * DIFF_CONTEXT {
+            if changed(scan) {
+                last = scan;
+            }
+            scan += 1;
+        }
+        let stop = (last + DIFF_CONTEXT + 1).min(lines.len());
+        let (old_start, new_start) = positions[start];
+        let (old_end, new_end) = positions[stop];
+        let range = |from: usize, count: usize| if count == 0 { format!("{from},0") } else { format!("{},{count}", from + 1) };
+        out.push_str(&format!("@@ -{} +{} @@\n", range(old_start, old_end - old_start), range(new_start, new_end - new_start)));
+        for line in &lines[start..stop] {
+            let (marker, text) = match line {
+                DiffLine::Same(text) => (' ', text),
+                DiffLine::Removed(text) => ('-', text),
+                DiffLine::Added(text) => ('+', text),
+            };
+            out.push(marker);
+            out.push_str(text);
+            out.push('\n');
+        }
+        k = stop;
+    }
+    Some(out)
+}
+
+
+pub fn changed_lines(diff: &str) -> usize {
+
+    // Counts the added and removed lines of a unified diff.
+
+    diff.lines()
+        .filter(|l| (l.starts_with('+') && !l.starts_with("+++")) || (l.starts_with('-') && !l.starts_with("---")))
+        .count()
+}
+
+
+// ====================================================
+//  Source Snapshots
+// ====================================================
+pub fn snapshot_path(dir: &Path, source_hash: &str) -> PathBuf {
+
+    // Returns where the notebook content for a hash is kept (`<dir>/<hex>.txt`).
+
+    dir.join(format!("{}.txt", source_hash.strip_prefix("sha256:").unwrap_or(source_hash)))
+}
+
+
+pub fn store_snapshot(dir: &Path, source_hash: &str, text: &str) -> Result<PathBuf, DoxcerError> {
+
+    // Keeps the notebook content a document was generated from, so the next
+    // `--edit-mode` run can diff against it. Written atomically.
+
+    fs::create_dir_all(dir).map_err(|e| DoxcerError::Input(format!("Failed to create {}: {e}", dir.display())))?;
+    let path = snapshot_path(dir, source_hash);
+    let tmp = path.with_extension("partial");
+    fs::write(&tmp, text).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", tmp.display())))?;
+    fs::rename(&tmp, &path).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", path.display())))?;
+    Ok(path)
+}
+
+
+pub fn load_snapshot(path: &Path, source_hash: &str) -> Option<String> {
+
+    // Reads a stored notebook content; `None` when it is missing or no longer
+    // matches the hash it was stored for.
+
+    let bytes = fs::read(path).ok()?;
+    if content_hash(&bytes) != source_hash {
+        return None;
+    }
+    String::from_utf8(bytes).ok()
+}
+
+
+// ====================================================
+//  Planning
+// ====================================================
+pub fn plan_edit(prior_doc: Option<&str>, prior_source: Option<&str>, current: &str, label: &str, max_diff_lines: usize) -> EditPlan {
+
+    // Decides between updating the previous document and regenerating it.
+    //
+    // # Description
+    // An edit needs the previous document and the notebook content it was made
+    // from; the texts compared are the notebook as prepared for the prompt
+    // (after secret and PII redaction), so the diff never leaks what the
+    // prompt would not. The run falls back to full regeneration when either is
+    // missing, when nothing changed, or when the diff has more than
+    // `max_diff_lines` added and removed lines.
+
+    let Some(prior_doc) = prior_doc.map(|doc| split_front_matter(doc).1.trim()).filter(|doc| !doc.is_empty()) else {
+        return EditPlan::Full("no previous document".to_string());
+    };
+    let Some(prior_source) = prior_source else {
+        return EditPlan::Full("the notebook content of the previous document is not stored".to_string());
+    };
+    let Some(diff) = unified_diff(prior_source, current, label) else {
+        return EditPlan::Full("the notebook changed too much to diff".to_string());
+    };
+    let changed = changed_lines(&diff);
+    if changed == 0 {
+        return EditPlan::Full("the notebook content is unchanged".to_string());
+    }
+    if changed > max_diff_lines {
+        return EditPlan::Full(format!("the diff has {changed} changed lines, over {max_diff_lines}"));
+    }
+    EditPlan::Edit { prior_doc: prior_doc.to_string(), diff }
+}
+
+
+pub fn edit_instruction(prior_doc: &str, diff: &str) -> String {
+
+    // Returns the instruction appended to the prompt in edit mode.
+
+    format!(
+        "Dit notebook is gewijzigd sinds de vorige documentatie. Hieronder staan de vorige documentatie \
+         en een unified diff van de wijzigingen in het notebook. Werk alleen de secties bij die door de \
+         wijzigingen geraakt worden en neem alle andere secties woordelijk over, met dezelfde kopjes, \
+         volgorde en opmaak. Geef het volledige bijgewerkte document terug.\n\n\
+         ----- VORIGE DOCUMENTATIE -----\n{prior_doc}\n----- EINDE VORIGE DOCUMENTATIE -----\n\n\
+         ----- WIJZIGINGEN IN HET NOTEBOOK -----\n{diff}----- EINDE WIJZIGINGEN -----"
+    )
+}
+
+
+pub fn section_instruction(doc: &str, title: &str) -> String {
+
+    // Returns the instruction appended to the prompt for `--section`.
+
+    format!(
+        "Hieronder staat de huidige documentatie van dit notebook. Herschrijf alleen de sectie \
+         `## {title}` op basis van het notebook; de andere secties blijven ongewijzigd en horen \
+         niet in je antwoord. Geef alleen die sectie terug, beginnend met het kopje `## {title}` \
+         en met eventuele subkopjes.\n\n\
+         ----- HUIDIGE DOCUMENTATIE -----\n{doc}\n----- EINDE HUIDIGE DOCUMENTATIE -----"
+    )
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbered(lines: std::ops::Range<usize>) -> String {
+        lines.map(|i| format!("line {i}\n")).collect()
+    }
+
+
+    #[test]
+    fn diffs_lines_around_a_common_head_and_tail() {
+        let diff = line_diff("a\nb\nc\nd\n", "a\nx\nc\nd\ne\n").unwrap();
+        assert_eq!(diff, [
+            DiffLine::Same("a"),
+            DiffLine::Removed("b"),
+            DiffLine::Added("x"),
+            DiffLine::Same("c"),
+            DiffLine::Same("d"),
+            DiffLine::Added("e"),
+        ]);
+        assert_eq!(line_diff("a\nb\n", "a\nb\n").unwrap(), [DiffLine::Same("a"), DiffLine::Same("b")]);
+    }
+
+
+    #[test]
+    fn renders_a_unified_diff_with_context() {
+        let old = numbered(1..11);
+        let new = old.replace("line 5\n", "line five\n");
+        assert_eq!(
+            unified_diff(&old, &new, "nb.py").unwrap(),
+            "--- a/nb.py\n+++ b/nb.py\n@@ -2,7 +2,7 @@\n line 2\n line 3\n line 4\n-line 5\n+line five\n line 6\n line 7\n line 8\n"
+        );
+        assert_eq!(unified_diff(&old, &old, "nb.py").unwrap(), "");
+    }
+
+
+    #[test]
+    fn splits_distant_changes_into_hunks_and_merges_close_ones() {
+        let old = numbered(1..31);
+        let distant = old.replace("line 2\n", "two\n").replace("line 25\n", "twenty-five\n");
+        let diff = unified_diff(&old, &distant, "nb.py").unwrap();
+        assert_eq!(diff.matches("@@ -").count(), 2);
+        assert!(diff.contains("@@ -1,5 +1,5 @@") && diff.contains("@@ -22,7 +22,7 @@"), "{diff}");
+
+        let close = old.replace("line 2\n", "two\n").replace("line 8\n", "eight\n");
+        let diff = unified_diff(&old, &close, "nb.py").unwrap();
+        assert_eq!(diff.matches("@@ -").count(), 1);
+        assert!(diff.contains("@@ -1,11 +1,11 @@"), "{diff}");
+        assert_eq!(changed_lines(&diff), 4);
+    }
+
+
+    #[test]
+    fn an_insertion_into_an_empty_text_has_an_empty_old_range() {
+        let diff = unified_diff("", "a\nb\n", "nb.py").unwrap();
+        assert_eq!(diff, "--- a/nb.py\n+++ b/nb.py\n@@ -0,0 +1,2 @@\n+a\n+b\n");
+    }
+
+
+    #[test]
+    fn gives_up_when_the_changed_middle_is_too_large() {
+        let old = numbered(0..2001);
+        let new = numbered(5000..7001);
+        assert!(line_diff(&old, &new).is_none());
+        assert!(unified_diff(&old, &new, "nb.py").is_none());
+
+        let mut grown = numbered(0..100_000);
+        grown.push_str("tail\n");
+        assert!(line_diff(&numbered(0..100_000), &grown).is_some());
+    }
+
+
+    #[test]
+    fn plans_an_edit_for_a_small_change() {
+        let old = numbered(1..11);
+        let new = old.replace("line 5\n", "line five\n");
+        let plan = plan_edit(Some("---\ndoxcer_source_hash: x\n---\n# Doc\n"), Some(&old), &new, "nb.py", 2);
+        let EditPlan::Edit { prior_doc, diff } = plan else {
+            panic!("expected an edit, got {plan:?}");
+        };
+        assert_eq!(prior_doc, "# Doc");
+        assert_eq!(changed_lines(&diff), 2);
+    }
+
+
+    #[test]
+    fn falls_back_to_full_regeneration() {
+        let old = numbered(1..11);
+        let new = old.replace("line 5\n", "line five\n");
+        let full = |plan: EditPlan| match plan {
+            EditPlan::Full(reason) => reason,
+            EditPlan::Edit { .. } => panic!("expected a full regeneration"),
+        };
+        assert_eq!(full(plan_edit(None, Some(&old), &new, "nb.py", 400)), "no previous document");
+        assert_eq!(full(plan_edit(Some("---\na: b\n---\n \n"), Some(&old), &new, "nb.py", 400)), "no previous document");
+        assert_eq!(full(plan_edit(Some("# Doc"), None, &new, "nb.py", 400)), "the notebook content of the previous document is not stored");
+        assert_eq!(full(plan_edit(Some("# Doc"), Some(&old), &old, "nb.py", 400)), "the notebook content is unchanged");
+        assert_eq!(full(plan_edit(Some("# Doc"), Some(&old), &new, "nb.py", 1)), "the diff has 2 changed lines, over 1");
+        assert_eq!(
+            full(plan_edit(Some("# Doc"), Some(&numbered(0..2001)), &numbered(5000..7001), "nb.py", 400)),
+            "the notebook changed too much to diff"
+        );
+    }
+
+
+    #[test]
+    fn snapshots_are_only_loaded_for_their_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let hash = content_hash(b"print(1)\n");
+        let path = store_snapshot(dir.path(), &hash, "print(1)\n").unwrap();
+        assert_eq!(path, snapshot_path(dir.path(), &hash));
+        assert_eq!(load_snapshot(&path, &hash).as_deref(), Some("print(1)\n"));
+
+        fs::write(&path, "print(2)\n").unwrap();
+        assert_eq!(load_snapshot(&path, &hash), None);
+        assert_eq!(load_snapshot(&dir.path().join("missing.txt"), &hash), None);
+    }
+}
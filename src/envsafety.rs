@@ -0,0 +1,361 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// External Libraries
+use ignore::Match;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Permissions
+// ====================================================
+#[cfg(unix)]
+pub fn check_env_permissions(path: &Path) -> Result<(), DoxcerError> {
+
+    // Checks that a `.env` file is not readable by group or others.
+    //
+    // # Returns
+    // * `Ok(())` when only the owner can read the file.
+    // * `Err(DoxcerError)` with the mode and a `chmod 600` suggestion otherwise.
+
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(path)
+        .map_err(|e| DoxcerError::Config(format!("Failed to read the permissions of {}: {e}", path.display())))?
+        .permissions()
+        .mode();
+    if mode & 0o044 == 0 {
+        return Ok(());
+    }
+    Err(DoxcerError::Config(format!(
+        "{} is readable by group/others (mode {:o}); restrict it with `chmod 600 {}`",
+        path.display(),
+        mode & 0o777,
+        path.display()
+    )))
+}
+
+
+#[cfg(not(unix))]
+pub fn check_env_permissions(_path: &Path) -> Result<(), DoxcerError> {
+
+    // Windows has no group/other mode bits; access is governed by ACLs, which
+    // this check does not inspect.
+
+    Ok(())
+}
+
+
+// ====================================================
+//  Git Ignore Coverage
+// ====================================================
+pub fn find_repo_root(path: &Path) -> Option<PathBuf> {
+
+    // Returns the nearest ancestor of `path` that contains a `.git` directory.
+
+    let path = fs::canonicalize(path).ok()?;
+    path.ancestors().skip(1).find(|a| a.join(".git").is_dir()).map(Path::to_path_buf)
+}
+
+
+pub fn check_env_gitignored(path: &Path) -> Result<(), DoxcerError> {
+
+    // Checks that the repository's ignore rules cover a `.env` file.
+    //
+    // # Description
+    // Only applies when the file lives in a git repository. The `.gitignore`
+    // files from the file's folder up to the repository root are consulted,
+    // deepest first (so `!.env` in a subfolder re-includes it), followed by
+    // `.git/info/exclude`.
+    //
+    // # Returns
+    // * `Ok(())` when the file is ignored or not inside a repository.
+    // * `Err(DoxcerError)` naming the path to add to `.gitignore` otherwise.
+
+    let Some(root) = find_repo_root(path) else {
+        return Ok(());
+    };
+    let path = fs::canonicalize(path).map_err(|e| DoxcerError::Config(format!("Failed to resolve {}: {e}", path.display())))?;
+
+    let mut matchers = Vec::new();
+    for dir in path.ancestors().skip(1) {
+        matchers.extend(matcher(dir, &dir.join(".gitignore"))?);
+        if dir == root {
+            break;
+        }
+    }
+    matchers.extend(matcher(&root, &root.join(".git").join("info").join("exclude"))?);
+
+    for matcher in &matchers {
+        match matcher.matched_path_or_any_parents(&path, false) {
+            Match::Ignore(_) => return Ok(()),
+            Match::Whitelist(_) => break,
+            Match::None => {}
+        }
+    }
+    let relative = path.strip_prefix(&root).unwrap_or(&path);
+    Err(DoxcerError::Config(format!(
+        "{} is not covered by .gitignore in {}; add `{}` so it is never committed",
+        path.display(),
+        root.display(),
+        relative.display()
+    )))
+}
+
+
+fn matcher(dir: &Path, file: &Path) -> Result<Option<Gitignore>, DoxcerError> {
+
+    // Loads one ignore file with `dir` as the root its patterns are relative to.
+
+    if !file.is_file() {
+        return Ok(None);
+    }
+    let mut builder = GitignoreBuilder::new(dir);
+    if let Some(e) = builder.add(file) {
+        return Err(DoxcerError::Config(format!("Invalid {}: {e}", file.display())));
+    }
+    builder.build().map(Some).map_err(|e| DoxcerError::Config(format!("Invalid {}: {e}", file.display())))
+}
+
+
+// ====================================================
+//  Combined
+// ====================================================
+pub fn env_safety_problems(path: &Path) -> Vec<String> {
+
+    // Runs every `.env` safety check and returns the problems found.
+
+    [check_env_permissions(path), check_env_gitignored(path)]
+        .into_iter()
+        .filter_map(Result::err)
+        .map(|e| e.to_string())
+        .collect()
+}
+
+
+// ====================================================
+//  Writing
+// ====================================================
+fn env_line_sets(line: &str, name: &str) -> bool {
+
+    // Returns whether a `.env` line assigns `name` (`NAME=...` or `export NAME=...`).
+
+    let line = line.trim_start();
+    let line = line.strip_prefix("export ").unwrap_or(line);
+    line.split_once('=').is_some_and(|(key, _)| key.trim() == name)
+}
+
+
+pub fn append_env_var(path: &Path, name: &str, value: &str) -> Result<(), DoxcerError> {
+
+    // Appends `NAME=value` to a `.env` file.
+    //
+    // # Description
+    // A missing file is created, on Unix with mode 600 so it passes
+    // [`check_env_permissions`]. A file that already sets `name` is left
+    // alone, so an existing key is never replaced by accident.
+    //
+    // # Returns
+    // * `Ok(())` once the line is written.
+    // * `Err(DoxcerError)` when the file already sets `name` or cannot be written.
+
+    let existing = match fs::read_to_string(path) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(DoxcerError::Config(format!("Failed to read {}: {e}", path.display()))),
+    };
+    if existing.as_deref().is_some_and(|text| text.lines().any(|line| env_line_sets(line, name))) {
+        return Err(DoxcerError::Config(format!("{} already sets {name}; remove that line first to replace it", path.display())));
+    }
+
+    let separator = match existing.as_deref() {
+        Some(text) if !text.is_empty() && !text.ends_with('\n') => "\n",
+        _ => "",
+    };
+    let mut options = fs::OpenOptions::new();
+    options.append(true).create(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| DoxcerError::Config(format!("Failed to create {}: {e}", parent.display())))?;
+    }
+    let mut file = options.open(path).map_err(|e| DoxcerError::Config(format!("Failed to open {}: {e}", path.display())))?;
+    writeln!(file, "{separator}{name}={value}").map_err(|e| DoxcerError::Config(format!("Failed to write {}: {e}", path.display())))
+}
+
+
+pub fn set_env_var(path: &Path, name: &str, value: &str) -> Result<bool, DoxcerError> {
+
+    // Sets `NAME=value` in a `.env` file, in place.
+    //
+    // # Description
+    // The first line that sets `name` is replaced and any later ones are
+    // dropped; every other line, comment and blank line is kept as it was.
+    // Without such a line the variable is appended (see [`append_env_var`]).
+    // The file is rewritten through a temporary sibling and a rename, with the
+    // permissions it had.
+    //
+    // # Returns
+    // * `Ok(true)` when an existing value was replaced, `Ok(false)` when the
+    //   variable was added.
+    // * `Err(DoxcerError)` when the file cannot be read or written.
+
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return append_env_var(path, name, value).map(|()| false),
+        Err(e) => return Err(DoxcerError::Input(format!("Failed to read {}: {e}", path.display()))),
+    };
+    if !text.lines().any(|line| env_line_sets(line, name)) {
+        return append_env_var(path, name, value).map(|()| false);
+    }
+
+    let mut replaced = false;
+    let mut out = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        if !env_line_sets(line, name) {
+            out.push_str(line);
+        } else if !replaced {
+            replaced = true;
+            let ending = if line.ends_with("\r\n") { "\r\n" } else { "\n" };
+            out.push_str(&format!("{name}={value}{ending}"));
+        }
+    }
+    let permissions = fs::metadata(path).map_err(|e| DoxcerError::Input(format!("Failed to read {}: {e}", path.display())))?.permissions();
+    let tmp = path.with_extension("partial");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(&tmp)
+        .and_then(|mut file| file.write_all(out.as_bytes()))
+        .map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", tmp.display())))?;
+    fs::set_permissions(&tmp, permissions).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", tmp.display())))?;
+    fs::rename(&tmp, path).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", path.display())))?;
+    Ok(true)
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo_with_env(gitignore: &str) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".gitignore"), gitignore).unwrap();
+        let env = dir.path().join(".env");
+        fs::write(&env, "KEY=value\n").unwrap();
+        (dir, env)
+    }
+
+
+    #[cfg(unix)]
+    #[test]
+    fn permissions_allow_only_the_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (_dir, env) = repo_with_env(".env\n");
+        fs::set_permissions(&env, fs::Permissions::from_mode(0o600)).unwrap();
+        assert!(check_env_permissions(&env).is_ok());
+
+        fs::set_permissions(&env, fs::Permissions::from_mode(0o640)).unwrap();
+        let err = check_env_permissions(&env).unwrap_err().to_string();
+        assert!(err.contains("mode 640") && err.contains("chmod 600"), "{err}");
+    }
+
+
+    #[test]
+    fn an_ignored_env_file_passes() {
+        let (_dir, env) = repo_with_env("target/\n.env\n");
+        assert!(check_env_gitignored(&env).is_ok());
+    }
+
+
+    #[test]
+    fn an_unignored_env_file_names_the_path_to_add() {
+        let (_dir, env) = repo_with_env("target/\n");
+        let err = check_env_gitignored(&env).unwrap_err().to_string();
+        assert!(err.contains("add `.env`"), "{err}");
+    }
+
+
+    #[test]
+    fn a_deeper_negation_reincludes_the_env_file() {
+        let (dir, _) = repo_with_env(".env\n");
+        let sub = dir.path().join("sub");
+        fs::create_dir(&sub).unwrap();
+        fs::write(sub.join(".gitignore"), "!.env\n").unwrap();
+        fs::write(sub.join(".env"), "KEY=value\n").unwrap();
+        let err = check_env_gitignored(&sub.join(".env")).unwrap_err().to_string();
+        assert!(err.contains("sub"), "{err}");
+    }
+
+
+    #[test]
+    fn git_info_exclude_counts_as_ignored() {
+        let (dir, env) = repo_with_env("");
+        fs::create_dir(dir.path().join(".git").join("info")).unwrap();
+        fs::write(dir.path().join(".git").join("info").join("exclude"), ".env\n").unwrap();
+        assert!(check_env_gitignored(&env).is_ok());
+    }
+
+
+    #[test]
+    fn files_outside_a_repository_are_not_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = dir.path().join(".env");
+        fs::write(&env, "KEY=value\n").unwrap();
+        assert!(find_repo_root(&env).is_none());
+        assert!(check_env_gitignored(&env).is_ok());
+    }
+
+
+    #[test]
+    fn appending_refuses_to_replace_an_existing_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = dir.path().join("nested").join(".env");
+        append_env_var(&env, "KEY", "one").unwrap();
+        append_env_var(&env, "OTHER", "two").unwrap();
+        assert_eq!(fs::read_to_string(&env).unwrap(), "KEY=one\nOTHER=two\n");
+        assert!(append_env_var(&env, "KEY", "three").unwrap_err().to_string().contains("already sets KEY"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&env).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+    }
+
+
+    #[test]
+    fn setting_replaces_the_first_assignment_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = dir.path().join(".env");
+        fs::write(&env, "# keys\r\nexport KEY=old\r\nOTHER=x\r\nKEY=dup\r\n").unwrap();
+        assert!(set_env_var(&env, "KEY", "new").unwrap());
+        assert_eq!(fs::read_to_string(&env).unwrap(), "# keys\r\nKEY=new\r\nOTHER=x\r\n");
+        assert!(!set_env_var(&env, "ADDED", "1").unwrap());
+        assert!(fs::read_to_string(&env).unwrap().ends_with("OTHER=x\r\nADDED=1\n"));
+    }
+}
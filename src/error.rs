@@ -0,0 +1,260 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+// The exit codes are a stable contract for scripts and orchestrators: codes are
+// only ever added, never renumbered.
+pub const EXIT_CODES: [(u8, &str, &str); 8] = [
+    (0, "success", "everything was generated or is up to date"),
+    (1, "incomplete", "a batch partly failed, or documents are stale or missing"),
+    (2, "usage", "invalid command line: unknown flag, missing or malformed value"),
+    (3, "config", "configuration or environment problem (.env, keys, doxcer.toml, template)"),
+    (4, "input", "a notebook, document or state file could not be read or written"),
+    (5, "provider", "the API request failed or returned an error; usually retryable"),
+    (6, "limit", "a size or budget limit was exceeded"),
+    (130, "interrupted", "the run was interrupted"),
+];
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+// Every error of the library and the binary. The first variants carry a
+// message and name the exit-code category it falls in; the environment,
+// secret and template variants are typed, so callers can tell a missing
+// variable from a bad key, and all exit with the `config` code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DoxcerError {
+    Incomplete(String),
+    Usage(String),
+    Config(String),
+    Input(String),
+    Provider(String),
+    Limit(String),
+    TooLarge(String),
+    OverBudget(String),
+    Interrupted,
+    // A request given up on through its `CancelToken`, with the text of a
+    // streamed answer received until then.
+    Cancelled { partial: Option<String> },
+    MissingEnvVar(String),
+    NotUnicode(String),
+    MissingSecret { name: String, enc_name: String },
+    // `encrypted` is the `_ENC` variable the key was needed for, if any.
+    MissingFernetKey { key_name: String, encrypted: Option<String> },
+    // `key_name` is the variable the key came from, if known.
+    InvalidFernetKey { key_name: Option<String> },
+    DecryptionFailed,
+    InvalidUtf8,
+    EnvFileNotFound { searched: Vec<PathBuf> },
+    EnvFileUnreadable { path: PathBuf, reason: String },
+    TemplateNotFound { searched: Vec<PathBuf> },
+    // `--template` or `DOXCER_TEMPLATE_PATH` names a file that does not exist.
+    TemplateMissing { path: PathBuf },
+}
+
+impl fmt::Display for DoxcerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DoxcerError::Incomplete(m)
+            | DoxcerError::Usage(m)
+            | DoxcerError::Config(m)
+            | DoxcerError::Input(m)
+            | DoxcerError::Provider(m)
+            | DoxcerError::Limit(m)
+            | DoxcerError::TooLarge(m)
+            | DoxcerError::OverBudget(m) => write!(f, "{m}"),
+            DoxcerError::Interrupted => write!(f, "interrupted"),
+            DoxcerError::Cancelled { partial: Some(text) } => write!(f, "cancelled after {} characters of the answer", text.chars().count()),
+            DoxcerError::Cancelled { partial: None } => write!(f, "cancelled"),
+            DoxcerError::MissingEnvVar(var) => write!(f, "Missing required env var: {var}"),
+            DoxcerError::NotUnicode(var) => write!(f, "{var} contains non-unicode data"),
+            DoxcerError::MissingSecret { name, enc_name } => write!(f, "Neither {name} nor {enc_name} found in environment"),
+            DoxcerError::MissingFernetKey { key_name, encrypted: Some(_) } => write!(f, "Missing {key_name} for Fernet decryption"),
+            DoxcerError::MissingFernetKey { key_name, encrypted: None } => write!(f, "Missing {key_name}"),
+            DoxcerError::InvalidFernetKey { key_name: Some(key_name) } => write!(f, "{key_name} is not a valid Fernet key"),
+            DoxcerError::InvalidFernetKey { key_name: None } => write!(f, "Invalid Fernet key"),
+            DoxcerError::DecryptionFailed => write!(f, "Decryption failed"),
+            DoxcerError::InvalidUtf8 => write!(f, "Decrypted bytes were not valid UTF-8"),
+            DoxcerError::EnvFileNotFound { searched } => {
+                let searched: Vec<String> = searched.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "Could not find a .env file. Searched:\n  - {}", searched.join("\n  - "))
+            }
+            DoxcerError::EnvFileUnreadable { path, reason } => write!(f, "Failed to load .env at {}: {reason}", path.display()),
+            DoxcerError::TemplateNotFound { searched } => {
+                let searched: Vec<String> = searched.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "Could not find the prompt template. Searched:\n  - {}", searched.join("\n  - "))
+            }
+            DoxcerError::TemplateMissing { path } => write!(f, "Template {} does not exist", path.display()),
+        }
+    }
+}
+
+impl Error for DoxcerError {}
+
+impl From<&DoxcerError> for ExitCode {
+    fn from(error: &DoxcerError) -> ExitCode {
+        ExitCode::from(error.exit_code())
+    }
+}
+
+
+// ====================================================
+//  Exit Codes
+// ====================================================
+impl DoxcerError {
+
+    pub fn exit_code(&self) -> u8 {
+
+        // Maps an error to its exit code, see `EXIT_CODES`.
+
+        match self {
+            DoxcerError::Incomplete(_) => 1,
+            DoxcerError::Usage(_) => 2,
+            DoxcerError::Config(_) => 3,
+            DoxcerError::Input(_) => 4,
+            DoxcerError::Provider(_) => 5,
+            DoxcerError::Limit(_) | DoxcerError::TooLarge(_) | DoxcerError::OverBudget(_) => 6,
+            DoxcerError::Interrupted | DoxcerError::Cancelled { .. } => 130,
+            DoxcerError::MissingEnvVar(_)
+            | DoxcerError::NotUnicode(_)
+            | DoxcerError::MissingSecret { .. }
+            | DoxcerError::MissingFernetKey { .. }
+            | DoxcerError::InvalidFernetKey { .. }
+            | DoxcerError::DecryptionFailed
+            | DoxcerError::InvalidUtf8
+            | DoxcerError::EnvFileNotFound { .. }
+            | DoxcerError::EnvFileUnreadable { .. }
+            | DoxcerError::TemplateNotFound { .. }
+            | DoxcerError::TemplateMissing { .. } => 3,
+        }
+    }
+
+
+    pub fn from_exit_code(code: i32, message: impl Into<String>) -> DoxcerError {
+
+        // Returns an error with the given exit code, e.g. to pass on the status
+        // of a doxcer child process. Unknown codes count as `incomplete`.
+
+        let message = message.into();
+        match code {
+            2 => DoxcerError::Usage(message),
+            3 => DoxcerError::Config(message),
+            4 => DoxcerError::Input(message),
+            5 => DoxcerError::Provider(message),
+            6 => DoxcerError::Limit(message),
+            130 => DoxcerError::Interrupted,
+            _ => DoxcerError::Incomplete(message),
+        }
+    }
+
+
+    pub fn kind(&self) -> &'static str {
+
+        // Returns the name of the error's kind (`provider`, `config`, ...), as
+        // listed in `EXIT_CODES`.
+
+        let code = self.exit_code();
+        EXIT_CODES.iter().find(|(c, _, _)| *c == code).map_or("unknown", |(_, name, _)| name)
+    }
+
+
+    pub fn with_message(&self, message: impl Into<String>) -> DoxcerError {
+
+        // Returns an error of the same kind with another message, e.g. to report
+        // a batch in which every document failed for the same reason. The typed
+        // environment errors become `Config`, their exit-code category.
+
+        let message = message.into();
+        match self {
+            DoxcerError::Incomplete(_) => DoxcerError::Incomplete(message),
+            DoxcerError::Usage(_) => DoxcerError::Usage(message),
+            DoxcerError::Config(_) => DoxcerError::Config(message),
+            DoxcerError::Input(_) => DoxcerError::Input(message),
+            DoxcerError::Provider(_) => DoxcerError::Provider(message),
+            DoxcerError::Limit(_) => DoxcerError::Limit(message),
+            DoxcerError::TooLarge(_) => DoxcerError::TooLarge(message),
+            DoxcerError::OverBudget(_) => DoxcerError::OverBudget(message),
+            DoxcerError::Interrupted => DoxcerError::Interrupted,
+            DoxcerError::Cancelled { partial } => DoxcerError::Cancelled { partial: partial.clone() },
+            _ => DoxcerError::Config(message),
+        }
+    }
+
+
+    pub fn same_kind(&self, other: &DoxcerError) -> bool {
+
+        // Whether two errors are of the same kind; the typed environment errors
+        // all count as `Config`.
+
+        mem::discriminant(&self.with_message("")) == mem::discriminant(&other.with_message(""))
+    }
+}
+
+
+pub fn render_exit_codes() -> String {
+
+    // Renders the exit-code table for `doxcer --explain-exit-codes`.
+
+    let mut out = String::from("Exit codes:\n");
+    for (code, name, description) in EXIT_CODES {
+        out.push_str(&format!("  {code:>3}  {name:<12} {description}\n"));
+    }
+    out
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_listed_code_round_trips_through_from_exit_code() {
+        for (code, name, _) in EXIT_CODES.iter().filter(|(code, _, _)| *code != 0) {
+            let error = DoxcerError::from_exit_code(i32::from(*code), "child failed");
+            assert_eq!(error.exit_code(), *code);
+            assert_eq!(error.kind(), *name);
+        }
+        assert_eq!(DoxcerError::from_exit_code(101, "panicked").exit_code(), 1);
+    }
+
+
+    #[test]
+    fn interruption_and_typed_errors_map_to_their_codes() {
+        assert_eq!(DoxcerError::Interrupted.exit_code(), 130);
+        assert_eq!(DoxcerError::Interrupted.kind(), "interrupted");
+        assert_eq!(DoxcerError::Cancelled { partial: Some("# Ord".to_string()) }.exit_code(), 130);
+        assert_eq!(DoxcerError::Cancelled { partial: Some("# Ord".to_string()) }.to_string(), "cancelled after 5 characters of the answer");
+        assert_eq!(DoxcerError::MissingEnvVar("OPENAI_API_KEY".to_string()).kind(), "config");
+        assert!(DoxcerError::DecryptionFailed.same_kind(&DoxcerError::Config(String::new())));
+        assert!(!DoxcerError::TooLarge(String::new()).same_kind(&DoxcerError::Limit(String::new())));
+    }
+
+
+    #[test]
+    fn missing_key_errors_keep_their_original_wording() {
+        let secret = DoxcerError::MissingSecret { name: "OPENAI_API_KEY".to_string(), enc_name: "OPENAI_API_KEY_ENC".to_string() };
+        assert_eq!(secret.to_string(), "Neither OPENAI_API_KEY nor OPENAI_API_KEY_ENC found in environment");
+        let key = |encrypted: Option<&str>| DoxcerError::MissingFernetKey { key_name: "ENCRYPTION_PASSWORD".to_string(), encrypted: encrypted.map(str::to_string) };
+        assert_eq!(key(Some("OPENAI_API_KEY_ENC")).to_string(), "Missing ENCRYPTION_PASSWORD for Fernet decryption");
+        assert_eq!(key(None).to_string(), "Missing ENCRYPTION_PASSWORD");
+        assert_eq!(DoxcerError::MissingEnvVar("FABRIC_TOKEN".to_string()).to_string(), "Missing required env var: FABRIC_TOKEN");
+    }
+}
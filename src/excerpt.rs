@@ -0,0 +1,446 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::path::Path;
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::notebook::{CellKind, MAGIC_PREFIX, MARKDOWN_MARKER, parse_ipynb};
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const EXCERPT_KEY: &str = "doxcer_excerpt";
+pub const DEFAULT_CONTEXT_CELLS: usize = 1;
+pub const CELL_MARKERS: [&str; 6] = ["# CELL ****", "# MARKDOWN ****", "# PARAMETERS CELL ****", "# COMMAND ----", "-- COMMAND ----", "# %%"];
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selector {
+    Lines(usize, usize),
+    Cells(usize, usize),
+    CellTitle(String),
+    // `--cells 3-10,15`: inclusive cell ranges, sorted and merged.
+    CellList(Vec<(usize, usize)>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cell {
+    pub index: usize,
+    pub title: Option<String>,
+    pub first_line: usize,
+    pub last_line: usize,
+    pub text: String,
+    // Markdown (or raw) cells carry prose, not code.
+    pub markdown: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Excerpt {
+    pub label: String,
+    pub slug: String,
+    pub text: String,
+}
+
+
+// ====================================================
+//  Selectors
+// ====================================================
+pub fn parse_range(value: &str) -> Result<Selector, DoxcerError> {
+
+    // Parses `--range <start>:<end>` (1-based, inclusive line numbers).
+
+    let invalid = || DoxcerError::Usage(format!("--range must be <start>:<end> with line numbers from 1, got {value:?}"));
+    let (start, end) = value.split_once(':').ok_or_else(invalid)?;
+    let start: usize = start.trim().parse().map_err(|_| invalid())?;
+    let end: usize = end.trim().parse().map_err(|_| invalid())?;
+    if start == 0 || start > end {
+        return Err(invalid());
+    }
+    Ok(Selector::Lines(start, end))
+}
+
+
+pub fn parse_cell(value: &str) -> Selector {
+
+    // Parses `--cell <index or title>`: `3`, `3-5`, or anything else as a title.
+
+    let value = value.trim();
+    if let Ok(index) = value.parse() {
+        return Selector::Cells(index, index);
+    }
+    if let Some((start, end)) = value.split_once('-')
+        && let (Ok(start), Ok(end)) = (start.trim().parse(), end.trim().parse())
+    {
+        return Selector::Cells(start, end);
+    }
+    Selector::CellTitle(value.to_string())
+}
+
+
+pub fn parse_cell_list(value: &str) -> Result<Selector, DoxcerError> {
+
+    // Parses `--cells <list>`: cell numbers and ranges separated by commas
+    // (`3-10,15`). Overlapping and adjacent ranges are merged.
+
+    let invalid = || DoxcerError::Usage(format!("--cells must be cell numbers or ranges from 1, such as 3-10,15, got {value:?}"));
+    let mut ranges = Vec::new();
+    for item in value.split(',').map(str::trim) {
+        let (start, end) = item.split_once('-').unwrap_or((item, item));
+        let start: usize = start.trim().parse().map_err(|_| invalid())?;
+        let end: usize = end.trim().parse().map_err(|_| invalid())?;
+        if start == 0 || start > end {
+            return Err(invalid());
+        }
+        ranges.push((start, end));
+    }
+    ranges.sort();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    Ok(Selector::CellList(merged))
+}
+
+
+fn cell_range_label(start: usize, end: usize) -> String {
+    match start == end {
+        true => start.to_string(),
+        false => format!("{start}-{end}"),
+    }
+}
+
+
+// ====================================================
+//  Cells
+// ====================================================
+pub fn split_cells(path: &Path, text: &str) -> Result<Vec<Cell>, DoxcerError> {
+
+    // Splits a notebook into its cells.
+    //
+    // # Description
+    // `.ipynb` notebooks are read from their JSON `cells`. Python sources are split
+    // at cell markers (Fabric `# CELL ****`/`# MARKDOWN ****`, Databricks
+    // `# COMMAND ----` or `-- COMMAND ----`, and `# %%`); a source without
+    // markers is one cell. Text before the first marker only counts as a cell
+    // when it contains code or `MAGIC` lines. A cell
+    // is titled by its first line when that is a comment or Markdown heading.
+
+    if path.extension().is_some_and(|e| e == "ipynb") {
+        return split_ipynb_cells(path, text);
+    }
+
+    let lines: Vec<&str> = text.lines().collect();
+    let mut starts: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| CELL_MARKERS.iter().any(|m| line.starts_with(m)))
+        .map(|(i, _)| i)
+        .collect();
+    let preamble_has_code = lines[..starts.first().copied().unwrap_or(lines.len())]
+        .iter()
+        .any(|l| {
+            let l = l.trim_start();
+            !l.is_empty() && (!(l.starts_with('#') || l.starts_with("--")) || l.starts_with(MAGIC_PREFIX) || l.starts_with("-- MAGIC"))
+        });
+    if starts.first() != Some(&0) && (starts.is_empty() || preamble_has_code) {
+        starts.insert(0, 0);
+    }
+
+    let mut cells = Vec::new();
+    for (i, start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(lines.len());
+        let body = &lines[*start..end];
+        let is_marker = body.first().is_some_and(|l| CELL_MARKERS.iter().any(|m| l.starts_with(m)));
+        let content = if is_marker { &body[1..] } else { body };
+        cells.push(Cell {
+            index: i + 1,
+            title: cell_title(content.iter().copied()),
+            first_line: start + 1,
+            last_line: end.max(start + 1),
+            text: body.join("\n"),
+            markdown: is_markdown_cell(body),
+        });
+    }
+    Ok(cells)
+}
+
+
+fn split_ipynb_cells(path: &Path, text: &str) -> Result<Vec<Cell>, DoxcerError> {
+
+    // Reads the cells of a Jupyter notebook, see `notebook::parse_ipynb`.
+
+    let notebook = parse_ipynb(text, false).map_err(|e| DoxcerError::Input(format!("Failed to parse notebook {}: {e}", path.display())))?;
+    Ok(notebook
+        .cells
+        .into_iter()
+        .map(|cell| Cell {
+            index: cell.index,
+            title: cell_title(cell.source.lines()),
+            first_line: 0,
+            last_line: 0,
+            markdown: matches!(cell.kind, CellKind::Markdown | CellKind::Raw),
+            text: cell.source,
+        })
+        .collect())
+}
+
+
+fn is_markdown_cell(body: &[&str]) -> bool {
+
+    // A `# MARKDOWN ****` cell or a Databricks cell starting with `%md`.
+
+    body.first().is_some_and(|l| l.starts_with(MARKDOWN_MARKER))
+        || body
+            .iter()
+            .map(|l| l.trim())
+            .find(|l| !l.is_empty() && !CELL_MARKERS.iter().any(|m| l.starts_with(m)))
+            .is_some_and(|l| {
+                let magic = l.strip_prefix(MAGIC_PREFIX).or_else(|| l.strip_prefix("-- MAGIC")).map(str::trim);
+                magic.is_some_and(|m| m == "%md" || m.starts_with("%md "))
+            })
+}
+
+
+fn cell_title<'a>(lines: impl Iterator<Item = &'a str>) -> Option<String> {
+
+    // Returns the first non-empty line without its `#` markers, if it is a
+    // comment or heading (not a Databricks `# MAGIC` line).
+
+    let first = lines.map(str::trim).find(|l| !l.is_empty())?;
+    if first.starts_with(MAGIC_PREFIX) {
+        return None;
+    }
+    let title = first.strip_prefix('#')?.trim_start_matches(['#', ' ']).trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+
+// ====================================================
+//  Excerpts
+// ====================================================
+pub fn select_excerpt(path: &Path, text: &str, selector: &Selector, context_cells: usize) -> Result<Excerpt, DoxcerError> {
+
+    // Cuts the selected lines or cells out of a notebook for documentation.
+    //
+    // # Description
+    // Up to `context_cells` cells before and after the selection are included,
+    // marked as context, and the text starts with a note telling the model it
+    // documents an excerpt, so it does not describe code it cannot see.
+    // Selections are checked against the actual line and cell counts.
+    //
+    // # Returns
+    // * `Ok(Excerpt)` with a label (`cells 3-5`), a file-name slug (`cells_3-5`)
+    //   and the prompt text.
+    // * `Err(DoxcerError)` when the selection is out of range or matches no cell.
+
+    let name = path.display();
+    let cells = split_cells(path, text)?;
+    let cell_count = cells.len();
+    let out_of_cells = |index: usize| {
+        DoxcerError::Usage(format!("cell {index} is out of range: {name} has {cell_count} cell(s) (1-{cell_count})"))
+    };
+
+    // Each block is `(first cell, last cell, text, Dutch label)`, 0-based.
+    let (blocks, label, slug, dutch) = match selector {
+        Selector::Lines(start, end) => {
+            if path.extension().is_some_and(|e| e == "ipynb") {
+                return Err(DoxcerError::Usage("--range needs a .py notebook; use --cell for .ipynb notebooks".to_string()));
+            }
+            let line_count = text.lines().count();
+            if *end > line_count {
+                return Err(DoxcerError::Usage(format!("--range {start}:{end} is out of range: {name} has {line_count} line(s)")));
+            }
+            let containing = |line: usize| {
+                cells.iter().position(|c| c.first_line <= line && line <= c.last_line).unwrap_or(0)
+            };
+            let fragment: Vec<&str> = text.lines().skip(start - 1).take(end - start + 1).collect();
+            let dutch = format!("regels {start}-{end}");
+            (vec![(containing(*start), containing(*end), fragment.join("\n"), dutch.clone())],
+             format!("lines {start}-{end}"), format!("lines_{start}-{end}"), dutch)
+        }
+        Selector::Cells(start, end) => {
+            if *start == 0 || start > end {
+                return Err(DoxcerError::Usage(format!("--cell {start}-{end} is not a valid cell range; cells are numbered from 1")));
+            }
+            if *end > cell_count {
+                return Err(out_of_cells(*end));
+            }
+            let fragment: Vec<&str> = cells[start - 1..*end].iter().map(|c| c.text.as_str()).collect();
+            let (label, slug, dutch) = if start == end {
+                (format!("cell {start}"), format!("cell_{start}"), format!("cel {start}"))
+            } else {
+                (format!("cells {start}-{end}"), format!("cells_{start}-{end}"), format!("cellen {start}-{end}"))
+            };
+            (vec![(start - 1, end - 1, fragment.join("\n"), dutch.clone())], label, slug, dutch)
+        }
+        Selector::CellTitle(title) => {
+            let wanted = title.to_lowercase();
+            let exact: Vec<&Cell> = cells.iter().filter(|c| c.title.as_deref().is_some_and(|t| t.to_lowercase() == wanted)).collect();
+            let matches = if exact.is_empty() {
+                cells.iter().filter(|c| c.title.as_deref().is_some_and(|t| t.to_lowercase().contains(&wanted))).collect()
+            } else {
+                exact
+            };
+            let cell = match matches.as_slice() {
+                [cell] => *cell,
+                [] => {
+                    let titles: Vec<String> = cells
+                        .iter()
+                        .filter_map(|c| c.title.as_ref().map(|t| format!("{} {t:?}", c.index)))
+                        .collect();
+                    return Err(DoxcerError::Usage(format!("no cell titled {title:?} in {name}; cell titles: {}", titles.join(", "))));
+                }
+                several => {
+                    let indices: Vec<String> = several.iter().map(|c| c.index.to_string()).collect();
+                    return Err(DoxcerError::Usage(format!("{title:?} matches cells {} in {name}; use the cell number", indices.join(", "))));
+                }
+            };
+            let i = cell.index;
+            (vec![(i - 1, i - 1, cell.text.clone(), format!("cel {i}"))], format!("cell {i}"), format!("cell_{i}"), format!("cel {i}"))
+        }
+        Selector::CellList(ranges) => {
+            if let Some((_, end)) = ranges.iter().find(|(_, end)| *end > cell_count) {
+                return Err(out_of_cells(*end));
+            }
+            let blocks: Vec<_> = ranges
+                .iter()
+                .map(|(start, end)| {
+                    let fragment: Vec<&str> = cells[start - 1..*end].iter().map(|c| c.text.as_str()).collect();
+                    let dutch = match start == end {
+                        true => format!("cel {start}"),
+                        false => format!("cellen {start}-{end}"),
+                    };
+                    (start - 1, end - 1, fragment.join("\n"), dutch)
+                })
+                .collect();
+            let labels: Vec<String> = ranges.iter().map(|(start, end)| cell_range_label(*start, *end)).collect();
+            let dutch = match labels.split_last() {
+                Some((last, rest)) if !rest.is_empty() => format!("cellen {} en {last}", rest.join(", ")),
+                _ => blocks[0].3.clone(),
+            };
+            (blocks, format!("cells {}", labels.join(",")), format!("cells_{}", labels.join("_")), dutch)
+        }
+    };
+
+    let context = |range: std::ops::Range<usize>| -> Vec<String> {
+        cells[range].iter().map(|c| format!("--- context: cel {} ---\n{}", c.index, c.text)).collect()
+    };
+    let mut parts = vec![format!(
+        "Let op: dit is een fragment van {name}, niet het hele notebook. Documenteer alleen {dutch} en \
+         verzin geen secties over code die hier niet staat. Cellen gemarkeerd als context staan erbij \
+         ter verduidelijking en hoeven niet gedocumenteerd te worden."
+    )];
+    // Context cells never repeat a selected cell or one shown already.
+    let mut shown = 0;
+    for (i, (first, last, fragment, block)) in blocks.iter().enumerate() {
+        let limit = blocks.get(i + 1).map_or(cell_count, |next| next.0);
+        parts.extend(context(first.saturating_sub(context_cells).max(shown)..*first));
+        parts.push(format!("--- fragment: {block} ---\n{fragment}"));
+        let after = (last + 1 + context_cells).min(limit);
+        parts.extend(context(last + 1..after));
+        shown = after;
+    }
+
+    Ok(Excerpt { label, slug, text: parts.join("\n\n") })
+}
+
+
+pub fn excerpt_file_name(path: &Path, excerpt: &Excerpt) -> String {
+
+    // Returns the suggested output file for an excerpt (`load_customers.cells_3-5.md`).
+
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    format!("{stem}.{}.md", excerpt.slug)
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTEBOOK: &str = "# CELL ********************\n# Load orders\ndf = spark.read.table('orders')\n\
+                            # CELL ********************\n# Clean\ndf = df.dropna()\n\
+                            # CELL ********************\n# Clean customers\nc = spark.read.table('customers')\n\
+                            # CELL ********************\ndf.write.saveAsTable('out')\n";
+
+    fn excerpt(selector: &Selector) -> Result<Excerpt, DoxcerError> {
+        select_excerpt(Path::new("load.py"), NOTEBOOK, selector, 1)
+    }
+
+
+    #[test]
+    fn parses_ranges_cells_and_cell_lists() {
+        assert_eq!(parse_range("3:7").unwrap(), Selector::Lines(3, 7));
+        assert_eq!(parse_range(" 2 : 2 ").unwrap(), Selector::Lines(2, 2));
+        for bad in ["7:3", "0:4", "3", "a:b", "3:"] {
+            assert!(matches!(parse_range(bad), Err(DoxcerError::Usage(_))), "{bad}");
+        }
+
+        assert_eq!(parse_cell("3"), Selector::Cells(3, 3));
+        assert_eq!(parse_cell("3-5"), Selector::Cells(3, 5));
+        assert_eq!(parse_cell(" Load orders "), Selector::CellTitle("Load orders".to_string()));
+        assert_eq!(parse_cell("3-x"), Selector::CellTitle("3-x".to_string()));
+
+        assert_eq!(parse_cell_list("15,3-10").unwrap(), Selector::CellList(vec![(3, 10), (15, 15)]));
+        assert_eq!(parse_cell_list("3-5,4-8,9").unwrap(), Selector::CellList(vec![(3, 9)]));
+        for bad in ["0", "5-3", "3,,4", "x"] {
+            assert!(matches!(parse_cell_list(bad), Err(DoxcerError::Usage(_))), "{bad}");
+        }
+    }
+
+
+    #[test]
+    fn selections_are_checked_against_the_notebook() {
+        let error = excerpt(&Selector::Cells(2, 9)).unwrap_err();
+        assert_eq!(error.to_string(), "cell 9 is out of range: load.py has 4 cell(s) (1-4)");
+        let error = excerpt(&Selector::Lines(2, 40)).unwrap_err();
+        assert_eq!(error.to_string(), "--range 2:40 is out of range: load.py has 11 line(s)");
+        assert!(excerpt(&Selector::Cells(0, 1)).unwrap_err().to_string().contains("cells are numbered from 1"));
+        assert!(excerpt(&Selector::CellList(vec![(1, 1), (5, 5)])).unwrap_err().to_string().starts_with("cell 5 is out of range"));
+
+        let error = excerpt(&Selector::CellTitle("ean".to_string())).unwrap_err();
+        assert_eq!(error.to_string(), "\"ean\" matches cells 2, 3 in load.py; use the cell number");
+        assert_eq!(excerpt(&Selector::CellTitle("clean".to_string())).unwrap().slug, "cell_2");
+        let error = excerpt(&Selector::CellTitle("publish".to_string())).unwrap_err();
+        assert!(error.to_string().starts_with("no cell titled \"publish\" in load.py; cell titles: 1 \"Load orders\""), "{error}");
+
+        let error = select_excerpt(Path::new("load.ipynb"), r#"{"cells": [], "nbformat": 4}"#, &Selector::Lines(1, 2), 1).unwrap_err();
+        assert!(error.to_string().starts_with("--range needs a .py notebook"));
+    }
+
+
+    #[test]
+    fn an_excerpt_is_labelled_and_carries_its_context() {
+        let cells = excerpt(&Selector::Cells(2, 3)).unwrap();
+        assert_eq!((cells.label.as_str(), cells.slug.as_str()), ("cells 2-3", "cells_2-3"));
+        assert!(cells.text.contains("Documenteer alleen cellen 2-3"), "{}", cells.text);
+        assert!(cells.text.contains("--- context: cel 1 ---") && cells.text.contains("--- context: cel 4 ---"));
+
+        let titled = excerpt(&Selector::CellTitle("LOAD orders".to_string())).unwrap();
+        assert_eq!(titled.slug, "cell_1");
+        let list = excerpt(&Selector::CellList(vec![(1, 1), (3, 4)])).unwrap();
+        assert_eq!((list.label.as_str(), list.slug.as_str()), ("cells 1,3-4", "cells_1_3-4"));
+        assert_eq!(list.text.matches("--- context: cel 2 ---").count(), 1);
+        let lines = excerpt(&Selector::Lines(5, 6)).unwrap();
+        assert_eq!(lines.slug, "lines_5-6");
+        assert!(lines.text.contains("--- fragment: regels 5-6 ---\n# Clean\ndf = df.dropna()"), "{}", lines.text);
+
+        assert_eq!(excerpt_file_name(Path::new("nb/load_customers.py"), &cells), "load_customers.cells_2-3.md");
+    }
+}
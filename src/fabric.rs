@@ -0,0 +1,351 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// External Libraries
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const FABRIC_API_BASE: &str = "https://api.fabric.microsoft.com/v1";
+pub const MAX_PAGES: usize = 5;
+pub const RECENT_RUNS: usize = 20;
+pub const RUN_STATS_KEY: &str = "doxcer_run_stats";
+pub const SUCCEEDED_STATUS: &str = "Completed";
+pub const FAILED_STATUS: &str = "Failed";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+// The HTTP side of the Fabric REST API: a GET returning the body. Implemented
+// over the shared client by the binary, and by fixtures in a mock.
+pub trait FabricApi {
+    fn get(&self, url: &str) -> Result<String, DoxcerError>;
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Page<T> {
+    value: Vec<T>,
+    continuation_uri: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FabricItem {
+    pub id: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobRun {
+    pub status: String,
+    pub start_time_utc: Option<String>,
+    pub end_time_utc: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunStats {
+    pub runs: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub median_seconds: Option<i64>,
+    pub last_success: Option<String>,
+    pub last_status: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunHistory {
+    Stats(RunStats),
+    Unavailable,
+}
+
+
+// ====================================================
+//  Fetching
+// ====================================================
+pub fn parse_page<T: DeserializeOwned>(json: &str) -> Result<(Vec<T>, Option<String>), DoxcerError> {
+
+    // Parses one page of a Fabric list response: `value` and, when there are
+    // more pages, `continuationUri`.
+
+    let page: Page<T> = serde_json::from_str(json).map_err(|e| DoxcerError::Provider(format!("unexpected Fabric API response: {e}")))?;
+    Ok((page.value, page.continuation_uri.filter(|u| !u.is_empty())))
+}
+
+
+fn fetch_all<T: DeserializeOwned>(api: &dyn FabricApi, url: &str) -> Result<Vec<T>, DoxcerError> {
+
+    // Follows `continuationUri` for at most `MAX_PAGES` pages.
+
+    let mut items = Vec::new();
+    let mut next = Some(url.to_string());
+    for _ in 0..MAX_PAGES {
+        let Some(url) = next.take() else {
+            break;
+        };
+        let (page, continuation) = parse_page(&api.get(&url)?)?;
+        items.extend(page);
+        next = continuation;
+    }
+    Ok(items)
+}
+
+
+pub fn list_notebooks(api: &dyn FabricApi, workspace: &str) -> Result<Vec<FabricItem>, DoxcerError> {
+
+    // Lists the notebooks of a workspace, to find an item by display name.
+
+    fetch_all(api, &format!("{FABRIC_API_BASE}/workspaces/{workspace}/items?type=Notebook"))
+}
+
+
+pub fn fetch_run_history(api: &dyn FabricApi, workspace: &str, item: &str) -> Result<Vec<JobRun>, DoxcerError> {
+
+    // Fetches the job instances (runs) of a notebook item, up to `MAX_PAGES` pages.
+
+    fetch_all(api, &format!("{FABRIC_API_BASE}/workspaces/{workspace}/items/{item}/jobs/instances"))
+}
+
+
+// ====================================================
+//  Statistics
+// ====================================================
+pub fn run_stats(runs: &[JobRun]) -> RunStats {
+
+    // Summarises the most recent `RECENT_RUNS` runs.
+    //
+    // # Description
+    // Runs are ordered by start time, newest first. The median duration is
+    // taken over completed runs that have both timestamps; the last success is
+    // the end time of the newest completed run.
+
+    let mut runs: Vec<&JobRun> = runs.iter().collect();
+    runs.sort_by(|a, b| b.start_time_utc.cmp(&a.start_time_utc));
+    runs.truncate(RECENT_RUNS);
+
+    let succeeded: Vec<&&JobRun> = runs.iter().filter(|r| r.status == SUCCEEDED_STATUS).collect();
+    let mut durations: Vec<i64> = succeeded
+        .iter()
+        .filter_map(|r| Some((parse_time(r.end_time_utc.as_deref()?)? - parse_time(r.start_time_utc.as_deref()?)?).num_seconds()))
+        .collect();
+    durations.sort_unstable();
+    RunStats {
+        runs: runs.len(),
+        succeeded: succeeded.len(),
+        failed: runs.iter().filter(|r| r.status == FAILED_STATUS).count(),
+        median_seconds: durations.get(durations.len() / 2).copied(),
+        last_success: succeeded
+            .first()
+            .and_then(|r| r.end_time_utc.as_deref())
+            .and_then(parse_time)
+            .map(|t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+        last_status: runs.first().map(|r| r.status.clone()),
+    }
+}
+
+
+fn parse_time(value: &str) -> Option<NaiveDateTime> {
+
+    // Parses a Fabric UTC timestamp (`2026-10-13T02:00:12.5743556`, with or
+    // without a trailing `Z`).
+
+    NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y-%m-%dT%H:%M:%S%.f").ok()
+}
+
+
+pub fn format_duration(seconds: i64) -> String {
+
+    // Renders a duration as `1h 4m`, `4m 12s` or `38s`.
+
+    match seconds {
+        s if s >= 3600 => format!("{}h {}m", s / 3600, s % 3600 / 60),
+        s if s >= 60 => format!("{}m {}s", s / 60, s % 60),
+        s => format!("{s}s"),
+    }
+}
+
+
+impl RunHistory {
+
+    pub fn render(&self) -> String {
+
+        // Renders `{{run_stats}}` for the prompt.
+
+        let RunHistory::Stats(stats) = self else {
+            return "Run-historie niet beschikbaar.".to_string();
+        };
+        if stats.runs == 0 {
+            return "Geen runs in de historie.".to_string();
+        }
+        let mut lines = vec![format!(
+            "- Recente runs: {} ({} geslaagd, {} mislukt)",
+            stats.runs, stats.succeeded, stats.failed
+        )];
+        if let Some(seconds) = stats.median_seconds {
+            lines.push(format!("- Mediane looptijd: {}", format_duration(seconds)));
+        }
+        lines.push(format!("- Laatst geslaagd: {}", stats.last_success.as_deref().unwrap_or("nooit")));
+        if let Some(status) = &stats.last_status {
+            lines.push(format!("- Status laatste run: {status}"));
+        }
+        lines.join("\n")
+    }
+
+
+    pub fn summary(&self) -> String {
+
+        // Renders the one-line `doxcer_run_stats` front-matter value.
+
+        let RunHistory::Stats(stats) = self else {
+            return "run history unavailable".to_string();
+        };
+        let mut parts = vec![format!("{} runs", stats.runs), format!("{} succeeded", stats.succeeded), format!("{} failed", stats.failed)];
+        if let Some(seconds) = stats.median_seconds {
+            parts.push(format!("median {}", format_duration(seconds)));
+        }
+        if let Some(last) = &stats.last_success {
+            parts.push(format!("last success {last}"));
+        }
+        parts.join(", ")
+    }
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+
+    const RUNS_URL: &str = "https://api.fabric.microsoft.com/v1/workspaces/ws/items/nb/jobs/instances";
+    const PAGE_2: &str = "https://api.fabric.microsoft.com/v1/workspaces/ws/items/nb/jobs/instances?continuationToken=2";
+
+    // Answers GETs from fixture pages and records the URLs it was asked for.
+    #[derive(Default)]
+    struct MockApi {
+        pages: BTreeMap<String, String>,
+        requested: RefCell<Vec<String>>,
+    }
+
+    impl MockApi {
+        fn with(pages: &[(&str, String)]) -> MockApi {
+            MockApi { pages: pages.iter().map(|(url, body)| (url.to_string(), body.clone())).collect(), ..Default::default() }
+        }
+    }
+
+    impl FabricApi for MockApi {
+        fn get(&self, url: &str) -> Result<String, DoxcerError> {
+            self.requested.borrow_mut().push(url.to_string());
+            self.pages.get(url).cloned().ok_or_else(|| DoxcerError::Provider(format!("HTTP 404 for {url}")))
+        }
+    }
+
+    fn run(status: &str, start: &str, end: Option<&str>) -> serde_json::Value {
+        serde_json::json!({ "id": start, "status": status, "startTimeUtc": start, "endTimeUtc": end })
+    }
+
+    fn page(runs: &[serde_json::Value], next: Option<&str>) -> String {
+        serde_json::json!({ "value": runs, "continuationUri": next, "continuationToken": next.map(|_| "2") }).to_string()
+    }
+
+    fn paginated() -> MockApi {
+        MockApi::with(&[
+            (RUNS_URL, page(&[
+                run(SUCCEEDED_STATUS, "2026-10-13T02:00:00.5743556", Some("2026-10-13T02:04:12.1")),
+                run(FAILED_STATUS, "2026-10-12T02:00:00", Some("2026-10-12T02:00:30")),
+            ], Some(PAGE_2))),
+            (PAGE_2, page(&[
+                run(SUCCEEDED_STATUS, "2026-10-11T02:00:00Z", Some("2026-10-11T02:10:00Z")),
+                run(SUCCEEDED_STATUS, "2026-10-14T02:00:00", Some("2026-10-14T02:01:00")),
+                run("InProgress", "2026-10-14T09:00:00", None),
+            ], None)),
+        ])
+    }
+
+
+    #[test]
+    fn follows_continuation_pages() {
+        let api = paginated();
+        let runs = fetch_run_history(&api, "ws", "nb").unwrap();
+        assert_eq!(runs.len(), 5);
+        assert_eq!(*api.requested.borrow(), [RUNS_URL, PAGE_2]);
+    }
+
+
+    #[test]
+    fn stops_after_max_pages() {
+        let looping = page(&[run(SUCCEEDED_STATUS, "2026-10-13T02:00:00", None)], Some(RUNS_URL));
+        let api = MockApi::with(&[(RUNS_URL, looping)]);
+        assert_eq!(fetch_run_history(&api, "ws", "nb").unwrap().len(), MAX_PAGES);
+        assert_eq!(api.requested.borrow().len(), MAX_PAGES);
+    }
+
+
+    #[test]
+    fn reports_api_and_parse_errors() {
+        let api = MockApi::with(&[(RUNS_URL, page(&[], Some(PAGE_2)))]);
+        assert_eq!(fetch_run_history(&api, "ws", "nb").unwrap_err().to_string(), format!("HTTP 404 for {PAGE_2}"));
+
+        let api = MockApi::with(&[(RUNS_URL, "{\"items\": []}".to_string())]);
+        assert!(fetch_run_history(&api, "ws", "nb").unwrap_err().to_string().starts_with("unexpected Fabric API response"));
+    }
+
+
+    #[test]
+    fn lists_notebooks_by_display_name() {
+        let url = format!("{FABRIC_API_BASE}/workspaces/ws/items?type=Notebook");
+        let body = serde_json::json!({ "value": [{ "id": "1", "displayName": "Load Sales", "type": "Notebook" }] }).to_string();
+        let items = list_notebooks(&MockApi::with(&[(&url, body)]), "ws").unwrap();
+        assert_eq!(items, [FabricItem { id: "1".to_string(), display_name: "Load Sales".to_string() }]);
+    }
+
+
+    #[test]
+    fn summarises_the_fetched_history() {
+        let stats = run_stats(&fetch_run_history(&paginated(), "ws", "nb").unwrap());
+        assert_eq!(stats, RunStats {
+            runs: 5,
+            succeeded: 3,
+            failed: 1,
+            median_seconds: Some(251),
+            last_success: Some("2026-10-14T02:01:00Z".to_string()),
+            last_status: Some("InProgress".to_string()),
+        });
+
+        let history = RunHistory::Stats(stats);
+        assert_eq!(
+            history.summary(),
+            "5 runs, 3 succeeded, 1 failed, median 4m 11s, last success 2026-10-14T02:01:00Z"
+        );
+        assert_eq!(
+            history.render(),
+            "- Recente runs: 5 (3 geslaagd, 1 mislukt)\n- Mediane looptijd: 4m 11s\n\
+             - Laatst geslaagd: 2026-10-14T02:01:00Z\n- Status laatste run: InProgress"
+        );
+        assert_eq!(RunHistory::Unavailable.summary(), "run history unavailable");
+    }
+
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(38), "38s");
+        assert_eq!(format_duration(252), "4m 12s");
+        assert_eq!(format_duration(3840), "1h 4m");
+    }
+}
@@ -0,0 +1,150 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// External Libraries
+use serde::{Deserialize, Serialize};
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::manifest::manifest_key;
+use crate::timefmt::now_rfc3339;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const DEFAULT_FAILED_PATH: &str = ".doxcer/failed.json";
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FailureLog {
+    #[serde(default)]
+    pub files: BTreeMap<String, FailedFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailedFile {
+    pub source_hash: String,
+    pub category: String,
+    pub error: String,
+    pub attempts: u32,
+    pub last_attempt: String,
+    #[serde(default)]
+    pub quarantined: bool,
+}
+
+
+// ====================================================
+//  Failure Log IO
+// ====================================================
+impl FailureLog {
+
+    pub fn load(path: &Path) -> Result<FailureLog, DoxcerError> {
+
+        // Loads the failure log; a missing file means nothing has failed.
+
+        if !path.exists() {
+            return Ok(FailureLog::default());
+        }
+        let raw = fs::read_to_string(path)
+            .map_err(|e| DoxcerError::Input(format!("Failed to read {}: {e}", path.display())))?;
+        serde_json::from_str(&raw)
+            .map_err(|e| DoxcerError::Input(format!("Failed to parse {}: {e}", path.display())))
+    }
+
+
+    pub fn save(&self, path: &Path) -> Result<(), DoxcerError> {
+
+        // Writes the failure log, creating its parent directory if needed.
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .map_err(|e| DoxcerError::Input(format!("Failed to create {}: {e}", parent.display())))?;
+        }
+        let raw = serde_json::to_string_pretty(self)
+            .map_err(|e| DoxcerError::Input(format!("Failed to serialize failure log: {e}")))?;
+        fs::write(path, raw + "\n")
+            .map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", path.display())))
+    }
+
+
+    pub fn get(&self, notebook: &Path) -> Option<&FailedFile> {
+
+        // Returns the failure recorded for a notebook, if any.
+
+        self.files.get(&manifest_key(notebook))
+    }
+
+
+    pub fn paths(&self) -> Vec<PathBuf> {
+
+        // Returns the notebooks that are recorded as failed (`--retry-failed`).
+
+        self.files.keys().map(PathBuf::from).collect()
+    }
+
+
+    pub fn record_failure(&mut self, notebook: &Path, source_hash: &str, category: &str, error: &str, max_attempts: u32) -> bool {
+
+        // Records a failed attempt and quarantines the notebook after `max_attempts`.
+        //
+        // # Description
+        // Attempts are counted per content: when the notebook changed since its
+        // last failure, the count starts over and a quarantine is lifted, since
+        // the edit may well have fixed it.
+        //
+        // # Returns
+        // * `true` when this attempt put the notebook in quarantine.
+
+        let entry = self.files.entry(manifest_key(notebook)).or_insert_with(|| FailedFile {
+            source_hash: source_hash.to_string(),
+            category: String::new(),
+            error: String::new(),
+            attempts: 0,
+            last_attempt: String::new(),
+            quarantined: false,
+        });
+        if entry.source_hash != source_hash {
+            entry.source_hash = source_hash.to_string();
+            entry.attempts = 0;
+            entry.quarantined = false;
+        }
+        entry.attempts += 1;
+        entry.category = category.to_string();
+        entry.error = error.to_string();
+        entry.last_attempt = now_rfc3339();
+
+        let newly = !entry.quarantined && entry.attempts >= max_attempts;
+        entry.quarantined |= newly;
+        newly
+    }
+
+
+    pub fn record_success(&mut self, notebook: &Path) -> bool {
+
+        // Clears a notebook that now succeeded; returns whether it was recorded.
+
+        self.files.remove(&manifest_key(notebook)).is_some()
+    }
+
+
+    pub fn is_quarantined(&self, notebook: &Path, source_hash: &str) -> bool {
+
+        // Checks whether a notebook is quarantined for its current content.
+
+        self.get(notebook).is_some_and(|f| f.quarantined && f.source_hash == source_hash)
+    }
+}
@@ -0,0 +1,343 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// External Libraries
+use serde_json::{Map, Value, json};
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::frontmatter::{FrontMatterValue, parse_front_matter, split_front_matter};
+use crate::html::{escape_html, render_html};
+use crate::provenance::read_provenance;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const WRITE_FAILED_STATUS: &str = "failed-in-write";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OutputFormat {
+    Markdown,
+    Json,
+    Html,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonOut {
+    // A single `.json` file, for a run over one notebook.
+    File(PathBuf),
+    // A directory that mirrors the layout of the docs directory.
+    Dir(PathBuf),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputTargets {
+    pub formats: Vec<OutputFormat>,
+    pub docs_dir: PathBuf,
+    pub json_out: Option<JsonOut>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Artifact {
+    pub format: OutputFormat,
+    pub path: PathBuf,
+    pub result: Result<(), DoxcerError>,
+}
+
+impl OutputFormat {
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputFormat::Markdown => "md",
+            OutputFormat::Json => "json",
+            OutputFormat::Html => "html",
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = DoxcerError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Ok(OutputFormat::Markdown),
+            "json" => Ok(OutputFormat::Json),
+            "html" => Ok(OutputFormat::Html),
+            _ => Err(DoxcerError::Input(format!("expected md, json or html, got {value:?}"))),
+        }
+    }
+}
+
+impl FromStr for JsonOut {
+    type Err = DoxcerError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+
+        // Parses `--json-out`: a path ending in `.json` is a file, anything
+        // else a directory.
+
+        match value.trim() {
+            "" => Err(DoxcerError::Input("--json-out needs a file or directory".to_string())),
+            path if path.to_ascii_lowercase().ends_with(".json") => Ok(JsonOut::File(PathBuf::from(path))),
+            path => Ok(JsonOut::Dir(PathBuf::from(path))),
+        }
+    }
+}
+
+impl Default for OutputTargets {
+    fn default() -> OutputTargets {
+        OutputTargets { formats: vec![OutputFormat::Markdown], docs_dir: PathBuf::new(), json_out: None }
+    }
+}
+
+
+// ====================================================
+//  Selection
+// ====================================================
+pub fn parse_formats(value: &str) -> Result<Vec<OutputFormat>, DoxcerError> {
+
+    // Parses `--format md,json,html` into its formats, in the fixed order
+    // md, json, html and without duplicates.
+
+    let mut formats = value.split(',').map(str::parse).collect::<Result<Vec<OutputFormat>, DoxcerError>>()?;
+    formats.sort();
+    formats.dedup();
+    Ok(formats)
+}
+
+
+impl OutputTargets {
+
+    pub fn path(&self, format: OutputFormat, doc_path: &Path) -> PathBuf {
+
+        // Returns where a format of the document at `doc_path` is written.
+        //
+        // # Description
+        // Markdown goes to `doc_path` and HTML next to it. JSON goes next to it
+        // as well, unless `--json-out` names a file or a directory; a directory
+        // gets the document's path below the docs directory.
+
+        match (format, &self.json_out) {
+            (OutputFormat::Markdown, _) => doc_path.to_path_buf(),
+            (OutputFormat::Html, _) => doc_path.with_extension("html"),
+            (OutputFormat::Json, None) => doc_path.with_extension("json"),
+            (OutputFormat::Json, Some(JsonOut::File(path))) => path.clone(),
+            (OutputFormat::Json, Some(JsonOut::Dir(dir))) => {
+                let relative = doc_path.strip_prefix(&self.docs_dir).unwrap_or(doc_path);
+                dir.join(relative).with_extension("json")
+            }
+        }
+    }
+}
+
+
+// ====================================================
+//  Rendering
+// ====================================================
+pub fn render_format(format: OutputFormat, markdown: &str) -> Result<String, DoxcerError> {
+
+    // Renders a stamped document in one output format.
+    //
+    // # Description
+    // Every format is derived from the same Markdown, so they carry the same
+    // provenance: the front-matter for Markdown, the top-level fields of the
+    // JSON record, and `<meta>` tags (plus the front-matter block) in HTML.
+
+    match format {
+        OutputFormat::Markdown => Ok(markdown.to_string()),
+        OutputFormat::Json => render_json(markdown),
+        OutputFormat::Html => Ok(render_html_page(markdown)),
+    }
+}
+
+
+pub fn render_json(markdown: &str) -> Result<String, DoxcerError> {
+
+    // Renders the JSON record of a document: the provenance, all front-matter
+    // keys under `metadata` and the Markdown body.
+
+    let (front_matter, body) = split_front_matter(markdown);
+    let mut metadata = Map::new();
+    for entry in parse_front_matter(front_matter.unwrap_or_default())? {
+        let value = match entry.value {
+            FrontMatterValue::Scalar(value) => Value::String(value),
+            FrontMatterValue::List(items) => Value::from(items),
+        };
+        metadata.insert(entry.key, value);
+    }
+    let provenance = read_provenance(markdown);
+    let record = json!({
+        "source": provenance.as_ref().map(|p| p.source.clone()),
+        "source_hash": provenance.as_ref().map(|p| p.source_hash.clone()),
+        "model": provenance.as_ref().and_then(|p| p.model.clone()),
+        "generated_at": provenance.as_ref().and_then(|p| p.generated_at.clone()),
+        "metadata": metadata,
+        "markdown": body.trim_start_matches(['\n', '\r']),
+    });
+    serde_json::to_string_pretty(&record).map(|json| format!("{json}\n")).map_err(|e| DoxcerError::Input(format!("Failed to serialize the JSON record: {e}")))
+}
+
+
+pub fn render_html_page(markdown: &str) -> String {
+
+    // Renders a standalone HTML page, with the front-matter keys as `<meta>`
+    // tags and the first heading as its title.
+
+    let (front_matter, body) = split_front_matter(markdown);
+    let entries = parse_front_matter(front_matter.unwrap_or_default()).unwrap_or_default();
+    let title = body
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("# "))
+        .map_or_else(String::new, |title| title.trim().to_string());
+    let mut out = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str(&format!("<title>{}</title>\n", escape_html(&title)));
+    for entry in entries {
+        let value = match entry.value {
+            FrontMatterValue::Scalar(value) => value,
+            FrontMatterValue::List(items) => items.join(", "),
+        };
+        out.push_str(&format!("<meta name=\"{}\" content=\"{}\">\n", escape_html(&entry.key), escape_html(&value)));
+    }
+    out.push_str("</head>\n<body>\n");
+    out.push_str(&render_html(markdown));
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+
+// ====================================================
+//  Reporting
+// ====================================================
+pub fn failed_writes(artifacts: &[Artifact]) -> Result<(), DoxcerError> {
+
+    // Joins the errors of the writers that failed, one per format.
+
+    let failed: Vec<String> = artifacts
+        .iter()
+        .filter_map(|artifact| artifact.result.as_ref().err().map(|e| format!("{}: {e}", artifact.format)))
+        .collect();
+    if failed.is_empty() { Ok(()) } else { Err(DoxcerError::Input(failed.join("; "))) }
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provenance::{MODEL_KEY, Provenance, SOURCE_HASH_KEY, SOURCE_KEY, stamp_provenance};
+    use crate::timefmt::GENERATED_AT_KEY;
+
+    fn stamped() -> (String, Provenance) {
+        let provenance = Provenance {
+            source: "fabric/gold/dim_project_t.py".to_string(),
+            source_hash: "sha256:3f2a".to_string(),
+            model: Some("gpt-5-mini".to_string()),
+            generated_at: Some("2026-10-14T12:00:00Z".to_string()),
+        };
+        let markdown = stamp_provenance("# dim_project & co\n\nLoads <projects>.\n", &provenance);
+        (markdown, provenance)
+    }
+
+    fn meta<'a>(html: &'a str, name: &str) -> Option<&'a str> {
+        let tag = format!("<meta name=\"{name}\" content=\"");
+        html[html.find(&tag)? + tag.len()..].split('"').next()
+    }
+
+
+    #[test]
+    fn all_formats_share_the_same_provenance() {
+        let (markdown, provenance) = stamped();
+        let artifacts: Vec<String> = [OutputFormat::Markdown, OutputFormat::Json, OutputFormat::Html]
+            .into_iter()
+            .map(|format| render_format(format, &markdown).unwrap())
+            .collect();
+
+        assert_eq!(read_provenance(&artifacts[0]), Some(provenance.clone()));
+
+        let record: Value = serde_json::from_str(&artifacts[1]).unwrap();
+        assert_eq!(record["source"], provenance.source);
+        assert_eq!(record["source_hash"], provenance.source_hash);
+        assert_eq!(record["model"], "gpt-5-mini");
+        assert_eq!(record["generated_at"], "2026-10-14T12:00:00Z");
+        assert_eq!(record["metadata"][SOURCE_HASH_KEY], provenance.source_hash);
+
+        let html = &artifacts[2];
+        assert_eq!(meta(html, SOURCE_KEY), Some(provenance.source.as_str()));
+        assert_eq!(meta(html, SOURCE_HASH_KEY), Some(provenance.source_hash.as_str()));
+        assert_eq!(meta(html, MODEL_KEY), Some("gpt-5-mini"));
+        assert_eq!(meta(html, GENERATED_AT_KEY), Some("2026-10-14T12:00:00Z"));
+        assert!(html.contains("<title>dim_project &amp; co</title>"));
+    }
+
+
+    #[test]
+    fn json_record_carries_the_body_without_front_matter() {
+        let (markdown, _) = stamped();
+        let record: Value = serde_json::from_str(&render_json(&markdown).unwrap()).unwrap();
+        assert_eq!(record["markdown"], "# dim_project & co\n\nLoads <projects>.\n");
+    }
+
+
+    #[test]
+    fn unstamped_documents_have_no_provenance_in_json() {
+        let record: Value = serde_json::from_str(&render_json("# Plain\n").unwrap()).unwrap();
+        assert_eq!(record["source"], Value::Null);
+        assert_eq!(record["metadata"], json!({}));
+    }
+
+
+    #[test]
+    fn formats_are_parsed_in_a_fixed_order_without_duplicates() {
+        assert_eq!(parse_formats("html,md,json,md").unwrap(), [OutputFormat::Markdown, OutputFormat::Json, OutputFormat::Html]);
+        assert_eq!(parse_formats("Markdown").unwrap(), [OutputFormat::Markdown]);
+        assert_eq!(parse_formats("md,pdf").unwrap_err().to_string(), "expected md, json or html, got \"pdf\"");
+    }
+
+
+    #[test]
+    fn each_format_has_its_own_target() {
+        let doc = Path::new("docs/gold/dim_project.md");
+        let mut targets = OutputTargets { formats: parse_formats("md,json,html").unwrap(), docs_dir: PathBuf::from("docs"), json_out: None };
+        assert_eq!(targets.path(OutputFormat::Markdown, doc), doc);
+        assert_eq!(targets.path(OutputFormat::Html, doc), Path::new("docs/gold/dim_project.html"));
+        assert_eq!(targets.path(OutputFormat::Json, doc), Path::new("docs/gold/dim_project.json"));
+        targets.json_out = Some("catalog".parse().unwrap());
+        assert_eq!(targets.path(OutputFormat::Json, doc), Path::new("catalog/gold/dim_project.json"));
+        targets.json_out = Some("record.JSON".parse().unwrap());
+        assert_eq!(targets.path(OutputFormat::Json, doc), Path::new("record.JSON"));
+    }
+
+
+    #[test]
+    fn one_failed_writer_does_not_hide_the_others() {
+        let artifacts = [
+            Artifact { format: OutputFormat::Markdown, path: PathBuf::from("a.md"), result: Ok(()) },
+            Artifact { format: OutputFormat::Json, path: PathBuf::from("a.json"), result: Err(DoxcerError::Input("disk full".to_string())) },
+            Artifact { format: OutputFormat::Html, path: PathBuf::from("a.html"), result: Ok(()) },
+        ];
+        assert_eq!(failed_writes(&artifacts).unwrap_err().to_string(), "json: disk full");
+        assert!(failed_writes(&artifacts[..1]).is_ok());
+    }
+}
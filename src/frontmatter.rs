@@ -0,0 +1,227 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Front-matter Splitting
+// ====================================================
+pub fn split_front_matter(text: &str) -> (Option<&str>, &str) {
+
+    // Splits a document into its YAML front-matter block and the remaining body.
+    //
+    // # Description
+    // A front-matter block starts on the very first line with `---` and ends at the
+    // next line that consists of `---` only. Anything else is treated as body.
+    //
+    // # Parameters
+    // * `text` – The full document.
+    //
+    // # Returns
+    // * `(Some(front_matter), body)` when a complete block is present.
+    // * `(None, text)` when the document has no (or an unterminated) block.
+
+    let rest = match text.strip_prefix("---\n").or_else(|| text.strip_prefix("---\r\n")) {
+        Some(rest) => rest,
+        None => return (None, text),
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end_matches(['\r', '\n']) == "---" {
+            let front = &rest[..offset];
+            let body = &rest[offset + line.len()..];
+            return (Some(front), body);
+        }
+        offset += line.len();
+    }
+
+    (None, text)
+}
+
+
+// ====================================================
+//  Front-matter Values
+// ====================================================
+pub fn front_matter_value(front_matter: &str, key: &str) -> Option<String> {
+
+    // Looks up a top-level `key: value` pair inside a front-matter block.
+    //
+    // # Description
+    // Only flat scalar values are supported; surrounding single or double quotes
+    // are removed. Nested YAML is ignored.
+    //
+    // # Parameters
+    // * `front_matter` – The front-matter block without its `---` delimiters.
+    // * `key` – The key to look up.
+    //
+    // # Returns
+    // * `Some(String)` with the unquoted value if the key exists.
+    // * `None` otherwise.
+
+    front_matter.lines().find_map(|line| {
+        let (k, v) = line.split_once(':')?;
+        if k.trim() != key || line.starts_with([' ', '\t']) {
+            return None;
+        }
+        Some(unquote(v.trim()))
+    })
+}
+
+
+pub fn upsert_front_matter(text: &str, pairs: &[(&str, String)]) -> String {
+
+    // Sets the given keys in the document's front-matter, creating a block if needed.
+    //
+    // # Description
+    // Existing lines for the given keys are replaced in place; new keys are appended
+    // to the end of the block. Documents without front-matter get a fresh block
+    // prepended. The body is left untouched.
+    //
+    // # Parameters
+    // * `text` – The full document.
+    // * `pairs` – The key/value pairs to write.
+    //
+    // # Returns
+    // * The document with updated front-matter.
+
+    let (front, body) = split_front_matter(text);
+    let mut lines: Vec<String> = front
+        .map(|f| f.lines().map(str::to_string).collect())
+        .unwrap_or_default();
+
+    for (key, value) in pairs {
+        let rendered = format!("{key}: {}", quote(value));
+        let existing = lines.iter().position(|line| {
+            !line.starts_with([' ', '\t'])
+                && line.split_once(':').is_some_and(|(k, _)| k.trim() == *key)
+        });
+        match existing {
+            Some(i) => lines[i] = rendered,
+            None => lines.push(rendered),
+        }
+    }
+
+    let separator = if front.is_none() && !body.is_empty() { "\n" } else { "" };
+    format!("---\n{}\n---\n{separator}{body}", lines.join("\n"))
+}
+
+
+fn unquote(value: &str) -> String {
+
+    // Removes one pair of matching surrounding quotes from a scalar value.
+
+    if let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        return inner.replace("\\\"", "\"").replace("\\\\", "\\");
+    }
+    if let Some(inner) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        return inner.replace("''", "'");
+    }
+    value.to_string()
+}
+
+
+fn quote(value: &str) -> String {
+
+    // Quotes a scalar value when it would otherwise be ambiguous YAML.
+
+    let needs_quotes = value.is_empty()
+        || value.contains(": ")
+        || value.contains(" #")
+        || value.starts_with(['"', '\'', '[', '{', '&', '*', '!', '|', '>', '%', '@', '`', '#'])
+        || value != value.trim();
+    if needs_quotes {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+
+// ====================================================
+//  Structured Front-matter
+// ====================================================
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrontMatterValue {
+    Scalar(String),
+    List(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontMatterEntry {
+    pub key: String,
+    pub value: FrontMatterValue,
+    pub line: usize,
+}
+
+
+pub fn parse_front_matter(front_matter: &str) -> Result<Vec<FrontMatterEntry>, DoxcerError> {
+
+    // Parses a front-matter block into its top-level entries.
+    //
+    // # Description
+    // Supports the small YAML subset doxcer uses: `key: scalar`, inline lists
+    // (`key: [a, b]`) and block lists (`key:` followed by `  - item` lines).
+    // Blank lines and `#` comments are ignored.
+    //
+    // # Parameters
+    // * `front_matter` – The front-matter block without its `---` delimiters.
+    //
+    // # Returns
+    // * `Ok(Vec<FrontMatterEntry>)` in document order; `line` is 1-based and
+    //   counts from the opening `---`.
+    // * `Err(DoxcerError)` naming the offending line when the block is malformed.
+
+    let mut entries: Vec<FrontMatterEntry> = Vec::new();
+
+    for (index, line) in front_matter.lines().enumerate() {
+        let line_no = index + 2;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if let Some(item) = trimmed.strip_prefix('-') {
+            let item = unquote(item.trim());
+            let Some(last) = entries.last_mut() else {
+                return Err(DoxcerError::Input(format!("line {line_no}: list item without a key")));
+            };
+            match &mut last.value {
+                FrontMatterValue::List(items) => items.push(item),
+                FrontMatterValue::Scalar(v) if v.is_empty() => last.value = FrontMatterValue::List(vec![item]),
+                FrontMatterValue::Scalar(_) => {
+                    return Err(DoxcerError::Input(format!("line {line_no}: list item under scalar key `{}`", last.key)));
+                }
+            }
+            continue;
+        }
+
+        if line.starts_with([' ', '\t']) {
+            return Err(DoxcerError::Input(format!("line {line_no}: nested values are not supported")));
+        }
+
+        let Some((key, raw)) = line.split_once(':') else {
+            return Err(DoxcerError::Input(format!("line {line_no}: expected `key: value`")));
+        };
+        let raw = raw.trim();
+        let value = match raw.strip_prefix('[').and_then(|r| r.strip_suffix(']')) {
+            Some(inner) => FrontMatterValue::List(
+                inner
+                    .split(',')
+                    .map(|item| unquote(item.trim()))
+                    .filter(|item| !item.is_empty())
+                    .collect(),
+            ),
+            None => FrontMatterValue::Scalar(unquote(raw)),
+        };
+        entries.push(FrontMatterEntry { key: key.trim().to_string(), value, line: line_no });
+    }
+
+    Ok(entries)
+}
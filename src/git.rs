@@ -0,0 +1,199 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const GIT_REF_KEY: &str = "doxcer_git_ref";
+pub const GIT_COMMIT_KEY: &str = "doxcer_git_commit";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitRef {
+    pub reference: String,
+    pub commit: String,
+}
+
+
+// ====================================================
+//  Git Plumbing
+// ====================================================
+fn git(args: &[&str]) -> Result<Vec<u8>, DoxcerError> {
+
+    // Runs a git command in the working directory and returns its stdout.
+
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| DoxcerError::Input(format!("Failed to run git: {e}")))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(DoxcerError::Input(format!("git {} failed: {}", args.join(" "), stderr.trim())));
+    }
+    Ok(output.stdout)
+}
+
+
+fn paths(stdout: &[u8]) -> Vec<String> {
+
+    // Splits the NUL-separated output of a `-z` git command.
+
+    stdout
+        .split(|b| *b == 0)
+        .filter(|p| !p.is_empty())
+        .map(|p| String::from_utf8_lossy(p).to_string())
+        .collect()
+}
+
+
+pub fn repo_root() -> Result<PathBuf, DoxcerError> {
+
+    // Returns the top-level directory of the repository around the working directory.
+
+    let out = git(&["rev-parse", "--show-toplevel"])?;
+    Ok(PathBuf::from(String::from_utf8_lossy(&out).trim()))
+}
+
+
+pub fn hooks_dir() -> Result<PathBuf, DoxcerError> {
+
+    // Returns the directory git runs hooks from.
+    //
+    // # Description
+    // Asks git itself (`rev-parse --git-path hooks`), so `core.hooksPath` and
+    // worktrees are honoured. The path is relative to the working directory.
+
+    let out = git(&["rev-parse", "--git-path", "hooks"])?;
+    Ok(PathBuf::from(String::from_utf8_lossy(&out).trim()))
+}
+
+
+pub fn changed_files(staged: bool) -> Result<Vec<PathBuf>, DoxcerError> {
+
+    // Lists the added, copied, modified and renamed files, as absolute paths.
+    //
+    // # Description
+    // With `staged`, only what is in the index (`git diff --cached`), which is
+    // exactly what the next commit contains. Otherwise everything that differs
+    // from `HEAD`, staged or not, plus untracked files that are not ignored.
+    // Deleted files are never listed.
+
+    let root = repo_root()?;
+    let mut changed = if staged {
+        paths(&git(&["diff", "--cached", "--name-only", "--diff-filter=ACMR", "-z"])?)
+    } else {
+        let mut changed = paths(&git(&["diff", "HEAD", "--name-only", "--diff-filter=ACMR", "-z"])?);
+        changed.extend(paths(&git(&["ls-files", "--others", "--exclude-standard", "--full-name", "-z"])?));
+        changed
+    };
+    changed.sort();
+    changed.dedup();
+    Ok(changed.into_iter().map(|p| root.join(p)).collect())
+}
+
+
+pub fn diff_files(range: &str) -> Result<Vec<PathBuf>, DoxcerError> {
+
+    // Lists the files a diff range added, copied, modified or renamed, as
+    // absolute paths.
+    //
+    // # Description
+    // `range` is anything `git diff` takes: `origin/main...HEAD` (changes on
+    // this branch since it forked), `A..B`, or a single ref (compared with the
+    // working tree). Deleted files are never listed.
+
+    if range.starts_with('-') || range.trim().is_empty() {
+        return Err(DoxcerError::Input(format!("invalid diff range {range:?}, expected e.g. origin/main...HEAD")));
+    }
+    let root = repo_root()?;
+    let mut changed = paths(&git(&["diff", "--name-only", "--diff-filter=ACMR", "-z", range, "--"])?);
+    changed.sort();
+    changed.dedup();
+    Ok(changed.into_iter().map(|p| root.join(p)).collect())
+}
+
+
+pub fn staged_content(path: &Path) -> Result<Option<Vec<u8>>, DoxcerError> {
+
+    // Reads a file as it is staged (`git show :<path>`), not as it is on disk.
+    //
+    // # Returns
+    // * `Ok(Some(bytes))` – the staged content.
+    // * `Ok(None)` – the file is not in the index.
+    // * `Err(DoxcerError)` – git could not be run.
+
+    show("", path)
+}
+
+
+pub fn ref_content(commit: &str, path: &Path) -> Result<Option<Vec<u8>>, DoxcerError> {
+
+    // Reads a file as it is at a commit (`git show <commit>:<path>`).
+    //
+    // # Returns
+    // * `Ok(None)` – the file does not exist at that commit.
+
+    show(commit, path)
+}
+
+
+fn show(revision: &str, path: &Path) -> Result<Option<Vec<u8>>, DoxcerError> {
+
+    // Runs `git show <revision>:<path>`; an empty revision means the index.
+    // Relative paths are taken from the working directory.
+
+    let path = path.strip_prefix(".").unwrap_or(path);
+    let spec = if path.is_absolute() {
+        let root = repo_root()?;
+        let relative = path.strip_prefix(&root).unwrap_or(path);
+        format!("{revision}:{}", relative.display())
+    } else {
+        format!("{revision}:./{}", path.display())
+    };
+    let output = Command::new("git")
+        .args(["show", &spec])
+        .output()
+        .map_err(|e| DoxcerError::Input(format!("Failed to run git: {e}")))?;
+    Ok(output.status.success().then_some(output.stdout))
+}
+
+
+// ====================================================
+//  Refs
+// ====================================================
+pub fn resolve_ref(reference: &str) -> Result<GitRef, DoxcerError> {
+
+    // Resolves a branch, tag or commit to the full SHA of its commit.
+
+    let out = git(&["rev-parse", "--verify", "--quiet", &format!("{reference}^{{commit}}")])
+        .map_err(|_| DoxcerError::Input(format!("{reference:?} is not a commit, branch or tag in this repository")))?;
+    Ok(GitRef { reference: reference.to_string(), commit: String::from_utf8_lossy(&out).trim().to_string() })
+}
+
+
+pub fn ref_files(commit: &str, inputs: &[PathBuf]) -> Result<Vec<PathBuf>, DoxcerError> {
+
+    // Lists the files below the given paths as they are at a commit (`git
+    // ls-tree -r`), relative to the working directory. Files deleted from the
+    // working tree since then are included.
+
+    let mut args = vec!["ls-tree", "-r", "--name-only", "-z", commit, "--"];
+    let inputs: Vec<String> = inputs.iter().map(|p| p.display().to_string()).collect();
+    args.extend(inputs.iter().map(String::as_str));
+    Ok(paths(&git(&args)?).into_iter().map(PathBuf::from).collect())
+}
@@ -0,0 +1,103 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fmt;
+use std::str::FromStr;
+
+// External Libraries
+use serde::Deserialize;
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::excerpt::Cell;
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    // One request for the whole notebook, as the template describes.
+    #[default]
+    Document,
+    // One request per code cell, assembled into a section per cell.
+    Cell,
+}
+
+impl fmt::Display for Granularity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Granularity::Document => write!(f, "document"),
+            Granularity::Cell => write!(f, "cell"),
+        }
+    }
+}
+
+impl FromStr for Granularity {
+    type Err = DoxcerError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "document" => Ok(Granularity::Document),
+            "cell" => Ok(Granularity::Cell),
+            other => Err(DoxcerError::Usage(format!("expected document or cell, got {other:?}"))),
+        }
+    }
+}
+
+
+// ====================================================
+//  Prompts
+// ====================================================
+pub fn cell_banner(name: &str, cell: &Cell, total: usize) -> String {
+
+    // Returns the note put above the excerpt of one cell, so the model writes
+    // the section of that cell instead of a whole document.
+
+    format!(
+        "Let op: {name} wordt per cel gedocumenteerd ({total} code-cellen). Beschrijf alleen cel {} \
+         in een paar alinea's Markdown: wat de cel doet, wat ze leest en schrijft en waar je op moet \
+         letten. Schrijf geen YAML front-matter en geen koppen van niveau 1 of 2; tabellen en \
+         koppen van niveau 3 mogen. De secties worden daarna tot één document samengevoegd.",
+        cell.index
+    )
+}
+
+
+// ====================================================
+//  Assembly
+// ====================================================
+pub fn cell_anchor(index: usize) -> String {
+    format!("cel-{index}")
+}
+
+
+pub fn cell_heading(cell: &Cell) -> String {
+    match &cell.title {
+        Some(title) => format!("Cel {}: {title}", cell.index),
+        None => format!("Cel {}", cell.index),
+    }
+}
+
+
+pub fn assemble_cell_document(name: &str, sections: &[(Cell, String)]) -> String {
+
+    // Assembles the answers for every code cell into one document: a title, a
+    // table of contents linking to each cell, and a section per cell under an
+    // `<a id="cel-N">` anchor, so links survive a changed cell title.
+
+    let mut doc = format!("# {name}\n\n## Inhoud\n\n");
+    for (cell, _) in sections {
+        doc.push_str(&format!("- [{}](#{})\n", cell_heading(cell), cell_anchor(cell.index)));
+    }
+    for (cell, text) in sections {
+        doc.push_str(&format!("\n<a id=\"{}\"></a>\n\n## {}\n\n{}\n", cell_anchor(cell.index), cell_heading(cell), text.trim()));
+    }
+    doc
+}
@@ -0,0 +1,137 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fs;
+use std::path::Path;
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const HOOK_NAME: &str = "pre-commit";
+pub const HOOK_BEGIN: &str = "# >>> doxcer >>> (managed by `doxcer hook`, do not edit)";
+pub const HOOK_END: &str = "# <<< doxcer <<<";
+pub const HOOK_COMMAND: &str = "doxcer verify --changed --staged || exit $?";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookChange {
+    Created,
+    Appended,
+    Updated,
+    Removed,
+    Deleted,
+    Absent,
+}
+
+
+// ====================================================
+//  Hook File
+// ====================================================
+pub fn hook_block() -> String {
+
+    // Returns the doxcer-managed block, markers included.
+
+    format!("{HOOK_BEGIN}\n{HOOK_COMMAND}\n{HOOK_END}\n")
+}
+
+
+pub fn install_hook(hook: &Path) -> Result<HookChange, DoxcerError> {
+
+    // Writes the doxcer block into a hook script.
+    //
+    // # Description
+    // A missing hook is created as a `#!/bin/sh` script. An existing hook keeps
+    // its own commands: the block is appended, or replaced in place when it is
+    // already there, so running `hook install` twice changes nothing. The script
+    // is made executable.
+
+    let existing = fs::read_to_string(hook).ok();
+    let (content, change) = match &existing {
+        None => (format!("#!/bin/sh\n{}", hook_block()), HookChange::Created),
+        Some(text) => match strip_block(text) {
+            Some((before, after)) => (format!("{before}{}{after}", hook_block()), HookChange::Updated),
+            None => {
+                let separator = if text.is_empty() || text.ends_with('\n') { "" } else { "\n" };
+                (format!("{text}{separator}{}", hook_block()), HookChange::Appended)
+            }
+        },
+    };
+    if let Some(parent) = hook.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| DoxcerError::Input(format!("Failed to create {}: {e}", parent.display())))?;
+    }
+    fs::write(hook, content).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", hook.display())))?;
+    make_executable(hook)?;
+    Ok(change)
+}
+
+
+pub fn uninstall_hook(hook: &Path) -> Result<HookChange, DoxcerError> {
+
+    // Removes only the doxcer block from a hook script.
+    //
+    // # Description
+    // The rest of the script is left untouched. When nothing but the shebang
+    // remains, the hook file is deleted.
+
+    let Ok(text) = fs::read_to_string(hook) else {
+        return Ok(HookChange::Absent);
+    };
+    let Some((before, after)) = strip_block(&text) else {
+        return Ok(HookChange::Absent);
+    };
+    let rest = format!("{before}{after}");
+    let meaningful = rest.lines().any(|l| !l.trim().is_empty() && !l.starts_with("#!"));
+    if !meaningful {
+        fs::remove_file(hook).map_err(|e| DoxcerError::Input(format!("Failed to remove {}: {e}", hook.display())))?;
+        return Ok(HookChange::Deleted);
+    }
+    fs::write(hook, rest).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", hook.display())))?;
+    Ok(HookChange::Removed)
+}
+
+
+fn strip_block(text: &str) -> Option<(&str, &str)> {
+
+    // Splits a script around the doxcer block; `None` when there is none.
+
+    let start = text.find(HOOK_BEGIN)?;
+    let end = text[start..].find(HOOK_END).map(|i| start + i + HOOK_END.len())?;
+    let end = if text[end..].starts_with('\n') { end + 1 } else { end };
+    Some((&text[..start], &text[end..]))
+}
+
+
+#[cfg(unix)]
+fn make_executable(hook: &Path) -> Result<(), DoxcerError> {
+
+    // Sets the executable bits git requires for hooks.
+
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = fs::metadata(hook)
+        .map_err(|e| DoxcerError::Input(format!("Failed to read {}: {e}", hook.display())))?
+        .permissions();
+    permissions.set_mode(permissions.mode() | 0o755);
+    fs::set_permissions(hook, permissions).map_err(|e| DoxcerError::Input(format!("Failed to make {} executable: {e}", hook.display())))
+}
+
+
+#[cfg(not(unix))]
+fn make_executable(_hook: &Path) -> Result<(), DoxcerError> {
+
+    // Git for Windows runs hooks through its own shell; no mode bits needed.
+
+    Ok(())
+}
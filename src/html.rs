@@ -0,0 +1,284 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Modules
+use crate::frontmatter::split_front_matter;
+
+
+// ====================================================
+//  Rendering
+// ====================================================
+pub fn render_html(markdown: &str) -> String {
+
+    // Renders a generated document to an HTML fragment.
+    //
+    // # Description
+    // Covers the Markdown the templates ask for: headings, paragraphs, flat
+    // lists, block quotes, rules, fenced code, pipe tables, and inline code,
+    // emphasis, links and images. Front-matter is shown as a preformatted block
+    // above the document. Everything else is escaped and shown as text, so a
+    // document can never inject markup.
+
+    let (front_matter, body) = split_front_matter(markdown);
+    let mut out = String::new();
+    if let Some(front_matter) = front_matter.filter(|f| !f.trim().is_empty()) {
+        out.push_str(&format!("<pre class=\"front-matter\">{}</pre>\n", escape_html(front_matter.trim())));
+    }
+
+    let lines: Vec<&str> = body.lines().collect();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim();
+        let block_start = trimmed.is_empty()
+            || trimmed.starts_with('#')
+            || trimmed.starts_with("```")
+            || trimmed.starts_with('|')
+            || trimmed.starts_with('>')
+            || list_item(trimmed).is_some()
+            || is_rule(trimmed);
+        if !block_start {
+            paragraph.push(trimmed);
+            i += 1;
+            continue;
+        }
+        flush_paragraph(&mut out, &mut paragraph);
+
+        if let Some(fence) = trimmed.strip_prefix("```") {
+            let language = fence.trim();
+            let end = lines[i + 1..].iter().position(|l| l.trim().starts_with("```")).map_or(lines.len(), |p| i + 1 + p);
+            let code = lines[i + 1..end].join("\n");
+            let class = if language.is_empty() { String::new() } else { format!(" class=\"language-{}\"", escape_html(language)) };
+            out.push_str(&format!("<pre><code{class}>{}</code></pre>\n", escape_html(&code)));
+            i = end + 1;
+        } else if trimmed.starts_with('#') {
+            let level = trimmed.chars().take_while(|c| *c == '#').count();
+            let text = trimmed[level..].trim();
+            if level <= 6 && (text.is_empty() || trimmed[level..].starts_with(' ')) {
+                out.push_str(&format!("<h{level} id=\"{}\">{}</h{level}>\n", slug(text), inline(text)));
+            } else {
+                paragraph.push(trimmed);
+            }
+            i += 1;
+        } else if trimmed.starts_with('|') {
+            let end = lines[i..].iter().position(|l| !l.trim().starts_with('|')).map_or(lines.len(), |p| i + p);
+            out.push_str(&render_table(&lines[i..end]));
+            i = end;
+        } else if trimmed.starts_with('>') {
+            let end = lines[i..].iter().position(|l| !l.trim().starts_with('>')).map_or(lines.len(), |p| i + p);
+            let quoted: Vec<&str> = lines[i..end].iter().map(|l| l.trim().trim_start_matches('>').trim()).collect();
+            out.push_str(&format!("<blockquote><p>{}</p></blockquote>\n", inline(&quoted.join(" "))));
+            i = end;
+        } else if let Some((ordered, _)) = list_item(trimmed) {
+            let tag = if ordered { "ol" } else { "ul" };
+            out.push_str(&format!("<{tag}>\n"));
+            while let Some((_, item)) = lines.get(i).and_then(|l| list_item(l.trim())) {
+                out.push_str(&format!("<li>{}</li>\n", inline(item)));
+                i += 1;
+            }
+            out.push_str(&format!("</{tag}>\n"));
+        } else if is_rule(trimmed) {
+            out.push_str("<hr>\n");
+            i += 1;
+        } else {
+            i += 1;
+        }
+    }
+    flush_paragraph(&mut out, &mut paragraph);
+    out
+}
+
+
+pub fn escape_html(text: &str) -> String {
+
+    // Escapes the characters that are special in HTML text and attributes.
+
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+
+fn flush_paragraph(out: &mut String, paragraph: &mut Vec<&str>) {
+
+    // Writes the pending paragraph lines, if any, as one `<p>`.
+
+    if !paragraph.is_empty() {
+        out.push_str(&format!("<p>{}</p>\n", inline(&paragraph.join(" "))));
+        paragraph.clear();
+    }
+}
+
+
+fn list_item(line: &str) -> Option<(bool, &str)> {
+
+    // Recognises `- item`, `* item` and `1. item`; returns whether it is ordered.
+
+    if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some((false, item));
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    line[digits..].strip_prefix(". ").filter(|_| digits > 0).map(|item| (true, item))
+}
+
+
+fn is_rule(line: &str) -> bool {
+    line.len() >= 3 && (line.chars().all(|c| c == '-') || line.chars().all(|c| c == '*'))
+}
+
+
+pub fn slug(text: &str) -> String {
+
+    // Turns a heading into an anchor id (`Technisch ontwerp` → `technisch-ontwerp`).
+    // `--split-output` names its pages with it, so links and ids agree.
+
+    let mut slug = String::new();
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') && !slug.is_empty() {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+
+fn render_table(lines: &[&str]) -> String {
+
+    // Renders a pipe table; the second row is the `|---|` separator.
+
+    let cells = |line: &str| -> Vec<String> {
+        let line = line.trim().trim_start_matches('|');
+        let line = line.strip_suffix('|').unwrap_or(line);
+        line.split('|').map(|c| c.trim().to_string()).collect()
+    };
+    let is_separator = |line: &str| cells(line).iter().all(|c| !c.is_empty() && c.chars().all(|ch| matches!(ch, '-' | ':')));
+
+    let mut out = String::from("<table>\n");
+    let mut rows = lines.iter();
+    if lines.len() > 1 && is_separator(lines[1]) {
+        let header = rows.next().map(|l| cells(l)).unwrap_or_default();
+        rows.next();
+        out.push_str("<thead><tr>");
+        for cell in header {
+            out.push_str(&format!("<th>{}</th>", inline(&cell)));
+        }
+        out.push_str("</tr></thead>\n");
+    }
+    out.push_str("<tbody>\n");
+    for row in rows {
+        out.push_str("<tr>");
+        for cell in cells(row) {
+            out.push_str(&format!("<td>{}</td>", inline(&cell)));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</tbody>\n</table>\n");
+    out
+}
+
+
+// ====================================================
+//  Inline Markup
+// ====================================================
+fn inline(text: &str) -> String {
+
+    // Renders inline markup; the contents of code spans are left as they are.
+
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('`') {
+        let Some(end) = rest[start + 1..].find('`') else {
+            break;
+        };
+        out.push_str(&inline_text(&rest[..start]));
+        out.push_str(&format!("<code>{}</code>", escape_html(&rest[start + 1..start + 1 + end])));
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(&inline_text(rest));
+    out
+}
+
+
+fn inline_text(text: &str) -> String {
+
+    // Renders links, images, `**strong**` and `*emphasis*` in text without code.
+
+    let escaped = escape_html(text);
+    let linked = links(&escaped);
+    let strong = wrap_pairs(&linked, "**", "strong");
+    wrap_pairs(&strong, "*", "em")
+}
+
+
+fn links(text: &str) -> String {
+
+    // Turns `[text](target)` and `![alt](target)` into links and images. The
+    // text is already escaped, so the target is safe in an attribute; only
+    // `javascript:` targets are dropped.
+
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(open) = rest.find('[') {
+        let Some(close) = rest[open..].find("](").map(|p| open + p) else {
+            break;
+        };
+        let Some(end) = rest[close + 2..].find(')').map(|p| close + 2 + p) else {
+            break;
+        };
+        let label = &rest[open + 1..close];
+        if label.contains(['[', ']']) {
+            out.push_str(&rest[..open + 1]);
+            rest = &rest[open + 1..];
+            continue;
+        }
+        let image = rest[..open].ends_with('!');
+        let target = rest[close + 2..end].split_whitespace().next().unwrap_or_default();
+        out.push_str(&rest[..if image { open - 1 } else { open }]);
+        if target.to_ascii_lowercase().starts_with("javascript:") {
+            out.push_str(label);
+        } else if image {
+            out.push_str(&format!("<img src=\"{target}\" alt=\"{label}\">"));
+        } else {
+            out.push_str(&format!("<a href=\"{target}\">{label}</a>"));
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+
+fn wrap_pairs(text: &str, marker: &str, tag: &str) -> String {
+
+    // Wraps text between pairs of `marker` in `<tag>`; an unpaired marker stays.
+
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(marker) {
+        let after = &rest[start + marker.len()..];
+        let Some(end) = after.find(marker).filter(|end| *end > 0 && !after.starts_with(' ')) else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        out.push_str(&format!("<{tag}>{}</{tag}>", &after[..end]));
+        rest = &after[end + marker.len()..];
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,385 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::path::Path;
+
+// External Libraries
+use serde::Serialize;
+use serde_json::{Value, json};
+use serde_json::ser::PrettyFormatter;
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::excerpt::{CELL_MARKERS, Cell, split_cells};
+use crate::notebook::{MARKDOWN_MARKER, is_fabric_source, is_ipynb, joined_text};
+use crate::provenance::content_hash;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+// Every injected cell starts with this comment, followed by the content hash
+// of the code cell it documents: `<!-- doxcer:cell sha256:... -->`.
+pub const INJECT_MARKER: &str = "<!-- doxcer:cell";
+// The full Fabric cell markers, as Fabric writes them.
+const FABRIC_MARKDOWN_LINE: &str = "# MARKDOWN ********************";
+const FABRIC_CELL_LINE: &str = "# CELL ********************";
+
+
+// ====================================================
+//  Markers
+// ====================================================
+pub fn is_injectable(path: &Path, text: &str) -> bool {
+
+    // Whether documentation can be written back into this notebook: a Jupyter
+    // notebook or a Fabric notebook source.
+
+    is_ipynb(path) || is_fabric_source(text)
+}
+
+
+pub fn inject_marker(cell: &Cell) -> String {
+
+    // Returns the first line of the injected cell above `cell`.
+
+    format!("{INJECT_MARKER} {} -->", content_hash(cell.text.trim().as_bytes()))
+}
+
+
+fn is_marker_text(text: &str) -> bool {
+
+    // Whether `text` starts with a whole marker line, as `inject_marker`
+    // writes it. A marker without its `-->` was not written by doxcer, so the
+    // cell is the user's and is kept.
+
+    text.trim_start()
+        .lines()
+        .next()
+        .is_some_and(|line| line.starts_with(INJECT_MARKER) && line.trim_end().ends_with("-->"))
+}
+
+
+// ====================================================
+//  Stripping
+// ====================================================
+pub fn strip_injected(path: &Path, text: &str) -> Result<(String, usize), DoxcerError> {
+
+    // Removes the cells an earlier `--inject` run wrote, so the notebook can
+    // be documented and injected again without duplicates.
+    //
+    // # Returns
+    // * `Ok((text, count))` – the notebook without injected cells, and how
+    //   many were removed.
+    // * `Err(DoxcerError)` – the notebook cannot be read.
+
+    if is_ipynb(path) {
+        let mut json = parse_json(path, text)?;
+        let cells = cells_mut(path, &mut json)?;
+        let before = cells.len();
+        cells.retain(|cell| !is_injected_ipynb_cell(cell));
+        let count = before - cells.len();
+        if count == 0 {
+            return Ok((text.to_string(), 0));
+        }
+        return Ok((write_json(path, &json)?, count));
+    }
+
+    let lines: Vec<&str> = text.lines().collect();
+    let injected: Vec<Cell> = split_cells(path, text)?.into_iter().filter(is_injected_fabric_cell).collect();
+    if injected.is_empty() {
+        return Ok((text.to_string(), 0));
+    }
+    let mut kept = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if !injected.iter().any(|cell| (cell.first_line - 1..cell.last_line).contains(&i)) {
+            kept.push(*line);
+        }
+    }
+    Ok((joined_lines(&kept, text), injected.len()))
+}
+
+
+fn is_injected_ipynb_cell(cell: &Value) -> bool {
+    cell.get("cell_type").and_then(Value::as_str) == Some("markdown") && is_marker_text(&joined_text(cell.get("source")))
+}
+
+
+fn is_injected_fabric_cell(cell: &Cell) -> bool {
+
+    // A `# MARKDOWN` cell whose first line is the inject marker.
+
+    let mut lines = cell.text.lines();
+    lines.next().is_some_and(|l| l.starts_with(MARKDOWN_MARKER))
+        && lines
+            .map(|l| l.trim_start_matches('#').trim())
+            .find(|l| !l.is_empty())
+            .is_some_and(is_marker_text)
+}
+
+
+// ====================================================
+//  Injection
+// ====================================================
+pub fn inject_docs(path: &Path, text: &str, docs: &[(Cell, String)]) -> Result<String, DoxcerError> {
+
+    // Writes a markdown cell above each documented code cell.
+    //
+    // # Description
+    // `text` is a notebook without injected cells (see `strip_injected`) and
+    // `docs` holds its cells, as `split_cells` numbers them, with their
+    // documentation. Jupyter notebooks get a `markdown` cell, Fabric sources a
+    // `# MARKDOWN` block; each starts with `inject_marker`. The rest of the
+    // notebook is kept as it was.
+    //
+    // # Returns
+    // * `Ok(String)` – the notebook with the documentation injected.
+    // * `Err(DoxcerError)` – the notebook cannot be read or written.
+
+    if is_ipynb(path) {
+        return inject_ipynb(path, text, docs);
+    }
+
+    let lines: Vec<&str> = text.lines().collect();
+    let mut out: Vec<String> = Vec::new();
+    let mut next = 0;
+    let mut docs: Vec<&(Cell, String)> = docs.iter().collect();
+    docs.sort_by_key(|(cell, _)| cell.first_line);
+    for (cell, doc) in docs {
+        let start = cell.first_line.saturating_sub(1).min(lines.len());
+        out.extend(lines[next..start].iter().map(|l| l.to_string()));
+        next = start;
+        out.push(FABRIC_MARKDOWN_LINE.to_string());
+        out.push(String::new());
+        out.push(format!("# {}", inject_marker(cell)));
+        for line in doc.trim().lines() {
+            out.push(match line.trim_end() {
+                "" => "#".to_string(),
+                line => format!("# {line}"),
+            });
+        }
+        out.push(String::new());
+        // A cell without its own marker (the first cell of a source) would
+        // otherwise become part of the markdown cell.
+        if !lines.get(start).is_some_and(|l| CELL_MARKERS.iter().any(|m| l.starts_with(m))) {
+            out.push(FABRIC_CELL_LINE.to_string());
+            out.push(String::new());
+        }
+    }
+    out.extend(lines[next..].iter().map(|l| l.to_string()));
+    let out: Vec<&str> = out.iter().map(String::as_str).collect();
+    Ok(joined_lines(&out, text))
+}
+
+
+fn inject_ipynb(path: &Path, text: &str, docs: &[(Cell, String)]) -> Result<String, DoxcerError> {
+
+    // Inserts a markdown cell before each documented cell; cell ids are added
+    // when the notebook uses them (nbformat 4.5 and later).
+
+    let mut json = parse_json(path, text)?;
+    let with_ids = json.get("nbformat_minor").and_then(Value::as_u64).is_some_and(|minor| minor >= 5);
+    let cells = cells_mut(path, &mut json)?;
+    let mut docs: Vec<&(Cell, String)> = docs.iter().collect();
+    docs.sort_by_key(|(cell, _)| std::cmp::Reverse(cell.index));
+    for (cell, doc) in docs {
+        let text = format!("{}\n{}", inject_marker(cell), doc.trim());
+        let lines: Vec<String> = text.split_inclusive('\n').map(str::to_string).collect();
+        let mut injected = json!({ "cell_type": "markdown", "metadata": {}, "source": lines });
+        if with_ids {
+            injected["id"] = Value::String(format!("doxcer-cel-{}", cell.index));
+        }
+        cells.insert((cell.index - 1).min(cells.len()), injected);
+    }
+    write_json(path, &json)
+}
+
+
+// ====================================================
+//  Helpers
+// ====================================================
+fn parse_json(path: &Path, text: &str) -> Result<Value, DoxcerError> {
+    serde_json::from_str(text).map_err(|e| DoxcerError::Input(format!("Failed to parse notebook {}: {e}", path.display())))
+}
+
+
+fn cells_mut<'a>(path: &Path, json: &'a mut Value) -> Result<&'a mut Vec<Value>, DoxcerError> {
+    json.get_mut("cells")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| DoxcerError::Input(format!("Failed to parse notebook {}: no `cells` list", path.display())))
+}
+
+
+fn write_json(path: &Path, json: &Value) -> Result<String, DoxcerError> {
+
+    // Serializes like Jupyter does: one-space indent and a final newline.
+
+    let mut bytes = Vec::new();
+    let mut serializer = serde_json::Serializer::with_formatter(&mut bytes, PrettyFormatter::with_indent(b" "));
+    json.serialize(&mut serializer).map_err(|e| DoxcerError::Input(format!("Failed to write notebook {}: {e}", path.display())))?;
+    let mut text = String::from_utf8(bytes).map_err(|e| DoxcerError::Input(format!("Failed to write notebook {}: {e}", path.display())))?;
+    text.push('\n');
+    Ok(text)
+}
+
+
+fn joined_lines(lines: &[&str], original: &str) -> String {
+
+    // Joins lines with `\n`, keeping the final newline of the original.
+
+    let mut text = lines.join("\n");
+    if original.ends_with('\n') {
+        text.push('\n');
+    }
+    text
+}
+
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FABRIC: &str = "\
+# Fabric notebook source
+
+# CELL ********************
+
+df = spark.read.table('orders')
+
+# CELL ********************
+
+display(df)
+";
+
+    const IPYNB: &str = r##"{
+ "cells": [
+  {"cell_type": "code", "metadata": {}, "source": ["df = spark.read.table('orders')"], "outputs": []},
+  {"cell_type": "code", "metadata": {}, "source": ["display(df)"], "outputs": []}
+ ],
+ "metadata": {},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"##;
+
+    fn injected(path: &Path, text: &str) -> String {
+
+        // The notebook with a document above each of its cells.
+
+        let docs: Vec<(Cell, String)> = split_cells(path, text)
+            .unwrap()
+            .into_iter()
+            .map(|cell| {
+                let doc = format!("Documents cell {}.\n\nSecond paragraph.", cell.index);
+                (cell, doc)
+            })
+            .collect();
+        inject_docs(path, text, &docs).unwrap()
+    }
+
+
+    #[test]
+    fn injected_fabric_cells_are_stripped_back_to_the_original() {
+        let path = Path::new("orders.py");
+        let text = injected(path, FABRIC);
+        assert!(text.contains("# MARKDOWN ********************\n\n# <!-- doxcer:cell sha256:"), "{text}");
+        assert!(text.contains("# Documents cell 2.\n#\n# Second paragraph.\n"), "{text}");
+        assert_eq!(strip_injected(path, &text).unwrap(), (FABRIC.to_string(), 2));
+        assert_eq!(strip_injected(path, FABRIC).unwrap(), (FABRIC.to_string(), 0));
+    }
+
+
+    #[test]
+    fn injected_ipynb_cells_are_stripped_back_to_the_original() {
+        let path = Path::new("orders.ipynb");
+        let text = injected(path, IPYNB);
+        let json: Value = serde_json::from_str(&text).unwrap();
+        let kinds: Vec<&str> = json["cells"].as_array().unwrap().iter().map(|c| c["cell_type"].as_str().unwrap()).collect();
+        assert_eq!(kinds, ["markdown", "code", "markdown", "code"]);
+        assert_eq!(json["cells"][2]["id"], "doxcer-cel-2");
+        let (stripped, count) = strip_injected(path, &text).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(serde_json::from_str::<Value>(&stripped).unwrap(), serde_json::from_str::<Value>(IPYNB).unwrap());
+    }
+
+
+    #[test]
+    fn duplicate_injected_cells_are_all_stripped() {
+        // Injecting into a notebook that was not stripped first stacks the
+        // cells; one strip removes every copy.
+        for path in [Path::new("orders.py"), Path::new("orders.ipynb")] {
+            let original = match is_ipynb(path) {
+                true => IPYNB,
+                false => FABRIC,
+            };
+            let twice = injected(path, &injected(path, original));
+            let (stripped, count) = strip_injected(path, &twice).unwrap();
+            assert_eq!(count, 6, "{}", path.display());
+            assert_eq!(serde_json::from_str::<Value>(&stripped).ok(), serde_json::from_str::<Value>(original).ok());
+            if !is_ipynb(path) {
+                assert_eq!(stripped, FABRIC);
+            }
+        }
+    }
+
+
+    #[test]
+    fn an_unterminated_marker_is_the_users_text() {
+        let fabric = FABRIC.replace(
+            "# CELL ********************\n\ndisplay",
+            "# MARKDOWN ********************\n\n# <!-- doxcer:cell notes about cell 2\n\n# CELL ********************\n\ndisplay",
+        );
+        assert_eq!(strip_injected(Path::new("orders.py"), &fabric).unwrap(), (fabric.clone(), 0));
+
+        let ipynb = IPYNB.replace(
+            r#"  {"cell_type": "code", "metadata": {}, "source": ["display(df)"]"#,
+            r#"  {"cell_type": "markdown", "metadata": {}, "source": ["<!-- doxcer:cell\n", "notes -->"]},
+  {"cell_type": "code", "metadata": {}, "source": ["display(df)"]"#,
+        );
+        assert_eq!(strip_injected(Path::new("orders.ipynb"), &ipynb).unwrap(), (ipynb.clone(), 0));
+    }
+
+
+    #[test]
+    fn only_a_marker_on_the_first_line_makes_an_injected_cell() {
+        let path = Path::new("orders.py");
+        let marker = inject_marker(&split_cells(path, FABRIC).unwrap()[1]);
+
+        // A marker nested in the text of an injected cell goes with that cell.
+        let nested = injected(path, FABRIC).replace("# Second paragraph.", &format!("# {marker}"));
+        assert_eq!(strip_injected(path, &nested).unwrap(), (FABRIC.to_string(), 2));
+
+        // A user's markdown cell that quotes a marker below its first line is kept.
+        let quoted = FABRIC.replace(
+            "# CELL ********************\n\ndisplay",
+            &format!("# MARKDOWN ********************\n\n# Notes\n# {marker}\n\n# CELL ********************\n\ndisplay"),
+        );
+        assert_eq!(strip_injected(path, &quoted).unwrap(), (quoted.clone(), 0));
+    }
+
+
+    #[test]
+    fn a_notebook_without_cell_markers_cannot_be_injected() {
+        // Without the Fabric header there is nowhere to put a markdown cell;
+        // `--inject` refuses such a source before reading it.
+        assert!(is_injectable(Path::new("orders.py"), FABRIC));
+        assert!(is_injectable(Path::new("orders.ipynb"), IPYNB));
+        assert!(!is_injectable(Path::new("orders.py"), "df = spark.read.table('orders')\n"));
+        assert!(!is_injectable(Path::new("orders.py"), "# Databricks notebook source\nx = 1\n"));
+
+        let path = Path::new("orders.ipynb");
+        for text in ["{\"nbformat\": 4}", "not json"] {
+            let error = strip_injected(path, text).unwrap_err();
+            assert!(matches!(error, DoxcerError::Input(_)));
+            assert!(error.to_string().contains("Failed to parse notebook orders.ipynb"), "{error}");
+        }
+    }
+}
@@ -0,0 +1,279 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+// Internal Modules
+use crate::cost::DEFAULT_COST_REPORT;
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const REPORT_NAME: &str = "doxcer";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Report {
+    Junit(PathBuf),
+    // `json`: the cost report of the run (see `cost::CostReport`).
+    Json(PathBuf),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaseOutcome {
+    Passed,
+    Failed { category: String, message: String },
+    Skipped(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCase {
+    pub suite: String,
+    pub name: String,
+    pub duration: Duration,
+    pub outcome: CaseOutcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JunitReport {
+    pub path: PathBuf,
+    pub properties: BTreeMap<String, String>,
+    pub cases: Vec<TestCase>,
+}
+
+#[derive(Default)]
+struct Totals {
+    tests: usize,
+    failures: usize,
+    skipped: usize,
+    time: Duration,
+}
+
+impl FromStr for Report {
+    type Err = DoxcerError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+
+        // Parses `junit=<path>`, `json=<path>` or `json` (for `doxcer-cost.json`).
+
+        match value.split_once('=') {
+            Some(("junit", path)) if !path.is_empty() => Ok(Report::Junit(PathBuf::from(path))),
+            Some(("junit", _)) => Err(DoxcerError::Input("junit needs a path, as in junit=report.xml".to_string())),
+            Some(("json", path)) if !path.is_empty() => Ok(Report::Json(PathBuf::from(path))),
+            None if value == "json" => Ok(Report::Json(PathBuf::from(DEFAULT_COST_REPORT))),
+            _ => Err(DoxcerError::Input(format!("expected junit=<path>, json or json=<path>, got {value:?}"))),
+        }
+    }
+}
+
+
+// ====================================================
+//  Recording
+// ====================================================
+impl JunitReport {
+
+    pub fn new(path: &Path, properties: BTreeMap<String, String>) -> JunitReport {
+        JunitReport { path: path.to_path_buf(), properties, cases: Vec::new() }
+    }
+
+
+    pub fn record(&mut self, case: TestCase) -> Result<(), DoxcerError> {
+
+        // Adds a test case and rewrites the file, so a run that is interrupted
+        // or exits early still leaves a report of everything that completed.
+
+        self.cases.push(case);
+        self.write()
+    }
+
+
+    pub fn write(&self) -> Result<(), DoxcerError> {
+
+        // Writes the report atomically (temp file + rename).
+
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| DoxcerError::Input(format!("Failed to create {}: {e}", parent.display())))?;
+        }
+        let tmp = self.path.with_extension("xml.partial");
+        fs::write(&tmp, render_junit(&self.cases, &self.properties)).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", tmp.display())))?;
+        fs::rename(&tmp, &self.path).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", self.path.display())))
+    }
+}
+
+
+pub fn suite_for(notebook: &Path, inputs: &[PathBuf]) -> String {
+
+    // Names the test suite of a notebook: the input directory it was found
+    // under, or the folder of a notebook that was given directly.
+
+    let notebook = notebook.strip_prefix(".").unwrap_or(notebook);
+    let suite = inputs
+        .iter()
+        .map(|input| input.strip_prefix(".").unwrap_or(input))
+        .filter(|input| notebook.starts_with(input) && notebook != *input)
+        .max_by_key(|input| input.components().count())
+        .map(Path::to_path_buf)
+        .or_else(|| notebook.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+    match suite.display().to_string().replace('\\', "/") {
+        name if name.is_empty() => ".".to_string(),
+        name => name,
+    }
+}
+
+
+// ====================================================
+//  Rendering
+// ====================================================
+pub fn escape_xml(text: &str) -> String {
+
+    // Escapes text for an XML attribute or element. Characters XML 1.0 does not
+    // allow at all (control characters other than tab, newline and carriage
+    // return) are replaced by U+FFFD.
+
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if (c as u32) < 0x20 || matches!(c, '\u{FFFE}' | '\u{FFFF}') => out.push('\u{FFFD}'),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+
+pub fn render_junit(cases: &[TestCase], properties: &BTreeMap<String, String>) -> String {
+
+    // Renders the cases as JUnit XML, one `<testsuite>` per suite name. The
+    // `properties` (the run tags) are repeated in every suite, where JUnit
+    // readers look for them.
+
+    let mut suites: BTreeMap<&str, Vec<&TestCase>> = BTreeMap::new();
+    for case in cases {
+        suites.entry(case.suite.as_str()).or_default().push(case);
+    }
+    let totals = |cases: &[&TestCase]| {
+        let mut totals = Totals::default();
+        for case in cases {
+            totals.tests += 1;
+            totals.time += case.duration;
+            match case.outcome {
+                CaseOutcome::Passed => {}
+                CaseOutcome::Failed { .. } => totals.failures += 1,
+                CaseOutcome::Skipped(_) => totals.skipped += 1,
+            }
+        }
+        totals
+    };
+    let counts = |t: &Totals| {
+        format!("tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{:.3}\"", t.tests, t.failures, t.skipped, t.time.as_secs_f64())
+    };
+
+    let all: Vec<&TestCase> = cases.iter().collect();
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!("<testsuites name=\"{REPORT_NAME}\" {}>\n", counts(&totals(&all))));
+    for (suite, cases) in &suites {
+        out.push_str(&format!("  <testsuite name=\"{}\" {}>\n", escape_xml(suite), counts(&totals(cases))));
+        if !properties.is_empty() {
+            out.push_str("    <properties>\n");
+            for (name, value) in properties {
+                out.push_str(&format!("      <property name=\"{}\" value=\"{}\"/>\n", escape_xml(name), escape_xml(value)));
+            }
+            out.push_str("    </properties>\n");
+        }
+        for case in cases {
+            let open = format!(
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                escape_xml(&case.name),
+                escape_xml(suite),
+                case.duration.as_secs_f64()
+            );
+            match &case.outcome {
+                CaseOutcome::Passed => out.push_str(&format!("{open}/>\n")),
+                CaseOutcome::Failed { category, message } => out.push_str(&format!(
+                    "{open}>\n      <failure type=\"{}\" message=\"{}\">{}</failure>\n    </testcase>\n",
+                    escape_xml(category),
+                    escape_xml(message.lines().next().unwrap_or_default()),
+                    escape_xml(message)
+                )),
+                CaseOutcome::Skipped(reason) => {
+                    out.push_str(&format!("{open}>\n      <skipped message=\"{}\"/>\n    </testcase>\n", escape_xml(reason)));
+                }
+            }
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn case(name: &str, outcome: CaseOutcome) -> TestCase {
+        TestCase { suite: "fabric/gold".to_string(), name: name.to_string(), duration: Duration::from_millis(1500), outcome }
+    }
+
+
+    #[test]
+    fn escapes_markup_characters() {
+        assert_eq!(escape_xml(r#"a & b < c > d " e ' f"#), "a &amp; b &lt; c &gt; d &quot; e &apos; f");
+        assert_eq!(escape_xml("&amp;"), "&amp;amp;");
+    }
+
+
+    #[test]
+    fn replaces_control_characters_but_keeps_whitespace() {
+        assert_eq!(escape_xml("a\u{0}b\u{1b}c\u{7}d"), "a\u{FFFD}b\u{FFFD}c\u{FFFD}d");
+        assert_eq!(escape_xml("tab\tnew\nline\r"), "tab\tnew\nline\r");
+        assert_eq!(escape_xml("\u{FFFE}\u{FFFF}"), "\u{FFFD}\u{FFFD}");
+    }
+
+
+    #[test]
+    fn keeps_unicode() {
+        assert_eq!(escape_xml("dim_project_€_ü_日本.py"), "dim_project_€_ü_日本.py");
+    }
+
+
+    #[test]
+    fn paths_with_markup_stay_well_formed() {
+        let cases = [
+            case("sales & <returns>.py", CaseOutcome::Passed),
+            case("bad\"name'.py", CaseOutcome::Failed { category: "provider".to_string(), message: "HTTP 500 <html>\nbody & more".to_string() }),
+            case("cached.py", CaseOutcome::Skipped("cache hit".to_string())),
+        ];
+        let xml = render_junit(&cases, &BTreeMap::from([("team".to_string(), "data & bi".to_string())]));
+        assert!(xml.contains("<testcase name=\"sales &amp; &lt;returns&gt;.py\" classname=\"fabric/gold\" time=\"1.500\"/>"), "{xml}");
+        assert!(xml.contains("<failure type=\"provider\" message=\"HTTP 500 &lt;html&gt;\">HTTP 500 &lt;html&gt;\nbody &amp; more</failure>"), "{xml}");
+        assert!(xml.contains("name=\"bad&quot;name&apos;.py\""), "{xml}");
+        assert!(xml.contains("<property name=\"team\" value=\"data &amp; bi\"/>"), "{xml}");
+        assert!(xml.contains("<testsuites name=\"doxcer\" tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"1\" time=\"4.500\">"), "{xml}");
+        assert!(!xml.contains(" & ") && !xml.contains("<returns>"));
+    }
+}
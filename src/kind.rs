@@ -0,0 +1,100 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+// Internal Modules
+use crate::datapipeline::{is_json_path, parse_pipeline};
+use crate::error::DoxcerError;
+use crate::language::source_header;
+use crate::notebook::parse_databricks;
+use crate::semanticmodel::is_semantic_model_path;
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+// What a file is documented as: it picks the prompt template and the header
+// above the source in the prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SourceKind {
+    #[default]
+    Notebook,
+    // A SQL script: stored procedures, views, functions or plain T-SQL.
+    Sql,
+    // A Fabric (or Data Factory) Data Pipeline definition in JSON.
+    Pipeline,
+    // A Power BI semantic model: a `model.bim` or a TMDL folder.
+    SemanticModel,
+}
+
+impl SourceKind {
+
+    pub fn header(&self, lang: Option<&str>) -> &'static str {
+
+        // Returns the line above the source in the prompt, in the language of
+        // the `lang` setting (see `source_header`).
+
+        source_header(*self, lang)
+    }
+}
+
+impl fmt::Display for SourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceKind::Notebook => write!(f, "notebook"),
+            SourceKind::Sql => write!(f, "sql"),
+            SourceKind::Pipeline => write!(f, "pipeline"),
+            SourceKind::SemanticModel => write!(f, "semantic-model"),
+        }
+    }
+}
+
+impl FromStr for SourceKind {
+    type Err = DoxcerError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "notebook" => Ok(SourceKind::Notebook),
+            "sql" => Ok(SourceKind::Sql),
+            "pipeline" => Ok(SourceKind::Pipeline),
+            "semantic-model" => Ok(SourceKind::SemanticModel),
+            other => Err(DoxcerError::Usage(format!("expected notebook, sql, pipeline, semantic-model or auto, got {other:?}"))),
+        }
+    }
+}
+
+
+// ====================================================
+//  Detection
+// ====================================================
+pub fn is_sql_path(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("sql"))
+}
+
+
+pub fn detect_kind(path: &Path, text: &str) -> SourceKind {
+
+    // Returns the kind of a file for `--kind auto`: a `.sql` file is a SQL
+    // script, unless it is a Databricks notebook export, a `.json` file
+    // with an `activities` list is a Data Pipeline, and a `model.bim` or
+    // `definition/model.tmdl` is a semantic model.
+
+    if is_semantic_model_path(path) {
+        return SourceKind::SemanticModel;
+    }
+    if is_sql_path(path) && parse_databricks(text).is_none() {
+        return SourceKind::Sql;
+    }
+    match is_json_path(path) && parse_pipeline(text).is_ok() {
+        true => SourceKind::Pipeline,
+        false => SourceKind::Notebook,
+    }
+}
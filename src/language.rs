@@ -0,0 +1,98 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Modules
+use crate::kind::SourceKind;
+use crate::template::{NOTEBOOK_HEADER, PIPELINE_HEADER, SEMANTIC_MODEL_HEADER, SQL_HEADER};
+
+
+// ====================================================
+//  Constants
+// ====================================================
+// Languages doxcer knows by name: the code, the English name and the name in
+// the language itself. Other `lang` values are passed to the model as given.
+pub const LANGUAGES: [(&str, &str, &str); 10] = [
+    ("nl", "Dutch", "Nederlands"),
+    ("en", "English", "English"),
+    ("de", "German", "Deutsch"),
+    ("fr", "French", "Français"),
+    ("es", "Spanish", "Español"),
+    ("it", "Italian", "Italiano"),
+    ("pt", "Portuguese", "Português"),
+    ("pl", "Polish", "Polski"),
+    ("sv", "Swedish", "Svenska"),
+    ("da", "Danish", "Dansk"),
+];
+// The line above the source in the prompt, per language, in the order
+// notebook, SQL script, Data Pipeline, semantic model. Languages without an
+// entry use English.
+pub const SOURCE_HEADERS: [(&str, [&str; 4]); 5] = [
+    ("nl", [NOTEBOOK_HEADER, SQL_HEADER, PIPELINE_HEADER, SEMANTIC_MODEL_HEADER]),
+    ("en", ["Here is the notebook:", "Here is the SQL script:", "Here is the Data Pipeline:", "Here is the semantic model:"]),
+    ("de", ["Hier ist das Notebook:", "Hier ist das SQL-Skript:", "Hier ist die Data Pipeline:", "Hier ist das semantische Modell:"]),
+    ("fr", ["Voici le notebook :", "Voici le script SQL :", "Voici le Data Pipeline :", "Voici le modèle sémantique :"]),
+    ("es", ["Este es el notebook:", "Este es el script SQL:", "Este es el Data Pipeline:", "Este es el modelo semántico:"]),
+];
+
+
+// ====================================================
+//  Languages
+// ====================================================
+pub fn language_code(lang: &str) -> Option<&'static str> {
+
+    // Returns the code of a known language for a `lang` setting: `en`,
+    // `en-GB`, `en_US`, `English` and `english` all give `en`.
+
+    let lang = lang.trim().to_lowercase();
+    let primary = lang.split(['-', '_']).next().unwrap_or_default();
+    LANGUAGES
+        .iter()
+        .find(|(code, english, native)| *code == primary || english.to_lowercase() == lang || native.to_lowercase() == lang)
+        .map(|(code, _, _)| *code)
+}
+
+
+pub fn language_instruction(lang: &str) -> String {
+
+    // Returns the instruction added to the prompt for a `lang` setting.
+    //
+    // # Description
+    // The instruction is in English, which every model follows, and names the
+    // language explicitly so it also overrides a template written in another
+    // language. Names in the code stay as they are.
+
+    let language = match language_code(lang).and_then(|code| LANGUAGES.iter().find(|(c, _, _)| *c == code)) {
+        Some((_, english, native)) if english == native => english.to_string(),
+        Some((_, english, native)) => format!("{english} ({native})"),
+        None => format!("the language \"{}\"", lang.trim()),
+    };
+    format!(
+        "Write the entire documentation in {language}, including headings, table headers and \
+         explanations, even where these instructions use another language. Keep the names of \
+         tables, columns, parameters and code as they are."
+    )
+}
+
+
+pub fn source_header(kind: SourceKind, lang: Option<&str>) -> &'static str {
+
+    // Returns the line above the source in the prompt: Dutch without a `lang`,
+    // as doxcer always did, else the language's own header or English.
+
+    let code = match lang {
+        Some(lang) => language_code(lang).unwrap_or("en"),
+        None => "nl",
+    };
+    let (_, english) = &SOURCE_HEADERS[1];
+    let headers = SOURCE_HEADERS.iter().find(|(c, _)| *c == code).map_or(english, |(_, headers)| headers);
+    match kind {
+        SourceKind::Notebook => headers[0],
+        SourceKind::Sql => headers[1],
+        SourceKind::Pipeline => headers[2],
+        SourceKind::SemanticModel => headers[3],
+    }
+}
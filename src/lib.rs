@@ -1,219 +1,584 @@
-////////////////////////////////////////////////////////
-// AUTHOR   : Stefan B. J. Meeuwessen
-// CREATION : 2025-11-07
-// VERSION  : 0.1.1
-////////////////////////////////////////////////////////
-
-
-// Compiler Directives
-#![allow(unused)]
-
-
-// Internal Libraries
-use std::env;
-use std::ffi::OsStr;
-use std::path::{Path, PathBuf};
-
-// External Libraries
-use dotenvy::from_path;
-use fernet::Fernet;
-
-
-// ====================================================
-//  Fernet Decryption
-// ====================================================
-pub fn decrypt_fernet(encrypted_value_b64: &str, fernet_key: &str) -> Result<String, String> {
-
-    // Decrypts a Fernet-encrypted, base64-encoded payload into a UTF-8 `String`.
-    //
-    // # Description
-    // Uses the `fernet` crate to decrypt a base64-encoded token with the provided key.
-    // Returns the plaintext as UTF-8.
-    //
-    // # Parameters
-    // * `encrypted_value_b64` – The encrypted string (base64-encoded).
-    // * `fernet_key` – The Fernet encryption key (URL-safe base64).
-    //
-    // # Returns
-    // * `Ok(String)` on successful decryption.
-    // * `Err(String)` if the key/ciphertext is invalid or not UTF-8.
-
-    let fernet = Fernet::new(fernet_key).ok_or_else(|| "Invalid Fernet key".to_string())?;
-    let decrypted = fernet
-        .decrypt(encrypted_value_b64)
-        .map_err(|_| "Decryption failed".to_string())?;
-    String::from_utf8(decrypted).map_err(|_| "Decrypted bytes were not valid UTF-8".to_string())
-}
-
-
-// ====================================================
-//  Environment Loading
-// ====================================================
-pub fn load_env_robust<P: AsRef<Path>>(override_path: Option<P>) -> Result<PathBuf, String> {
-
-    // Loads a `.env` file from multiple potential locations, in priority order.
-    //
-    // # Description
-    // Searches for a valid `.env` file across:
-    // 1. Explicit `override_path` argument (if provided)
-    // 2. `DOXCER_ENV_PATH` environment variable
-    // 3. Current working directory and its `config` subfolder
-    // 4. Executable directory and its parent directories
-    //
-    // Returns the path that was successfully loaded, or an error if none found.
-
-    let override_path = override_path.map(|p| p.as_ref().to_path_buf());
-    let explicit_env = env::var("DOXCER_ENV_PATH").ok().map(PathBuf::from);
-
-    let cwd = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-    let exe_dir = env::current_exe()
-        .ok()
-        .and_then(|p| p.parent().map(|p| p.to_path_buf()))
-        .unwrap_or_else(|| PathBuf::from("."));
-    let exe_parent = exe_dir.parent().map(|p| p.to_path_buf());
-    let exe_grandparent = exe_parent.as_ref().and_then(|p| p.parent().map(|p| p.to_path_buf()));
-
-    let candidates_in = |root: &Path| -> [PathBuf; 2] {
-        [root.join("config").join(".env"), root.join(".env")]
-    };
-
-    let mut candidates: Vec<PathBuf> = Vec::new();
-    if let Some(p) = override_path { candidates.push(p); }
-    if let Some(p) = explicit_env { candidates.push(p); }
-
-    candidates.extend(candidates_in(&cwd));
-    candidates.extend(candidates_in(&exe_dir));
-    if let Some(p) = &exe_parent { candidates.extend(candidates_in(p)); }
-    if let Some(p) = &exe_grandparent { candidates.extend(candidates_in(p)); }
-
-    let tried = candidates.clone();
-
-    if let Some(found) = candidates.into_iter().find(|p| p.exists()) {
-        from_path(&found)
-            .map_err(|e| format!("Failed to load .env at {}: {e}", found.display()))?;
-        Ok(found)
-    } else {
-        let searched = tried
-            .iter()
-            .map(|p| p.display().to_string())
-            .collect::<Vec<_>>()
-            .join("\n  - ");
-        Err(format!("Could not find a .env file. Searched:\n  - {}", searched))
-    }
-}
-
-
-// ====================================================
-//  Environment Helpers
-// ====================================================
-pub fn env_plain(var: &str) -> Result<String, String> {
-
-    // Fetches an environment variable as plaintext.
-    //
-    // # Description
-    // Retrieves the environment variable value directly without decryption.
-    // Fails if the variable is missing.
-    //
-    // # Parameters
-    // * `var` – The name of the environment variable.
-    //
-    // # Returns
-    // * `Ok(String)` containing the variable value.
-    // * `Err(String)` if the variable is not found.
-
-    env::var(var).map_err(|_| format!("Missing required env var: {var}"))
-}
-
-
-pub fn env_secret(name: &str, key_override: Option<&str>) -> Result<String, String> {
-
-    // Retrieves an environment secret, supporting both plaintext and encrypted values.
-    //
-    // # Description
-    // The function checks for the following variables:
-    // - `{name}` → returned as plaintext if found.
-    // - `{name}_ENC` → decrypted using Fernet with either:
-    //   - the provided `key_override`, or
-    //   - the `ENCRYPTION_PASSWORD` environment variable.
-    //
-    // # Parameters
-    // * `name` – The base name of the environment variable.
-    // * `key_override` – Optional Fernet key to override `ENCRYPTION_PASSWORD`.
-    //
-    // # Returns
-    // * `Ok(String)` containing the secret.
-    // * `Err(String)` if the variable is missing or decryption fails.
-
-    if let Ok(v) = env::var(name) {
-        return Ok(v);
-    }
-
-    let enc_name = format!("{name}_ENC");
-    let enc = env::var(&enc_name)
-        .map_err(|_| format!("Neither {name} nor {enc_name} found in environment"))?;
-
-    let key = if let Some(k) = key_override {
-        k.to_string()
-    } else {
-        env::var("ENCRYPTION_PASSWORD")
-            .map_err(|_| "Missing ENCRYPTION_PASSWORD for Fernet decryption".to_string())?
-    };
-
-    decrypt_fernet(&enc, &key)
-}
-
-
-pub fn env_fernet_key() -> Result<String, String> {
-
-    // Retrieves and validates the Fernet key from the environment.
-    //
-    // # Description
-    // Ensures that `ENCRYPTION_PASSWORD` is set and structurally valid as a Fernet key.
-    //
-    // # Returns
-    // * `Ok(String)` containing the valid Fernet key.
-    // * `Err(String)` if the key is missing or invalid.
-
-    let key = env::var("ENCRYPTION_PASSWORD")
-        .map_err(|_| "Missing ENCRYPTION_PASSWORD".to_string())?;
-    Fernet::new(&key).ok_or_else(|| "ENCRYPTION_PASSWORD is not a valid Fernet key".to_string())?;
-    Ok(key)
-}
-
-
-pub fn env_path_opt(var: &str) -> Result<Option<PathBuf>, String> {
-
-    // Resolves an optional path-like environment variable into a `PathBuf`.
-    //
-    // # Description
-    // If the variable is set, returns its value as a `PathBuf`.
-    // If not set, returns `Ok(None)`. Fails on invalid Unicode.
-    //
-    // # Parameters
-    // * `var` – The name of the environment variable.
-    //
-    // # Returns
-    // * `Ok(Some(PathBuf))` if the variable exists.
-    // * `Ok(None)` if the variable is not present.
-    // * `Err(String)` if the variable contains invalid Unicode.
-
-    match env::var(var) {
-        Ok(v) => Ok(Some(PathBuf::from(v))),
-        Err(env::VarError::NotPresent) => Ok(None),
-        Err(env::VarError::NotUnicode(_)) => Err(format!("{var} contains non-unicode data")),
-    }
-}
-
-
-pub fn is_dotenv_name<S: AsRef<OsStr>>(name: S) -> bool {
-
-    // Checks whether a given file name equals `.env` (case-sensitive).
-    //
-    // # Parameters
-    // * `name` – The file name to check.
-    //
-    // # Returns
-    // * `true` if the file name is `.env`, otherwise `false`.
-
-    name.as_ref() == ".env"
-}
\ No newline at end of file
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2025-11-07
+// VERSION  : 0.1.1
+////////////////////////////////////////////////////////
+
+
+// Compiler Directives
+#![deny(unused_must_use)]
+
+
+// Internal Libraries
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+// External Libraries
+use dotenvy::from_path;
+use fernet::Fernet;
+use sha2::{Digest, Sha256};
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+// Modules
+pub mod archive;
+pub mod cache;
+pub mod cancel;
+pub mod chunk;
+pub mod clipboard;
+pub mod config;
+pub mod contextfiles;
+pub mod cost;
+pub mod coverage;
+pub mod datapipeline;
+pub mod dbt;
+pub mod diagnostics;
+pub mod directives;
+pub mod editmode;
+pub mod envsafety;
+pub mod error;
+pub mod discovery;
+pub mod docfile;
+pub mod excerpt;
+pub mod fabric;
+pub mod failures;
+pub mod formats;
+pub mod frontmatter;
+pub mod git;
+pub mod granularity;
+pub mod hook;
+pub mod html;
+pub mod inject;
+pub mod junit;
+pub mod kind;
+pub mod language;
+pub mod limits;
+pub mod links;
+pub mod lineage;
+pub mod locale;
+pub mod manifest;
+pub mod notebook;
+pub mod output;
+pub mod oversize;
+pub mod pii;
+pub mod pipeline;
+pub mod posthook;
+pub mod profile;
+pub mod provenance;
+pub mod provider;
+pub mod queue;
+pub mod retry;
+pub mod routing;
+pub mod runlog;
+pub mod runrefs;
+pub mod secrets;
+pub mod semanticmodel;
+pub mod serve;
+pub mod source;
+pub mod split;
+pub mod style;
+pub mod summary;
+pub mod telemetry;
+pub mod template;
+pub mod templatecheck;
+pub mod templateregistry;
+pub mod templating;
+pub mod timefmt;
+pub mod verify;
+pub mod watch;
+pub mod workspace;
+pub mod wrap;
+
+
+// ====================================================
+//  Version Information
+// ====================================================
+pub fn version() -> &'static str {
+
+    // Returns the version of this doxcer build (from `CARGO_PKG_VERSION`).
+
+    env!("CARGO_PKG_VERSION")
+}
+
+
+pub fn user_agent() -> String {
+
+    // Builds the `User-Agent` header sent with every outgoing request.
+    //
+    // # Description
+    // Formats as `doxcer/<version> (<os>; <arch>)`. When `DOXCER_USER_AGENT_SUFFIX`
+    // is set, it is appended after a space so gateways can attribute traffic per
+    // team, e.g. `doxcer/0.1.0 (linux; x86_64) team-data-bi`.
+
+    let base = format!("doxcer/{} ({}; {})", version(), env::consts::OS, env::consts::ARCH);
+    match env::var("DOXCER_USER_AGENT_SUFFIX") {
+        Ok(suffix) if !suffix.trim().is_empty() => format!("{base} {}", suffix.trim()),
+        _ => base,
+    }
+}
+
+
+// ====================================================
+//  Fernet Decryption
+// ====================================================
+// Validated keys, found by a SHA-256 digest of the key material so the key
+// itself only lives inside the `Fernet` instances (which have no `Debug`).
+// With per-variable keys several keys are in use at once, so each keeps its
+// own entry; the map is cleared when it reaches `FERNET_CACHE_CAPACITY`.
+const FERNET_CACHE_CAPACITY: usize = 64;
+
+static FERNET_CACHE: Mutex<Option<HashMap<[u8; 32], Fernet>>> = Mutex::new(None);
+
+#[cfg(test)]
+thread_local! {
+    // The keys validated on this thread, so tests can tell a cache hit from a
+    // new `Fernet` instance.
+    static FERNET_BUILDS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+fn cached_fernet(fernet_key: &str) -> Option<Fernet> {
+
+    // Returns the `Fernet` instance for a key, validating each key only once.
+    //
+    // # Description
+    // Batch runs decrypt with the same keys over and over; each instance is
+    // built once and cloned per call. Another key (a `key_override`, a
+    // per-variable key, or a changed `ENCRYPTION_PASSWORD`) gets its own
+    // entry, so alternating keys do not evict each other. Invalid keys are
+    // never cached. Safe to call from several threads.
+
+    let digest: [u8; 32] = Sha256::digest(fernet_key.as_bytes()).into();
+    let mut slot = FERNET_CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+    let cache = slot.get_or_insert_with(HashMap::new);
+    if let Some(fernet) = cache.get(&digest) {
+        return Some(fernet.clone());
+    }
+    let fernet = Fernet::new(fernet_key)?;
+    #[cfg(test)]
+    FERNET_BUILDS.with(|builds| builds.set(builds.get() + 1));
+    if cache.len() >= FERNET_CACHE_CAPACITY {
+        cache.clear();
+    }
+    cache.insert(digest, fernet.clone());
+    Some(fernet)
+}
+
+
+pub fn generate_fernet_key() -> String {
+
+    // Returns a fresh random Fernet key (URL-safe base64), for
+    // `ENCRYPTION_PASSWORD` or a `<name>_ENC_KEY` variable.
+
+    Fernet::generate_key()
+}
+
+
+pub fn encrypt_fernet(plaintext: &str, fernet_key: &str) -> Result<String, DoxcerError> {
+
+    // Encrypts a UTF-8 string into a Fernet token, the value of a `*_ENC`
+    // variable.
+    //
+    // # Parameters
+    // * `plaintext` – The secret to encrypt.
+    // * `fernet_key` – The Fernet encryption key (URL-safe base64).
+    //
+    // # Returns
+    // * `Ok(String)` with the URL-safe base64 token.
+    // * `Err(DoxcerError)` if the key is invalid.
+
+    encrypt_fernet_bytes(plaintext.as_bytes(), fernet_key)
+}
+
+
+pub fn encrypt_fernet_bytes(data: &[u8], fernet_key: &str) -> Result<String, DoxcerError> {
+
+    // Encrypts raw bytes into a Fernet token; see [`encrypt_fernet`]. Every
+    // call uses a fresh IV and the current time, so the same input gives a
+    // different token each time.
+
+    let fernet = cached_fernet(fernet_key).ok_or(DoxcerError::InvalidFernetKey { key_name: None })?;
+    Ok(fernet.encrypt(data))
+}
+
+
+pub fn decrypt_fernet(encrypted_value_b64: &str, fernet_key: &str) -> Result<String, DoxcerError> {
+
+    // Decrypts a Fernet-encrypted, base64-encoded payload into a UTF-8 `String`.
+    //
+    // # Description
+    // Uses the `fernet` crate to decrypt a base64-encoded token with the provided key.
+    // Returns the plaintext as UTF-8. The key is validated once per key, not
+    // once per call (see `cached_fernet`).
+    //
+    // # Parameters
+    // * `encrypted_value_b64` – The encrypted string (base64-encoded).
+    // * `fernet_key` – The Fernet encryption key (URL-safe base64).
+    //
+    // # Returns
+    // * `Ok(String)` on successful decryption.
+    // * `Err(DoxcerError)` if the key/ciphertext is invalid or not UTF-8.
+
+    let decrypted = decrypt_fernet_bytes(encrypted_value_b64, fernet_key)?;
+    String::from_utf8(decrypted).map_err(|_| DoxcerError::InvalidUtf8)
+}
+
+
+pub fn decrypt_fernet_bytes(encrypted_value_b64: &str, fernet_key: &str) -> Result<Vec<u8>, DoxcerError> {
+
+    // Decrypts a Fernet token into raw bytes; see [`decrypt_fernet`].
+
+    let fernet = cached_fernet(fernet_key).ok_or(DoxcerError::InvalidFernetKey { key_name: None })?;
+    fernet.decrypt(encrypted_value_b64).map_err(|_| DoxcerError::DecryptionFailed)
+}
+
+
+// ====================================================
+//  Environment Loading
+// ====================================================
+pub fn load_env_robust<P: AsRef<Path>>(override_path: Option<P>) -> Result<PathBuf, DoxcerError> {
+
+    // Loads a `.env` file from multiple potential locations, in priority order.
+    //
+    // # Description
+    // Searches for a valid `.env` file across:
+    // 1. Explicit `override_path` argument (if provided)
+    // 2. `DOXCER_ENV_PATH` environment variable
+    // 3. Current working directory and its `config` subfolder
+    // 4. Executable directory and its parent directories
+    //
+    // Returns the path that was successfully loaded, or `EnvFileNotFound` with
+    // every candidate when none exists.
+
+    let override_path = override_path.map(|p| p.as_ref().to_path_buf());
+    let explicit_env = env::var("DOXCER_ENV_PATH").ok().map(PathBuf::from);
+
+    let cwd = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let exe_dir = env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+        .unwrap_or_else(|| PathBuf::from("."));
+    let exe_parent = exe_dir.parent().map(|p| p.to_path_buf());
+    let exe_grandparent = exe_parent.as_ref().and_then(|p| p.parent().map(|p| p.to_path_buf()));
+
+    let candidates_in = |root: &Path| -> [PathBuf; 2] {
+        [root.join("config").join(".env"), root.join(".env")]
+    };
+
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    if let Some(p) = override_path { candidates.push(p); }
+    if let Some(p) = explicit_env { candidates.push(p); }
+
+    candidates.extend(candidates_in(&cwd));
+    candidates.extend(candidates_in(&exe_dir));
+    if let Some(p) = &exe_parent { candidates.extend(candidates_in(p)); }
+    if let Some(p) = &exe_grandparent { candidates.extend(candidates_in(p)); }
+
+    let tried = candidates.clone();
+
+    if let Some(found) = candidates.into_iter().find(|p| p.exists()) {
+        from_path(&found)
+            .map_err(|e| DoxcerError::EnvFileUnreadable { path: found.clone(), reason: e.to_string() })?;
+        Ok(found)
+    } else {
+        Err(DoxcerError::EnvFileNotFound { searched: tried })
+    }
+}
+
+
+// ====================================================
+//  Template Discovery
+// ====================================================
+pub const TEMPLATE_FILE_NAME: &str = "prompt.md";
+
+
+pub fn resolve_template<P: AsRef<Path>>(override_path: Option<P>) -> Result<PathBuf, DoxcerError> {
+
+    // Finds the prompt template (`prompt.md`), in priority order.
+    //
+    // # Description
+    // Mirrors [`load_env_robust`]:
+    // 1. Explicit `override_path` argument (`--template`)
+    // 2. `DOXCER_TEMPLATE_PATH` environment variable
+    // 3. Current working directory: `templates/` and `config/templates/`
+    // 4. Executable directory and its parent directories, likewise
+    //
+    // An explicit path (1 or 2) must exist: a typo there fails with
+    // `TemplateMissing` instead of silently using another template. When no
+    // template is found, `TemplateNotFound` lists every candidate; doxcer then
+    // uses its built-in prompt template. The other templates
+    // (`summary.md`, `sql.md`, ...) and the partials are looked up next to the
+    // template this returns.
+
+    let explicit = override_path
+        .map(|p| p.as_ref().to_path_buf())
+        .or_else(|| env::var("DOXCER_TEMPLATE_PATH").ok().filter(|p| !p.trim().is_empty()).map(PathBuf::from));
+    if let Some(path) = explicit {
+        return match path.is_file() {
+            true => Ok(path),
+            false => Err(DoxcerError::TemplateMissing { path }),
+        };
+    }
+
+    let cwd = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let exe_dir = env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+        .unwrap_or_else(|| PathBuf::from("."));
+    let exe_parent = exe_dir.parent().map(|p| p.to_path_buf());
+    let exe_grandparent = exe_parent.as_ref().and_then(|p| p.parent().map(|p| p.to_path_buf()));
+
+    let candidates_in = |root: &Path| -> [PathBuf; 2] {
+        [root.join("templates").join(TEMPLATE_FILE_NAME), root.join("config").join("templates").join(TEMPLATE_FILE_NAME)]
+    };
+
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    candidates.extend(candidates_in(&cwd));
+    candidates.extend(candidates_in(&exe_dir));
+    if let Some(p) = &exe_parent { candidates.extend(candidates_in(p)); }
+    if let Some(p) = &exe_grandparent { candidates.extend(candidates_in(p)); }
+
+    match candidates.iter().find(|p| p.is_file()) {
+        Some(found) => Ok(found.clone()),
+        None => Err(DoxcerError::TemplateNotFound { searched: candidates }),
+    }
+}
+
+
+// ====================================================
+//  Environment Helpers
+// ====================================================
+pub fn env_plain(var: &str) -> Result<String, DoxcerError> {
+
+    // Fetches an environment variable as plaintext.
+    //
+    // # Description
+    // Retrieves the environment variable value directly without decryption.
+    // Fails if the variable is missing.
+    //
+    // # Parameters
+    // * `var` – The name of the environment variable.
+    //
+    // # Returns
+    // * `Ok(String)` containing the variable value.
+    // * `Err(DoxcerError)` if the variable is not found.
+
+    env::var(var).map_err(|_| DoxcerError::MissingEnvVar(var.to_string()))
+}
+
+
+pub fn env_secret(name: &str, key_override: Option<&str>) -> Result<String, DoxcerError> {
+
+    // Retrieves an environment secret, supporting both plaintext and encrypted values.
+    //
+    // # Description
+    // The function checks for the following variables:
+    // - `{name}` → returned as plaintext if found.
+    // - `{name}_ENC` → decrypted using Fernet with, in order of precedence:
+    //   - the provided `key_override`,
+    //   - the variable named by `{name}_ENC_KEY` (see [`secret_key_name`]), or
+    //   - the `ENCRYPTION_PASSWORD` environment variable.
+    //
+    // # Parameters
+    // * `name` – The base name of the environment variable.
+    // * `key_override` – Optional Fernet key to override `ENCRYPTION_PASSWORD`.
+    //
+    // # Returns
+    // * `Ok(String)` containing the secret.
+    // * `Err(DoxcerError)` if the variable is missing or decryption fails.
+
+    if let Ok(v) = env::var(name) {
+        return Ok(v);
+    }
+
+    let enc_name = format!("{name}_ENC");
+    let enc = env::var(&enc_name)
+        .map_err(|_| DoxcerError::MissingSecret { name: name.to_string(), enc_name: enc_name.clone() })?;
+
+    let key = if let Some(k) = key_override {
+        k.to_string()
+    } else {
+        let key_name = secret_key_name(name);
+        env::var(&key_name)
+            .map_err(|_| DoxcerError::MissingFernetKey { key_name: key_name.clone(), encrypted: Some(enc_name.clone()) })?
+    };
+
+    decrypt_fernet(&enc, &key)
+}
+
+
+pub fn secret_key_name(name: &str) -> String {
+
+    // Returns the name of the variable holding the Fernet key for `{name}_ENC`.
+    //
+    // # Description
+    // A `.env` shared between tools may encrypt variables with different keys.
+    // `{name}_ENC_KEY` names the key variable for one secret, for example
+    // `FABRIC_TOKEN_ENC_KEY=ENCRYPTION_PASSWORD_FABRIC`; without it the global
+    // `ENCRYPTION_PASSWORD` is used.
+    //
+    // # Parameters
+    // * `name` – The base name of the secret, without `_ENC`.
+
+    env::var(format!("{name}_ENC_KEY"))
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "ENCRYPTION_PASSWORD".to_string())
+}
+
+
+pub fn env_fernet_key() -> Result<String, DoxcerError> {
+
+    // Retrieves and validates the Fernet key from the environment.
+    //
+    // # Description
+    // Ensures that `ENCRYPTION_PASSWORD` is set and structurally valid as a Fernet key.
+    //
+    // # Returns
+    // * `Ok(String)` containing the valid Fernet key.
+    // * `Err(DoxcerError)` if the key is missing or invalid.
+
+    let key_name = "ENCRYPTION_PASSWORD";
+    let key = env::var(key_name)
+        .map_err(|_| DoxcerError::MissingFernetKey { key_name: key_name.to_string(), encrypted: None })?;
+    cached_fernet(&key).ok_or_else(|| DoxcerError::InvalidFernetKey { key_name: Some(key_name.to_string()) })?;
+    Ok(key)
+}
+
+
+pub fn env_path_opt(var: &str) -> Result<Option<PathBuf>, DoxcerError> {
+
+    // Resolves an optional path-like environment variable into a `PathBuf`.
+    //
+    // # Description
+    // If the variable is set, returns its value as a `PathBuf`.
+    // If not set, returns `Ok(None)`. Fails on invalid Unicode.
+    //
+    // # Parameters
+    // * `var` – The name of the environment variable.
+    //
+    // # Returns
+    // * `Ok(Some(PathBuf))` if the variable exists.
+    // * `Ok(None)` if the variable is not present.
+    // * `Err(DoxcerError)` if the variable contains invalid Unicode.
+
+    match env::var(var) {
+        Ok(v) => Ok(Some(PathBuf::from(v))),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(env::VarError::NotUnicode(_)) => Err(DoxcerError::NotUnicode(var.to_string())),
+    }
+}
+
+
+pub fn is_dotenv_name<S: AsRef<OsStr>>(name: S) -> bool {
+
+    // Checks whether a given file name equals `.env` (case-sensitive).
+    //
+    // # Parameters
+    // * `name` – The file name to check.
+    //
+    // # Returns
+    // * `true` if the file name is `.env`, otherwise `false`.
+
+    name.as_ref() == ".env"
+}
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builds() -> usize {
+        FERNET_BUILDS.with(|builds| builds.get())
+    }
+
+
+    #[test]
+    fn decrypting_many_tokens_validates_each_key_once() {
+        let key_a = generate_fernet_key();
+        let key_b = generate_fernet_key();
+        let token_a = encrypt_fernet("secret a", &key_a).unwrap();
+        let token_b = encrypt_fernet("secret b", &key_b).unwrap();
+        let before = builds();
+
+        // Alternate between two keys, as two `_ENC` variables with their own
+        // keys do; neither may evict the other.
+        for i in 0..10_000 {
+            match i % 2 {
+                0 => assert_eq!(decrypt_fernet(&token_a, &key_a).unwrap(), "secret a"),
+                _ => assert_eq!(decrypt_fernet(&token_b, &key_b).unwrap(), "secret b"),
+            }
+        }
+        assert_eq!(builds(), before);
+    }
+
+
+    #[test]
+    fn invalid_keys_are_not_cached() {
+        let before = builds();
+        assert_eq!(decrypt_fernet("token", "not a key"), Err(DoxcerError::InvalidFernetKey { key_name: None }));
+        assert_eq!(decrypt_fernet("token", "not a key"), Err(DoxcerError::InvalidFernetKey { key_name: None }));
+        assert_eq!(builds(), before);
+    }
+
+
+    #[test]
+    fn a_changed_key_gets_its_own_instance() {
+        let key_a = generate_fernet_key();
+        let key_b = generate_fernet_key();
+        let token = encrypt_fernet("secret", &key_a).unwrap();
+        let before = builds();
+        assert_eq!(decrypt_fernet(&token, &key_b), Err(DoxcerError::DecryptionFailed));
+        assert_eq!(decrypt_fernet(&token, &key_a).unwrap(), "secret");
+        assert_eq!(builds(), before + 1);
+    }
+
+
+    #[test]
+    fn secret_key_precedence() {
+        // `key_override` wins over `{name}_ENC_KEY`, which wins over the
+        // global `ENCRYPTION_PASSWORD`. The variable names are unique to this
+        // test, which is the only one to set `ENCRYPTION_PASSWORD`.
+        let global = generate_fernet_key();
+        let scoped = generate_fernet_key();
+        let explicit = generate_fernet_key();
+        unsafe {
+            env::set_var("ENCRYPTION_PASSWORD", &global);
+            env::set_var("DOXCER_TEST_SCOPED_KEY", &scoped);
+            env::set_var("DOXCER_TEST_GLOBAL_ENC", encrypt_fernet("global", &global).unwrap());
+            env::set_var("DOXCER_TEST_SCOPED_ENC", encrypt_fernet("scoped", &scoped).unwrap());
+            env::set_var("DOXCER_TEST_SCOPED_ENC_KEY", "DOXCER_TEST_SCOPED_KEY");
+            env::set_var("DOXCER_TEST_EXPLICIT_ENC", encrypt_fernet("explicit", &explicit).unwrap());
+            env::set_var("DOXCER_TEST_EXPLICIT_ENC_KEY", "DOXCER_TEST_SCOPED_KEY");
+        }
+
+        assert_eq!(secret_key_name("DOXCER_TEST_GLOBAL"), "ENCRYPTION_PASSWORD");
+        assert_eq!(env_secret("DOXCER_TEST_GLOBAL", None).unwrap(), "global");
+
+        assert_eq!(secret_key_name("DOXCER_TEST_SCOPED"), "DOXCER_TEST_SCOPED_KEY");
+        assert_eq!(env_secret("DOXCER_TEST_SCOPED", None).unwrap(), "scoped");
+
+        assert_eq!(env_secret("DOXCER_TEST_EXPLICIT", None), Err(DoxcerError::DecryptionFailed));
+        assert_eq!(env_secret("DOXCER_TEST_EXPLICIT", Some(&explicit)).unwrap(), "explicit");
+    }
+
+
+    #[test]
+    fn missing_scoped_key_names_the_key_variable() {
+        unsafe {
+            env::set_var("DOXCER_TEST_UNSET_ENC", "token");
+            env::set_var("DOXCER_TEST_UNSET_ENC_KEY", "DOXCER_TEST_UNSET_KEY");
+        }
+        let error = env_secret("DOXCER_TEST_UNSET", None).unwrap_err();
+        assert_eq!(
+            error,
+            DoxcerError::MissingFernetKey { key_name: "DOXCER_TEST_UNSET_KEY".to_string(), encrypted: Some("DOXCER_TEST_UNSET_ENC".to_string()) }
+        );
+    }
+}
@@ -0,0 +1,150 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::env;
+use std::io::Read;
+
+// Internal Modules
+use crate::clipboard::format_size;
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const DEFAULT_PROVIDER: &str = "openai";
+pub const FALLBACK_MAX_REQUEST_BYTES: u64 = 4 * 1024 * 1024;
+pub const PROVIDER_REQUEST_LIMITS: [(&str, u64); 3] = [
+    ("openai", 32 * 1024 * 1024),
+    ("azure", 4 * 1024 * 1024),
+    ("anthropic", 32 * 1024 * 1024),
+];
+
+
+// ====================================================
+//  Request Size Limits
+// ====================================================
+pub fn max_request_bytes(provider: &str) -> Result<u64, DoxcerError> {
+
+    // Returns the largest request body that may be sent to a provider.
+    //
+    // # Description
+    // `DOXCER_MAX_REQUEST_BYTES` overrides the table, e.g. for a gateway with a
+    // stricter limit. Unknown providers get a conservative 4 MB.
+
+    if let Ok(v) = env::var("DOXCER_MAX_REQUEST_BYTES") {
+        return v
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| DoxcerError::Limit(format!("DOXCER_MAX_REQUEST_BYTES must be a positive integer, got {v:?}")));
+    }
+    Ok(PROVIDER_REQUEST_LIMITS
+        .iter()
+        .find(|(name, _)| *name == provider)
+        .map(|(_, limit)| *limit)
+        .unwrap_or(FALLBACK_MAX_REQUEST_BYTES))
+}
+
+
+pub fn check_request_size(provider: &str, body_len: usize) -> Result<(), DoxcerError> {
+
+    // Fails fast when a serialized request body exceeds the provider's limit.
+    //
+    // # Parameters
+    // * `provider` – The provider the body is meant for.
+    // * `body_len` – Length of the exact bytes that would be sent.
+    //
+    // # Returns
+    // * `Ok(())` if the body fits.
+    // * `Err(DoxcerError)` naming the size, the limit and what to do about it.
+
+    let limit = max_request_bytes(provider)?;
+    if body_len as u64 <= limit {
+        return Ok(());
+    }
+    Err(DoxcerError::Limit(format!(
+        "request body is {} ({body_len} bytes), over the {} limit for {provider} (DOXCER_MAX_REQUEST_BYTES); \
+         trim the notebook or split it before documenting",
+        format_size(body_len),
+        format_size(limit as usize)
+    )))
+}
+
+
+pub fn read_capped(reader: impl Read, limit: u64) -> Result<Result<Vec<u8>, Vec<u8>>, DoxcerError> {
+
+    // Reads at most `limit` bytes from a response body.
+    //
+    // # Returns
+    // * `Ok(Ok(body))` – the whole body fit within the limit.
+    // * `Ok(Err(prefix))` – the body was larger; `prefix` holds the first `limit` bytes.
+    // * `Err(DoxcerError)` – reading the body failed.
+
+    let mut body = Vec::new();
+    reader
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut body)
+        .map_err(|e| DoxcerError::Provider(format!("Failed to read response: {e}")))?;
+    if body.len() as u64 > limit {
+        body.truncate(limit as usize);
+        return Ok(Err(body));
+    }
+    Ok(Ok(body))
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+// These rely on `DOXCER_MAX_REQUEST_BYTES` being unset, so nothing here sets it.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+
+    #[test]
+    fn body_at_the_limit_fits() {
+        let limit = max_request_bytes("azure").unwrap() as usize;
+        assert_eq!(limit, 4 * 1024 * 1024);
+        assert!(check_request_size("azure", limit).is_ok());
+    }
+
+
+    #[test]
+    fn body_one_byte_over_the_limit_fails() {
+        let limit = max_request_bytes("azure").unwrap() as usize;
+        let error = check_request_size("azure", limit + 1).unwrap_err().to_string();
+        assert!(error.contains(&format!("({} bytes)", limit + 1)), "{error}");
+        assert!(error.contains("limit for azure"), "{error}");
+        assert!(error.contains("DOXCER_MAX_REQUEST_BYTES"), "{error}");
+    }
+
+
+    #[test]
+    fn unknown_providers_get_the_fallback_limit() {
+        assert_eq!(max_request_bytes("some-gateway").unwrap(), FALLBACK_MAX_REQUEST_BYTES);
+        assert_eq!(max_request_bytes("openai").unwrap(), 32 * 1024 * 1024);
+    }
+
+
+    #[test]
+    fn size_is_measured_after_escaping() {
+        // Quotes, backslashes, newlines and control characters grow when the
+        // notebook is put in a JSON string; the check must see those bytes.
+        let notebook = "path = \"C:\\\\data\"\n\tprint('é')\u{1}\n".repeat(1000);
+        let body = serde_json::to_vec(&json!({ "model": "gpt-5", "input": notebook })).unwrap();
+        assert!(body.len() > notebook.len());
+
+        let limit = max_request_bytes("azure").unwrap() as usize;
+        let fill = "\"".repeat(limit / 2);
+        let body = serde_json::to_vec(&json!({ "input": fill })).unwrap();
+        assert!(fill.len() < limit && body.len() > limit);
+        assert!(check_request_size("azure", body.len()).is_err());
+    }
+}
@@ -0,0 +1,441 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::{BTreeMap, BTreeSet};
+
+// Internal Modules
+use crate::frontmatter::split_front_matter;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const READ_CALLS: [&str; 3] = ["spark.read.table(", "spark.table(", ".load("];
+pub const WRITE_CALLS: [&str; 3] = [".saveAsTable(", ".insertInto(", ".save("];
+pub const SQL_READ_KEYWORDS: [&str; 2] = ["FROM", "JOIN"];
+pub const SQL_WRITE_KEYWORDS: [&str; 2] = ["INTO", "TABLE"];
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Lineage {
+    pub reads: BTreeSet<String>,
+    pub writes: BTreeSet<String>,
+}
+
+
+// ====================================================
+//  Extraction
+// ====================================================
+pub fn extract_lineage(source: &str) -> Lineage {
+
+    // Extracts the tables a PySpark notebook reads and writes.
+    //
+    // # Description
+    // A heuristic, not a parser: string literals passed to `spark.read.table`,
+    // `spark.table` and `.load` count as reads, those passed to `.saveAsTable`,
+    // `.insertInto` and `.save` as writes. Inside `spark.sql("...")`, names after
+    // `FROM`/`JOIN` are reads and names after `INSERT INTO`/`CREATE TABLE` are
+    // writes. A table the notebook writes is not also listed as a read.
+
+    let mut lineage = Lineage::default();
+
+    for call in READ_CALLS {
+        lineage.reads.extend(literal_arguments(source, call));
+    }
+    for call in WRITE_CALLS {
+        lineage.writes.extend(literal_arguments(source, call));
+    }
+    for sql in literal_arguments(source, "spark.sql(") {
+        let words: Vec<&str> = sql.split_whitespace().collect();
+        for (i, word) in words.iter().enumerate() {
+            let keyword = word.to_ascii_uppercase();
+            let is_read = SQL_READ_KEYWORDS.contains(&keyword.as_str());
+            let is_write = SQL_WRITE_KEYWORDS.contains(&keyword.as_str());
+            if !is_read && !is_write {
+                continue;
+            }
+            let mut next = i + 1;
+            if words.get(next).is_some_and(|w| w.eq_ignore_ascii_case("IF")) {
+                next += 3;
+            }
+            let Some(name) = words.get(next).map(|w| w.trim_matches(|c: char| matches!(c, '`' | ')' | ';' | ','))) else {
+                continue;
+            };
+            if name.is_empty() || name.starts_with('(') || name.eq_ignore_ascii_case("SELECT") {
+                continue;
+            }
+            if is_read {
+                lineage.reads.insert(name.to_string());
+            } else {
+                lineage.writes.insert(name.to_string());
+            }
+        }
+    }
+
+    lineage.reads.retain(|t| !lineage.writes.contains(t));
+    lineage
+}
+
+
+fn literal_arguments(source: &str, call: &str) -> Vec<String> {
+
+    // Returns the first string-literal argument of every occurrence of `call`.
+
+    let mut found = Vec::new();
+    let mut rest = source;
+    while let Some(pos) = rest.find(call) {
+        rest = &rest[pos + call.len()..];
+        let arg = rest.trim_start();
+        let quote = arg.trim_start_matches("f").chars().next();
+        if let Some(q @ ('"' | '\'')) = quote {
+            let body = &arg[arg.find(q).unwrap_or(0)..];
+            let triple = body.starts_with(&format!("{q}{q}{q}"));
+            let (open, close) = if triple { (3, format!("{q}{q}{q}")) } else { (1, q.to_string()) };
+            if let Some(end) = body[open..].find(close.as_str()) {
+                found.push(body[open..open + end].to_string());
+            }
+        }
+    }
+    found
+}
+
+
+// ====================================================
+//  Dependency Graph
+// ====================================================
+pub fn mermaid_graph(notebooks: &[(String, Lineage)]) -> String {
+
+    // Renders a Mermaid dependency graph between notebooks.
+    //
+    // # Description
+    // Draws `writer --> reader` for every table one notebook writes and another
+    // reads, labelled with the table. Notebooks without any such link are shown
+    // as lone nodes so the graph lists the whole workspace.
+
+    let mut writers: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, (_, lineage)) in notebooks.iter().enumerate() {
+        for table in &lineage.writes {
+            writers.entry(table.as_str()).or_default().push(i);
+        }
+    }
+
+    let mut out = String::from("```mermaid\ngraph LR\n");
+    for (i, (name, _)) in notebooks.iter().enumerate() {
+        out.push_str(&format!("    n{i}[\"{}\"]\n", name.replace('"', "'")));
+    }
+    for (reader, (_, lineage)) in notebooks.iter().enumerate() {
+        for table in &lineage.reads {
+            for writer in writers.get(table.as_str()).into_iter().flatten() {
+                if *writer != reader {
+                    out.push_str(&format!("    n{writer} -->|{}| n{reader}\n", table.replace('|', "/")));
+                }
+            }
+        }
+    }
+    out.push_str("```\n");
+    out
+}
+
+
+pub fn notebook_dependencies(lineages: &[Lineage]) -> Vec<BTreeSet<usize>> {
+
+    // Returns, per notebook, the notebooks it depends on: those that write a
+    // table it reads.
+
+    let mut writers: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, lineage) in lineages.iter().enumerate() {
+        for table in &lineage.writes {
+            writers.entry(table.as_str()).or_default().push(i);
+        }
+    }
+    lineages
+        .iter()
+        .enumerate()
+        .map(|(reader, lineage)| {
+            lineage
+                .reads
+                .iter()
+                .flat_map(|table| writers.get(table.as_str()).into_iter().flatten())
+                .copied()
+                .filter(|writer| *writer != reader)
+                .collect()
+        })
+        .collect()
+}
+
+
+// ====================================================
+//  Processing Order
+// ====================================================
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DependencyOrder {
+    pub order: Vec<usize>,
+    pub cycles: Vec<Vec<usize>>,
+}
+
+
+pub fn dependency_order(upstream: &[BTreeSet<usize>]) -> DependencyOrder {
+
+    // Orders notebooks so producers come before their consumers.
+    //
+    // # Description
+    // Notebooks that depend on each other in a cycle are grouped (strongly
+    // connected components) and kept in path (index) order within the group;
+    // each such group is reported in `cycles`. Between independent notebooks the
+    // path order is kept as well, so the result is stable.
+    //
+    // # Parameters
+    // * `upstream` – Per notebook, the indices of the notebooks it depends on.
+
+    let components = strongly_connected(upstream);
+    let mut component_of = vec![0; upstream.len()];
+    for (c, members) in components.iter().enumerate() {
+        for member in members {
+            component_of[*member] = c;
+        }
+    }
+
+    let mut waiting: Vec<BTreeSet<usize>> = components
+        .iter()
+        .enumerate()
+        .map(|(c, members)| {
+            members
+                .iter()
+                .flat_map(|m| upstream[*m].iter())
+                .map(|u| component_of[*u])
+                .filter(|u| *u != c)
+                .collect()
+        })
+        .collect();
+
+    // Ready components are taken by their first notebook, keeping path order.
+    let mut ready: BTreeSet<(usize, usize)> = BTreeSet::new();
+    for (c, upstream) in waiting.iter().enumerate() {
+        if upstream.is_empty() {
+            ready.insert((components[c][0], c));
+        }
+    }
+
+    let mut result = DependencyOrder::default();
+    while let Some((_, c)) = ready.pop_first() {
+        result.order.extend(&components[c]);
+        if components[c].len() > 1 {
+            result.cycles.push(components[c].clone());
+        }
+        for (other, upstream) in waiting.iter_mut().enumerate() {
+            if upstream.remove(&c) && upstream.is_empty() {
+                ready.insert((components[other][0], other));
+            }
+        }
+    }
+    result
+}
+
+
+fn strongly_connected(upstream: &[BTreeSet<usize>]) -> Vec<Vec<usize>> {
+
+    // Tarjan's algorithm; every component is returned with sorted members.
+
+    struct Tarjan<'a> {
+        upstream: &'a [BTreeSet<usize>],
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next: usize,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, v: usize) {
+            self.index[v] = Some(self.next);
+            self.low[v] = self.next;
+            self.next += 1;
+            self.stack.push(v);
+            self.on_stack[v] = true;
+            for &w in self.upstream[v].iter() {
+                match self.index[w] {
+                    None => {
+                        self.visit(w);
+                        self.low[v] = self.low[v].min(self.low[w]);
+                    }
+                    Some(index) if self.on_stack[w] => self.low[v] = self.low[v].min(index),
+                    Some(_) => {}
+                }
+            }
+            if Some(self.low[v]) == self.index[v] {
+                let mut component = Vec::new();
+                while let Some(w) = self.stack.pop() {
+                    self.on_stack[w] = false;
+                    component.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                self.components.push(component);
+            }
+        }
+    }
+
+    let n = upstream.len();
+    let mut tarjan = Tarjan {
+        upstream,
+        index: vec![None; n],
+        low: vec![0; n],
+        on_stack: vec![false; n],
+        stack: Vec::new(),
+        next: 0,
+        components: Vec::new(),
+    };
+    for v in 0..n {
+        if tarjan.index[v].is_none() {
+            tarjan.visit(v);
+        }
+    }
+    tarjan.components
+}
+
+
+// ====================================================
+//  Upstream Summaries
+// ====================================================
+pub fn summary_line(markdown: &str) -> Option<String> {
+
+    // Returns the first prose line of a generated document as its one-line
+    // summary, cut at 200 characters.
+
+    let (_, body) = split_front_matter(markdown);
+    let mut fence = false;
+    let line = body.lines().map(str::trim).find(|line| {
+        if line.starts_with("```") {
+            fence = !fence;
+            return false;
+        }
+        !fence && !line.is_empty() && !line.starts_with(['#', '|', '-', '>', '<', '!']) && *line != "---"
+    })?;
+    let line = line.replace("**", "");
+    Some(match line.char_indices().nth(200) {
+        Some((cut, _)) => format!("{}…", line[..cut].trim_end()),
+        None => line,
+    })
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lineage(reads: &[&str], writes: &[&str]) -> Lineage {
+        Lineage {
+            reads: reads.iter().map(|t| t.to_string()).collect(),
+            writes: writes.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    fn set(items: &[usize]) -> BTreeSet<usize> {
+        items.iter().copied().collect()
+    }
+
+
+    #[test]
+    fn extracts_reads_and_writes_from_calls_and_sql() {
+        let source = r#"
+df = spark.read.table("bronze.orders")
+ref = spark.table('bronze.customers')
+raw = spark.read.format("delta").load("/lake/raw")
+spark.sql("""
+    INSERT INTO silver.orders
+    SELECT * FROM bronze.orders o JOIN `dim.dates` d ON o.day = d.day
+""")
+spark.sql("CREATE TABLE IF NOT EXISTS silver.log (id INT)")
+df.write.saveAsTable("silver.summary")
+"#;
+        let found = extract_lineage(source);
+        assert_eq!(found, lineage(
+            &["/lake/raw", "bronze.customers", "bronze.orders", "dim.dates"],
+            &["silver.log", "silver.orders", "silver.summary"],
+        ));
+    }
+
+
+    #[test]
+    fn a_table_the_notebook_writes_is_not_a_read() {
+        let source = r#"spark.sql("INSERT INTO t1 SELECT * FROM t1")
+spark.table("t2").write.insertInto("t2")"#;
+        assert_eq!(extract_lineage(source), lineage(&[], &["t1", "t2"]));
+    }
+
+
+    #[test]
+    fn builds_edges_from_writers_to_readers() {
+        let lineages = [
+            lineage(&["raw"], &["bronze"]),
+            lineage(&["bronze"], &["silver"]),
+            lineage(&["bronze", "silver"], &["gold"]),
+            lineage(&["unknown"], &[]),
+        ];
+        assert_eq!(notebook_dependencies(&lineages), vec![set(&[]), set(&[0]), set(&[0, 1]), set(&[])]);
+
+        let named: Vec<_> = ["ingest", "clean", "report", "adhoc"].iter().map(|n| n.to_string()).zip(lineages).collect();
+        assert_eq!(
+            mermaid_graph(&named),
+            "```mermaid\ngraph LR\n    n0[\"ingest\"]\n    n1[\"clean\"]\n    n2[\"report\"]\n    n3[\"adhoc\"]\n    \
+             n0 -->|bronze| n1\n    n0 -->|bronze| n2\n    n1 -->|silver| n2\n```\n"
+        );
+    }
+
+
+    #[test]
+    fn orders_producers_before_consumers_keeping_path_order() {
+        let upstream = [set(&[2]), set(&[]), set(&[1]), set(&[])];
+        let order = dependency_order(&upstream);
+        assert_eq!(order.order, [1, 2, 0, 3]);
+        assert!(order.cycles.is_empty());
+    }
+
+
+    #[test]
+    fn groups_a_cycle_and_reports_it() {
+        // 0 and 2 read each other's tables; 1 depends on the cycle, 3 is apart.
+        let lineages = [
+            lineage(&["b"], &["a"]),
+            lineage(&["a"], &[]),
+            lineage(&["a"], &["b"]),
+            lineage(&[], &["c"]),
+        ];
+        let upstream = notebook_dependencies(&lineages);
+        assert_eq!(upstream, vec![set(&[2]), set(&[0]), set(&[0]), set(&[])]);
+
+        let order = dependency_order(&upstream);
+        assert_eq!(order.cycles, vec![vec![0, 2]]);
+        assert_eq!(order.order, [0, 2, 1, 3]);
+    }
+
+
+    #[test]
+    fn a_self_write_is_not_a_dependency() {
+        assert_eq!(notebook_dependencies(&[lineage(&["t"], &[]), lineage(&["t"], &["t"])]), vec![set(&[1]), set(&[])]);
+        assert_eq!(dependency_order(&[set(&[])]).cycles, Vec::<Vec<usize>>::new());
+    }
+
+
+    #[test]
+    fn summary_line_skips_front_matter_headings_and_code() {
+        let doc = "---\ntitle: x\n---\n# Title\n\n```python\nprint()\n```\n| a |\nLoads **orders** daily.\n";
+        assert_eq!(summary_line(doc).as_deref(), Some("Loads orders daily."));
+        assert_eq!(summary_line("# Only a heading\n"), None);
+        assert!(summary_line(&"x".repeat(300)).unwrap().ends_with('…'));
+    }
+}
@@ -0,0 +1,630 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::BTreeSet;
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+
+// External Libraries
+use ignore::WalkBuilder;
+use serde::Deserialize;
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkPolicy {
+    #[default]
+    Warn,
+    Strip,
+    Fail,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkForm {
+    Inline,
+    Reference(String),
+    Definition(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub form: LinkForm,
+    pub image: bool,
+    pub text: Range<usize>,
+    pub target: Range<usize>,
+    pub span: Range<usize>,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    Found,
+    Rewrite(String),
+    Missing,
+    External { host: String, allowed: bool },
+    Skip,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LinkReport {
+    pub markdown: String,
+    pub rewritten: Vec<String>,
+    pub problems: Vec<String>,
+}
+
+impl fmt::Display for LinkPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkPolicy::Warn => write!(f, "warn"),
+            LinkPolicy::Strip => write!(f, "strip"),
+            LinkPolicy::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+impl FromStr for LinkPolicy {
+    type Err = DoxcerError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "warn" => Ok(LinkPolicy::Warn),
+            "strip" => Ok(LinkPolicy::Strip),
+            "fail" => Ok(LinkPolicy::Fail),
+            other => Err(DoxcerError::Usage(format!("expected warn, strip or fail, got {other:?}"))),
+        }
+    }
+}
+
+
+// ====================================================
+//  Extraction
+// ====================================================
+pub fn extract_links(markdown: &str) -> Vec<Link> {
+
+    // Finds the links and images in a Markdown document.
+    //
+    // # Description
+    // Recognises inline links and images (`[text](target "title")`,
+    // `![alt](<tar get>)`), reference-style ones (`[text][label]`, `[label][]`
+    // and `[label]` when `label` is defined) and the definitions themselves
+    // (`[label]: target`). Links nested in link text, such as a badge
+    // `[![alt](img)](url)`, and links inside table cells are found too. Fenced
+    // code blocks and inline code spans are skipped. All ranges are byte
+    // offsets into `markdown`.
+
+    let mut links = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut offset = 0;
+    for (index, raw_line) in markdown.split_inclusive('\n').enumerate() {
+        let line_start = offset;
+        offset += raw_line.len();
+        let line = raw_line.trim_end_matches(['\n', '\r']);
+        let trimmed = line.trim_start();
+
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = Some(marker);
+            continue;
+        }
+        if let Some(link) = definition(line, line_start, index + 1) {
+            links.push(link);
+            continue;
+        }
+        inline_links(line, line_start, index + 1, &mut links);
+    }
+
+    let defined: BTreeSet<String> = links
+        .iter()
+        .filter_map(|l| match &l.form {
+            LinkForm::Definition(label) => Some(label.clone()),
+            _ => None,
+        })
+        .collect();
+    links.retain(|l| match &l.form {
+        LinkForm::Reference(label) => defined.contains(label),
+        _ => true,
+    });
+    links
+}
+
+
+fn definition(line: &str, line_start: usize, line_no: usize) -> Option<Link> {
+
+    // Parses a reference definition line: `[label]: target "title"`.
+
+    let indent = line.len() - line.trim_start().len();
+    if indent > 3 || !line[indent..].starts_with('[') {
+        return None;
+    }
+    let close = line[indent..].find("]:")? + indent;
+    let label = &line[indent + 1..close];
+    if label.trim().is_empty() || label.starts_with('^') {
+        return None;
+    }
+    let rest = &line[close + 2..];
+    let target_start = close + 2 + (rest.len() - rest.trim_start().len());
+    let (target, _) = destination(&line[target_start..])?;
+    let target = target.start + target_start..target.end + target_start;
+    Some(Link {
+        form: LinkForm::Definition(normalize_label(label)),
+        image: false,
+        text: line_start + indent + 1..line_start + close,
+        target: line_start + target.start..line_start + target.end,
+        span: line_start..line_start + line.len(),
+        line: line_no,
+    })
+}
+
+
+fn inline_links(line: &str, line_start: usize, line_no: usize, links: &mut Vec<Link>) {
+
+    // Collects the inline and reference-style links of one line.
+
+    let bytes = line.as_bytes();
+    let mut code = false;
+    let mut label: Option<Range<usize>> = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(skip) = label.take_if(|l| l.start == i) {
+            i = skip.end;
+            continue;
+        }
+        match bytes[i] {
+            b'\\' => {
+                i += 2;
+                continue;
+            }
+            b'`' => code = !code,
+            b'[' if !code => {
+                let image = i > 0 && bytes[i - 1] == b'!' && !(i > 1 && bytes[i - 2] == b'\\');
+                let start = if image { i - 1 } else { i };
+                if let Some(close) = matching_bracket(line, i) {
+                    let text = i + 1..close;
+                    let after = &line[close + 1..];
+                    if let Some(inner) = after.strip_prefix('(')
+                        && let Some((target, end)) = destination(inner)
+                        && inner[end..].starts_with(')')
+                    {
+                        let base = close + 2;
+                        links.push(Link {
+                            form: LinkForm::Inline,
+                            image,
+                            text: line_start + text.start..line_start + text.end,
+                            target: line_start + base + target.start..line_start + base + target.end,
+                            span: line_start + start..line_start + base + end + 1,
+                            line: line_no,
+                        });
+                    } else if let Some(inner) = after.strip_prefix('[')
+                        && let Some(label_end) = inner.find(']')
+                    {
+                        let name = if label_end == 0 { &line[text.clone()] } else { &inner[..label_end] };
+                        label = Some(close + 1..close + 2 + label_end + 1);
+                        links.push(Link {
+                            form: LinkForm::Reference(normalize_label(name)),
+                            image,
+                            text: line_start + text.start..line_start + text.end,
+                            target: line_start + close + 2..line_start + close + 2 + label_end,
+                            span: line_start + start..line_start + close + 3 + label_end,
+                            line: line_no,
+                        });
+                    } else if !after.starts_with(':') {
+                        links.push(Link {
+                            form: LinkForm::Reference(normalize_label(&line[text.clone()])),
+                            image,
+                            text: line_start + text.start..line_start + text.end,
+                            target: line_start + text.start..line_start + text.end,
+                            span: line_start + start..line_start + close + 1,
+                            line: line_no,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+
+fn matching_bracket(line: &str, open: usize) -> Option<usize> {
+
+    // Returns the index of the `]` closing the `[` at `open`, allowing nesting.
+
+    let mut depth = 0;
+    let mut escaped = false;
+    for (i, b) in line.bytes().enumerate().skip(open) {
+        match b {
+            _ if escaped => escaped = false,
+            b'\\' => escaped = true,
+            b'[' => depth += 1,
+            b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+
+fn destination(text: &str) -> Option<(Range<usize>, usize)> {
+
+    // Parses a link destination, optionally followed by a title.
+    //
+    // # Returns
+    // * `Some((target, end))` – the target's range and the index of the closing
+    //   `)` (or the end of `text` for definitions).
+
+    let lead = text.len() - text.trim_start().len();
+    let rest = &text[lead..];
+    let target = if let Some(inner) = rest.strip_prefix('<') {
+        let close = inner.find('>')?;
+        lead + 1..lead + 1 + close
+    } else {
+        let mut depth = 0usize;
+        let len = rest
+            .char_indices()
+            .find(|(_, c)| match c {
+                '(' => {
+                    depth += 1;
+                    false
+                }
+                ')' if depth == 0 => true,
+                ')' => {
+                    depth -= 1;
+                    false
+                }
+                c => c.is_whitespace(),
+            })
+            .map_or(rest.len(), |(i, _)| i);
+        lead..lead + len
+    };
+    let end = match text[target.end..].find(')') {
+        Some(close) => target.end + close,
+        None => text.len(),
+    };
+    Some((target, end))
+}
+
+
+fn normalize_label(label: &str) -> String {
+
+    // Reference labels match case-insensitively and ignore extra whitespace.
+
+    label.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+
+// ====================================================
+//  Resolution
+// ====================================================
+pub fn resolve_target(target: &str, base: &str, files: &BTreeSet<String>, allowlist: &[String]) -> Resolution {
+
+    // Resolves a link target against the workspace file tree.
+    //
+    // # Description
+    // `base` is the folder the document lives in, relative to the workspace
+    // root and `/`-separated (empty for the root); `files` holds every file of
+    // the workspace in the same form. A target that only misses by case or by
+    // its extension is rewritten to the real file, relative to `base`, keeping
+    // its `#fragment`. External `http(s)://` targets are allowed when their host
+    // is (a subdomain of) a domain in `allowlist`. Anchors and other schemes,
+    // such as `mailto:`, are skipped.
+
+    let target = target.trim();
+    if target.is_empty() {
+        return Resolution::Missing;
+    }
+    if target.starts_with('#') {
+        return Resolution::Skip;
+    }
+    if let Some(rest) = target.strip_prefix("https://").or_else(|| target.strip_prefix("http://")).or_else(|| target.strip_prefix("//")) {
+        let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let host = host.rsplit('@').next().unwrap_or(host).split(':').next().unwrap_or(host).to_lowercase();
+        let allowed = allowlist.iter().any(|domain| {
+            let domain = domain.trim().trim_start_matches("*.").to_lowercase();
+            host == domain || host.ends_with(&format!(".{domain}"))
+        });
+        return Resolution::External { host, allowed };
+    }
+    if target.split_once(':').is_some_and(|(scheme, _)| !scheme.is_empty() && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c)) && !scheme.contains('/')) {
+        return Resolution::Skip;
+    }
+
+    let cut = target.find(['#', '?']).unwrap_or(target.len());
+    let (path, suffix) = target.split_at(cut);
+    let decoded = path.replace("%20", " ");
+    let Some(joined) = join_relative(if decoded.starts_with('/') { "" } else { base }, &decoded) else {
+        return Resolution::Missing;
+    };
+    if joined.is_empty() || files.contains(&joined) || files.iter().any(|f| f.starts_with(&format!("{joined}/"))) {
+        return Resolution::Found;
+    }
+
+    let lower = joined.to_lowercase();
+    let by_case: Vec<&String> = files.iter().filter(|f| f.to_lowercase() == lower).collect();
+    let by_stem: Vec<&String> = files
+        .iter()
+        .filter(|f| Path::new(f.as_str()).extension().is_some() && f.rsplit_once('.').is_some_and(|(stem, _)| stem.to_lowercase() == lower))
+        .collect();
+    let found = match (by_case.as_slice(), by_stem.as_slice()) {
+        ([one], _) | ([], [one]) => *one,
+        _ => return Resolution::Missing,
+    };
+    let mut rewritten = relative_to(base, found);
+    if path.starts_with("./") && !rewritten.starts_with("../") {
+        rewritten = format!("./{rewritten}");
+    }
+    Resolution::Rewrite(format!("{}{suffix}", rewritten.replace(' ', "%20")))
+}
+
+
+fn join_relative(base: &str, target: &str) -> Option<String> {
+
+    // Joins a relative target onto `base`, resolving `.` and `..`; `None` when
+    // it climbs out of the workspace.
+
+    let mut parts: Vec<&str> = base.split('/').filter(|p| !p.is_empty()).collect();
+    for part in target.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+
+fn relative_to(base: &str, file: &str) -> String {
+
+    // Returns the path of `file` as seen from the folder `base`.
+
+    let base: Vec<&str> = base.split('/').filter(|p| !p.is_empty()).collect();
+    let file: Vec<&str> = file.split('/').collect();
+    let common = base.iter().zip(&file).take_while(|(a, b)| a == b).count();
+    let mut parts = vec![".."; base.len() - common];
+    parts.extend(&file[common..]);
+    parts.join("/")
+}
+
+
+pub fn workspace_files(root: &Path) -> BTreeSet<String> {
+
+    // Lists every file below `root` that git would not ignore, `/`-separated
+    // and relative to `root`.
+
+    WalkBuilder::new(root)
+        .hidden(false)
+        .filter_entry(|e| e.file_name() != ".git")
+        .build()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+        .filter_map(|e| {
+            let relative = e.path().strip_prefix(root).ok()?;
+            let parts: Vec<String> = relative.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect();
+            Some(parts.join("/"))
+        })
+        .collect()
+}
+
+
+// ====================================================
+//  Normalization
+// ====================================================
+pub fn normalize_links(markdown: &str, base: &str, files: &BTreeSet<String>, allowlist: &[String], policy: LinkPolicy) -> Result<LinkReport, DoxcerError> {
+
+    // Checks every link and image of a generated document and applies the policy.
+    //
+    // # Description
+    // Near-misses are always rewritten (see [`resolve_target`]). Missing files
+    // and external hosts outside the allowlist are problems: with `Warn` they
+    // are only reported, with `Strip` the link is replaced by its text (an
+    // image by its alt text, a definition is removed), and with `Fail` the
+    // document is refused.
+    //
+    // # Returns
+    // * `Ok(LinkReport)` – the new Markdown, the rewrites and the problems.
+    // * `Err(DoxcerError)` – with `Fail`, every problem found.
+
+    let links = extract_links(markdown);
+    let mut report = LinkReport::default();
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    let mut stripped_labels = BTreeSet::new();
+
+    for link in links.iter().filter(|l| !matches!(l.form, LinkForm::Reference(_))) {
+        let target = &markdown[link.target.clone()];
+        let what = if link.image { "image" } else { "link" };
+        let problem = match resolve_target(target, base, files, allowlist) {
+            Resolution::Found | Resolution::Skip | Resolution::External { allowed: true, .. } => continue,
+            Resolution::Rewrite(new) => {
+                report.rewritten.push(format!("line {}: {what} {target} -> {new}", link.line));
+                edits.push((link.target.clone(), new));
+                continue;
+            }
+            Resolution::Missing => format!("line {}: {what} target {target} does not exist", link.line),
+            Resolution::External { host, .. } => format!("line {}: external {what} {target} ({host} is not in link_allowlist)", link.line),
+        };
+        report.problems.push(problem);
+        if policy != LinkPolicy::Strip {
+            continue;
+        }
+        match &link.form {
+            LinkForm::Definition(label) => {
+                stripped_labels.insert(label.clone());
+                let end = markdown[link.span.end..].find('\n').map_or(link.span.end, |i| link.span.end + i + 1);
+                edits.push((link.span.start..end, String::new()));
+            }
+            _ => unwrap_link(link, &mut edits),
+        }
+    }
+    for link in &links {
+        if let LinkForm::Reference(label) = &link.form
+            && stripped_labels.contains(label)
+        {
+            unwrap_link(link, &mut edits);
+        }
+    }
+
+    if policy == LinkPolicy::Fail && !report.problems.is_empty() {
+        return Err(DoxcerError::Usage(format!("{} broken link(s): {}", report.problems.len(), report.problems.join("; "))));
+    }
+
+    edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    let mut out = markdown.to_string();
+    for (range, replacement) in edits {
+        out.replace_range(range, &replacement);
+    }
+    report.markdown = out;
+    Ok(report)
+}
+
+
+fn unwrap_link(link: &Link, edits: &mut Vec<(Range<usize>, String)>) {
+
+    // Replaces a link by its text: drops the `[` / `![` before the text and
+    // everything after it, so edits inside the text (a nested image) survive.
+
+    edits.push((link.text.end..link.span.end, String::new()));
+    edits.push((link.span.start..link.text.start, String::new()));
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(markdown: &str) -> Vec<(LinkForm, bool, &str, &str, usize)> {
+        extract_links(markdown)
+            .into_iter()
+            .map(|l| (l.form, l.image, &markdown[l.text], &markdown[l.target], l.line))
+            .collect()
+    }
+
+    fn files(paths: &[&str]) -> BTreeSet<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    fn reference(label: &str) -> LinkForm {
+        LinkForm::Reference(label.to_string())
+    }
+
+    fn definition(label: &str) -> LinkForm {
+        LinkForm::Definition(label.to_string())
+    }
+
+
+    #[test]
+    fn finds_reference_links_and_images_inside_tables() {
+        let markdown = "| Table | Docs |\n\
+                        |---|---|\n\
+                        | orders | [spec][Orders  Spec] and ![logo][] |\n\
+                        | [orders spec] | [undefined] |\n\
+                        \n\
+                        [orders spec]: ./specs/orders.md\n\
+                        [logo]: img/logo.png \"Logo\"\n";
+        assert_eq!(found(markdown), [
+            (reference("orders spec"), false, "spec", "Orders  Spec", 3),
+            (reference("logo"), true, "logo", "", 3),
+            (reference("orders spec"), false, "orders spec", "orders spec", 4),
+            (definition("orders spec"), false, "orders spec", "./specs/orders.md", 6),
+            (definition("logo"), false, "logo", "img/logo.png", 7),
+        ]);
+    }
+
+
+    #[test]
+    fn finds_inline_images_and_badges_in_table_cells() {
+        let markdown = "| ![chart](img/chart.png) | [![build](img/b.svg)](https://ci.example.com) | `[x](code.md)` |\n";
+        assert_eq!(found(markdown), [
+            (LinkForm::Inline, true, "chart", "img/chart.png", 1),
+            (LinkForm::Inline, false, "![build](img/b.svg)", "https://ci.example.com", 1),
+            (LinkForm::Inline, true, "build", "img/b.svg", 1),
+        ]);
+    }
+
+
+    #[test]
+    fn skips_fenced_code_blocks() {
+        let markdown = "```md\n[a](a.md)\n```\n~~~\n[b]: b.md\n~~~\n[c](<c d.md> \"title\")\n";
+        assert_eq!(found(markdown), [(LinkForm::Inline, false, "c", "c d.md", 7)]);
+    }
+
+
+    #[test]
+    fn resolves_targets_against_the_workspace() {
+        let files = files(&["docs/img/chart.png", "docs/Orders.md", "README.md"]);
+        let allow = ["example.com".to_string()];
+        let resolve = |target: &str| resolve_target(target, "docs", &files, &allow);
+        assert_eq!(resolve("Orders.md"), Resolution::Found);
+        assert_eq!(resolve("../README.md#usage"), Resolution::Found);
+        assert_eq!(resolve("img"), Resolution::Found);
+        assert_eq!(resolve("orders.md#top"), Resolution::Rewrite("Orders.md#top".to_string()));
+        assert_eq!(resolve("./Img/Chart"), Resolution::Rewrite("./img/chart.png".to_string()));
+        assert_eq!(resolve("../../outside.md"), Resolution::Missing);
+        assert_eq!(resolve("#section"), Resolution::Skip);
+        assert_eq!(resolve("mailto:team@example.com"), Resolution::Skip);
+        assert_eq!(resolve("https://docs.example.com/x"), Resolution::External { host: "docs.example.com".to_string(), allowed: true });
+        assert_eq!(resolve("http://user@evil.test:8080/"), Resolution::External { host: "evil.test".to_string(), allowed: false });
+    }
+
+
+    #[test]
+    fn rewrites_near_misses_in_tables_and_definitions() {
+        let markdown = "| ![Chart](Img/Chart) |\n\n[spec]: orders.md\n";
+        let report = normalize_links(markdown, "docs", &files(&["docs/img/chart.png", "docs/Orders.md"]), &[], LinkPolicy::Warn).unwrap();
+        assert_eq!(report.markdown, "| ![Chart](img/chart.png) |\n\n[spec]: Orders.md\n");
+        assert_eq!(report.rewritten, ["line 1: image Img/Chart -> img/chart.png", "line 3: link orders.md -> Orders.md"]);
+        assert!(report.problems.is_empty());
+    }
+
+
+    #[test]
+    fn strip_removes_broken_definitions_and_unwraps_their_references() {
+        let markdown = "| a | [Spec][s] and ![gone](gone.png) |\n\n[s]: missing.md\n";
+        let report = normalize_links(markdown, "", &BTreeSet::new(), &[], LinkPolicy::Strip).unwrap();
+        assert_eq!(report.markdown, "| a | Spec and gone |\n\n");
+        assert_eq!(report.problems, [
+            "line 1: image target gone.png does not exist",
+            "line 3: link target missing.md does not exist",
+        ]);
+    }
+
+
+    #[test]
+    fn fail_refuses_documents_with_broken_or_external_links() {
+        let markdown = "[a](https://tracker.test/1) [b](b.md)\n";
+        let err = normalize_links(markdown, "", &BTreeSet::new(), &[], LinkPolicy::Fail).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "2 broken link(s): line 1: external link https://tracker.test/1 (tracker.test is not in link_allowlist); line 1: link target b.md does not exist"
+        );
+        assert_eq!("strip".parse::<LinkPolicy>(), Ok(LinkPolicy::Strip));
+    }
+}
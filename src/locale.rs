@@ -0,0 +1,299 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::env;
+
+// External Libraries
+use chrono::{DateTime, Datelike, Timelike};
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const DEFAULT_CURRENCY: &str = "USD";
+pub const MONTHS_EN: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+pub const MONTHS_NL: [&str; 12] = [
+    "januari", "februari", "maart", "april", "mei", "juni",
+    "juli", "augustus", "september", "oktober", "november", "december",
+];
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    Nl,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Currency {
+    pub code: String,
+    pub fx_rate: Option<f64>,
+}
+
+
+// ====================================================
+//  Locale Selection
+// ====================================================
+impl Locale {
+
+    pub fn from_lang(lang: Option<&str>) -> Locale {
+
+        // Picks the formatting locale for a `lang` setting (`--lang`/`DOXCER_LANG`).
+        //
+        // # Description
+        // `nl`, `nl-NL`, `nl_BE`, `Dutch`, `Nederlands` and `Vlaams` select Dutch
+        // formatting; anything else, or no setting, uses English.
+
+        let lang = lang.unwrap_or_default().trim().to_lowercase();
+        let dutch = lang == "nl"
+            || lang.starts_with("nl-")
+            || lang.starts_with("nl_")
+            || ["dutch", "nederlands", "vlaams"].iter().any(|n| lang.contains(n));
+        if dutch { Locale::Nl } else { Locale::En }
+    }
+
+
+    fn separators(self) -> (char, char) {
+
+        // Returns the thousands and decimal separators.
+
+        match self {
+            Locale::En => (',', '.'),
+            Locale::Nl => ('.', ','),
+        }
+    }
+}
+
+
+// ====================================================
+//  Numbers
+// ====================================================
+pub fn format_decimal(value: f64, decimals: usize, locale: Locale) -> String {
+
+    // Formats a number with thousands grouping: `1,234.56` or `1.234,56`.
+
+    let (thousands, decimal) = locale.separators();
+    let fixed = format!("{:.*}", decimals, value.abs());
+    let (int_part, frac_part) = fixed.split_once('.').unwrap_or((&fixed, ""));
+
+    let mut grouped = String::new();
+    for (i, c) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % 3 == 0 {
+            grouped.push(thousands);
+        }
+        grouped.push(c);
+    }
+    let sign = if value < 0.0 && fixed.chars().any(|c| c.is_ascii_digit() && c != '0') { "-" } else { "" };
+    if frac_part.is_empty() {
+        format!("{sign}{grouped}")
+    } else {
+        format!("{sign}{grouped}{decimal}{frac_part}")
+    }
+}
+
+
+pub fn format_percent(value: f64, decimals: usize, locale: Locale) -> String {
+
+    // Formats a percentage (`12.5%` or `12,5%`).
+
+    format!("{}%", format_decimal(value, decimals, locale))
+}
+
+
+pub fn format_size_in(bytes: usize, locale: Locale) -> String {
+
+    // Formats a byte count for humans, e.g. `512 B`, `4.2 KB` or `4,2 KB`.
+
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+
+    let b = bytes as f64;
+    if b >= MB {
+        format!("{} MB", format_decimal(b / MB, 1, locale))
+    } else if b >= KB {
+        format!("{} KB", format_decimal(b / KB, 1, locale))
+    } else {
+        format!("{bytes} B")
+    }
+}
+
+
+// ====================================================
+//  Dates
+// ====================================================
+pub fn format_datetime(timestamp: &str, locale: Locale) -> Option<String> {
+
+    // Renders an RFC 3339 timestamp for people, keeping its own offset.
+    //
+    // # Description
+    // `October 14, 2026 09:30 UTC` or `14 oktober 2026 09:30 UTC`. Only for
+    // human-facing output; everything machines read stays RFC 3339.
+    //
+    // # Returns
+    // * `None` when the timestamp cannot be parsed.
+
+    let time = DateTime::parse_from_rfc3339(timestamp).ok()?;
+    let offset = time.offset().local_minus_utc();
+    let zone = if offset == 0 { "UTC".to_string() } else { time.format("%:z").to_string() };
+    let month = time.month0() as usize;
+    let clock = format!("{:02}:{:02}", time.hour(), time.minute());
+    Some(match locale {
+        Locale::En => format!("{} {}, {} {clock} {zone}", MONTHS_EN[month], time.day(), time.year()),
+        Locale::Nl => format!("{} {} {} {clock} {zone}", time.day(), MONTHS_NL[month], time.year()),
+    })
+}
+
+
+// ====================================================
+//  Currency
+// ====================================================
+pub fn currency_from_env() -> Result<Currency, DoxcerError> {
+
+    // Reads the cost display currency from `DOXCER_CURRENCY` (default USD) and
+    // an optional USD→EUR rate from `DOXCER_FX_RATE`.
+
+    let code = env::var("DOXCER_CURRENCY")
+        .ok()
+        .map(|c| c.trim().to_uppercase())
+        .filter(|c| !c.is_empty())
+        .unwrap_or_else(|| DEFAULT_CURRENCY.to_string());
+    let fx_rate = match env::var("DOXCER_FX_RATE") {
+        Ok(v) if !v.trim().is_empty() => Some(
+            v.trim()
+                .replace(',', ".")
+                .parse::<f64>()
+                .ok()
+                .filter(|r| *r > 0.0)
+                .ok_or_else(|| DoxcerError::Config(format!("DOXCER_FX_RATE must be a positive number, got {v:?}")))?,
+        ),
+        _ => None,
+    };
+    Ok(Currency { code, fx_rate })
+}
+
+
+pub fn format_money(amount: f64, currency: &Currency, locale: Locale) -> String {
+
+    // Formats a cost as billed by the API, with an approximate EUR figure when
+    // a rate is configured: `$1,234.56 (≈ €1,141.07)` or `$ 1.234,56 (≈ € 1.141,07)`.
+    //
+    // # Description
+    // Amounts under one cent get four decimals, so per-request costs do not all
+    // show as zero.
+
+    let mut out = format_amount(amount, &currency.code, locale);
+    if let Some(rate) = currency.fx_rate.filter(|_| currency.code != "EUR") {
+        out.push_str(&format!(" (≈ {})", format_amount(amount * rate, "EUR", locale)));
+    }
+    out
+}
+
+
+fn format_amount(amount: f64, code: &str, locale: Locale) -> String {
+
+    // Formats one amount with its currency symbol in the locale's position.
+
+    let decimals = if amount != 0.0 && amount.abs() < 0.01 { 4 } else { 2 };
+    let number = format_decimal(amount, decimals, locale);
+    let symbol = match code {
+        "USD" => "$",
+        "EUR" => "€",
+        "GBP" => "£",
+        other => other,
+    };
+    match locale {
+        Locale::En if symbol.chars().count() == 1 => format!("{symbol}{number}"),
+        _ => format!("{symbol} {number}"),
+    }
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usd(fx_rate: Option<f64>) -> Currency {
+        Currency { code: "USD".to_string(), fx_rate }
+    }
+
+
+    #[test]
+    fn picks_dutch_for_dutch_lang_settings() {
+        for lang in ["nl", "NL-nl", "nl_BE", "Dutch", "Nederlands", "vlaams"] {
+            assert_eq!(Locale::from_lang(Some(lang)), Locale::Nl, "{lang}");
+        }
+        for lang in [None, Some("en"), Some("de"), Some("nlx"), Some("")] {
+            assert_eq!(Locale::from_lang(lang), Locale::En, "{lang:?}");
+        }
+    }
+
+
+    #[test]
+    fn formats_decimals_per_locale() {
+        assert_eq!(format_decimal(1234.56, 2, Locale::En), "1,234.56");
+        assert_eq!(format_decimal(1234.56, 2, Locale::Nl), "1.234,56");
+        assert_eq!(format_decimal(1234567.0, 0, Locale::En), "1,234,567");
+        assert_eq!(format_decimal(1234567.0, 0, Locale::Nl), "1.234.567");
+        assert_eq!(format_decimal(999.0, 1, Locale::Nl), "999,0");
+        assert_eq!(format_decimal(-1234.5, 1, Locale::Nl), "-1.234,5");
+        assert_eq!(format_decimal(-0.001, 2, Locale::En), "0.00");
+    }
+
+
+    #[test]
+    fn formats_percentages_and_sizes_per_locale() {
+        assert_eq!(format_percent(12.5, 1, Locale::En), "12.5%");
+        assert_eq!(format_percent(12.5, 1, Locale::Nl), "12,5%");
+        assert_eq!(format_size_in(512, Locale::Nl), "512 B");
+        assert_eq!(format_size_in(4300, Locale::En), "4.2 KB");
+        assert_eq!(format_size_in(4300, Locale::Nl), "4,2 KB");
+        assert_eq!(format_size_in(5 * 1024 * 1024, Locale::Nl), "5,0 MB");
+    }
+
+
+    #[test]
+    fn formats_dates_per_locale_keeping_the_offset() {
+        assert_eq!(format_datetime("2026-10-14T09:30:00Z", Locale::En).as_deref(), Some("October 14, 2026 09:30 UTC"));
+        assert_eq!(format_datetime("2026-10-14T09:30:00Z", Locale::Nl).as_deref(), Some("14 oktober 2026 09:30 UTC"));
+        assert_eq!(format_datetime("2026-03-01T23:05:00+02:00", Locale::Nl).as_deref(), Some("1 maart 2026 23:05 +02:00"));
+        assert_eq!(format_datetime("14-10-2026", Locale::En), None);
+    }
+
+
+    #[test]
+    fn formats_money_per_locale() {
+        assert_eq!(format_money(1234.56, &usd(None), Locale::En), "$1,234.56");
+        assert_eq!(format_money(1234.56, &usd(None), Locale::Nl), "$ 1.234,56");
+        assert_eq!(format_money(0.0042, &usd(None), Locale::En), "$0.0042");
+        assert_eq!(format_money(0.0, &usd(None), Locale::Nl), "$ 0,00");
+        let chf = Currency { code: "CHF".to_string(), fx_rate: None };
+        assert_eq!(format_money(10.0, &chf, Locale::En), "CHF 10.00");
+    }
+
+
+    #[test]
+    fn adds_an_approximate_eur_figure_with_a_rate() {
+        assert_eq!(format_money(1234.56, &usd(Some(0.5)), Locale::En), "$1,234.56 (≈ €617.28)");
+        assert_eq!(format_money(1234.56, &usd(Some(0.5)), Locale::Nl), "$ 1.234,56 (≈ € 617,28)");
+        let eur = Currency { code: "EUR".to_string(), fx_rate: Some(0.5) };
+        assert_eq!(format_money(10.0, &eur, Locale::Nl), "€ 10,00");
+    }
+}
@@ -1,240 +1,6386 @@
-////////////////////////////////////////////////////////
-// AUTHOR   : Stefan B. J. Meeuwessen
-// COMPANY  : Hoppenbrouwers
-// TEAM     : Team Data & BI
-// CREATION : 2025-11-05
-// VERSION  : 0.1.4
-////////////////////////////////////////////////////////
-
-
-// Compiler Directives
-#![allow(unused)]
-
-
-// Internal Libraries
-use std::env;
-use std::fs;
-use std::process;
-use std::path::{Path, PathBuf};
-
-// External Libraries
-use dotenvy::from_path;
-use fernet::Fernet;
-use reqwest::blocking::Client;
-use serde::{Deserialize, Serialize};
-
-
-// ----------------------------
-// Data Structures
-// ----------------------------
-#[derive(Serialize)]
-struct ChatRequest {
-    model: String,
-    input: String,
-}
-
-#[derive(Deserialize)]
-struct ChatResponse {
-    output: Option<Vec<ChatOutput>>,
-}
-
-#[derive(Deserialize)]
-struct ChatOutput {
-    content: Vec<ChatMessage>,
-}
-
-#[derive(Deserialize)]
-struct ChatMessage {
-    r#type: String,
-    text: Option<String>,
-}
-
-
-// ----------------------------
-// Constants
-// ----------------------------
-const URL: &str = "https://api.openai.com/v1/responses";
-
-
-// ----------------------------
-// Helper Functions
-// ----------------------------
-fn decrypt_value(encrypted_value: &str, encryption_key: &str) -> Option<String> {
-
-    /// Decrypts an encrypted string using Fernet symmetric encryption.
-    ///
-    /// # Description
-    /// This function takes a Fernet-encrypted, base64-encoded string and decrypts it using
-    /// a provided encryption key. It ensures that the resulting value is valid UTF-8.
-    ///
-    /// # Parameters
-    /// * `encrypted_value` – The encrypted string to be decrypted.
-    /// * `encryption_key` – The Fernet encryption key, as defined in the environment.
-    ///
-    /// # Returns
-    /// * `Option<String>` – Returns the decrypted UTF-8 string if successful; otherwise `None`.
-
-    let fernet = Fernet::new(encryption_key)?;
-    let decrypted_bytes = fernet.decrypt(encrypted_value).ok()?;
-    String::from_utf8(decrypted_bytes).ok()
-}
-
-fn load_env_robust() {
-
-    /// Loads the environment configuration from a `.env` file using a robust multi-path search.
-    ///
-    /// # Description
-    /// The function searches for a valid `.env` file across multiple potential locations,
-    /// including:
-    /// - An explicit override path defined by `DOXCER_ENV_PATH`.
-    /// - The current working directory and its `config` subdirectory.
-    /// - The directory of the executing binary and its parent directories.
-    ///
-    /// The first valid `.env` file discovered will be loaded into the process environment.
-    /// If no configuration file is found, the function panics with a detailed list of
-    /// the paths it attempted to locate.
-    ///
-    /// # Panics
-    /// Panics if no `.env` file can be found or if the selected file cannot be read.
-
-    let exe_dir = env::current_exe()
-        .ok()
-        .and_then(|p| p.parent().map(|p| p.to_path_buf()))
-        .unwrap_or_else(|| PathBuf::from("."));
-
-    let cwd = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-    let explicit = env::var("DOXCER_ENV_PATH").ok().map(PathBuf::from);
-
-    let candidates: Vec<PathBuf> = [
-        explicit.as_ref().cloned(),
-        Some(cwd.join("config").join(".env")),
-        Some(cwd.join(".env")),
-        Some(exe_dir.join("config").join(".env")),
-        Some(exe_dir.join(".env")),
-        exe_dir.parent().map(|p| p.join("config").join(".env")),
-        exe_dir.parent().map(|p| p.join(".env")),
-        exe_dir.parent().and_then(|p| p.parent()).map(|p| p.join("config").join(".env")),
-        exe_dir.parent().and_then(|p| p.parent()).map(|p| p.join(".env")),
-    ]
-    .into_iter()
-    .flatten()
-    .collect();
-
-    if let Some(found) = candidates.iter().find(|p| p.exists()) {
-        from_path(found)
-            .unwrap_or_else(|e| panic!("Failed to load .env at {}: {e}", found.display()));
-        eprintln!("Loaded .env from: {}", found.display());
-    } else {
-        let searched = candidates
-            .iter()
-            .map(|p| p.display().to_string())
-            .collect::<Vec<_>>()
-            .join("\n  - ");
-        panic!("Could not find a .env file. Searched:\n  - {}", searched);
-    }
-}
-
-
-// ----------------------------
-// Runtime
-// ----------------------------
-fn main() {
-
-    /// Entry point for the Doxcer notebook documentation generator.
-    ///
-    /// # Description
-    /// This function orchestrates the end-to-end workflow of the Doxcer tool:
-    /// 1. Loads environment configuration via [`load_env_robust`].
-    /// 2. Retrieves and decrypts the OpenAI API key from the environment.
-    /// 3. Parses the CLI argument specifying the target Fabric PySpark notebook.
-    /// 4. Reads the notebook and the Markdown template (`prompt.md`).
-    /// 5. Constructs a prompt for the OpenAI API and sends a documentation generation request.
-    /// 6. Outputs the generated documentation to standard output.
-    ///
-    /// # Usage
-    /// ```bash
-    /// doxcer <path/to/notebook.py>
-    /// ```
-    ///
-    /// # Panics
-    /// The function will panic if:
-    /// - The `.env` file or its variables cannot be loaded.
-    /// - The notebook or template files cannot be read.
-    /// - The OpenAI API request fails unexpectedly.
-
-    load_env_robust();
-
-    let encryption_key = env::var("ENCRYPTION_PASSWORD")
-        .expect("Missing ENCRYPTION_PASSWORD in .env");
-
-    let encrypted_api_key = env::var("OPENAI_API_KEY_ENC")
-        .expect("Missing OPENAI_API_KEY_ENC in .env");
-    let api_key = decrypt_value(&encrypted_api_key, &encryption_key)
-        .expect("Failed to decrypt API key");
-
-    let args: Vec<String> = env::args()
-        .collect();
-    if args.len() != 2 {
-        eprintln!("Usage: doxcer <path/to/notebook.py>");
-        process::exit(1);
-    }
-
-    let file_path = &args[1];
-    let notebook_content = fs::read_to_string(file_path)
-        .unwrap_or_else(|_| panic!("Failed to read file {}", file_path));
-
-    let template_path = "./templates/prompt.md";
-    let template_content = fs::read_to_string(template_path)
-        .unwrap_or_else(|_| panic!("Failed to read {}", template_path));
-
-    println!(
-        "Loaded prompt template from: {}\n--- Preview ---\n{}\n--- End of Preview ---\n",
-        template_path,
-        &template_content.chars()
-            .take(250)
-            .collect::<String>()
-    );
-
-    let prompt = format!("{}\n\nHier is de Notebook.py:\n\n{}", template_content, notebook_content);
-
-    let request = ChatRequest {
-        model: "gpt-5-mini".to_string(),
-        input: prompt,
-    };
-
-    let client = Client::new();
-    let response = client
-        .post(URL)
-        .header("Content-Type", "application/json")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .json(&request)
-        .send();
-
-    match response {
-        Ok(res) => {
-
-            if res.status().is_success() {
-
-                let parsed: ChatResponse = res.json()
-                    .unwrap_or(ChatResponse { output: None });
-                
-                if let Some(outputs) = parsed.output {
-                    
-                    for o in outputs {
-                        for msg in o.content {
-                            
-                            if let Some(text) = msg.text {
-                                println!("{}", text);
-                            }
-                        }
-                    }
-                } else {
-                    println!("No output received from API.");
-                }
-            } else {
-                eprintln!("API request failed: {}", res.text().unwrap_or_default());
-            }
-        }
-        Err(e) => eprintln!("Request error: {}", e),
-    }
-}
\ No newline at end of file
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// COMPANY  : Hoppenbrouwers
+// TEAM     : Team Data & BI
+// CREATION : 2025-11-05
+// VERSION  : 0.1.4
+////////////////////////////////////////////////////////
+
+
+// Compiler Directives
+// Functions are documented with `///` as the first lines of their body.
+#![allow(unused_doc_comments)]
+#![deny(unused_must_use)]
+
+
+// Internal Libraries
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Write};
+use std::process::{self, ExitCode};
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock, mpsc};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+// External Libraries
+use chrono::{DateTime, Utc};
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+use reqwest::blocking::Client;
+use reqwest::header::RETRY_AFTER;
+use serde::Serialize;
+use serde_json::Value;
+
+// Doxcer Library
+use doxcer::archive::{Archive, member_path, open_archive, split_archive_input, split_member_path};
+use doxcer::cancel::{self, CancelToken, CancellableReader, interrupt_token};
+use doxcer::cache::{CacheEntry, ResponseCache, cache_key, default_cache_dir, render_stats};
+use doxcer::chunk::{Chunk, ChunkStrategy, chunk_banner, merge_input, split_chunks};
+use doxcer::clipboard::{copy_to_clipboard, format_size};
+use doxcer::config::{ConfigFile, DEFAULT_CONFIG_FILE, DEFAULT_MODEL, Setting, Settings, SettingsLayer, Source, env_layer, load_config_file, resolve_config_path, selected_run_profile, split_list};
+use doxcer::contextfiles::load_context;
+use doxcer::cost::{Budget, OVER_BUDGET_STATUS, cost_line, cost_report, render_cost_summary};
+use doxcer::coverage::{bucket, coverage_report, doc_age, render_coverage};
+use doxcer::datapipeline::{is_json_path, parse_pipeline, pipeline_name};
+use doxcer::dbt::{PROJECT_FILE, model_description, model_paths, schema_files, set_model_description, write_schema_file};
+use doxcer::diagnostics::{env_snapshot, render_env_snapshot};
+use doxcer::directives::{parse_directives, pinned_model};
+use doxcer::discovery::{Discovery, DiscoveryOptions, Excluded, discover, discover_listed, is_notebook_path};
+use doxcer::docfile::{DocTarget, ExistingPolicy, check_writable, write_document};
+use doxcer::editmode::{DEFAULT_MAX_DIFF_LINES, EditPlan, SNAPSHOT_DIR, changed_lines, edit_instruction, load_snapshot, plan_edit, section_instruction, snapshot_path, store_snapshot, unified_diff};
+use doxcer::envsafety::{append_env_var, env_safety_problems, set_env_var};
+use doxcer::error::{DoxcerError, render_exit_codes};
+use doxcer::fabric::{FabricApi, FabricItem, RUN_STATS_KEY, RunHistory, fetch_run_history, list_notebooks, run_stats};
+use doxcer::excerpt::{Cell, DEFAULT_CONTEXT_CELLS, EXCERPT_KEY, Selector, excerpt_file_name, parse_cell, parse_cell_list, parse_range, select_excerpt, split_cells};
+use doxcer::failures::{DEFAULT_FAILED_PATH, DEFAULT_MAX_ATTEMPTS, FailureLog};
+use doxcer::formats::{Artifact, JsonOut, OutputFormat, OutputTargets, WRITE_FAILED_STATUS, failed_writes, parse_formats, render_format};
+use doxcer::frontmatter::{split_front_matter, upsert_front_matter};
+use doxcer::git::{self, GIT_COMMIT_KEY, GIT_REF_KEY, GitRef};
+use doxcer::granularity::{Granularity, assemble_cell_document, cell_banner};
+use doxcer::hook::{HOOK_NAME, HookChange, install_hook, uninstall_hook};
+use doxcer::html::slug;
+use doxcer::inject::{inject_docs, is_injectable, strip_injected};
+use doxcer::junit::{CaseOutcome, JunitReport, Report, TestCase, suite_for};
+use doxcer::limits::{DEFAULT_PROVIDER, read_capped};
+use doxcer::links::{LinkPolicy, normalize_links, workspace_files};
+use doxcer::lineage::{DependencyOrder, Lineage, dependency_order, extract_lineage, mermaid_graph, notebook_dependencies, summary_line};
+use doxcer::locale::{Currency, DEFAULT_CURRENCY, Locale, currency_from_env, format_datetime, format_money, format_size_in};
+use doxcer::manifest::{DEFAULT_MANIFEST_PATH, Manifest, ManifestEntry, STDOUT_OUTPUT, manifest_key};
+use doxcer::kind::{SourceKind, detect_kind, is_sql_path};
+use doxcer::notebook::{is_ipynb, output_count, parse_ipynb};
+use doxcer::oversize::{COVERAGE_KEY, OversizePolicy, coverage_note, default_max_total_tokens, count_tokens, partial_banner, size_report, truncate_to_tokens};
+use doxcer::pii::{PII_AUDIT_ARTIFACT, PiiPolicy, PiiReport};
+use doxcer::pipeline::{Pipeline, PromptDoc, Removed};
+use doxcer::profile::{Profile, apply_profiles, matching_profiles, resolve_profiles};
+use doxcer::posthook::{HookContext, POST_HOOK_FAILED_STATUS, run_post_hook};
+use doxcer::provenance::{MODEL_KEY, Provenance, VERSION_KEY, content_hash, read_provenance, stamp_provenance};
+use doxcer::provider::{ANTHROPIC_API_KEY, AZURE_API_KEY, ChatRequest, Generation, LlmProvider, OPENAI_API_KEY, OpenAi, Reasoning, ReasoningEffort, StreamEvent, Usage, check_sampling, select_provider};
+use doxcer::queue::{Pushed, WorkQueue, capacity_from_env};
+use doxcer::retry::{is_retryable_status, parse_retry_after, retry_delay};
+use doxcer::runlog::{DEFAULT_RUN_LOG_PATH, RunRecord, RunWindow, TAGS_ENV, append_run_record, parse_tag, parse_tags, read_run_records, render_summary_csv, render_summary_json, render_summary_table, summarize_runs};
+use doxcer::runrefs::{child_notebooks, display_name, extract_run_references, render_child_notebooks, resolve_run_reference};
+use doxcer::routing::{describe_band, route_by_tokens, validate_routing};
+use doxcer::serve::{DEFAULT_HOST, serve};
+use doxcer::secrets::{SecretPatternConfig, SecretScanner, load_secret_patterns_file};
+use doxcer::semanticmodel::{is_semantic_model_path, parse_semantic_model, semantic_model_name};
+use doxcer::source::SourceSnapshot;
+use doxcer::split::{SplitLimit, find_section, replace_section, write_atomic, write_split};
+use doxcer::style::{Color, ColorChoice, Style, set_color_choice};
+use doxcer::summary::{DocMode, MODE_KEY, check_summary, parse_modes, shorter_instruction};
+use doxcer::telemetry::Tracer;
+use doxcer::template::{BUILTIN_PARTIALS, BUILTIN_TEMPLATES, Prompt, REPETITION_LIMIT, REPETITION_WINDOW, Template, builtin_template, language_variant, load_notebook_summary_template, load_pipeline_template, load_prompt_template, load_semantic_model_template, load_sql_template, load_summary_template, load_template, missing_sections, prompt_context, repeated_window, unavailable_placeholders};
+use doxcer::templatecheck::{Severity, TemplateChecker};
+use doxcer::templateregistry::{TemplateRoute, available_templates, kind_template_name, template_file_name, template_name_for};
+use doxcer::templating::parse_var;
+use doxcer::timefmt::{GENERATED_AT_KEY, TimestampTz, age_of, now_in, now_rfc3339, parse_age, parse_instant, tz_from_env};
+use doxcer::verify::{DEFAULT_DOCS_DIR, DocStatus, VerifyResult, check_doc_paths, expected_doc_path, render_table, verify_notebook, verify_notebook_with};
+use doxcer::watch::{DEFAULT_DEBOUNCE_MS, DEFAULT_TICK_MS, Watcher, canonical_paths};
+use doxcer::wrap::{display_width, soft_wrap};
+use doxcer::workspace::{ATTEMPT_GROUP_ARTIFACT, RunWorkspace, default_workspace_base, gc_workspaces, idempotency_key, max_age_from_env, new_run_id};
+use doxcer::{decrypt_fernet, encrypt_fernet, env_secret, err, errln, generate_fernet_key, load_env_robust, out, outln, resolve_template, secret_key_name, user_agent, version};
+
+
+// ----------------------------
+// Data Structures
+// ----------------------------
+#[derive(Clone)]
+struct GenerationSetup {
+    provider: Arc<dyn LlmProvider>,
+    template: Template,
+    settings: Settings,
+    profiles: Vec<Profile>,
+    pipeline: Pipeline,
+    secrets: SecretScanner,
+    upstream_summaries: String,
+    child_notebooks: String,
+    run_history: Option<RunHistory>,
+    edit_instruction: Option<String>,
+    run_profile: Vec<String>,
+    context: String,
+    // What this setup documents: notebooks, SQL scripts, Data Pipelines or semantic models (`--kind`).
+    kind: SourceKind,
+    // The named template it was loaded from (`notebook`, `sql`, or a name from
+    // `--template-name` or `[[templates]]`).
+    template_name: String,
+}
+
+// The flags shared by every generating command (`generate`, `verify`).
+#[derive(Args, Default)]
+struct RunOptions {
+    /// Base directory of the per-run workspaces
+    #[arg(long, value_name = "DIR")]
+    workdir: Option<PathBuf>,
+    /// Keep the run workspace, also after a successful run
+    #[arg(long = "keep-workdir")]
+    keep: bool,
+    /// Reuse the answer of an earlier, interrupted run for the same request
+    #[arg(long)]
+    resume: bool,
+    /// Zone of the generated_at timestamp: utc or local
+    #[arg(long, value_name = "TZ")]
+    timestamp_tz: Option<TimestampTz>,
+    /// Leave generated_at out of the front-matter
+    #[arg(long)]
+    no_timestamps: bool,
+    /// Skip a stage of the prompt pipeline (repeatable)
+    #[arg(long = "disable-stage", value_name = "NAME")]
+    disabled_stages: Vec<String>,
+    /// Secret patterns file, over the built-in patterns
+    #[arg(long, value_name = "FILE")]
+    secret_patterns: Option<PathBuf>,
+    /// Write every pipeline stage of the prompt to this directory
+    #[arg(long, value_name = "DIR")]
+    pipeline_dump: Option<PathBuf>,
+    /// Largest prompt in tokens (default: the model's context window)
+    #[arg(long, value_name = "N", value_parser = positive::<usize>)]
+    max_total_tokens: Option<usize>,
+    /// What to do with a prompt over the limit: fail, warn, truncate or chunk
+    #[arg(long, value_name = "POLICY", conflicts_with = "force_oversize")]
+    oversize_policy: Option<OversizePolicy>,
+    /// Same as --oversize-policy truncate
+    #[arg(long)]
+    force_oversize: bool,
+    /// How to split an oversized notebook: cells, lines or tokens
+    #[arg(long, value_name = "STRATEGY")]
+    chunk_strategy: Option<ChunkStrategy>,
+    /// Document the notebook as a whole or cell by cell: document or cell
+    #[arg(long, value_name = "GRANULARITY")]
+    granularity: Option<Granularity>,
+    /// What to do with a broken link in the answer: warn, strip or fail
+    #[arg(long, value_name = "POLICY")]
+    link_policy: Option<LinkPolicy>,
+    /// Personal data in the notebook: off, warn, redact or fail
+    #[arg(long, value_name = "POLICY")]
+    pii: Option<PiiPolicy>,
+    /// Write the PII findings of each request to the run workspace
+    #[arg(long)]
+    pii_audit: bool,
+    /// Add the recent Fabric runs of each notebook to the prompt
+    #[arg(long)]
+    fabric_runs: bool,
+    /// Add a file to the prompt as {{context}} (repeatable)
+    #[arg(long = "context", value_name = "PATH")]
+    context_files: Vec<String>,
+    /// Token cap of the context files (default 8000)
+    #[arg(long, value_name = "N", value_parser = positive::<usize>)]
+    max_context_tokens: Option<usize>,
+    /// Send the template and the notebook as one input
+    #[arg(long)]
+    single_input: bool,
+    /// Send the cell outputs of .ipynb notebooks along
+    #[arg(long, conflicts_with = "strip_outputs")]
+    include_outputs: bool,
+    /// Leave the cell outputs out, also over doxcer.toml
+    #[arg(long)]
+    strip_outputs: bool,
+    /// Replace string literals in the source before sending it
+    #[arg(long)]
+    redact_literals: bool,
+    /// Attempts per request on 429 and 5xx answers
+    #[arg(long, value_name = "N", value_parser = positive::<u32>)]
+    retry_max_attempts: Option<u32>,
+    /// First delay of the exponential backoff
+    #[arg(long, value_name = "MS")]
+    retry_base_delay_ms: Option<u64>,
+    /// Time limit per file in seconds
+    #[arg(long, value_name = "SECS", value_parser = positive::<u64>)]
+    file_timeout: Option<u64>,
+    /// Write a report: junit=<path> or json[=<path>] (repeatable)
+    #[arg(long = "report", value_name = "REPORT")]
+    reports: Vec<Report>,
+    /// Tag the run in the run log: key=value (repeatable)
+    #[arg(long = "tag", value_name = "KEY=VALUE", value_parser = parse_tag)]
+    tags: Vec<(String, String)>,
+    /// Template variable over [vars] in doxcer.toml: name=value (repeatable)
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_var)]
+    vars: Vec<(String, String)>,
+    /// Language of the documentation, e.g. nl
+    #[arg(long, value_name = "CODE")]
+    lang: Option<String>,
+    /// Cap on the estimated spend of the run in EUR
+    #[arg(long, value_name = "EUR", value_parser = positive::<f64>)]
+    max_cost: Option<f64>,
+    /// Send every request again instead of answering from the cache
+    #[arg(long)]
+    no_cache: bool,
+    /// Ignore cache entries older than this age, e.g. 7d
+    #[arg(long, value_name = "AGE", value_parser = parse_age)]
+    cache_ttl: Option<Duration>,
+}
+
+#[derive(Serialize)]
+struct Probe {
+    name: &'static str,
+    ok: bool,
+    detail: String,
+}
+
+struct RunContext {
+    client: Client,
+    api_key: String,
+    setup: GenerationSetup,
+    workspace: RunWorkspace,
+    cache: ResponseCache,
+    resume: bool,
+    timestamps: Option<TimestampTz>,
+    tracer: Tracer,
+    verbose: bool,
+    pii_audit: bool,
+    fabric_runs: bool,
+    fabric_items: Arc<OnceLock<Result<Vec<FabricItem>, DoxcerError>>>,
+    workspace_files: Arc<OnceLock<BTreeSet<String>>>,
+    at_ref: Option<GitRef>,
+    archive: Option<Archive>,
+    tags: BTreeMap<String, String>,
+    stream: bool,
+    budget: Option<Budget>,
+    cancel: CancelToken,
+}
+
+struct GenerateRequest {
+    name: Option<String>,
+    selector: Option<Selector>,
+    context_cells: usize,
+    wrap: Option<usize>,
+    verbose: bool,
+    output: Option<DocTarget>,
+    policy: ExistingPolicy,
+    workspace: Vec<PathBuf>,
+    inputs: Vec<PathBuf>,
+    // `--kind`; `None` detects the kind per file.
+    kind: Option<SourceKind>,
+    // `--inject`: write the documentation into the notebook, see `inject_target`.
+    inject: bool,
+    // `--template-name`, else the `[[templates]]` entries; see `template_name_for`.
+    template_name: Option<String>,
+    template_routes: Vec<TemplateRoute>,
+}
+
+fn inject_target(run: &mut RunContext, jobs: &[(DocMode, GenerationSetup)], file_path: &String, request: &GenerateRequest) -> TargetResult {
+
+    /// Documents one notebook cell by cell and writes the sections back into
+    /// it (`--inject`).
+    ///
+    /// # Description
+    /// Cells injected by an earlier run are removed first (see
+    /// [`strip_injected`]), so they are neither documented nor duplicated.
+    /// Every code cell is then documented with [`cell_sections`] and gets a
+    /// markdown cell above it that starts with its content hash (see
+    /// [`inject_docs`]). The notebook is rewritten atomically, and only when it
+    /// did not change while generating; with `--backup` the previous version
+    /// is kept as `<file>.bak`.
+
+    let mut result = TargetResult::default();
+    let started = Instant::now();
+    let case = |outcome: CaseOutcome| TestCase {
+        suite: suite_for(Path::new(file_path), &request.inputs),
+        name: file_path.clone(),
+        duration: started.elapsed(),
+        outcome,
+    };
+    let snapshot = match read_generate_target(&run.client, file_path, None) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            result.cases.push(case(CaseOutcome::Failed { category: e.kind().to_string(), message: e.to_string() }));
+            result.failure = Some(e);
+            return result;
+        }
+    };
+    result.source_hash = Some(snapshot.hash.clone());
+    let notebook_path = snapshot.path.clone();
+    let template = template_name_for(request.template_name.as_deref(), &request.template_routes, &notebook_path, SourceKind::Notebook);
+    if let Some((_, setup)) = jobs.iter().find(|(mode, setup)| *mode == DocMode::Full && setup.kind == SourceKind::Notebook && setup.template_name == template) {
+        run.setup = setup.clone();
+    }
+    run.setup.child_notebooks = child_notebooks_for(&notebook_path, &snapshot.text, &request.workspace, Path::new(DEFAULT_DOCS_DIR));
+    route_model(run, &run.setup.settings.model.clone(), file_path, &snapshot.text, request.verbose);
+    let model = run.setup.settings.model.value.clone();
+
+    let file_span = run.tracer.span("doxcer.file");
+    file_span.set("doxcer.notebook", file_path);
+    file_span.set("doxcer.mode", "inject");
+    let injected = request_injection(run, &notebook_path, &snapshot);
+    match &injected {
+        Ok(_) => file_span.set("doxcer.status", "ok"),
+        Err(e) => file_span.fail(e),
+    }
+    drop(file_span);
+    let written = injected.and_then(|injected| match injected {
+        Some(_) if !snapshot.is_current().unwrap_or(false) => Err(DoxcerError::Input(format!(
+            "{file_path} changed while generating; the documentation was not injected"
+        ))),
+        Some((text, sections)) => {
+            let policy = match request.policy {
+                ExistingPolicy::Backup => ExistingPolicy::Backup,
+                _ => ExistingPolicy::Overwrite,
+            };
+            write_document(&notebook_path, &text, policy).map(|backup| Some((sections, backup)))
+        }
+        None => Ok(None),
+    });
+    match written {
+        Ok(Some((CellSections { sections, model: used, cached, usage }, backup))) => {
+            if used != model {
+                result.notes.push(format!("{file_path}: generated with fallback model {used}"));
+            }
+            result.notes.push(format!("injected the documentation of {} cell(s) into {file_path}", sections.len()));
+            if let Some(backup) = backup {
+                result.notes.push(format!("kept the previous version as {}", backup.display()));
+            }
+            if cached {
+                result.notes.push(format!("{file_path}: answered from the response cache"));
+            }
+            log_run(run, RunRecord {
+                cache_hit: Some(cached),
+                input_tokens: usage.and_then(|u| u.input_tokens),
+                output_tokens: usage.and_then(|u| u.output_tokens),
+                ..RunRecord::new(Path::new(file_path), &snapshot.hash, &used, "ok")
+            });
+            result.cases.push(case(if cached { CaseOutcome::Skipped("cache hit".to_string()) } else { CaseOutcome::Passed }));
+            result.usage.push(UsageEntry { notebook: file_path.clone(), model: used, cached, usage });
+        }
+        Ok(None) => {
+            log_run(run, RunRecord::new(Path::new(file_path), &snapshot.hash, &model, "empty"));
+            let e = DoxcerError::Provider("No output received from API.".to_string());
+            result.cases.push(case(CaseOutcome::Failed { category: e.kind().to_string(), message: e.to_string() }));
+            result.failure = Some(e);
+        }
+        Err(e) => {
+            let status = match e {
+                DoxcerError::TooLarge(_) => "too-large",
+                DoxcerError::OverBudget(_) => OVER_BUDGET_STATUS,
+                _ => "failed",
+            };
+            log_run(run, RunRecord { error: Some(e.to_string()), ..RunRecord::new(Path::new(file_path), &snapshot.hash, &model, status) });
+            result.cases.push(case(CaseOutcome::Failed { category: e.kind().to_string(), message: e.to_string() }));
+            result.failure = Some(e);
+        }
+    }
+    result
+}
+
+fn request_injection(run: &RunContext, notebook_path: &Path, snapshot: &SourceSnapshot) -> Result<Option<(String, CellSections)>, DoxcerError> {
+
+    /// Returns the notebook with the documentation of every code cell
+    /// injected, and the sections it was built from.
+
+    if !is_injectable(notebook_path, &snapshot.text) {
+        return Err(DoxcerError::Input(format!(
+            "{}: --inject writes into .ipynb notebooks and Fabric .py sources only",
+            notebook_path.display()
+        )));
+    }
+    let (clean, removed) = strip_injected(notebook_path, &snapshot.text)?;
+    if removed > 0 {
+        errln!("{}: replacing {removed} injected cell(s) of an earlier run", notebook_path.display());
+    }
+    let Some(sections) = cell_sections(run, notebook_path, &snapshot.hash, &clean)? else {
+        return Ok(None);
+    };
+    let text = inject_docs(notebook_path, &clean, &sections.sections)?;
+    Ok(Some((text, sections)))
+}
+
+#[derive(Default)]
+struct TargetResult {
+    stdout: Vec<String>,
+    notes: Vec<String>,
+    cases: Vec<TestCase>,
+    documents: Vec<String>,
+    usage: Vec<UsageEntry>,
+    failure: Option<DoxcerError>,
+    source_hash: Option<String>,
+}
+
+struct UsageEntry {
+    notebook: String,
+    model: String,
+    cached: bool,
+    usage: Option<Usage>,
+}
+
+struct Generated {
+    text: String,
+    model: String,
+    cached: bool,
+    coverage: Option<String>,
+    usage: Option<Usage>,
+}
+
+struct CellSections {
+    // Each code cell with the answer documenting it.
+    sections: Vec<(Cell, String)>,
+    model: String,
+    cached: bool,
+    usage: Option<Usage>,
+}
+
+struct SendError {
+    error: DoxcerError,
+    capacity: bool,
+    retryable: bool,
+    retry_after: Option<Duration>,
+}
+
+enum FixOutcome {
+    Fixed { source_hash: String, model: String, cached: bool, usage: Option<Usage>, artifacts: Vec<Artifact> },
+    StaleInput { source_hash: String, model: String },
+}
+
+
+
+// ----------------------------
+// Constants
+// ----------------------------
+const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+const DEFAULT_TEMPLATE_DIR: &str = "./templates";
+const SUMMARY_TEMPLATE_NAME: &str = "summary.md";
+const NOTEBOOK_SUMMARY_TEMPLATE_NAME: &str = "notebook_summary.md";
+const SQL_TEMPLATE_NAME: &str = "sql.md";
+const PIPELINE_TEMPLATE_NAME: &str = "pipeline.md";
+const SEMANTIC_MODEL_TEMPLATE_NAME: &str = "semantic_model.md";
+const SUMMARY_FILE_NAME: &str = "overview.md";
+const SUMMARY_MAX_FAILED_PERCENT: u32 = 20;
+const MAX_SOURCE_BYTES: u64 = 16 * 1024 * 1024;
+const MAX_REDIRECTS: usize = 5;
+const HTTP_CONNECT_TIMEOUT_SECS: u64 = 10;
+const HTTP_TIMEOUT_SECS: u64 = 300;
+static STRICT_ENV_SECURITY: AtomicBool = AtomicBool::new(false);
+static RUN_PROFILE: OnceLock<String> = OnceLock::new();
+static TEMPLATE_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+// The places searched for a `.env` that was not found; see `load_env`.
+static ENV_SEARCHED: OnceLock<Vec<PathBuf>> = OnceLock::new();
+const REQUEST_ARTIFACT: &str = "request.json";
+const SOURCE_AUTH_HEADER: &str = "DOXCER_SOURCE_AUTH_HEADER";
+const FABRIC_AUTH_HEADER: &str = "DOXCER_FABRIC_AUTH_HEADER";
+const RESPONSE_ARTIFACT: &str = "response.md";
+const TRUNCATED_ARTIFACT: &str = "response.truncated";
+const AUTH_TEST_TIMEOUT_SECS: u64 = 5;
+const SELFTEST_FILE: &str = ".selftest";
+
+
+// ----------------------------
+// Command Line
+// ----------------------------
+const HELP_TEMPLATE: &str = "{usage-heading} {usage}\n\n{about-with-newline}\n{all-args}{after-help}";
+const COMMAND_NAMES: [&str; 19] = [
+    "generate", "verify", "check", "init", "config", "coverage", "doctor", "selftest", "cache", "runs",
+    "template", "hook", "keygen", "encrypt", "decrypt", "serve", "watch", "dbt", "help",
+];
+
+#[derive(Parser)]
+#[command(
+    name = "doxcer",
+    about = "Generates Markdown documentation for notebooks, SQL scripts, pipelines and semantic models",
+    override_usage = "doxcer <command> [options]\n       doxcer <paths>... [options]",
+    disable_version_flag = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Colored diagnostics: auto, always or never
+    #[arg(long, global = true, value_name = "WHEN", default_value = "auto")]
+    color: ColorChoice,
+    /// Fail instead of warn on an unsafe .env file
+    #[arg(long, global = true)]
+    strict_env_security: bool,
+    /// Apply a run profile from doxcer.toml
+    #[arg(long, global = true, value_name = "NAME")]
+    run_profile: Option<String>,
+    /// The prompt template (default: found like .env, see README)
+    #[arg(long, global = true, value_name = "FILE")]
+    template: Option<PathBuf>,
+    /// Print the version and the user agent sent to APIs
+    #[arg(short = 'V', long)]
+    version: bool,
+    /// Print the stable exit-code table
+    #[arg(long)]
+    explain_exit_codes: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Document notebooks and directories to stdout (also: doxcer <paths>...)
+    #[command(override_usage = "doxcer [generate] [OPTIONS] <PATHS>...")]
+    Generate(Box<GenerateArgs>),
+    /// Check that committed docs are up to date, or regenerate them with --fix
+    Verify(Box<VerifyArgs>),
+    /// Fail when committed docs are stale or missing; never writes (for CI)
+    Check(CheckArgs),
+    /// Set up a project: doxcer.toml, .env, .gitignore and the templates
+    Init(InitArgs),
+    /// Print the effective configuration (secrets redacted)
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Documented and stale notebooks per folder, no API calls
+    Coverage(CoverageArgs),
+    /// Check the local setup and print the configuration
+    Doctor(ConfigArgs),
+    /// Fast health check for container probes, no tokens spent
+    Selftest(SelftestArgs),
+    /// Inspect and maintain the response cache
+    #[command(subcommand)]
+    Cache(CacheCommand),
+    /// Run, cache and token totals per tag or template
+    #[command(subcommand)]
+    Runs(RunsCommand),
+    /// Validate, print or export the prompt templates
+    #[command(subcommand)]
+    Template(TemplateCommand),
+    /// Manage the git pre-commit hook
+    #[command(subcommand)]
+    Hook(HookCommand),
+    /// Generate a Fernet key
+    Keygen(KeygenArgs),
+    /// Turn a secret into its _ENC value
+    Encrypt(EncryptArgs),
+    /// Turn an _ENC value back into the secret
+    Decrypt(SecretArgs),
+    /// Preview the generated docs in a browser
+    Serve(ServeArgs),
+    /// Regenerate a notebook's documentation whenever it changes
+    Watch(WatchArgs),
+    /// Document the models of a dbt project, optionally into schema.yml
+    Dbt(DbtArgs),
+}
+
+#[derive(Args)]
+struct GenerateArgs {
+    /// Notebooks, directories, https:// URLs or export.zip!member paths
+    #[arg(value_name = "PATHS")]
+    paths: Vec<String>,
+    /// Also place the Markdown on the clipboard (single notebook)
+    #[arg(long)]
+    copy: bool,
+    /// Write each document to <dir>/<stem>.md instead of stdout
+    #[arg(long, value_name = "DIR", conflicts_with = "output_file")]
+    output: Option<PathBuf>,
+    /// Write the single document to this file
+    #[arg(long, value_name = "PATH")]
+    output_file: Option<PathBuf>,
+    /// Replace an existing document
+    #[arg(long, conflicts_with = "backup")]
+    overwrite: bool,
+    /// Keep an existing document as <file>.bak
+    #[arg(long)]
+    backup: bool,
+    /// Print the resolved settings instead of sending the request
+    #[arg(long)]
+    dry_run: bool,
+    /// Only list the notebooks that would be documented
+    #[arg(long)]
+    list: bool,
+    /// Show the configuration and the prompt size per notebook
+    #[arg(long)]
+    verbose: bool,
+    /// Wait for the whole answer instead of streaming it
+    #[arg(long)]
+    no_stream: bool,
+    /// Skip notebooks unchanged since they were last documented
+    #[arg(long)]
+    changed_only: bool,
+    /// Write the documentation into the notebook itself
+    #[arg(long)]
+    inject: bool,
+    /// Document the notebooks a git diff range changed, e.g. origin/main...HEAD
+    #[arg(long, value_name = "RANGE")]
+    git_diff: Option<String>,
+    /// Manifest of the documented notebooks
+    #[arg(long, value_name = "FILE", default_value = DEFAULT_MANIFEST_PATH)]
+    manifest: PathBuf,
+    /// Document the lines <start>:<end> only
+    #[arg(long, value_name = "START:END", group = "selector", value_parser = parse_range)]
+    range: Option<Selector>,
+    /// Document one cell: an index, a range start-end or a title
+    #[arg(long, value_name = "CELL", group = "selector", value_parser = parse_cell_selector)]
+    cell: Option<Selector>,
+    /// Document a list of cells and ranges, e.g. 3-10,15
+    #[arg(long, value_name = "LIST", group = "selector", value_parser = parse_cell_list)]
+    cells: Option<Selector>,
+    /// Neighbouring cells sent along as context
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_CONTEXT_CELLS)]
+    context_cells: usize,
+    /// Wrap prose at this width on a terminal (0 to disable)
+    #[arg(long, value_name = "COLS")]
+    wrap: Option<usize>,
+    /// Name of a downloaded notebook
+    #[arg(long, value_name = "NAME")]
+    name: Option<String>,
+    /// What to write: full, summary or full,summary
+    #[arg(long = "mode", value_name = "MODES", default_value = "full", value_parser = parse_modes)]
+    modes: ::std::vec::Vec<DocMode>,
+    /// What the inputs are: auto, notebook, sql, pipeline or semantic-model
+    // `auto` parses to `None`: the kind is then detected per file.
+    #[arg(long, value_name = "KIND", default_value = "auto", value_parser = parse_kind)]
+    kind: ::std::option::Option<SourceKind>,
+    /// Document every file with this named template
+    #[arg(long, value_name = "NAME", value_parser = parse_template_name)]
+    template_name: Option<String>,
+    /// Character limit of --mode summary (default 600)
+    #[arg(long, value_name = "N", value_parser = positive::<usize>)]
+    summary_max_chars: Option<usize>,
+    /// Only document notebooks matching this pattern (repeatable)
+    #[arg(long, value_name = "PATTERN")]
+    include: Vec<String>,
+    /// Skip notebooks matching this pattern (repeatable)
+    #[arg(long, value_name = "PATTERN")]
+    exclude: Vec<String>,
+    /// Notebooks documented at a time in a batch
+    #[arg(long, visible_alias = "jobs", value_name = "N", default_value_t = 1, value_parser = positive::<usize>)]
+    concurrency: usize,
+    #[command(flatten)]
+    model: ModelArgs,
+    #[command(flatten)]
+    run: RunOptions,
+}
+
+// The flags that pick and tune the model of `generate`.
+#[derive(Args)]
+struct ModelArgs {
+    /// Provider: openai, azure, azure-deployment, anthropic or ollama
+    #[arg(long, value_name = "NAME")]
+    provider: Option<String>,
+    /// Model (or Azure deployment) to use
+    #[arg(long, value_name = "NAME")]
+    model: Option<String>,
+    /// Models to try on capacity errors, e.g. gpt-4.1-mini,gpt-4o
+    #[arg(long, value_name = "M1,M2")]
+    fallback_models: Option<String>,
+    /// Output token limit of the answer
+    #[arg(long, value_name = "N", value_parser = positive::<u32>)]
+    max_output_tokens: Option<u32>,
+    /// Sampling temperature
+    #[arg(long, value_name = "T")]
+    temperature: Option<f64>,
+    /// Nucleus sampling
+    #[arg(long, value_name = "P")]
+    top_p: Option<f64>,
+    /// Reasoning effort: minimal, low, medium or high
+    #[arg(long, value_name = "EFFORT")]
+    reasoning_effort: Option<ReasoningEffort>,
+    /// Size limit of the answer in bytes
+    #[arg(long, value_name = "N", value_parser = positive::<u64>)]
+    max_output_bytes: Option<u64>,
+    /// Config file (default: ./doxcer.toml)
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+}
+
+// `check` fills these in itself, so they also have a `Default`.
+#[derive(Args, Default)]
+struct VerifyArgs {
+    /// Notebooks, directories or export.zip[!folder] archives
+    #[arg(value_name = "PATHS")]
+    paths: Vec<PathBuf>,
+    /// Regenerate the stale and missing documents
+    #[arg(long)]
+    fix: bool,
+    /// Only list the notebooks that would be checked
+    #[arg(long)]
+    list: bool,
+    /// Show more about each notebook
+    #[arg(long)]
+    verbose: bool,
+    /// Directory of the documents
+    #[arg(long, value_name = "DIR", default_value = DEFAULT_DOCS_DIR)]
+    docs_dir: PathBuf,
+    /// Manifest of the documented notebooks
+    #[arg(long, value_name = "FILE", default_value = DEFAULT_MANIFEST_PATH)]
+    manifest: PathBuf,
+    /// Config file (default: ./doxcer.toml)
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// Skip notebooks matching this pattern (repeatable)
+    #[arg(long, value_name = "PATTERN")]
+    exclude: Vec<String>,
+    /// Write a split document over this size: lines, or bytes like 64kb
+    #[arg(long, value_name = "LIMIT")]
+    split_output: Option<SplitLimit>,
+    /// Change only the sections the notebook diff affects
+    #[arg(long)]
+    edit_mode: bool,
+    /// Rewrite only this ## section of an up-to-date document (repeatable)
+    #[arg(long = "section", value_name = "HEADING")]
+    sections: Vec<String>,
+    /// Formats to write: md, json and html, e.g. md,json
+    #[arg(long, value_name = "FORMATS", value_parser = parse_formats)]
+    format: Option<::std::vec::Vec<OutputFormat>>,
+    /// File or directory of the JSON documents
+    #[arg(long, value_name = "FILE.JSON|DIR")]
+    json_out: Option<JsonOut>,
+    /// Run this command after every regenerated document (repeatable)
+    #[arg(long = "post-hook", value_name = "COMMAND")]
+    post_hooks: Vec<String>,
+    /// Time limit of a post-hook in seconds (default 60)
+    #[arg(long, value_name = "SECS", value_parser = positive::<u64>)]
+    post_hook_timeout: Option<u64>,
+    /// Also generate docs/overview.md for the whole workspace
+    #[arg(long)]
+    workspace_summary: bool,
+    /// Skip the overview above this percentage of failed documents (default 20)
+    #[arg(long, value_name = "PERCENT", value_parser = percentage)]
+    summary_max_failed: Option<u32>,
+    /// Regenerate the documents that failed before (implies --fix)
+    #[arg(long)]
+    retry_failed: bool,
+    /// Quarantine a notebook after this many failures (default 3)
+    #[arg(long = "max-attempts-per-file", value_name = "N", value_parser = positive::<u32>)]
+    max_attempts: Option<u32>,
+    /// Also process quarantined notebooks
+    #[arg(long)]
+    include_quarantined: bool,
+    /// Keep the input order instead of documenting upstream notebooks first
+    #[arg(long)]
+    no_dependency_order: bool,
+    /// Add the notebooks started with %run to the batch
+    #[arg(long = "follow-runs")]
+    follow: bool,
+    /// Only notebooks that differ from HEAD
+    #[arg(long)]
+    changed: bool,
+    /// Only staged notebooks, read from the git index
+    #[arg(long)]
+    staged: bool,
+    /// Check the notebooks as they are at this tag, branch or commit
+    #[arg(long, value_name = "GIT-REF")]
+    at_ref: Option<String>,
+    // Refused with a pointer to single-notebook `generate`.
+    #[arg(long, hide = true)]
+    copy: bool,
+    #[command(flatten)]
+    run: RunOptions,
+}
+
+#[derive(Args)]
+struct CheckArgs {
+    /// Notebooks, directories or export.zip[!folder] archives
+    #[arg(value_name = "PATHS")]
+    paths: Vec<PathBuf>,
+    /// Directory of the documents
+    #[arg(long, value_name = "DIR", default_value = DEFAULT_DOCS_DIR)]
+    docs_dir: PathBuf,
+    /// Manifest of the documented notebooks
+    #[arg(long, value_name = "FILE", default_value = DEFAULT_MANIFEST_PATH)]
+    manifest: PathBuf,
+    /// Config file (default: ./doxcer.toml)
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// Skip notebooks matching this pattern (repeatable)
+    #[arg(long, value_name = "PATTERN")]
+    exclude: Vec<String>,
+    /// Only notebooks that differ from HEAD
+    #[arg(long)]
+    changed: bool,
+    /// Only staged notebooks, read from the git index
+    #[arg(long)]
+    staged: bool,
+    /// Check the notebooks as they are at this tag, branch or commit
+    #[arg(long, value_name = "GIT-REF")]
+    at_ref: Option<String>,
+    /// Write a JUnit report: junit=<path> (repeatable)
+    #[arg(long = "report", value_name = "REPORT")]
+    reports: Vec<Report>,
+    /// Show more about each notebook
+    #[arg(long)]
+    verbose: bool,
+    // Refused with a pointer to `verify`, which regenerates documents.
+    #[arg(long, hide = true)]
+    fix: bool,
+    #[arg(long, hide = true)]
+    retry_failed: bool,
+}
+
+#[derive(Args)]
+struct InitArgs {
+    /// Project directory (default: the current directory)
+    #[arg(value_name = "DIR")]
+    dir: Option<PathBuf>,
+    /// Provider: openai, azure, azure-deployment, anthropic or ollama
+    #[arg(long, value_name = "NAME", default_value = DEFAULT_PROVIDER, value_parser = ["openai", "azure", "azure-deployment", "anthropic", "ollama"])]
+    provider: String,
+    /// Model (or Azure deployment) for doxcer.toml
+    #[arg(long, value_name = "NAME")]
+    model: Option<String>,
+    /// Replace doxcer.toml and the templates when they exist
+    #[arg(long)]
+    overwrite: bool,
+}
+
+#[derive(Args)]
+struct ConfigArgs {
+    /// Config file (default: ./doxcer.toml)
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print the effective configuration (secrets redacted)
+    Dump(ConfigArgs),
+}
+
+#[derive(Args)]
+struct CoverageArgs {
+    /// Notebooks and directories (default: .)
+    #[arg(value_name = "PATHS")]
+    paths: Vec<PathBuf>,
+    /// Directory of the documents
+    #[arg(long, value_name = "DIR", default_value = DEFAULT_DOCS_DIR)]
+    docs_dir: PathBuf,
+    /// Manifest of the documented notebooks
+    #[arg(long, value_name = "FILE", default_value = DEFAULT_MANIFEST_PATH)]
+    manifest: PathBuf,
+    /// Skip notebooks matching this pattern (repeatable)
+    #[arg(long, value_name = "PATTERN")]
+    exclude: Vec<String>,
+    /// Report format
+    #[arg(long, value_name = "FORMAT", default_value = "table", value_parser = ["table", "json"])]
+    format: String,
+    /// Fail below this fraction of documented notebooks, from 0 to 1
+    #[arg(long, value_name = "RATIO", value_parser = fraction)]
+    min_coverage: Option<f64>,
+}
+
+#[derive(Args)]
+struct SelftestArgs {
+    /// Also send an authenticated request that costs no tokens
+    #[arg(long)]
+    network: bool,
+    /// Report format
+    #[arg(long, value_name = "FORMAT", default_value = "table", value_parser = ["table", "json"])]
+    format: String,
+    /// Config file (default: ./doxcer.toml)
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum CacheCommand {
+    /// Entry count, size, hit-rate and a breakdown by model and template
+    Stats(ConfigArgs),
+    /// Remove entries, optionally filtered
+    Clear {
+        /// Only entries of this model
+        #[arg(long, value_name = "NAME")]
+        model: Option<String>,
+        /// Only entries older than this age, e.g. 7d
+        #[arg(long, value_name = "AGE", value_parser = parse_age)]
+        older_than: Option<Duration>,
+        /// Only entries made with an outdated template
+        #[arg(long)]
+        template_outdated: bool,
+        /// Config file (default: ./doxcer.toml)
+        #[arg(long, value_name = "FILE")]
+        config: Option<PathBuf>,
+    },
+    /// Print the cached generation of a notebook's current content
+    Get {
+        /// The notebook
+        #[arg(value_name = "NOTEBOOK")]
+        notebook: PathBuf,
+        /// Config file (default: ./doxcer.toml)
+        #[arg(long, value_name = "FILE")]
+        config: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum RunsCommand {
+    /// Totals of runs, cache hits and tokens per group
+    Summary {
+        /// Group by tag keys, template or model, e.g. team,template
+        #[arg(long, value_name = "KEY,...", value_parser = parse_group_by)]
+        group_by: Option<::std::vec::Vec<String>>,
+        /// Only runs from this date, timestamp or age on
+        #[arg(long, value_name = "TIME", value_parser = parse_instant)]
+        since: Option<DateTime<Utc>>,
+        /// Only runs before this date, timestamp or age
+        #[arg(long, value_name = "TIME", value_parser = parse_instant)]
+        until: Option<DateTime<Utc>>,
+        /// Report format
+        #[arg(long, value_name = "FORMAT", default_value = "table", value_parser = ["table", "csv", "json"])]
+        format: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TemplateCommand {
+    /// Validate the prompt templates and partials
+    Check {
+        /// Templates to check (default: every .md file in the template directory)
+        #[arg(value_name = "TEMPLATE.MD")]
+        paths: Vec<PathBuf>,
+        /// Template directory (default: the directory of the prompt template)
+        #[arg(long, value_name = "DIR")]
+        template_dir: Option<PathBuf>,
+        /// Config file (default: ./doxcer.toml)
+        #[arg(long, value_name = "FILE")]
+        config: Option<PathBuf>,
+        /// A template variable that counts as known: name=value (repeatable)
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_var)]
+        vars: Vec<(String, String)>,
+        /// Fail on warnings too
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Print a built-in template or partial
+    Show {
+        /// prompt, summary, notebook_summary, sql, pipeline, semantic_model or markdown_tables
+        #[arg(value_name = "NAME", default_value = "prompt.md")]
+        name: String,
+    },
+    /// Write the built-in templates to a directory
+    Export {
+        /// Target directory
+        #[arg(value_name = "DIR", default_value = DEFAULT_TEMPLATE_DIR)]
+        dir: PathBuf,
+        /// Replace existing files
+        #[arg(long)]
+        overwrite: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum HookCommand {
+    /// Add the doxcer check to the pre-commit hook
+    Install,
+    /// Remove the doxcer check from the pre-commit hook
+    Uninstall,
+}
+
+#[derive(Args)]
+struct KeygenArgs {
+    /// Append the key to this .env file as ENCRYPTION_PASSWORD
+    #[arg(long, value_name = "FILE")]
+    write_env: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct SecretArgs {
+    /// The secret's variable; a trailing _ENC is dropped
+    #[arg(long, value_name = "VAR", default_value = OPENAI_API_KEY, value_parser = parse_secret_name)]
+    name: String,
+    /// The value (default: stdin, which stays out of the shell history)
+    #[arg(long, value_name = "VALUE")]
+    value: Option<String>,
+}
+
+#[derive(Args)]
+struct EncryptArgs {
+    #[command(flatten)]
+    secret: SecretArgs,
+    /// Set <VAR>_ENC in this .env file instead of printing it
+    #[arg(long, value_name = "FILE")]
+    write_env: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct ServeArgs {
+    /// Directory of the documents
+    #[arg(value_name = "DOCS-DIR", default_value = DEFAULT_DOCS_DIR)]
+    docs_dir: PathBuf,
+    /// Address to listen on
+    #[arg(long, value_name = "ADDR", default_value = DEFAULT_HOST)]
+    host: String,
+    /// Port to listen on (default: a free one)
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    port: u16,
+    /// Manifest of the documented notebooks
+    #[arg(long, value_name = "FILE", default_value = DEFAULT_MANIFEST_PATH)]
+    manifest: PathBuf,
+}
+
+#[derive(Args)]
+struct WatchArgs {
+    /// Notebooks and directories (default: .)
+    #[arg(value_name = "PATHS")]
+    paths: Vec<PathBuf>,
+    /// Directory of the documents
+    #[arg(long, value_name = "DIR", default_value = DEFAULT_DOCS_DIR)]
+    docs_dir: PathBuf,
+    /// Only watch notebooks matching this pattern (repeatable)
+    #[arg(long, value_name = "PATTERN")]
+    include: Vec<String>,
+    /// Skip notebooks matching this pattern (repeatable)
+    #[arg(long, value_name = "PATTERN")]
+    exclude: Vec<String>,
+    /// How often settled changes and finished runs are checked, in milliseconds
+    #[arg(long = "interval-ms", value_name = "MS", default_value_t = DEFAULT_TICK_MS, value_parser = positive::<u64>)]
+    interval: u64,
+    /// Quiet time before a changed notebook is documented, in milliseconds
+    #[arg(long = "debounce-ms", value_name = "MS", default_value_t = DEFAULT_DEBOUNCE_MS, value_parser = positive::<u64>)]
+    debounce: u64,
+    /// Notebooks documented at a time
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = positive::<usize>)]
+    jobs: usize,
+    /// Queued notebooks before the oldest is dropped (default 256)
+    #[arg(long = "queue-capacity", value_name = "N", value_parser = positive::<usize>)]
+    capacity: Option<usize>,
+    /// Show the queue depth and running jobs
+    #[arg(long)]
+    verbose: bool,
+    /// Options passed on to every generate run
+    #[arg(last = true, value_name = "GENERATE OPTIONS")]
+    passed: Vec<String>,
+}
+
+#[derive(Args)]
+struct DbtArgs {
+    /// The dbt project (default: .)
+    #[arg(value_name = "PROJECT-DIR", default_value = ".")]
+    project: PathBuf,
+    /// Directory of the documents
+    #[arg(long, value_name = "DIR", default_value = DEFAULT_DOCS_DIR)]
+    docs_dir: PathBuf,
+    /// Only document models matching this pattern (repeatable)
+    #[arg(long, value_name = "PATTERN")]
+    select: Vec<String>,
+    /// Write each model's description into its schema.yml
+    #[arg(long)]
+    write_schema: bool,
+    /// Options passed on to the generate batch
+    #[arg(last = true, value_name = "GENERATE OPTIONS")]
+    passed: Vec<String>,
+}
+
+fn positive<T: FromStr + PartialOrd + Default>(value: &str) -> Result<T, String> {
+
+    /// Parses a number above zero, for the count, size and time flags.
+
+    value.parse().ok().filter(|n| *n > T::default()).ok_or_else(|| "expected a positive number".to_string())
+}
+
+fn percentage(value: &str) -> Result<u32, String> {
+
+    /// Parses a whole percentage from 0 to 100.
+
+    value.parse().ok().filter(|p| *p <= 100).ok_or_else(|| "expected a percentage from 0 to 100".to_string())
+}
+
+fn fraction(value: &str) -> Result<f64, String> {
+
+    /// Parses a fraction from 0 to 1.
+
+    value.parse().ok().filter(|r| (0.0..=1.0).contains(r)).ok_or_else(|| "expected a fraction from 0 to 1".to_string())
+}
+
+fn parse_kind(value: &str) -> Result<Option<SourceKind>, DoxcerError> {
+
+    /// Parses `--kind`, where `auto` (detect per file) is `None`.
+
+    match value {
+        "auto" => Ok(None),
+        other => other.parse().map(Some),
+    }
+}
+
+fn parse_cell_selector(value: &str) -> Result<Selector, DoxcerError> {
+
+    /// Parses `--cell`; a value that is no index or range is a cell title.
+
+    Ok(parse_cell(value))
+}
+
+fn parse_template_name(value: &str) -> Result<String, DoxcerError> {
+
+    /// Checks a `--template-name`, see [`template_file_name`].
+
+    template_file_name(value)?;
+    Ok(value.to_string())
+}
+
+fn parse_group_by(value: &str) -> Result<Vec<String>, String> {
+
+    /// Parses the comma-separated `--group-by` keys.
+
+    value
+        .split(',')
+        .map(str::trim)
+        .map(|key| match key.is_empty() || key.contains(['=', '\n', '\r']) {
+            true => Err(format!("invalid key {key:?}")),
+            false => Ok(key.to_string()),
+        })
+        .collect()
+}
+
+fn parse_secret_name(value: &str) -> Result<String, String> {
+
+    /// Parses the `--name` of `encrypt` and `decrypt`, without a trailing `_ENC`.
+
+    let name = value.strip_suffix("_ENC").unwrap_or(value);
+    match name.is_empty() {
+        true => Err("expected a variable name".to_string()),
+        false => Ok(name.to_string()),
+    }
+}
+
+fn command_line() -> clap::Command {
+
+    /// Builds the command line with every help page starting with its usage.
+
+    usage_first(Cli::command())
+}
+
+fn usage_first(command: clap::Command) -> clap::Command {
+
+    /// Applies [`HELP_TEMPLATE`] to `command` and all of its subcommands.
+
+    command.help_template(HELP_TEMPLATE).mut_subcommands(usage_first)
+}
+
+fn with_default_command(mut args: Vec<String>) -> Vec<String> {
+
+    /// Inserts `generate` where the arguments name no command, so that
+    /// `doxcer <paths>...` keeps documenting notebooks. The global flags and
+    /// their values may come first. A word that is neither a command nor a
+    /// path is left for clap to report as an unknown command.
+
+    let mut i = 0;
+    while let Some(arg) = args.get(i).map(String::as_str) {
+        match arg {
+            "--color" | "--run-profile" | "--template" => i += 2,
+            "--strict-env-security" => i += 1,
+            _ if arg.starts_with("--color=") || arg.starts_with("--run-profile=") || arg.starts_with("--template=") => i += 1,
+            "--help" | "-h" | "--version" | "-V" | "--explain-exit-codes" => break,
+            _ if COMMAND_NAMES.contains(&arg) => break,
+            _ if !arg.starts_with('-') && !looks_like_path(arg) => break,
+            _ => {
+                args.insert(i, "generate".to_string());
+                break;
+            }
+        }
+    }
+    args
+}
+
+fn looks_like_path(arg: &str) -> bool {
+
+    /// Whether a bare argument names an input rather than a misspelled command:
+    /// an existing file or directory, or anything with a separator, an
+    /// extension, an archive member (`!`) or a URL scheme.
+
+    let separators = ['/', '\\', '.', '!', ':'];
+    arg.contains(separators) || Path::new(arg).exists()
+}
+
+fn usage_error(command: &str, message: &str) -> DoxcerError {
+
+    /// Returns a usage error with the usage of `command`, in the form clap
+    /// reports its own errors.
+
+    let mut cli = command_line();
+    cli.build();
+    let usage = cli.find_subcommand_mut(command).map(|c| c.render_usage().to_string()).unwrap_or_default();
+    DoxcerError::Usage(format!("{message}\n\n{usage}\n\nFor more information, try '--help'."))
+}
+
+fn parse_command_line(args: Vec<String>) -> Result<Option<Cli>, DoxcerError> {
+
+    /// Parses the arguments (without the program name).
+    ///
+    /// # Returns
+    /// * `Ok(None)` when help was asked for and printed.
+    /// * `Err(DoxcerError::Usage)` with clap's message for an invalid command line.
+
+    let args = std::iter::once("doxcer".to_string()).chain(with_default_command(args));
+    match command_line().try_get_matches_from(args).and_then(|matches| Cli::from_arg_matches(&matches)) {
+        Ok(cli) => Ok(Some(cli)),
+        Err(e) if !e.use_stderr() => {
+            out!("{}", e.render());
+            Ok(None)
+        }
+        Err(e) => {
+            let message = e.render().to_string();
+            Err(DoxcerError::Usage(message.strip_prefix("error: ").unwrap_or(&message).trim_end().to_string()))
+        }
+    }
+}
+
+
+// ----------------------------
+// Helper Functions
+// ----------------------------
+fn warn(message: impl Display) {
+
+    /// Prints a non-fatal diagnostic with a (possibly colored) `warning:` prefix.
+
+    errln!("{} {message}", Style::stderr().warning_prefix());
+}
+
+fn report_error(error: &DoxcerError) {
+
+    /// Prints a fatal diagnostic with a (possibly colored) `error:` prefix.
+
+    errln!("{} {error}", Style::stderr().error_prefix());
+}
+
+fn load_env() -> Result<(), DoxcerError> {
+
+    /// Loads the `.env` file via [`load_env_robust`] and reports where it was found.
+    ///
+    /// A missing `.env` is not an error here: a provider without a key
+    /// (Ollama) needs none. The searched locations are kept, and
+    /// [`provider_api_key`] fails with them when the key is not set otherwise.
+
+    let found = match load_env_robust::<&Path>(None) {
+        Ok(found) => found,
+        Err(DoxcerError::EnvFileNotFound { searched }) => {
+            let _ = ENV_SEARCHED.set(searched);
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    };
+    errln!("Loaded .env from: {}", found.display());
+    check_env_safety(&found)?;
+    Ok(())
+}
+
+fn check_env_safety(env_file: &Path) -> Result<(), DoxcerError> {
+
+    /// Warns when the loaded `.env` file is readable by others or not covered
+    /// by the repository's `.gitignore`; with `--strict-env-security` these are
+    /// configuration errors instead.
+
+    let problems = env_safety_problems(env_file);
+    if problems.is_empty() {
+        return Ok(());
+    }
+    if STRICT_ENV_SECURITY.load(Ordering::Relaxed) {
+        return Err(DoxcerError::Config(format!("{} (--strict-env-security)", problems.join("\n"))));
+    }
+    for problem in problems {
+        warn(problem);
+    }
+    Ok(())
+}
+
+fn provider_api_key(provider: &dyn LlmProvider) -> Result<String, DoxcerError> {
+
+    /// Reads the API key of a provider from the environment.
+    ///
+    /// # Description
+    /// The OpenAI key must be encrypted: `OPENAI_API_KEY_ENC`, decrypted with
+    /// `ENCRYPTION_PASSWORD` (or the variable named by `OPENAI_API_KEY_ENC_KEY`).
+    /// Other keys, such as `AZURE_OPENAI_API_KEY`, may be given in plain text or
+    /// as `<name>_ENC` (see [`env_secret`]). A provider without authentication
+    /// (Ollama) gets an empty key. The `.env` file must already be loaded
+    /// ([`load_env`]); when there was none and the key is not in the
+    /// environment either, the error lists where the `.env` was looked for.
+
+    let Some(name) = provider.api_key_name() else {
+        return Ok(String::new());
+    };
+    let key = read_api_key(name);
+    match ENV_SEARCHED.get() {
+        Some(searched) if key.is_err() => Err(DoxcerError::EnvFileNotFound { searched: searched.clone() }),
+        _ => key,
+    }
+}
+
+fn read_api_key(name: &str) -> Result<String, DoxcerError> {
+
+    /// Reads the API key `name` from the environment; see [`provider_api_key`].
+
+    if name != OPENAI_API_KEY {
+        return env_secret(name, None).map_err(|e| DoxcerError::Config(format!("Failed to load {name}: {e}")));
+    }
+    let key_name = secret_key_name(name);
+    let encryption_key = env::var(&key_name).map_err(|_| DoxcerError::Config(format!("Missing {key_name} in .env")))?;
+    let encrypted_api_key = env::var("OPENAI_API_KEY_ENC").map_err(|_| DoxcerError::Config("Missing OPENAI_API_KEY_ENC in .env".to_string()))?;
+    decrypt_fernet(&encrypted_api_key, &encryption_key).map_err(|e| DoxcerError::Config(format!("Failed to decrypt API key: {e}")))
+}
+
+fn http_client() -> Result<Client, DoxcerError> {
+
+    /// Builds the HTTP client shared by all outgoing requests.
+    ///
+    /// # Description
+    /// Identifies doxcer to API gateways with `User-Agent: doxcer/<version> (<os>; <arch>)`,
+    /// optionally followed by `DOXCER_USER_AGENT_SUFFIX`. Proxies come from the
+    /// usual `HTTPS_PROXY`/`NO_PROXY` variables; at most 5 redirects are followed.
+    ///
+    /// Connecting may take 10 seconds, and waiting for the answer or for the
+    /// next part of its body 5 minutes. These also bound a request that Ctrl-C
+    /// gave up on: its worker thread and connection end at the latest then.
+
+    Client::builder()
+        .user_agent(user_agent())
+        .redirect(reqwest::redirect::Policy::limited(MAX_REDIRECTS))
+        .connect_timeout(Duration::from_secs(HTTP_CONNECT_TIMEOUT_SECS))
+        .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| DoxcerError::Config(format!("Failed to build HTTP client: {e}")))
+}
+
+fn log_run(run: &RunContext, record: RunRecord) {
+
+    /// Appends a record to the run log, warning (not failing) when that is impossible.
+    /// The record is stamped with the template hash and the run tags.
+
+    let record = RunRecord {
+        template_hash: Some(run.setup.template.hash.clone()),
+        tags: run.tags.clone(),
+        ..record
+    };
+    if let Err(e) = append_run_record(Path::new(DEFAULT_RUN_LOG_PATH), &record) {
+        warn(e);
+    }
+}
+
+fn template_path() -> Result<Option<PathBuf>, DoxcerError> {
+
+    /// Finds the prompt template via [`resolve_template`], with the global
+    /// `--template <file>` flag as its explicit path.
+    ///
+    /// Returns `None` when no template file exists, so the built-in
+    /// `prompt.md` is used.
+
+    match resolve_template(TEMPLATE_OVERRIDE.get()) {
+        Ok(path) => Ok(Some(path)),
+        Err(DoxcerError::TemplateNotFound { .. }) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn prompt_template_dir() -> PathBuf {
+
+    /// Returns the directory of the prompt template, or `./templates` when none
+    /// is found, so the built-in fallbacks of the other templates still apply.
+
+    template_path()
+        .ok()
+        .flatten()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_TEMPLATE_DIR))
+}
+
+fn companion_template(name: &str, lang: Option<&str>) -> PathBuf {
+
+    /// Returns the path of a template that lives next to the prompt template,
+    /// such as `summary.md` or `sql.md`, or its variant for `lang`.
+
+    language_variant(&prompt_template_dir().join(name), lang)
+}
+
+fn load_setup(cli: &SettingsLayer, config_path: Option<&Path>) -> Result<GenerationSetup, DoxcerError> {
+
+    /// Loads the prompt template and resolves the effective generation settings.
+    ///
+    /// # Description
+    /// Settings are layered as built-in defaults < template front-matter <
+    /// `doxcer.toml` < `DOXCER_*` environment variables < CLI flags. Warnings about
+    /// unknown template keys are printed to stderr.
+
+    let lang = requested_lang(cli, config_path);
+    let path = template_path()?.map(|path| language_variant(&path, lang.as_deref()));
+    setup_from_template(load_prompt_template(path.as_deref())?, cli, config_path)
+}
+
+fn load_summary_setup(cli: &SettingsLayer, config_path: Option<&Path>) -> Result<GenerationSetup, DoxcerError> {
+
+    /// Loads the setup for `--mode summary` from the notebook summary template.
+    ///
+    /// # Description
+    /// Settings are layered as in [`load_setup`], with two exceptions: the
+    /// `max_output_tokens` of the summary template always wins, so the limit
+    /// meant for full documents does not apply to summaries, and
+    /// `required_sections` is cleared.
+
+    let template = load_notebook_summary_template(&companion_template(NOTEBOOK_SUMMARY_TEMPLATE_NAME, requested_lang(cli, config_path).as_deref()))?;
+    let max_output_tokens = template.settings.max_output_tokens;
+    let mut setup = setup_from_template(template, cli, config_path)?;
+    if let Some(value) = max_output_tokens {
+        setup.settings.max_output_tokens = Setting { value: Some(value), source: Source::Template };
+    }
+    setup.settings.required_sections = Setting { value: Vec::new(), source: Source::Default };
+    Ok(setup)
+}
+
+fn load_kind_setup(kind: SourceKind, cli: &SettingsLayer, config_path: Option<&Path>) -> Result<GenerationSetup, DoxcerError> {
+
+    /// Loads the setup for SQL scripts, Data Pipelines or semantic models from
+    /// their own template, with the settings layered as in [`load_setup`].
+
+    let lang = requested_lang(cli, config_path);
+    let template = match kind {
+        SourceKind::Notebook => return load_setup(cli, config_path),
+        SourceKind::Sql => load_sql_template(&companion_template(SQL_TEMPLATE_NAME, lang.as_deref()))?,
+        SourceKind::Pipeline => load_pipeline_template(&companion_template(PIPELINE_TEMPLATE_NAME, lang.as_deref()))?,
+        SourceKind::SemanticModel => load_semantic_model_template(&companion_template(SEMANTIC_MODEL_TEMPLATE_NAME, lang.as_deref()))?,
+    };
+    let mut setup = setup_from_template(template, cli, config_path)?;
+    setup.kind = kind;
+    setup.template_name = kind_template_name(kind);
+    Ok(setup)
+}
+
+fn load_named_setup(kind: SourceKind, name: &str, cli: &SettingsLayer, config_path: Option<&Path>) -> Result<GenerationSetup, DoxcerError> {
+
+    /// Loads the setup for a named template (`--template-name`, `[[templates]]`
+    /// in `doxcer.toml`) to document files of `kind` with.
+    ///
+    /// # Description
+    /// The names of the kinds (`notebook`, `sql`, `pipeline`, `semantic-model`)
+    /// load the template of that kind, with its built-in fallback; any other
+    /// name is `<name>.md` next to the prompt template and must exist.
+
+    let mut setup = match name.parse::<SourceKind>() {
+        Ok(template_kind) => load_kind_setup(template_kind, cli, config_path)?,
+        Err(_) => {
+            let dir = prompt_template_dir();
+            let path = dir.join(template_file_name(name)?);
+            if !path.is_file() {
+                return Err(DoxcerError::Config(format!("Unknown template `{name}`: {} does not exist; available: {}", path.display(), available_templates(&dir).join(", "))));
+            }
+            setup_from_template(load_template(&language_variant(&path, requested_lang(cli, config_path).as_deref()))?, cli, config_path)?
+        }
+    };
+    setup.kind = kind;
+    setup.template_name = name.to_string();
+    Ok(setup)
+}
+
+fn load_template_routes(config_path: Option<&Path>) -> Result<Vec<TemplateRoute>, DoxcerError> {
+
+    /// Reads the `[[templates]]` entries of `doxcer.toml`; they pick the
+    /// template before any settings are resolved, so only the config file
+    /// can set them.
+
+    match resolve_config_path(config_path) {
+        Some(path) => Ok(load_config_file(&path)?.template_routes),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn plausible_kind(kind: SourceKind, path: &Path) -> bool {
+
+    /// Whether a file could be documented as `kind` under `--kind auto`: every
+    /// file can fall back to a notebook.
+
+    match kind {
+        SourceKind::Notebook => true,
+        SourceKind::Sql => is_sql_path(path),
+        SourceKind::Pipeline => is_json_path(path),
+        SourceKind::SemanticModel => is_semantic_model_path(path),
+    }
+}
+
+fn setup_from_template(template: Template, cli: &SettingsLayer, config_path: Option<&Path>) -> Result<GenerationSetup, DoxcerError> {
+
+    /// Resolves the generation settings around an already loaded template.
+    ///
+    /// # Description
+    /// The selected run profile (`--run-profile`, `DOXCER_RUN_PROFILE`) and the
+    /// profiles it inherits from sit between `doxcer.toml` and the environment.
+    /// The context files of the resolved settings are read here (see
+    /// [`load_context`]), so a missing one stops the run before any request.
+
+    for warning in &template.warnings {
+        warn(warning);
+    }
+
+    let config = match resolve_config_path(config_path) {
+        Some(path) => load_config_file(&path)?,
+        None => ConfigFile::default(),
+    };
+    let chain = match selected_run_profile(RUN_PROFILE.get().map(String::as_str)) {
+        Some(name) => config.profile_chain(&name)?,
+        None => Vec::new(),
+    };
+    let env = env_layer()?;
+
+    let mut layers = vec![(Source::Template, &template.settings), (Source::ConfigFile, &config.settings)];
+    layers.extend(chain.iter().map(|(_, layer)| (Source::Profile, *layer)));
+    layers.extend([(Source::Env, &env), (Source::Cli, cli)]);
+    let settings = Settings::resolve(&layers);
+    validate_routing(&settings.model_routing.value)?;
+    check_sampling(settings.temperature.value, settings.top_p.value)?;
+    let run_profile = chain.iter().map(|(name, _)| name.to_string()).collect();
+
+    let provider = select_provider(&settings.provider.value, settings.provider_endpoint.value.as_deref())?;
+    let profiles = resolve_profiles(&settings.profiles.value)?;
+    let secrets = SecretScanner::new(&settings.secret_patterns.value)?;
+    let context = load_context(&settings.context_files.value, settings.context_files.source, settings.max_context_tokens.value, &settings.model.value)?;
+
+    Ok(GenerationSetup { provider, template, settings, profiles, pipeline: Pipeline::default(), secrets, upstream_summaries: String::new(), child_notebooks: String::new(), run_history: None, edit_instruction: None, run_profile, context, kind: SourceKind::Notebook, template_name: kind_template_name(SourceKind::Notebook) })
+}
+
+fn display_locale(config_path: Option<&Path>) -> Locale {
+
+    /// Resolves the locale for human-facing output of commands that do not load
+    /// the prompt template, from `lang` in `doxcer.toml`, the run profile and
+    /// `DOXCER_LANG`. Problems with any of them are reported by the commands
+    /// that need them.
+
+    Locale::from_lang(requested_lang(&SettingsLayer::default(), config_path).as_deref())
+}
+
+fn requested_lang(cli: &SettingsLayer, config_path: Option<&Path>) -> Option<String> {
+
+    /// Resolves `lang` without a template: from `doxcer.toml`, the run profile,
+    /// `DOXCER_LANG` and `--lang`. It picks the language variant of a template
+    /// (see [`language_variant`]), so a `lang` in the front-matter of that
+    /// template cannot.
+
+    let config = resolve_config_path(config_path)
+        .and_then(|path| load_config_file(&path).ok())
+        .unwrap_or_default();
+    let chain = selected_run_profile(RUN_PROFILE.get().map(String::as_str))
+        .and_then(|name| config.profile_chain(&name).ok())
+        .unwrap_or_default();
+    let env = env_layer().unwrap_or_default();
+    let mut layers = vec![(Source::ConfigFile, &config.settings)];
+    layers.extend(chain.iter().map(|(_, layer)| (Source::Profile, *layer)));
+    layers.extend([(Source::Env, &env), (Source::Cli, cli)]);
+    Settings::resolve(&layers).lang.value
+}
+
+fn resolve_pipeline(options: &RunOptions) -> Result<Pipeline, DoxcerError> {
+
+    /// Builds the prompt pipeline from `--disable-stage` and `--pipeline-dump`.
+
+    Pipeline::new(&options.disabled_stages, options.pipeline_dump.clone())
+        .map_err(|e| DoxcerError::Usage(format!("--disable-stage: {e}")))
+}
+
+fn secret_patterns_layer(options: &RunOptions) -> Result<Option<BTreeMap<String, SecretPatternConfig>>, DoxcerError> {
+
+    /// Reads the `--secret-patterns` file into the CLI settings layer, so its
+    /// patterns are merged over `[secret_patterns]` from `doxcer.toml`.
+
+    let Some(path) = options.secret_patterns.as_ref() else {
+        return Ok(None);
+    };
+    Ok(Some(load_secret_patterns_file(path)?))
+}
+
+fn prepare_prompt(setup: &GenerationSetup, notebook_path: &Path, notebook: &str) -> Result<(Prompt, PiiReport), DoxcerError> {
+
+    /// Turns a notebook into the prompt that is sent, via the prompt pipeline.
+    ///
+    /// # Description
+    /// The notebook passes the enabled stages in order (metadata stripping,
+    /// secret redaction, PII detection, literal collapsing, directive stripping)
+    /// before the template is rendered around it. Secret pattern and PII findings
+    /// are printed as warnings, naming what fired but never the value.
+
+    let run_stats = setup.run_history.as_ref().map(RunHistory::render).unwrap_or_default();
+    let mut context = prompt_context(notebook_path, &setup.settings, &setup.upstream_summaries, &setup.child_notebooks, &run_stats, &setup.context);
+    /// Optional placeholders this doxcer does not provide render empty; the
+    /// template loader already warned about them once.
+    for name in unavailable_placeholders(&setup.template.optional_placeholders) {
+        context.insert(name.to_string(), String::new());
+    }
+    /// Jupyter notebooks are sent as their cells, not as nbformat JSON. Chunks
+    /// and excerpts of one are cell sources already and pass unchanged.
+    let mut removed = Removed::default();
+    let notebook = match is_ipynb(notebook_path).then(|| parse_ipynb(notebook, setup.settings.include_outputs.value).ok()).flatten() {
+        Some(parsed) => {
+            if !setup.settings.include_outputs.value {
+                removed.outputs = output_count(notebook);
+            }
+            parsed.render()
+        }
+        None => notebook.to_string(),
+    };
+    /// A Data Pipeline is sent as a summary of its activities, not as the raw
+    /// JSON with its layout and workspace identifiers.
+    let notebook = match (setup.kind == SourceKind::Pipeline).then(|| parse_pipeline(&notebook).ok()).flatten() {
+        Some(parsed) => parsed.render(&pipeline_name(notebook_path).unwrap_or_else(|| display_name(notebook_path))),
+        None => notebook,
+    };
+    /// A semantic model is sent as its tables, measures and relationships.
+    let notebook = match (setup.kind == SourceKind::SemanticModel).then(|| parse_semantic_model(notebook_path, &notebook).ok()).flatten() {
+        Some(parsed) => {
+            let name = semantic_model_name(notebook_path).or_else(|| parsed.name.clone()).unwrap_or_else(|| display_name(notebook_path));
+            parsed.render(&name)
+        }
+        None => notebook,
+    };
+    let doc = PromptDoc {
+        template: setup.template.body.clone(),
+        layouts: setup.template.layouts.clone(),
+        notebook,
+        context: &context,
+        settings: &setup.settings,
+        secrets: &setup.secrets,
+        findings: Vec::new(),
+        pii: PiiReport::default(),
+        prompt: None,
+        kind: setup.kind,
+        removed,
+    };
+    let label = notebook_path.display().to_string();
+    let prepared = setup.pipeline.run(doc, &label)?;
+    for finding in &prepared.findings {
+        warn(format!("{label}: {finding}"));
+    }
+    for finding in &prepared.pii.findings {
+        warn(format!("{label}: {finding}"));
+    }
+    if !prepared.removed.is_empty() {
+        errln!("{label}: {}", prepared.removed);
+    }
+    Ok((prepared.prompt, prepared.pii))
+}
+
+fn prepared_source(setup: &GenerationSetup, notebook: &str) -> Result<String, DoxcerError> {
+
+    /// Returns a notebook as the prompt would contain it (see [`Pipeline::prepare_source`]).
+
+    let context = BTreeMap::new();
+    let doc = PromptDoc {
+        template: String::new(),
+        layouts: Vec::new(),
+        notebook: notebook.to_string(),
+        context: &context,
+        settings: &setup.settings,
+        secrets: &setup.secrets,
+        findings: Vec::new(),
+        pii: PiiReport::default(),
+        prompt: None,
+        kind: setup.kind,
+        removed: Removed::default(),
+    };
+    setup.pipeline.prepare_source(doc)
+}
+
+fn plan_edit_mode(setup: &GenerationSetup, notebook: &Path, prior_doc: Option<&str>, entry: Option<&ManifestEntry>, current: &str) -> EditPlan {
+
+    /// Decides for `--edit-mode` whether to update the previous document of a
+    /// notebook (see [`plan_edit`]), diffing the stored content the manifest
+    /// points to against the current content, both after the prompt pipeline.
+
+    let prior_source = entry.and_then(|e| e.snapshot.as_deref().and_then(|path| load_snapshot(Path::new(path), &e.source_hash)));
+    let prepared = prior_source
+        .map(|text| prepared_source(setup, &text))
+        .transpose()
+        .and_then(|prior| Ok((prior, prepared_source(setup, current)?)));
+    match prepared {
+        Ok((prior, current)) => plan_edit(prior_doc, prior.as_deref(), &current, &manifest_key(notebook), DEFAULT_MAX_DIFF_LINES),
+        Err(e) => EditPlan::Full(e.to_string()),
+    }
+}
+
+fn open_workspace(options: &RunOptions) -> Result<RunWorkspace, DoxcerError> {
+
+    /// Creates the workspace for this run after removing expired ones.
+    ///
+    /// # Description
+    /// Workspaces live under `.doxcer/cache/workspaces` (or `--workdir`). Those
+    /// older than `DOXCER_WORKDIR_MAX_AGE_HOURS` (default one week) are removed
+    /// first, so directories left behind by crashed or failed runs do not pile up.
+
+    let base = options.workdir.clone().unwrap_or_else(default_workspace_base);
+    let max_age = max_age_from_env()?;
+    if let Err(e) = gc_workspaces(&base, max_age, None) {
+        warn(e);
+    }
+    RunWorkspace::create(&base, &new_run_id(), options.keep)
+}
+
+fn open_cache(options: &RunOptions) -> ResponseCache {
+
+    /// Opens the response cache for this run.
+    ///
+    /// # Description
+    /// With `--no-cache` no answer is taken from the cache, but the fresh
+    /// answers are still stored. With `--cache-ttl <age>` (e.g. `7d`) older
+    /// entries count as missing; the request is sent again and its answer
+    /// replaces the entry.
+
+    ResponseCache { ttl: options.cache_ttl, read: !options.no_cache, ..ResponseCache::new(&default_cache_dir()) }
+}
+
+fn resolve_timestamps(options: &RunOptions) -> Result<Option<TimestampTz>, DoxcerError> {
+
+    /// Resolves the zone of the front-matter `generated_at` field.
+    ///
+    /// # Description
+    /// `--no-timestamps` leaves the field out entirely; otherwise `--timestamp-tz`
+    /// wins over `DOXCER_TZ`, and UTC is the default.
+
+    if options.no_timestamps {
+        return Ok(None);
+    }
+    let env = tz_from_env()?;
+    Ok(Some(options.timestamp_tz.or(env).unwrap_or_default()))
+}
+
+fn vars_layer(options: &RunOptions) -> Option<BTreeMap<String, String>> {
+
+    /// Returns the `--var` template variables as a settings layer; a later
+    /// `--var` of the same name wins.
+
+    Some(options.vars.iter().cloned().collect::<BTreeMap<_, _>>()).filter(|vars| !vars.is_empty())
+}
+
+fn resolve_tags(options: &RunOptions) -> Result<BTreeMap<String, String>, DoxcerError> {
+
+    /// Resolves the run tags: `DOXCER_TAGS` (`team=data,cost_center=42`), with
+    /// every `--tag key=value` replacing the value of its key.
+
+    let mut tags = match env::var(TAGS_ENV) {
+        Ok(list) => parse_tags(&list).map_err(|e| DoxcerError::Config(format!("{TAGS_ENV}: {e}")))?,
+        Err(_) => BTreeMap::new(),
+    };
+    tags.extend(options.tags.iter().cloned());
+    Ok(tags)
+}
+
+fn resolve_oversize_policy(options: &RunOptions) -> Option<OversizePolicy> {
+
+    /// Returns the oversize policy of the command line: `--oversize-policy` or
+    /// `--force-oversize`, else `chunk` when only `--chunk-strategy` is given.
+
+    options
+        .oversize_policy
+        .or(options.force_oversize.then_some(OversizePolicy::Truncate))
+        .or(options.chunk_strategy.map(|_| OversizePolicy::Chunk))
+}
+
+fn open_tracer() -> Tracer {
+
+    /// Sets up trace export from the `OTEL_EXPORTER_OTLP_*` variables (builds
+    /// with the `otel` feature); a misconfiguration only disables tracing.
+
+    Tracer::from_env().unwrap_or_else(|e| {
+        warn(e);
+        Tracer::default()
+    })
+}
+
+fn export_traces(tracer: &Tracer) {
+
+    /// Sends the recorded spans; a failed export is only a warning.
+
+    if let Err(e) = tracer.export() {
+        warn(e);
+    }
+}
+
+fn finish_workspace(workspace: RunWorkspace, success: bool) {
+
+    /// Removes the workspace after a successful run, or reports where it was kept.
+
+    match workspace.finish(success) {
+        Ok(Some(root)) => errln!("workspace kept at {}", root.display()),
+        Ok(None) => {}
+        Err(e) => warn(e),
+    }
+}
+
+fn request_key(run: &RunContext, source_hash: &str, model: &str) -> Option<String> {
+
+    /// Returns the idempotency key sent for a notebook and model, for the run log.
+    ///
+    /// # Description
+    /// `None` when no request was prepared for the notebook in this run (for
+    /// example because reading it failed).
+
+    let path = run.workspace.artifact_path(source_hash, ATTEMPT_GROUP_ARTIFACT);
+    let group = fs::read_to_string(path).ok()?;
+    Some(idempotency_key(&group, source_hash, model))
+}
+
+fn resumed_response(run: &RunContext, source_hash: &str, request_json: &str) -> Option<String> {
+
+    /// Finds a response from an earlier run for exactly the same request (`--resume`).
+
+    run.workspace
+        .previous_artifacts(source_hash, RESPONSE_ARTIFACT)
+        .into_iter()
+        .find(|response| {
+            let request = response.with_file_name(REQUEST_ARTIFACT);
+            fs::read_to_string(request).is_ok_and(|r| r == request_json)
+        })
+        .and_then(|response| {
+            errln!("resumed from {}", response.display());
+            fs::read_to_string(response).ok()
+        })
+}
+
+fn request_documentation(run: &RunContext, notebook_path: &Path, snapshot: &SourceSnapshot, text: &str) -> Result<Option<Generated>, DoxcerError> {
+
+    /// Generates the documentation for one notebook.
+    ///
+    /// # Description
+    /// Builds the prompt from the template and `text` (the notebook contents, or
+    /// an excerpt of them) and sends it
+    /// via [`generate`]. Required sections from the settings that are missing in
+    /// the answer, and answers that repeat the same text over and over, are
+    /// reported as warnings.
+    ///
+    /// # Returns
+    /// * `Ok(Some(Generated))` – the generated Markdown and the model that produced it.
+    /// * `Ok(None)` – the API answered without any output.
+    /// * `Err(DoxcerError)` – the prompt could not be prepared, the notebook is
+    ///   too large (see [`fitted_prompt`]), the request failed or the API returned
+    ///   an error status.
+    ///
+    /// With `oversize_policy = chunk`, a notebook that is too large is documented
+    /// in chunks instead, see [`chunked_documentation`]. With `granularity =
+    /// cell`, a whole notebook (not an excerpt, nor in `--edit-mode`) is
+    /// documented cell by cell, see [`cell_documentation`].
+
+    let setup = &run.setup;
+    let per_cell = setup.settings.granularity.value == Granularity::Cell
+        && setup.edit_instruction.is_none()
+        && setup.kind == SourceKind::Notebook
+        && text == snapshot.text;
+    let generated = if per_cell {
+        cell_documentation(run, notebook_path, &snapshot.hash, text)?
+    } else {
+        let render = run.tracer.span("prompt-render");
+        let prompt = fitted_prompt(run, notebook_path, text, &snapshot.hash);
+        let chunked = setup.settings.oversize_policy.value == OversizePolicy::Chunk && setup.edit_instruction.is_none();
+        if let Err(e) = &prompt
+            && !(chunked && matches!(e, DoxcerError::TooLarge(_)))
+        {
+            render.fail(e);
+        }
+        drop(render);
+        let label = notebook_path.display().to_string();
+        match prompt {
+            Err(DoxcerError::TooLarge(report)) if chunked => chunked_documentation(run, notebook_path, &snapshot.hash, text, report)?,
+            prompt => {
+                let (prompt, coverage) = prompt?;
+                generate(run, &prompt, &snapshot.hash, &label, &setup.template.hash)?
+                    .map(|generated| Generated { coverage, ..generated })
+            }
+        }
+    };
+
+    if let Some(Generated { text, .. }) = &generated {
+        // The assembled cell document has its own layout, not the template's sections.
+        if !per_cell {
+            for section in missing_sections(text, &setup.settings.required_sections.value) {
+                warn(format!("generated documentation is missing required section `{section}`"));
+            }
+        }
+        if let Some((_, count)) = repeated_window(text, REPETITION_WINDOW, REPETITION_LIMIT) {
+            warn(format!(
+                "generated documentation repeats the same {REPETITION_WINDOW}-character passage {count} times; \
+                 the output is probably runaway, consider a different model (--model)"
+            ));
+        }
+    }
+    Ok(generated)
+}
+
+fn chunked_documentation(run: &RunContext, notebook_path: &Path, source_hash: &str, text: &str, report: String) -> Result<Option<Generated>, DoxcerError> {
+
+    /// Documents a notebook that is too large for one prompt in chunks, and
+    /// merges the parts into one document (`oversize_policy = chunk`).
+    ///
+    /// # Description
+    /// The notebook is split with `chunk_strategy` (see [`split_chunks`]) into
+    /// chunks that fit `--max-total-tokens` next to the template and context.
+    /// Each chunk is documented on its own, under a banner naming its part, and
+    /// stored in the run workspace under [`chunk_key`]. A final merge prompt
+    /// gets the template again with every part, and its answer is the document.
+    /// The token usage is that of all requests together.
+    ///
+    /// # Returns
+    /// * `Ok(Some(Generated))` – the merged document.
+    /// * `Ok(None)` – the API answered one of the requests without any output.
+    /// * `Err(DoxcerError)` – a request failed, or the notebook cannot be split
+    ///   small enough (`report` is then the original size report) or the merge
+    ///   prompt does not fit.
+
+    let setup = &run.setup;
+    let settings = &setup.settings;
+    let limit = settings
+        .max_total_tokens
+        .value
+        .unwrap_or_else(|| default_max_total_tokens(&settings.model.value, settings.max_output_tokens.value));
+    let strategy = settings.chunk_strategy.value;
+    let name = notebook_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let label = notebook_path.display().to_string();
+    let model = settings.model.value.as_str();
+    let widest = Chunk { index: usize::MAX, cells: Some((usize::MAX, usize::MAX)), text: String::new() };
+    let overhead = count_tokens(&setup.template.body, model) + count_tokens(&setup.context, model) + count_tokens(&chunk_banner(&name, &widest, usize::MAX), model);
+    let chunks = split_chunks(notebook_path, text, strategy, limit.saturating_sub(overhead), model);
+    if limit <= overhead || chunks.len() < 2 {
+        return Err(DoxcerError::TooLarge(report));
+    }
+    errln!("{label}: too large for one prompt; documenting it in {} chunks (--chunk-strategy {strategy})", chunks.len());
+
+    let mut usage: Option<Usage> = None;
+    let mut cached = true;
+    let mut parts = Vec::new();
+    let total = chunks.len();
+    for chunk in chunks {
+        let key = chunk_key(source_hash, chunk.index);
+        let chunk_label = format!("{label} (chunk {}/{total})", chunk.index);
+        let input = format!("{}\n\n{}", chunk_banner(&name, &chunk, total), chunk.text);
+        let (prompt, pii) = prepare_prompt(setup, notebook_path, &input)?;
+        write_pii_audit(run, &key, &pii)?;
+        let Some(generated) = generate(run, &prompt, &key, &chunk_label, &setup.template.hash)? else {
+            return Ok(None);
+        };
+        usage = sum_usage(usage, &generated.usage);
+        cached &= generated.cached;
+        parts.push((chunk, generated.text));
+    }
+
+    let (prompt, _) = prepare_prompt(setup, notebook_path, &merge_input(&name, &parts))?;
+    let merge_tokens = prompt_tokens(&prompt, &run.setup.settings.model.value);
+    if merge_tokens > limit {
+        return Err(DoxcerError::TooLarge(format!(
+            "{label}: the merge prompt of {total} chunks is about {merge_tokens} tokens, over the limit of {limit} \
+             (--max-total-tokens); split the notebook or use a model with a larger context window"
+        )));
+    }
+    let Some(generated) = generate(run, &prompt, source_hash, &label, &setup.template.hash)? else {
+        return Ok(None);
+    };
+    let usage = sum_usage(usage, &generated.usage);
+    Ok(Some(Generated { cached: cached && generated.cached, usage, ..generated }))
+}
+
+fn cell_documentation(run: &RunContext, notebook_path: &Path, source_hash: &str, text: &str) -> Result<Option<Generated>, DoxcerError> {
+
+    /// Documents a notebook cell by cell (`--granularity cell`).
+    ///
+    /// # Description
+    /// The sections of [`cell_sections`] are assembled into one document with
+    /// a table of contents and a `cel-N` anchor per cell (see
+    /// [`assemble_cell_document`]); there is no merge request.
+    ///
+    /// # Returns
+    /// * `Ok(Some(Generated))` – the assembled document.
+    /// * `Ok(None)` – the API answered one of the requests without any output.
+    /// * `Err(DoxcerError)` – see [`cell_sections`].
+
+    let name = notebook_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let Some(CellSections { sections, model, cached, usage }) = cell_sections(run, notebook_path, source_hash, text)? else {
+        return Ok(None);
+    };
+    Ok(Some(Generated { text: assemble_cell_document(&name, &sections), model, cached, coverage: None, usage }))
+}
+
+fn cell_sections(run: &RunContext, notebook_path: &Path, source_hash: &str, text: &str) -> Result<Option<CellSections>, DoxcerError> {
+
+    /// Documents every code cell of a notebook on its own.
+    ///
+    /// # Description
+    /// Every code cell is sent with `DEFAULT_CONTEXT_CELLS` cells around it as
+    /// context and a banner asking for the section of that cell only, and
+    /// stored in the run workspace under [`cell_key`]. Markdown cells are
+    /// context, not sections. The token usage is that of all requests together.
+    ///
+    /// # Returns
+    /// * `Ok(Some(CellSections))` – the answer for each code cell, in order.
+    /// * `Ok(None)` – the API answered one of the requests without any output.
+    /// * `Err(DoxcerError)` – the notebook has no code cells, its cells could
+    ///   not be read, or a request failed.
+
+    let setup = &run.setup;
+    let name = notebook_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let label = notebook_path.display().to_string();
+    let cells: Vec<Cell> = split_cells(notebook_path, text)?.into_iter().filter(|c| !c.markdown).collect();
+    if cells.is_empty() {
+        return Err(DoxcerError::Input(format!("{label}: no code cells to document one by one")));
+    }
+    let total = cells.len();
+    errln!("{label}: documenting {total} code cell(s) one by one");
+
+    let mut usage: Option<Usage> = None;
+    let mut cached = true;
+    let mut model = String::new();
+    let mut sections = Vec::new();
+    for cell in cells {
+        let key = cell_key(source_hash, cell.index);
+        let cell_label = format!("{label} (cell {})", cell.index);
+        let excerpt = select_excerpt(notebook_path, text, &Selector::Cells(cell.index, cell.index), DEFAULT_CONTEXT_CELLS)?;
+        let input = format!("{}\n\n{}", cell_banner(&name, &cell, total), excerpt.text);
+        let (prompt, pii) = prepare_prompt(setup, notebook_path, &input)?;
+        write_pii_audit(run, &key, &pii)?;
+        let Some(generated) = generate(run, &prompt, &key, &cell_label, &setup.template.hash)? else {
+            return Ok(None);
+        };
+        usage = sum_usage(usage, &generated.usage);
+        cached &= generated.cached;
+        model = generated.model;
+        sections.push((cell, generated.text));
+    }
+    Ok(Some(CellSections { sections, model, cached, usage }))
+}
+
+fn sum_usage(total: Option<Usage>, next: &Option<Usage>) -> Option<Usage> {
+
+    /// Adds the token usage of one request to that of the requests before it.
+
+    let Some(next) = next else {
+        return total;
+    };
+    let add = |a: Option<u64>, b: Option<u64>| if a.is_none() && b.is_none() { None } else { Some(a.unwrap_or(0) + b.unwrap_or(0)) };
+    let sum = total.unwrap_or_default();
+    Some(Usage { input_tokens: add(sum.input_tokens, next.input_tokens), output_tokens: add(sum.output_tokens, next.output_tokens) })
+}
+
+fn request_summary(run: &RunContext, notebook_path: &Path, snapshot: &SourceSnapshot, text: &str) -> Result<Option<Generated>, DoxcerError> {
+
+    /// Generates the one-paragraph summary of a notebook (`--mode summary`).
+    ///
+    /// # Description
+    /// The prompt is built like in [`request_documentation`], from the summary
+    /// setup. The answer must be a single paragraph of at most
+    /// `summary_max_chars` characters (see [`check_summary`]); otherwise the
+    /// request is sent once more with an instruction to write it shorter.
+    /// Workspace artifacts are kept under [`summary_key`], apart from those of
+    /// the full document.
+    ///
+    /// # Returns
+    /// * `Ok(Some(Generated))` – the summary and the model that produced it.
+    /// * `Ok(None)` – the API answered without any output.
+    /// * `Err(DoxcerError)` – as for [`request_documentation`], or a `Limit` error
+    ///   when the second answer does not pass the check either.
+
+    let setup = &run.setup;
+    let render = run.tracer.span("prompt-render");
+    let key = summary_key(&snapshot.hash);
+    let prompt = fitted_prompt(run, notebook_path, text, &key);
+    if let Err(e) = &prompt {
+        render.fail(e);
+    }
+    drop(render);
+    let (prompt, coverage) = prompt?;
+
+    let label = notebook_path.display().to_string();
+    let max_chars = setup.settings.summary_max_chars.value;
+    let Some(generated) = generate(run, &prompt, &key, &label, &setup.template.hash)? else {
+        return Ok(None);
+    };
+    let Err(problem) = check_summary(&generated.text, max_chars) else {
+        return Ok(Some(Generated { coverage, ..generated }));
+    };
+
+    warn(format!("{label}: {problem}; asking once more for a shorter summary"));
+    let shorter = Prompt {
+        instructions: prompt.instructions.clone(),
+        input: format!("{}\n\n{}", prompt.input, shorter_instruction(max_chars)),
+    };
+    let Some(generated) = generate(run, &shorter, &key, &label, &setup.template.hash)? else {
+        return Ok(None);
+    };
+    if let Err(problem) = check_summary(&generated.text, max_chars) {
+        return Err(DoxcerError::Limit(format!("{label}: {problem}, also after asking for a shorter one")));
+    }
+    Ok(Some(Generated { coverage, ..generated }))
+}
+
+fn route_model(run: &mut RunContext, base: &Setting<String>, label: &str, notebook: &str, verbose: bool) {
+
+    /// Picks the model for one notebook.
+    ///
+    /// # Description
+    /// `--model` always wins. Otherwise a `# doxcer: model=<name>` directive in
+    /// the notebook pins the model, and failing that the first `model_routing`
+    /// band its estimated size falls in decides. Without either the resolved
+    /// `model` setting (`base`) is used as is. With `--verbose` the decision is
+    /// printed to stderr.
+
+    let pinned = pinned_model(&parse_directives(notebook)).map(str::to_string);
+    run.setup.settings.model = base.clone();
+    let routing = &run.setup.settings.model_routing;
+    if base.source == Source::Cli {
+        if verbose && !routing.value.bands.is_empty() {
+            errln!("routing {label}: {} (pinned by --model)", base.value);
+        }
+        return;
+    }
+    if let Some(model) = pinned {
+        if verbose {
+            errln!("routing {label}: {model} (pinned by its model directive)");
+        }
+        run.setup.settings.model = Setting { value: model, source: Source::Directive };
+        return;
+    }
+    if routing.value.bands.is_empty() {
+        return;
+    }
+    let tokens = count_tokens(notebook, &base.value);
+    match route_by_tokens(&routing.value, tokens) {
+        Some((index, band)) => {
+            if verbose {
+                errln!("routing {label}: ~{tokens} tokens, band {} -> {}", describe_band(&routing.value, index), band.model);
+            }
+            run.setup.settings.model = Setting { value: band.model.clone(), source: routing.source };
+        }
+        None if verbose => errln!("routing {label}: ~{tokens} tokens, no band matches -> {}", base.value),
+        None => {}
+    }
+}
+
+fn fitted_prompt(run: &RunContext, notebook_path: &Path, text: &str, key: &str) -> Result<(Prompt, Option<String>), DoxcerError> {
+
+    /// Prepares the prompt and checks that it fits `--max-total-tokens`.
+    ///
+    /// # Description
+    /// The count covers the rendered prompt, after the pipeline has trimmed
+    /// the notebook, uses the model's tokenizer where there is one (see
+    /// [`count_tokens`]) and is printed with `--verbose`. The default limit is the
+    /// model's context window minus `max_output_tokens` (see
+    /// [`default_max_total_tokens`]). What happens over the limit follows
+    /// `oversize_policy`: `fail` refuses the prompt with a `TooLarge` error that
+    /// lists the largest cells, `warn` sends it whole after a warning, and
+    /// `truncate` (`--force-oversize`) sends only the first part of the notebook
+    /// that fits, under a banner telling the model the coverage is partial.
+    /// `chunk` fails like `fail` here; the caller documents the notebook in
+    /// chunks instead (see [`chunked_documentation`]).
+    /// With `--pii-audit`, the PII placeholders and the values they stand for
+    /// are written to the run workspace under `key`, never to the document.
+    /// In `--edit-mode` the previous document and the notebook diff are appended
+    /// first, so they count towards the limit.
+    ///
+    /// # Returns
+    /// * `Ok((Prompt, None))` – the whole notebook fits.
+    /// * `Ok((Prompt, Some(note)))` – only part is sent; `note` is the
+    ///   `doxcer_coverage` front-matter value.
+    /// * `Err(DoxcerError)` – the prompt could not be prepared or is too large.
+
+    let setup = &run.setup;
+    let (mut prompt, pii) = prepare_prompt(setup, notebook_path, text)?;
+    write_pii_audit(run, key, &pii)?;
+    if let Some(instruction) = &setup.edit_instruction {
+        prompt.input = format!("{}\n\n{instruction}", prompt.input);
+    }
+    let settings = &setup.settings;
+    let limit = settings
+        .max_total_tokens
+        .value
+        .unwrap_or_else(|| default_max_total_tokens(&settings.model.value, settings.max_output_tokens.value));
+    let model = settings.model.value.as_str();
+    let total = prompt_tokens(&prompt, model);
+    if run.verbose {
+        errln!("tokens {}: ~{total} prompt tokens, limit {limit} ({})", notebook_path.display(), settings.model.value);
+    }
+    if total <= limit {
+        return Ok((prompt, None));
+    }
+    match settings.oversize_policy.value {
+        OversizePolicy::Fail | OversizePolicy::Chunk => {
+            return Err(DoxcerError::TooLarge(size_report(notebook_path, text, model, total, limit).to_string()));
+        }
+        OversizePolicy::Warn => {
+            warn(format!(
+                "{}: about {total} tokens, over the limit of {limit}; sending it anyway (oversize_policy = warn)",
+                notebook_path.display()
+            ));
+            return Ok((prompt, None));
+        }
+        OversizePolicy::Truncate => {}
+    }
+
+    let notebook_tokens = count_tokens(text, model);
+    let name = notebook_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let overhead = count_tokens(&setup.template.body, model) + count_tokens(&setup.context, model) + count_tokens(&partial_banner(&name, notebook_tokens, notebook_tokens), model);
+    let part = truncate_to_tokens(text, limit.saturating_sub(overhead), model);
+    let covered = count_tokens(part, model);
+    warn(format!(
+        "{}: about {total} tokens, over the limit of {limit}; documenting only the first ~{covered} of ~{notebook_tokens} notebook tokens (--force-oversize)",
+        notebook_path.display()
+    ));
+    let partial = format!("{}\n\n{part}", partial_banner(&name, covered, notebook_tokens));
+    let (prompt, _) = prepare_prompt(setup, notebook_path, &partial)?;
+    Ok((prompt, Some(coverage_note(covered, notebook_tokens))))
+}
+
+fn prompt_tokens(prompt: &Prompt, model: &str) -> usize {
+
+    /// Counts the tokens of a prepared prompt, its instructions included,
+    /// with the tokenizer of `model` (see [`count_tokens`]).
+
+    let instructions = prompt.instructions.as_deref().map_or(0, |instructions| count_tokens(instructions, model));
+    instructions + count_tokens(&prompt.input, model)
+}
+
+fn write_pii_audit(run: &RunContext, key: &str, pii: &PiiReport) -> Result<(), DoxcerError> {
+
+    /// Writes the placeholder mapping of a notebook to the run workspace
+    /// (`--pii-audit`); nothing is written without placeholders.
+
+    if !run.pii_audit || pii.placeholders.is_empty() {
+        return Ok(());
+    }
+    let json = serde_json::to_string_pretty(&pii.placeholders).map_err(|e| DoxcerError::Input(e.to_string()))?;
+    run.workspace.write_artifact(key, PII_AUDIT_ARTIFACT, &json)?;
+    Ok(())
+}
+
+fn checked_links(run: &RunContext, generated: Generated, base_dir: &Path, label: &str) -> Result<Generated, DoxcerError> {
+
+    /// Validates the links and images of a generated document (`--link-policy`).
+    ///
+    /// # Description
+    /// Relative targets are checked against the files of the working directory
+    /// (listed once per run, honouring `.gitignore`), as seen from `base_dir`:
+    /// the folder of the document, or of the notebook when printing to stdout.
+    /// Near-misses are rewritten; missing files and external hosts outside
+    /// `link_allowlist` are warned about, stripped or fail the document, see
+    /// [`normalize_links`].
+
+    let files = run.workspace_files.get_or_init(|| workspace_files(Path::new(".")));
+    let settings = &run.setup.settings;
+    let policy = settings.link_policy.value;
+    let report = normalize_links(&generated.text, &workspace_dir(base_dir), files, &settings.link_allowlist.value, policy)
+        .map_err(|e| DoxcerError::Provider(format!("{label}: {e} (--link-policy fail)")))?;
+    for rewrite in &report.rewritten {
+        errln!("{label}: rewrote {rewrite}");
+    }
+    for problem in &report.problems {
+        let stripped = if policy == LinkPolicy::Strip { " (stripped)" } else { "" };
+        warn(format!("{label}: {problem}{stripped}"));
+    }
+    Ok(Generated { text: report.markdown, ..generated })
+}
+
+fn workspace_dir(dir: &Path) -> String {
+
+    /// Returns a folder relative to the working directory, `/`-separated; empty
+    /// for the working directory itself or a folder outside it.
+
+    let (Ok(dir), Ok(root)) = (fs::canonicalize(dir), env::current_dir().and_then(fs::canonicalize)) else {
+        return String::new();
+    };
+    let Ok(relative) = dir.strip_prefix(&root) else {
+        return String::new();
+    };
+    relative.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect::<Vec<_>>().join("/")
+}
+
+fn summary_key(source_hash: &str) -> String {
+
+    /// Returns the key the summary of a notebook is stored under in the run workspace.
+
+    format!("{source_hash}-summary")
+}
+
+fn chunk_key(source_hash: &str, index: usize) -> String {
+
+    /// Returns the key the documentation of one chunk is stored under in the
+    /// run workspace (see [`chunked_documentation`]).
+
+    format!("{source_hash}-chunk-{index}")
+}
+
+fn cell_key(source_hash: &str, index: usize) -> String {
+
+    /// Returns the key the documentation of one cell is stored under in the
+    /// run workspace (see [`cell_documentation`]).
+
+    format!("{source_hash}-cell-{index}")
+}
+
+fn section_key(source_hash: &str, title: &str) -> String {
+
+    /// Returns the key a rewritten section is stored under in the run workspace.
+
+    format!("{source_hash}-section-{}", slug(title))
+}
+
+fn generate(run: &RunContext, prompt: &Prompt, source_hash: &str, label: &str, template_hash: &str) -> Result<Option<Generated>, DoxcerError> {
+
+    /// Runs [`send_with_fallbacks`] inside the `api-call` trace span.
+
+    let span = run.tracer.span("api-call");
+    span.set("gen_ai.request.model", &run.setup.settings.model.value);
+    let generated = send_with_fallbacks(run, prompt, source_hash, label, template_hash);
+    match &generated {
+        Ok(Some(Generated { model, cached, .. })) => {
+            span.set("gen_ai.response.model", model);
+            span.set("doxcer.cache_hit", *cached);
+        }
+        Ok(None) => span.fail("No output received from API."),
+        Err(e) => span.fail(e),
+    }
+    generated
+}
+
+fn send_with_fallbacks(run: &RunContext, prompt: &Prompt, source_hash: &str, label: &str, template_hash: &str) -> Result<Option<Generated>, DoxcerError> {
+
+    /// Sends a rendered prompt to the OpenAI API and returns the answer.
+    ///
+    /// # Description
+    /// Rate limits and server errors are first retried on the same model, see
+    /// [`send_with_retries`]. When the model still fails with a capacity or
+    /// quota error (429, 503, `insufficient_quota`, ...), the same request is
+    /// sent to the next model in `fallback_models`. Authentication and
+    /// validation errors are not retried.
+    ///
+    /// The request and the answer are stored in the run workspace, keyed by
+    /// `source_hash`. With `--resume`, an answer from an earlier run for the same
+    /// request is reused instead of calling the API again. Answers are also kept in
+    /// the response cache (`.doxcer/cache/responses`), keyed by the request, so an
+    /// identical request is answered from disk.
+    ///
+    /// Each request carries an `Idempotency-Key` derived from the run that first
+    /// attempted the notebook, so gateways that support it do not bill a request
+    /// twice when it is sent again (also across `--resume`).
+    ///
+    /// # Parameters
+    /// * `source_hash` – Hash of the input, used for workspace artifacts and the cache entry.
+    /// * `label` – What is being documented (the notebook path), stored in the cache entry.
+    /// * `template_hash` – Hash of the template the prompt was rendered from.
+
+    let models = model_chain(&run.setup.settings);
+    let attempt_group = run.workspace.attempt_group(source_hash, run.resume)?;
+    for (i, model) in models.iter().enumerate() {
+        run.tracer.set_current("doxcer.retry_count", i);
+        let request = build_request(&run.setup, prompt, model);
+        let request_json = serde_json::to_string_pretty(&request)
+            .map_err(|e| DoxcerError::Input(format!("Failed to serialize request: {e}")))?;
+        let key = cache_key(&request_json);
+
+        let resumed = if run.resume { resumed_response(run, source_hash, &request_json) } else { None };
+        run.workspace.write_artifact(source_hash, REQUEST_ARTIFACT, &request_json)?;
+
+        let cached = resumed.is_none().then(|| run.cache.get(&key)).flatten();
+        let from_cache = cached.is_some();
+        let (text, usage) = match (resumed, cached) {
+            (Some(text), _) => (Some(text), None),
+            (None, Some(entry)) => (Some(entry.text), None),
+            (None, None) => {
+                let estimate = reserve_budget(run, prompt, model)?;
+                let sent = send_with_retries(run, source_hash, &request, &idempotency_key(&attempt_group, source_hash, model));
+                settle_budget(run, model, estimate, &sent);
+                match sent {
+                    Ok(answer) => answer,
+                    Err(e) if e.capacity && i + 1 < models.len() => {
+                        warn(format!("{model} is unavailable ({}); falling back to {}", e.error, models[i + 1]));
+                        continue;
+                    }
+                    Err(e) => return Err(e.error),
+                }
+            }
+        };
+
+        let Some(text) = text else {
+            return Ok(None);
+        };
+        run.workspace.write_artifact(source_hash, RESPONSE_ARTIFACT, &text)?;
+        if !from_cache {
+            let entry = CacheEntry {
+                key,
+                model: model.to_string(),
+                template_hash: template_hash.to_string(),
+                source_hash: source_hash.to_string(),
+                notebook: label.to_string(),
+                created_at: now_rfc3339(),
+                text: text.clone(),
+                tags: run.tags.clone(),
+            };
+            if let Err(e) = run.cache.put(&entry) {
+                warn(e);
+            }
+        }
+        return Ok(Some(Generated { text, model: model.to_string(), cached: from_cache, coverage: None, usage }));
+    }
+    unreachable!("the model chain always contains the primary model")
+}
+
+fn reserve_budget(run: &RunContext, prompt: &Prompt, model: &str) -> Result<f64, DoxcerError> {
+
+    /// Reserves the estimated cost of a request against the `--max-cost`
+    /// budget and returns the estimate (0 without a budget).
+    ///
+    /// # Description
+    /// The input is estimated from the prompt and the output from the answers
+    /// of this run so far, see [`Budget::estimate`]. Once a request would
+    /// exceed the cap, it and every later request of the run fail with
+    /// `OverBudget` without being sent; answers from the response cache and
+    /// `--resume` cost nothing and still come through.
+
+    let Some(budget) = &run.budget else {
+        return Ok(0.0);
+    };
+    let settings = &run.setup.settings;
+    let input = prompt_tokens(prompt, model);
+    let estimate = budget
+        .estimate(model, input as u64, settings.max_output_tokens.value, &settings.prices.value)
+        .ok_or_else(|| DoxcerError::Config(format!("--max-cost needs a price for {model}; add it under [prices] in doxcer.toml")))?;
+    let reached = budget.exhausted();
+    if !budget.reserve(estimate) {
+        let locale = Locale::from_lang(settings.lang.value.as_deref());
+        let eur = Currency { code: "EUR".to_string(), fx_rate: None };
+        let message = match reached {
+            true => format!("skipped: the budget of {} was reached earlier in this run", format_money(budget.cap, &eur, locale)),
+            false => format!(
+                "skipped: the budget of {} would be exceeded ({} spent, this request ~{})",
+                format_money(budget.cap, &eur, locale),
+                format_money(budget.spent(), &eur, locale),
+                format_money(estimate, &eur, locale)
+            ),
+        };
+        return Err(DoxcerError::OverBudget(message));
+    }
+    Ok(estimate)
+}
+
+fn settle_budget(run: &RunContext, model: &str, estimate: f64, sent: &Result<(Option<String>, Option<Usage>), SendError>) {
+
+    /// Books a sent request on the `--max-cost` budget: the reported usage at
+    /// the model's price, the estimate when no usage was reported, nothing
+    /// when the request failed.
+
+    let Some(budget) = &run.budget else {
+        return;
+    };
+    let (cost, output_tokens) = match sent {
+        Ok((_, Some(Usage { input_tokens, output_tokens }))) if input_tokens.is_some() || output_tokens.is_some() => {
+            let prices = &run.setup.settings.prices.value;
+            let cost = budget.cost(model, input_tokens.unwrap_or(0), output_tokens.unwrap_or(0), prices);
+            (cost.unwrap_or(estimate), *output_tokens)
+        }
+        Ok(_) => (estimate, None),
+        Err(_) => (0.0, None),
+    };
+    budget.settle(estimate, cost, output_tokens);
+}
+
+fn model_chain(settings: &Settings) -> Vec<&String> {
+
+    /// Returns the primary model followed by the fallback models.
+
+    std::iter::once(&settings.model.value)
+        .chain(&settings.fallback_models.value)
+        .collect()
+}
+
+fn build_request(setup: &GenerationSetup, prompt: &Prompt, model: &str) -> ChatRequest {
+
+    /// Builds the API request for a rendered prompt and a model.
+    ///
+    /// # Description
+    /// The request profiles for the provider and model add their default
+    /// parameters and drop the ones the model rejects, see [`apply_profiles`].
+
+    let request = ChatRequest {
+        max_output_tokens: setup.settings.max_output_tokens.value,
+        temperature: setup.settings.temperature.value,
+        top_p: setup.settings.top_p.value,
+        reasoning: setup.settings.reasoning_effort.value.map(|effort| Reasoning { effort }),
+        ..ChatRequest::new(model, prompt)
+    };
+    let profiles = matching_profiles(&setup.profiles, setup.provider.name(), model);
+    if profiles.is_empty() {
+        return request;
+    }
+    let Ok(Value::Object(mut body)) = serde_json::to_value(&request) else {
+        return request;
+    };
+    apply_profiles(&mut body, &profiles);
+    serde_json::from_value(Value::Object(body)).unwrap_or(request)
+}
+
+fn describe_profiles(setup: &GenerationSetup) -> Vec<String> {
+
+    /// Lists, per model in the fallback chain, the request profiles that apply
+    /// and what they contribute.
+
+    model_chain(&setup.settings)
+        .into_iter()
+        .map(|model| {
+            let profiles = matching_profiles(&setup.profiles, setup.provider.name(), model);
+            let described: Vec<String> = profiles.iter().map(|p| p.describe()).collect();
+            match described.as_slice() {
+                [] => format!("{model}: no profile"),
+                _ => format!("{model}: {}", described.join(" + ")),
+            }
+        })
+        .collect()
+}
+
+fn is_capacity_error(status: u16, body: &str) -> bool {
+
+    /// Decides whether an API error means "try another model".
+    ///
+    /// # Description
+    /// Rate limits, exhausted quota and overloaded servers are capacity errors.
+    /// Authentication (401/403) and validation (400/404/422) errors are not: a
+    /// different model would fail the same way.
+
+    if matches!(status, 401 | 403) {
+        return false;
+    }
+    matches!(status, 429 | 503 | 529)
+        || ["insufficient_quota", "rate_limit_exceeded", "server_is_overloaded"]
+            .iter()
+            .any(|code| body.contains(code))
+}
+
+fn send_with_retries(run: &RunContext, source_hash: &str, request: &ChatRequest, idempotency_key: &str) -> Result<(Option<String>, Option<Usage>), SendError> {
+
+    /// Sends a request, retrying it on the same model after a 429 or 5xx.
+    ///
+    /// # Description
+    /// A request is sent at most `retry_max_attempts` times (`--retry-max-attempts`,
+    /// `DOXCER_RETRY_MAX_ATTEMPTS`, default 4). Between attempts it waits for the
+    /// `Retry-After` the API asked for, or else an exponential backoff with
+    /// jitter starting at `retry_base_delay_ms`, see [`retry_delay`]. A
+    /// `Retry-After` over 60 seconds ends the retries with an error saying so.
+    /// Retries keep the idempotency key, so a retried request is not billed
+    /// twice.
+
+    let settings = &run.setup.settings;
+    let max_attempts = settings.retry_max_attempts.value.max(1);
+    let base = Duration::from_millis(settings.retry_base_delay_ms.value);
+    let mut attempt = 1;
+    loop {
+        match send_request(run, source_hash, request, idempotency_key) {
+            Err(e) if e.retryable && attempt < max_attempts => {
+                let delay = match retry_delay(attempt, base, e.retry_after) {
+                    Ok(delay) => delay,
+                    Err(too_long) => {
+                        return Err(SendError { error: DoxcerError::Provider(format!("{}; {too_long}", e.error)), retryable: false, ..e });
+                    }
+                };
+                warn(format!(
+                    "{} failed ({}); retrying in {:.1}s (attempt {} of {max_attempts})",
+                    request.model, e.error, delay.as_secs_f64(), attempt + 1
+                ));
+                if !run.cancel.sleep(delay) {
+                    return Err(SendError { error: cancelled(run, None), capacity: false, retryable: false, retry_after: None });
+                }
+                attempt += 1;
+                run.tracer.set_current("http.request.resend_count", attempt as usize - 1);
+            }
+            result => return result,
+        }
+    }
+}
+
+fn send_request(run: &RunContext, source_hash: &str, request: &ChatRequest, idempotency_key: &str) -> Result<(Option<String>, Option<Usage>), SendError> {
+
+    /// Posts a request to the configured provider (see [`select_provider`]) and
+    /// returns the text of the answer with the token usage the API reported.
+    ///
+    /// # Description
+    /// The request is serialized once and those exact bytes are both checked
+    /// against the provider's request size limit and sent, so an oversized body
+    /// fails before the upload instead of with a 413 afterwards.
+    ///
+    /// The response body is read with a cap of `max_output_bytes` instead of being
+    /// buffered without limit. When the cap is hit the file fails, and the
+    /// truncated prefix is saved in the run workspace for inspection.
+    ///
+    /// When the run streams and the provider supports it, the request asks for
+    /// server-sent events and a successful answer is read with [`read_stream`].
+    ///
+    /// Both the request and the body are given up on as soon as the run's
+    /// cancel token is cancelled (Ctrl-C) or runs out (`--file-timeout`).
+
+    let fatal = |error: DoxcerError| SendError { error, capacity: false, retryable: false, retry_after: None };
+
+    let provider = &run.setup.provider;
+    let stream = run.stream && provider.supports_streaming();
+    let body = provider.request_body(request, stream).map_err(fatal)?;
+
+    let post = provider
+        .post(&run.client, &run.api_key, &request.model)
+        .header(IDEMPOTENCY_HEADER, idempotency_key)
+        .body(body);
+    let res = cancel::send(&run.cancel, post).map_err(|e| match e {
+        DoxcerError::Cancelled { partial } => fatal(cancelled(run, partial)),
+        e => fatal(e),
+    })?;
+
+    let status = res.status();
+    run.tracer.set_current("http.response.status_code", status.as_u16());
+    let retry_after = res.headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| parse_retry_after(value, Utc::now()));
+    let limit = run.setup.settings.max_output_bytes.value;
+    let res = CancellableReader::new(&run.cancel, res);
+    if stream && status.is_success() {
+        let generation = read_stream(run, source_hash, res, limit).map_err(fatal)?;
+        record_usage(run, &generation);
+        return Ok((generation.text, generation.usage));
+    }
+    let read = read_capped(res, limit).map_err(|e| match run.cancel.is_cancelled() {
+        true => fatal(cancelled(run, None)),
+        false => fatal(e),
+    })?;
+    let body = match read {
+        Ok(body) => body,
+        Err(prefix) => {
+            let saved = run.workspace
+                .write_artifact(source_hash, TRUNCATED_ARTIFACT, &String::from_utf8_lossy(&prefix))
+                .map_err(fatal)?;
+            return Err(fatal(DoxcerError::Limit(format!(
+                "output exceeded {limit} bytes (--max-output-bytes); truncated response saved to {}",
+                saved.display()
+            ))));
+        }
+    };
+
+    if !status.is_success() {
+        let body = String::from_utf8_lossy(&body);
+        return Err(SendError {
+            capacity: is_capacity_error(status.as_u16(), &body),
+            retryable: is_retryable_status(status.as_u16(), &body),
+            retry_after,
+            error: DoxcerError::Provider(format!("API request failed: {body}")),
+        });
+    }
+
+    let generation = provider.parse_generation(&body);
+    record_usage(run, &generation);
+    Ok((generation.text, generation.usage))
+}
+
+fn cancelled(run: &RunContext, partial: Option<String>) -> DoxcerError {
+
+    /// The error for a request the run's cancel token gave up on: a provider
+    /// error once `--file-timeout` passed, else `Cancelled` (Ctrl-C).
+
+    match (run.cancel.timed_out(), run.setup.settings.file_timeout.value) {
+        (true, Some(secs)) => DoxcerError::Provider(format!("timed out after {secs}s (--file-timeout)")),
+        _ => DoxcerError::Cancelled { partial },
+    }
+}
+
+fn record_usage(run: &RunContext, generation: &Generation) {
+
+    /// Adds the token usage of an answer to the current span.
+
+    if let Some(usage) = &generation.usage {
+        if let Some(tokens) = usage.input_tokens {
+            run.tracer.set_current("gen_ai.usage.input_tokens", tokens);
+        }
+        if let Some(tokens) = usage.output_tokens {
+            run.tracer.set_current("gen_ai.usage.output_tokens", tokens);
+        }
+    }
+}
+
+fn read_stream(run: &RunContext, source_hash: &str, reader: impl Read, limit: u64) -> Result<Generation, DoxcerError> {
+
+    /// Reads a streamed answer and shows its text on stderr as it arrives.
+    ///
+    /// # Description
+    /// The body is a series of server-sent events, each a block of `data:`
+    /// lines ended by an empty line; every block is handed to the provider's
+    /// [`LlmProvider::stream_event`]. The preview is dimmed so it is not taken
+    /// for the finished document, which is post-processed and printed later.
+    ///
+    /// The `max_output_bytes` cap counts the whole event stream, as for a
+    /// non-streamed body. The text received so far is saved in the run
+    /// workspace when the cap is hit, the stream ends without a final event or
+    /// the run is cancelled; a cancelled error also carries it.
+
+    let style = Style::stderr();
+    let mut stderr = io::stderr();
+    let mut text = String::new();
+    let mut data = String::new();
+    let mut read = 0u64;
+    let mut lines = BufReader::new(reader);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let n = match lines.read_line(&mut line) {
+            Ok(n) => n,
+            Err(_) if run.cancel.is_cancelled() => {
+                let _ = writeln!(stderr);
+                if !text.is_empty() {
+                    run.workspace.write_artifact(source_hash, TRUNCATED_ARTIFACT, &text)?;
+                }
+                return Err(cancelled(run, Some(text).filter(|text| !text.is_empty())));
+            }
+            Err(e) => return Err(DoxcerError::Provider(format!("Failed to read response: {e}"))),
+        };
+        read += n as u64;
+        if read > limit {
+            let saved = run.workspace.write_artifact(source_hash, TRUNCATED_ARTIFACT, &text)?;
+            let _ = writeln!(stderr);
+            return Err(DoxcerError::Limit(format!(
+                "output exceeded {limit} bytes (--max-output-bytes); truncated response saved to {}",
+                saved.display()
+            )));
+        }
+        let trimmed = line.trim_end_matches(['\r', '\n']);
+        if let Some(payload) = trimmed.strip_prefix("data:") {
+            if !data.is_empty() {
+                data.push('\n');
+            }
+            data.push_str(payload.strip_prefix(' ').unwrap_or(payload));
+            continue;
+        }
+        if !trimmed.is_empty() && n > 0 {
+            // `event:`, `id:` and comment lines carry nothing the payload lacks.
+            continue;
+        }
+        if !data.is_empty() {
+            match run.setup.provider.stream_event(&data) {
+                StreamEvent::Delta(delta) => {
+                    let _ = write!(stderr, "{}", style.paint(&delta, Color::Dim));
+                    let _ = stderr.flush();
+                    text.push_str(&delta);
+                }
+                StreamEvent::Completed(mut generation) => {
+                    let _ = writeln!(stderr);
+                    if generation.text.is_none() && !text.is_empty() {
+                        generation.text = Some(text);
+                    }
+                    return Ok(generation);
+                }
+                StreamEvent::Failed(message) => {
+                    let _ = writeln!(stderr);
+                    return Err(DoxcerError::Provider(format!("API request failed: {message}")));
+                }
+                StreamEvent::Ignored => {}
+            }
+            data.clear();
+        }
+        if n == 0 {
+            break;
+        }
+    }
+    let _ = writeln!(stderr);
+    let saved = run.workspace.write_artifact(source_hash, TRUNCATED_ARTIFACT, &text)?;
+    Err(DoxcerError::Provider(format!(
+        "the answer stream ended before the response was complete; partial response saved to {}",
+        saved.display()
+    )))
+}
+
+fn download_source(client: &Client, url: &str, name: Option<&str>) -> Result<SourceSnapshot, DoxcerError> {
+
+    /// Downloads a notebook given as an `https://` URL.
+    ///
+    /// # Description
+    /// Uses the shared HTTP client, so proxy settings and the redirect limit
+    /// apply. `DOXCER_SOURCE_AUTH_HEADER` (or `_ENC`) adds a header for
+    /// authenticated repositories: `Name: value`, or only a value for
+    /// `Authorization`. Downloads over 16 MB are refused.
+    ///
+    /// The notebook is named after `--name`, or else the last segment of the
+    /// final URL (or of its `path=` query for Azure DevOps item links). The hash
+    /// is that of the downloaded bytes, so caching works as for local files.
+
+    let mut request = client.get(url);
+    if let Some((header, value)) = auth_header(SOURCE_AUTH_HEADER)? {
+        request = request.header(header, value);
+    }
+    let res = request.send().map_err(|e| DoxcerError::Input(format!("Failed to download {url}: {e}")))?;
+    let status = res.status();
+    let final_url = res.url().clone();
+    if status != reqwest::StatusCode::OK {
+        let via = if final_url.as_str() != url { format!(" (redirected to {final_url})") } else { String::new() };
+        return Err(DoxcerError::Input(format!("Failed to download {url}{via}: HTTP {status}")));
+    }
+    let bytes = read_capped(res, MAX_SOURCE_BYTES)?
+        .map_err(|_| DoxcerError::Input(format!("{url} is larger than the download limit of {}", format_size(MAX_SOURCE_BYTES as usize))))?;
+    let name = name.map(str::to_string).unwrap_or_else(|| source_name(&final_url));
+    SourceSnapshot::from_bytes(Path::new(&name), bytes, Some(url))
+}
+
+fn auth_header(name: &str) -> Result<Option<(String, String)>, DoxcerError> {
+
+    /// Reads an auth header variable such as `DOXCER_SOURCE_AUTH_HEADER`, in
+    /// plain text or as `<name>_ENC`: `Name: value`, or only a value for
+    /// `Authorization`.
+
+    if env::var(name).is_err() && env::var(format!("{name}_ENC")).is_err() {
+        return Ok(None);
+    }
+    let raw = env_secret(name, None)?;
+    Ok(Some(match raw.split_once(':') {
+        Some((header, value)) if !header.is_empty() && !header.contains(char::is_whitespace) => {
+            (header.to_string(), value.trim().to_string())
+        }
+        _ => ("Authorization".to_string(), raw.trim().to_string()),
+    }))
+}
+
+struct FabricHttp<'a> {
+    client: &'a Client,
+    auth: Option<(String, String)>,
+}
+
+impl FabricApi for FabricHttp<'_> {
+    fn get(&self, url: &str) -> Result<String, DoxcerError> {
+        let mut request = self.client.get(url);
+        if let Some((header, value)) = &self.auth {
+            request = request.header(header, value);
+        }
+        let res = request.send().map_err(|e| DoxcerError::Provider(format!("Fabric API request failed: {e}")))?;
+        let status = res.status();
+        if !status.is_success() {
+            return Err(DoxcerError::Provider(format!("Fabric API returned HTTP {status} for {url}")));
+        }
+        res.text().map_err(|e| DoxcerError::Provider(format!("Failed to read the Fabric API response: {e}")))
+    }
+}
+
+fn check_fabric_workspace(options: &RunOptions, setup: &GenerationSetup) -> Result<(), DoxcerError> {
+
+    /// Fails with a config error when `--fabric-runs` has no workspace to ask.
+
+    if options.fabric_runs && setup.settings.fabric_workspace.value.is_none() {
+        return Err(DoxcerError::Config("--fabric-runs needs fabric_workspace in doxcer.toml or DOXCER_FABRIC_WORKSPACE".to_string()));
+    }
+    Ok(())
+}
+
+fn fabric_run_history(run: &RunContext, notebook: &Path, source: &str) -> RunHistory {
+
+    /// Fetches the recent Fabric runs of a notebook (`--fabric-runs`).
+    ///
+    /// # Description
+    /// The notebook item is taken from a `# doxcer: fabric_item=<id>` directive,
+    /// or looked up by display name among the notebooks of `fabric_workspace`
+    /// (listed once per run). Requests go through the shared client with
+    /// `DOXCER_FABRIC_AUTH_HEADER`, falling back to the header used for
+    /// downloads. Any failure is a warning and gives `RunHistory::Unavailable`,
+    /// so the document is still generated.
+
+    let history = || -> Result<RunHistory, DoxcerError> {
+        let workspace = run.setup.settings.fabric_workspace.value.as_deref().ok_or_else(|| DoxcerError::Config("no fabric_workspace is configured".to_string()))?;
+        let auth = match auth_header(FABRIC_AUTH_HEADER)? {
+            Some(auth) => Some(auth),
+            None => auth_header(SOURCE_AUTH_HEADER)?,
+        };
+        let api = FabricHttp { client: &run.client, auth };
+        let item = match parse_directives(source).get("fabric_item") {
+            Some(item) => item.clone(),
+            None => {
+                let name = display_name(notebook);
+                let items = run.fabric_items.get_or_init(|| list_notebooks(&api, workspace)).clone()?;
+                items
+                    .into_iter()
+                    .find(|i| i.display_name == name)
+                    .map(|i| i.id)
+                    .ok_or_else(|| DoxcerError::Config(format!("no notebook named {name:?} in Fabric workspace {workspace}")))?
+            }
+        };
+        Ok(RunHistory::Stats(run_stats(&fetch_run_history(&api, workspace, &item)?)))
+    };
+    history().unwrap_or_else(|e| {
+        warn(format!("{}: run history unavailable: {e}", notebook.display()));
+        RunHistory::Unavailable
+    })
+}
+
+fn source_name(url: &reqwest::Url) -> String {
+
+    /// Derives a notebook name from a download URL.
+
+    let query_path = url.query_pairs().find(|(k, _)| k == "path").map(|(_, v)| v.to_string());
+    let path = query_path.unwrap_or_else(|| url.path().to_string());
+    path.rsplit('/')
+        .find(|segment| !segment.is_empty())
+        .map(|segment| segment.replace("%20", " "))
+        .unwrap_or_else(|| "notebook.py".to_string())
+}
+
+fn describe_run_profile(chain: &[String]) -> Option<String> {
+
+    /// Renders the selected run profile and what it inherits, e.g.
+    /// `nightly (inherits base)`; `None` without a run profile.
+
+    let (selected, inherited) = chain.split_last()?;
+    if inherited.is_empty() {
+        return Some(selected.clone());
+    }
+    let parents: Vec<&str> = inherited.iter().rev().map(String::as_str).collect();
+    Some(format!("{selected} (inherits {})", parents.join(" -> ")))
+}
+
+fn print_dry_run(setup: &GenerationSetup, notebook_path: &Path, notebook: &str) -> Result<(), DoxcerError> {
+
+    /// Prints the resolved settings and prompt size for `--dry-run`.
+    ///
+    /// # Description
+    /// Shows every setting together with the layer it came from (`default`,
+    /// `template`, `config`, `profile`, `env` or `cli`), so it is clear which
+    /// values the template front-matter contributed, followed by the rendered
+    /// `{{context}}`. No API call is made.
+
+    outln!("Template: {}", setup.template.path.display());
+    for include in &setup.template.includes {
+        outln!("  includes {include}");
+    }
+    outln!("  hash = {}", setup.template.hash);
+    if let Some(v) = &setup.template.min_doxcer_version {
+        outln!("  min_doxcer_version = {v}  (template)");
+    }
+    if !setup.template.optional_placeholders.is_empty() {
+        outln!("  optional_placeholders = {}  (template)", setup.template.optional_placeholders.join(", "));
+    }
+    if let Some(profile) = describe_run_profile(&setup.run_profile) {
+        outln!("  run_profile = {profile}");
+    }
+    for (name, value, source) in setup.settings.describe() {
+        outln!("  {name} = {value}  ({source})");
+    }
+    outln!("Profiles ({}):", setup.provider.name());
+    for line in describe_profiles(setup) {
+        outln!("  {line}");
+    }
+
+    if !setup.context.is_empty() {
+        outln!("Context ({} file(s), about {} tokens):", setup.settings.context_files.value.len(), count_tokens(&setup.context, &setup.settings.model.value));
+        if !setup.template.body.contains("{{context}}") {
+            outln!("  (the template has no {{{{context}}}} placeholder, so this is not sent)");
+        }
+        outln!("--- Context ---\n{}\n--- End of Context ---", setup.context);
+    }
+    let (prompt, _) = prepare_prompt(setup, notebook_path, notebook)?;
+    outln!(
+        "Prompt: {} chars input, {} chars instructions",
+        prompt.input.chars().count(),
+        prompt.instructions.map(|i| i.chars().count()).unwrap_or(0)
+    );
+    outln!("Dry run: no API request was sent.");
+    Ok(())
+}
+
+
+fn include_outputs_layer(options: &RunOptions) -> Option<bool> {
+
+    /// Returns the `include_outputs` the flags set: `--include-outputs` turns
+    /// outputs on, `--strip-outputs` off (also over `doxcer.toml` and the
+    /// environment); clap refuses the two together.
+
+    match (options.include_outputs, options.strip_outputs) {
+        (true, _) => Some(true),
+        (false, true) => Some(false),
+        (false, false) => None,
+    }
+}
+
+fn retain_changed(notebooks: &mut Vec<PathBuf>, changed: Vec<PathBuf>) {
+
+    /// Keeps the discovered notebooks that git lists as changed (absolute
+    /// paths, see [`git::changed_files`] and [`git::diff_files`]), so the
+    /// exclusions of discovery still apply.
+
+    let changed: BTreeSet<PathBuf> = changed.into_iter().collect();
+    notebooks.retain(|n| fs::canonicalize(n).is_ok_and(|c| changed.contains(&c)));
+}
+
+fn open_report(options: &RunOptions) -> Result<Option<JunitReport>, DoxcerError> {
+
+    /// Starts the `--report junit=<path>` report, if one was asked for.
+
+    let Some(path) = options.reports.iter().find_map(|report| match report {
+        Report::Junit(path) => Some(path),
+        Report::Json(_) => None,
+    }) else {
+        return Ok(None);
+    };
+    Ok(Some(JunitReport::new(path, resolve_tags(options)?)))
+}
+
+fn open_budget(settings: &Settings) -> Result<Option<Budget>, DoxcerError> {
+
+    /// Sets up the `--max-cost` budget of a run, if one was given; see [`reserve_budget`].
+
+    let Some(cap) = settings.max_cost_eur.value else {
+        return Ok(None);
+    };
+    let currency = currency_from_env()?;
+    Ok(Some(Budget::new(cap, &currency)?))
+}
+
+fn warn_over_budget(run: &RunContext, skipped: &[String]) {
+
+    /// Lists the notebooks that were not sent because the `--max-cost` budget
+    /// was used up.
+
+    let Some(budget) = run.budget.as_ref().filter(|_| !skipped.is_empty()) else {
+        return;
+    };
+    let locale = Locale::from_lang(run.setup.settings.lang.value.as_deref());
+    let eur = Currency { code: "EUR".to_string(), fx_rate: None };
+    warn(format!(
+        "the budget of {} was reached after {}; {} notebook(s) were not sent: {}. Raise --max-cost to document them; answers from the response cache cost nothing",
+        format_money(budget.cap, &eur, locale),
+        format_money(budget.spent(), &eur, locale),
+        skipped.len(),
+        skipped.join(", ")
+    ));
+}
+
+fn finish_cost_report(options: &RunOptions, settings: &Settings, usage: Vec<UsageEntry>) {
+
+    /// Prints the cost of a run per document and in total to stderr, and writes
+    /// it as JSON for `--report json[=<path>]`.
+    ///
+    /// # Description
+    /// Tokens are priced with `[prices]` from `doxcer.toml` and the built-in
+    /// list prices (see [`cost_line`]), in `DOXCER_CURRENCY`. Nothing is
+    /// printed when no document was generated.
+
+    let currency = currency_from_env().unwrap_or_else(|e| {
+        warn(e);
+        Currency { code: DEFAULT_CURRENCY.to_string(), fx_rate: None }
+    });
+    let lines = usage
+        .into_iter()
+        .map(|entry| {
+            let tokens = (entry.usage.and_then(|u| u.input_tokens), entry.usage.and_then(|u| u.output_tokens));
+            cost_line(&entry.notebook, &entry.model, entry.cached, tokens, &settings.prices.value, &currency)
+        })
+        .collect();
+    let report = cost_report(lines, &currency);
+    if !report.files.is_empty() {
+        let locale = Locale::from_lang(settings.lang.value.as_deref());
+        err!("{}", render_cost_summary(&report, &currency, locale));
+    }
+    for path in options.reports.iter().filter_map(|report| match report {
+        Report::Json(path) => Some(path),
+        Report::Junit(_) => None,
+    }) {
+        let written = serde_json::to_string_pretty(&report)
+            .map_err(|e| e.to_string())
+            .and_then(|json| fs::write(path, format!("{json}\n")).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            warn(format!("Failed to write cost report {}: {e}", path.display()));
+        }
+    }
+}
+
+fn report_case(report: &mut Option<JunitReport>, suite: String, name: String, started: Instant, outcome: CaseOutcome) {
+
+    /// Adds a notebook to the JUnit report, warning (not failing) when the file
+    /// cannot be written.
+
+    let Some(report) = report else {
+        return;
+    };
+    if let Err(e) = report.record(TestCase { suite, name, duration: started.elapsed(), outcome }) {
+        warn(e);
+    }
+}
+
+fn print_listing(discovery: &Discovery, verbose: bool) {
+
+    /// Prints the discovered notebooks for `--list`.
+    ///
+    /// # Description
+    /// Included notebooks go to stdout, one per line. With `--verbose`, excluded
+    /// files and folders are listed on stderr together with the rule that
+    /// excluded them.
+
+    for notebook in &discovery.notebooks {
+        outln!("{}", notebook.display());
+    }
+    if verbose {
+        for excluded in &discovery.excluded {
+            let style = Style::stderr();
+            errln!(
+                "{} {}  {}",
+                style.paint("excluded", Color::Dim),
+                excluded.path.display(),
+                style.paint(&format!("[{}]", excluded.rule), Color::Dim)
+            );
+        }
+    }
+}
+
+
+fn fix_document(run: &RunContext, notebook: &Path, doc_path: &Path, outputs: &OutputTargets, split: Option<SplitLimit>) -> Result<FixOutcome, DoxcerError> {
+
+    /// Regenerates the documentation of one notebook and writes it to `doc_path`.
+    ///
+    /// # Description
+    /// The notebook is read once; the prompt and the recorded hash both come from
+    /// those bytes. Just before writing, the file is re-hashed: if it changed in
+    /// the meantime the document is not written and `StaleInput` is returned, so
+    /// nothing claims to document a version that was never sent.
+    ///
+    /// The notebook content is kept in `.doxcer/sources` for `--edit-mode`.
+    ///
+    /// With `split`, a document over the limit is written as a landing page plus
+    /// one page per `##` section (see [`write_split`]).
+    ///
+    /// Every format in `outputs` is written from the same stamped Markdown (see
+    /// [`write_outputs`]); when only another format fails, the outcome is still
+    /// `Fixed` and the error is in its artifact.
+    ///
+    /// With `--at-ref`, the notebook is read from that commit and the ref and
+    /// commit are added to the front-matter.
+    ///
+    /// # Returns
+    /// * `Ok(FixOutcome::Fixed { .. })` – the document was written for `source_hash`.
+    /// * `Ok(FixOutcome::StaleInput { .. })` – the notebook changed after `source_hash` was sent.
+    /// * `Err(DoxcerError)` – reading, generating or writing failed.
+
+    let parse = run.tracer.span("parse");
+    let snapshot = read_source(run.at_ref.as_ref(), run.archive.as_ref(), notebook);
+    if let Err(e) = &snapshot {
+        parse.fail(e);
+    }
+    drop(parse);
+    let snapshot = snapshot?;
+    let generated = request_documentation(run, notebook, &snapshot, &snapshot.text)?
+        .ok_or_else(|| DoxcerError::Provider("No output received from API.".to_string()))?;
+    let label = notebook.display().to_string();
+    let Generated { text, model, cached, coverage, usage } = checked_links(run, generated, doc_path.parent().unwrap_or(Path::new(".")), &label)?;
+
+    if !snapshot.is_current()? {
+        return Ok(FixOutcome::StaleInput { source_hash: snapshot.hash, model });
+    }
+
+    let provenance = Provenance {
+        source: notebook.display().to_string(),
+        source_hash: snapshot.hash.clone(),
+        model: Some(model.clone()),
+        generated_at: run.timestamps.map(now_in),
+    };
+    let write = run.tracer.span("output-write");
+    write.set("doxcer.output", doc_path);
+    let mut markdown = stamp_provenance(&text, &provenance);
+    if let Some(coverage) = coverage {
+        markdown = upsert_front_matter(&markdown, &[(COVERAGE_KEY, coverage)]);
+    }
+    if let Some(history) = &run.setup.run_history {
+        markdown = upsert_front_matter(&markdown, &[(RUN_STATS_KEY, history.summary())]);
+    }
+    if let Some(at_ref) = &run.at_ref {
+        markdown = upsert_front_matter(&markdown, &[(GIT_REF_KEY, at_ref.reference.clone()), (GIT_COMMIT_KEY, at_ref.commit.clone())]);
+    }
+    let artifacts = write_outputs(outputs, doc_path, &markdown, split)?;
+    if let Err(e) = store_snapshot(Path::new(SNAPSHOT_DIR), &snapshot.hash, &snapshot.text) {
+        warn(format!("{}: {e}; --edit-mode cannot diff against this version", notebook.display()));
+    }
+    Ok(FixOutcome::Fixed { source_hash: snapshot.hash, model, cached, usage, artifacts })
+}
+
+
+fn write_outputs(outputs: &OutputTargets, doc_path: &Path, markdown: &str, split: Option<SplitLimit>) -> Result<Vec<Artifact>, DoxcerError> {
+
+    /// Writes a stamped document in every selected format.
+    ///
+    /// # Description
+    /// Each writer runs even when an earlier one failed, so one broken target
+    /// does not cost the others; all of them render the same Markdown and so
+    /// carry the same provenance (see [`render_format`]). Markdown honours
+    /// `split` and goes to `doc_path`; the other paths come from
+    /// [`OutputTargets::path`].
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Artifact>)` – one artifact per format with its own result.
+    /// * `Err(DoxcerError)` – the Markdown could not be written; the message
+    ///   includes the other writers' errors.
+
+    let artifacts: Vec<Artifact> = outputs
+        .formats
+        .iter()
+        .map(|&format| {
+            let path = outputs.path(format, doc_path);
+            let result = match (format, split) {
+                (OutputFormat::Markdown, Some(limit)) => write_split(&path, markdown, limit).map(|pages| {
+                    if pages.len() > 1 {
+                        errln!("split {} into {} pages (over {limit})", path.display(), pages.len());
+                    }
+                }),
+                (format, _) => render_format(format, markdown).and_then(|text| write_atomic(&path, &text)),
+            };
+            Artifact { format, path, result }
+        })
+        .collect();
+    let markdown_failed = artifacts.iter().any(|a| a.format == OutputFormat::Markdown && a.result.is_err());
+    match failed_writes(&artifacts) {
+        Err(e) if markdown_failed => Err(DoxcerError::Input(format!("{}: {e}", doc_path.display()))),
+        _ => Ok(artifacts),
+    }
+}
+
+
+fn fix_sections(run: &mut RunContext, notebook: &Path, doc_path: &Path, outputs: &OutputTargets, titles: &[String]) -> Result<FixOutcome, DoxcerError> {
+
+    /// Rewrites only the named `##` sections of an existing document (`--section`).
+    ///
+    /// # Description
+    /// Every title must be a section of the document; otherwise nothing is sent
+    /// and the error lists the sections it has. Per section, the prompt holds
+    /// the notebook, the full current document and an instruction to rewrite
+    /// only that section (see [`section_instruction`]); the answer is spliced
+    /// back in place with [`replace_section`], so the front-matter and the other
+    /// sections stay byte-for-byte as they were. Workspace artifacts are kept
+    /// under [`section_key`]. As in [`fix_document`], nothing is written when
+    /// the notebook changed while generating.
+    ///
+    /// # Returns
+    /// * `Ok(FixOutcome::Fixed { .. })` – the sections were rewritten; `cached`
+    ///   only when every answer came from the cache.
+    /// * `Ok(FixOutcome::StaleInput { .. })` – the notebook changed meanwhile.
+    /// * `Err(DoxcerError)` – a section is missing, or generating or writing failed.
+
+    let snapshot = read_source(run.at_ref.as_ref(), run.archive.as_ref(), notebook)?;
+    let mut doc = fs::read_to_string(doc_path)
+        .map_err(|e| DoxcerError::Input(format!("Failed to read {}: {e}", doc_path.display())))?;
+    for title in titles {
+        find_section(&doc, title).map_err(|e| DoxcerError::Input(format!("{}: {e}", doc_path.display())))?;
+    }
+
+    let label = notebook.display().to_string();
+    let base_dir = doc_path.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let mut model = run.setup.settings.model.value.clone();
+    let mut cached = true;
+    let mut usage: Option<Usage> = None;
+    let sum = |a: Option<u64>, b: Option<u64>| if a.is_none() && b.is_none() { None } else { Some(a.unwrap_or(0) + b.unwrap_or(0)) };
+    for title in titles {
+        run.setup.edit_instruction = Some(section_instruction(split_front_matter(&doc).1.trim(), title));
+        let key = section_key(&snapshot.hash, title);
+        let generated = fitted_prompt(run, notebook, &snapshot.text, &key)
+            .and_then(|(prompt, _)| generate(run, &prompt, &key, &label, &run.setup.template.hash));
+        run.setup.edit_instruction = None;
+        let generated = generated?.ok_or_else(|| DoxcerError::Provider("No output received from API.".to_string()))?;
+        let generated = checked_links(run, generated, &base_dir, &label)?;
+        doc = replace_section(&doc, title, &generated.text)
+            .map_err(|e| DoxcerError::Provider(format!("{}: {e}", doc_path.display())))?;
+        errln!("rewrote section `{title}` of {}", doc_path.display());
+        model = generated.model;
+        cached &= generated.cached;
+        usage = match (usage, generated.usage) {
+            (Some(a), Some(b)) => Some(Usage { input_tokens: sum(a.input_tokens, b.input_tokens), output_tokens: sum(a.output_tokens, b.output_tokens) }),
+            (a, b) => a.or(b),
+        };
+    }
+
+    if !snapshot.is_current()? {
+        return Ok(FixOutcome::StaleInput { source_hash: snapshot.hash, model });
+    }
+    let artifacts = write_outputs(outputs, doc_path, &doc, None)?;
+    Ok(FixOutcome::Fixed { source_hash: snapshot.hash, model, cached, usage, artifacts })
+}
+
+
+fn read_source(at_ref: Option<&GitRef>, archive: Option<&Archive>, notebook: &Path) -> Result<SourceSnapshot, DoxcerError> {
+
+    /// Reads a notebook from the working tree, from the commit of `--at-ref`,
+    /// or from the ZIP archive given as input.
+
+    if let Some(archive) = archive {
+        let bytes = archive_member(archive, notebook)
+            .ok_or_else(|| DoxcerError::Input(format!("{} does not exist in {}", notebook.display(), archive.path.display())))?;
+        return SourceSnapshot::in_archive(notebook, bytes.to_vec(), &archive.path);
+    }
+    let Some(at_ref) = at_ref else {
+        return SourceSnapshot::read(notebook);
+    };
+    let bytes = git::ref_content(&at_ref.commit, notebook)?
+        .ok_or_else(|| DoxcerError::Input(format!("{} does not exist at {}", notebook.display(), at_ref.reference)))?;
+    SourceSnapshot::at_commit(notebook, bytes, &at_ref.commit)
+}
+
+
+fn archive_member<'a>(archive: &'a Archive, notebook: &Path) -> Option<&'a [u8]> {
+
+    /// Looks up the member an `archive.zip!member` notebook path names.
+
+    split_member_path(notebook).and_then(|(_, member)| archive.read(&member))
+}
+
+fn read_archive_input(archive: &Path, member: &str) -> Result<SourceSnapshot, DoxcerError> {
+
+    /// Reads one archive member a generate run was given (`export.zip!path.py`).
+
+    let opened = open_archive(archive, |name| name == member)?;
+    if let Some(skipped) = opened.skipped.first() {
+        return Err(DoxcerError::Input(format!("{}: skipped {skipped}", archive.display())));
+    }
+    let bytes = opened.read(member).ok_or_else(|| DoxcerError::Input(format!("{member} does not exist in {}", archive.display())))?;
+    SourceSnapshot::in_archive(&member_path(archive, member), bytes.to_vec(), archive)
+}
+
+fn open_archive_inputs(inputs: &[(PathBuf, Option<String>)]) -> Result<(Archive, Vec<PathBuf>), DoxcerError> {
+
+    /// Opens the ZIP archive of a verify run and returns it with the folders or
+    /// members inside it to discover (`.` for the whole archive).
+    ///
+    /// # Description
+    /// Only notebook members below those folders are read. Skipped members
+    /// (non-UTF-8 names, unsupported compression, over the size limit) are
+    /// reported as warnings.
+
+    let path = inputs[0].0.clone();
+    if inputs.iter().any(|(archive, _)| *archive != path) {
+        return Err(DoxcerError::Usage("only one archive can be checked per run".to_string()));
+    }
+    let inner: Vec<PathBuf> = inputs.iter().map(|(_, inner)| PathBuf::from(inner.as_deref().unwrap_or("."))).collect();
+    let selected = |name: &str| {
+        let name = Path::new(name);
+        is_notebook_path(name) && inner.iter().any(|i| i.as_os_str() == "." || name.starts_with(i))
+    };
+    let archive = open_archive(&path, selected)?;
+    for skipped in &archive.skipped {
+        warn(format!("{}: skipped {skipped}", path.display()));
+    }
+    for folder in inner.iter().filter(|i| i.as_os_str() != ".") {
+        if !archive.members.keys().any(|name| Path::new(name).starts_with(folder)) {
+            return Err(DoxcerError::Input(format!("{} contains no notebooks below {}", path.display(), folder.display())));
+        }
+    }
+    Ok((archive, inner))
+}
+
+fn discover_archive(archive: &Archive, inner: &[PathBuf], options: &DiscoveryOptions) -> Result<Discovery, DoxcerError> {
+
+    /// Applies the discovery rules (`--exclude`, `.doxcerignore`, `skip=true`)
+    /// to the notebook members of an opened archive, naming each one as
+    /// `export.zip!member`.
+
+    let members: Vec<PathBuf> = archive.members.keys().map(PathBuf::from).collect();
+    let found = discover_listed(inner, options, &members, |path| {
+        archive.read(&path.to_string_lossy()).and_then(|bytes| String::from_utf8(bytes.to_vec()).ok())
+    })?;
+    let named = |path: &Path| member_path(&archive.path, &path.to_string_lossy());
+    Ok(Discovery {
+        notebooks: found.notebooks.iter().map(|n| named(n)).collect(),
+        excluded: found.excluded.into_iter().map(|e| Excluded { path: named(&e.path), ..e }).collect(),
+    })
+}
+
+fn run_post_hooks(run: &RunContext, hook: &HookContext) -> Result<(), DoxcerError> {
+
+    /// Runs the configured post-hooks on a generated document, in order,
+    /// stopping at the first one that fails (see [`run_post_hook`]).
+
+    let timeout = Duration::from_secs(run.setup.settings.post_hook_timeout.value);
+    for command in &run.setup.settings.post_hooks.value {
+        run_post_hook(command, hook, timeout)?;
+    }
+    Ok(())
+}
+
+
+fn write_workspace_summary(run: &RunContext, results: &[VerifyResult], docs_dir: &Path) -> Result<PathBuf, DoxcerError> {
+
+    /// Writes `docs/overview.md`, a summary of the whole workspace.
+    ///
+    /// # Description
+    /// Sends one request with the summary template (`templates/summary.md`, or the
+    /// built-in one), the body of every generated document and the tables each
+    /// notebook reads and writes. The answer is followed by a Mermaid graph that
+    /// doxcer draws itself from the same lineage (writer of X → reader of X).
+    /// Notebooks without a document are left out.
+
+    let template = load_summary_template(&companion_template(SUMMARY_TEMPLATE_NAME, run.setup.settings.lang.value.as_deref()))?;
+    for warning in &template.warnings {
+        warn(warning);
+    }
+
+    let mut input = template.body.clone();
+    let mut lineages = Vec::new();
+    for result in results {
+        let Ok(doc) = fs::read_to_string(&result.doc_path) else {
+            continue;
+        };
+        let source = read_source(run.at_ref.as_ref(), run.archive.as_ref(), &result.notebook)?;
+        let lineage = extract_lineage(&source.text);
+        let name = result.notebook.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let list = |tables: &BTreeSet<String>| {
+            if tables.is_empty() { "-".to_string() } else { tables.iter().cloned().collect::<Vec<_>>().join(", ") }
+        };
+        input.push_str(&format!(
+            "\n\n## {name}\nLeest: {}\nSchrijft: {}\n\n{}",
+            list(&lineage.reads),
+            list(&lineage.writes),
+            split_front_matter(&doc).1.trim()
+        ));
+        lineages.push((name, lineage));
+    }
+
+    let prompt = Prompt { instructions: None, input };
+    let hash = content_hash(prompt.input.as_bytes());
+    let Generated { text, model, .. } = generate(run, &prompt, &hash, SUMMARY_FILE_NAME, &template.hash)?
+        .ok_or_else(|| DoxcerError::Provider("No output received from API.".to_string()))?;
+
+    let mut pairs = vec![(VERSION_KEY, version().to_string()), (MODEL_KEY, model)];
+    if let Some(tz) = run.timestamps {
+        pairs.push((GENERATED_AT_KEY, now_in(tz)));
+    }
+    let markdown = format!("{}\n\n## Afhankelijkheden\n\n{}", text.trim_end(), mermaid_graph(&lineages));
+    let path = docs_dir.join(SUMMARY_FILE_NAME);
+    fs::create_dir_all(docs_dir).map_err(|e| DoxcerError::Input(format!("Failed to create {}: {e}", docs_dir.display())))?;
+    fs::write(&path, upsert_front_matter(&markdown, &pairs))
+        .map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", path.display())))?;
+    Ok(path)
+}
+
+
+// ----------------------------
+// Commands
+// ----------------------------
+fn run_generate(args: GenerateArgs) -> Result<(), DoxcerError> {
+
+    /// Generates documentation for a notebook, or a batch of them, and prints it to stdout.
+    ///
+    /// # Description
+    /// 1. Loads environment configuration and decrypts the OpenAI API key.
+    /// 2. Reads the notebook and the Markdown template (`prompt.md`), resolving the
+    ///    settings from template front-matter, `doxcer.toml`, env vars and flags.
+    /// 3. Sends the documentation request to the OpenAI API.
+    /// 4. Stamps the source provenance into the front-matter and prints the result.
+    /// 5. With `--copy`, also places the final Markdown on the system clipboard.
+    ///
+    /// With `--list`, only discovery runs: the notebooks below the given paths are
+    /// printed (honouring `--exclude`, `.doxcerignore` and `skip=true` directives)
+    /// and no API call is made. With `--dry-run`, the resolved settings are
+    /// printed instead of sending the request.
+    ///
+    /// `--range <start>:<end>`, `--cell <index|start-end|title>` or
+    /// `--cells <list>` (`3-10,15`, cells as numbered by the notebook parser)
+    /// documents only part of the notebook, with `--context-cells` neighbouring
+    /// cells (default 1) as context. The output is marked with `doxcer_excerpt`
+    /// in its front-matter.
+    ///
+    /// On a terminal, prose is soft-wrapped at the terminal width (`--wrap <cols>`,
+    /// `--wrap 0` to disable); redirected output and `--copy` are never wrapped.
+    ///
+    /// The notebook may also be an `https://` URL; it is downloaded first (see
+    /// [`download_source`]) and named after the URL or `--name <name>`.
+    ///
+    /// Links and images in the answer are checked against the working
+    /// directory before printing, see [`checked_links`] and `--link-policy`.
+    ///
+    /// A notebook too large for the model fails with exit code 6 and a list of
+    /// its largest cells, unless `--force-oversize` is given (see [`fitted_prompt`]).
+    ///
+    /// `--mode summary` writes a single paragraph instead of the full document,
+    /// from the notebook summary template (see [`request_summary`]); its limit is
+    /// `--summary-max-chars` (default 600). `--mode full,summary` prints both,
+    /// full document first, from one read of the notebook and in one run.
+    ///
+    /// `--context <path>` (repeatable, or `context_files` in `doxcer.toml` or
+    /// the template) adds files such as naming conventions or a data
+    /// dictionary to the prompt as `{{context}}`, capped by
+    /// `--max-context-tokens` (default 8000); see [`load_context`].
+    ///
+    /// Intermediate files go to a per-run workspace that is removed on success
+    /// (kept with `--keep-workdir` or when the run fails); `--resume` reuses the
+    /// answer of an earlier, interrupted run for the same request.
+    ///
+    /// Answers come from the response cache when the same request was sent
+    /// before; `--no-cache` sends every request again and `--cache-ttl <age>`
+    /// ignores entries older than that (see [`open_cache`]).
+    ///
+    /// More than one path, a directory or an `--include <pattern>` makes a
+    /// batch: the notebooks are discovered as for `--list` and documented in
+    /// turn, each printed to stdout with an `ok` or `failed` line on stderr.
+    /// A failed notebook does not stop the batch; the run exits non-zero when
+    /// any failed (see [`batch_failure`]). A notebook named twice is documented
+    /// once (see [`coalesce_targets`]). `--concurrency <n>` (or `--jobs <n>`,
+    /// default 1) documents up to `n` notebooks at a time, so at most `n` API requests are
+    /// in flight; the output still follows the input order (see
+    /// [`generate_in_order`]).
+    ///
+    /// `--changed-only` skips notebooks whose content hash matches their entry
+    /// in the manifest (`.doxcer/manifest.json`, or `--manifest <file>`) and
+    /// whose document still exists, printing `unchanged` for each; documented
+    /// notebooks are recorded there with their hash and output. The manifest
+    /// is shared with `verify`, so a document fixed there counts as well.
+    ///
+    /// `--git-diff origin/main...HEAD` documents, as a batch, only the
+    /// discovered notebooks that the diff range added, modified or renamed
+    /// (see [`git::diff_files`]); paths default to `.`.
+    ///
+    /// `--output <dir>` writes each document to a file instead of stdout:
+    /// `<dir>/<stem>.md`, as `verify` expects it, and `<stem>.summary.md` for
+    /// `--mode summary`; `--output-file <path>` names the file of a single
+    /// document. Files are written atomically (see [`write_document`]). An
+    /// existing file fails the document before any API request, unless
+    /// `--overwrite` replaces it or `--backup` keeps it as `<file>.bak`. The
+    /// template preview always goes to stderr, so stdout holds only documents.
+    ///
+    /// For a single notebook on a terminal the answer is streamed: the text is
+    /// shown dimmed on stderr while the model writes it (see [`read_stream`]),
+    /// and the finished document follows as usual. `--no-stream` waits for the
+    /// whole answer instead.
+    ///
+    /// `--max-cost <eur>` (or `DOXCER_MAX_COST_EUR`) caps the estimated spend of
+    /// the run: once the next request would exceed it, that notebook and every
+    /// later one are marked `over-budget` and not sent, and the run ends with
+    /// a list of them (see [`reserve_budget`]).
+    ///
+    /// `--kind sql` documents SQL scripts (stored procedures, views, T-SQL)
+    /// with the SQL template (`templates/sql.md`, else the built-in one) and
+    /// collects every `.sql` file from directories. With the default `auto`,
+    /// a `.sql` file that is not a Databricks export is a SQL script and
+    /// everything else a notebook (see [`detect_kind`]).
+    ///
+    /// `--kind pipeline` documents Fabric Data Pipelines: the JSON definition
+    /// is summarized into its parameters, variables and activities (with what
+    /// each waits for) before it is sent, with the pipeline template
+    /// (`templates/pipeline.md`). Directories yield their
+    /// `<name>.DataPipeline/pipeline-content.json` files. With `auto`, a `.json`
+    /// file with an `activities` list is a pipeline.
+    ///
+    /// `--kind semantic-model` documents Power BI semantic models, a `model.bim`
+    /// or the `definition/model.tmdl` of a TMDL folder (read together with the
+    /// other `.tmdl` files there): their tables, columns, DAX measures,
+    /// sources and relationships are listed before the model is sent, with
+    /// the semantic model template (`templates/semantic_model.md`). With
+    /// `auto`, those two file names are semantic models.
+    ///
+    /// `--template-name <name>` documents every file with a named template:
+    /// `notebook`, `sql`, `pipeline`, `semantic-model`, or `<name>.md` next to
+    /// the prompt template. Without it, the first `[[templates]]` entry in
+    /// `doxcer.toml` whose pattern matches the file picks the template, else
+    /// the kind does (see [`load_named_setup`]).
+    ///
+    /// `--granularity cell` documents a notebook cell by cell and assembles the
+    /// answers into a section per cell (see [`cell_documentation`]).
+    ///
+    /// `--inject` writes the documentation back into the notebook itself: a
+    /// markdown cell (`.ipynb`) or `# MARKDOWN` block (Fabric `.py`) above each
+    /// code cell, marked with the hash of that cell, so a later run replaces
+    /// the injected cells instead of adding more (see [`inject_target`]).
+    /// `--backup` keeps the previous notebook as `<file>.bak`.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer [generate] <path/to/notebook.py|https://...> [--copy] [--output <dir> | --output-file <path>] [--overwrite | --backup] [--dry-run] [--range <start>:<end> | --cell <index|title> | --cells <list>] [--context-cells <n>] [--wrap <cols>] [--name <name>] [--mode full|summary|full,summary] [--kind auto|notebook|sql|pipeline|semantic-model] [--template-name <name>] [--inject] [--summary-max-chars <n>] [--lang <code>] [--provider <name>] [--model <name>] [--fallback-models <m1,m2>] [--max-output-tokens <n>] [--temperature <t>] [--top-p <p>] [--reasoning-effort minimal|low|medium|high] [--max-output-bytes <n>] [--config <file>] [--workdir <dir>] [--keep-workdir] [--resume] [--no-cache] [--cache-ttl <age>] [--timestamp-tz utc|local] [--no-timestamps] [--disable-stage <name>]... [--pipeline-dump <dir>] [--secret-patterns <file>] [--max-total-tokens <n>] [--oversize-policy fail|warn|truncate|chunk] [--force-oversize] [--chunk-strategy cells|lines|tokens] [--granularity document|cell] [--link-policy warn|strip|fail] [--pii off|warn|redact|fail] [--pii-audit] [--fabric-runs] [--context <path>]... [--max-context-tokens <n>] [--single-input] [--include-outputs] [--strip-outputs] [--redact-literals] [--retry-max-attempts <n>] [--retry-base-delay-ms <ms>] [--file-timeout <secs>] [--max-cost <eur>] [--no-stream] [--report junit=<path>|json[=<path>]]... [--tag <key=value>]... [--var <name=value>]...
+    /// doxcer [generate] <paths>... [--include <pattern>]... [--exclude <pattern>]... [--concurrency <n>|--jobs <n>] [--changed-only] [--manifest <file>] [options]
+    /// doxcer [generate] --git-diff <range> [<paths>...] [options]
+    /// doxcer <paths>... --list [--verbose] [--include <pattern>]... [--exclude <pattern>]...
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the notebook or template files cannot be read, or if the
+    /// environment cannot be loaded.
+
+    let GenerateArgs {
+        mut paths, copy, output, output_file, overwrite, backup, dry_run, list, verbose, no_stream, changed_only, inject, git_diff,
+        manifest: manifest_path, range, cell, cells, context_cells, wrap, name, modes, kind, template_name, summary_max_chars,
+        include, exclude, concurrency, model, run: run_options,
+    } = args;
+    let output = output.map(DocTarget::Dir).or(output_file.map(DocTarget::File));
+    let policy = match (overwrite, backup) {
+        (true, _) => ExistingPolicy::Overwrite,
+        (false, true) => ExistingPolicy::Backup,
+        (false, false) => ExistingPolicy::Refuse,
+    };
+    let selector = range.or(cell).or(cells);
+    let options = DiscoveryOptions {
+        includes: include,
+        excludes: exclude,
+        sql_scripts: kind == Some(SourceKind::Sql),
+        pipelines: kind == Some(SourceKind::Pipeline),
+        semantic_models: kind == Some(SourceKind::SemanticModel),
+    };
+    let config_path = model.config;
+    let mut cli = SettingsLayer {
+        provider: model.provider,
+        model: model.model,
+        fallback_models: model.fallback_models.as_deref().map(split_list),
+        max_output_tokens: model.max_output_tokens,
+        temperature: model.temperature,
+        top_p: model.top_p,
+        reasoning_effort: model.reasoning_effort,
+        max_output_bytes: model.max_output_bytes,
+        summary_max_chars,
+        ..SettingsLayer::default()
+    };
+
+    if paths.is_empty() && git_diff.is_some() {
+        paths.push(".".to_string());
+    }
+    let inputs: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+    if list {
+        print_listing(&discover(&inputs, &options)?, verbose);
+        return Ok(());
+    }
+
+    if dry_run {
+        match load_env_robust::<&Path>(None) {
+            Ok(found) => check_env_safety(&found)?,
+            Err(e) => warn(e),
+        }
+    } else {
+        load_env()?;
+    }
+
+    if paths.is_empty() {
+        return Err(usage_error("generate", "no notebook given"));
+    }
+
+    // More than one path, a directory, an `--include` pattern or `--git-diff`
+    // makes a batch: every discovered notebook is documented in turn, one
+    // after the other on stdout, with an `ok / failed` line per notebook on stderr.
+    // A `.zip` on its own, or a folder in one (`export.zip!ws`), is a batch of
+    // the notebooks inside it.
+    let archive_batch = match paths.as_slice() {
+        [path] => split_archive_input(path).filter(|(_, inner)| !inner.as_deref().is_some_and(|inner| is_notebook_path(Path::new(inner)))),
+        _ => None,
+    };
+    let batch_mode = archive_batch.is_some() || paths.len() > 1 || !options.includes.is_empty() || git_diff.is_some() || inputs.iter().any(|p| p.is_dir());
+    let mut targets: Vec<String> = if batch_mode {
+        if selector.is_some() {
+            return Err(DoxcerError::Usage("--range, --cell and --cells document a single notebook, not a batch".to_string()));
+        }
+        if name.is_some() {
+            return Err(DoxcerError::Usage("--name names a single downloaded notebook, not a batch".to_string()));
+        }
+        if copy {
+            return Err(DoxcerError::Usage("--copy puts a single document on the clipboard, not a batch".to_string()));
+        }
+        if archive_batch.is_none()
+            && let Some(path) = paths.iter().find(|p| p.starts_with("http://") || p.starts_with("https://") || split_archive_input(p).is_some())
+        {
+            return Err(DoxcerError::Usage(format!("a batch takes local notebooks, directories or one .zip, got {path}")));
+        }
+        if archive_batch.is_some() && git_diff.is_some() {
+            return Err(DoxcerError::Usage("a .zip input cannot be combined with --git-diff".to_string()));
+        }
+        let mut notebooks = match &archive_batch {
+            Some(input) => {
+                let (archive, inner) = open_archive_inputs(std::slice::from_ref(input))?;
+                discover_archive(&archive, &inner, &options)?.notebooks
+            }
+            None => discover(&inputs, &options)?.notebooks,
+        };
+        if notebooks.is_empty() {
+            return Err(DoxcerError::Input(format!("No notebooks found below {}", paths.iter().map(|p| p.as_str()).collect::<Vec<_>>().join(", "))));
+        }
+        if let Some(range) = &git_diff {
+            retain_changed(&mut notebooks, git::diff_files(range)?);
+            if notebooks.is_empty() {
+                errln!("No notebooks changed in {range}.");
+                return Ok(());
+            }
+        }
+        coalesce_targets(notebooks.iter().map(|n| n.display().to_string()))
+    } else {
+        vec![paths[0].clone()]
+    };
+    if let Some(path) = targets.iter().find(|p| p.starts_with("http://")) {
+        return Err(DoxcerError::Usage(format!("only https:// URLs are supported, got {path}")));
+    }
+    let mut manifest = Manifest::default();
+    if changed_only {
+        if selector.is_some() {
+            return Err(DoxcerError::Usage("--changed-only documents whole notebooks, not a --range, --cell or --cells".to_string()));
+        }
+        if let Some(path) = targets.iter().find(|p| p.starts_with("https://") || split_archive_input(p).is_some()) {
+            return Err(DoxcerError::Usage(format!("--changed-only takes local notebooks, got {path}")));
+        }
+        manifest = Manifest::load(&manifest_path)?;
+        let all = targets.len();
+        targets.retain(|file_path| {
+            let unchanged = unchanged_since_last_run(&manifest, file_path, &manifest_output(output.as_ref(), file_path, modes[0]));
+            if unchanged {
+                errln!("{} {file_path}", Style::stderr().paint("unchanged  ", Color::Dim));
+            }
+            !unchanged
+        });
+        if targets.is_empty() {
+            errln!("all {all} notebook(s) are unchanged since they were last documented");
+            return Ok(());
+        }
+    }
+    if inject {
+        if selector.is_some() {
+            return Err(DoxcerError::Usage("--inject documents whole notebooks, not a --range, --cell or --cells".to_string()));
+        }
+        if output.is_some() {
+            return Err(DoxcerError::Usage("--inject writes into the notebook itself; drop --output and --output-file".to_string()));
+        }
+        if modes != [DocMode::Full] || kind.is_some_and(|kind| kind != SourceKind::Notebook) {
+            return Err(DoxcerError::Usage("--inject takes notebooks and the full documentation, not --mode summary or another --kind".to_string()));
+        }
+        if let Some(path) = targets.iter().find(|p| p.starts_with("https://") || split_archive_input(p).is_some()) {
+            return Err(DoxcerError::Usage(format!("--inject takes local notebooks, got {path}")));
+        }
+    }
+    if policy != ExistingPolicy::Refuse && output.is_none() && !inject {
+        return Err(DoxcerError::Usage("--overwrite and --backup require --output or --output-file".to_string()));
+    }
+    if matches!(output, Some(DocTarget::File(_))) && (batch_mode || modes.len() > 1) {
+        return Err(DoxcerError::Usage("--output-file takes a single document; use --output <dir> for a batch or several modes".to_string()));
+    }
+    if let Some(target @ DocTarget::Dir(_)) = &output {
+        let mut seen: BTreeMap<PathBuf, &String> = BTreeMap::new();
+        for file_path in &targets {
+            for mode in &modes {
+                let path = target.path(Path::new(file_path), *mode);
+                if let Some(other) = seen.insert(path.clone(), file_path) {
+                    return Err(DoxcerError::Usage(format!("{other} and {file_path} would both be written to {}", path.display())));
+                }
+            }
+        }
+    }
+
+    cli.secret_patterns = secret_patterns_layer(&run_options)?;
+    cli.link_policy = run_options.link_policy;
+    cli.pii = run_options.pii;
+    cli.context_files = Some(run_options.context_files.clone()).filter(|files| !files.is_empty());
+    cli.max_context_tokens = run_options.max_context_tokens;
+    cli.max_total_tokens = run_options.max_total_tokens;
+    cli.oversize_policy = resolve_oversize_policy(&run_options);
+    cli.chunk_strategy = run_options.chunk_strategy;
+    cli.granularity = run_options.granularity;
+    cli.single_input = run_options.single_input.then_some(true);
+    cli.retry_max_attempts = run_options.retry_max_attempts;
+    cli.retry_base_delay_ms = run_options.retry_base_delay_ms;
+    cli.file_timeout = run_options.file_timeout;
+    cli.max_cost_eur = run_options.max_cost;
+    cli.include_outputs = include_outputs_layer(&run_options);
+    cli.redact_literals = run_options.redact_literals.then_some(true);
+    cli.vars = vars_layer(&run_options);
+    cli.lang = run_options.lang.clone();
+    let local: Vec<PathBuf> = targets.iter().filter(|p| !p.starts_with("https://")).map(PathBuf::from).collect();
+    let workspace = workspace_notebooks(&options, &local);
+    /// One setup per mode and per kind of file in the run.
+    let kind_given = kind.is_some();
+    let kinds = match kind {
+        Some(kind) => vec![kind],
+        None => {
+            let mut kinds = vec![SourceKind::Notebook];
+            if targets.iter().any(|t| is_sql_path(Path::new(t))) {
+                kinds.push(SourceKind::Sql);
+            }
+            if targets.iter().any(|t| is_json_path(Path::new(t))) {
+                kinds.push(SourceKind::Pipeline);
+            }
+            if targets.iter().any(|t| is_semantic_model_path(Path::new(t))) {
+                kinds.push(SourceKind::SemanticModel);
+            }
+            kinds
+        }
+    };
+    /// Full documents get one setup per kind and named template in use.
+    let template_routes = load_template_routes(config_path.as_deref())?;
+    let mut templates: Vec<(SourceKind, String)> = Vec::new();
+    for kind in &kinds {
+        for target in targets.iter().map(Path::new).filter(|path| kind_given || plausible_kind(*kind, path)) {
+            let template = (*kind, template_name_for(template_name.as_deref(), &template_routes, target, *kind));
+            if !templates.contains(&template) {
+                templates.push(template);
+            }
+        }
+    }
+    let mut jobs = Vec::new();
+    for mode in modes {
+        let setups: Vec<(SourceKind, Option<&str>)> = match mode {
+            DocMode::Full => templates.iter().map(|(kind, name)| (*kind, Some(name.as_str()))).collect(),
+            DocMode::Summary => kinds.iter().map(|kind| (*kind, None)).collect(),
+        };
+        for (kind, name) in &setups {
+            let setup = match name {
+                Some(name) => load_named_setup(*kind, name, &cli, config_path.as_deref()),
+                None => load_summary_setup(&cli, config_path.as_deref()).map(|setup| GenerationSetup { kind: *kind, ..setup }),
+            };
+            let mut setup = setup?;
+            setup.pipeline = resolve_pipeline(&run_options)?;
+            check_fabric_workspace(&run_options, &setup)?;
+            if verbose {
+                print_verbose_config(config_path.as_deref(), &setup)?;
+            }
+            jobs.push((mode, setup));
+        }
+    }
+    let api_key = if dry_run { String::new() } else { provider_api_key(jobs[0].1.provider.as_ref())? };
+
+    let client = http_client()?;
+    if dry_run {
+        for file_path in &targets {
+            let snapshot = read_generate_target(&client, file_path, name.as_deref())?;
+            if batch_mode {
+                outln!("Notebook: {file_path}");
+            }
+            let excerpt = selector
+                .as_ref()
+                .map(|selector| select_excerpt(&snapshot.path, &snapshot.text, selector, context_cells))
+                .transpose()?;
+            let notebook_content = excerpt.as_ref().map_or(&snapshot.text, |e| &e.text);
+            let child_notebooks = child_notebooks_for(&snapshot.path, notebook_content, &workspace, Path::new(DEFAULT_DOCS_DIR));
+            let file_kind = kind.unwrap_or_else(|| detect_kind(&snapshot.path, &snapshot.text));
+            let template = template_name_for(template_name.as_deref(), &template_routes, &snapshot.path, file_kind);
+            for (_, setup) in jobs.iter_mut().filter(|(mode, setup)| setup.kind == file_kind && (*mode == DocMode::Summary || setup.template_name == template)) {
+                setup.child_notebooks = child_notebooks.clone();
+                print_dry_run(setup, &snapshot.path, notebook_content)?;
+            }
+        }
+        return Ok(());
+    }
+
+    for (_, setup) in &jobs {
+        let preview = format!(
+            "Loaded prompt template from: {}\n--- Preview ---\n{}\n--- End of Preview ---\n",
+            setup.template.path.display(),
+            &setup.template.body.chars()
+                .take(250)
+                .collect::<String>()
+        );
+        errln!("{preview}");
+    }
+
+    let run = RunContext {
+        client,
+        api_key,
+        setup: jobs[0].1.clone(),
+        workspace: open_workspace(&run_options)?,
+        cache: open_cache(&run_options),
+        resume: run_options.resume,
+        timestamps: resolve_timestamps(&run_options)?,
+        tracer: open_tracer(),
+        verbose,
+        pii_audit: run_options.pii_audit,
+        fabric_runs: run_options.fabric_runs,
+        fabric_items: Arc::new(OnceLock::new()),
+        workspace_files: Arc::new(OnceLock::new()),
+        at_ref: None,
+        archive: None,
+        tags: resolve_tags(&run_options)?,
+        stream: !no_stream && !batch_mode && io::stderr().is_terminal(),
+        budget: open_budget(&jobs[0].1.settings)?,
+        cancel: interrupt_token(),
+    };
+    let request = GenerateRequest { name, selector, context_cells, wrap, verbose, output, policy, workspace, inputs, kind, inject, template_name, template_routes };
+    let mut documents = Vec::new();
+    let mut errors = Vec::new();
+    let run_span = run.tracer.span("doxcer.run");
+    run_span.set("doxcer.command", "generate");
+    let mut report = open_report(&run_options)?;
+    let mut usage = Vec::new();
+    let mut over_budget = Vec::new();
+    let concurrency = if batch_mode { concurrency } else { 1 };
+    generate_in_order(&run, &jobs, &targets, &request, concurrency, |file_path, result| {
+        for note in &result.notes {
+            errln!("{note}");
+        }
+        for text in &result.stdout {
+            out!("{text}");
+        }
+        for case in result.cases {
+            if let Some(Err(e)) = report.as_mut().map(|report| report.record(case)) {
+                warn(e);
+            }
+        }
+        documents.extend(result.documents);
+        usage.extend(result.usage);
+        if changed_only
+            && result.failure.is_none()
+            && let Some(source_hash) = result.source_hash
+        {
+            let output = manifest_output(request.output.as_ref(), file_path, jobs[0].0);
+            manifest.record(Path::new(file_path), ManifestEntry {
+                source_hash,
+                output,
+                snapshot: None,
+                git_ref: None,
+                git_commit: None,
+                generated_at: run.timestamps.map(|_| now_rfc3339()),
+            });
+        }
+        match result.failure {
+            Some(e @ DoxcerError::OverBudget(_)) if batch_mode => {
+                errln!("{} {file_path}: {e}", Style::stderr().paint("over-budget", Color::Yellow));
+                over_budget.push(file_path.to_string());
+                errors.push(e);
+            }
+            Some(e) if batch_mode => {
+                errln!("{} {file_path}: {e}", Style::stderr().paint("failed     ", Color::Red));
+                errors.push(e);
+            }
+            Some(e) => errors.push(e),
+            None if batch_mode => errln!("{} {file_path}", Style::stderr().paint("ok         ", Color::Green)),
+            None => {}
+        }
+    });
+
+    if changed_only && let Err(e) = manifest.save(&manifest_path) {
+        return Err(e);
+    }
+    if copy && errors.is_empty() {
+        let markdown = documents.join("\n\n");
+        match copy_to_clipboard(&markdown) {
+            Ok(()) => {
+                let locale = Locale::from_lang(run.setup.settings.lang.value.as_deref());
+                errln!("copied {} to clipboard", format_size_in(markdown.len(), locale));
+            }
+            Err(e) => warn(e),
+        }
+    }
+    if batch_mode {
+        errln!("documented {} of {} notebook(s)", targets.len() - errors.len(), targets.len());
+        run_span.set("doxcer.files", targets.len());
+        run_span.set("doxcer.failed", errors.len());
+    }
+    drop(run_span);
+    finish_cost_report(&run_options, &run.setup.settings, usage);
+    warn_over_budget(&run, &over_budget);
+    export_traces(&run.tracer);
+    finish_workspace(run.workspace, errors.is_empty());
+    if run.cancel.is_cancelled() {
+        return Err(DoxcerError::Interrupted);
+    }
+    if !batch_mode && let Some(e) = errors.pop() {
+        return Err(e);
+    }
+    if let Some(e) = batch_failure(&errors, targets.len(), "generated") {
+        return Err(e);
+    }
+    Ok(())
+}
+
+fn coalesce_targets(targets: impl Iterator<Item = String>) -> Vec<String> {
+
+    /// Puts the notebooks of a batch through a [`WorkQueue`] keyed by their
+    /// manifest key, so a notebook named twice (`doxcer a a/x.py`) is
+    /// documented once, in the place it was first named.
+
+    let targets: Vec<String> = targets.collect();
+    let mut queue = WorkQueue::new(targets.len());
+    for target in targets {
+        queue.push(manifest_key(Path::new(&target)), target);
+    }
+    std::iter::from_fn(|| queue.pop()).map(|(_, target)| target).collect()
+}
+
+fn manifest_output(output: Option<&DocTarget>, file_path: &str, mode: DocMode) -> String {
+
+    /// Names where a `generate` run writes a notebook's document, as recorded
+    /// in the manifest: the file, or `-` for stdout.
+
+    output.map_or_else(|| STDOUT_OUTPUT.to_string(), |target| target.path(Path::new(file_path), mode).display().to_string())
+}
+
+fn unchanged_since_last_run(manifest: &Manifest, file_path: &str, output: &str) -> bool {
+
+    /// Tells whether `--changed-only` may skip a notebook: its content hash
+    /// matches the manifest entry of its last documentation, that run wrote
+    /// to the same `output` as this one, and a document written to a file
+    /// then still exists.
+
+    let path = Path::new(file_path);
+    let Some(entry) = manifest.get(path) else {
+        return false;
+    };
+    SourceSnapshot::read(path).is_ok_and(|snapshot| snapshot.hash == entry.source_hash)
+        && entry.output == output
+        && (entry.output == STDOUT_OUTPUT || Path::new(&entry.output).exists())
+}
+
+fn read_generate_target(client: &Client, file_path: &str, name: Option<&str>) -> Result<SourceSnapshot, DoxcerError> {
+
+    /// Reads a notebook given to `generate`: an `https://` URL, an archive
+    /// member (`export.zip!path`) or a local file.
+
+    let snapshot = if file_path.starts_with("https://") {
+        download_source(client, file_path, name)
+    } else if let Some((archive, Some(member))) = split_archive_input(file_path) {
+        read_archive_input(&archive, &member)
+    } else {
+        SourceSnapshot::read(Path::new(file_path))
+    };
+    snapshot
+}
+
+fn fork_run(run: &RunContext) -> RunContext {
+
+    /// Copies the run context for one notebook of a `generate` run, so its
+    /// model routing and setup changes stay its own; the caches, the tracer
+    /// and the workspace are shared. Its cancel token also runs out after
+    /// `--file-timeout`.
+
+    RunContext {
+        client: run.client.clone(),
+        api_key: run.api_key.clone(),
+        setup: run.setup.clone(),
+        workspace: run.workspace.clone(),
+        cache: run.cache.clone(),
+        resume: run.resume,
+        timestamps: run.timestamps,
+        tracer: run.tracer.clone(),
+        verbose: run.verbose,
+        pii_audit: run.pii_audit,
+        fabric_runs: run.fabric_runs,
+        fabric_items: Arc::clone(&run.fabric_items),
+        workspace_files: Arc::clone(&run.workspace_files),
+        at_ref: run.at_ref.clone(),
+        archive: run.archive.clone(),
+        tags: run.tags.clone(),
+        stream: run.stream,
+        budget: run.budget.clone(),
+        cancel: file_cancel(&run.cancel, &run.setup.settings),
+    }
+}
+
+fn file_cancel(run_cancel: &CancelToken, settings: &Settings) -> CancelToken {
+
+    /// The cancel token for one notebook: the run's token, which Ctrl-C
+    /// cancels, with a deadline `--file-timeout` from now when it is set.
+
+    match settings.file_timeout.value {
+        Some(secs) => run_cancel.with_deadline(Instant::now() + Duration::from_secs(secs)),
+        None => run_cancel.clone(),
+    }
+}
+
+fn generate_in_order<F>(run: &RunContext, jobs: &[(DocMode, GenerationSetup)], targets: &[String], request: &GenerateRequest, concurrency: usize, mut done: F)
+where
+    F: FnMut(&str, TargetResult),
+{
+
+    /// Generates the documents of every target and hands the results to `done`
+    /// in input order.
+    ///
+    /// # Description
+    /// With a `concurrency` of 1 the targets are generated one after the
+    /// other. Above that, as many worker threads take the next target each
+    /// (so at most that many API requests are in flight), and finished results
+    /// wait until every earlier target is done, so stdout, the report and the
+    /// `ok / failed` lines come out the same as in a sequential run.
+    ///
+    /// After Ctrl-C no further target is started; the results handed over
+    /// stop at the first target that did not finish. With `--verbose`, a batch
+    /// prints a `started` line per target with the number still queued.
+
+    let started = |index: usize, file_path: &str| {
+        if request.verbose && targets.len() > 1 {
+            errln!("{} {file_path} (queue {})", Style::stderr().paint("started    ", Color::Dim), targets.len().saturating_sub(index + 1));
+        }
+    };
+    if concurrency <= 1 || targets.len() <= 1 {
+        for (index, file_path) in targets.iter().enumerate().take_while(|_| !run.cancel.is_cancelled()) {
+            started(index, file_path);
+            done(file_path, generate_target(&mut fork_run(run), jobs, file_path, request));
+        }
+        return;
+    }
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..concurrency.min(targets.len()) {
+            let sender = sender.clone();
+            let next = &next;
+            let started = &started;
+            scope.spawn(move || {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(file_path) = targets.get(index).filter(|_| !run.cancel.is_cancelled()) else {
+                        break;
+                    };
+                    started(index, file_path);
+                    let result = generate_target(&mut fork_run(run), jobs, file_path, request);
+                    if sender.send((index, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+        let mut finished = BTreeMap::new();
+        let mut flushed = 0;
+        for (index, result) in receiver {
+            finished.insert(index, result);
+            while let Some(result) = finished.remove(&flushed) {
+                done(&targets[flushed], result);
+                flushed += 1;
+            }
+        }
+    });
+}
+
+fn generate_target(run: &mut RunContext, jobs: &[(DocMode, GenerationSetup)], file_path: &String, request: &GenerateRequest) -> TargetResult {
+
+    /// Generates the documents of one notebook, one per `--mode`, stopping at
+    /// the first mode that fails.
+    ///
+    /// # Description
+    /// Documents that go to a file (`--output`) are written here; everything
+    /// meant for stdout, the stderr notes and the report cases are returned,
+    /// so a concurrent batch can print them in input order. With `--inject`,
+    /// see [`inject_target`] instead.
+
+    if request.inject {
+        return inject_target(run, jobs, file_path, request);
+    }
+    let mut result = TargetResult::default();
+    let started = Instant::now();
+    let snapshot = match read_generate_target(&run.client, file_path, request.name.as_deref()) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            result.cases.push(TestCase {
+                suite: suite_for(Path::new(file_path), &request.inputs),
+                name: file_path.clone(),
+                duration: started.elapsed(),
+                outcome: CaseOutcome::Failed { category: e.kind().to_string(), message: e.to_string() },
+            });
+            result.failure = Some(e);
+            return result;
+        }
+    };
+    result.source_hash = Some(snapshot.hash.clone());
+    let notebook_path = snapshot.path.clone();
+    let excerpt = match request.selector.as_ref().map(|selector| select_excerpt(&notebook_path, &snapshot.text, selector, request.context_cells)).transpose() {
+        Ok(excerpt) => excerpt,
+        Err(e) => {
+            result.cases.push(TestCase {
+                suite: suite_for(&notebook_path, &request.inputs),
+                name: file_path.clone(),
+                duration: started.elapsed(),
+                outcome: CaseOutcome::Failed { category: e.kind().to_string(), message: e.to_string() },
+            });
+            result.failure = Some(e);
+            return result;
+        }
+    };
+    let notebook_content = excerpt.as_ref().map_or(&snapshot.text, |e| &e.text);
+    let child_notebooks = child_notebooks_for(&notebook_path, notebook_content, &request.workspace, Path::new(DEFAULT_DOCS_DIR));
+    let link_base = match &snapshot.url {
+        Some(_) => PathBuf::from("."),
+        None => notebook_path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new(".")).to_path_buf(),
+    };
+    let run_history = run.fabric_runs.then(|| fabric_run_history(run, &notebook_path, &snapshot.text));
+    let suite = suite_for(&notebook_path, &request.inputs);
+    let kind = request.kind.unwrap_or_else(|| detect_kind(&notebook_path, &snapshot.text));
+    let template = template_name_for(request.template_name.as_deref(), &request.template_routes, &notebook_path, kind);
+    for (mode, setup) in jobs.iter().filter(|(mode, setup)| setup.kind == kind && (*mode == DocMode::Summary || setup.template_name == template)) {
+        let mode = *mode;
+        run.setup = setup.clone();
+        run.setup.child_notebooks = child_notebooks.clone();
+        let started = Instant::now();
+        let case_name = match mode {
+            DocMode::Full => notebook_path.display().to_string(),
+            DocMode::Summary => format!("{} ({})", notebook_path.display(), mode.name()),
+        };
+        let case = |outcome: CaseOutcome| TestCase { suite: suite.clone(), name: case_name.clone(), duration: started.elapsed(), outcome };
+        route_model(run, &setup.settings.model, file_path, notebook_content, request.verbose);
+        run.setup.run_history = run_history.clone();
+        let model = run.setup.settings.model.value.clone();
+        let key = match mode {
+            DocMode::Full => snapshot.hash.clone(),
+            DocMode::Summary => summary_key(&snapshot.hash),
+        };
+        // An excerpt written below a directory gets its own file
+        // (`load_customers.cells_3-5.md`), so it never replaces the document
+        // of the whole notebook.
+        let doc_path = request.output.as_ref().map(|target| match (target, &excerpt) {
+            (DocTarget::Dir(dir), Some(excerpt)) => {
+                let path = dir.join(excerpt_file_name(&notebook_path, excerpt));
+                match mode {
+                    DocMode::Full => path,
+                    DocMode::Summary => path.with_extension("summary.md"),
+                }
+            }
+            _ => target.path(&notebook_path, mode),
+        });
+        if let Some(Err(e)) = doc_path.as_deref().map(|path| check_writable(path, request.policy)) {
+            result.cases.push(case(CaseOutcome::Failed { category: e.kind().to_string(), message: e.to_string() }));
+            result.failure = Some(e);
+            break;
+        }
+        let file_span = run.tracer.span("doxcer.file");
+        file_span.set("doxcer.notebook", file_path);
+        file_span.set("doxcer.mode", mode.name());
+        let documented = match mode {
+            DocMode::Full => request_documentation(run, &notebook_path, &snapshot, notebook_content),
+            DocMode::Summary => request_summary(run, &notebook_path, &snapshot, notebook_content),
+        };
+        let documented = documented.and_then(|generated| match generated {
+            Some(generated) => checked_links(run, generated, &link_base, file_path).map(Some),
+            None => Ok(None),
+        });
+        match &documented {
+            Ok(_) => file_span.set("doxcer.status", "ok"),
+            Err(e) => file_span.fail(e),
+        }
+        drop(file_span);
+        match documented {
+            Ok(Some(Generated { text, model: used, cached, coverage, usage })) => {
+                if used != model {
+                    result.notes.push(format!("{file_path}: generated with fallback model {used}"));
+                }
+                if !snapshot.is_current().unwrap_or(false) {
+                    result.notes.push(format!(
+                        "{} {file_path} changed while generating; the output documents the version that was read",
+                        Style::stderr().warning_prefix()
+                    ));
+                }
+                let provenance = Provenance {
+                    source: file_path.clone(),
+                    source_hash: snapshot.hash.clone(),
+                    model: Some(used.clone()),
+                    generated_at: run.timestamps.map(now_in),
+                };
+                let mut markdown = stamp_provenance(&text, &provenance);
+                if mode == DocMode::Summary {
+                    markdown = upsert_front_matter(&markdown, &[(MODE_KEY, mode.name().to_string())]);
+                }
+                if let Some(coverage) = coverage {
+                    markdown = upsert_front_matter(&markdown, &[(COVERAGE_KEY, coverage)]);
+                }
+                if let Some(history) = &run.setup.run_history {
+                    markdown = upsert_front_matter(&markdown, &[(RUN_STATS_KEY, history.summary())]);
+                }
+                if let Some(excerpt) = &excerpt {
+                    markdown = upsert_front_matter(&markdown, &[(EXCERPT_KEY, excerpt.label.clone())]);
+                    if doc_path.is_none() {
+                        result.notes.push(format!(
+                            "documented {} of {file_path}; suggested file name {}",
+                            excerpt.label,
+                            excerpt_file_name(&notebook_path, excerpt)
+                        ));
+                    }
+                }
+                match &doc_path {
+                    Some(path) => match write_document(path, &markdown, request.policy) {
+                        Ok(backup) => {
+                            result.notes.push(format!("wrote {}", path.display()));
+                            if let Some(backup) = backup {
+                                result.notes.push(format!("kept the previous version as {}", backup.display()));
+                            }
+                        }
+                        Err(e) => {
+                            log_run(run, RunRecord {
+                                error: Some(e.to_string()),
+                                ..RunRecord::new(Path::new(file_path), &snapshot.hash, &used, WRITE_FAILED_STATUS)
+                            });
+                            result.cases.push(case(CaseOutcome::Failed { category: WRITE_FAILED_STATUS.to_string(), message: e.to_string() }));
+                            result.failure = Some(e);
+                            break;
+                        }
+                    },
+                    None => result.stdout.push(match display_width(request.wrap) {
+                        Some(width) => soft_wrap(&markdown, width),
+                        None => format!("{markdown}\n"),
+                    }),
+                }
+                if cached {
+                    result.notes.push(format!("{file_path}: answered from the response cache"));
+                }
+                log_run(run, RunRecord {
+                    cache_hit: Some(cached),
+                    idempotency_key: request_key(run, &key, &used).filter(|_| !cached),
+                    input_tokens: usage.and_then(|u| u.input_tokens),
+                    output_tokens: usage.and_then(|u| u.output_tokens),
+                    ..RunRecord::new(Path::new(file_path), &snapshot.hash, &used, "ok")
+                });
+                result.cases.push(case(if cached { CaseOutcome::Skipped("cache hit".to_string()) } else { CaseOutcome::Passed }));
+                result.usage.push(UsageEntry { notebook: case_name.clone(), model: used, cached, usage });
+                result.documents.push(markdown);
+            }
+            Ok(None) => {
+                log_run(run, RunRecord::new(Path::new(file_path), &snapshot.hash, &model, "empty"));
+                let e = DoxcerError::Provider("No output received from API.".to_string());
+                result.cases.push(case(CaseOutcome::Failed { category: e.kind().to_string(), message: e.to_string() }));
+                result.failure = Some(e);
+                break;
+            }
+            Err(e) => {
+                let status = match e {
+                    DoxcerError::TooLarge(_) => "too-large",
+                    DoxcerError::OverBudget(_) => OVER_BUDGET_STATUS,
+                    _ => "failed",
+                };
+                log_run(run, RunRecord {
+                    error: Some(e.to_string()),
+                    idempotency_key: request_key(run, &key, &model),
+                    ..RunRecord::new(Path::new(file_path), &snapshot.hash, &model, status)
+                });
+                result.cases.push(case(CaseOutcome::Failed { category: e.kind().to_string(), message: e.to_string() }));
+                result.failure = Some(e);
+                break;
+            }
+        }
+    }
+    result
+}
+
+fn run_verify(args: VerifyArgs) -> Result<(), DoxcerError> {
+
+    /// Checks whether the committed documentation of each notebook is up to date.
+    ///
+    /// # Description
+    /// For every notebook found below the given paths, compares the notebook's
+    /// content hash with the provenance stored in `docs/<stem>.md` (or, if the doc
+    /// has no provenance, with the manifest) and prints an `ok / stale / missing`
+    /// table. No API calls are made unless `--fix` is given, in which case only the
+    /// stale and missing documents are regenerated.
+    ///
+    /// With `--workspace-summary`, a `docs/overview.md` describing the whole
+    /// workspace is generated after the batch, unless more than
+    /// `--summary-max-failed` percent (default 20) of the documents failed.
+    ///
+    /// Failed documents are recorded in `.doxcer/failed.json` (content hash, error
+    /// kind, attempts) and cleared once they succeed. `--retry-failed` processes
+    /// exactly that set. A notebook that failed `--max-attempts-per-file` times
+    /// (default 3) for the same content is quarantined: skipped with a warning
+    /// until `--include-quarantined` is given or the notebook changes.
+    ///
+    /// Child notebooks started with `%run` or `mssparkutils.notebook.run` are
+    /// resolved against the workspace and passed to the template as
+    /// `{{child_notebooks}}`. `--follow-runs` adds children that are not in the
+    /// batch yet.
+    ///
+    /// `--changed` limits the check to notebooks that differ from `HEAD` (paths
+    /// default to `.`); with `--staged` only staged notebooks count, and both the
+    /// notebook and its document are read from the git index (`git show :<path>`),
+    /// so the pre-commit hook checks exactly what is committed.
+    ///
+    /// A notebook whose prompt is estimated over `--max-total-tokens` (default:
+    /// the model's context window minus `max_output_tokens`) is skipped as
+    /// `too-large`, with its largest cells listed; it is not quarantined.
+    /// `--force-oversize` documents the first part that fits instead, marked
+    /// with `doxcer_coverage` in the front-matter.
+    ///
+    /// Links and images in regenerated documents are checked (see
+    /// [`checked_links`]); with `--link-policy fail` a broken one fails the document.
+    ///
+    /// `--split-output 400` (lines) or `--split-output 64kb` (bytes) writes a
+    /// regenerated document over that size as `docs/<stem>.md` with links to
+    /// `docs/<stem>/<section>.md`, one page per `##` section.
+    ///
+    /// `--edit-mode` sends the previous document and a diff of the notebook
+    /// since then, asking the model to change only the affected sections (see
+    /// [`plan_edit`]); without a previous document, stored content or with a
+    /// diff over 400 changed lines the document is regenerated in full.
+    ///
+    /// `--section <heading>` (repeatable) rewrites only that `##` section of an
+    /// up-to-date document and splices it back in place, leaving the rest of the
+    /// file untouched (see [`fix_sections`]); stale and missing documents fail
+    /// and must be regenerated in full first.
+    ///
+    /// `--format md,json,html` writes every regenerated document in each format
+    /// from the one API response (see [`write_outputs`]): HTML next to the
+    /// Markdown, JSON next to it or below `--json-out <file.json|dir>`. A format
+    /// that fails to write marks the document `failed-in-write` without
+    /// discarding the others; the run ends with the artifacts per format.
+    ///
+    /// `--post-hook <command>` (repeatable, or `post_hooks` in `doxcer.toml`)
+    /// runs after every regenerated document with its path as the last
+    /// argument and `DOXCER_HOOK_*` variables for the output, source, model,
+    /// status and token usage; secret-looking variables are not passed on. A
+    /// hook that exits non-zero or runs past `--post-hook-timeout` (default 60
+    /// seconds) marks the document `failed-in-post`, with its stderr in the
+    /// run log.
+    ///
+    /// `--max-cost <eur>` stops sending requests once the estimated spend of
+    /// the run would exceed the cap; the remaining documents are skipped as
+    /// `over-budget` and listed at the end, without being quarantined.
+    ///
+    /// `--report junit=<path>` writes a JUnit XML report with one test case
+    /// per notebook, grouped per input path; unchanged, cached and quarantined
+    /// notebooks are skipped. The file is rewritten after every notebook, so
+    /// an interrupted run still reports what completed.
+    ///
+    /// `--at-ref v1.2` checks and documents the notebooks as they are at that
+    /// tag, branch or commit: discovery lists the ref's tree (`git ls-tree`), so
+    /// notebooks deleted since are included, notebooks are read with `git show`
+    /// and the documents are written to the working tree with the ref and commit
+    /// in the front-matter and the manifest.
+    ///
+    /// A `.zip` input (optionally `export.zip!folder`) is read without unpacking
+    /// it: discovery runs over the member names, notebooks are read from the
+    /// archive and documented below `--docs-dir` by their member paths.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer verify [--fix] [--docs-dir <dir>] [--manifest <file>] [--config <file>] [--exclude <pattern>]... <paths>...
+    /// doxcer verify --fix [--workdir <dir>] [--keep-workdir] [--resume] [--no-cache] [--cache-ttl <age>] [--timestamp-tz utc|local] [--no-timestamps] [--disable-stage <name>]... [--pipeline-dump <dir>] [--secret-patterns <file>] [--max-total-tokens <n>] [--oversize-policy fail|warn|truncate|chunk] [--force-oversize] [--chunk-strategy cells|lines|tokens] [--granularity document|cell] [--link-policy warn|strip|fail] [--pii off|warn|redact|fail] [--pii-audit] [--fabric-runs] [--max-cost <eur>] <paths>...
+    /// doxcer verify --fix --workspace-summary [--summary-max-failed <percent>] <paths>...
+    /// doxcer verify --fix --split-output <lines|bytes> <paths>...
+    /// doxcer verify --fix --edit-mode <paths>...
+    /// doxcer verify --fix --section <heading>... <paths>...
+    /// doxcer verify --fix --format md,json,html [--json-out <file.json|dir>] <paths>...
+    /// doxcer verify --fix --post-hook <command>... [--post-hook-timeout <secs>] <paths>...
+    /// doxcer verify --retry-failed [--max-attempts-per-file <n>] [--include-quarantined] [<paths>...]
+    /// doxcer verify --fix --follow-runs <paths>...
+    /// doxcer verify --changed [--staged] [<paths>...]
+    /// doxcer verify [--fix] --at-ref <git-ref> <paths>...
+    /// doxcer verify [--fix] <export.zip[!folder]>...
+    /// doxcer verify --list [--verbose] <paths>...
+    /// ```
+    ///
+    /// # Exit Codes
+    /// * `0` – every document is up to date (or was fixed).
+    /// * `1` – at least one document is stale, missing or quarantined, or some fixes failed.
+    /// * See `doxcer --explain-exit-codes` for the other codes.
+
+    let VerifyArgs {
+        paths: mut inputs, mut fix, list, verbose, docs_dir, manifest: manifest_path, config: config_path, exclude, split_output,
+        edit_mode, sections, format, json_out, post_hooks, post_hook_timeout, workspace_summary, summary_max_failed, retry_failed,
+        max_attempts, include_quarantined, no_dependency_order, follow, changed, staged, at_ref, copy, run: run_options,
+    } = args;
+    if copy {
+        return Err(DoxcerError::Usage("--copy is only supported when generating a single notebook, not for batch runs".to_string()));
+    }
+    fix |= retry_failed;
+    let summary_max_failed = summary_max_failed.unwrap_or(SUMMARY_MAX_FAILED_PERCENT);
+    let max_attempts = max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS);
+    let dependency_ordering = !no_dependency_order;
+    let formats = format.unwrap_or_else(|| vec![OutputFormat::Markdown]);
+    let options = DiscoveryOptions { excludes: exclude, ..DiscoveryOptions::default() };
+
+    let failed_path = Path::new(DEFAULT_FAILED_PATH);
+    let mut failures = FailureLog::load(failed_path)?;
+    if retry_failed && inputs.is_empty() {
+        inputs = failures.paths();
+        if inputs.is_empty() {
+            outln!("No failed documents to retry.");
+            return Ok(());
+        }
+    }
+    if inputs.is_empty() && changed {
+        inputs.push(PathBuf::from("."));
+    }
+    if inputs.is_empty() {
+        return Err(usage_error("verify", "no notebook given"));
+    }
+    if staged && fix {
+        return Err(DoxcerError::Usage("--staged checks the staged content and cannot --fix; run --fix without --staged and stage the result".to_string()));
+    }
+    if at_ref.is_some() && (changed || staged) {
+        return Err(DoxcerError::Usage("--at-ref reads a commit and cannot be combined with --changed or --staged".to_string()));
+    }
+    if workspace_summary && !fix {
+        return Err(DoxcerError::Usage("--workspace-summary requires --fix".to_string()));
+    }
+    if split_output.is_some() && !fix {
+        return Err(DoxcerError::Usage("--split-output requires --fix".to_string()));
+    }
+    if edit_mode && !fix {
+        return Err(DoxcerError::Usage("--edit-mode requires --fix".to_string()));
+    }
+    if !post_hooks.is_empty() && !fix {
+        return Err(DoxcerError::Usage("--post-hook requires --fix".to_string()));
+    }
+    if !sections.is_empty() && !fix {
+        return Err(DoxcerError::Usage("--section requires --fix".to_string()));
+    }
+    if formats != [OutputFormat::Markdown] && !fix {
+        return Err(DoxcerError::Usage("--format requires --fix".to_string()));
+    }
+    if !formats.contains(&OutputFormat::Markdown) {
+        return Err(DoxcerError::Usage("--format must include md; verify checks the Markdown documents".to_string()));
+    }
+    if json_out.is_some() && !formats.contains(&OutputFormat::Json) {
+        return Err(DoxcerError::Usage("--json-out requires json in --format".to_string()));
+    }
+    let outputs = OutputTargets { formats, docs_dir: docs_dir.clone(), json_out };
+    if !sections.is_empty() && (edit_mode || split_output.is_some()) {
+        return Err(DoxcerError::Usage("--section cannot be combined with --edit-mode or --split-output".to_string()));
+    }
+
+    let archive_inputs: Vec<_> = inputs.iter().filter_map(|input| split_archive_input(&input.to_string_lossy())).collect();
+    if !archive_inputs.is_empty() && archive_inputs.len() != inputs.len() {
+        return Err(DoxcerError::Usage("a .zip input cannot be combined with other paths; name folders inside it as export.zip!folder".to_string()));
+    }
+    if !archive_inputs.is_empty() && (at_ref.is_some() || changed || staged) {
+        return Err(DoxcerError::Usage("a .zip input cannot be combined with --at-ref, --changed or --staged".to_string()));
+    }
+
+    let at_ref = at_ref.map(|reference| git::resolve_ref(&reference)).transpose()?;
+    let archive = (!archive_inputs.is_empty()).then(|| open_archive_inputs(&archive_inputs)).transpose()?;
+    let discovery = match (&at_ref, &archive) {
+        (Some(at_ref), _) => git::ref_files(&at_ref.commit, &inputs)
+            .and_then(|files| {
+                discover_listed(&inputs, &options, &files, |path| {
+                    git::ref_content(&at_ref.commit, path).ok().flatten().and_then(|bytes| String::from_utf8(bytes).ok())
+                })
+            })
+            ?,
+        (None, Some((archive, inner))) => discover_archive(archive, inner, &options)?,
+        (None, None) => discover(&inputs, &options)?,
+    };
+    let archive = archive.map(|(archive, _)| archive);
+    if list {
+        print_listing(&discovery, verbose);
+        return Ok(());
+    }
+
+    let mut notebooks = discovery.notebooks;
+    if changed {
+        retain_changed(&mut notebooks, git::changed_files(staged)?);
+        if notebooks.is_empty() {
+            outln!("No changed notebooks.");
+            return Ok(());
+        }
+    }
+    let workspace = match (fix || follow, &archive) {
+        (true, Some(_)) => notebooks.clone(),
+        (true, None) => workspace_notebooks(&options, &notebooks),
+        (false, _) => Vec::new(),
+    };
+    if follow {
+        follow_runs(&mut notebooks, &workspace);
+    }
+    check_doc_paths(&notebooks, &docs_dir)?;
+    let mut manifest = Manifest::load(&manifest_path)?;
+
+    let mut results = Vec::new();
+    let mut check_times = BTreeMap::new();
+    for notebook in &notebooks {
+        check_times.insert(notebook.clone(), Instant::now());
+        let verified = if staged {
+            verify_notebook_with(notebook, &docs_dir, &manifest, git::staged_content)
+        } else if let Some(archive) = &archive {
+            verify_notebook_with(notebook, &docs_dir, &manifest, |path| {
+                if path == notebook.as_path() {
+                    return Ok(archive_member(archive, path).map(<[u8]>::to_vec));
+                }
+                if !path.exists() {
+                    return Ok(None);
+                }
+                fs::read(path).map(Some).map_err(|e| DoxcerError::Input(format!("Failed to read {}: {e}", path.display())))
+            })
+        } else if let Some(at_ref) = &at_ref {
+            verify_notebook_with(notebook, &docs_dir, &manifest, |path| {
+                if path == notebook.as_path() {
+                    return git::ref_content(&at_ref.commit, path);
+                }
+                if !path.exists() {
+                    return Ok(None);
+                }
+                fs::read(path).map(Some).map_err(|e| DoxcerError::Input(format!("Failed to read {}: {e}", path.display())))
+            })
+        } else {
+            verify_notebook(notebook, &docs_dir, &manifest)
+        };
+        results.push(verified?);
+    }
+
+    if retry_failed {
+        results.retain(|r| failures.get(&r.notebook).is_some());
+    }
+
+    out!("{}", render_table(&results, &Style::stdout()));
+
+    let mut report = open_report(&run_options)?;
+    if !fix {
+        for result in &results {
+            let outcome = match result.status {
+                DocStatus::Ok => CaseOutcome::Passed,
+                DocStatus::Stale => CaseOutcome::Failed {
+                    category: result.status.to_string(),
+                    message: format!("{} does not match the current {}", result.doc_path.display(), result.notebook.display()),
+                },
+                DocStatus::Missing => CaseOutcome::Failed {
+                    category: result.status.to_string(),
+                    message: format!("{} does not exist", result.doc_path.display()),
+                },
+            };
+            let started = check_times.get(&result.notebook).copied().unwrap_or_else(Instant::now);
+            report_case(&mut report, suite_for(&result.notebook, &inputs), result.notebook.display().to_string(), started, outcome);
+        }
+    }
+
+    if fix {
+        let before = failures.files.len();
+        for result in results.iter().filter(|r| r.status == DocStatus::Ok) {
+            failures.record_success(&result.notebook);
+        }
+        if failures.files.len() != before
+            && let Err(e) = failures.save(failed_path)
+        {
+            return Err(e);
+        }
+    }
+
+    let mut upstream = Vec::new();
+    if fix {
+        let mut lineages: Vec<Lineage> = results
+            .iter()
+            .map(|r| read_source(at_ref.as_ref(), archive.as_ref(), &r.notebook).map(|source| extract_lineage(&source.text)).unwrap_or_default())
+            .collect();
+        if dependency_ordering {
+            let DependencyOrder { order, cycles } = dependency_order(&notebook_dependencies(&lineages));
+            for cycle in &cycles {
+                let names: Vec<String> = cycle.iter().map(|i| results[*i].notebook.display().to_string()).collect();
+                warn(format!("dependency cycle between {}; these are processed in path order", names.join(", ")));
+            }
+            results = order.iter().map(|i| results[*i].clone()).collect();
+            lineages = order.iter().map(|i| lineages[*i].clone()).collect();
+        }
+        upstream = notebook_dependencies(&lineages);
+    }
+
+    let outdated: Vec<_> = results.iter().filter(|r| r.status != DocStatus::Ok).collect();
+    let targets = if sections.is_empty() { outdated.len() } else { results.len() };
+    if matches!(outputs.json_out, Some(JsonOut::File(_))) && targets > 1 {
+        return Err(DoxcerError::Usage("--json-out names a single file but more than one document is regenerated; give a directory instead".to_string()));
+    }
+    if outdated.is_empty() && !workspace_summary && sections.is_empty() {
+        return Ok(());
+    }
+    if !fix {
+        return Err(DoxcerError::Incomplete(format!("{} of {} document(s) are stale or missing.", outdated.len(), results.len())));
+    }
+
+    load_env()?;
+    let cli = SettingsLayer {
+        secret_patterns: secret_patterns_layer(&run_options)?,
+        link_policy: run_options.link_policy,
+        pii: run_options.pii,
+        post_hooks: Some(post_hooks).filter(|hooks| !hooks.is_empty()),
+        post_hook_timeout,
+        context_files: Some(run_options.context_files.clone()).filter(|files| !files.is_empty()),
+        max_context_tokens: run_options.max_context_tokens,
+        max_total_tokens: run_options.max_total_tokens,
+        oversize_policy: resolve_oversize_policy(&run_options),
+        chunk_strategy: run_options.chunk_strategy,
+        granularity: run_options.granularity,
+        single_input: run_options.single_input.then_some(true),
+        retry_max_attempts: run_options.retry_max_attempts,
+        retry_base_delay_ms: run_options.retry_base_delay_ms,
+        file_timeout: run_options.file_timeout,
+        max_cost_eur: run_options.max_cost,
+        include_outputs: include_outputs_layer(&run_options),
+        redact_literals: run_options.redact_literals.then_some(true),
+        vars: vars_layer(&run_options),
+        lang: run_options.lang.clone(),
+        ..SettingsLayer::default()
+    };
+    let mut setup = load_setup(&cli, config_path.as_deref())?;
+    setup.pipeline = resolve_pipeline(&run_options)?;
+    check_fabric_workspace(&run_options, &setup)?;
+    if verbose {
+        print_verbose_config(config_path.as_deref(), &setup)?;
+    }
+    let budget = open_budget(&setup.settings)?;
+    let mut run = RunContext {
+        client: http_client()?,
+        api_key: provider_api_key(setup.provider.as_ref())?,
+        setup,
+        workspace: open_workspace(&run_options)?,
+        cache: open_cache(&run_options),
+        resume: run_options.resume,
+        timestamps: resolve_timestamps(&run_options)?,
+        tracer: open_tracer(),
+        verbose,
+        pii_audit: run_options.pii_audit,
+        fabric_runs: run_options.fabric_runs,
+        fabric_items: Arc::new(OnceLock::new()),
+        workspace_files: Arc::new(OnceLock::new()),
+        at_ref,
+        archive,
+        tags: resolve_tags(&run_options)?,
+        stream: false,
+        budget,
+        cancel: interrupt_token(),
+    };
+    let mut base_model = run.setup.settings.model.clone();
+    /// Notebooks a `[[templates]]` entry routes elsewhere use that setup instead.
+    let template_routes = load_template_routes(config_path.as_deref())?;
+    let default_setup = run.setup.clone();
+    let mut named_setups: BTreeMap<String, GenerationSetup> = BTreeMap::new();
+
+    let mut attempted = 0;
+    let mut quarantined = 0;
+    let mut too_large = 0usize;
+    let mut over_budget = Vec::new();
+    let mut models: BTreeMap<String, usize> = BTreeMap::new();
+    let mut usage_entries = Vec::new();
+    let mut written: BTreeMap<OutputFormat, usize> = BTreeMap::new();
+    let mut errors = Vec::new();
+    let run_span = run.tracer.span("doxcer.run");
+    run_span.set("doxcer.command", "verify");
+    let interrupt = run.cancel.clone();
+    for (index, result) in results.iter().enumerate() {
+        if interrupt.is_cancelled() {
+            break;
+        }
+        let started = Instant::now();
+        let suite = suite_for(&result.notebook, &inputs);
+        let name = result.notebook.display().to_string();
+        if result.status == DocStatus::Ok && sections.is_empty() {
+            report_case(&mut report, suite, name, started, CaseOutcome::Skipped("unchanged".to_string()));
+            continue;
+        }
+        if !include_quarantined && failures.is_quarantined(&result.notebook, &result.source_hash) {
+            quarantined += 1;
+            if let Some(f) = failures.get(&result.notebook) {
+                warn(format!(
+                    "skipping {}: quarantined after {} failed attempts ({}: {}); pass --include-quarantined to retry",
+                    result.notebook.display(), f.attempts, f.category, f.error
+                ));
+            }
+            let reason = failures.get(&result.notebook).map_or_else(|| "quarantined".to_string(), |f| format!("quarantined after {} failed attempts", f.attempts));
+            report_case(&mut report, suite, name, started, CaseOutcome::Skipped(reason));
+            continue;
+        }
+        attempted += 1;
+        if !sections.is_empty() && result.status != DocStatus::Ok {
+            let message = format!("{} is {}; regenerate it in full before rewriting sections", result.doc_path.display(), result.status);
+            errln!("{} {message}", Style::stderr().paint("failed     ", Color::Red));
+            report_case(&mut report, suite, name, started, CaseOutcome::Failed { category: result.status.to_string(), message: message.clone() });
+            errors.push(DoxcerError::Input(message));
+            continue;
+        }
+        if !template_routes.is_empty() {
+            let template = template_name_for(None, &template_routes, &result.notebook, SourceKind::Notebook);
+            run.setup = match template == default_setup.template_name {
+                true => default_setup.clone(),
+                false => {
+                    if !named_setups.contains_key(&template) {
+                        let mut setup = load_named_setup(SourceKind::Notebook, &template, &cli, config_path.as_deref())?;
+                        setup.pipeline = resolve_pipeline(&run_options)?;
+                        named_setups.insert(template.clone(), setup);
+                    }
+                    named_setups[&template].clone()
+                }
+            };
+            base_model = run.setup.settings.model.clone();
+        }
+        run.setup.upstream_summaries = upstream_summaries(&results, &upstream[index]);
+        run.cancel = file_cancel(&interrupt, &run.setup.settings);
+        let source = read_source(run.at_ref.as_ref(), run.archive.as_ref(), &result.notebook).ok().map(|source| source.text);
+        run.setup.child_notebooks = source
+            .as_deref()
+            .map(|source| child_notebooks_for(&result.notebook, source, &workspace, &docs_dir))
+            .unwrap_or_default();
+        route_model(&mut run, &base_model, &result.notebook.display().to_string(), source.as_deref().unwrap_or_default(), verbose);
+        run.setup.run_history = run.fabric_runs.then(|| fabric_run_history(&run, &result.notebook, source.as_deref().unwrap_or_default()));
+        let model = run.setup.settings.model.value.clone();
+        let file_span = run.tracer.span("doxcer.file");
+        file_span.set("doxcer.notebook", result.notebook.as_path());
+        let prior_doc = edit_mode.then(|| fs::read_to_string(&result.doc_path).ok()).flatten();
+        let prior_snapshot = manifest.get(&result.notebook).and_then(|e| e.snapshot.clone());
+        run.setup.edit_instruction = None;
+        if edit_mode {
+            let plan = plan_edit_mode(&run.setup, &result.notebook, prior_doc.as_deref(), manifest.get(&result.notebook), source.as_deref().unwrap_or_default());
+            match plan {
+                EditPlan::Edit { prior_doc, diff } => {
+                    errln!("edit mode for {}: {} changed notebook line(s)", result.notebook.display(), changed_lines(&diff));
+                    run.setup.edit_instruction = Some(edit_instruction(&prior_doc, &diff));
+                }
+                EditPlan::Full(reason) => errln!("full regeneration of {}: {reason}", result.notebook.display()),
+            }
+        }
+        let outcome = if sections.is_empty() {
+            fix_document(&run, &result.notebook, &result.doc_path, &outputs, split_output)
+        } else {
+            fix_sections(&mut run, &result.notebook, &result.doc_path, &outputs, &sections)
+        };
+        match &outcome {
+            Ok(FixOutcome::Fixed { .. }) => {
+                file_span.set("doxcer.status", "ok");
+                failures.record_success(&result.notebook);
+            }
+            Ok(FixOutcome::StaleInput { .. }) => file_span.set("doxcer.status", "stale-input"),
+            Err(DoxcerError::TooLarge(_)) => file_span.set("doxcer.status", "too-large"),
+            Err(DoxcerError::OverBudget(_)) => file_span.set("doxcer.status", OVER_BUDGET_STATUS),
+            Err(e) => {
+                file_span.fail(e);
+                if failures.record_failure(&result.notebook, &result.source_hash, e.kind(), &e.to_string(), max_attempts) {
+                    warn(format!(
+                        "{} failed {max_attempts} times and is now quarantined; it is skipped until it changes or --include-quarantined is passed",
+                        result.notebook.display()
+                    ));
+                }
+            }
+        }
+        match outcome {
+            Ok(FixOutcome::Fixed { source_hash, model: used, cached, usage, artifacts }) => {
+                for artifact in artifacts.iter().filter(|a| a.result.is_ok()) {
+                    *written.entry(artifact.format).or_default() += 1;
+                }
+                let write_failure = failed_writes(&artifacts);
+                let hooked = run_post_hooks(&run, &HookContext {
+                    output: result.doc_path.clone(),
+                    source: result.notebook.display().to_string(),
+                    source_hash: source_hash.clone(),
+                    model: used.clone(),
+                    status: "ok".to_string(),
+                    cached,
+                    input_tokens: usage.and_then(|u| u.input_tokens),
+                    output_tokens: usage.and_then(|u| u.output_tokens),
+                });
+                log_run(&run, RunRecord {
+                    cache_hit: Some(cached),
+                    idempotency_key: request_key(&run, &source_hash, &used).filter(|_| !cached),
+                    error: hooked.as_ref().err().or(write_failure.as_ref().err()).map(ToString::to_string),
+                    input_tokens: usage.and_then(|u| u.input_tokens),
+                    output_tokens: usage.and_then(|u| u.output_tokens),
+                    ..RunRecord::new(&result.notebook, &source_hash, &used, match (&hooked, &write_failure) {
+                        (Err(_), _) => POST_HOOK_FAILED_STATUS,
+                        (Ok(()), Err(_)) => WRITE_FAILED_STATUS,
+                        (Ok(()), Ok(())) => "ok",
+                    })
+                });
+                let snapshot = Some(snapshot_path(Path::new(SNAPSHOT_DIR), &source_hash))
+                    .filter(|path| path.exists())
+                    .map(|path| path.display().to_string());
+                manifest.record(&result.notebook, ManifestEntry {
+                    source_hash,
+                    output: result.doc_path.display().to_string(),
+                    snapshot: snapshot.clone(),
+                    git_ref: run.at_ref.as_ref().map(|r| r.reference.clone()),
+                    git_commit: run.at_ref.as_ref().map(|r| r.commit.clone()),
+                    generated_at: run.timestamps.map(|_| now_rfc3339()),
+                });
+                let unused = prior_snapshot
+                    .filter(|old| Some(old) != snapshot.as_ref())
+                    .filter(|old| !manifest.entries.values().any(|e| e.snapshot.as_ref() == Some(old)));
+                if let Some(old) = unused {
+                    let _ = fs::remove_file(&old);
+                }
+                if let Some(prior) = prior_doc.filter(|_| run.setup.edit_instruction.is_some()) {
+                    let written = fs::read_to_string(&result.doc_path).unwrap_or_default();
+                    let body = |doc: &str| split_front_matter(doc).1.to_string();
+                    if let Some(diff) = unified_diff(&body(&prior), &body(&written), &result.doc_path.display().to_string()) {
+                        errln!("edit mode for {}: {} documentation line(s) changed", result.doc_path.display(), changed_lines(&diff));
+                    }
+                }
+                *models.entry(used.clone()).or_default() += 1;
+                usage_entries.push(UsageEntry { notebook: result.notebook.display().to_string(), model: used.clone(), cached, usage });
+                if let Err(e) = hooked {
+                    errln!("{} {}: {e}", Style::stderr().paint("failed-post", Color::Red), result.doc_path.display());
+                    let outcome = CaseOutcome::Failed { category: POST_HOOK_FAILED_STATUS.to_string(), message: e.to_string() };
+                    report_case(&mut report, suite, name, started, outcome);
+                    errors.push(e);
+                    continue;
+                }
+                if let Err(e) = write_failure {
+                    errln!("{} {}: {e}", Style::stderr().paint("failed-write", Color::Red), result.doc_path.display());
+                    let outcome = CaseOutcome::Failed { category: WRITE_FAILED_STATUS.to_string(), message: e.to_string() };
+                    report_case(&mut report, suite, name, started, outcome);
+                    errors.push(e);
+                    continue;
+                }
+                let outcome = if cached { CaseOutcome::Skipped("cache hit".to_string()) } else { CaseOutcome::Passed };
+                report_case(&mut report, suite, name, started, outcome);
+                let fallback = if used != model { format!(" (fallback model {used})") } else { String::new() };
+                let fallback = if cached { format!("{fallback} (cached)") } else { fallback };
+                errln!("{} {}{fallback}", Style::stderr().paint("fixed      ", Color::Green), result.doc_path.display());
+            }
+            Ok(FixOutcome::StaleInput { source_hash, model: used }) => {
+                let message = format!("{} changed while generating", result.notebook.display());
+                report_case(&mut report, suite, name, started, CaseOutcome::Failed { category: "stale-input".to_string(), message: message.clone() });
+                errors.push(DoxcerError::Input(message));
+                log_run(&run, RunRecord::new(&result.notebook, &source_hash, &used, "stale-input"));
+                errln!(
+                    "{} {}: notebook changed while generating; not recorded, run again",
+                    Style::stderr().paint("stale-input", Color::Yellow),
+                    result.notebook.display()
+                );
+            }
+            Err(e @ DoxcerError::TooLarge(_)) => {
+                too_large += 1;
+                errln!("{} {e}", Style::stderr().paint("too-large  ", Color::Yellow));
+                log_run(&run, RunRecord {
+                    error: Some(e.to_string()),
+                    ..RunRecord::new(&result.notebook, &result.source_hash, &model, "too-large")
+                });
+                report_case(&mut report, suite, name, started, CaseOutcome::Failed { category: "too-large".to_string(), message: e.to_string() });
+                errors.push(e);
+            }
+            Err(e @ DoxcerError::OverBudget(_)) => {
+                errln!("{} {}: {e}", Style::stderr().paint("over-budget", Color::Yellow), result.notebook.display());
+                log_run(&run, RunRecord {
+                    error: Some(e.to_string()),
+                    ..RunRecord::new(&result.notebook, &result.source_hash, &model, OVER_BUDGET_STATUS)
+                });
+                report_case(&mut report, suite, name, started, CaseOutcome::Failed { category: OVER_BUDGET_STATUS.to_string(), message: e.to_string() });
+                over_budget.push(result.notebook.display().to_string());
+                errors.push(e);
+            }
+            Err(e) => {
+                errln!("{} {}: {e}", Style::stderr().paint("failed     ", Color::Red), result.notebook.display());
+                log_run(&run, RunRecord {
+                    error: Some(e.to_string()),
+                    idempotency_key: request_key(&run, &result.source_hash, &model),
+                    ..RunRecord::new(&result.notebook, &result.source_hash, &model, "failed")
+                });
+                report_case(&mut report, suite, name, started, CaseOutcome::Failed { category: e.kind().to_string(), message: e.to_string() });
+                errors.push(e);
+            }
+        }
+    }
+    let failed = errors.len();
+    if !models.is_empty() {
+        let breakdown: Vec<String> = models.iter().map(|(model, count)| format!("{model} ({count})")).collect();
+        errln!("documents per model: {}", breakdown.join(", "));
+    }
+    if outputs.formats.len() > 1 {
+        let breakdown: Vec<String> = outputs.formats.iter().map(|format| format!("{format} ({})", written.get(format).unwrap_or(&0))).collect();
+        errln!("artifacts per format: {}", breakdown.join(", "));
+    }
+    if too_large > 0 {
+        warn(format!(
+            "{too_large} notebook(s) skipped as too large; split them at the cells listed above, \
+             raise --max-total-tokens or pass --force-oversize to document them partially"
+        ));
+    }
+    warn_over_budget(&run, &over_budget);
+
+    manifest.save(&manifest_path)?;
+    failures.save(failed_path)?;
+
+    let mut summary_error = None;
+    if workspace_summary && !interrupt.is_cancelled() {
+        let failed_percent = failed * 100 / results.len().max(1);
+        if failed_percent > summary_max_failed as usize {
+            warn(format!(
+                "skipping the workspace summary: {failed} of {} document(s) failed ({failed_percent}% > {summary_max_failed}%)",
+                results.len()
+            ));
+        } else {
+            match write_workspace_summary(&run, &results, &docs_dir) {
+                Ok(path) => errln!("{} {}", Style::stderr().paint("summary    ", Color::Green), path.display()),
+                Err(e) => {
+                    errln!("{} workspace summary: {e}", Style::stderr().paint("failed     ", Color::Red));
+                    summary_error = Some(e);
+                }
+            }
+        }
+    }
+
+    run_span.set("doxcer.files", attempted);
+    run_span.set("doxcer.failed", failed);
+    run_span.set("doxcer.too_large", too_large);
+    drop(run_span);
+    finish_cost_report(&run_options, &run.setup.settings, usage_entries);
+    export_traces(&run.tracer);
+    finish_workspace(run.workspace, failed == 0 && summary_error.is_none());
+    if interrupt.is_cancelled() {
+        return Err(DoxcerError::Interrupted);
+    }
+    if let Some(e) = batch_failure(&errors, attempted, "fixed") {
+        return Err(e);
+    }
+    if let Some(e) = summary_error {
+        return Err(e.with_message("the workspace summary could not be generated."));
+    }
+    if quarantined > 0 {
+        return Err(DoxcerError::Incomplete(format!("{quarantined} quarantined document(s) were skipped.")));
+    }
+    Ok(())
+}
+
+fn run_check(args: CheckArgs) -> Result<(), DoxcerError> {
+
+    /// Fails when committed documentation is out of date, for CI.
+    ///
+    /// # Description
+    /// `verify` without `--fix`: every notebook's content hash is compared with
+    /// the `doxcer_source_hash` in the front-matter of its document (or, without
+    /// provenance, with the manifest) and the `ok / stale / missing` table is
+    /// printed. Nothing is written and no API call is made, so the flags that
+    /// regenerate documents are refused.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer check [--docs-dir <dir>] [--manifest <file>] [--config <file>] [--exclude <pattern>]... [--changed [--staged]] [--at-ref <git-ref>] [--report junit=<path>] <paths>...
+    /// ```
+    ///
+    /// # Exit Codes
+    /// * `0` – every document is up to date.
+    /// * `1` – at least one document is stale or missing.
+    /// * See `doxcer --explain-exit-codes` for the other codes.
+
+    let CheckArgs { paths, docs_dir, manifest, config, exclude, changed, staged, at_ref, reports, verbose, fix, retry_failed } = args;
+    if let Some(flag) = [(fix, "--fix"), (retry_failed, "--retry-failed")].into_iter().find_map(|(given, flag)| given.then_some(flag)) {
+        return Err(DoxcerError::Usage(format!("doxcer check does not change documents; use doxcer verify {flag} to regenerate them")));
+    }
+    if paths.is_empty() && !changed {
+        return Err(usage_error("check", "no notebook given"));
+    }
+    run_verify(VerifyArgs {
+        paths,
+        docs_dir,
+        manifest,
+        config,
+        exclude,
+        changed,
+        staged,
+        at_ref,
+        verbose,
+        run: RunOptions { reports, ..RunOptions::default() },
+        ..VerifyArgs::default()
+    })
+}
+
+fn run_init(args: InitArgs) -> Result<(), DoxcerError> {
+
+    /// Sets up a project for doxcer (`doxcer init`).
+    ///
+    /// # Description
+    /// Writes `doxcer.toml` with the chosen provider and model (for Azure with
+    /// a commented `provider_endpoint`), exports the built-in templates to
+    /// `templates/` (see [`export_templates`]) and, when the provider needs an
+    /// API key, adds a fresh `ENCRYPTION_PASSWORD` to `.env` (see
+    /// [`append_env_var`]). `.env` and `.doxcer/` are added to `.gitignore`.
+    /// Existing files are kept with a warning unless `--overwrite` is given;
+    /// `.env` and `.gitignore` are only ever added to. Ends with the next steps.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer init [<dir>] [--provider openai|azure|azure-deployment|anthropic|ollama] [--model <name>] [--overwrite]
+    /// ```
+
+    let InitArgs { dir, provider, model, overwrite } = args;
+    let dir = dir.unwrap_or_else(|| PathBuf::from("."));
+    fs::create_dir_all(&dir).map_err(|e| DoxcerError::Input(format!("Failed to create {}: {e}", dir.display())))?;
+
+    let (default_model, key_name) = match provider.as_str() {
+        "azure" | "azure-deployment" => ("gpt-5-mini", Some(AZURE_API_KEY)),
+        "anthropic" => ("claude-sonnet-4-5", Some(ANTHROPIC_API_KEY)),
+        "ollama" => ("llama3", None),
+        _ => (DEFAULT_MODEL, Some(OPENAI_API_KEY)),
+    };
+    let model = model.unwrap_or_else(|| default_model.to_string());
+    let config_path = dir.join(DEFAULT_CONFIG_FILE);
+    if config_path.exists() && !overwrite {
+        warn(format!("{} already exists; kept (use --overwrite to replace it)", config_path.display()));
+    } else {
+        let mut config = format!("# doxcer settings; see the README for every key.\nprovider = \"{provider}\"\nmodel = \"{model}\"\n");
+        if provider.starts_with("azure") {
+            config.push_str("# provider_endpoint = \"https://<resource>.openai.azure.com\"\n");
+        }
+        fs::write(&config_path, config).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", config_path.display())))?;
+        outln!("wrote {}", config_path.display());
+    }
+    export_templates(&dir.join(DEFAULT_TEMPLATE_DIR.trim_start_matches("./")), overwrite)?;
+
+    let env_path = dir.join(".env");
+    if key_name.is_some() {
+        let existing = fs::read_to_string(&env_path).unwrap_or_default();
+        if existing.lines().any(|line| line.trim_start().trim_start_matches("export ").starts_with("ENCRYPTION_PASSWORD=")) {
+            warn(format!("{} already sets ENCRYPTION_PASSWORD; kept", env_path.display()));
+        } else {
+            append_env_var(&env_path, "ENCRYPTION_PASSWORD", &generate_fernet_key())?;
+            outln!("added ENCRYPTION_PASSWORD to {}", env_path.display());
+        }
+    }
+
+    let gitignore_path = dir.join(".gitignore");
+    let gitignore = fs::read_to_string(&gitignore_path).unwrap_or_default();
+    let missing: Vec<&str> = [".env", ".doxcer/"].into_iter().filter(|entry| !gitignore.lines().any(|line| line.trim() == *entry)).collect();
+    if !missing.is_empty() {
+        let separator = if gitignore.is_empty() || gitignore.ends_with('\n') { "" } else { "\n" };
+        let text = format!("{gitignore}{separator}{}\n", missing.join("\n"));
+        fs::write(&gitignore_path, text).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", gitignore_path.display())))?;
+        outln!("added {} to {}", missing.join(" and "), gitignore_path.display());
+    }
+
+    outln!("\nNext steps:");
+    if let Some(name) = key_name {
+        outln!("  doxcer encrypt --name {name} --write-env {} < api_key.txt", env_path.display());
+    }
+    outln!("  doxcer doctor");
+    outln!("  doxcer <notebook.py>");
+    Ok(())
+}
+
+fn upstream_summaries(results: &[VerifyResult], upstream: &BTreeSet<usize>) -> String {
+
+    /// Renders `{{upstream_summaries}}`: one line per upstream notebook that has a
+    /// document, taken from that document as it is now (so including the ones
+    /// regenerated earlier in this run).
+
+    let mut lines = Vec::new();
+    for result in upstream.iter().map(|i| &results[*i]) {
+        let Some(summary) = fs::read_to_string(&result.doc_path).ok().and_then(|doc| summary_line(&doc)) else {
+            continue;
+        };
+        let name = result.notebook.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        lines.push(format!("- {name}: {summary}"));
+    }
+    lines.join("\n")
+}
+
+fn workspace_notebooks(options: &DiscoveryOptions, batch: &[PathBuf]) -> Vec<PathBuf> {
+
+    /// Lists the notebooks `%run` references are resolved against: everything
+    /// discovered below the working directory (honouring `--exclude` and
+    /// `.doxcerignore`), plus the notebooks of the batch itself.
+
+    let mut workspace: Vec<PathBuf> = discover(&[PathBuf::from(".")], options)
+        .map(|d| d.notebooks.iter().map(|n| n.strip_prefix(".").unwrap_or(n).to_path_buf()).collect())
+        .unwrap_or_else(|e| {
+            warn(format!("could not scan the workspace for child notebooks: {e}"));
+            Vec::new()
+        });
+    for notebook in batch {
+        if !contains_notebook(&workspace, notebook) {
+            workspace.push(notebook.clone());
+        }
+    }
+    workspace
+}
+
+fn contains_notebook(notebooks: &[PathBuf], notebook: &Path) -> bool {
+
+    /// Checks whether a list holds a notebook, however its path is spelled.
+
+    let target = fs::canonicalize(notebook).ok();
+    notebooks.iter().any(|n| n == notebook || (target.is_some() && fs::canonicalize(n).ok() == target))
+}
+
+fn follow_runs(notebooks: &mut Vec<PathBuf>, workspace: &[PathBuf]) {
+
+    /// Adds the notebooks that batch notebooks run (`%run`,
+    /// `mssparkutils.notebook.run`) to the batch, also the children of added ones.
+    /// Only workspace notebooks are added, so excluded ones stay out.
+
+    let mut index = 0;
+    while index < notebooks.len() {
+        let parent = notebooks[index].clone();
+        index += 1;
+        let Ok(source) = fs::read_to_string(&parent) else {
+            continue;
+        };
+        for reference in extract_run_references(&source) {
+            let Some(child) = resolve_run_reference(&reference.name, &parent, workspace) else {
+                continue;
+            };
+            if !contains_notebook(notebooks, &child) {
+                errln!("following  {} (run by {})", child.display(), parent.display());
+                notebooks.push(child);
+            }
+        }
+    }
+}
+
+fn child_notebooks_for(notebook: &Path, source: &str, workspace: &[PathBuf], docs_dir: &Path) -> String {
+
+    /// Renders `{{child_notebooks}}` for a notebook.
+
+    render_child_notebooks(&child_notebooks(notebook, source, workspace, docs_dir))
+}
+
+fn batch_failure(errors: &[DoxcerError], attempted: usize, action: &str) -> Option<DoxcerError> {
+
+    /// Decides how a batch with failed documents exits.
+    ///
+    /// # Description
+    /// When every attempted document failed for the same kind of reason (all
+    /// provider errors, say), the batch exits with the code of that kind, so an
+    /// orchestrator can tell an outage from a partial failure. Otherwise the batch
+    /// exits as `incomplete` (1).
+
+    let first = errors.first()?;
+    let message = format!("{} of {attempted} document(s) could not be {action}.", errors.len());
+    if errors.len() == attempted && errors.iter().all(|e| e.same_kind(first)) {
+        return Some(first.with_message(message));
+    }
+    Some(DoxcerError::Incomplete(message))
+}
+
+
+fn render_config_dump(env_file: Option<&Path>, config_path: Option<&Path>) -> Result<String, DoxcerError> {
+
+    /// Renders the effective configuration for `config dump` and `doctor`.
+    ///
+    /// # Description
+    /// Lists the version, the `.env`, config and template files in use (with
+    /// the `[[templates]]` entries), every resolved setting with its source,
+    /// and a snapshot of the `DOXCER_*` and provider variables (`OPENAI_*`,
+    /// `AZURE_OPENAI_*`, `ANTHROPIC_*`, `OLLAMA_HOST`) in which secrets only
+    /// show their length and fingerprint. Safe to paste into a
+    /// support ticket.
+
+    let mut out = format!("doxcer {} ({})\n", version(), user_agent());
+    out.push_str(&format!(
+        ".env: {}\n",
+        env_file.map(|p| p.display().to_string()).unwrap_or_else(|| "not found".to_string())
+    ));
+    out.push_str(&format!(
+        "Config file: {}\n",
+        resolve_config_path(config_path).map(|p| p.display().to_string()).unwrap_or_else(|| "none".to_string())
+    ));
+
+    match load_setup(&SettingsLayer::default(), config_path) {
+        Ok(setup) => {
+            out.push_str(&format!("Template: {} ({})\n", setup.template.path.display(), setup.template.hash));
+            for route in load_template_routes(config_path)? {
+                out.push_str(&format!("  {} -> {}\n", route.pattern, route.template));
+            }
+            if let Some(profile) = describe_run_profile(&setup.run_profile) {
+                out.push_str(&format!("Run profile: {profile}\n"));
+            }
+            out.push_str("Settings:\n");
+            for (name, value, source) in setup.settings.describe() {
+                out.push_str(&format!("  {name} = {value}  ({source})\n"));
+            }
+            out.push_str(&format!("Profiles ({}):\n", setup.provider.name()));
+            for line in describe_profiles(&setup) {
+                out.push_str(&format!("  {line}\n"));
+            }
+        }
+        Err(e) => out.push_str(&format!("Settings: unavailable ({e})\n")),
+    }
+
+    out.push_str("Environment:\n");
+    out.push_str(&render_env_snapshot(&env_snapshot(env::vars())));
+    Ok(out)
+}
+
+fn print_verbose_config(config_path: Option<&Path>, setup: &GenerationSetup) -> Result<(), DoxcerError> {
+
+    /// Writes the configuration dump, the prompt pipeline and the secret
+    /// patterns to stderr for `--verbose` runs.
+
+    let env_file = load_env_robust::<&Path>(None).ok();
+    let dump = render_config_dump(env_file.as_deref(), config_path)?;
+    err!("{}", Style::stderr().paint(&dump, Color::Dim));
+    err!("{}", Style::stderr().paint(&setup.pipeline.describe(), Color::Dim));
+    err!("{}", Style::stderr().paint(&setup.secrets.describe(), Color::Dim));
+    Ok(())
+}
+
+fn run_config(command: ConfigCommand) -> Result<(), DoxcerError> {
+
+    /// Prints the resolved configuration (`doxcer config dump`).
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer config dump [--config <file>]
+    /// ```
+
+    let ConfigCommand::Dump(ConfigArgs { config: config_path }) = command;
+
+    let env_file = load_env_robust::<&Path>(None).ok();
+    out!("{}", render_config_dump(env_file.as_deref(), config_path.as_deref())?);
+    Ok(())
+}
+
+fn run_doctor(args: ConfigArgs) -> Result<(), DoxcerError> {
+
+    /// Checks the local setup and prints the configuration dump (`doxcer doctor`).
+    ///
+    /// # Description
+    /// Verifies that the `.env` file is found, that the provider's API key
+    /// (`OPENAI_API_KEY_ENC` with `ENCRYPTION_PASSWORD` by default) is set and
+    /// decrypts, and that the template and config load. No API request is made.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer doctor [--config <file>]
+    /// ```
+    ///
+    /// # Exit Codes
+    /// * `0` – every check passed.
+    /// * `1` – at least one check failed.
+
+    let ConfigArgs { config: config_path } = args;
+
+    let style = Style::stdout();
+    let mut probes = Vec::new();
+    let env_file = env_probes(&mut probes);
+    let (probe, provider) = setup_probe(config_path.as_deref());
+    key_probes(&mut probes, provider.as_deref().unwrap_or(&OpenAi::default()));
+    probes.push(probe);
+    for probe in &probes {
+        outln!("{}", style.check_line(probe.ok, &probe.detail));
+    }
+    let failures = probes.iter().filter(|p| !p.ok).count();
+
+    outln!();
+    out!("{}", render_config_dump(env_file.as_deref(), config_path.as_deref())?);
+
+    if failures > 0 {
+        return Err(DoxcerError::Config(format!("{failures} check(s) failed.")));
+    }
+    Ok(())
+}
+
+
+fn env_probes(probes: &mut Vec<Probe>) -> Option<PathBuf> {
+
+    /// Checks the `.env` file, for `doctor` and `selftest`.
+    ///
+    /// # Description
+    /// Loads `.env` and checks that it is private and git-ignored (problems are
+    /// warnings unless `--strict-env-security`).
+    ///
+    /// # Returns
+    /// * The `.env` path, when it was found.
+
+    let env_file = match load_env_robust::<&Path>(None) {
+        Ok(path) => {
+            probes.push(Probe { name: "env", ok: true, detail: format!(".env loaded from {}", path.display()) });
+            Some(path)
+        }
+        Err(e) => {
+            probes.push(Probe { name: "env", ok: false, detail: format!(".env not found: {e}") });
+            None
+        }
+    };
+    if let Some(path) = &env_file {
+        let strict = STRICT_ENV_SECURITY.load(Ordering::Relaxed);
+        let problems = env_safety_problems(path);
+        if problems.is_empty() {
+            probes.push(Probe { name: "env-security", ok: true, detail: ".env is private and git-ignored".to_string() });
+        }
+        for problem in problems {
+            if strict {
+                probes.push(Probe { name: "env-security", ok: false, detail: problem });
+            } else {
+                warn(problem);
+            }
+        }
+    }
+    env_file
+}
+
+fn key_probes(probes: &mut Vec<Probe>, provider: &dyn LlmProvider) -> Option<String> {
+
+    /// Checks the provider's API key, for `doctor` and `selftest`.
+    ///
+    /// # Description
+    /// For OpenAI: that `ENCRYPTION_PASSWORD` (or the key named by
+    /// `OPENAI_API_KEY_ENC_KEY`) and `OPENAI_API_KEY_ENC` are set and that the
+    /// key decrypts. Other providers' keys only need to load (see
+    /// [`provider_api_key`]). Every other `*_ENC` variable is listed with the key
+    /// name it decrypts with (see [`secret_key_name`]).
+    ///
+    /// # Returns
+    /// * The decrypted API key, when it was found.
+
+    let name = provider.api_key_name();
+    let enc_name = name.map(|name| format!("{name}_ENC"));
+    let mut others: Vec<(String, String)> = env::vars()
+        .filter(|(other, _)| other.ends_with("_ENC") && Some(other) != enc_name.as_ref())
+        .collect();
+    others.sort();
+    let mut api_key = None;
+    let Some(name) = name else {
+        probes.push(Probe { name: "api-key", ok: true, detail: format!("{} needs no API key", provider.name()) });
+        others_probes(probes, others);
+        return Some(String::new());
+    };
+    if name != OPENAI_API_KEY {
+        match provider_api_key(provider) {
+            Ok(key) => {
+                probes.push(Probe { name: "api-key", ok: true, detail: format!("{name} loads") });
+                api_key = Some(key);
+            }
+            Err(e) => probes.push(Probe { name: "api-key", ok: false, detail: e.to_string() }),
+        }
+        others_probes(probes, others);
+        return api_key;
+    }
+
+    let key_name = secret_key_name(name);
+    let password = env::var(&key_name).ok();
+    let encrypted = env::var("OPENAI_API_KEY_ENC").ok();
+    probes.push(Probe { name: "encryption-password", ok: password.is_some(), detail: format!("{key_name} is set") });
+    probes.push(Probe { name: "api-key", ok: encrypted.is_some(), detail: "OPENAI_API_KEY_ENC is set".to_string() });
+    if let (Some(password), Some(encrypted)) = (&password, &encrypted) {
+        match decrypt_fernet(encrypted, password) {
+            Ok(key) => {
+                probes.push(Probe { name: "fernet-key", ok: true, detail: format!("API key decrypts with {key_name}") });
+                api_key = Some(key);
+            }
+            Err(e) => probes.push(Probe { name: "fernet-key", ok: false, detail: format!("API key does not decrypt with {key_name}: {e}") }),
+        }
+    }
+    others_probes(probes, others);
+    api_key
+}
+
+fn others_probes(probes: &mut Vec<Probe>, others: Vec<(String, String)>) {
+
+    /// Checks that every other `*_ENC` variable decrypts with its key.
+
+    for (name, encrypted) in others {
+        let key_name = secret_key_name(name.strip_suffix("_ENC").unwrap_or(&name));
+        let probe = match env::var(&key_name) {
+            Err(_) => Probe { name: "secret", ok: false, detail: format!("{name}: key {key_name} is not set") },
+            Ok(key) => match decrypt_fernet(&encrypted, &key) {
+                Ok(_) => Probe { name: "secret", ok: true, detail: format!("{name} decrypts with {key_name}") },
+                Err(e) => Probe { name: "secret", ok: false, detail: format!("{name} does not decrypt with {key_name}: {e}") },
+            },
+        };
+        probes.push(probe);
+    }
+}
+
+fn setup_probe(config_path: Option<&Path>) -> (Probe, Option<Arc<dyn LlmProvider>>) {
+
+    /// Checks that the template and config load and the settings resolve, and
+    /// returns the configured provider when they do.
+
+    match load_setup(&SettingsLayer::default(), config_path) {
+        Ok(setup) => (Probe { name: "config", ok: true, detail: format!("template {} and config load", setup.template.path.display()) }, Some(setup.provider)),
+        Err(e) => (Probe { name: "config", ok: false, detail: format!("template or config does not load: {e}") }, None),
+    }
+}
+
+fn template_probe() -> Probe {
+
+    /// Runs the `template check` checker over the prompt template and its partials.
+
+    let path = match template_path() {
+        Ok(Some(path)) => path,
+        Ok(None) => return Probe { name: "template", ok: true, detail: "no template file found; using the built-in prompt.md".to_string() },
+        Err(e) => return Probe { name: "template", ok: false, detail: e.to_string() },
+    };
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut checker = TemplateChecker::default();
+    if let Err(e) = checker.check_file(&path, dir) {
+        return Probe { name: "template", ok: false, detail: e.to_string() };
+    }
+    let errors = checker.count(Severity::Error);
+    let detail = match checker.problems.iter().find(|p| p.severity == Severity::Error) {
+        Some(first) => format!("{errors} template error(s), first: {first}"),
+        None => format!("{} parses ({} file(s))", path.display(), checker.files),
+    };
+    Probe { name: "template", ok: errors == 0, detail }
+}
+
+fn cache_probe() -> Probe {
+
+    /// Checks that the response cache directory can be written to.
+
+    let dir = default_cache_dir();
+    let file = dir.join(SELFTEST_FILE);
+    let written = fs::create_dir_all(&dir).and_then(|_| fs::write(&file, b"ok")).and_then(|_| fs::remove_file(&file));
+    match written {
+        Ok(()) => Probe { name: "cache", ok: true, detail: format!("{} is writable", dir.display()) },
+        Err(e) => Probe { name: "cache", ok: false, detail: format!("{} is not writable: {e}", dir.display()) },
+    }
+}
+
+fn network_probe(provider: &dyn LlmProvider, api_key: Option<&str>) -> Probe {
+
+    /// Checks that the provider answers an authenticated request that costs no
+    /// tokens (listing the models), within a few seconds.
+
+    let Some(api_key) = api_key else {
+        return Probe { name: "network", ok: false, detail: "skipped: no decrypted API key".to_string() };
+    };
+    let client = match http_client() {
+        Ok(client) => client,
+        Err(e) => return Probe { name: "network", ok: false, detail: e.to_string() },
+    };
+    let url = provider.models_url();
+    let res = provider
+        .authorize(client.get(&url), api_key)
+        .timeout(Duration::from_secs(AUTH_TEST_TIMEOUT_SECS))
+        .send();
+    match res {
+        Ok(res) if res.status().is_success() => Probe { name: "network", ok: true, detail: format!("{url} answered {}", res.status()) },
+        Ok(res) => Probe { name: "network", ok: false, detail: format!("{url} answered {}", res.status()) },
+        Err(e) => Probe { name: "network", ok: false, detail: format!("{url} did not answer: {e}") },
+    }
+}
+
+fn run_selftest(args: SelftestArgs) -> Result<(), DoxcerError> {
+
+    /// Runs a fast health check for container probes (`doxcer selftest`).
+    ///
+    /// # Description
+    /// Runs the checks of `doctor` (`.env`, the Fernet key, config), the
+    /// `template check` checker over the prompt template and a write to the
+    /// cache directory, without any API call. `--network` adds an
+    /// authenticated request to the provider that costs no tokens. The result
+    /// is a compact table, or with `--format json` one JSON object:
+    /// `{"healthy": .., "elapsed_ms": .., "checks": [{"name", "ok", "detail"}]}`.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer selftest [--network] [--format table|json] [--config <file>]
+    /// ```
+    ///
+    /// # Exit Codes
+    /// * `0` – every check passed.
+    /// * `3` – at least one check failed.
+
+    let SelftestArgs { network, format, config: config_path } = args;
+    let json = format == "json";
+
+    let started = Instant::now();
+    let mut probes = Vec::new();
+    env_probes(&mut probes);
+    let (probe, provider) = setup_probe(config_path.as_deref());
+    let provider = provider.unwrap_or_else(|| Arc::new(OpenAi::default()));
+    let api_key = key_probes(&mut probes, provider.as_ref());
+    probes.push(probe);
+    probes.push(template_probe());
+    probes.push(cache_probe());
+    if network {
+        probes.push(network_probe(provider.as_ref(), api_key.as_deref()));
+    }
+    let healthy = probes.iter().all(|p| p.ok);
+    let elapsed_ms = started.elapsed().as_millis();
+
+    if json {
+        let result = serde_json::json!({ "healthy": healthy, "elapsed_ms": elapsed_ms, "checks": probes });
+        outln!("{result}");
+    } else {
+        let style = Style::stdout();
+        let width = probes.iter().map(|p| p.name.len()).max().unwrap_or(0);
+        for probe in &probes {
+            outln!("{} {:<width$}  {}", style.mark(probe.ok), probe.name, probe.detail);
+        }
+        outln!("{} in {elapsed_ms} ms", if healthy { "healthy" } else { "unhealthy" });
+    }
+    if !healthy {
+        let failed: Vec<&str> = probes.iter().filter(|p| !p.ok).map(|p| p.name).collect();
+        return Err(DoxcerError::Config(format!("unhealthy: {}", failed.join(", "))));
+    }
+    Ok(())
+}
+
+
+fn run_coverage(args: CoverageArgs) -> Result<(), DoxcerError> {
+
+    /// Reports how many notebooks are documented and how stale the docs are
+    /// (`doxcer coverage`).
+    ///
+    /// # Description
+    /// Notebooks are discovered like for `verify` and generation (`.doxcerignore`,
+    /// `--exclude`, `skip=true`) and checked against their documents and the
+    /// manifest without any API call. Each lands in a bucket: up-to-date, stale
+    /// for less or more than 30 days (by the document's `generated_at`, or its
+    /// modification time), or never documented. The counts are printed per
+    /// folder and in total, or as JSON with `--format json`. Coverage is the
+    /// fraction of notebooks that have a document at all.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer coverage [--docs-dir <dir>] [--manifest <file>] [--exclude <pattern>]... [--format table|json] [--min-coverage <ratio>] [<paths>...]
+    /// ```
+    ///
+    /// # Exit Codes
+    /// * `0` – report printed, coverage at or above `--min-coverage`.
+    /// * `1` – coverage below `--min-coverage`.
+
+    let CoverageArgs { paths: mut inputs, docs_dir, manifest: manifest_path, exclude, format, min_coverage } = args;
+    let options = DiscoveryOptions { excludes: exclude, ..DiscoveryOptions::default() };
+    let json = format == "json";
+    if inputs.is_empty() {
+        inputs.push(PathBuf::from("."));
+    }
+
+    let discovery = discover(&inputs, &options)?;
+    let manifest = Manifest::load(&manifest_path)?;
+    let results: Vec<_> = discovery
+        .notebooks
+        .iter()
+        .map(|notebook| {
+            let result = verify_notebook(notebook, &docs_dir, &manifest)?;
+            let age = (result.status == DocStatus::Stale).then(|| doc_age(&result.doc_path, manifest.get(notebook))).flatten();
+            let bucket = bucket(result.status, age);
+            Ok((result, bucket))
+        })
+        .collect::<Result<_, DoxcerError>>()?;
+    let report = coverage_report(&results);
+
+    if json {
+        let rendered = serde_json::to_string_pretty(&report).map_err(|e| DoxcerError::Input(e.to_string()))?;
+        outln!("{rendered}");
+    } else {
+        out!("{}", render_coverage(&report));
+    }
+    if let Some(min) = min_coverage.filter(|min| report.total.coverage < *min) {
+        return Err(DoxcerError::Incomplete(format!(
+            "documentation coverage {:.1}% is below --min-coverage {:.1}%",
+            report.total.coverage * 100.0,
+            min * 100.0
+        )));
+    }
+    Ok(())
+}
+
+
+fn run_cache(command: CacheCommand) -> Result<(), DoxcerError> {
+
+    /// Inspects and maintains the response cache (`doxcer cache ...`).
+    ///
+    /// # Description
+    /// * `stats` – entry count, total size, hit-rate from the run log and a
+    ///   breakdown by model and template hash.
+    /// * `clear` – removes entries, optionally only those for `--model`, older
+    ///   than `--older-than` (e.g. `7d`) or made with an outdated template
+    ///   (`--template-outdated`). Filters combine.
+    /// * `get <notebook>` – prints the cached generation for the notebook's current
+    ///   content without any API call; fails when there is none.
+    ///
+    /// Entries that are being written by a running batch are skipped, never fatal.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer cache stats
+    /// doxcer cache clear [--model <name>] [--older-than <age>] [--template-outdated] [--config <file>]
+    /// doxcer cache get <path/to/notebook.py> [--config <file>]
+    /// ```
+
+
+    // Settings from `.env` take part in the cache key, so load it like a real run.
+    let _ = load_env_robust::<&Path>(None);
+
+    let cache = ResponseCache::new(&default_cache_dir());
+    let files = cache.entries()?;
+
+    match command {
+        CacheCommand::Stats(ConfigArgs { config: config_path }) => {
+            let records = read_run_records(Path::new(DEFAULT_RUN_LOG_PATH))?;
+            out!("{}", render_stats(&files, &records, display_locale(config_path.as_deref())));
+        }
+        CacheCommand::Clear { model, older_than, template_outdated, config: config_path } => {
+            let current_template = template_outdated
+                .then(|| load_setup(&SettingsLayer::default(), config_path.as_deref()).map(|setup| setup.template.hash))
+                .transpose()?;
+            let mut removed = 0;
+            let mut freed = 0;
+            for file in &files {
+                let entry = &file.entry;
+                let selected = model.as_ref().is_none_or(|m| *m == entry.model)
+                    && older_than.is_none_or(|max| age_of(&entry.created_at).is_some_and(|age| age > max))
+                    && current_template.as_ref().is_none_or(|hash| *hash != entry.template_hash);
+                if selected && cache.remove(file)? {
+                    removed += 1;
+                    freed += file.size;
+                }
+            }
+            let locale = display_locale(config_path.as_deref());
+            outln!("Removed {removed} of {} cache entries ({}).", files.len(), format_size_in(freed as usize, locale));
+        }
+        CacheCommand::Get { notebook, config: config_path } => {
+            let setup = load_setup(&SettingsLayer::default(), config_path.as_deref())?;
+            let snapshot = SourceSnapshot::read(&notebook)?;
+            let (prompt, _) = prepare_prompt(&setup, &notebook, &snapshot.text)?;
+
+            let hit = model_chain(&setup.settings).into_iter().find_map(|model| {
+                let request = build_request(&setup, &prompt, model);
+                let json = serde_json::to_string_pretty(&request).ok()?;
+                cache.get(&cache_key(&json))
+            });
+            match hit {
+                Some(entry) => {
+                    let locale = Locale::from_lang(setup.settings.lang.value.as_deref());
+                    let created = format_datetime(&entry.created_at, locale).unwrap_or_else(|| entry.created_at.clone());
+                    errln!("cached {} by {} ({created})", entry.notebook, entry.model);
+                    outln!("{}", entry.text);
+                }
+                None => return Err(DoxcerError::Input(format!("no cached generation for the current content of {}", notebook.display()))),
+            }
+        }
+    }
+    Ok(())
+}
+
+
+fn run_hook(command: HookCommand) -> Result<(), DoxcerError> {
+
+    /// Installs or removes the doxcer pre-commit hook (`doxcer hook ...`).
+    ///
+    /// # Description
+    /// The hook runs `doxcer verify --changed --staged` inside a marked block,
+    /// so an existing pre-commit script keeps working and `uninstall` removes
+    /// only that block. The hook directory comes from git, so `core.hooksPath`
+    /// is honoured.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer hook install
+    /// doxcer hook uninstall
+    /// ```
+
+    let hook = git::hooks_dir()
+        .map(|dir| dir.join(HOOK_NAME))
+        ?;
+    let change = match command {
+        HookCommand::Install => install_hook(&hook),
+        HookCommand::Uninstall => uninstall_hook(&hook),
+    }
+    ?;
+
+    let hook = hook.display();
+    match change {
+        HookChange::Created => outln!("created {hook} with the doxcer check"),
+        HookChange::Appended => outln!("added the doxcer check to {hook}"),
+        HookChange::Updated => outln!("updated the doxcer check in {hook}"),
+        HookChange::Removed => outln!("removed the doxcer check from {hook}"),
+        HookChange::Deleted => outln!("removed {hook}; it only contained the doxcer check"),
+        HookChange::Absent => outln!("no doxcer check in {hook}; nothing to remove"),
+    }
+    Ok(())
+}
+
+fn run_keygen(args: KeygenArgs) -> Result<(), DoxcerError> {
+
+    /// Generates a Fernet key (`doxcer keygen`).
+    ///
+    /// # Description
+    /// Prints a fresh key for `ENCRYPTION_PASSWORD`. With `--write-env <file>`
+    /// the key is appended to that `.env` file as `ENCRYPTION_PASSWORD=<key>`
+    /// instead of printed (see [`append_env_var`]); a file that already sets
+    /// it is refused, so an existing key is never lost.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer keygen [--write-env <file>]
+    /// ```
+
+    let KeygenArgs { write_env } = args;
+
+    let key = generate_fernet_key();
+    match write_env {
+        Some(path) => {
+            append_env_var(&path, "ENCRYPTION_PASSWORD", &key)?;
+            errln!("added ENCRYPTION_PASSWORD to {}", path.display());
+        }
+        None => outln!("{key}"),
+    }
+    Ok(())
+}
+
+fn read_stdin_value() -> Result<String, DoxcerError> {
+
+    /// Reads a value from stdin without its trailing line break.
+
+    let mut value = String::new();
+    if let Err(e) = io::stdin().read_to_string(&mut value) {
+        return Err(DoxcerError::Input(format!("Failed to read stdin: {e}")));
+    }
+    Ok(value.trim_end_matches(['\n', '\r']).to_string())
+}
+
+fn secret_encryption_key(name: &str) -> Result<String, DoxcerError> {
+
+    /// Returns the Fernet key for `<name>_ENC`, from the variable named by
+    /// [`secret_key_name`], after loading `.env` when there is one.
+
+    let _ = load_env_robust::<&Path>(None);
+    let key_name = secret_key_name(name);
+    env::var(&key_name)
+        .map_err(|_| DoxcerError::Config(format!("Missing {key_name} for {name}_ENC; create a key with `doxcer keygen --write-env <file>`")))
+}
+
+fn run_encrypt(args: EncryptArgs) -> Result<(), DoxcerError> {
+
+    /// Encrypts a secret into its `<NAME>_ENC` value (`doxcer encrypt`).
+    ///
+    /// # Description
+    /// The plaintext comes from `--value` or stdin; prefer stdin, so the
+    /// secret stays out of the shell history. It is encrypted with the key
+    /// that decrypts `<NAME>_ENC` ([`secret_key_name`]: `<NAME>_ENC_KEY` or
+    /// `ENCRYPTION_PASSWORD`). The token is printed, or with
+    /// `--write-env <file>` set as `<NAME>_ENC` in that file, replacing an
+    /// older value in place (see [`set_env_var`]).
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer encrypt [--name <VAR>] [--value <plaintext>] [--write-env <file>]
+    /// ```
+
+    let EncryptArgs { secret: SecretArgs { name, value }, write_env } = args;
+    let value = match value {
+        Some(value) => value,
+        None => {
+            if io::stdin().is_terminal() {
+                errln!("Enter the value of {name}, then press Ctrl-D:");
+            }
+            read_stdin_value()?
+        }
+    };
+    if value.is_empty() {
+        return Err(DoxcerError::Usage(format!("nothing to encrypt; pass the value of {name} on stdin or with --value")));
+    }
+
+    let key = secret_encryption_key(&name)?;
+    let token = encrypt_fernet(&value, &key).map_err(|e| DoxcerError::Config(format!("{}: {e}", secret_key_name(&name))))?;
+    let enc_name = format!("{name}_ENC");
+    match write_env {
+        Some(path) => match set_env_var(&path, &enc_name, &token)? {
+            true => errln!("replaced {enc_name} in {}", path.display()),
+            false => errln!("added {enc_name} to {}", path.display()),
+        },
+        None => outln!("{token}"),
+    }
+    Ok(())
+}
+
+fn run_decrypt(args: SecretArgs) -> Result<(), DoxcerError> {
+
+    /// Decrypts a `<NAME>_ENC` value (`doxcer decrypt`).
+    ///
+    /// # Description
+    /// The token comes from `--value`, from stdin when it is not a terminal and
+    /// not empty, or otherwise from the `<NAME>_ENC` variable in the
+    /// environment or `.env`.
+    /// It is decrypted with the same key as in [`run_encrypt`] and the
+    /// plaintext is printed.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer decrypt [--name <VAR>] [--value <token>]
+    /// ```
+
+    let SecretArgs { name, value } = args;
+    let key = secret_encryption_key(&name)?;
+    let enc_name = format!("{name}_ENC");
+    let piped = match value.is_none() && !io::stdin().is_terminal() {
+        true => Some(read_stdin_value()?),
+        false => None,
+    };
+    let token = value
+        .or_else(|| piped.filter(|token| !token.trim().is_empty()))
+        .or_else(|| env::var(&enc_name).ok())
+        .ok_or_else(|| DoxcerError::Config(format!("{enc_name} is not set; pass the token on stdin or with --value")))?;
+    match decrypt_fernet(token.trim(), &key) {
+        Ok(plaintext) => outln!("{plaintext}"),
+        Err(e) => return Err(DoxcerError::Config(format!("{enc_name} does not decrypt with {}: {e}", secret_key_name(&name)))),
+    }
+    Ok(())
+}
+
+fn run_serve(args: ServeArgs) -> Result<(), DoxcerError> {
+
+    /// Serves a live preview of the generated docs (`doxcer serve`, builds with
+    /// the `serve` feature).
+    ///
+    /// # Description
+    /// Renders the Markdown in the docs directory to HTML per request, with a
+    /// sidebar of all documents (and their notebooks, from the manifest). Open
+    /// pages poll for changes and reload when a document is regenerated. The
+    /// server listens on 127.0.0.1 and a free port unless `--host`/`--port` say
+    /// otherwise.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer serve [docs-dir] [--host <addr>] [--port <n>] [--manifest <file>]
+    /// ```
+
+    let ServeArgs { docs_dir, host, port, manifest: manifest_path } = args;
+    if !docs_dir.is_dir() {
+        return Err(DoxcerError::Input(format!("{} is not a directory", docs_dir.display())));
+    }
+    if !matches!(host.as_str(), "127.0.0.1" | "localhost" | "::1") {
+        warn(format!("serving on {host}: anyone who can reach this address can read the docs"));
+    }
+
+    serve(&docs_dir, &host, port, &manifest_path, |url| {
+        errln!("serving {} at {url} (Ctrl+C to stop)", docs_dir.display());
+    })
+}
+
+fn run_watch(args: WatchArgs) -> Result<(), DoxcerError> {
+
+    /// Regenerates the documentation of a notebook whenever it changes
+    /// (`doxcer watch`).
+    ///
+    /// # Description
+    /// The given paths are watched with file notifications (see [`Watcher`])
+    /// for changes to the notebooks below them, found with the same
+    /// discovery as a batch; a new notebook file re-runs discovery, so it is
+    /// picked up at once. A changed notebook waits until it has not changed
+    /// for `--debounce-ms` (default 300), checked every `--interval-ms`
+    /// (default 500) together with the finished runs, and when
+    /// its content differs from the last run it is queued (see [`WorkQueue`]):
+    /// more changes to a queued notebook coalesce, and a full queue
+    /// (`--queue-capacity`, or `DOXCER_QUEUE_CAPACITY`, default 256) drops its
+    /// oldest entry with a warning. Up to `--jobs` (default 1) notebooks are
+    /// documented at a time, each by `doxcer generate <notebook> --output
+    /// <docs-dir> --overwrite` with the arguments after `--` passed on; a
+    /// notebook that changes again while it runs waits for that run. Each run
+    /// is a child process, so a failed run does not stop the watch. With
+    /// `--verbose` the `changed` line shows the queue depth and the running
+    /// jobs. Stops with Ctrl+C.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer watch [<paths>...] [--docs-dir <dir>] [--include <pattern>]... [--exclude <pattern>]... [--interval-ms <ms>] [--debounce-ms <ms>] [--jobs <n>] [--queue-capacity <n>] [--verbose] [-- <generate options>...]
+    /// ```
+
+    let WatchArgs { paths: mut inputs, docs_dir, include, exclude, interval, debounce, jobs, capacity, verbose, passed } = args;
+    let options = DiscoveryOptions { includes: include, excludes: exclude, ..DiscoveryOptions::default() };
+    if inputs.is_empty() {
+        inputs.push(PathBuf::from("."));
+    }
+    let exe = env::current_exe().map_err(|e| DoxcerError::Config(format!("Failed to locate the doxcer executable: {e}")))?;
+    let capacity = match capacity {
+        Some(capacity) => capacity,
+        None => capacity_from_env()?,
+    };
+
+    let mut watcher = Watcher::new(&inputs, Duration::from_millis(debounce))?;
+    let notebooks = discover(&inputs, &options)?.notebooks;
+    let mut known = canonical_paths(&notebooks);
+    let mut hashes: BTreeMap<PathBuf, String> = notebooks
+        .iter()
+        .filter_map(|path| Some((path.clone(), SourceSnapshot::read(path).ok()?.hash)))
+        .collect();
+    let mut queue: WorkQueue<PathBuf, String> = WorkQueue::new(capacity);
+    let mut running: Vec<(PathBuf, process::Child)> = Vec::new();
+    let below: Vec<String> = inputs.iter().map(|p| p.display().to_string()).collect();
+    errln!("watching {} notebook(s) below {} (Ctrl+C to stop)", notebooks.len(), below.join(", "));
+    loop {
+        let changed = match watcher.wait(Duration::from_millis(interval)) {
+            Ok(changed) => changed,
+            Err(e) => {
+                warn(e);
+                Vec::new()
+            }
+        };
+        if changed.iter().any(|path| !known.contains_key(path) && is_notebook_path(path)) {
+            match discover(&inputs, &options) {
+                Ok(discovery) => known = canonical_paths(&discovery.notebooks),
+                Err(e) => warn(e),
+            }
+        }
+        for notebook in changed.iter().filter_map(|path| known.get(path)).cloned() {
+            let Ok(snapshot) = SourceSnapshot::read(&notebook) else {
+                continue;
+            };
+            if hashes.get(&notebook) == Some(&snapshot.hash) {
+                continue;
+            }
+            if let Pushed::DroppedOldest(dropped) = queue.push(notebook, snapshot.hash) {
+                warn(format!("the watch queue is full ({capacity}); dropped the change to {}", dropped.display()));
+            }
+        }
+
+        running.retain_mut(|(notebook, child)| {
+            let status = match child.try_wait() {
+                Ok(None) => return true,
+                Ok(Some(status)) => status,
+                Err(e) => {
+                    warn(format!("Failed to wait for doxcer generate for {}: {e}", notebook.display()));
+                    return false;
+                }
+            };
+            match status.code() {
+                Some(0) => errln!("{} {}", Style::stderr().paint("ok         ", Color::Green), notebook.display()),
+                code => errln!(
+                    "{} {} (exit code {})",
+                    Style::stderr().paint("failed     ", Color::Red),
+                    notebook.display(),
+                    code.map_or_else(|| "-".to_string(), |code| code.to_string())
+                ),
+            }
+            false
+        });
+
+        let busy: BTreeSet<PathBuf> = running.iter().map(|(notebook, _)| notebook.clone()).collect();
+        for (notebook, hash) in queue.take_ready(jobs.saturating_sub(running.len()), |notebook| busy.contains(notebook)) {
+            if hashes.get(&notebook) == Some(&hash) {
+                continue;
+            }
+            hashes.insert(notebook.clone(), hash);
+            let child = process::Command::new(&exe)
+                .arg("generate")
+                .arg(&notebook)
+                .arg("--output")
+                .arg(&docs_dir)
+                .arg("--overwrite")
+                .args(&passed)
+                .spawn();
+            match child {
+                Ok(child) => {
+                    match verbose {
+                        true => errln!(
+                            "{} {} (queue {}, running {} of {jobs})",
+                            Style::stderr().paint("changed    ", Color::Cyan),
+                            notebook.display(),
+                            queue.len(),
+                            running.len() + 1
+                        ),
+                        false => errln!("{} {}", Style::stderr().paint("changed    ", Color::Cyan), notebook.display()),
+                    }
+                    running.push((notebook, child));
+                }
+                Err(e) => warn(format!("Failed to run doxcer generate for {}: {e}", notebook.display())),
+            }
+        }
+    }
+}
+
+fn run_dbt(args: DbtArgs) -> Result<(), DoxcerError> {
+
+    /// Documents the models of a dbt project (`doxcer dbt`).
+    ///
+    /// # Description
+    /// The `.sql` files below the `model-paths` of `dbt_project.yml` (default
+    /// `models/`), narrowed by `--select` patterns (gitignore syntax), are
+    /// documented by one `doxcer generate --kind sql --output <docs-dir>
+    /// --overwrite` batch, with the arguments after `--` passed on; batching,
+    /// concurrency and the response cache work as in `generate`.
+    ///
+    /// With `--write-schema`, the first paragraph of each model's document
+    /// becomes the `description` of that model in the `schema.yml` (any
+    /// `.yml` below the model paths) that lists it. Only documents of the
+    /// current model source are used, so a failed model keeps its old
+    /// description; models without an entry are reported, not added.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer dbt [<project-dir>] [--docs-dir <dir>] [--select <pattern>]... [--write-schema] [-- <generate options>...]
+    /// ```
+    ///
+    /// # Exit Codes
+    /// * The exit code of the `generate` batch.
+
+    let DbtArgs { project, docs_dir, select, write_schema, passed } = args;
+    let options = DiscoveryOptions { sql_scripts: true, includes: select, ..DiscoveryOptions::default() };
+    let project_file = project.join(PROJECT_FILE);
+    let config = fs::read_to_string(&project_file)
+        .map_err(|e| DoxcerError::Input(format!("{} is not a dbt project: failed to read {}: {e}", project.display(), project_file.display())))?;
+    let dirs: Vec<PathBuf> = model_paths(&config).iter().map(|dir| project.join(dir)).filter(|dir| dir.is_dir()).collect();
+    let mut models = discover(&dirs, &options)?.notebooks;
+    models.retain(|path| is_sql_path(path));
+    if models.is_empty() {
+        errln!("No dbt models found in {}.", project.display());
+        return Ok(());
+    }
+
+    let exe = env::current_exe().map_err(|e| DoxcerError::Config(format!("Failed to locate the doxcer executable: {e}")))?;
+    let status = process::Command::new(&exe)
+        .arg("generate")
+        .args(&models)
+        .args(["--kind", "sql", "--output"])
+        .arg(&docs_dir)
+        .arg("--overwrite")
+        .args(passed)
+        .status()
+        .map_err(|e| DoxcerError::Config(format!("Failed to run doxcer generate: {e}")))?;
+
+    if write_schema {
+        write_model_descriptions(&models, &docs_dir, &schema_files(&dirs));
+    }
+    if !status.success() {
+        let code = status.code().map_or("a signal".to_string(), |code| format!("exit code {code}"));
+        return Err(DoxcerError::from_exit_code(status.code().unwrap_or(1), format!("doxcer generate failed with {code}")));
+    }
+    Ok(())
+}
+
+fn write_model_descriptions(models: &[PathBuf], docs_dir: &Path, schemas: &[PathBuf]) {
+
+    /// Writes the description of every documented model into the properties
+    /// file that lists it, for `doxcer dbt --write-schema`.
+
+    let mut files: Vec<(PathBuf, String, bool)> = schemas
+        .iter()
+        .filter_map(|path| Some((path.clone(), fs::read_to_string(path).ok()?, false)))
+        .collect();
+    let mut updated = 0;
+    for model in models {
+        let Some(name) = model.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let doc = fs::read_to_string(expected_doc_path(model, docs_dir)).ok();
+        let current = SourceSnapshot::read(model).ok().map(|snapshot| snapshot.hash);
+        let description = doc
+            .as_deref()
+            .filter(|doc| read_provenance(doc).map(|p| p.source_hash) == current)
+            .and_then(model_description);
+        let Some(description) = description else {
+            warn(format!("{}: no current documentation; its description was not changed", model.display()));
+            continue;
+        };
+        let entry = files.iter_mut().find_map(|(path, text, dirty)| Some((path, set_model_description(text, &name, &description)?, text, dirty)));
+        match entry {
+            Some((_, new_text, text, dirty)) => {
+                *text = new_text;
+                *dirty = true;
+                updated += 1;
+            }
+            None => warn(format!("{}: model {name} is not listed in a schema.yml; add an entry to get its description", model.display())),
+        }
+    }
+    let mut written = 0;
+    for (path, text, _) in files.iter().filter(|(_, _, dirty)| *dirty) {
+        match write_schema_file(path, text) {
+            Ok(()) => written += 1,
+            Err(e) => warn(e),
+        }
+    }
+    errln!("wrote the descriptions of {updated} model(s) to {written} schema file(s)");
+}
+
+fn run_runs(command: RunsCommand) -> Result<(), DoxcerError> {
+
+    /// Reports on the run log (`doxcer runs summary`).
+    ///
+    /// # Description
+    /// Totals runs, successful runs, cache hits and tokens per group. Groups
+    /// are formed by `--group-by` keys: tag keys (`--tag`, `DOXCER_TAGS`) and
+    /// the built-in `template` (template hash) and `model`. `--since` and
+    /// `--until` take a date, an RFC 3339 timestamp or an age such as `30d`;
+    /// `--until` is exclusive. The log is streamed, not loaded at once.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer runs summary [--group-by team,template] [--since 2026-01-01] [--until 2027-01-01] [--format table|csv|json]
+    /// ```
+
+    let RunsCommand::Summary { group_by, since, until, format } = command;
+    let group_by = group_by.unwrap_or_default();
+    let window = RunWindow { since, until };
+
+    let summary = summarize_runs(Path::new(DEFAULT_RUN_LOG_PATH), &group_by, window)?;
+    match format.as_str() {
+        "csv" => out!("{}", render_summary_csv(&summary)),
+        "json" => outln!("{}", render_summary_json(&summary)?),
+        _ => out!("{}", render_summary_table(&summary)),
+    }
+    Ok(())
+}
+
+fn run_template(command: TemplateCommand) -> Result<(), DoxcerError> {
+
+    /// Checks or exports prompt templates (`doxcer template ...`).
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer template check [<template.md>...] [options]
+    /// doxcer template show [<name>]
+    /// doxcer template export [<dir>] [--overwrite]
+    /// ```
+
+    match command {
+        TemplateCommand::Check { paths, template_dir, config, vars, strict } => {
+            let vars = vars.into_iter().map(|(name, _)| name).collect();
+            run_template_check(paths, template_dir, config, vars, strict)
+        }
+        TemplateCommand::Show { name } => run_template_show(&name),
+        TemplateCommand::Export { dir, overwrite } => run_template_export(&dir, overwrite),
+    }
+}
+
+fn run_template_show(name: &str) -> Result<(), DoxcerError> {
+
+    /// Prints a template compiled into doxcer (`doxcer template show`).
+    ///
+    /// # Description
+    /// Without a name the built-in `prompt.md` is printed, the template used
+    /// when no template file is found (see [`template_path`]). Any built-in
+    /// template or partial can be named, with or without `.md`.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer template show [prompt|summary|notebook_summary|sql|pipeline|semantic_model|markdown_tables]
+    /// ```
+
+    let Some((_, raw)) = builtin_template(name) else {
+        let names: Vec<&str> = BUILTIN_TEMPLATES.iter().chain(BUILTIN_PARTIALS.iter()).map(|(file, _)| *file).collect();
+        return Err(DoxcerError::Usage(format!("No built-in template `{name}`; expected one of {}", names.join(", "))));
+    };
+    out!("{raw}");
+    Ok(())
+}
+
+fn run_template_export(dir: &Path, overwrite: bool) -> Result<(), DoxcerError> {
+
+    /// Writes the built-in templates to a directory for customization
+    /// (`doxcer template export`).
+    ///
+    /// # Description
+    /// Every built-in template and partial is written to `<dir>` (default
+    /// `./templates`, where [`template_path`] finds it from then on). Existing
+    /// files are kept unless `--overwrite` is given, so an export never loses
+    /// local changes; the command fails when it skipped any file.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer template export [<dir>] [--overwrite]
+    /// ```
+
+    let skipped = export_templates(dir, overwrite)?;
+    if skipped > 0 {
+        return Err(DoxcerError::Input(format!("{skipped} template file(s) already existed in {}", dir.display())));
+    }
+    Ok(())
+}
+
+fn export_templates(dir: &Path, overwrite: bool) -> Result<usize, DoxcerError> {
+
+    /// Writes every built-in template and partial to `dir`, keeping existing
+    /// files unless `overwrite`; returns the number of files kept.
+
+    fs::create_dir_all(dir).map_err(|e| DoxcerError::Input(format!("Failed to create {}: {e}", dir.display())))?;
+    let mut skipped = 0;
+    for (file, raw) in BUILTIN_TEMPLATES.iter().chain(BUILTIN_PARTIALS.iter()) {
+        let path = dir.join(file);
+        if path.exists() && !overwrite {
+            warn(format!("{} already exists; kept (use --overwrite to replace it)", path.display()));
+            skipped += 1;
+            continue;
+        }
+        fs::write(&path, raw).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", path.display())))?;
+        outln!("wrote {}", path.display());
+    }
+    Ok(skipped)
+}
+
+fn run_template_check(paths: Vec<PathBuf>, template_dir: Option<PathBuf>, config_path: Option<PathBuf>, mut vars: Vec<String>, strict: bool) -> Result<(), DoxcerError> {
+
+    /// Checks prompt templates without generating anything (`doxcer template check`).
+    ///
+    /// # Description
+    /// Parses every given template, or every `.md` file in the template directory
+    /// (by default the directory of the prompt template, see [`template_path`]),
+    /// and each partial they include. Placeholders, includes and front-matter are
+    /// checked, and every problem is printed as
+    /// `file:line:column: error|warning: message`. Warnings (such as unknown
+    /// front-matter keys or unset `env:` variables) only fail with `--strict`.
+    /// Template variables from `[vars]` in `doxcer.toml` (or `--config`) and
+    /// `--var` count as known placeholders.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer template check [<template.md>...] [--template-dir <dir>] [--config <file>] [--var <name=value>]... [--strict]
+    /// ```
+
+
+    if let Some(path) = resolve_config_path(config_path.as_deref()) {
+        let config = load_config_file(&path)?;
+        vars.extend(config.settings.vars.unwrap_or_default().into_keys());
+    }
+    let mut checker = TemplateChecker::default();
+    checker.vars = vars;
+    let default_dir = prompt_template_dir();
+    let checked = if paths.is_empty() || template_dir.is_some() {
+        checker.check_dir(template_dir.as_deref().unwrap_or(&default_dir))
+    } else {
+        Ok(())
+    };
+    checked?;
+    for path in &paths {
+        let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let dir = template_dir.clone().unwrap_or_else(|| parent.to_path_buf());
+        checker.check_file(path, &dir)?;
+    }
+
+    let style = Style::stdout();
+    for problem in &checker.problems {
+        let color = if problem.severity == Severity::Error { Color::Red } else { Color::Yellow };
+        outln!("{}", style.paint(&problem.to_string(), color));
+    }
+    let (errors, warnings) = (checker.count(Severity::Error), checker.count(Severity::Warning));
+    let summary = format!("Checked {} template file(s): {errors} error(s), {warnings} warning(s).", checker.files);
+    if errors > 0 || (strict && warnings > 0) {
+        return Err(DoxcerError::Config(summary));
+    }
+    outln!("{summary}");
+    Ok(())
+}
+
+
+// ----------------------------
+// Runtime
+// ----------------------------
+fn main() -> ExitCode {
+
+    /// Entry point for the Doxcer notebook documentation generator.
+    ///
+    /// # Description
+    /// Dispatches to the requested command:
+    /// * `doxcer [generate] <paths>...` – generate documentation for notebooks and directories to stdout.
+    /// * `doxcer verify <paths>...` – check that committed docs are up to date.
+    /// * `doxcer check <paths>...` – fail when committed docs are stale or missing, without writing.
+    /// * `doxcer init [<dir>]` – write `doxcer.toml`, the templates, `.env` and `.gitignore` for a new project.
+    /// * `doxcer config dump` – print the effective configuration (secrets redacted).
+    /// * `doxcer coverage [<paths>...]` – documented and stale notebooks per folder, no API calls.
+    /// * `doxcer doctor` – check the local setup and print the configuration.
+    /// * `doxcer selftest` – fast health check for container probes, no tokens spent.
+    /// * `doxcer cache stats|clear|get` – inspect and maintain the response cache.
+    /// * `doxcer runs summary` – run, cache and token totals per tag or template.
+    /// * `doxcer template check` – validate the prompt templates and partials.
+    /// * `doxcer hook install|uninstall` – manage the git pre-commit hook.
+    /// * `doxcer keygen [--write-env <file>]` – generate a Fernet key.
+    /// * `doxcer encrypt|decrypt [--name <VAR>]` – turn a secret into its `_ENC` value and back.
+    /// * `doxcer serve [docs-dir]` – preview the generated docs in a browser.
+    /// * `doxcer --version` – print the version and the user agent sent to APIs.
+    /// * `doxcer --explain-exit-codes` – print the stable exit-code table.
+    /// * `doxcer --help` – list the commands; `doxcer <command> --help` prints its usage.
+    ///
+    /// Every failure is returned to here as a [`DoxcerError`] and exits with
+    /// the code of its kind (see `doxcer --explain-exit-codes`).
+    ///
+    /// The command line is parsed with clap (see [`Cli`]). `--color`,
+    /// `--strict-env-security`, `--run-profile <name>` and `--template <file>`
+    /// are global flags, accepted anywhere.
+    ///
+    /// # Usage
+    /// ```bash
+    /// doxcer <path/to/notebook.py> [--copy]
+    /// doxcer verify [--fix] <paths>...
+    /// ```
+
+    let args: Vec<String> = env::args()
+        .skip(1)
+        .collect();
+
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            report_error(&error);
+            ExitCode::from(&error)
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), DoxcerError> {
+
+    /// Parses the command line, applies the global flags and runs the
+    /// command, see [`main`].
+
+    let Some(cli) = parse_command_line(args)? else {
+        return Ok(());
+    };
+    set_color_choice(cli.color);
+    if cli.strict_env_security {
+        STRICT_ENV_SECURITY.store(true, Ordering::Relaxed);
+    }
+    if let Some(name) = cli.run_profile {
+        let _ = RUN_PROFILE.set(name);
+    }
+    if let Some(path) = cli.template {
+        let _ = TEMPLATE_OVERRIDE.set(path);
+    }
+    if cli.version {
+        outln!("doxcer {} ({})", version(), user_agent());
+        return Ok(());
+    }
+    if cli.explain_exit_codes {
+        out!("{}", render_exit_codes());
+        return Ok(());
+    }
+
+    match cli.command {
+        Some(Command::Generate(args)) => run_generate(*args),
+        Some(Command::Verify(args)) => run_verify(*args),
+        Some(Command::Check(args)) => run_check(args),
+        Some(Command::Init(args)) => run_init(args),
+        Some(Command::Config(command)) => run_config(command),
+        Some(Command::Coverage(args)) => run_coverage(args),
+        Some(Command::Doctor(args)) => run_doctor(args),
+        Some(Command::Selftest(args)) => run_selftest(args),
+        Some(Command::Cache(command)) => run_cache(command),
+        Some(Command::Runs(command)) => run_runs(command),
+        Some(Command::Template(command)) => run_template(command),
+        Some(Command::Hook(command)) => run_hook(command),
+        Some(Command::Keygen(args)) => run_keygen(args),
+        Some(Command::Encrypt(args)) => run_encrypt(args),
+        Some(Command::Decrypt(args)) => run_decrypt(args),
+        Some(Command::Serve(args)) => run_serve(args),
+        Some(Command::Watch(args)) => run_watch(args),
+        Some(Command::Dbt(args)) => run_dbt(args),
+        None => {
+            let usage = command_line().render_usage().to_string();
+            Err(DoxcerError::Usage(format!("no command or notebook given\n\n{usage}\n\nFor more information, try '--help'.")))
+        }
+    }
+}
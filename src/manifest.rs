@@ -0,0 +1,122 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+// External Libraries
+use serde::{Deserialize, Serialize};
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const DEFAULT_MANIFEST_PATH: &str = ".doxcer/manifest.json";
+// The `output` of a document that `generate` printed to stdout.
+pub const STDOUT_OUTPUT: &str = "-";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    #[serde(default)]
+    pub entries: BTreeMap<String, ManifestEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub source_hash: String,
+    pub output: String,
+    // The stored notebook content `source_hash` was taken from (`--edit-mode`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<String>,
+    // The ref and commit the notebook was read from (`--at-ref`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_commit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generated_at: Option<String>,
+}
+
+
+// ====================================================
+//  Manifest Keys
+// ====================================================
+pub fn manifest_key(path: &Path) -> String {
+
+    // Normalizes a notebook path into the key used inside the manifest.
+    //
+    // # Description
+    // Uses forward slashes and drops a leading `./`, so the same notebook maps to
+    // the same entry regardless of platform or how the path was typed.
+
+    let key = path.to_string_lossy().replace('\\', "/");
+    key.strip_prefix("./").unwrap_or(&key).to_string()
+}
+
+
+// ====================================================
+//  Manifest IO
+// ====================================================
+impl Manifest {
+
+    pub fn load(path: &Path) -> Result<Manifest, DoxcerError> {
+
+        // Loads the manifest from disk.
+        //
+        // # Returns
+        // * `Ok(Manifest)` – an empty manifest when the file does not exist yet.
+        // * `Err(DoxcerError)` if the file exists but cannot be read or parsed.
+
+        if !path.exists() {
+            return Ok(Manifest::default());
+        }
+        let raw = fs::read_to_string(path)
+            .map_err(|e| DoxcerError::Input(format!("Failed to read manifest {}: {e}", path.display())))?;
+        serde_json::from_str(&raw)
+            .map_err(|e| DoxcerError::Input(format!("Failed to parse manifest {}: {e}", path.display())))
+    }
+
+
+    pub fn save(&self, path: &Path) -> Result<(), DoxcerError> {
+
+        // Writes the manifest to disk, creating its parent directory if needed.
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .map_err(|e| DoxcerError::Input(format!("Failed to create {}: {e}", parent.display())))?;
+        }
+        let raw = serde_json::to_string_pretty(self)
+            .map_err(|e| DoxcerError::Input(format!("Failed to serialize manifest: {e}")))?;
+        fs::write(path, raw + "\n")
+            .map_err(|e| DoxcerError::Input(format!("Failed to write manifest {}: {e}", path.display())))
+    }
+
+
+    pub fn get(&self, notebook: &Path) -> Option<&ManifestEntry> {
+
+        // Returns the entry recorded for a notebook, if any.
+
+        self.entries.get(&manifest_key(notebook))
+    }
+
+
+    pub fn record(&mut self, notebook: &Path, entry: ManifestEntry) {
+
+        // Records (or replaces) the entry for a notebook.
+
+        self.entries.insert(manifest_key(notebook), entry);
+    }
+}
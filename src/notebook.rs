@@ -0,0 +1,783 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fmt;
+use std::path::Path;
+
+// External Libraries
+use serde_json::Value;
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const FABRIC_HEADER: &str = "# Fabric notebook source";
+// Databricks exports start with this after the comment marker of the language
+// (`# ` for Python, `-- ` for SQL) and separate cells with `COMMAND ----------`.
+pub const DATABRICKS_HEADER: &str = "Databricks notebook source";
+pub const DATABRICKS_MARKER: &str = "COMMAND ----";
+pub const DATABRICKS_LANGUAGES: [&str; 4] = ["python", "sql", "scala", "r"];
+pub const CODE_MARKER: &str = "# CELL ****";
+pub const MARKDOWN_MARKER: &str = "# MARKDOWN ****";
+pub const PARAMETERS_MARKER: &str = "# PARAMETERS CELL ****";
+pub const METADATA_MARKER: &str = "# METADATA ****";
+pub const META_LINE_PREFIX: &str = "# META";
+pub const MAGIC_PREFIX: &str = "# MAGIC";
+// Papermill marks the parameters cell of a Jupyter notebook with this tag.
+pub const PARAMETERS_TAG: &str = "parameters";
+// Characters of output kept per cell with `include_outputs`.
+pub const OUTPUT_LIMIT: usize = 2000;
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellKind {
+    Code,
+    Markdown,
+    Parameters,
+    // A Jupyter `raw` cell, passed on as is.
+    Raw,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    // Position of the cell, starting at 1; the same numbering as `--cell`.
+    pub index: usize,
+    pub kind: CellKind,
+    // Line of the cell marker in the source, starting at 1.
+    pub first_line: usize,
+    // The source without the `# ` of markdown lines and the `# MAGIC ` prefix.
+    pub source: String,
+    // The JSON of the `# METADATA` block after the cell (Fabric) or the cell
+    // `metadata` (Jupyter).
+    pub metadata: Option<Value>,
+    // Text of the stored outputs of a Jupyter code cell, with `include_outputs`.
+    pub outputs: Vec<String>,
+}
+
+// A cell read back from the rendered prompt text (see `rendered_cells`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedCell {
+    pub index: usize,
+    pub kind: String,
+    pub language: Option<String>,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Notebook {
+    // The JSON of the `# METADATA` block before the first cell (kernel, lakehouse).
+    pub metadata: Option<Value>,
+    pub cells: Vec<Cell>,
+}
+
+// The block a line of the source belongs to while parsing.
+enum Block {
+    Preamble,
+    Cell,
+    Metadata(Vec<String>),
+}
+
+impl fmt::Display for CellKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CellKind::Code => write!(f, "code"),
+            CellKind::Markdown => write!(f, "markdown"),
+            CellKind::Parameters => write!(f, "parameters"),
+            CellKind::Raw => write!(f, "raw"),
+        }
+    }
+}
+
+
+// ====================================================
+//  Parsing
+// ====================================================
+pub fn is_fabric_source(text: &str) -> bool {
+
+    // Whether `text` is a Fabric notebook source: its first non-blank line is
+    // `# Fabric notebook source`.
+
+    text.lines().find(|line| !line.trim().is_empty()).is_some_and(|line| line.trim_end() == FABRIC_HEADER)
+}
+
+
+pub fn parse_notebook(text: &str) -> Option<Notebook> {
+
+    // Parses a Fabric or Databricks notebook source into its cells; `None`
+    // when `text` is neither.
+
+    parse_fabric(text).or_else(|| parse_databricks(text))
+}
+
+
+pub fn parse_fabric(text: &str) -> Option<Notebook> {
+
+    // Parses a Fabric notebook source into its cells.
+    //
+    // # Description
+    // Cells start at `# CELL`, `# MARKDOWN` and `# PARAMETERS CELL` markers. A
+    // `# METADATA` block holds JSON on `# META` lines and belongs to the cell
+    // before it, or to the notebook when no cell came yet. Markdown lines lose
+    // their `# ` and magic lines (`# MAGIC %%sql`) their `# MAGIC ` prefix, so
+    // `source` is the text as it shows in Fabric. `None` when `text` is not a
+    // Fabric source.
+
+    if !is_fabric_source(text) {
+        return None;
+    }
+    let mut notebook = Notebook::default();
+    let mut block = Block::Preamble;
+    for (i, line) in text.lines().enumerate() {
+        let marker = line.trim_end();
+        let kind = match marker {
+            m if m.starts_with(PARAMETERS_MARKER) => Some(CellKind::Parameters),
+            m if m.starts_with(MARKDOWN_MARKER) => Some(CellKind::Markdown),
+            m if m.starts_with(CODE_MARKER) => Some(CellKind::Code),
+            _ => None,
+        };
+        if kind.is_some() || marker.starts_with(METADATA_MARKER) {
+            close_block(&mut notebook, block);
+            block = match kind {
+                Some(kind) => {
+                    let index = notebook.cells.len() + 1;
+                    notebook.cells.push(Cell { index, kind, first_line: i + 1, source: String::new(), metadata: None, outputs: Vec::new() });
+                    Block::Cell
+                }
+                None => Block::Metadata(Vec::new()),
+            };
+            continue;
+        }
+        match &mut block {
+            Block::Preamble => {}
+            Block::Metadata(json) => {
+                if let Some(rest) = line.trim_start().strip_prefix(META_LINE_PREFIX) {
+                    json.push(rest.strip_prefix(' ').unwrap_or(rest).to_string());
+                } else if let Some(cell) = notebook.cells.last_mut().filter(|_| !line.trim().is_empty()) {
+                    // Text after a metadata block without a new marker still
+                    // belongs to the cell.
+                    push_line(cell, line);
+                }
+            }
+            Block::Cell => {
+                if let Some(cell) = notebook.cells.last_mut() {
+                    push_line(cell, line);
+                }
+            }
+        }
+    }
+    close_block(&mut notebook, block);
+    for cell in &mut notebook.cells {
+        cell.source = cell.source.trim_matches('\n').to_string();
+    }
+    Some(notebook)
+}
+
+
+fn push_line(cell: &mut Cell, line: &str) {
+
+    // Adds one source line to a cell, without the Fabric comment prefixes.
+
+    let line = match cell.kind {
+        CellKind::Markdown => line.strip_prefix("# ").or_else(|| line.strip_prefix('#')).unwrap_or(line),
+        CellKind::Raw => line,
+        CellKind::Code | CellKind::Parameters => match line.strip_prefix(MAGIC_PREFIX) {
+            Some(rest) => rest.strip_prefix(' ').unwrap_or(rest),
+            None => line,
+        },
+    };
+    cell.source.push_str(line);
+    cell.source.push('\n');
+}
+
+
+fn close_block(notebook: &mut Notebook, block: Block) {
+
+    // Attaches the JSON of a finished metadata block to the last cell, or to
+    // the notebook before the first cell. JSON that does not parse is dropped.
+
+    let Block::Metadata(lines) = block else {
+        return;
+    };
+    let Ok(value) = serde_json::from_str::<Value>(&lines.join("\n")) else {
+        return;
+    };
+    match notebook.cells.last_mut() {
+        Some(cell) => cell.metadata = Some(value),
+        None => notebook.metadata = Some(value),
+    }
+}
+
+
+fn databricks_comment(text: &str) -> Option<&'static str> {
+
+    // Returns the comment marker of a Databricks export (`#` or `--`), read from
+    // its `Databricks notebook source` header.
+
+    let first = text.lines().find(|line| !line.trim().is_empty())?.trim_end();
+    ["#", "--"].into_iter().find(|c| first.strip_prefix(c).is_some_and(|rest| rest.trim() == DATABRICKS_HEADER))
+}
+
+
+pub fn parse_databricks(text: &str) -> Option<Notebook> {
+
+    // Parses a Databricks notebook source export (`.py` or `.sql`) into cells.
+    //
+    // # Description
+    // Cells are separated by `# COMMAND ----------` (`-- COMMAND ----------` in
+    // SQL). Lines of a cell in another language than the notebook are
+    // `# MAGIC` comments; the prefix is removed. A cell starting with `%md` is
+    // markdown (the `%md` line is dropped); `%sql`, `%python`, `%scala` and
+    // `%r` set the language of the cell, else it is that of the notebook. Text
+    // before the first marker is a cell when it has code, as in `--cell`.
+
+    let comment = databricks_comment(text)?;
+    let marker = format!("{comment} {DATABRICKS_MARKER}");
+    let magic = format!("{comment} MAGIC");
+    let default_language = match comment {
+        "--" => "sql",
+        _ => "python",
+    };
+
+    // (first line, lines) of every cell; the header line is not part of one.
+    let mut blocks: Vec<(usize, Vec<&str>)> = vec![(1, Vec::new())];
+    let mut header = true;
+    for (i, line) in text.lines().enumerate() {
+        if header && !line.trim().is_empty() {
+            header = false;
+            continue;
+        }
+        if line.starts_with(&marker) {
+            blocks.push((i + 1, Vec::new()));
+        } else if let Some((_, lines)) = blocks.last_mut() {
+            lines.push(line);
+        }
+    }
+    let preamble_has_code = blocks[0]
+        .1
+        .iter()
+        .any(|l| !l.trim().is_empty() && (!l.trim_start().starts_with(comment) || l.starts_with(&magic)));
+    if !preamble_has_code {
+        blocks.remove(0);
+    }
+
+    let cells = blocks
+        .into_iter()
+        .enumerate()
+        .map(|(i, (first_line, lines))| {
+            let mut source: Vec<&str> = lines
+                .into_iter()
+                .map(|line| match line.strip_prefix(&magic) {
+                    Some(rest) => rest.strip_prefix(' ').unwrap_or(rest),
+                    None => line,
+                })
+                .collect();
+            let first = source.iter().position(|l| !l.trim().is_empty());
+            let command = first.and_then(|at| source[at].trim().strip_prefix('%')).map(|c| c.split_whitespace().next().unwrap_or_default().to_lowercase());
+            let (kind, language) = match command.as_deref() {
+                Some("md") => {
+                    source.remove(first.unwrap_or_default());
+                    (CellKind::Markdown, None)
+                }
+                Some(lang) if DATABRICKS_LANGUAGES.contains(&lang) => (CellKind::Code, Some(lang.to_string())),
+                _ => (CellKind::Code, Some(default_language.to_string())),
+            };
+            let metadata = language.map(|l| serde_json::json!({ "language": l }));
+            let source = source.join("\n").trim_matches('\n').to_string();
+            Cell { index: i + 1, kind, first_line, source, metadata, outputs: Vec::new() }
+        })
+        .collect();
+    Some(Notebook { metadata: None, cells })
+}
+
+
+pub fn is_ipynb(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "ipynb")
+}
+
+
+pub fn parse_ipynb(text: &str, outputs: bool) -> Result<Notebook, DoxcerError> {
+
+    // Parses a Jupyter notebook (nbformat 4 JSON) into the same cells as a
+    // Fabric source.
+    //
+    // # Description
+    // Code, markdown and raw cells keep their position, so the numbers match
+    // `--cell`. A code cell tagged `parameters` is the parameters cell. The
+    // language comes from the cell metadata, else from the kernel of the
+    // notebook. With `outputs`, the text of stream, result and error outputs
+    // is kept, up to `OUTPUT_LIMIT` characters per cell; images and HTML
+    // are never included.
+
+    let json: Value = serde_json::from_str(text).map_err(|e| DoxcerError::Input(format!("not a Jupyter notebook: {e}")))?;
+    let cells = json.get("cells").and_then(Value::as_array).ok_or_else(|| DoxcerError::Input("not a Jupyter notebook: no `cells` list".to_string()))?;
+    let kernel_language = json
+        .pointer("/metadata/kernelspec/language")
+        .or_else(|| json.pointer("/metadata/language_info/name"))
+        .and_then(Value::as_str);
+    let notebook_metadata = json.get("metadata").cloned();
+
+    let cells = cells
+        .iter()
+        .enumerate()
+        .map(|(i, cell)| {
+            let tagged = cell
+                .pointer("/metadata/tags")
+                .and_then(Value::as_array)
+                .is_some_and(|tags| tags.iter().any(|t| t.as_str() == Some(PARAMETERS_TAG)));
+            let kind = match cell.get("cell_type").and_then(Value::as_str) {
+                Some("markdown") => CellKind::Markdown,
+                Some("raw") => CellKind::Raw,
+                _ if tagged => CellKind::Parameters,
+                _ => CellKind::Code,
+            };
+            let mut metadata = cell.get("metadata").cloned().filter(|m| m.as_object().is_some_and(|o| !o.is_empty()));
+            if let (CellKind::Code | CellKind::Parameters, Some(language)) = (kind, kernel_language) {
+                let object = metadata.get_or_insert_with(|| Value::Object(Default::default()));
+                if let Some(object) = object.as_object_mut() {
+                    object.entry("language").or_insert_with(|| Value::String(language.to_string()));
+                }
+            }
+            let outputs = match outputs {
+                true => cell_outputs(cell),
+                false => Vec::new(),
+            };
+            Cell { index: i + 1, kind, first_line: 0, source: joined_text(cell.get("source")).trim_matches('\n').to_string(), metadata, outputs }
+        })
+        .collect();
+    Ok(Notebook { metadata: notebook_metadata, cells })
+}
+
+
+pub fn output_count(text: &str) -> usize {
+
+    // Counts the stored outputs of a Jupyter notebook, for the summary of
+    // what was left out of the prompt.
+
+    let Ok(json) = serde_json::from_str::<Value>(text) else {
+        return 0;
+    };
+    json.get("cells")
+        .and_then(Value::as_array)
+        .map(|cells| cells.iter().filter_map(|c| c.get("outputs").and_then(Value::as_array)).map(Vec::len).sum())
+        .unwrap_or_default()
+}
+
+
+pub fn joined_text(value: Option<&Value>) -> String {
+
+    // nbformat stores text as one string or as a list of lines.
+
+    match value {
+        Some(Value::Array(parts)) => parts.iter().filter_map(Value::as_str).collect(),
+        Some(Value::String(s)) => s.clone(),
+        _ => String::new(),
+    }
+}
+
+
+fn cell_outputs(cell: &Value) -> Vec<String> {
+
+    // Returns the text of the outputs of a code cell, cut at `OUTPUT_LIMIT`
+    // characters in total.
+
+    let outputs = cell.get("outputs").and_then(Value::as_array).cloned().unwrap_or_default();
+    let mut left = OUTPUT_LIMIT;
+    let mut texts = Vec::new();
+    for output in &outputs {
+        let text = match output.get("output_type").and_then(Value::as_str) {
+            Some("stream") => joined_text(output.get("text")),
+            Some("execute_result" | "display_data") => joined_text(output.pointer("/data/text~1plain")),
+            Some("error") => format!(
+                "{}: {}",
+                output.get("ename").and_then(Value::as_str).unwrap_or("Error"),
+                output.get("evalue").and_then(Value::as_str).unwrap_or_default()
+            ),
+            _ => String::new(),
+        };
+        let text = text.trim_end();
+        if text.is_empty() || left == 0 {
+            continue;
+        }
+        let kept: String = text.chars().take(left).collect();
+        left -= kept.chars().count();
+        match kept.len() < text.len() {
+            true => texts.push(format!("{kept}\n[... output ingekort]")),
+            false => texts.push(kept),
+        }
+    }
+    texts
+}
+
+
+// ====================================================
+//  Cells
+// ====================================================
+impl Cell {
+
+    pub fn language(&self) -> Option<String> {
+
+        // Returns the language of the cell: a leading `%%` magic (`%%sql`),
+        // else `language` in its metadata. Markdown cells have none.
+
+        if matches!(self.kind, CellKind::Markdown | CellKind::Raw) {
+            return None;
+        }
+        let magic = self.source.lines().find(|line| !line.trim().is_empty()).and_then(|line| line.trim().strip_prefix("%%"));
+        if let Some(magic) = magic.and_then(|m| m.split_whitespace().next()) {
+            return Some(magic.to_string());
+        }
+        self.metadata.as_ref()?.get("language")?.as_str().map(str::to_string)
+    }
+}
+
+
+// ====================================================
+//  Rendering
+// ====================================================
+impl Notebook {
+
+    pub fn render(&self) -> String {
+
+        // Renders the notebook for a prompt: every cell under a numbered
+        // marker with its kind and language, without the metadata blocks,
+        // followed by its outputs if any were kept. Empty cells are left out;
+        // the numbers stay those of the source.
+
+        let mut out = String::new();
+        for cell in self.cells.iter().filter(|cell| !cell.source.trim().is_empty()) {
+            let language = cell.language().map(|l| format!(", {l}")).unwrap_or_default();
+            out.push_str(&format!("# CELL {} ({}{language})\n{}\n\n", cell.index, cell.kind, cell.source));
+            for output in &cell.outputs {
+                out.push_str(&format!("# OUTPUT {}\n{output}\n\n", cell.index));
+            }
+        }
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+        out
+    }
+}
+
+
+pub fn rendered_cells(text: &str) -> Vec<RenderedCell> {
+
+    // Reads the cells back from a notebook as `Notebook::render` wrote it, for
+    // the `cells` template variable. Outputs are left out. Text without
+    // `# CELL <n> (<kind>)` markers, such as a SQL script, is one code cell.
+
+    let mut cells: Vec<RenderedCell> = Vec::new();
+    let mut in_output = false;
+    for line in text.lines() {
+        if let Some((index, kind, language)) = rendered_marker(line) {
+            cells.push(RenderedCell { index, kind, language, source: String::new() });
+            in_output = false;
+            continue;
+        }
+        if line.starts_with("# OUTPUT ") && !cells.is_empty() {
+            in_output = true;
+            continue;
+        }
+        if in_output {
+            continue;
+        }
+        if cells.is_empty() {
+            cells.push(RenderedCell { index: 1, kind: CellKind::Code.to_string(), language: None, source: String::new() });
+        }
+        if let Some(cell) = cells.last_mut() {
+            cell.source.push_str(line);
+            cell.source.push('\n');
+        }
+    }
+    for cell in &mut cells {
+        cell.source = cell.source.trim_matches('\n').to_string();
+    }
+    cells.retain(|cell| !cell.source.trim().is_empty());
+    cells
+}
+
+
+fn rendered_marker(line: &str) -> Option<(usize, String, Option<String>)> {
+
+    // Parses `# CELL 3 (code, python)` into its number, kind and language.
+
+    let (index, rest) = line.strip_prefix("# CELL ")?.split_once(' ')?;
+    let index = index.parse().ok()?;
+    let inner = rest.strip_prefix('(')?.strip_suffix(')')?;
+    let (kind, language) = match inner.split_once(", ") {
+        Some((kind, language)) => (kind, Some(language.to_string())),
+        None => (inner, None),
+    };
+    Some((index, kind.to_string(), language))
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FABRIC: &str = "\
+# Fabric notebook source
+
+# METADATA ********************
+
+# META {
+# META   \"kernel_info\": {\"name\": \"synapse_pyspark\"}
+# META }
+
+# PARAMETERS CELL ********************
+
+run_date = '2026-01-01'
+
+# CELL ********************
+
+df = spark.read.table('orders')
+
+# METADATA ********************
+
+# META {\"language\": \"python\"}
+
+# MARKDOWN ********************
+
+# # Orders
+# Reads the orders.
+
+# CELL ********************
+
+# MAGIC %%sql
+# MAGIC SELECT * FROM orders
+
+# CELL ********************
+
+
+# CELL ********************
+
+display(df)
+";
+
+    fn sources(notebook: &Notebook) -> Vec<(CellKind, &str)> {
+        notebook.cells.iter().map(|cell| (cell.kind, cell.source.as_str())).collect()
+    }
+
+
+    #[test]
+    fn a_fabric_source_splits_into_cells_at_its_markers() {
+        let notebook = parse_fabric(FABRIC).unwrap();
+        assert_eq!(
+            sources(&notebook),
+            [
+                (CellKind::Parameters, "run_date = '2026-01-01'"),
+                (CellKind::Code, "df = spark.read.table('orders')"),
+                (CellKind::Markdown, "# Orders\nReads the orders."),
+                (CellKind::Code, "%%sql\nSELECT * FROM orders"),
+                (CellKind::Code, ""),
+                (CellKind::Code, "display(df)"),
+            ]
+        );
+        let first_lines: Vec<usize> = notebook.cells.iter().map(|cell| cell.first_line).collect();
+        assert_eq!(first_lines, [9, 13, 21, 26, 31, 34]);
+    }
+
+
+    #[test]
+    fn fabric_metadata_goes_to_the_notebook_or_the_cell_before_it() {
+        let notebook = parse_fabric(FABRIC).unwrap();
+        assert_eq!(notebook.metadata, Some(serde_json::json!({"kernel_info": {"name": "synapse_pyspark"}})));
+        assert_eq!(notebook.cells[1].metadata, Some(serde_json::json!({"language": "python"})));
+        assert_eq!(notebook.cells[0].metadata, None);
+        assert!(!notebook.render().contains("META"));
+    }
+
+
+    #[test]
+    fn a_fabric_magic_sets_the_language_of_its_cell() {
+        let notebook = parse_fabric(FABRIC).unwrap();
+        let languages: Vec<Option<String>> = notebook.cells.iter().map(Cell::language).collect();
+        assert_eq!(languages, [None, Some("python".to_string()), None, Some("sql".to_string()), None, None]);
+    }
+
+
+    #[test]
+    fn empty_fabric_cells_keep_their_number_but_are_not_rendered() {
+        let rendered = parse_fabric(FABRIC).unwrap().render();
+        assert!(rendered.contains("# CELL 4 (code, sql)\n%%sql\nSELECT * FROM orders\n\n# CELL 6 (code)\ndisplay(df)\n"), "{rendered}");
+        assert!(!rendered.contains("# CELL 5"), "{rendered}");
+        let numbers: Vec<usize> = rendered_cells(&rendered).iter().map(|cell| cell.index).collect();
+        assert_eq!(numbers, [1, 2, 3, 4, 6]);
+    }
+
+
+    #[test]
+    fn text_without_the_fabric_header_is_not_a_fabric_source() {
+        assert!(parse_fabric("df = spark.read.table('orders')\n").is_none());
+        assert!(parse_fabric("\n\n# Fabric notebook source\n").is_some_and(|notebook| notebook.cells.is_empty()));
+        assert!(!is_fabric_source(""));
+    }
+
+
+    const IPYNB: &str = r##"{
+  "nbformat": 4,
+  "metadata": {"kernelspec": {"name": "python3", "language": "python"}},
+  "cells": [
+    {"cell_type": "markdown", "metadata": {}, "source": ["# Orders\n", "Reads the orders."]},
+    {"cell_type": "code", "metadata": {"tags": ["parameters"]}, "source": "run_date = '2026-01-01'\n", "outputs": []},
+    {"cell_type": "code", "metadata": {}, "source": ["%%sql\n", "SELECT * FROM orders"], "outputs": []},
+    {"cell_type": "code", "metadata": {}, "source": [], "outputs": []},
+    {"cell_type": "raw", "metadata": {}, "source": "raw text"},
+    {"cell_type": "code", "metadata": {}, "source": "df.count()", "outputs": [
+      {"output_type": "stream", "name": "stdout", "text": ["loaded\n"]},
+      {"output_type": "execute_result", "data": {"text/plain": ["42"], "image/png": "iVBOR"}},
+      {"output_type": "error", "ename": "ValueError", "evalue": "bad"}
+    ]}
+  ]
+}"##;
+
+
+    #[test]
+    fn a_jupyter_notebook_keeps_every_cell_in_order() {
+        let notebook = parse_ipynb(IPYNB, false).unwrap();
+        assert_eq!(
+            sources(&notebook),
+            [
+                (CellKind::Markdown, "# Orders\nReads the orders."),
+                (CellKind::Parameters, "run_date = '2026-01-01'"),
+                (CellKind::Code, "%%sql\nSELECT * FROM orders"),
+                (CellKind::Code, ""),
+                (CellKind::Raw, "raw text"),
+                (CellKind::Code, "df.count()"),
+            ]
+        );
+        assert!(notebook.cells.iter().all(|cell| cell.outputs.is_empty()));
+    }
+
+
+    #[test]
+    fn a_jupyter_magic_wins_over_the_kernel_language() {
+        let notebook = parse_ipynb(IPYNB, false).unwrap();
+        let languages: Vec<Option<String>> = notebook.cells.iter().map(Cell::language).collect();
+        let python = Some("python".to_string());
+        assert_eq!(languages, [None, python.clone(), Some("sql".to_string()), python.clone(), None, python]);
+    }
+
+
+    #[test]
+    fn empty_jupyter_cells_keep_their_number_but_are_not_rendered() {
+        let rendered = parse_ipynb(IPYNB, false).unwrap().render();
+        assert!(!rendered.contains("# CELL 4"), "{rendered}");
+        let numbers: Vec<usize> = rendered_cells(&rendered).iter().map(|cell| cell.index).collect();
+        assert_eq!(numbers, [1, 2, 3, 5, 6]);
+    }
+
+
+    #[test]
+    fn jupyter_outputs_are_kept_as_text_only_when_asked() {
+        let notebook = parse_ipynb(IPYNB, true).unwrap();
+        assert_eq!(notebook.cells[5].outputs, ["loaded", "42", "ValueError: bad"]);
+        assert_eq!(output_count(IPYNB), 3);
+        let rendered = notebook.render();
+        assert!(rendered.ends_with("# OUTPUT 6\nValueError: bad\n"), "{rendered}");
+        assert!(!rendered.contains("iVBOR"));
+    }
+
+
+    #[test]
+    fn text_that_is_not_a_jupyter_notebook_is_an_input_error() {
+        for text in ["# Fabric notebook source\n", r#"{"nbformat": 4}"#] {
+            let error = parse_ipynb(text, false).unwrap_err();
+            assert!(matches!(error, DoxcerError::Input(_)), "{text}");
+            assert!(error.to_string().contains("not a Jupyter notebook"), "{error}");
+        }
+    }
+
+
+    const DATABRICKS: &str = "\
+# Databricks notebook source
+df = spark.read.table('orders')
+
+# COMMAND ----------
+
+# MAGIC %md
+# MAGIC # Orders
+# MAGIC Reads the orders.
+
+# COMMAND ----------
+
+# MAGIC %sql
+# MAGIC SELECT * FROM orders
+
+# COMMAND ----------
+
+# COMMAND ----------
+
+display(df)
+";
+
+
+    #[test]
+    fn a_databricks_source_splits_into_cells_at_command_markers() {
+        let notebook = parse_databricks(DATABRICKS).unwrap();
+        assert_eq!(
+            sources(&notebook),
+            [
+                (CellKind::Code, "df = spark.read.table('orders')"),
+                (CellKind::Markdown, "# Orders\nReads the orders."),
+                (CellKind::Code, "%sql\nSELECT * FROM orders"),
+                (CellKind::Code, ""),
+                (CellKind::Code, "display(df)"),
+            ]
+        );
+        let first_lines: Vec<usize> = notebook.cells.iter().map(|cell| cell.first_line).collect();
+        assert_eq!(first_lines, [1, 4, 10, 15, 17]);
+        assert_eq!(parse_notebook(DATABRICKS), Some(notebook));
+    }
+
+
+    #[test]
+    fn a_databricks_magic_sets_the_language_of_its_cell() {
+        let notebook = parse_databricks(DATABRICKS).unwrap();
+        let languages: Vec<Option<String>> = notebook.cells.iter().map(Cell::language).collect();
+        let python = Some("python".to_string());
+        assert_eq!(languages, [python.clone(), None, Some("sql".to_string()), python.clone(), python]);
+    }
+
+
+    #[test]
+    fn a_databricks_sql_export_uses_sql_comments() {
+        let text = "-- Databricks notebook source\nSELECT 1\n\n-- COMMAND ----------\n\n-- MAGIC %python\n-- MAGIC df.count()\n";
+        let notebook = parse_databricks(text).unwrap();
+        assert_eq!(sources(&notebook), [(CellKind::Code, "SELECT 1"), (CellKind::Code, "%python\ndf.count()")]);
+        let languages: Vec<Option<String>> = notebook.cells.iter().map(Cell::language).collect();
+        assert_eq!(languages, [Some("sql".to_string()), Some("python".to_string())]);
+    }
+
+
+    #[test]
+    fn empty_databricks_cells_keep_their_number_but_are_not_rendered() {
+        let rendered = parse_databricks(DATABRICKS).unwrap().render();
+        assert!(!rendered.contains("# CELL 4"), "{rendered}");
+        let numbers: Vec<usize> = rendered_cells(&rendered).iter().map(|cell| cell.index).collect();
+        assert_eq!(numbers, [1, 2, 3, 5]);
+    }
+
+
+    #[test]
+    fn a_databricks_preamble_of_only_comments_is_not_a_cell() {
+        let text = "# Databricks notebook source\n# Owner: sales\n\n# COMMAND ----------\n\nx = 1\n";
+        let notebook = parse_databricks(text).unwrap();
+        assert_eq!(sources(&notebook), [(CellKind::Code, "x = 1")]);
+        assert_eq!(notebook.cells[0].index, 1);
+        assert!(parse_databricks("# Fabric notebook source\n").is_none());
+    }
+}
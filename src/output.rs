@@ -0,0 +1,101 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::io::{self, ErrorKind, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Internal Modules
+use crate::style::Style;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+static STDOUT_CLOSED: AtomicBool = AtomicBool::new(false);
+
+
+// ====================================================
+//  Stdout Writing
+// ====================================================
+pub fn write_stdout(text: &str) {
+
+    // Writes to stdout, handling a reader that went away.
+    //
+    // # Description
+    // When stdout is a pipe whose reader exited early (`doxcer ... | head`), the
+    // write fails with `BrokenPipe`. Instead of panicking like `println!`, all
+    // further stdout output is dropped and the run carries on, so batch runs
+    // still write their files and single-file runs still exit cleanly. Other
+    // write errors are reported once on stderr and handled the same way.
+
+    if STDOUT_CLOSED.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut stdout = io::stdout().lock();
+    if let Err(e) = stdout.write_all(text.as_bytes()).and_then(|_| stdout.flush()) {
+        STDOUT_CLOSED.store(true, Ordering::Relaxed);
+        if e.kind() != ErrorKind::BrokenPipe {
+            write_stderr(&format!("{} stdout is no longer writable ({e}); further output is suppressed\n", Style::stderr().warning_prefix()));
+        }
+    }
+}
+
+
+// ====================================================
+//  Stderr Writing
+// ====================================================
+pub fn write_stderr(text: &str) {
+
+    // Writes to stderr, ignoring a reader that went away.
+    //
+    // # Description
+    // `eprintln!` panics when stderr is closed (`doxcer nb.py 2>&1 | head -1`
+    // once `head` exited). Diagnostics are best effort, so a failed write is
+    // dropped and the run carries on with its exit code.
+
+    let mut stderr = io::stderr().lock();
+    let _ = stderr.write_all(text.as_bytes()).and_then(|_| stderr.flush());
+}
+
+
+#[macro_export]
+macro_rules! out {
+    ($($arg:tt)*) => {
+        $crate::output::write_stdout(&format!($($arg)*))
+    };
+}
+
+
+#[macro_export]
+macro_rules! outln {
+    () => {
+        $crate::output::write_stdout("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::output::write_stdout(&format!("{}\n", format_args!($($arg)*)))
+    };
+}
+
+
+#[macro_export]
+macro_rules! err {
+    ($($arg:tt)*) => {
+        $crate::output::write_stderr(&format!($($arg)*))
+    };
+}
+
+
+#[macro_export]
+macro_rules! errln {
+    () => {
+        $crate::output::write_stderr("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::output::write_stderr(&format!("{}\n", format_args!($($arg)*)))
+    };
+}
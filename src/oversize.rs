@@ -0,0 +1,327 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+// External Libraries
+use serde::Deserialize;
+use tiktoken_rs::CoreBPE;
+use tiktoken_rs::tokenizer::{Tokenizer, get_tokenizer};
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::excerpt::split_cells;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+// Context windows in tokens, matched on the start of the model name; the first
+// match wins, so longer prefixes come first.
+pub const MODEL_CONTEXT_TOKENS: [(&str, usize); 8] = [
+    ("gpt-5", 400_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4", 200_000),
+    ("claude", 200_000),
+    ("llama", 128_000),
+];
+pub const FALLBACK_CONTEXT_TOKENS: usize = 128_000;
+// Room kept free for the answer when `max_output_tokens` is not set.
+pub const DEFAULT_OUTPUT_RESERVE_TOKENS: usize = 16_000;
+pub const CHARS_PER_TOKEN: usize = 4;
+pub const LARGEST_CELLS: usize = 5;
+pub const COVERAGE_KEY: &str = "doxcer_coverage";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OversizePolicy {
+    // A prompt over the limit is not sent.
+    #[default]
+    Fail,
+    // It is sent whole, with a warning; for when the estimate is too cautious.
+    Warn,
+    // Only the first part of the notebook that fits is sent (`--force-oversize`).
+    Truncate,
+    // The notebook is documented in chunks that are merged (`--chunk-strategy`).
+    Chunk,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellSize {
+    pub index: usize,
+    pub title: Option<String>,
+    pub first_line: usize,
+    pub last_line: usize,
+    pub tokens: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeReport {
+    pub notebook: String,
+    pub total_tokens: usize,
+    pub notebook_tokens: usize,
+    pub limit: usize,
+    pub largest: Vec<CellSize>,
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is too large: the prompt is about {} tokens, over the limit of {} (--max-total-tokens); \
+             the notebook itself is ~{} tokens before trimming",
+            self.notebook, self.total_tokens, self.limit, self.notebook_tokens
+        )?;
+        if !self.largest.is_empty() {
+            write!(f, "; largest cells:")?;
+        }
+        for cell in &self.largest {
+            let title = cell.title.as_ref().map(|t| format!(" {t:?}")).unwrap_or_default();
+            write!(f, "\n  cell {}{title} (lines {}-{}): ~{} tokens", cell.index, cell.first_line, cell.last_line, cell.tokens)?;
+        }
+        Ok(())
+    }
+}
+
+
+impl fmt::Display for OversizePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OversizePolicy::Fail => write!(f, "fail"),
+            OversizePolicy::Warn => write!(f, "warn"),
+            OversizePolicy::Truncate => write!(f, "truncate"),
+            OversizePolicy::Chunk => write!(f, "chunk"),
+        }
+    }
+}
+
+impl FromStr for OversizePolicy {
+    type Err = DoxcerError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "fail" => Ok(OversizePolicy::Fail),
+            "warn" => Ok(OversizePolicy::Warn),
+            "truncate" => Ok(OversizePolicy::Truncate),
+            "chunk" => Ok(OversizePolicy::Chunk),
+            other => Err(DoxcerError::Usage(format!("expected fail, warn, truncate or chunk, got {other:?}"))),
+        }
+    }
+}
+
+
+// ====================================================
+//  Estimation
+// ====================================================
+pub fn estimate_tokens(text: &str) -> usize {
+
+    // Estimates the token count of a text at four characters per token, which
+    // is close enough for code and prose to decide whether a prompt fits.
+
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+
+pub fn count_tokens(text: &str, model: &str) -> usize {
+
+    // Counts the tokens of a text for a model.
+    //
+    // # Description
+    // OpenAI models (also as an Azure deployment or `openai/...` name) are
+    // counted exactly with their tiktoken encoding. Other models, such as
+    // Claude or Llama, have no public tokenizer here and fall back to
+    // [`estimate_tokens`].
+
+    match tokenizer(model) {
+        Some(bpe) => bpe.encode_with_special_tokens(text).len(),
+        None => estimate_tokens(text),
+    }
+}
+
+
+fn tokenizer(model: &str) -> Option<&'static CoreBPE> {
+
+    // Returns the shared tiktoken encoding of a model, if it has one.
+
+    let model = model.rsplit('/').next().unwrap_or(model);
+    match get_tokenizer(model)? {
+        Tokenizer::O200kBase => Some(tiktoken_rs::o200k_base_singleton()),
+        Tokenizer::O200kHarmony => Some(tiktoken_rs::o200k_harmony_singleton()),
+        Tokenizer::Cl100kBase => Some(tiktoken_rs::cl100k_base_singleton()),
+        Tokenizer::P50kBase => Some(tiktoken_rs::p50k_base_singleton()),
+        Tokenizer::P50kEdit => Some(tiktoken_rs::p50k_edit_singleton()),
+        Tokenizer::R50kBase | Tokenizer::Gpt2 => Some(tiktoken_rs::r50k_base_singleton()),
+    }
+}
+
+
+pub fn context_tokens(model: &str) -> usize {
+
+    // Returns the context window of a model, see `MODEL_CONTEXT_TOKENS`.
+
+    let model = model.rsplit('/').next().unwrap_or(model);
+    MODEL_CONTEXT_TOKENS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map_or(FALLBACK_CONTEXT_TOKENS, |(_, tokens)| *tokens)
+}
+
+
+pub fn default_max_total_tokens(model: &str, max_output_tokens: Option<u32>) -> usize {
+
+    // Returns the default `--max-total-tokens`: the model's context window minus
+    // the room reserved for the answer.
+
+    let reserve = max_output_tokens.map_or(DEFAULT_OUTPUT_RESERVE_TOKENS, |t| t as usize);
+    context_tokens(model).saturating_sub(reserve)
+}
+
+
+pub fn size_report(path: &Path, notebook: &str, model: &str, total_tokens: usize, limit: usize) -> SizeReport {
+
+    // Builds the report for a notebook over the limit, with its largest cells
+    // so the author knows what to split off.
+
+    let mut cells: Vec<CellSize> = split_cells(path, notebook)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|c| CellSize {
+            index: c.index,
+            title: c.title,
+            first_line: c.first_line,
+            last_line: c.last_line,
+            tokens: count_tokens(&c.text, model),
+        })
+        .collect();
+    cells.sort_by(|a, b| b.tokens.cmp(&a.tokens).then(a.index.cmp(&b.index)));
+    cells.truncate(LARGEST_CELLS);
+    SizeReport {
+        notebook: path.display().to_string(),
+        total_tokens,
+        notebook_tokens: count_tokens(notebook, model),
+        limit,
+        largest: cells,
+    }
+}
+
+
+// ====================================================
+//  Partial Coverage
+// ====================================================
+pub fn truncate_to_tokens<'a>(text: &'a str, tokens: usize, model: &str) -> &'a str {
+
+    // Returns the longest run of whole lines from the start of `text` that
+    // stays within `tokens` for `model`, found by a binary search over the
+    // line ends so a long notebook is only counted a few times.
+
+    let ends: Vec<usize> = text
+        .split_inclusive('\n')
+        .scan(0, |end, line| {
+            *end += line.len();
+            Some(*end)
+        })
+        .collect();
+    let fitting = ends.partition_point(|&end| count_tokens(&text[..end], model) <= tokens);
+    &text[..fitting.checked_sub(1).map_or(0, |last| ends[last])]
+}
+
+
+pub fn partial_banner(name: &str, covered: usize, total: usize) -> String {
+
+    // Returns the note put above a partially sent notebook (`--force-oversize`).
+
+    format!(
+        "Let op: {name} is te groot om in zijn geheel te versturen. Hieronder staan alleen de eerste \
+         ~{covered} van ~{total} tokens. Documenteer alleen wat hieronder staat en vermeld bovenaan \
+         de documentatie dat deze het notebook slechts gedeeltelijk beschrijft."
+    )
+}
+
+
+pub fn coverage_note(covered: usize, total: usize) -> String {
+
+    // Returns the `doxcer_coverage` front-matter value of a partial document.
+
+    format!("partial (first ~{covered} of ~{total} notebook tokens)")
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn estimates_four_characters_per_token_rounded_up() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abc"), 1);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        assert_eq!(estimate_tokens(&"x".repeat(4000)), 1000);
+    }
+
+
+    #[test]
+    fn estimate_counts_characters_not_bytes() {
+        assert_eq!(estimate_tokens("éééé"), 1);
+        assert_eq!(estimate_tokens("€€€€€"), 2);
+    }
+
+
+    #[test]
+    fn openai_models_are_counted_with_their_tokenizer() {
+        assert_eq!(count_tokens("hello world", "gpt-4o"), 2);
+        assert_eq!(count_tokens("hello world", "gpt-4"), 2);
+        assert_eq!(count_tokens("hello world", "openai/gpt-5-mini"), 2);
+        let code = "df = spark.read.table('orders').filter(col('amount') > 0)\n";
+        assert_ne!(count_tokens(code, "gpt-5"), estimate_tokens(code));
+        assert_eq!(count_tokens("", "gpt-5"), 0);
+    }
+
+
+    #[test]
+    fn other_models_fall_back_to_the_estimate() {
+        let code = "df = spark.read.table('orders').filter(col('amount') > 0)\n";
+        for model in ["claude-sonnet-4-5", "llama3", "mistral-large", ""] {
+            assert_eq!(count_tokens(code, model), estimate_tokens(code), "{model}");
+        }
+    }
+
+
+    #[test]
+    fn truncation_keeps_whole_lines_within_the_budget() {
+        let text = "abcd\nefg\nhijk\n";
+        assert_eq!(truncate_to_tokens(text, 3, "llama3"), "abcd\nefg\n");
+        assert_eq!(truncate_to_tokens(text, 1, "llama3"), "");
+        assert_eq!(truncate_to_tokens(text, 4, "llama3"), text);
+
+        let counted = truncate_to_tokens(text, 4, "gpt-4o");
+        assert!(counted.len() < text.len() && counted.ends_with('\n'), "{counted:?}");
+        assert!(count_tokens(counted, "gpt-4o") <= 4);
+    }
+
+
+    #[test]
+    fn default_limit_leaves_room_for_the_answer() {
+        assert_eq!(default_max_total_tokens("gpt-5-mini", None), 400_000 - DEFAULT_OUTPUT_RESERVE_TOKENS);
+        assert_eq!(default_max_total_tokens("openai/gpt-4o", Some(4_000)), 124_000);
+        assert_eq!(default_max_total_tokens("unknown", Some(200_000)), 0);
+    }
+}
@@ -0,0 +1,380 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+// External Libraries
+use regex_automata::meta::Regex;
+use serde::Deserialize;
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+// Detectors in the order they are applied; earlier kinds win where matches
+// overlap (the digits of an IBAN are never also reported as a BSN).
+pub const PII_PATTERNS: [(PiiKind, &str); 4] = [
+    (PiiKind::Email, r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}"),
+    (PiiKind::Iban, r"\b[A-Z]{2}[0-9]{2}(?: ?[A-Z0-9]){11,30}"),
+    (PiiKind::Phone, r"\+[1-9](?:[ -]?[0-9]){7,14}\b"),
+    (PiiKind::Bsn, r"\b[0-9]{9}\b"),
+];
+pub const IBAN_MIN_CHARS: usize = 15;
+pub const IBAN_MAX_CHARS: usize = 34;
+pub const PII_AUDIT_ARTIFACT: &str = "pii-placeholders.json";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PiiPolicy {
+    #[default]
+    Off,
+    Warn,
+    Redact,
+    Fail,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PiiKind {
+    Email,
+    Phone,
+    Iban,
+    Bsn,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PiiFinding {
+    pub kind: PiiKind,
+    pub line: usize,
+    pub placeholder: String,
+    pub policy: PiiPolicy,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PiiReport {
+    pub findings: Vec<PiiFinding>,
+    // Placeholder → original value; for the audit artifact only.
+    pub placeholders: BTreeMap<String, String>,
+}
+
+impl PiiKind {
+
+    pub fn label(&self) -> &'static str {
+
+        // Returns the placeholder prefix, as in `<EMAIL_1>`.
+
+        match self {
+            PiiKind::Email => "EMAIL",
+            PiiKind::Phone => "PHONE",
+            PiiKind::Iban => "IBAN",
+            PiiKind::Bsn => "BSN",
+        }
+    }
+
+
+    pub fn describe(&self) -> &'static str {
+
+        // Returns the name used in findings.
+
+        match self {
+            PiiKind::Email => "email address",
+            PiiKind::Phone => "phone number",
+            PiiKind::Iban => "IBAN",
+            PiiKind::Bsn => "BSN",
+        }
+    }
+}
+
+impl fmt::Display for PiiPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PiiPolicy::Off => write!(f, "off"),
+            PiiPolicy::Warn => write!(f, "warn"),
+            PiiPolicy::Redact => write!(f, "redact"),
+            PiiPolicy::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+impl FromStr for PiiPolicy {
+    type Err = DoxcerError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "off" => Ok(PiiPolicy::Off),
+            "warn" => Ok(PiiPolicy::Warn),
+            "redact" => Ok(PiiPolicy::Redact),
+            "fail" => Ok(PiiPolicy::Fail),
+            other => Err(DoxcerError::Usage(format!("expected off, warn, redact or fail, got {other:?}"))),
+        }
+    }
+}
+
+impl fmt::Display for PiiFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcome = match self.policy {
+            PiiPolicy::Off | PiiPolicy::Warn => "left in the prompt".to_string(),
+            PiiPolicy::Redact => format!("replaced by {}", self.placeholder),
+            PiiPolicy::Fail => "the notebook is not sent".to_string(),
+        };
+        write!(f, "line {}: possible {} found; {outcome}", self.line, self.kind.describe())
+    }
+}
+
+
+// ====================================================
+//  Validation
+// ====================================================
+pub fn is_valid_iban(value: &str) -> bool {
+
+    // Checks an IBAN with the ISO 13616 mod-97 check (spaces are ignored).
+    //
+    // # Description
+    // The first four characters move to the end, letters become 10..35, and the
+    // resulting number must leave remainder 1 when divided by 97.
+
+    let compact: String = value.chars().filter(|c| *c != ' ').collect();
+    if !(IBAN_MIN_CHARS..=IBAN_MAX_CHARS).contains(&compact.len()) || !has_iban_shape(&compact) {
+        return false;
+    }
+    let (head, tail) = compact.split_at(4);
+    let mut remainder = 0u32;
+    for c in tail.chars().chain(head.chars()) {
+        let digit = c.to_digit(36).unwrap_or(0);
+        remainder = if digit < 10 { (remainder * 10 + digit) % 97 } else { (remainder * 100 + digit) % 97 };
+    }
+    remainder == 1
+}
+
+
+fn has_iban_shape(value: &str) -> bool {
+
+    // Two capitals, two check digits, then capitals and digits only.
+
+    let b = value.as_bytes();
+    b.len() > 4
+        && b[..2].iter().all(u8::is_ascii_uppercase)
+        && b[2..4].iter().all(u8::is_ascii_digit)
+        && b[4..].iter().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+
+pub fn is_valid_bsn(value: &str) -> bool {
+
+    // Checks a Dutch BSN with the 11-test: the digits weighted 9 down to 2, and
+    // the last one by -1, must sum to a multiple of 11. All zeros is rejected.
+
+    let digits: Vec<i64> = value.chars().filter_map(|c| c.to_digit(10)).map(i64::from).collect();
+    if digits.len() != 9 || value.chars().count() != 9 || digits.iter().all(|d| *d == 0) {
+        return false;
+    }
+    let sum: i64 = digits
+        .iter()
+        .zip([9, 8, 7, 6, 5, 4, 3, 2, -1])
+        .map(|(d, w)| d * w)
+        .sum();
+    sum % 11 == 0
+}
+
+
+pub fn is_valid_phone(value: &str) -> bool {
+
+    // Checks the E.164 length: a `+` and 8 to 15 digits.
+
+    let digits = value.chars().filter(char::is_ascii_digit).count();
+    value.starts_with('+') && (8..=15).contains(&digits)
+}
+
+
+fn valid_iban_prefix(candidate: &str) -> Option<usize> {
+
+    // Returns the byte length of the longest prefix of a matched IBAN candidate
+    // that passes the check and ends a word, since the match may have run on
+    // into the text that follows (`NL91 ABNA 0417 1643 00 MAAND`).
+
+    (IBAN_MIN_CHARS..=candidate.len())
+        .rev()
+        .filter(|end| candidate.as_bytes().get(*end).is_none_or(|c| !c.is_ascii_alphanumeric()))
+        .find(|end| is_valid_iban(&candidate[..*end]))
+}
+
+
+// ====================================================
+//  Scanning
+// ====================================================
+pub fn scan_pii(text: &str, policy: PiiPolicy) -> Result<(String, PiiReport), DoxcerError> {
+
+    // Scans text line by line for email addresses, E.164 phone numbers, IBANs
+    // and BSNs.
+    //
+    // # Description
+    // IBANs and BSNs only count when their checksum holds. Every distinct value
+    // gets a typed placeholder (`<EMAIL_1>`, `<IBAN_2>`) in order of first
+    // appearance; with `redact` the value is replaced by it, so the model can
+    // still refer to "the email parameter". With `off` the text is returned
+    // unchanged. Findings never contain the matched value; the mapping back to
+    // the originals is kept in the report for the audit artifact.
+    //
+    // # Returns
+    // * The text after redaction and the report.
+    // * `Err(DoxcerError)` if a detector regex does not compile.
+
+    let mut report = PiiReport::default();
+    if policy == PiiPolicy::Off {
+        return Ok((text.to_string(), report));
+    }
+    let detectors = PII_PATTERNS
+        .iter()
+        .map(|(kind, pattern)| Regex::new(pattern).map(|r| (*kind, r)).map_err(|e| DoxcerError::Usage(format!("PII pattern {}: {e}", kind.label()))))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut seen: BTreeMap<(PiiKind, String), String> = BTreeMap::new();
+    let mut counts: BTreeMap<PiiKind, usize> = BTreeMap::new();
+    let mut out = String::with_capacity(text.len());
+    for (index, line) in text.lines().enumerate() {
+        let mut line = line.to_string();
+        for (kind, regex) in &detectors {
+            let mut replaced = String::with_capacity(line.len());
+            let mut last = 0;
+            for found in regex.find_iter(&line) {
+                if found.start() < last {
+                    continue;
+                }
+                let candidate = &line[found.range()];
+                let end = match kind {
+                    PiiKind::Iban => valid_iban_prefix(candidate),
+                    PiiKind::Bsn => is_valid_bsn(candidate).then_some(candidate.len()),
+                    PiiKind::Phone => is_valid_phone(candidate).then_some(candidate.len()),
+                    PiiKind::Email => Some(candidate.len()),
+                };
+                let Some(end) = end else {
+                    continue;
+                };
+                let value = &candidate[..end];
+                let normalized: String = value.chars().filter(|c| !matches!(c, ' ' | '-')).collect::<String>().to_lowercase();
+                let placeholder = seen
+                    .entry((*kind, normalized))
+                    .or_insert_with(|| {
+                        let count = counts.entry(*kind).or_default();
+                        *count += 1;
+                        let placeholder = format!("<{}_{count}>", kind.label());
+                        report.placeholders.insert(placeholder.clone(), value.to_string());
+                        placeholder
+                    })
+                    .clone();
+                report.findings.push(PiiFinding { kind: *kind, line: index + 1, placeholder: placeholder.clone(), policy });
+                if policy == PiiPolicy::Redact {
+                    replaced.push_str(&line[last..found.start()]);
+                    replaced.push_str(&placeholder);
+                    last = found.start() + end;
+                }
+            }
+            if last > 0 {
+                replaced.push_str(&line[last..]);
+                line = replaced;
+            }
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    Ok((out, report))
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn valid_ibans_pass_the_mod_97_check() {
+        for iban in ["NL91ABNA0417164300", "NL91 ABNA 0417 1643 00", "DE89370400440532013000", "GB82WEST12345698765432", "BE68539007547034"] {
+            assert!(is_valid_iban(iban), "{iban}");
+        }
+    }
+
+
+    #[test]
+    fn invalid_ibans_are_rejected() {
+        for iban in [
+            "NL91ABNA0417164301",     // wrong check
+            "NL19ABNA0417164300",     // swapped check digits
+            "DE89370400440532013001", // one digit off
+            "nl91abna0417164300",     // lowercase country code
+            "NL91ABNA04",             // too short
+            "NL9XABNA0417164300",     // letter in the check digits
+            "NL91ABNA0417164300NL91ABNA0417164300", // too long
+        ] {
+            assert!(!is_valid_iban(iban), "{iban}");
+        }
+    }
+
+
+    #[test]
+    fn valid_bsns_pass_the_11_test() {
+        for bsn in ["111222333", "123456782", "999999990"] {
+            assert!(is_valid_bsn(bsn), "{bsn}");
+        }
+    }
+
+
+    #[test]
+    fn invalid_bsns_are_rejected() {
+        for bsn in ["123456789", "111222334", "000000000", "12345678", "1234567820", "12345678a", "12345 782"] {
+            assert!(!is_valid_bsn(bsn), "{bsn}");
+        }
+    }
+
+
+    #[test]
+    fn phone_numbers_need_a_plus_and_8_to_15_digits() {
+        for phone in ["+31612345678", "+31 6 1234 5678", "+1-202-555-0143", "+49301234"] {
+            assert!(is_valid_phone(phone), "{phone}");
+        }
+        for phone in ["0612345678", "+3161234", "+3161234567890123"] {
+            assert!(!is_valid_phone(phone), "{phone}");
+        }
+    }
+
+
+    #[test]
+    fn only_checksummed_numbers_are_redacted() {
+        let text = "iban = 'NL91ABNA0417164300'\nother = 'NL91ABNA0417164301'\nbsn = 111222333\nid = 123456789\n";
+        let (redacted, report) = scan_pii(text, PiiPolicy::Redact).unwrap();
+        assert_eq!(redacted, "iban = '<IBAN_1>'\nother = 'NL91ABNA0417164301'\nbsn = <BSN_1>\nid = 123456789\n");
+        assert_eq!(report.findings.len(), 2);
+        assert_eq!(report.placeholders["<IBAN_1>"], "NL91ABNA0417164300");
+    }
+
+
+    #[test]
+    fn iban_followed_by_a_word_keeps_the_word() {
+        let (redacted, _) = scan_pii("NL91 ABNA 0417 1643 00 MAAND\n", PiiPolicy::Redact).unwrap();
+        assert_eq!(redacted, "<IBAN_1> MAAND\n");
+    }
+
+
+    #[test]
+    fn warn_reports_without_changing_the_text() {
+        let text = "contact = 'jan@example.com'\n";
+        let (out, report) = scan_pii(text, PiiPolicy::Warn).unwrap();
+        assert_eq!(out, text);
+        assert_eq!(report.findings[0].to_string(), "line 1: possible email address found; left in the prompt");
+    }
+}
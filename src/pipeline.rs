@@ -0,0 +1,665 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Internal Modules
+use crate::config::Settings;
+use crate::diagnostics::is_secret_name;
+use crate::directives::DIRECTIVE_PREFIX;
+use crate::error::DoxcerError;
+use crate::kind::SourceKind;
+use crate::notebook::parse_notebook;
+use crate::pii::{PiiPolicy, PiiReport, scan_pii};
+use crate::secrets::{SecretAction, SecretFinding, SecretScanner, SecretSeverity};
+use crate::template::{Prompt, render_prompt};
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const META_PREFIX: &str = "# META";
+pub const LITERAL_LIMIT: usize = 200;
+pub const LITERAL_KEEP: usize = 40;
+// With `redact_literals`, literals longer than this are removed entirely.
+pub const LITERAL_REDACT_MIN: usize = 40;
+pub const SECRET_MIN_CHARS: usize = 8;
+pub const REDACTED: &str = "[REDACTED]";
+// The finding reported for a literal redacted by its name or connection string key.
+pub const SECRET_LITERAL_FINDING: &str = "secret-literal";
+pub const RENDER_STAGE: &str = "template-render";
+
+// Secrets are redacted before literals are collapsed, so a collapsed literal
+// never keeps the first characters of a secret.
+pub const STAGES: [Stage; 6] = [
+    Stage { name: "meta-strip", description: "parses Fabric sources into numbered cells without their `# META` metadata", run: meta_strip },
+    Stage { name: "secret-redact", description: "redacts secret-looking assignments and secret pattern matches", run: secret_redact },
+    Stage { name: "pii-redact", description: "detects emails, phone numbers, IBANs and BSNs (`--pii`)", run: pii_redact },
+    Stage { name: "literal-collapse", description: "shortens string literals over 200 characters (removes those over 40 with `--redact-literals`)", run: literal_collapse },
+    Stage { name: "directive-strip", description: "removes `# doxcer:` directive lines", run: directive_strip },
+    Stage { name: RENDER_STAGE, description: "renders the template around the notebook", run: template_render },
+];
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone)]
+pub struct PromptDoc<'a> {
+    pub template: String,
+    // The templates `template` extends or imports, see `Template::layouts`.
+    pub layouts: Vec<(String, String)>,
+    pub notebook: String,
+    pub context: &'a BTreeMap<String, String>,
+    pub settings: &'a Settings,
+    pub secrets: &'a SecretScanner,
+    pub findings: Vec<SecretFinding>,
+    pub pii: PiiReport,
+    pub prompt: Option<Prompt>,
+    pub kind: SourceKind,
+    pub removed: Removed,
+}
+
+// What was left out of the prompt: cell outputs and, with `redact_literals`,
+// string literals.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Removed {
+    pub outputs: usize,
+    pub literals: usize,
+    pub literal_chars: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct PreparedPrompt {
+    pub prompt: Prompt,
+    pub findings: Vec<SecretFinding>,
+    pub pii: PiiReport,
+    pub removed: Removed,
+}
+
+pub type StageFn = for<'a> fn(PromptDoc<'a>) -> Result<PromptDoc<'a>, DoxcerError>;
+
+#[derive(Clone, Copy)]
+pub struct Stage {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub run: StageFn,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    pub disabled: BTreeSet<String>,
+    pub dump_dir: Option<PathBuf>,
+}
+
+
+// ====================================================
+//  Pipeline
+// ====================================================
+impl Pipeline {
+
+    pub fn new(disabled: &[String], dump_dir: Option<PathBuf>) -> Result<Pipeline, DoxcerError> {
+
+        // Creates a pipeline with some stages turned off (`--disable-stage`).
+        //
+        // # Returns
+        // * `Err(DoxcerError)` for an unknown stage name, or when the template would
+        //   not be rendered at all.
+
+        for name in disabled {
+            if !STAGES.iter().any(|s| s.name == name) {
+                let known: Vec<&str> = STAGES.iter().map(|s| s.name).collect();
+                return Err(DoxcerError::Config(format!("unknown pipeline stage {name:?}; expected one of {}", known.join(", "))));
+            }
+            if name == RENDER_STAGE {
+                return Err(DoxcerError::Config(format!("the {RENDER_STAGE} stage cannot be disabled")));
+            }
+        }
+        Ok(Pipeline { disabled: disabled.iter().cloned().collect(), dump_dir })
+    }
+
+
+    pub fn is_enabled(&self, stage: &Stage) -> bool {
+        !self.disabled.contains(stage.name)
+    }
+
+
+    pub fn run(&self, doc: PromptDoc<'_>, label: &str) -> Result<PreparedPrompt, DoxcerError> {
+
+        // Runs the enabled stages in order and returns the rendered prompt, with
+        // the secret pattern findings of the `secret-redact` stage and the PII
+        // report of the `pii-redact` stage.
+        //
+        // # Description
+        // With a dump directory (`--pipeline-dump`), the document is written to
+        // `<dir>/<label>/00-input.md` and after every enabled stage to
+        // `<dir>/<label>/<NN>-<stage>.md`, so it is visible what each stage changed.
+        //
+        // # Parameters
+        // * `label` – What is being prepared (the notebook path), used as dump folder.
+
+        let dump_dir = self.dump_dir.as_ref().map(|dir| dir.join(dump_folder(label)));
+        if let Some(dir) = &dump_dir {
+            fs::create_dir_all(dir).map_err(|e| DoxcerError::Config(format!("Failed to create {}: {e}", dir.display())))?;
+            write_dump(dir, "00-input", &doc)?;
+        }
+
+        let mut doc = doc;
+        for (i, stage) in STAGES.iter().enumerate() {
+            if !self.is_enabled(stage) {
+                continue;
+            }
+            doc = (stage.run)(doc).map_err(|e| DoxcerError::Config(format!("{}: {e}", stage.name)))?;
+            if let Some(dir) = &dump_dir {
+                write_dump(dir, &format!("{:02}-{}", i + 1, stage.name), &doc)?;
+            }
+        }
+        let prompt = doc.prompt.ok_or_else(|| DoxcerError::Config(format!("the {RENDER_STAGE} stage did not produce a prompt")))?;
+        Ok(PreparedPrompt { prompt, findings: doc.findings, pii: doc.pii, removed: doc.removed })
+    }
+
+
+    pub fn prepare_source(&self, doc: PromptDoc<'_>) -> Result<String, DoxcerError> {
+
+        // Runs the enabled stages before `template-render` and returns the
+        // notebook text as it would appear in the prompt. Nothing is dumped.
+
+        let mut doc = doc;
+        for stage in STAGES.iter().filter(|s| s.name != RENDER_STAGE && self.is_enabled(s)) {
+            doc = (stage.run)(doc).map_err(|e| DoxcerError::Config(format!("{}: {e}", stage.name)))?;
+        }
+        Ok(doc.notebook)
+    }
+
+
+    pub fn describe(&self) -> String {
+
+        // Lists the stages in order for `--verbose`, marking the disabled ones.
+
+        let mut out = String::from("Prompt pipeline:\n");
+        for (i, stage) in STAGES.iter().enumerate() {
+            let state = if self.is_enabled(stage) { "" } else { "  (disabled)" };
+            out.push_str(&format!("  {}. {:<17} {}{state}\n", i + 1, stage.name, stage.description));
+        }
+        out
+    }
+}
+
+
+fn dump_folder(label: &str) -> String {
+
+    // Turns a notebook path into a single folder name (`nb/a.py` → `nb_a.py`).
+
+    label
+        .trim_start_matches("./")
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\' | ':') { '_' } else { c })
+        .collect()
+}
+
+
+fn write_dump(dir: &Path, name: &str, doc: &PromptDoc<'_>) -> Result<(), DoxcerError> {
+
+    // Writes the notebook as it stands, or the rendered prompt once there is one.
+
+    let text = match &doc.prompt {
+        Some(Prompt { instructions: Some(instructions), input }) => {
+            format!("<!-- instructions -->\n{instructions}\n\n<!-- input -->\n{input}\n")
+        }
+        Some(Prompt { instructions: None, input }) => input.clone(),
+        None => doc.notebook.clone(),
+    };
+    let path = dir.join(format!("{name}.md"));
+    fs::write(&path, text).map_err(|e| DoxcerError::Config(format!("Failed to write {}: {e}", path.display())))
+}
+
+
+impl Removed {
+
+    pub fn is_empty(&self) -> bool {
+        self.outputs == 0 && self.literals == 0
+    }
+}
+
+impl fmt::Display for Removed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if self.outputs > 0 {
+            parts.push(format!("{} cell output(s)", self.outputs));
+        }
+        if self.literals > 0 {
+            parts.push(format!("{} string literal(s) of {} characters in total", self.literals, self.literal_chars));
+        }
+        write!(f, "left out of the prompt: {}", parts.join(" and "))
+    }
+}
+
+
+// ====================================================
+//  Stages
+// ====================================================
+pub fn meta_strip(mut doc: PromptDoc<'_>) -> Result<PromptDoc<'_>, DoxcerError> {
+
+    // Removes the `# METADATA` headers and `# META` lines of Fabric notebook
+    // sources; they describe the kernel and lakehouse, not what the code does.
+    // A whole Fabric source is parsed into its cells and rendered with numbered
+    // cell markers (see `notebook::parse_notebook`); other text, like a chunk
+    // of a notebook, only loses its `# META` lines. Blank lines left behind are
+    // collapsed to at most one.
+
+    if let Some(notebook) = parse_notebook(&doc.notebook) {
+        doc.notebook = notebook.render();
+        return Ok(doc);
+    }
+    let mut out = String::new();
+    let mut blank = false;
+    for line in doc.notebook.lines() {
+        if line.trim_start().starts_with(META_PREFIX) {
+            continue;
+        }
+        let is_blank = line.trim().is_empty();
+        if is_blank && blank {
+            continue;
+        }
+        blank = is_blank;
+        out.push_str(line);
+        out.push('\n');
+    }
+    doc.notebook = out;
+    Ok(doc)
+}
+
+
+pub fn secret_redact(mut doc: PromptDoc<'_>) -> Result<PromptDoc<'_>, DoxcerError> {
+
+    // Replaces secrets in string literals with `[REDACTED]`.
+    //
+    // # Description
+    // A literal is redacted when it is assigned to a secret-looking name
+    // (`api_key = "..."`, `"password": "..."`, `token="..."`) and is at least
+    // eight characters without whitespace, so `partition_key = "date"` stays. In
+    // connection strings, the values of secret-looking keys (`AccountKey=...;`)
+    // are redacted as well.
+    //
+    // Then the secret patterns are applied (see `SecretScanner`): `redact`
+    // matches are replaced, and a `fail` match stops the notebook from being
+    // sent at all. The findings name the pattern that fired, or
+    // `secret-literal` for a line whose literals were redacted.
+
+    let mut literal_findings = Vec::new();
+    let mut redacted = String::with_capacity(doc.notebook.len());
+    for (index, line) in doc.notebook.lines().enumerate() {
+        let new_line = replace_literals(line, |start, content| {
+            let prefix = line[..start - 1].trim_end();
+            let assigned = prefix
+                .strip_suffix(['=', ':'])
+                .filter(|p| !p.ends_with(['=', '!', '<', '>']))
+                .map(|p| p.trim_end().trim_end_matches(['"', '\'']));
+            let name_is_secret = assigned.is_some_and(|p| {
+                let name_start = p.rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).map_or(0, |i| i + 1);
+                is_secret_name(&p[name_start..])
+            });
+            if name_is_secret && content.chars().count() >= SECRET_MIN_CHARS && !content.contains(char::is_whitespace) {
+                return Some(REDACTED.to_string());
+            }
+            redact_connection_string(content)
+        });
+        if new_line != line {
+            literal_findings.push(SecretFinding {
+                pattern: SECRET_LITERAL_FINDING.to_string(),
+                severity: SecretSeverity::High,
+                action: SecretAction::Redact,
+                line: index + 1,
+            });
+        }
+        redacted.push_str(&new_line);
+        redacted.push('\n');
+    }
+    doc.notebook = redacted;
+
+    let (notebook, mut findings) = doc.secrets.scan(&doc.notebook);
+    if let Some(finding) = findings.iter().find(|f| f.action == SecretAction::Fail) {
+        return Err(DoxcerError::Config(format!(
+            "{finding}; remove the secret or change the action of `{}` in [secret_patterns]",
+            finding.pattern
+        )));
+    }
+    doc.notebook = notebook;
+    findings.extend(literal_findings);
+    findings.sort_by_key(|f| f.line);
+    doc.findings.extend(findings);
+    Ok(doc)
+}
+
+
+fn redact_connection_string(content: &str) -> Option<String> {
+
+    // Redacts `Key=value` pairs of a connection string whose key looks secret.
+
+    let mut changed = false;
+    let parts: Vec<String> = content
+        .split(';')
+        .map(|part| match part.split_once('=') {
+            Some((key, value)) if !value.is_empty() && is_secret_name(key.trim()) => {
+                changed = true;
+                format!("{key}={REDACTED}")
+            }
+            _ => part.to_string(),
+        })
+        .collect();
+    changed.then(|| parts.join(";"))
+}
+
+
+pub fn pii_redact(mut doc: PromptDoc<'_>) -> Result<PromptDoc<'_>, DoxcerError> {
+
+    // Applies the PII detectors with the `pii` policy (see [`scan_pii`]). Off by
+    // default; `fail` stops the notebook from being sent when anything is found.
+
+    let policy = doc.settings.pii.value;
+    let (notebook, report) = scan_pii(&doc.notebook, policy)?;
+    if let Some(finding) = report.findings.first().filter(|_| policy == PiiPolicy::Fail) {
+        return Err(DoxcerError::Config(format!("{finding}; remove the data or run with a different --pii policy")));
+    }
+    doc.notebook = notebook;
+    doc.pii = report;
+    Ok(doc)
+}
+
+
+pub fn literal_collapse(mut doc: PromptDoc<'_>) -> Result<PromptDoc<'_>, DoxcerError> {
+
+    // Shortens single-line string literals longer than 200 characters (inline
+    // JSON, base64 blobs, long value lists) to their first 40 characters and a
+    // note of how much was left out. Triple-quoted strings such as SQL are kept.
+    //
+    // With `redact_literals`, a literal longer than 40 characters is removed
+    // entirely instead, so sample data and customer values are not sent; the
+    // count ends up in the `Removed` summary.
+
+    let redact = doc.settings.redact_literals.value;
+    let literals = Cell::new(0);
+    let literal_chars = Cell::new(0);
+    doc.notebook = map_lines(&doc.notebook, |line| {
+        replace_literals(line, |_, content| {
+            let chars = content.chars().count();
+            match redact {
+                true => (chars > LITERAL_REDACT_MIN).then(|| {
+                    literals.set(literals.get() + 1);
+                    literal_chars.set(literal_chars.get() + chars);
+                    format!("<literal removed: {chars} chars>")
+                }),
+                false => (chars > LITERAL_LIMIT).then(|| {
+                    let kept: String = content.chars().take(LITERAL_KEEP).collect();
+                    format!("{kept}…(+{} chars)", chars - LITERAL_KEEP)
+                }),
+            }
+        })
+    });
+    doc.removed.literals += literals.get();
+    doc.removed.literal_chars += literal_chars.get();
+    Ok(doc)
+}
+
+
+pub fn directive_strip(mut doc: PromptDoc<'_>) -> Result<PromptDoc<'_>, DoxcerError> {
+
+    // Removes `# doxcer:` directive lines; they steer doxcer, not the notebook.
+
+    doc.notebook = doc
+        .notebook
+        .lines()
+        .filter(|line| !line.trim_start().starts_with(DIRECTIVE_PREFIX))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    Ok(doc)
+}
+
+
+pub fn template_render(mut doc: PromptDoc<'_>) -> Result<PromptDoc<'_>, DoxcerError> {
+
+    // Renders the template around the prepared notebook, see [`render_prompt`].
+
+    doc.prompt = Some(render_prompt(&doc.template, &doc.layouts, &doc.notebook, doc.context, doc.settings, doc.kind)?);
+    Ok(doc)
+}
+
+
+// ====================================================
+//  Literal Scanning
+// ====================================================
+fn map_lines(text: &str, f: impl Fn(&str) -> String) -> String {
+
+    // Applies `f` to every line, keeping the line endings.
+
+    text.lines().map(|line| format!("{}\n", f(line))).collect()
+}
+
+
+fn replace_literals(line: &str, replace: impl Fn(usize, &str) -> Option<String>) -> String {
+
+    // Rewrites the contents of the single-line string literals on a line.
+    //
+    // # Description
+    // `replace` gets the byte offset and contents of each literal and returns the
+    // new contents, or `None` to keep it. Lines with triple quotes and anything
+    // after a `#` comment are left alone.
+
+    if line.contains("\"\"\"") || line.contains("'''") {
+        return line.to_string();
+    }
+    let bytes = line.as_bytes();
+    let mut out = String::new();
+    let mut copied = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let quote = bytes[i];
+        if quote == b'#' {
+            break;
+        }
+        if quote != b'"' && quote != b'\'' {
+            i += 1;
+            continue;
+        }
+        let start = i + 1;
+        let mut end = start;
+        while end < bytes.len() && bytes[end] != quote {
+            end += if bytes[end] == b'\\' { 2 } else { 1 };
+        }
+        if end >= bytes.len() {
+            break;
+        }
+        if let Some(new) = replace(start, &line[start..end]) {
+            out.push_str(&line[copied..start]);
+            out.push_str(&new);
+            copied = end;
+        }
+        i = end + 1;
+    }
+    out.push_str(&line[copied..]);
+    out
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{SettingsLayer, Source};
+
+    struct Fixture {
+        settings: Settings,
+        secrets: SecretScanner,
+        context: BTreeMap<String, String>,
+    }
+
+    impl Fixture {
+        fn new(layer: SettingsLayer) -> Fixture {
+            Fixture {
+                settings: Settings::resolve(&[(Source::Cli, &layer)]),
+                secrets: SecretScanner::new(&BTreeMap::new()).unwrap(),
+                context: BTreeMap::from([("notebook_name".to_string(), "load.py".to_string())]),
+            }
+        }
+
+        fn doc(&self, notebook: &str) -> PromptDoc<'_> {
+            PromptDoc {
+                template: "Documenteer {{notebook_name}}.".to_string(),
+                layouts: Vec::new(),
+                notebook: notebook.to_string(),
+                context: &self.context,
+                settings: &self.settings,
+                secrets: &self.secrets,
+                findings: Vec::new(),
+                pii: PiiReport::default(),
+                prompt: None,
+                kind: SourceKind::Notebook,
+                removed: Removed::default(),
+            }
+        }
+    }
+
+
+    #[test]
+    fn meta_strip_removes_meta_lines_and_collapses_blanks() {
+        let fixture = Fixture::new(SettingsLayer::default());
+        let doc = meta_strip(fixture.doc("x = 1\n# META {\n# META }\n\n\n\ny = 2\n")).unwrap();
+        assert_eq!(doc.notebook, "x = 1\n\ny = 2\n");
+    }
+
+
+    #[test]
+    fn secret_redact_redacts_secret_assignments_only() {
+        let fixture = Fixture::new(SettingsLayer::default());
+        let doc = secret_redact(fixture.doc(
+            "api_key = \"abcd1234efgh\"\npartition_key = \"date\"\nconn = \"Endpoint=sb://x;SharedAccessKey=abc123;EntityPath=q\"\n",
+        ))
+        .unwrap();
+        assert_eq!(
+            doc.notebook,
+            "api_key = \"[REDACTED]\"\npartition_key = \"date\"\nconn = \"Endpoint=sb://x;SharedAccessKey=[REDACTED];EntityPath=q\"\n"
+        );
+        let lines: Vec<usize> = doc.findings.iter().map(|f| f.line).collect();
+        assert!(lines.contains(&1) && lines.contains(&3) && !lines.contains(&2));
+        assert!(doc.findings.iter().all(|f| !f.to_string().contains("abcd1234efgh")));
+    }
+
+
+    #[test]
+    fn pii_redact_is_off_by_default() {
+        let fixture = Fixture::new(SettingsLayer::default());
+        let doc = pii_redact(fixture.doc("owner = 'jan@example.com'\n")).unwrap();
+        assert_eq!(doc.notebook, "owner = 'jan@example.com'\n");
+
+        let fixture = Fixture::new(SettingsLayer { pii: Some(PiiPolicy::Redact), ..SettingsLayer::default() });
+        let doc = pii_redact(fixture.doc("owner = 'jan@example.com'\n")).unwrap();
+        assert_eq!(doc.notebook, "owner = '<EMAIL_1>'\n");
+        assert_eq!(doc.pii.findings.len(), 1);
+
+        let fixture = Fixture::new(SettingsLayer { pii: Some(PiiPolicy::Fail), ..SettingsLayer::default() });
+        assert!(pii_redact(fixture.doc("owner = 'jan@example.com'\n")).is_err());
+    }
+
+
+    #[test]
+    fn literal_collapse_shortens_long_literals() {
+        let fixture = Fixture::new(SettingsLayer::default());
+        let blob = "a".repeat(250);
+        let doc = literal_collapse(fixture.doc(&format!("data = \"{blob}\"\nshort = \"{}\"\nsql = \"\"\"{blob}\"\"\"\n", "b".repeat(200)))).unwrap();
+        assert_eq!(
+            doc.notebook,
+            format!("data = \"{}…(+210 chars)\"\nshort = \"{}\"\nsql = \"\"\"{blob}\"\"\"\n", "a".repeat(40), "b".repeat(200))
+        );
+        assert!(doc.removed.is_empty());
+    }
+
+
+    #[test]
+    fn literal_collapse_removes_literals_with_redact_literals() {
+        let fixture = Fixture::new(SettingsLayer { redact_literals: Some(true), ..SettingsLayer::default() });
+        let doc = literal_collapse(fixture.doc(&format!("rows = ['{}', 'ok']\n", "x".repeat(41)))).unwrap();
+        assert_eq!(doc.notebook, "rows = ['<literal removed: 41 chars>', 'ok']\n");
+        assert_eq!(doc.removed, Removed { outputs: 0, literals: 1, literal_chars: 41 });
+    }
+
+
+    #[test]
+    fn directive_strip_removes_directive_lines() {
+        let fixture = Fixture::new(SettingsLayer::default());
+        let doc = directive_strip(fixture.doc("# doxcer: model=gpt-5\nx = 1\n  # doxcer: skip=false\n# a comment\n")).unwrap();
+        assert_eq!(doc.notebook, "x = 1\n# a comment\n");
+    }
+
+
+    #[test]
+    fn template_render_puts_the_notebook_under_the_header() {
+        let fixture = Fixture::new(SettingsLayer::default());
+        let doc = template_render(fixture.doc("x = 1\n")).unwrap();
+        let prompt = doc.prompt.unwrap();
+        assert_eq!(prompt.instructions.as_deref(), Some("Documenteer load.py."));
+        assert_eq!(prompt.input, "Hier is de Notebook.py:\n\nx = 1\n");
+    }
+
+
+    #[test]
+    fn stages_compose_in_order() {
+        // A long secret is redacted before literals are collapsed, so none of
+        // it survives; the directive and META lines are gone; the template is
+        // rendered around what is left.
+        let fixture = Fixture::new(SettingsLayer::default());
+        let secret = "s".repeat(250);
+        let notebook = format!("# doxcer: model=gpt-5\n# META {{}}\napi_key = \"{secret}\"\ndata = \"{}\"\n", "d".repeat(250));
+        let prepared = Pipeline::default().run(fixture.doc(&notebook), "nb/load.py").unwrap();
+        assert_eq!(
+            prepared.prompt.input,
+            format!("Hier is de Notebook.py:\n\napi_key = \"[REDACTED]\"\ndata = \"{}…(+210 chars)\"\n", "d".repeat(40))
+        );
+        assert_eq!(prepared.findings.len(), 1);
+        assert!(!prepared.prompt.input.contains("sss"));
+    }
+
+
+    #[test]
+    fn disabled_stages_are_skipped() {
+        let fixture = Fixture::new(SettingsLayer::default());
+        let pipeline = Pipeline::new(&["directive-strip".to_string(), "secret-redact".to_string()], None).unwrap();
+        let source = pipeline.prepare_source(fixture.doc("# doxcer: skip=false\ntoken = \"abcdefgh12345\"\n")).unwrap();
+        assert_eq!(source, "# doxcer: skip=false\ntoken = \"abcdefgh12345\"\n");
+        assert!(pipeline.describe().contains("5. directive-strip   removes `# doxcer:` directive lines  (disabled)"));
+    }
+
+
+    #[test]
+    fn unknown_and_render_stages_cannot_be_disabled() {
+        assert!(Pipeline::new(&["trim".to_string()], None).unwrap_err().to_string().starts_with("unknown pipeline stage \"trim\""));
+        assert_eq!(Pipeline::new(&[RENDER_STAGE.to_string()], None).unwrap_err().to_string(), "the template-render stage cannot be disabled");
+    }
+
+
+    #[test]
+    fn dump_writes_the_document_after_every_stage() {
+        let fixture = Fixture::new(SettingsLayer::default());
+        let dir = tempfile::tempdir().unwrap();
+        let pipeline = Pipeline::new(&["pii-redact".to_string()], Some(dir.path().to_path_buf())).unwrap();
+        pipeline.run(fixture.doc("# doxcer: model=gpt-5\nx = 1\n"), "./nb/load.py").unwrap();
+
+        let folder = dir.path().join("nb_load.py");
+        let mut files: Vec<String> = fs::read_dir(&folder).unwrap().map(|e| e.unwrap().file_name().to_string_lossy().into_owned()).collect();
+        files.sort();
+        assert_eq!(files, ["00-input.md", "01-meta-strip.md", "02-secret-redact.md", "04-literal-collapse.md", "05-directive-strip.md", "06-template-render.md"]);
+        assert_eq!(fs::read_to_string(folder.join("04-literal-collapse.md")).unwrap(), "# doxcer: model=gpt-5\nx = 1\n");
+        assert_eq!(fs::read_to_string(folder.join("05-directive-strip.md")).unwrap(), "x = 1\n");
+        assert!(fs::read_to_string(folder.join("06-template-render.md")).unwrap().starts_with("<!-- instructions -->\nDocumenteer load.py.\n"));
+    }
+}
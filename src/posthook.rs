@@ -0,0 +1,190 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::env;
+use std::io::Read;
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Internal Modules
+use crate::diagnostics::is_secret_name;
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const DEFAULT_POST_HOOK_TIMEOUT_SECS: u64 = 60;
+pub const HOOK_ENV_PREFIX: &str = "DOXCER_HOOK_";
+pub const POST_HOOK_FAILED_STATUS: &str = "failed-in-post";
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookContext {
+    pub output: PathBuf,
+    pub source: String,
+    pub source_hash: String,
+    pub model: String,
+    pub status: String,
+    pub cached: bool,
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+}
+
+
+// ====================================================
+//  Environment
+// ====================================================
+pub fn hook_env(hook: &HookContext) -> Vec<(String, String)> {
+
+    // Returns the `DOXCER_HOOK_*` variables describing a generated document.
+    // Token counts are left out when the answer came from the cache.
+
+    let mut vars = vec![
+        ("OUTPUT", hook.output.display().to_string()),
+        ("SOURCE", hook.source.clone()),
+        ("SOURCE_HASH", hook.source_hash.clone()),
+        ("MODEL", hook.model.clone()),
+        ("STATUS", hook.status.clone()),
+        ("CACHED", hook.cached.to_string()),
+    ];
+    if let Some(tokens) = hook.input_tokens {
+        vars.push(("INPUT_TOKENS", tokens.to_string()));
+    }
+    if let Some(tokens) = hook.output_tokens {
+        vars.push(("OUTPUT_TOKENS", tokens.to_string()));
+    }
+    vars.into_iter().map(|(name, value)| (format!("{HOOK_ENV_PREFIX}{name}"), value)).collect()
+}
+
+
+pub fn inherited_env() -> Vec<(String, String)> {
+
+    // Returns the environment a hook inherits: everything except secret-looking
+    // names (see [`is_secret_name`]), so `ENCRYPTION_PASSWORD`,
+    // `OPENAI_API_KEY_ENC` and other keys loaded from `.env` stay with doxcer.
+
+    env::vars().filter(|(name, _)| !is_secret_name(name)).collect()
+}
+
+
+// ====================================================
+//  Running
+// ====================================================
+pub fn run_post_hook(command: &str, hook: &HookContext, timeout: Duration) -> Result<(), DoxcerError> {
+
+    // Runs one post-hook on a generated document.
+    //
+    // # Description
+    // The command goes through the shell (`sh -c`, `cmd /C` on Windows) with
+    // the output path appended as its last argument. It gets the environment
+    // of [`inherited_env`] plus [`hook_env`]; stdout is discarded and stderr is
+    // captured for the error.
+    //
+    // On Unix the hook runs in its own process group. A hook still running
+    // after `timeout` is killed with everything it started. Processes the hook
+    // left in the background may keep its stderr open until `timeout`; they
+    // are then killed too, and the hook's own exit status counts.
+    //
+    // # Returns
+    // * `Ok(())` – the hook exited with status 0.
+    // * `Err(DoxcerError)` – it could not start, timed out or exited non-zero; the
+    //   message ends with the hook's stderr.
+
+    let mut command_line = shell(command, &hook.output);
+    #[cfg(unix)]
+    command_line.process_group(0);
+    let mut child = command_line
+        .env_clear()
+        .envs(inherited_env())
+        .envs(hook_env(hook))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| DoxcerError::Input(format!("Failed to start post-hook {command:?}: {e}")))?;
+
+    // Read stderr on the side so a chatty hook cannot fill the pipe and block.
+    let mut stderr = child.stderr.take();
+    let (sender, reader) = mpsc::channel();
+    thread::spawn(move || {
+        let mut text = String::new();
+        if let Some(stderr) = stderr.as_mut() {
+            let _ = stderr.read_to_string(&mut text);
+        }
+        let _ = sender.send(text);
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                kill_group(&mut child);
+                let _ = child.wait();
+                return Err(DoxcerError::Input(format!("post-hook {command:?} timed out after {}s", timeout.as_secs())));
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(DoxcerError::Input(format!("Failed to wait for post-hook {command:?}: {e}"))),
+        }
+    };
+    let stderr = match reader.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+        Ok(text) => text,
+        Err(_) => {
+            kill_group(&mut child);
+            reader.recv_timeout(Duration::from_secs(1)).unwrap_or_default()
+        }
+    };
+    if status.success() {
+        return Ok(());
+    }
+    let code = status.code().map_or_else(|| "a signal".to_string(), |code| format!("exit code {code}"));
+    match stderr.trim() {
+        "" => Err(DoxcerError::Input(format!("post-hook {command:?} failed with {code}"))),
+        stderr => Err(DoxcerError::Input(format!("post-hook {command:?} failed with {code}: {stderr}"))),
+    }
+}
+
+
+fn kill_group(child: &mut Child) {
+
+    // Kills a hook and, on Unix, every process left in its process group.
+
+    #[cfg(unix)]
+    // SAFETY: `kill` has no memory effects; the group id is the hook's pid,
+    // set by `process_group(0)` when it was spawned.
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+}
+
+
+fn shell(command: &str, output: &Path) -> Command {
+
+    // Builds the shell invocation of a hook with the output path as argument.
+
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(format!("{command} \"{}\"", output.display()));
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(format!("{command} \"$1\"")).arg("doxcer-hook").arg(output);
+        shell
+    }
+}
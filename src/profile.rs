@@ -0,0 +1,367 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::BTreeMap;
+
+// External Libraries
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+// Internal Modules
+use crate::config::Source;
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+// Fields every request needs; a profile can never remove them.
+pub const PROTECTED_FIELDS: [&str; 2] = ["model", "input"];
+const REASONING_MODELS: [&str; 4] = ["gpt-5*", "o1*", "o3*", "o4*"];
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub provider: String,
+    pub models: Vec<String>,
+    pub params: Map<String, Value>,
+    pub unsupported: Vec<String>,
+    pub source: Source,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    pub provider: Option<String>,
+    pub models: Option<Vec<String>>,
+    pub params: Option<Map<String, Value>>,
+    pub unsupported: Option<Vec<String>>,
+}
+
+
+// ====================================================
+//  Profile Table
+// ====================================================
+pub fn builtin_profiles() -> Vec<Profile> {
+
+    // Returns the built-in request profiles per provider and model family.
+    //
+    // # Description
+    // * `openai-reasoning` / `azure-reasoning` – GPT-5 and o-series models reject
+    //   sampling parameters, so `temperature` and `top_p` are omitted.
+    // * `anthropic` – the Messages API requires `max_tokens` and has no
+    //   `max_output_tokens`.
+    // * `ollama` – the default context window is too small for most notebooks.
+
+    let profile = |name: &str, provider: &str, models: &[&str], params: Value, unsupported: &[&str]| Profile {
+        name: name.to_string(),
+        provider: provider.to_string(),
+        models: models.iter().map(|m| m.to_string()).collect(),
+        params: params.as_object().cloned().unwrap_or_default(),
+        unsupported: unsupported.iter().map(|u| u.to_string()).collect(),
+        source: Source::Default,
+    };
+    vec![
+        profile("openai-reasoning", "openai", &REASONING_MODELS, json!({}), &["temperature", "top_p"]),
+        profile("azure-reasoning", "azure", &REASONING_MODELS, json!({}), &["temperature", "top_p"]),
+        profile("anthropic", "anthropic", &["claude-*"], json!({ "max_tokens": 8192 }), &["max_output_tokens"]),
+        profile("ollama", "ollama", &["*"], json!({ "options": { "num_ctx": 32768 } }), &[]),
+    ]
+}
+
+
+pub fn resolve_profiles(config: &BTreeMap<String, ProfileConfig>) -> Result<Vec<Profile>, DoxcerError> {
+
+    // Combines the built-in profiles with the `[profiles.<name>]` sections of
+    // `doxcer.toml`.
+    //
+    // # Description
+    // A section named after a built-in profile overrides the parts it sets:
+    // `params` are merged over the built-in ones, the other keys replace them.
+    // Other sections add profiles and must name a `provider` and `models`.
+    // Profiles keep this order: built-in first, then the added ones.
+    //
+    // # Returns
+    // * `Ok(Vec<Profile>)` with every profile.
+    // * `Err(DoxcerError)` when an added profile misses `provider` or `models`.
+
+    let mut profiles = builtin_profiles();
+    for (name, section) in config {
+        let index = match profiles.iter().position(|p| p.name == *name) {
+            Some(index) => index,
+            None => {
+                let (Some(provider), Some(models)) = (&section.provider, &section.models) else {
+                    return Err(DoxcerError::Config(format!("[profiles.{name}] needs `provider` and `models` (e.g. models = [\"gpt-4.1*\"])")));
+                };
+                profiles.push(Profile {
+                    name: name.clone(),
+                    provider: provider.clone(),
+                    models: models.clone(),
+                    params: Map::new(),
+                    unsupported: Vec::new(),
+                    source: Source::ConfigFile,
+                });
+                profiles.len() - 1
+            }
+        };
+        let profile = &mut profiles[index];
+        profile.source = Source::ConfigFile;
+        if let Some(provider) = &section.provider {
+            profile.provider = provider.clone();
+        }
+        if let Some(models) = &section.models {
+            profile.models = models.clone();
+        }
+        if let Some(params) = &section.params {
+            merge_defaults(&mut profile.params, params, true);
+        }
+        if let Some(unsupported) = &section.unsupported {
+            profile.unsupported = unsupported.clone();
+        }
+    }
+    Ok(profiles)
+}
+
+
+pub fn matching_profiles<'a>(profiles: &'a [Profile], provider: &str, model: &str) -> Vec<&'a Profile> {
+
+    // Returns the profiles for a provider whose model patterns match the model.
+
+    profiles
+        .iter()
+        .filter(|p| p.provider == provider && p.models.iter().any(|m| matches_pattern(m, model)))
+        .collect()
+}
+
+
+// ====================================================
+//  Request Shaping
+// ====================================================
+pub fn apply_profiles(body: &mut Map<String, Value>, profiles: &[&Profile]) -> Vec<String> {
+
+    // Applies matching profiles to a serialized request body.
+    //
+    // # Description
+    // Profile parameters are defaults: a field the request already sets (such as
+    // `max_output_tokens` from the settings) is kept, and later profiles win over
+    // earlier ones. Afterwards every field that any of the profiles marks as
+    // unsupported is removed (`options.num_ctx` addresses a nested field), so the
+    // API never sees it. `model` and `input` are never removed.
+    //
+    // # Returns
+    // * The unsupported fields that were actually present and removed.
+
+    let mut defaults = Map::new();
+    for profile in profiles {
+        merge_defaults(&mut defaults, &profile.params, true);
+    }
+    merge_defaults(body, &defaults, false);
+
+    let mut removed = Vec::new();
+    for field in profiles.iter().flat_map(|p| p.unsupported.iter()) {
+        if !PROTECTED_FIELDS.contains(&field.as_str()) && remove_path(body, field) {
+            removed.push(field.clone());
+        }
+    }
+    removed
+}
+
+
+impl Profile {
+
+    pub fn describe(&self) -> String {
+
+        // Describes what a profile contributes, for `--dry-run` and `config dump`.
+
+        let origin = if self.source == Source::Default { "built-in" } else { "doxcer.toml" };
+        let mut out = format!("{} ({origin}, {}: {})", self.name, self.provider, self.models.join(", "));
+        if !self.params.is_empty() {
+            out.push_str(&format!("; sets {}", Value::Object(self.params.clone())));
+        }
+        if !self.unsupported.is_empty() {
+            out.push_str(&format!("; omits {}", self.unsupported.join(", ")));
+        }
+        out
+    }
+}
+
+
+// ====================================================
+//  Helpers
+// ====================================================
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+
+    // Matches a model name against a pattern in which `*` stands for any text.
+
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !name.starts_with(first) || name.len() < first.len() + last.len() || !name.ends_with(last) {
+        return false;
+    }
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+
+fn merge_defaults(target: &mut Map<String, Value>, source: &Map<String, Value>, overwrite: bool) {
+
+    // Deep-merges `source` into `target`; existing scalars are only replaced
+    // when `overwrite` is set.
+
+    for (key, value) in source {
+        match (target.get_mut(key), value) {
+            (Some(Value::Object(existing)), Value::Object(nested)) => merge_defaults(existing, nested, overwrite),
+            (Some(_), _) if !overwrite => {}
+            _ => {
+                target.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+
+fn remove_path(body: &mut Map<String, Value>, path: &str) -> bool {
+
+    // Removes a dotted field path from a JSON object; returns whether it existed.
+
+    match path.split_once('.') {
+        None => body.remove(path).is_some(),
+        Some((head, tail)) => match body.get_mut(head) {
+            Some(Value::Object(nested)) => remove_path(nested, tail),
+            _ => false,
+        },
+    }
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{Anthropic, AzureOpenAi, LlmProvider, Ollama, OpenAi};
+
+    fn request(model: &str) -> Map<String, Value> {
+
+        // A request as doxcer serializes it before the profiles apply.
+
+        json!({
+            "model": model,
+            "input": "notebook",
+            "instructions": "document it",
+            "max_output_tokens": 2000,
+            "temperature": 0.2,
+            "top_p": 0.9,
+        })
+        .as_object()
+        .cloned()
+        .unwrap()
+    }
+
+    fn shaped(provider: &dyn LlmProvider, model: &str) -> (Vec<String>, Value) {
+
+        // Applies the matching built-in profiles and the provider's encoding.
+
+        let profiles = builtin_profiles();
+        let matching = matching_profiles(&profiles, provider.name(), model);
+        let mut body = request(model);
+        let removed = apply_profiles(&mut body, &matching);
+        (removed, provider.encode_request(Value::Object(body)))
+    }
+
+
+    #[test]
+    fn openai_reasoning_omits_sampling_parameters() {
+        let (removed, body) = shaped(&OpenAi::new(None), "gpt-5-mini");
+        assert_eq!(removed, ["temperature", "top_p"]);
+        assert_eq!(body, json!({
+            "model": "gpt-5-mini",
+            "input": "notebook",
+            "instructions": "document it",
+            "max_output_tokens": 2000,
+        }));
+    }
+
+
+    #[test]
+    fn azure_reasoning_omits_sampling_parameters() {
+        let (removed, body) = shaped(&AzureOpenAi::new("https://example.openai.azure.com"), "o4-mini");
+        assert_eq!(removed, ["temperature", "top_p"]);
+        assert_eq!(body, json!({
+            "model": "o4-mini",
+            "input": "notebook",
+            "instructions": "document it",
+            "max_output_tokens": 2000,
+        }));
+    }
+
+
+    #[test]
+    fn anthropic_sets_max_tokens_and_drops_max_output_tokens() {
+        let (removed, body) = shaped(&Anthropic::new(None), "claude-sonnet-4");
+        assert_eq!(removed, ["max_output_tokens"]);
+        assert_eq!(body, json!({
+            "model": "claude-sonnet-4",
+            "messages": [{ "role": "user", "content": "notebook" }],
+            "system": "document it",
+            "max_tokens": 8192,
+            "temperature": 0.2,
+            "top_p": 0.9,
+        }));
+    }
+
+
+    #[test]
+    fn ollama_raises_the_context_window() {
+        let (removed, body) = shaped(&Ollama::new(None), "llama3.1");
+        assert!(removed.is_empty());
+        assert_eq!(body, json!({
+            "model": "llama3.1",
+            "prompt": "notebook",
+            "system": "document it",
+            "options": { "num_ctx": 32768, "num_predict": 2000, "temperature": 0.2, "top_p": 0.9 },
+            "stream": false,
+        }));
+    }
+
+
+    #[test]
+    fn other_models_keep_the_request_as_is() {
+        let (removed, body) = shaped(&OpenAi::new(None), "gpt-4.1");
+        assert!(removed.is_empty());
+        assert_eq!(body, Value::Object(request("gpt-4.1")));
+    }
+
+
+    #[test]
+    fn a_config_section_merges_over_a_builtin_profile() {
+        let section = ProfileConfig { params: Some(json!({ "max_tokens": 1024 }).as_object().cloned().unwrap()), ..Default::default() };
+        let profiles = resolve_profiles(&BTreeMap::from([("anthropic".to_string(), section)])).unwrap();
+        let anthropic = profiles.iter().find(|p| p.name == "anthropic").unwrap();
+        assert_eq!(anthropic.source, Source::ConfigFile);
+        assert_eq!(anthropic.params["max_tokens"], 1024);
+        assert_eq!(anthropic.unsupported, ["max_output_tokens"]);
+
+        let missing = BTreeMap::from([("mine".to_string(), ProfileConfig::default())]);
+        assert!(resolve_profiles(&missing).unwrap_err().to_string().contains("[profiles.mine] needs `provider` and `models`"));
+    }
+}
@@ -0,0 +1,102 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// External Libraries
+use sha2::{Digest, Sha256};
+
+// Internal Modules
+use crate::frontmatter::{front_matter_value, split_front_matter, upsert_front_matter};
+use crate::timefmt::GENERATED_AT_KEY;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const SOURCE_KEY: &str = "doxcer_source";
+pub const SOURCE_HASH_KEY: &str = "doxcer_source_hash";
+pub const VERSION_KEY: &str = "doxcer_version";
+pub const MODEL_KEY: &str = "doxcer_model";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    pub source: String,
+    pub source_hash: String,
+    pub model: Option<String>,
+    pub generated_at: Option<String>,
+}
+
+
+// ====================================================
+//  Content Hashing
+// ====================================================
+pub fn content_hash(bytes: &[u8]) -> String {
+
+    // Computes the content hash used to tie a generated document to its notebook.
+    //
+    // # Parameters
+    // * `bytes` – The raw notebook contents.
+    //
+    // # Returns
+    // * A `sha256:<hex>` string.
+
+    let digest = Sha256::digest(bytes);
+    let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+    format!("sha256:{hex}")
+}
+
+
+// ====================================================
+//  Front-matter Provenance
+// ====================================================
+pub fn stamp_provenance(markdown: &str, provenance: &Provenance) -> String {
+
+    // Writes the provenance keys into the front-matter of a generated document.
+    //
+    // # Description
+    // Keeps any front-matter the model produced and adds (or replaces) the
+    // `doxcer_source` and `doxcer_source_hash` keys, so `doxcer verify` can later
+    // tell whether the document still matches its notebook. The generating
+    // doxcer version is recorded as `doxcer_version` and the model that actually
+    // answered (which may be a fallback) as `doxcer_model`; `generated_at` is only
+    // written when a timestamp is given (`--no-timestamps` leaves it out).
+
+    let mut pairs = vec![
+        (SOURCE_KEY, provenance.source.clone()),
+        (SOURCE_HASH_KEY, provenance.source_hash.clone()),
+        (VERSION_KEY, crate::version().to_string()),
+    ];
+    if let Some(model) = &provenance.model {
+        pairs.push((MODEL_KEY, model.clone()));
+    }
+    if let Some(at) = &provenance.generated_at {
+        pairs.push((GENERATED_AT_KEY, at.clone()));
+    }
+    upsert_front_matter(markdown, &pairs)
+}
+
+
+pub fn read_provenance(markdown: &str) -> Option<Provenance> {
+
+    // Reads the provenance keys back from a generated document.
+    //
+    // # Returns
+    // * `Some(Provenance)` when both keys are present in the front-matter.
+    // * `None` when the document has no front-matter or no provenance.
+
+    let (front, _) = split_front_matter(markdown);
+    let front = front?;
+    Some(Provenance {
+        source: front_matter_value(front, SOURCE_KEY)?,
+        source_hash: front_matter_value(front, SOURCE_HASH_KEY)?,
+        model: front_matter_value(front, MODEL_KEY),
+        generated_at: front_matter_value(front, GENERATED_AT_KEY),
+    })
+}
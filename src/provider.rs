@@ -0,0 +1,920 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+// External Libraries
+use reqwest::blocking::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+// Internal Modules
+use crate::cancel::{self, CancelToken, CancellableReader};
+use crate::config::DEFAULT_MAX_OUTPUT_BYTES;
+use crate::error::DoxcerError;
+use crate::limits::{check_request_size, read_capped};
+use crate::template::Prompt;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+pub const OPENAI_API_KEY: &str = "OPENAI_API_KEY";
+pub const AZURE_API_KEY: &str = "AZURE_OPENAI_API_KEY";
+pub const AZURE_ENDPOINT_ENV: &str = "AZURE_OPENAI_ENDPOINT";
+pub const AZURE_DEPLOYMENT_ENV: &str = "AZURE_OPENAI_DEPLOYMENT";
+pub const AZURE_API_VERSION_ENV: &str = "AZURE_OPENAI_API_VERSION";
+pub const DEFAULT_AZURE_API_VERSION: &str = "2024-10-21";
+pub const ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com/v1";
+pub const ANTHROPIC_API_KEY: &str = "ANTHROPIC_API_KEY";
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+pub const DEFAULT_ANTHROPIC_MAX_TOKENS: u32 = 8192;
+pub const OLLAMA_HOST_ENV: &str = "OLLAMA_HOST";
+pub const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
+pub const PROVIDER_NAMES: [&str; 5] = ["openai", "azure", "azure-deployment", "anthropic", "ollama"];
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    Minimal,
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct Usage {
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Generation {
+    // The text parts of the answer, joined by newlines; `None` when the
+    // response had no output.
+    pub text: Option<String>,
+    pub usage: Option<Usage>,
+}
+
+// A generation request in the Responses API format; `encode_request` turns it
+// into the body a provider expects.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatRequest {
+    pub model: String,
+    pub input: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<Reasoning>,
+    // Request profile parameters, sent as extra top-level keys.
+    #[serde(flatten)]
+    pub params: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reasoning {
+    pub effort: ReasoningEffort,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamEvent {
+    // A piece of the answer text.
+    Delta(String),
+    // The final event, with the whole answer and its usage.
+    Completed(Generation),
+    // The API reported an error in the stream.
+    Failed(String),
+    // Any other event (created, in_progress, content part boundaries, ...).
+    Ignored,
+}
+
+#[derive(Deserialize)]
+struct ResponsesBody {
+    output: Option<Vec<ResponsesOutput>>,
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct ResponsesOutput {
+    content: Vec<ResponsesContent>,
+}
+
+#[derive(Deserialize)]
+struct ResponsesContent {
+    text: Option<String>,
+}
+
+impl ChatRequest {
+
+    pub fn new(model: &str, prompt: &Prompt) -> ChatRequest {
+
+        // A request for a rendered prompt with the API's default sampling.
+
+        ChatRequest {
+            model: model.to_string(),
+            input: prompt.input.clone(),
+            instructions: prompt.instructions.clone(),
+            max_output_tokens: None,
+            temperature: None,
+            top_p: None,
+            reasoning: None,
+            params: Map::new(),
+        }
+    }
+}
+
+impl fmt::Display for ReasoningEffort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReasoningEffort::Minimal => write!(f, "minimal"),
+            ReasoningEffort::Low => write!(f, "low"),
+            ReasoningEffort::Medium => write!(f, "medium"),
+            ReasoningEffort::High => write!(f, "high"),
+        }
+    }
+}
+
+impl FromStr for ReasoningEffort {
+    type Err = DoxcerError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "minimal" => Ok(ReasoningEffort::Minimal),
+            "low" => Ok(ReasoningEffort::Low),
+            "medium" => Ok(ReasoningEffort::Medium),
+            "high" => Ok(ReasoningEffort::High),
+            other => Err(DoxcerError::Config(format!("expected minimal, low, medium or high, got {other:?}"))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenAi {
+    base_url: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct AzureOpenAi {
+    base_url: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct AzureDeployment {
+    endpoint: String,
+    // `AZURE_OPENAI_DEPLOYMENT`; without it the model name is the deployment.
+    deployment: Option<String>,
+    api_version: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Anthropic {
+    base_url: String,
+}
+
+#[derive(Deserialize)]
+struct MessagesBody {
+    content: Option<Vec<MessagesContent>>,
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct MessagesContent {
+    text: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Ollama {
+    base_url: String,
+}
+
+#[derive(Deserialize)]
+struct OllamaBody {
+    response: Option<String>,
+    prompt_eval_count: Option<u64>,
+    eval_count: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct CompletionsBody {
+    choices: Option<Vec<CompletionsChoice>>,
+    usage: Option<CompletionsUsage>,
+}
+
+#[derive(Deserialize)]
+struct CompletionsChoice {
+    message: Option<CompletionsMessage>,
+}
+
+#[derive(Deserialize)]
+struct CompletionsMessage {
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CompletionsUsage {
+    prompt_tokens: Option<u64>,
+    completion_tokens: Option<u64>,
+}
+
+
+// ====================================================
+//  Provider Trait
+// ====================================================
+pub trait LlmProvider: Send + Sync {
+
+    // A backend that documents notebooks: where requests go, how they are
+    // authenticated and how an answer is read.
+    //
+    // # Description
+    // A provider only describes the wire; the default methods build on that.
+    // `generate` sends one request and reads one answer, which is all a
+    // library caller needs. The CLI adds retries, fallbacks, streaming and
+    // caching around `request_body` and `parse_generation`.
+
+    // The name used for request profiles and size limits (`openai`, `azure`).
+    fn name(&self) -> &'static str;
+
+    // The URL generation requests for `model` are posted to.
+    fn generate_url(&self, model: &str) -> String;
+
+    // A URL that answers an authenticated request without costing tokens,
+    // used by `doxcer selftest --network`.
+    fn models_url(&self) -> String;
+
+    // Adds the API key to a request.
+    fn authorize(&self, request: RequestBuilder, api_key: &str) -> RequestBuilder;
+
+    // The `.env` variable holding the API key (plain or as `<name>_ENC`);
+    // `None` for a backend without authentication.
+    fn api_key_name(&self) -> Option<&'static str> {
+        Some(OPENAI_API_KEY)
+    }
+
+    fn post(&self, client: &Client, api_key: &str, model: &str) -> RequestBuilder {
+
+        // Starts a generation request with the JSON content type and the API key.
+
+        self.authorize(client.post(self.generate_url(model)), api_key).header("Content-Type", "application/json")
+    }
+
+    fn encode_request(&self, request: Value) -> Value {
+
+        // Turns a request in the Responses API format (`model`, `input`,
+        // `instructions`, `max_output_tokens` and profile parameters) into the
+        // body this provider expects.
+
+        request
+    }
+
+    fn parse_generation(&self, body: &[u8]) -> Generation {
+
+        // Reads an answer in the Responses API format. A body that does not
+        // parse is an answer without output.
+
+        parse_responses_body(body)
+    }
+
+    fn request_body(&self, request: &ChatRequest, stream: bool) -> Result<Vec<u8>, DoxcerError> {
+
+        // Serializes a request once, in this provider's format, and checks
+        // those exact bytes against its request size limit, so an oversized
+        // body fails before the upload instead of with a 413 afterwards.
+        // `stream` asks for server-sent events.
+
+        let body = serde_json::to_value(request)
+            .map(|request| self.encode_request(request))
+            .and_then(|mut request| {
+                if stream && let Some(object) = request.as_object_mut() {
+                    object.insert("stream".to_string(), Value::Bool(true));
+                }
+                serde_json::to_vec(&request)
+            })
+            .map_err(|e| DoxcerError::Input(format!("Failed to serialize request: {e}")))?;
+        check_request_size(self.name(), body.len())?;
+        Ok(body)
+    }
+
+    fn generate(&self, client: &Client, api_key: &str, request: &ChatRequest) -> Result<Generation, DoxcerError> {
+
+        // Sends one request and reads the whole answer, without retries,
+        // fallbacks or caching.
+        //
+        // # Returns
+        // * `Ok(Generation)` for a successful answer.
+        // * `Err(DoxcerError::Provider)` when the request fails or the API
+        //   answers with an error status.
+        // * `Err(DoxcerError::Limit)` when the request or the answer is larger
+        //   than allowed (the answer is capped at `DEFAULT_MAX_OUTPUT_BYTES`).
+
+        self.generate_cancellable(client, api_key, request, &CancelToken::new())
+    }
+
+    fn generate_cancellable(&self, client: &Client, api_key: &str, request: &ChatRequest, cancel: &CancelToken) -> Result<Generation, DoxcerError> {
+
+        // [`generate`](LlmProvider::generate), given up on with
+        // `Err(DoxcerError::Cancelled)` as soon as `cancel` is cancelled or
+        // its deadline passes, while waiting for the answer or reading it.
+
+        let body = self.request_body(request, false)?;
+        let res = cancel::send(cancel, self.post(client, api_key, &request.model).body(body))?;
+        let status = res.status();
+        let read = read_capped(CancellableReader::new(cancel, res), DEFAULT_MAX_OUTPUT_BYTES).map_err(|e| match cancel.is_cancelled() {
+            true => DoxcerError::Cancelled { partial: None },
+            false => e,
+        })?;
+        let body = read.map_err(|_| DoxcerError::Limit(format!("output exceeded {DEFAULT_MAX_OUTPUT_BYTES} bytes")))?;
+        if !status.is_success() {
+            return Err(DoxcerError::Provider(format!("API request failed: {}", String::from_utf8_lossy(&body))));
+        }
+        Ok(self.parse_generation(&body))
+    }
+
+    // Whether the provider can send the answer as server-sent events when the
+    // request has `"stream": true`.
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
+    fn stream_event(&self, data: &str) -> StreamEvent {
+
+        // Reads the `data:` payload of one server-sent event, in the Responses
+        // API format.
+
+        parse_responses_event(data)
+    }
+}
+
+impl OpenAi {
+
+    pub fn new(base_url: Option<&str>) -> OpenAi {
+
+        // `base_url` replaces `https://api.openai.com/v1`, e.g. for an
+        // OpenAI-compatible gateway.
+
+        OpenAi { base_url: base_url.unwrap_or(OPENAI_BASE_URL).trim_end_matches('/').to_string() }
+    }
+}
+
+impl Default for OpenAi {
+    fn default() -> OpenAi {
+        OpenAi::new(None)
+    }
+}
+
+impl LlmProvider for OpenAi {
+
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn generate_url(&self, _model: &str) -> String {
+        format!("{}/responses", self.base_url)
+    }
+
+    fn models_url(&self) -> String {
+        format!("{}/models", self.base_url)
+    }
+
+    fn authorize(&self, request: RequestBuilder, api_key: &str) -> RequestBuilder {
+        request.header("Authorization", format!("Bearer {api_key}"))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+}
+
+impl AzureOpenAi {
+
+    pub fn new(endpoint: &str) -> AzureOpenAi {
+
+        // Accepts the resource endpoint (`https://<resource>.openai.azure.com`)
+        // as shown in the Azure portal, with or without the `/openai/v1` path
+        // of the v1 API.
+
+        let endpoint = endpoint.trim_end_matches('/');
+        let base_url = match endpoint.ends_with("/openai/v1") {
+            true => endpoint.to_string(),
+            false => format!("{endpoint}/openai/v1"),
+        };
+        AzureOpenAi { base_url }
+    }
+}
+
+impl LlmProvider for AzureOpenAi {
+
+    fn name(&self) -> &'static str {
+        "azure"
+    }
+
+    fn generate_url(&self, _model: &str) -> String {
+        format!("{}/responses", self.base_url)
+    }
+
+    fn models_url(&self) -> String {
+        format!("{}/models", self.base_url)
+    }
+
+    fn authorize(&self, request: RequestBuilder, api_key: &str) -> RequestBuilder {
+        request.header("api-key", api_key)
+    }
+
+    fn api_key_name(&self) -> Option<&'static str> {
+        Some(AZURE_API_KEY)
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+}
+
+impl AzureDeployment {
+
+    pub fn new(endpoint: &str, deployment: Option<String>, api_version: Option<String>) -> AzureDeployment {
+        AzureDeployment {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            deployment,
+            api_version: api_version.unwrap_or_else(|| DEFAULT_AZURE_API_VERSION.to_string()),
+        }
+    }
+}
+
+impl LlmProvider for AzureDeployment {
+
+    fn name(&self) -> &'static str {
+        "azure"
+    }
+
+    fn generate_url(&self, model: &str) -> String {
+
+        // `https://{resource}.openai.azure.com/openai/deployments/{deployment}/chat/completions`;
+        // the fallback models are deployment names as well when no fixed
+        // deployment is set.
+
+        let deployment = self.deployment.as_deref().unwrap_or(model);
+        format!("{}/openai/deployments/{deployment}/chat/completions?api-version={}", self.endpoint, self.api_version)
+    }
+
+    fn models_url(&self) -> String {
+        format!("{}/openai/models?api-version={}", self.endpoint, self.api_version)
+    }
+
+    fn authorize(&self, request: RequestBuilder, api_key: &str) -> RequestBuilder {
+        request.header("api-key", api_key)
+    }
+
+    fn api_key_name(&self) -> Option<&'static str> {
+        Some(AZURE_API_KEY)
+    }
+
+    fn encode_request(&self, request: Value) -> Value {
+
+        // Deployments speak the Chat Completions API: the instructions become
+        // a system message and the input a user message. The deployment picks
+        // the model, so `model` is dropped; every other parameter is kept.
+
+        let Value::Object(mut body) = request else {
+            return request;
+        };
+        body.remove("model");
+        let mut messages = Vec::new();
+        if let Some(instructions) = body.remove("instructions") {
+            messages.push(json!({ "role": "system", "content": instructions }));
+        }
+        if let Some(input) = body.remove("input") {
+            messages.push(json!({ "role": "user", "content": input }));
+        }
+        if let Some(tokens) = body.remove("max_output_tokens") {
+            body.insert("max_completion_tokens".to_string(), tokens);
+        }
+        if let Some(effort) = body.remove("reasoning").and_then(|r| r.get("effort").cloned()) {
+            body.insert("reasoning_effort".to_string(), effort);
+        }
+        let mut out = Map::new();
+        out.insert("messages".to_string(), Value::from(messages));
+        out.extend(body);
+        Value::Object(out)
+    }
+
+    fn parse_generation(&self, body: &[u8]) -> Generation {
+
+        // Reads a Chat Completions answer: the content of the first choice and
+        // the prompt and completion token counts.
+
+        let Ok(parsed) = serde_json::from_slice::<CompletionsBody>(body) else {
+            return Generation::default();
+        };
+        let text = parsed.choices.map(|choices| {
+            choices.into_iter().filter_map(|c| c.message.and_then(|m| m.content)).take(1).collect::<String>()
+        });
+        let usage = parsed.usage.map(|u| Usage { input_tokens: u.prompt_tokens, output_tokens: u.completion_tokens });
+        Generation { text, usage }
+    }
+}
+
+
+impl Anthropic {
+
+    pub fn new(base_url: Option<&str>) -> Anthropic {
+        Anthropic { base_url: base_url.unwrap_or(ANTHROPIC_BASE_URL).trim_end_matches('/').to_string() }
+    }
+}
+
+impl LlmProvider for Anthropic {
+
+    fn name(&self) -> &'static str {
+        "anthropic"
+    }
+
+    fn generate_url(&self, _model: &str) -> String {
+        format!("{}/messages", self.base_url)
+    }
+
+    fn models_url(&self) -> String {
+        format!("{}/models", self.base_url)
+    }
+
+    fn authorize(&self, request: RequestBuilder, api_key: &str) -> RequestBuilder {
+        request.header("x-api-key", api_key).header("anthropic-version", ANTHROPIC_VERSION)
+    }
+
+    fn api_key_name(&self) -> Option<&'static str> {
+        Some(ANTHROPIC_API_KEY)
+    }
+
+    fn encode_request(&self, request: Value) -> Value {
+
+        // The Messages API takes the instructions as `system` and the input as
+        // a user message, and requires `max_tokens`: the built-in `anthropic`
+        // profile sets it, else `max_output_tokens` or 8192 is used. It has no
+        // reasoning effort, so `reasoning` is dropped.
+
+        let Value::Object(mut body) = request else {
+            return request;
+        };
+        body.remove("reasoning");
+        if let Some(input) = body.remove("input") {
+            body.insert("messages".to_string(), json!([{ "role": "user", "content": input }]));
+        }
+        if let Some(instructions) = body.remove("instructions") {
+            body.insert("system".to_string(), instructions);
+        }
+        let max_output_tokens = body.remove("max_output_tokens");
+        if !body.contains_key("max_tokens") {
+            body.insert("max_tokens".to_string(), max_output_tokens.unwrap_or_else(|| Value::from(DEFAULT_ANTHROPIC_MAX_TOKENS)));
+        }
+        Value::Object(body)
+    }
+
+    fn parse_generation(&self, body: &[u8]) -> Generation {
+
+        // Joins the text blocks of the answer; the usage has the same
+        // `input_tokens`/`output_tokens` fields as the Responses API.
+
+        let Ok(parsed) = serde_json::from_slice::<MessagesBody>(body) else {
+            return Generation::default();
+        };
+        let text = parsed.content.map(|blocks| blocks.into_iter().filter_map(|b| b.text).collect::<Vec<_>>().join("\n"));
+        Generation { text, usage: parsed.usage }
+    }
+}
+
+impl Ollama {
+
+    pub fn new(host: Option<&str>) -> Ollama {
+
+        // Accepts a URL or, like `OLLAMA_HOST`, a bare `host:port`.
+
+        let host = host.unwrap_or(DEFAULT_OLLAMA_URL).trim_end_matches('/');
+        let base_url = match host.contains("://") {
+            true => host.to_string(),
+            false => format!("http://{host}"),
+        };
+        Ollama { base_url }
+    }
+}
+
+impl LlmProvider for Ollama {
+
+    fn name(&self) -> &'static str {
+        "ollama"
+    }
+
+    fn generate_url(&self, _model: &str) -> String {
+        format!("{}/api/generate", self.base_url)
+    }
+
+    fn models_url(&self) -> String {
+        format!("{}/api/tags", self.base_url)
+    }
+
+    fn authorize(&self, request: RequestBuilder, _api_key: &str) -> RequestBuilder {
+        request
+    }
+
+    fn api_key_name(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn encode_request(&self, request: Value) -> Value {
+
+        // `/api/generate` takes the input as `prompt`, the instructions as
+        // `system`, and the token limit (`num_predict`), `temperature` and
+        // `top_p` in `options`, next to the `options` of the request profile.
+        // `reasoning` is dropped. Streaming is turned off so the answer arrives
+        // as one JSON object.
+
+        let Value::Object(mut body) = request else {
+            return request;
+        };
+        if let Some(input) = body.remove("input") {
+            body.insert("prompt".to_string(), input);
+        }
+        if let Some(instructions) = body.remove("instructions") {
+            body.insert("system".to_string(), instructions);
+        }
+        body.remove("reasoning");
+        for (key, option) in [("max_output_tokens", "num_predict"), ("temperature", "temperature"), ("top_p", "top_p")] {
+            let Some(value) = body.remove(key) else {
+                continue;
+            };
+            let options = body.entry("options").or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(options) = options {
+                options.entry(option).or_insert(value);
+            }
+        }
+        body.insert("stream".to_string(), Value::Bool(false));
+        Value::Object(body)
+    }
+
+    fn parse_generation(&self, body: &[u8]) -> Generation {
+
+        // Reads the `response` text and the prompt and answer token counts.
+
+        let Ok(parsed) = serde_json::from_slice::<OllamaBody>(body) else {
+            return Generation::default();
+        };
+        let usage = match (parsed.prompt_eval_count, parsed.eval_count) {
+            (None, None) => None,
+            (input_tokens, output_tokens) => Some(Usage { input_tokens, output_tokens }),
+        };
+        Generation { text: parsed.response, usage }
+    }
+}
+
+
+// ====================================================
+//  Selection
+// ====================================================
+pub fn check_sampling(temperature: Option<f64>, top_p: Option<f64>) -> Result<(), DoxcerError> {
+
+    // Checks the sampling settings before any request: `temperature` must be
+    // between 0 and 2 and `top_p` between 0 and 1.
+
+    if let Some(t) = temperature.filter(|t| !(0.0..=2.0).contains(t)) {
+        return Err(DoxcerError::Config(format!("temperature must be between 0 and 2, got {t}")));
+    }
+    if let Some(p) = top_p.filter(|p| !(0.0..=1.0).contains(p)) {
+        return Err(DoxcerError::Config(format!("top_p must be between 0 and 1, got {p}")));
+    }
+    Ok(())
+}
+
+
+pub fn select_provider(name: &str, endpoint: Option<&str>) -> Result<Arc<dyn LlmProvider>, DoxcerError> {
+
+    // Builds the provider named by the `provider` setting.
+    //
+    // # Description
+    // The Azure providers take their endpoint from `AZURE_OPENAI_ENDPOINT`
+    // when `provider_endpoint` is not set. `azure-deployment` also reads
+    // `AZURE_OPENAI_DEPLOYMENT` and `AZURE_OPENAI_API_VERSION` (default
+    // 2024-10-21). `ollama` falls back to `OLLAMA_HOST` and then
+    // `http://localhost:11434`; it is the only provider allowed over plain
+    // http, since it usually runs on the same machine.
+    //
+    // # Parameters
+    // * `name` – `openai`, `azure` (the v1 Responses API), `azure-deployment`
+    //   (`/openai/deployments/<deployment>/...`), `anthropic` or `ollama`.
+    // * `endpoint` – The `provider_endpoint` setting: optional for `openai`,
+    //   `anthropic` and `ollama`, required (or from the environment) for the
+    //   Azure providers.
+    //
+    // # Returns
+    // * `Err(DoxcerError)` for an unknown provider or a missing or non-https endpoint.
+
+    let var = |name: &str| env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let name = name.trim().to_ascii_lowercase();
+    let endpoint = match name.as_str() {
+        "azure" | "azure-deployment" => endpoint.map(str::to_string).or_else(|| var(AZURE_ENDPOINT_ENV)),
+        "ollama" => return Ok(Arc::new(Ollama::new(endpoint.map(str::to_string).or_else(|| var(OLLAMA_HOST_ENV)).as_deref()))),
+        _ => endpoint.map(str::to_string),
+    };
+    if let Some(endpoint) = endpoint.as_ref().filter(|e| !e.starts_with("https://")) {
+        return Err(DoxcerError::Config(format!("provider_endpoint must be an https:// URL, got {endpoint:?}")));
+    }
+    let missing = || DoxcerError::Config(format!("provider = \"{name}\" needs provider_endpoint or {AZURE_ENDPOINT_ENV} (https://<resource>.openai.azure.com)"));
+    match name.as_str() {
+        "openai" => Ok(Arc::new(OpenAi::new(endpoint.as_deref()))),
+        "anthropic" => Ok(Arc::new(Anthropic::new(endpoint.as_deref()))),
+        "azure" => Ok(Arc::new(AzureOpenAi::new(&endpoint.ok_or_else(missing)?))),
+        "azure-deployment" => Ok(Arc::new(AzureDeployment::new(
+            &endpoint.ok_or_else(missing)?,
+            var(AZURE_DEPLOYMENT_ENV),
+            var(AZURE_API_VERSION_ENV),
+        ))),
+        other => Err(DoxcerError::Config(format!("unknown provider {other:?}; expected one of {}", PROVIDER_NAMES.join(", ")))),
+    }
+}
+
+
+pub fn parse_responses_body(body: &[u8]) -> Generation {
+
+    // Joins the text parts of a Responses API answer and reads its usage.
+
+    let Ok(parsed) = serde_json::from_slice::<ResponsesBody>(body) else {
+        return Generation::default();
+    };
+    let text = parsed.output.map(|outputs| {
+        outputs
+            .into_iter()
+            .flat_map(|o| o.content)
+            .filter_map(|content| content.text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+    Generation { text, usage: parsed.usage }
+}
+
+
+pub fn parse_responses_event(data: &str) -> StreamEvent {
+
+    // Reads one event of a streamed Responses API answer.
+    //
+    // # Description
+    // `response.output_text.delta` carries a piece of text. The answer ends with
+    // `response.completed` (or `response.incomplete`, e.g. at the token limit),
+    // whose `response` has the same shape as a non-streamed answer.
+    // `response.failed` and `error` end the stream with the API's message.
+
+    let Ok(event) = serde_json::from_str::<Value>(data) else {
+        return StreamEvent::Ignored;
+    };
+    let message = |value: &Value| value.get("message").and_then(Value::as_str).unwrap_or("unknown error").to_string();
+    match event.get("type").and_then(Value::as_str) {
+        Some("response.output_text.delta") => {
+            StreamEvent::Delta(event.get("delta").and_then(Value::as_str).unwrap_or_default().to_string())
+        }
+        Some("response.completed" | "response.incomplete") => {
+            let response = event.get("response").cloned().unwrap_or(Value::Null);
+            StreamEvent::Completed(parse_responses_body(response.to_string().as_bytes()))
+        }
+        Some("response.failed") => {
+            let error = event.pointer("/response/error").cloned().unwrap_or(Value::Null);
+            StreamEvent::Failed(message(&error))
+        }
+        Some("error") => StreamEvent::Failed(message(&event)),
+        _ => StreamEvent::Ignored,
+    }
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::{self, JoinHandle};
+    use std::time::{Duration, Instant};
+
+    // Answers one request with `status` and `body`; joining the handle gives
+    // the request line, headers and body as received.
+    fn serve_once(status: &str, body: &str) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let response = format!("HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len());
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request = String::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+                request.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            request.push_str(&String::from_utf8_lossy(&body));
+            reader.into_inner().write_all(response.as_bytes()).unwrap();
+            request
+        });
+        (url, handle)
+    }
+
+    fn prompt() -> Prompt {
+        Prompt { instructions: Some("Document this notebook.".to_string()), input: "df = spark.read.table('x')".to_string() }
+    }
+
+
+    #[test]
+    fn generate_posts_the_request_and_reads_the_answer() {
+        let (url, server) = serve_once("200 OK", r##"{"output":[{"content":[{"text":"# Notebook"}]}],"usage":{"input_tokens":12,"output_tokens":3}}"##);
+        let provider = OpenAi::new(Some(&url));
+        let generation = provider.generate(&Client::new(), "sk-test", &ChatRequest::new("gpt-test", &prompt())).unwrap();
+        assert_eq!(generation.text.as_deref(), Some("# Notebook"));
+        assert_eq!(generation.usage, Some(Usage { input_tokens: Some(12), output_tokens: Some(3) }));
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /responses "), "{request}");
+        assert!(request.to_ascii_lowercase().contains("authorization: bearer sk-test"), "{request}");
+        assert!(request.contains(r#""model":"gpt-test""#), "{request}");
+        assert!(request.contains(r#""instructions":"Document this notebook.""#), "{request}");
+        assert!(!request.contains("stream"), "{request}");
+    }
+
+
+    #[test]
+    fn generate_uses_the_provider_wire_format() {
+        let (url, server) = serve_once("200 OK", r##"{"content":[{"type":"text","text":"# Notebook"}],"usage":{"input_tokens":12,"output_tokens":3}}"##);
+        let provider = Anthropic::new(Some(&url));
+        let generation = provider.generate(&Client::new(), "sk-ant", &ChatRequest::new("claude-test", &prompt())).unwrap();
+        assert_eq!(generation.text.as_deref(), Some("# Notebook"));
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /messages "), "{request}");
+        assert!(request.contains(r#""system":"Document this notebook.""#), "{request}");
+        assert!(request.contains(r#""max_tokens""#), "{request}");
+    }
+
+
+    #[test]
+    fn an_error_status_is_a_provider_error() {
+        let (url, server) = serve_once("429 Too Many Requests", r#"{"error":{"code":"rate_limit_exceeded"}}"#);
+        let provider = OpenAi::new(Some(&url));
+        let error = provider.generate(&Client::new(), "sk-test", &ChatRequest::new("gpt-test", &prompt())).unwrap_err();
+        assert!(matches!(&error, DoxcerError::Provider(message) if message.contains("rate_limit_exceeded")), "{error}");
+        server.join().unwrap();
+    }
+
+
+    #[test]
+    fn generate_cancellable_gives_up_on_a_silent_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let open: Vec<_> = listener.incoming().flatten().collect();
+            drop(open);
+        });
+        let cancel = CancelToken::new();
+        let canceller = cancel.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            canceller.cancel();
+        });
+        let started = Instant::now();
+        let provider = OpenAi::new(Some(&url));
+        let error = provider.generate_cancellable(&Client::new(), "sk-test", &ChatRequest::new("gpt-test", &prompt()), &cancel).unwrap_err();
+        assert_eq!(error, DoxcerError::Cancelled { partial: None });
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+
+    #[test]
+    fn request_body_adds_the_stream_flag() {
+        let request = ChatRequest::new("gpt-test", &prompt());
+        let body: Value = serde_json::from_slice(&OpenAi::default().request_body(&request, true).unwrap()).unwrap();
+        assert_eq!(body["stream"], Value::Bool(true));
+        assert_eq!(body["input"], "df = spark.read.table('x')");
+        let body: Value = serde_json::from_slice(&OpenAi::default().request_body(&request, false).unwrap()).unwrap();
+        assert!(body.get("stream").is_none());
+    }
+}
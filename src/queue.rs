@@ -0,0 +1,263 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::hash::Hash;
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const DEFAULT_QUEUE_CAPACITY: usize = 256;
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone)]
+pub struct WorkQueue<K, V> {
+    pub capacity: usize,
+    order: VecDeque<K>,
+    pending: HashMap<K, V>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pushed<K> {
+    Queued,
+    Coalesced,
+    DroppedOldest(K),
+}
+
+
+// ====================================================
+//  Work Queue
+// ====================================================
+pub fn capacity_from_env() -> Result<usize, DoxcerError> {
+
+    // Reads the queue capacity from `DOXCER_QUEUE_CAPACITY` (default 256).
+
+    match env::var("DOXCER_QUEUE_CAPACITY") {
+        Ok(v) => v
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|c| *c > 0)
+            .ok_or_else(|| DoxcerError::Config(format!("DOXCER_QUEUE_CAPACITY must be a positive whole number, got {v:?}"))),
+        Err(_) => Ok(DEFAULT_QUEUE_CAPACITY),
+    }
+}
+
+
+impl<K: Clone + Eq + Hash, V> WorkQueue<K, V> {
+
+    pub fn new(capacity: usize) -> WorkQueue<K, V> {
+
+        // Creates an empty queue holding at most `capacity` distinct keys.
+
+        WorkQueue { capacity: capacity.max(1), order: VecDeque::new(), pending: HashMap::new() }
+    }
+
+
+    pub fn push(&mut self, key: K, value: V) -> Pushed<K> {
+
+        // Queues work for a key (a notebook path), coalescing repeated events.
+        //
+        // # Description
+        // A key that is already queued keeps its place, but its value is replaced:
+        // the latest content wins. A new key on a full queue evicts the oldest one,
+        // which is returned so the caller can log what was dropped.
+
+        if let Some(existing) = self.pending.get_mut(&key) {
+            *existing = value;
+            return Pushed::Coalesced;
+        }
+        let dropped = if self.order.len() >= self.capacity {
+            self.order.pop_front().inspect(|oldest| {
+                self.pending.remove(oldest);
+            })
+        } else {
+            None
+        };
+        self.order.push_back(key.clone());
+        self.pending.insert(key, value);
+        dropped.map_or(Pushed::Queued, Pushed::DroppedOldest)
+    }
+
+
+    pub fn pop(&mut self) -> Option<(K, V)> {
+
+        // Takes the oldest queued work.
+
+        let key = self.order.pop_front()?;
+        let value = self.pending.remove(&key)?;
+        Some((key, value))
+    }
+
+
+    pub fn take_ready<F>(&mut self, slots: usize, busy: F) -> Vec<(K, V)>
+    where
+        F: Fn(&K) -> bool,
+    {
+
+        // Takes up to `slots` of the oldest queued work whose key is not busy.
+        //
+        // # Description
+        // `slots` is the number of free workers (`--jobs` minus the running
+        // generations), so the queue never starts more than that. A key that is
+        // still being worked on stays queued in its place and is taken, with its
+        // latest value, once it is no longer busy.
+
+        let mut taken = Vec::new();
+        let mut index = 0;
+        while taken.len() < slots && index < self.order.len() {
+            if busy(&self.order[index]) {
+                index += 1;
+                continue;
+            }
+            if let Some(key) = self.order.remove(index)
+                && let Some(value) = self.pending.remove(&key)
+            {
+                taken.push((key, value));
+            }
+        }
+        taken
+    }
+
+
+    pub fn len(&self) -> usize {
+
+        // Returns the queue depth (distinct keys waiting).
+
+        self.order.len()
+    }
+
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watch::Watcher;
+    use notify::event::ModifyKind;
+    use notify::{Event, EventKind};
+    use std::collections::HashSet;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::{Duration, Instant};
+
+
+    #[test]
+    fn repeated_events_coalesce_and_the_latest_value_wins() {
+        let mut queue = WorkQueue::new(4);
+        assert_eq!(queue.push("a.py", 1), Pushed::Queued);
+        assert_eq!(queue.push("b.py", 1), Pushed::Queued);
+        assert_eq!(queue.push("a.py", 2), Pushed::Coalesced);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop(), Some(("a.py", 2)));
+        assert_eq!(queue.pop(), Some(("b.py", 1)));
+        assert!(queue.is_empty());
+    }
+
+
+    #[test]
+    fn a_full_queue_drops_the_oldest_key() {
+        let mut queue = WorkQueue::new(2);
+        queue.push("a.py", 1);
+        queue.push("b.py", 1);
+        assert_eq!(queue.push("c.py", 1), Pushed::DroppedOldest("a.py"));
+        // A coalesced event does not need room, so nothing more is dropped.
+        assert_eq!(queue.push("b.py", 2), Pushed::Coalesced);
+        assert_eq!(queue.take_ready(5, |_| false), vec![("b.py", 2), ("c.py", 1)]);
+    }
+
+
+    #[test]
+    fn busy_keys_keep_their_place() {
+        let mut queue = WorkQueue::new(8);
+        for key in ["a.py", "b.py", "c.py", "d.py"] {
+            queue.push(key, 0);
+        }
+        assert_eq!(queue.take_ready(2, |key| *key == "a.py"), vec![("b.py", 0), ("c.py", 0)]);
+        assert_eq!(queue.take_ready(0, |_| false), Vec::new());
+        assert_eq!(queue.take_ready(2, |_| false), vec![("a.py", 0), ("d.py", 0)]);
+    }
+
+
+    #[test]
+    fn an_event_storm_stays_within_capacity_and_jobs() {
+        // 40 ticks of 25 events spread over 12 files, into a queue of 6 with
+        // 3 jobs; a job takes 2 ticks.
+        let (capacity, jobs) = (6, 3);
+        let mut queue = WorkQueue::new(capacity);
+        let mut running: Vec<(usize, u32, usize)> = Vec::new();
+        let mut dropped = 0;
+        let mut started = 0;
+        for tick in 0..40u32 {
+            for event in 0..25usize {
+                let file = (event * 7 + tick as usize) % 12;
+                match queue.push(file, tick) {
+                    Pushed::DroppedOldest(_) => dropped += 1,
+                    Pushed::Queued | Pushed::Coalesced => {}
+                }
+                assert!(queue.len() <= capacity);
+            }
+            running.retain(|(_, _, done)| *done > tick as usize);
+            let busy: HashSet<usize> = running.iter().map(|(file, _, _)| *file).collect();
+            for (file, value) in queue.take_ready(jobs - running.len(), |file| busy.contains(file)) {
+                assert_eq!(value, tick, "a queued file runs with its latest event");
+                running.push((file, value, tick as usize + 2));
+                started += 1;
+            }
+            assert!(running.len() <= jobs);
+            let files: HashSet<usize> = running.iter().map(|(file, _, _)| *file).collect();
+            assert_eq!(files.len(), running.len(), "a file never runs twice at once");
+        }
+        // 1000 events, but only as many generations as the jobs allow.
+        assert!(started <= 40 * jobs);
+        assert!(dropped > 0);
+    }
+
+
+    #[test]
+    fn a_storm_of_saves_reaches_the_queue_once_per_file() {
+        // The watcher's clock is passed in, so the debounce is simulated.
+        let dir = tempfile::tempdir().unwrap();
+        let files: Vec<PathBuf> = (0..3).map(|i| dir.path().join(format!("nb{i}.py"))).collect();
+        for file in &files {
+            fs::write(file, "x").unwrap();
+        }
+        let start = Instant::now();
+        let mut watcher = Watcher::new(&[dir.path().to_path_buf()], Duration::from_millis(300)).unwrap();
+        let mut queue = WorkQueue::new(8);
+        for save in 1..=20usize {
+            let now = start + Duration::from_millis(save as u64 * 10);
+            for file in &files {
+                watcher.record(&Event::new(EventKind::Modify(ModifyKind::Any)).add_path(file.clone()), now);
+            }
+            for path in watcher.settled(now) {
+                queue.push(path, save);
+            }
+        }
+        assert!(queue.is_empty(), "nothing is ready while the files keep changing");
+        for path in watcher.settled(start + Duration::from_secs(1)) {
+            queue.push(path, 0);
+        }
+        assert_eq!(queue.len(), 3);
+    }
+}
@@ -0,0 +1,180 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// External Libraries
+use chrono::{DateTime, Utc};
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const DEFAULT_RETRY_MAX_ATTEMPTS: u32 = 4;
+pub const DEFAULT_RETRY_BASE_DELAY_MS: u64 = 1000;
+pub const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+
+// ====================================================
+//  Classification
+// ====================================================
+pub fn is_retryable_status(status: u16, body: &str) -> bool {
+
+    // Decides whether a failed API request is worth sending again.
+    //
+    // # Description
+    // Rate limits (429) and server errors (5xx) are usually over within
+    // seconds. A 429 for exhausted quota is not: it stays until the account
+    // is topped up, so it goes straight to the fallback models instead.
+
+    match status {
+        429 => !body.contains("insufficient_quota"),
+        500..=599 => status != 501,
+        _ => false,
+    }
+}
+
+
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+
+    // Parses a `Retry-After` header: a number of seconds or an HTTP date.
+    // A date in the past means "now".
+
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+    Some((at - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+
+// ====================================================
+//  Backoff
+// ====================================================
+pub fn retry_delay(attempt: u32, base: Duration, retry_after: Option<Duration>) -> Result<Duration, DoxcerError> {
+
+    // Returns how long to wait before the next attempt.
+    //
+    // # Description
+    // A `Retry-After` from the server wins, as long as it is within
+    // `MAX_RETRY_DELAY`; a longer one is not retried at all, since waiting less
+    // than the server asked would only be refused again. Otherwise the delay
+    // doubles per attempt (`base`, `2 × base`, `4 × base`, ...), capped at
+    // `MAX_RETRY_DELAY`, and a random half of it is added or left out ("equal
+    // jitter"), so parallel runs that were limited together do not all come
+    // back at the same moment.
+    //
+    // # Parameters
+    // * `attempt` – The attempt that just failed, starting at 1.
+    //
+    // # Returns
+    // * `Ok(Duration)` – the time to wait.
+    // * `Err(DoxcerError)` – the server asked to wait longer than `MAX_RETRY_DELAY`.
+
+    if let Some(retry_after) = retry_after {
+        if retry_after > MAX_RETRY_DELAY {
+            return Err(DoxcerError::Provider(format!(
+                "the API asked to retry after {}s (Retry-After), longer than the {}s doxcer waits",
+                retry_after.as_secs(),
+                MAX_RETRY_DELAY.as_secs()
+            )));
+        }
+        return Ok(retry_after);
+    }
+    let exponential = base.saturating_mul(1 << attempt.saturating_sub(1).min(16)).min(MAX_RETRY_DELAY);
+    let half = exponential / 2;
+    Ok(half + jitter(half))
+}
+
+
+fn jitter(max: Duration) -> Duration {
+
+    // Returns a pseudo-random duration up to `max`, from the clock and a
+    // counter; no cryptographic quality is needed to spread retries.
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos() as u64).unwrap_or(0);
+    let mut seed = nanos ^ COUNTER.fetch_add(1, Ordering::Relaxed).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    seed ^= seed >> 33;
+    seed = seed.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    seed ^= seed >> 33;
+    let max_ms = max.as_millis() as u64;
+    Duration::from_millis(if max_ms == 0 { 0 } else { seed % (max_ms + 1) })
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-10-15T12:00:00Z").unwrap().with_timezone(&Utc)
+    }
+
+
+    #[test]
+    fn retry_after_in_seconds_is_honoured() {
+        let after = parse_retry_after(" 30 ", now());
+        assert_eq!(after, Some(Duration::from_secs(30)));
+        assert_eq!(retry_delay(1, Duration::from_secs(1), after).unwrap(), Duration::from_secs(30));
+        assert_eq!(retry_delay(3, Duration::from_secs(1), Some(MAX_RETRY_DELAY)).unwrap(), MAX_RETRY_DELAY);
+    }
+
+
+    #[test]
+    fn retry_after_as_an_http_date_is_the_time_until_then() {
+        let after = parse_retry_after("Thu, 15 Oct 2026 12:00:45 GMT", now());
+        assert_eq!(after, Some(Duration::from_secs(45)));
+        assert_eq!(retry_delay(1, Duration::from_secs(1), after).unwrap(), Duration::from_secs(45));
+        assert_eq!(parse_retry_after("Thu, 15 Oct 2026 11:59:00 GMT", now()), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("tomorrow", now()), None);
+    }
+
+
+    #[test]
+    fn retry_after_over_the_cap_fails_instead_of_being_cut() {
+        for value in ["120", "Thu, 15 Oct 2026 12:05:00 GMT"] {
+            let error = retry_delay(1, Duration::from_secs(1), parse_retry_after(value, now())).unwrap_err();
+            assert!(matches!(error, DoxcerError::Provider(_)), "{value}");
+            assert!(error.to_string().contains("longer than the 60s doxcer waits"), "{error}");
+        }
+    }
+
+
+    #[test]
+    fn backoff_doubles_with_jitter_up_to_the_cap() {
+        let base = Duration::from_millis(1000);
+        for attempt in 1..=3 {
+            let full = base * (1 << (attempt - 1));
+            for _ in 0..20 {
+                let delay = retry_delay(attempt, base, None).unwrap();
+                assert!(delay >= full / 2 && delay <= full, "attempt {attempt}: {delay:?}");
+            }
+        }
+        assert!(retry_delay(30, base, None).unwrap() <= MAX_RETRY_DELAY);
+    }
+
+
+    #[test]
+    fn quota_errors_and_client_errors_are_not_retried() {
+        assert!(is_retryable_status(429, "rate limited"));
+        assert!(!is_retryable_status(429, r#"{"error": {"code": "insufficient_quota"}}"#));
+        assert!(is_retryable_status(503, ""));
+        assert!(!is_retryable_status(501, ""));
+        assert!(!is_retryable_status(400, ""));
+    }
+}
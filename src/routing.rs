@@ -0,0 +1,175 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// External Libraries
+use serde::Deserialize;
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoutingBand {
+    pub below: Option<usize>,
+    pub model: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelRouting {
+    pub bands: Vec<RoutingBand>,
+}
+
+
+// ====================================================
+//  Validation
+// ====================================================
+pub fn validate_routing(routing: &ModelRouting) -> Result<(), DoxcerError> {
+
+    // Checks that the bands are ordered by strictly increasing `below` and that
+    // only the last band, the catch-all, leaves `below` out.
+
+    let mut previous = 0;
+    for (i, band) in routing.bands.iter().enumerate() {
+        match band.below {
+            Some(below) if below <= previous => {
+                return Err(DoxcerError::Config(format!(
+                    "model_routing band {} (`below = {below}`) must have a larger `below` than the band before it",
+                    i + 1
+                )));
+            }
+            Some(below) => previous = below,
+            None if i + 1 < routing.bands.len() => {
+                return Err(DoxcerError::Config(format!("model_routing band {} has no `below`; only the last band may be a catch-all", i + 1)));
+            }
+            None => {}
+        }
+        if band.model.trim().is_empty() {
+            return Err(DoxcerError::Config(format!("model_routing band {} has an empty `model`", i + 1)));
+        }
+    }
+    Ok(())
+}
+
+
+// ====================================================
+//  Routing
+// ====================================================
+pub fn route_by_tokens(routing: &ModelRouting, tokens: usize) -> Option<(usize, &RoutingBand)> {
+
+    // Returns the first band a token count falls in, with its index.
+    //
+    // # Description
+    // A band covers every count from the previous band's `below` up to, but not
+    // including, its own `below`; a band without `below` covers everything
+    // above. `None` when the count is above the last band and there is no
+    // catch-all.
+
+    routing
+        .bands
+        .iter()
+        .enumerate()
+        .find(|(_, band)| band.below.is_none_or(|below| tokens < below))
+}
+
+
+pub fn describe_band(routing: &ModelRouting, index: usize) -> String {
+
+    // Renders a band's token range, e.g. `<2000`, `2000-29999` or `>=30000`.
+
+    let from = index.checked_sub(1).and_then(|i| routing.bands.get(i)).and_then(|b| b.below);
+    let below = routing.bands.get(index).and_then(|b| b.below);
+    match (from, below) {
+        (None, Some(below)) => format!("<{below}"),
+        (Some(from), Some(below)) => format!("{from}-{}", below - 1),
+        (Some(from), None) => format!(">={from}"),
+        (None, None) => "any size".to_string(),
+    }
+}
+
+
+pub fn describe_routing(routing: &ModelRouting) -> String {
+
+    // Renders all bands for `config dump` and `--dry-run`.
+
+    let bands: Vec<String> = (0..routing.bands.len())
+        .map(|i| format!("{}: {}", describe_band(routing, i), routing.bands[i].model))
+        .collect();
+    format!("[{}]", bands.join(", "))
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routing(bands: &[(Option<usize>, &str)]) -> ModelRouting {
+        ModelRouting { bands: bands.iter().map(|(below, model)| RoutingBand { below: *below, model: model.to_string() }).collect() }
+    }
+
+    fn model(routing: &ModelRouting, tokens: usize) -> Option<&str> {
+        route_by_tokens(routing, tokens).map(|(_, band)| band.model.as_str())
+    }
+
+
+    #[test]
+    fn each_below_is_exclusive() {
+        let routing = routing(&[(Some(2000), "mini"), (Some(30000), "standard"), (None, "large")]);
+        assert_eq!(model(&routing, 0), Some("mini"));
+        assert_eq!(model(&routing, 1999), Some("mini"));
+        assert_eq!(model(&routing, 2000), Some("standard"));
+        assert_eq!(model(&routing, 29999), Some("standard"));
+        assert_eq!(model(&routing, 30000), Some("large"));
+        assert_eq!(model(&routing, usize::MAX), Some("large"));
+        assert_eq!(route_by_tokens(&routing, 2000).map(|(i, _)| i), Some(1));
+    }
+
+
+    #[test]
+    fn counts_above_the_last_band_without_a_catch_all_are_unrouted() {
+        let routing = routing(&[(Some(1), "tiny"), (Some(2000), "mini")]);
+        assert_eq!(model(&routing, 0), Some("tiny"));
+        assert_eq!(model(&routing, 1), Some("mini"));
+        assert_eq!(model(&routing, 1999), Some("mini"));
+        assert_eq!(model(&routing, 2000), None);
+        assert_eq!(model(&ModelRouting::default(), 0), None);
+    }
+
+
+    #[test]
+    fn describes_band_ranges_at_their_boundaries() {
+        let routing = routing(&[(Some(2000), "mini"), (Some(2001), "one"), (None, "large")]);
+        assert_eq!(describe_band(&routing, 0), "<2000");
+        assert_eq!(describe_band(&routing, 1), "2000-2000");
+        assert_eq!(describe_band(&routing, 2), ">=2001");
+        assert_eq!(describe_routing(&routing), "[<2000: mini, 2000-2000: one, >=2001: large]");
+        assert_eq!(describe_routing(&self::routing(&[(None, "only")])), "[any size: only]");
+    }
+
+
+    #[test]
+    fn validation_requires_strictly_increasing_bands() {
+        assert!(validate_routing(&routing(&[(Some(2000), "a"), (Some(2001), "b"), (None, "c")])).is_ok());
+        assert_eq!(
+            validate_routing(&routing(&[(Some(2000), "a"), (Some(2000), "b")])).unwrap_err().to_string(),
+            "model_routing band 2 (`below = 2000`) must have a larger `below` than the band before it"
+        );
+        assert!(validate_routing(&routing(&[(Some(0), "a")])).unwrap_err().to_string().contains("band 1 (`below = 0`)"));
+        assert_eq!(
+            validate_routing(&routing(&[(None, "a"), (Some(10), "b")])).unwrap_err().to_string(),
+            "model_routing band 1 has no `below`; only the last band may be a catch-all"
+        );
+        assert_eq!(validate_routing(&routing(&[(None, " ")])).unwrap_err().to_string(), "model_routing band 1 has an empty `model`");
+    }
+}
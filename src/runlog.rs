@@ -0,0 +1,359 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+// External Libraries
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const DEFAULT_RUN_LOG_PATH: &str = ".doxcer/runs.jsonl";
+pub const TAGS_ENV: &str = "DOXCER_TAGS";
+// Group keys that read a record field instead of a tag.
+pub const BUILTIN_GROUPS: [&str; 2] = ["template", "model"];
+// Shown for a record without the grouped tag.
+pub const UNTAGGED: &str = "-";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    #[serde(default)]
+    pub timestamp: String,
+    pub doxcer_version: String,
+    pub notebook: String,
+    pub source_hash: String,
+    pub model: String,
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_hit: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunWindow {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RunTotals {
+    pub runs: u64,
+    pub ok: u64,
+    pub cache_hits: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunSummary {
+    pub group_by: Vec<String>,
+    pub groups: BTreeMap<Vec<String>, RunTotals>,
+    pub total: RunTotals,
+}
+
+impl RunTotals {
+
+    pub fn add(&mut self, record: &RunRecord) {
+        self.runs += 1;
+        self.ok += u64::from(record.status == "ok");
+        self.cache_hits += u64::from(record.cache_hit == Some(true));
+        self.input_tokens += record.input_tokens.unwrap_or(0);
+        self.output_tokens += record.output_tokens.unwrap_or(0);
+    }
+}
+
+
+// ====================================================
+//  Run Log
+// ====================================================
+impl RunRecord {
+
+    pub fn new(notebook: &Path, source_hash: &str, model: &str, status: &str) -> RunRecord {
+
+        // Creates a record for one documented notebook, stamped with the current
+        // UTC time and this doxcer version.
+
+        RunRecord {
+            timestamp: crate::timefmt::now_rfc3339(),
+            doxcer_version: crate::version().to_string(),
+            notebook: notebook.display().to_string(),
+            source_hash: source_hash.to_string(),
+            model: model.to_string(),
+            status: status.to_string(),
+            cache_hit: None,
+            idempotency_key: None,
+            error: None,
+            template_hash: None,
+            input_tokens: None,
+            output_tokens: None,
+            tags: BTreeMap::new(),
+        }
+    }
+}
+
+
+pub fn append_run_record(path: &Path, record: &RunRecord) -> Result<(), DoxcerError> {
+
+    // Appends a record as one JSON line to the run log.
+    //
+    // # Description
+    // The run log is append-only JSONL, so concurrent or interrupted runs never
+    // corrupt earlier records. The parent directory is created when needed.
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .map_err(|e| DoxcerError::Input(format!("Failed to create {}: {e}", parent.display())))?;
+    }
+    let line = serde_json::to_string(record)
+        .map_err(|e| DoxcerError::Input(format!("Failed to serialize run record: {e}")))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| DoxcerError::Input(format!("Failed to open run log {}: {e}", path.display())))?;
+    writeln!(file, "{line}").map_err(|e| DoxcerError::Input(format!("Failed to write run log {}: {e}", path.display())))
+}
+
+
+pub fn read_run_records(path: &Path) -> Result<Vec<RunRecord>, DoxcerError> {
+
+    // Reads all records from the run log.
+    //
+    // # Description
+    // A missing log yields no records. Lines that cannot be parsed (for example a
+    // line still being written by a concurrent run) are skipped.
+
+    let raw = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(DoxcerError::Input(format!("Failed to read run log {}: {e}", path.display()))),
+    };
+    Ok(raw.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
+
+
+// ====================================================
+//  Tags
+// ====================================================
+pub fn parse_tag(text: &str) -> Result<(String, String), DoxcerError> {
+
+    // Parses one `key=value` run tag.
+    //
+    // # Description
+    // Neither part may be empty or contain `=`, `,` or a line break, so tags
+    // survive `DOXCER_TAGS` lists, `--group-by` lists and CSV rows unchanged.
+    // Surrounding whitespace is trimmed.
+
+    let Some((key, value)) = text.split_once('=') else {
+        return Err(DoxcerError::Input(format!("expected key=value, got {text:?}")));
+    };
+    let (key, value) = (key.trim(), value.trim());
+    for (part, name) in [(key, "key"), (value, "value")] {
+        if part.is_empty() {
+            return Err(DoxcerError::Input(format!("tag {text:?} has an empty {name}")));
+        }
+        if part.contains(['=', ',', '\n', '\r']) {
+            return Err(DoxcerError::Input(format!("tag {name} {part:?} may not contain `=`, `,` or a line break")));
+        }
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+
+pub fn parse_tags(list: &str) -> Result<BTreeMap<String, String>, DoxcerError> {
+
+    // Parses a comma-separated list of tags, as in `DOXCER_TAGS=team=data,cost_center=42`.
+
+    list.split(',').map(str::trim).filter(|t| !t.is_empty()).map(parse_tag).collect()
+}
+
+
+// ====================================================
+//  Summary
+// ====================================================
+impl RunWindow {
+
+    pub fn contains(&self, record: &RunRecord) -> bool {
+
+        // Checks a record against `[since, until)`. Without bounds every record
+        // counts; with bounds a record without a readable timestamp does not.
+
+        if self.since.is_none() && self.until.is_none() {
+            return true;
+        }
+        let Ok(at) = DateTime::parse_from_rfc3339(&record.timestamp).map(|at| at.with_timezone(&Utc)) else {
+            return false;
+        };
+        self.since.is_none_or(|since| at >= since) && self.until.is_none_or(|until| at < until)
+    }
+}
+
+
+pub fn group_value(record: &RunRecord, key: &str) -> String {
+
+    // Returns the value a record is grouped under for one `--group-by` key:
+    // the template hash or model for the built-in keys, else the tag.
+
+    let value = match key {
+        "template" => record.template_hash.clone(),
+        "model" => Some(record.model.clone()),
+        tag => record.tags.get(tag).cloned(),
+    };
+    value.unwrap_or_else(|| UNTAGGED.to_string())
+}
+
+
+pub fn summarize_runs(path: &Path, group_by: &[String], window: RunWindow) -> Result<RunSummary, DoxcerError> {
+
+    // Totals the run log per group, reading it one line at a time.
+    //
+    // # Description
+    // Only the running totals per group are kept in memory, so a year of
+    // history costs as much as the number of distinct groups. Like
+    // [`read_run_records`], a missing log yields nothing and unreadable lines
+    // are skipped.
+
+    let mut summary = RunSummary { group_by: group_by.to_vec(), ..RunSummary::default() };
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(summary),
+        Err(e) => return Err(DoxcerError::Input(format!("Failed to read run log {}: {e}", path.display()))),
+    };
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| DoxcerError::Input(format!("Failed to read run log {}: {e}", path.display())))?;
+        let Ok(record) = serde_json::from_str::<RunRecord>(&line) else {
+            continue;
+        };
+        if !window.contains(&record) {
+            continue;
+        }
+        let group = group_by.iter().map(|key| group_value(&record, key)).collect();
+        summary.groups.entry(group).or_default().add(&record);
+        summary.total.add(&record);
+    }
+    Ok(summary)
+}
+
+
+// ====================================================
+//  Rendering
+// ====================================================
+const TOTAL_COLUMNS: [&str; 5] = ["RUNS", "OK", "CACHED", "INPUT_TOKENS", "OUTPUT_TOKENS"];
+
+fn total_cells(totals: &RunTotals) -> [String; 5] {
+    [totals.runs, totals.ok, totals.cache_hits, totals.input_tokens, totals.output_tokens].map(|n| n.to_string())
+}
+
+
+pub fn render_summary_table(summary: &RunSummary) -> String {
+
+    // Renders `doxcer runs summary` as an aligned table with a total row.
+
+    let keys = summary.group_by.len();
+    let mut header: Vec<String> = summary.group_by.iter().map(|key| key.to_uppercase()).collect();
+    header.extend(TOTAL_COLUMNS.map(String::from));
+    let mut rows = vec![header];
+    if keys > 0 {
+        for (group, totals) in &summary.groups {
+            rows.push(group.iter().cloned().chain(total_cells(totals)).collect());
+        }
+    }
+    let mut total: Vec<String> = (0..keys).map(|i| if i == 0 { "TOTAL".to_string() } else { String::new() }).collect();
+    total.extend(total_cells(&summary.total));
+    rows.push(total);
+
+    let columns = rows[0].len();
+    let widths: Vec<usize> = (0..columns).map(|i| rows.iter().map(|row| row[i].chars().count()).max().unwrap_or(0)).collect();
+    let mut out = String::new();
+    for row in &rows {
+        let cells: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| if i < keys { format!("{cell:<w$}", w = widths[i]) } else { format!("{cell:>w$}", w = widths[i]) })
+            .collect();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+
+pub fn render_summary_csv(summary: &RunSummary) -> String {
+
+    // Renders `doxcer runs summary --format csv`: a header and one row per
+    // group, without a total row so spreadsheet sums stay correct.
+
+    let mut header: Vec<String> = summary.group_by.clone();
+    header.extend(TOTAL_COLUMNS.map(str::to_lowercase));
+    let mut out = csv_row(&header);
+    for (group, totals) in &summary.groups {
+        out.push_str(&csv_row(&group.iter().cloned().chain(total_cells(totals)).collect::<Vec<_>>()));
+    }
+    out
+}
+
+
+pub fn render_summary_json(summary: &RunSummary) -> Result<String, DoxcerError> {
+
+    // Renders `doxcer runs summary --format json`: each group with its key
+    // values and totals, followed by the overall total.
+
+    let groups: Vec<serde_json::Value> = summary
+        .groups
+        .iter()
+        .map(|(group, totals)| {
+            let keys: serde_json::Map<String, serde_json::Value> =
+                summary.group_by.iter().cloned().zip(group.iter().cloned().map(serde_json::Value::String)).collect();
+            serde_json::json!({ "group": keys, "totals": totals })
+        })
+        .collect();
+    let report = serde_json::json!({ "group_by": summary.group_by, "groups": groups, "total": summary.total });
+    serde_json::to_string_pretty(&report).map_err(|e| DoxcerError::Input(format!("Failed to serialize run summary: {e}")))
+}
+
+
+fn csv_row(cells: &[String]) -> String {
+
+    // Joins cells into one RFC 4180 line, quoting cells with `,` or `"`.
+
+    let cells: Vec<String> = cells
+        .iter()
+        .map(|cell| {
+            if cell.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.clone()
+            }
+        })
+        .collect();
+    format!("{}\r\n", cells.join(","))
+}
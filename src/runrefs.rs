@@ -0,0 +1,327 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Internal Modules
+use crate::discovery::NOTEBOOK_EXTENSIONS;
+use crate::lineage::summary_line;
+use crate::verify::expected_doc_path;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const RUN_MAGIC: &str = "%run";
+pub const RUN_CALLS: [&str; 3] = ["mssparkutils.notebook.run(", "notebookutils.notebook.run(", "dbutils.notebook.run("];
+// Fabric stores a notebook in git as `<display name>.Notebook/notebook-content.py`.
+pub const FABRIC_NOTEBOOK_SUFFIX: &str = ".Notebook";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunForm {
+    Magic,
+    Call,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunReference {
+    pub name: String,
+    pub line: usize,
+    pub form: RunForm,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChildNotebook {
+    pub name: String,
+    pub path: Option<PathBuf>,
+    pub summary: Option<String>,
+}
+
+
+// ====================================================
+//  Extraction
+// ====================================================
+pub fn extract_run_references(source: &str) -> Vec<RunReference> {
+
+    // Finds the child notebooks a notebook runs.
+    //
+    // # Description
+    // Two forms are recognised, both with or without parameters:
+    // * `%run child` / `%run ./folder/child {"p": 1}`, also behind `# MAGIC`.
+    //   Options such as `-b` before the name are skipped.
+    // * `mssparkutils.notebook.run("child", 90, {"p": 1})`, and the same on
+    //   `notebookutils` and `dbutils`; `path="child"` works too.
+    //
+    // Like the lineage extraction this is a heuristic: only string literals
+    // count, so `notebook.run(name)` with a variable (or an f-string with
+    // `{...}`) is not reported. Commented-out calls are ignored. Each name is
+    // reported once, at its first line.
+
+    let mut found: Vec<RunReference> = Vec::new();
+    let mut push = |name: String, line: usize, form: RunForm| {
+        if !name.is_empty() && !found.iter().any(|r| r.name == name) {
+            found.push(RunReference { name, line, form });
+        }
+    };
+
+    let mut offset = 0;
+    for (index, raw_line) in source.lines().enumerate() {
+        let line_start = offset;
+        offset += raw_line.len() + 1;
+
+        let line = raw_line.trim();
+        let magic = line.strip_prefix("# MAGIC").map(str::trim);
+        if let Some(rest) = magic.unwrap_or(line).strip_prefix(RUN_MAGIC)
+            && rest.starts_with(char::is_whitespace)
+            && let Some(name) = magic_target(rest)
+        {
+            push(name, index + 1, RunForm::Magic);
+            continue;
+        }
+        if line.starts_with('#') && magic.is_none() {
+            continue;
+        }
+        for call in RUN_CALLS {
+            let mut search = 0;
+            while let Some(pos) = raw_line[search..].find(call) {
+                let args_start = line_start + search + pos + call.len();
+                search += pos + call.len();
+                if let Some(name) = first_literal(&source[args_start..]) {
+                    push(name, index + 1, RunForm::Call);
+                }
+            }
+        }
+    }
+    found
+}
+
+
+fn magic_target(rest: &str) -> Option<String> {
+
+    // Returns the notebook name of a `%run` line (the text after `%run`).
+
+    let rest = rest.trim_start();
+    let mut rest = rest;
+    while let Some(option) = rest.strip_prefix('-') {
+        rest = option.split_once(char::is_whitespace).map_or("", |(_, after)| after).trim_start();
+    }
+    match rest.chars().next()? {
+        q @ ('"' | '\'') => rest[1..].split_once(q).map(|(name, _)| name.trim().to_string()),
+        '{' | '$' => None,
+        _ => rest.split_whitespace().next().map(str::to_string),
+    }
+}
+
+
+fn first_literal(args: &str) -> Option<String> {
+
+    // Returns the first argument of a call when it is a plain string literal.
+
+    let arg = args.trim_start();
+    let arg = match arg.strip_prefix("path") {
+        Some(keyword) if keyword.trim_start().starts_with('=') => keyword.trim_start()[1..].trim_start(),
+        _ => arg,
+    };
+    let formatted = arg.starts_with(['f', 'F']);
+    let arg = arg.trim_start_matches(['r', 'R', 'f', 'F']);
+    let quote = arg.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+    let (name, _) = arg[1..].split_once(quote)?;
+    if name.contains('\n') || (formatted && name.contains('{')) {
+        return None;
+    }
+    Some(name.trim().to_string())
+}
+
+
+// ====================================================
+//  Resolution
+// ====================================================
+pub fn display_name(notebook: &Path) -> String {
+
+    // Returns the name a notebook is run by: the Fabric display name for
+    // `<name>.Notebook/notebook-content.py`, the file stem otherwise.
+
+    let fabric = notebook
+        .parent()
+        .and_then(|p| p.file_name())
+        .and_then(|n| n.to_string_lossy().strip_suffix(FABRIC_NOTEBOOK_SUFFIX).map(str::to_string));
+    fabric.unwrap_or_else(|| notebook.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default())
+}
+
+
+pub fn resolve_run_reference(name: &str, notebook: &Path, workspace: &[PathBuf]) -> Option<PathBuf> {
+
+    // Resolves a run reference to a notebook of the scanned workspace.
+    //
+    // # Description
+    // A name with a `/` (or starting with `.`) is a path relative to the calling
+    // notebook's folder; the notebook extension may be left out. Any other
+    // name is matched against the display names of the workspace notebooks,
+    // exactly first and then ignoring case.
+    //
+    // # Returns
+    // * `Some(PathBuf)` – the workspace path of the child notebook.
+    // * `None` – the reference is external to the scanned workspace.
+
+    let canonical = |p: &Path| fs::canonicalize(p).ok();
+    if name.contains('/') || name.starts_with('.') {
+        let base = notebook.parent().unwrap_or(Path::new(".")).join(name);
+        let mut tries = vec![base.clone()];
+        tries.extend(NOTEBOOK_EXTENSIONS.iter().map(|ext| base.with_extension(ext)));
+        let target = tries.iter().filter(|p| p.is_file()).find_map(|p| canonical(p))?;
+        return workspace.iter().find(|w| canonical(w).as_ref() == Some(&target)).cloned();
+    }
+    workspace
+        .iter()
+        .find(|w| display_name(w) == name)
+        .or_else(|| workspace.iter().find(|w| display_name(w).eq_ignore_ascii_case(name)))
+        .cloned()
+}
+
+
+pub fn child_notebooks(notebook: &Path, source: &str, workspace: &[PathBuf], docs_dir: &Path) -> Vec<ChildNotebook> {
+
+    // Lists the child notebooks of a notebook, with the one-line summary of
+    // each child's document when that has been generated already.
+
+    extract_run_references(source)
+        .into_iter()
+        .map(|reference| {
+            let path = resolve_run_reference(&reference.name, notebook, workspace);
+            let summary = path
+                .as_ref()
+                .and_then(|p| fs::read_to_string(expected_doc_path(p, docs_dir)).ok())
+                .and_then(|doc| summary_line(&doc));
+            ChildNotebook { name: reference.name, path, summary }
+        })
+        .collect()
+}
+
+
+pub fn render_child_notebooks(children: &[ChildNotebook]) -> String {
+
+    // Renders `{{child_notebooks}}`: one line per child notebook.
+
+    children
+        .iter()
+        .map(|child| match (&child.path, &child.summary) {
+            (Some(path), Some(summary)) => format!("- {}: {} — {summary}", child.name, path.display()),
+            (Some(path), None) => format!("- {}: {}", child.name, path.display()),
+            (None, _) => format!("- {}: external (not in the scanned workspace)", child.name),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(source: &str) -> Vec<(String, usize, RunForm)> {
+        extract_run_references(source).into_iter().map(|r| (r.name, r.line, r.form)).collect()
+    }
+
+    fn found(name: &str, line: usize, form: RunForm) -> (String, usize, RunForm) {
+        (name.to_string(), line, form)
+    }
+
+
+    #[test]
+    fn finds_run_magics_with_options_and_parameters() {
+        let source = "%run ./shared/config\n\
+                      # MAGIC %run -b setup_tables\n\
+                      %run \"My Helpers\" {\"env\": \"dev\"}\n\
+                      %run ./load {\"day\": 1}\n\
+                      %run $notebook\n\
+                      %running total\n";
+        assert_eq!(names(source), [
+            found("./shared/config", 1, RunForm::Magic),
+            found("setup_tables", 2, RunForm::Magic),
+            found("My Helpers", 3, RunForm::Magic),
+            found("./load", 4, RunForm::Magic),
+        ]);
+    }
+
+
+    #[test]
+    fn finds_notebook_run_calls_on_every_api() {
+        let source = r#"result = mssparkutils.notebook.run("Child One", 90, {"p": 1})
+notebookutils.notebook.run('child_two')
+dbutils.notebook.run(path="./child_three", timeout_seconds=60)
+a = mssparkutils.notebook.run("x"); b = mssparkutils.notebook.run("y")"#;
+        assert_eq!(names(source), [
+            found("Child One", 1, RunForm::Call),
+            found("child_two", 2, RunForm::Call),
+            found("./child_three", 3, RunForm::Call),
+            found("x", 4, RunForm::Call),
+            found("y", 4, RunForm::Call),
+        ]);
+    }
+
+
+    #[test]
+    fn handles_parameterized_calls_split_over_lines() {
+        let source = "mssparkutils.notebook.run(\n    \"child\",\n    600,\n    {\"run_date\": run_date},\n)\n";
+        assert_eq!(names(source), [found("child", 1, RunForm::Call)]);
+    }
+
+
+    #[test]
+    fn skips_variables_f_strings_comments_and_duplicates() {
+        let source = r#"mssparkutils.notebook.run(name, 90)
+mssparkutils.notebook.run(f"{prefix}_load")
+# mssparkutils.notebook.run("commented")
+mssparkutils.notebook.run(f"fixed_name")
+%run fixed_name
+"#;
+        assert_eq!(names(source), [found("fixed_name", 4, RunForm::Call)]);
+    }
+
+
+    #[test]
+    fn resolves_names_and_relative_paths_in_the_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let fabric = dir.path().join("Load Sales.Notebook").join("notebook-content.py");
+        let helper = dir.path().join("shared").join("helpers.ipynb");
+        let caller = dir.path().join("main.ipynb");
+        for path in [&fabric, &helper, &caller] {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "{}").unwrap();
+        }
+        let workspace = vec![fabric.clone(), helper.clone(), caller.clone()];
+
+        assert_eq!(display_name(&fabric), "Load Sales");
+        assert_eq!(resolve_run_reference("Load Sales", &caller, &workspace), Some(fabric.clone()));
+        assert_eq!(resolve_run_reference("load sales", &caller, &workspace), Some(fabric));
+        assert_eq!(resolve_run_reference("./shared/helpers", &caller, &workspace), Some(helper));
+        assert_eq!(resolve_run_reference("elsewhere", &caller, &workspace), None);
+    }
+
+
+    #[test]
+    fn renders_resolved_and_external_children() {
+        let children = [
+            ChildNotebook { name: "a".to_string(), path: Some(PathBuf::from("nb/a.ipynb")), summary: Some("Loads a.".to_string()) },
+            ChildNotebook { name: "b".to_string(), path: Some(PathBuf::from("nb/b.ipynb")), summary: None },
+            ChildNotebook { name: "c".to_string(), path: None, summary: None },
+        ];
+        assert_eq!(
+            render_child_notebooks(&children),
+            "- a: nb/a.ipynb — Loads a.\n- b: nb/b.ipynb\n- c: external (not in the scanned workspace)"
+        );
+    }
+}
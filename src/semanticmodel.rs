@@ -0,0 +1,660 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// External Libraries
+use serde_json::Value;
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+// Fabric stores a semantic model in git as `<name>.SemanticModel/`, with
+// either a `model.bim` or a TMDL `definition/` folder.
+pub const FABRIC_MODEL_SUFFIX: &str = ".SemanticModel";
+pub const BIM_FILE: &str = "model.bim";
+pub const TMDL_MODEL_FILE: &str = "model.tmdl";
+pub const TMDL_DEFINITION_DIR: &str = "definition";
+// Heads every file of a TMDL folder read as one source.
+pub const TMDL_FILE_MARKER: &str = "=== FILE ";
+pub const SOURCE_LINE_LIMIT: usize = 40;
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SemanticModel {
+    pub name: Option<String>,
+    pub tables: Vec<Table>,
+    pub relationships: Vec<Relationship>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Table {
+    pub name: String,
+    pub description: Option<String>,
+    pub columns: Vec<Column>,
+    pub measures: Vec<Measure>,
+    pub partitions: Vec<Partition>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Column {
+    pub name: String,
+    pub data_type: Option<String>,
+    // The DAX of a calculated column.
+    pub expression: Option<String>,
+    pub description: Option<String>,
+    pub hidden: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Measure {
+    pub name: String,
+    pub expression: String,
+    pub format_string: Option<String>,
+    pub display_folder: Option<String>,
+    pub description: Option<String>,
+    pub hidden: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Partition {
+    pub name: String,
+    // `m`, `calculated`, `query` or `entity`; `mode` is `import`, `directQuery`…
+    pub source_type: Option<String>,
+    pub mode: Option<String>,
+    pub expression: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relationship {
+    // `(table, column)` on the many side and on the one side.
+    pub from: (String, String),
+    pub to: (String, String),
+    pub from_cardinality: String,
+    pub to_cardinality: String,
+    pub both_directions: bool,
+    pub active: bool,
+}
+
+impl Default for Relationship {
+    fn default() -> Self {
+        Relationship {
+            from: Default::default(),
+            to: Default::default(),
+            from_cardinality: "many".to_string(),
+            to_cardinality: "one".to_string(),
+            both_directions: false,
+            active: true,
+        }
+    }
+}
+
+
+// ====================================================
+//  Detection
+// ====================================================
+pub fn is_semantic_model_path(path: &Path) -> bool {
+
+    // Checks whether a path is a semantic model: a `model.bim`, or the
+    // `model.tmdl` of a TMDL `definition/` folder.
+
+    is_bim_path(path) || is_tmdl_model_path(path)
+}
+
+
+pub fn is_bim_path(path: &Path) -> bool {
+    path.file_name().is_some_and(|n| n == BIM_FILE)
+}
+
+
+pub fn is_tmdl_model_path(path: &Path) -> bool {
+    path.file_name().is_some_and(|n| n == TMDL_MODEL_FILE)
+        && path.parent().and_then(|p| p.file_name()).is_some_and(|n| n == TMDL_DEFINITION_DIR)
+}
+
+
+pub fn semantic_model_name(path: &Path) -> Option<String> {
+
+    // Returns the display name of a Fabric semantic model item from its
+    // `<name>.SemanticModel` folder.
+
+    path.ancestors()
+        .skip(1)
+        .take(2)
+        .find_map(|dir| dir.file_name()?.to_string_lossy().strip_suffix(FABRIC_MODEL_SUFFIX).map(str::to_string))
+}
+
+
+// ====================================================
+//  Reading
+// ====================================================
+pub fn read_tmdl_definition(model: &Path) -> io::Result<Vec<u8>> {
+
+    // Reads the `model.tmdl` of a TMDL folder together with every other
+    // `.tmdl` file of its `definition/` folder.
+    //
+    // # Description
+    // A TMDL model spreads its tables and relationships over many files. They
+    // are read as one text, each file headed by `=== FILE <relative path>`
+    // in sorted order, so the content hash covers the whole model and a
+    // changed table makes its documentation stale.
+
+    let definition = model.parent().unwrap_or(Path::new("."));
+    let mut files = Vec::new();
+    let mut pending = vec![definition.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?.path();
+            if entry.is_dir() {
+                pending.push(entry);
+            } else if entry.extension().is_some_and(|e| e == "tmdl") {
+                files.push(entry);
+            }
+        }
+    }
+    files.sort();
+    let mut out = Vec::new();
+    for file in files {
+        let relative: PathBuf = file.strip_prefix(definition).unwrap_or(&file).to_path_buf();
+        out.extend_from_slice(format!("{TMDL_FILE_MARKER}{}\n", relative.to_string_lossy().replace('\\', "/")).as_bytes());
+        out.extend_from_slice(&fs::read(&file)?);
+        if !out.ends_with(b"\n") {
+            out.push(b'\n');
+        }
+    }
+    Ok(out)
+}
+
+
+// ====================================================
+//  Parsing
+// ====================================================
+pub fn parse_semantic_model(path: &Path, text: &str) -> Result<SemanticModel, DoxcerError> {
+
+    // Parses a semantic model as read by [`crate::source::read_source`]: a `model.bim` as
+    // JSON, anything else as TMDL.
+
+    match is_bim_path(path) || text.trim_start().starts_with('{') {
+        true => parse_bim(text),
+        false => parse_tmdl(text),
+    }
+}
+
+
+pub fn parse_bim(text: &str) -> Result<SemanticModel, DoxcerError> {
+
+    // Parses a `model.bim` (the JSON of the Tabular Object Model).
+
+    let json: Value = serde_json::from_str(text).map_err(|e| DoxcerError::Input(format!("not a semantic model: {e}")))?;
+    let model = json.get("model").ok_or_else(|| DoxcerError::Input("not a semantic model: no `model` object".to_string()))?;
+    let tables = model
+        .get("tables")
+        .and_then(Value::as_array)
+        .map(|tables| tables.iter().filter_map(bim_table).collect())
+        .unwrap_or_default();
+    let relationships = model
+        .get("relationships")
+        .and_then(Value::as_array)
+        .map(|list| {
+            list.iter()
+                .map(|r| Relationship {
+                    from: (text_of(r.get("fromTable")).unwrap_or_default(), text_of(r.get("fromColumn")).unwrap_or_default()),
+                    to: (text_of(r.get("toTable")).unwrap_or_default(), text_of(r.get("toColumn")).unwrap_or_default()),
+                    from_cardinality: text_of(r.get("fromCardinality")).unwrap_or_else(|| "many".to_string()),
+                    to_cardinality: text_of(r.get("toCardinality")).unwrap_or_else(|| "one".to_string()),
+                    both_directions: text_of(r.get("crossFilteringBehavior")).as_deref() == Some("bothDirections"),
+                    active: r.get("isActive").and_then(Value::as_bool).unwrap_or(true),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(SemanticModel { name: text_of(json.get("name")), tables, relationships })
+}
+
+
+fn bim_table(table: &Value) -> Option<Table> {
+    let list = |key: &str| table.get(key).and_then(Value::as_array).cloned().unwrap_or_default();
+    Some(Table {
+        name: text_of(table.get("name"))?,
+        description: text_of(table.get("description")),
+        columns: list("columns")
+            .iter()
+            .filter(|c| text_of(c.get("type")).as_deref() != Some("rowNumber"))
+            .filter_map(|c| {
+                Some(Column {
+                    name: text_of(c.get("name"))?,
+                    data_type: text_of(c.get("dataType")),
+                    expression: text_of(c.get("expression")),
+                    description: text_of(c.get("description")),
+                    hidden: c.get("isHidden").and_then(Value::as_bool).unwrap_or(false),
+                })
+            })
+            .collect(),
+        measures: list("measures")
+            .iter()
+            .filter_map(|m| {
+                Some(Measure {
+                    name: text_of(m.get("name"))?,
+                    expression: text_of(m.get("expression")).unwrap_or_default(),
+                    format_string: text_of(m.get("formatString")),
+                    display_folder: text_of(m.get("displayFolder")),
+                    description: text_of(m.get("description")),
+                    hidden: m.get("isHidden").and_then(Value::as_bool).unwrap_or(false),
+                })
+            })
+            .collect(),
+        partitions: list("partitions")
+            .iter()
+            .map(|p| Partition {
+                name: text_of(p.get("name")).unwrap_or_default(),
+                source_type: p.get("source").and_then(|s| text_of(s.get("type"))),
+                mode: text_of(p.get("mode")),
+                expression: p.get("source").and_then(|s| text_of(s.get("expression")).or_else(|| text_of(s.get("query")))),
+            })
+            .collect(),
+    })
+}
+
+
+fn text_of(value: Option<&Value>) -> Option<String> {
+
+    // Reads a string, or a list of lines as the BIM format writes longer
+    // expressions and descriptions.
+
+    match value? {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(lines) => Some(lines.iter().filter_map(Value::as_str).collect::<Vec<_>>().join("\n")),
+        _ => None,
+    }
+}
+
+
+// What the properties at the next indent belong to.
+enum Current {
+    None,
+    Table,
+    Relationship,
+    Column,
+    Measure,
+    Partition,
+}
+
+
+pub fn parse_tmdl(text: &str) -> Result<SemanticModel, DoxcerError> {
+
+    // Parses TMDL (Tabular Model Definition Language), one or more files.
+    //
+    // # Description
+    // TMDL nests by tab indentation: `table`, `relationship` and `model` at
+    // the top, `column`, `measure` and `partition` one level in, and their
+    // properties (`dataType: int64`, `source =`) one level deeper. A `///`
+    // line describes the object below it. An expression after `=` continues
+    // on the lines indented deeper than the properties, or runs between
+    // ```` ``` ```` fences. Roles, perspectives, cultures and annotations are
+    // skipped.
+
+    let lines: Vec<&str> = text.lines().collect();
+    let mut model = SemanticModel::default();
+    let mut current = Current::None;
+    let mut description: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let depth = indent(line);
+        let trimmed = line.trim();
+        i += 1;
+        if trimmed.is_empty() || line.starts_with(TMDL_FILE_MARKER) {
+            continue;
+        }
+        if let Some(doc) = trimmed.strip_prefix("///") {
+            description.push(doc.trim().to_string());
+            continue;
+        }
+        let doc = (!description.is_empty()).then(|| description.join(" "));
+        description.clear();
+        let (keyword, rest) = trimmed.split_once(' ').unwrap_or((trimmed, ""));
+        match (depth, keyword) {
+            (0, "model") => {
+                model.name = Some(unquote(rest));
+                current = Current::None;
+            }
+            (0, "table") => {
+                model.tables.push(Table { name: unquote(rest), description: doc, ..Table::default() });
+                current = Current::Table;
+            }
+            (0, "relationship") => {
+                model.relationships.push(Relationship::default());
+                current = Current::Relationship;
+            }
+            (0, _) => current = Current::None,
+            (1, "column" | "measure" | "partition") if model.tables.last().is_some() && !matches!(current, Current::None | Current::Relationship) => {
+                let (name, first) = split_declaration(rest);
+                let expression = read_expression(first, &lines, &mut i, 3);
+                let table = model.tables.last_mut().expect("checked above");
+                current = match keyword {
+                    "column" => {
+                        table.columns.push(Column { name, expression, description: doc, ..Column::default() });
+                        Current::Column
+                    }
+                    "measure" => {
+                        table.measures.push(Measure { name, expression: expression.unwrap_or_default(), description: doc, ..Measure::default() });
+                        Current::Measure
+                    }
+                    _ => {
+                        table.partitions.push(Partition { name, source_type: expression, ..Partition::default() });
+                        Current::Partition
+                    }
+                };
+            }
+            (1, _) => match current {
+                Current::Relationship => relationship_property(model.relationships.last_mut(), trimmed),
+                Current::Column | Current::Measure | Current::Partition => current = Current::Table,
+                _ => {}
+            },
+            (2, _) => {
+                let (key, value) = property(trimmed);
+                let value = match value {
+                    Some(value) => Some(value),
+                    None => read_expression(trimmed.split_once('=').map_or("", |(_, v)| v.trim()), &lines, &mut i, 3),
+                };
+                apply_property(&mut model, &current, key, value);
+            }
+            _ => {}
+        }
+    }
+    match model.tables.is_empty() && model.name.is_none() {
+        true => Err(DoxcerError::Input("not a semantic model: no `table` or `model` in the TMDL".to_string())),
+        false => Ok(model),
+    }
+}
+
+
+fn apply_property(model: &mut SemanticModel, current: &Current, key: &str, value: Option<String>) {
+
+    // Sets a property of the column, measure or partition being read.
+
+    let Some(table) = model.tables.last_mut() else {
+        return;
+    };
+    let flag = value.as_deref().is_none_or(|v| v == "true");
+    match current {
+        Current::Column => {
+            if let Some(column) = table.columns.last_mut() {
+                match key {
+                    "dataType" => column.data_type = value,
+                    "isHidden" => column.hidden = flag,
+                    _ => {}
+                }
+            }
+        }
+        Current::Measure => {
+            if let Some(measure) = table.measures.last_mut() {
+                match key {
+                    "formatString" => measure.format_string = value,
+                    "displayFolder" => measure.display_folder = value,
+                    "isHidden" => measure.hidden = flag,
+                    _ => {}
+                }
+            }
+        }
+        Current::Partition => {
+            if let Some(partition) = table.partitions.last_mut() {
+                match key {
+                    "mode" => partition.mode = value,
+                    "source" | "query" | "expression" => partition.expression = value,
+                    _ => {}
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+
+fn relationship_property(relationship: Option<&mut Relationship>, line: &str) {
+    let (Some(relationship), (key, Some(value))) = (relationship, property(line)) else {
+        return;
+    };
+    match key {
+        "fromColumn" => relationship.from = split_column(&value),
+        "toColumn" => relationship.to = split_column(&value),
+        "fromCardinality" => relationship.from_cardinality = value,
+        "toCardinality" => relationship.to_cardinality = value,
+        "crossFilteringBehavior" => relationship.both_directions = value == "bothDirections",
+        "isActive" => relationship.active = value != "false",
+        _ => {}
+    }
+}
+
+
+fn property(line: &str) -> (&str, Option<String>) {
+
+    // Splits `key: value` (a value) from `key = …` and a bare `isHidden`
+    // (no value).
+
+    let key_end = line.find([':', '=', ' ']).unwrap_or(line.len());
+    let key = &line[..key_end];
+    match line[key_end..].trim_start().strip_prefix(':') {
+        Some(value) => (key, Some(unquote(value))),
+        None => (key, None),
+    }
+}
+
+
+fn read_expression(first: &str, lines: &[&str], i: &mut usize, min_indent: usize) -> Option<String> {
+
+    // Reads an expression that starts after `=`: the rest of that line, then
+    // the following lines indented at least `min_indent` deep, or everything
+    // up to the closing fence of a ```` ``` ```` block.
+
+    let mut body: Vec<&str> = Vec::new();
+    if first.starts_with("```") {
+        while *i < lines.len() && lines[*i].trim() != "```" {
+            body.push(lines[*i]);
+            *i += 1;
+        }
+        *i += 1;
+    } else {
+        while *i < lines.len() {
+            let line = lines[*i];
+            let continues = match line.trim().is_empty() {
+                true => lines[*i..].iter().find(|l| !l.trim().is_empty()).is_some_and(|l| indent(l) >= min_indent),
+                false => indent(line) >= min_indent,
+            };
+            if !continues {
+                break;
+            }
+            body.push(line);
+            *i += 1;
+        }
+    }
+    let strip = body.iter().filter(|l| !l.trim().is_empty()).map(|l| indent(l)).min().unwrap_or(0);
+    let mut text: Vec<String> = Vec::new();
+    if !first.is_empty() && !first.starts_with("```") {
+        text.push(first.to_string());
+    }
+    text.extend(body.iter().map(|l| dedent(l, strip)));
+    let text = text.join("\n").trim_end().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+
+fn indent(line: &str) -> usize {
+
+    // Counts the indent in levels: a tab, or four spaces.
+
+    let mut spaces = 0;
+    let mut levels = 0;
+    for c in line.chars() {
+        match c {
+            '\t' => levels += 1,
+            ' ' => spaces += 1,
+            _ => break,
+        }
+    }
+    levels + spaces / 4
+}
+
+
+fn dedent(line: &str, levels: usize) -> String {
+    let mut rest = line;
+    for _ in 0..levels {
+        rest = rest.strip_prefix('\t').or_else(|| rest.strip_prefix("    ")).unwrap_or(rest);
+    }
+    rest.trim_end().to_string()
+}
+
+
+fn split_declaration(rest: &str) -> (String, &str) {
+
+    // Splits `'Total Sales' = SUM(...)` into the name and what follows the
+    // `=`; an `=` inside a quoted name does not count.
+
+    let mut quoted = false;
+    for (at, c) in rest.char_indices() {
+        match c {
+            '\'' => quoted = !quoted,
+            '=' if !quoted => return (unquote(&rest[..at]), rest[at + 1..].trim()),
+            _ => {}
+        }
+    }
+    (unquote(rest), "")
+}
+
+
+fn split_column(value: &str) -> (String, String) {
+
+    // Splits `Sales.CustomerKey` or `'Sales Table'.'Customer Key'`.
+
+    let mut quoted = false;
+    for (at, c) in value.char_indices() {
+        match c {
+            '\'' => quoted = !quoted,
+            '.' if !quoted => return (unquote(&value[..at]), unquote(&value[at + 1..])),
+            _ => {}
+        }
+    }
+    (String::new(), unquote(value))
+}
+
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    match value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        Some(inner) => inner.replace("''", "'"),
+        None => value.trim_matches('"').to_string(),
+    }
+}
+
+
+// ====================================================
+//  Rendering
+// ====================================================
+impl SemanticModel {
+
+    pub fn render(&self, name: &str) -> String {
+
+        // Renders the model for a prompt: per table its columns, measures (with
+        // their DAX) and sources, then the relationships.
+
+        let mut out = format!("Semantisch model: {name}\n");
+        for table in &self.tables {
+            out.push_str(&format!("\nTabel {}\n", table.name));
+            if let Some(description) = &table.description {
+                out.push_str(&format!("  Beschrijving: {description}\n"));
+            }
+            if !table.columns.is_empty() {
+                out.push_str("  Kolommen:\n");
+            }
+            for column in &table.columns {
+                let mut notes: Vec<String> = column.data_type.iter().cloned().collect();
+                if column.expression.is_some() {
+                    notes.push("berekend".to_string());
+                }
+                if column.hidden {
+                    notes.push("verborgen".to_string());
+                }
+                let notes = match notes.is_empty() {
+                    true => String::new(),
+                    false => format!(" ({})", notes.join(", ")),
+                };
+                let expression = column.expression.as_ref().map(|e| format!(" = {}", e.replace('\n', " "))).unwrap_or_default();
+                let description = column.description.as_ref().map(|d| format!(" — {d}")).unwrap_or_default();
+                out.push_str(&format!("  - {}{notes}{expression}{description}\n", column.name));
+            }
+            if !table.measures.is_empty() {
+                out.push_str("  Measures:\n");
+            }
+            for measure in &table.measures {
+                let mut notes: Vec<String> = Vec::new();
+                if let Some(format) = &measure.format_string {
+                    notes.push(format!("opmaak {format}"));
+                }
+                if let Some(folder) = &measure.display_folder {
+                    notes.push(format!("map {folder}"));
+                }
+                if measure.hidden {
+                    notes.push("verborgen".to_string());
+                }
+                let notes = match notes.is_empty() {
+                    true => String::new(),
+                    false => format!(" ({})", notes.join("; ")),
+                };
+                out.push_str(&format!("  - {}{notes}\n", measure.name));
+                if let Some(description) = &measure.description {
+                    out.push_str(&format!("    Beschrijving: {description}\n"));
+                }
+                for line in measure.expression.lines() {
+                    out.push_str(&format!("      {line}\n"));
+                }
+            }
+            for partition in &table.partitions {
+                let kind: Vec<&str> = [partition.source_type.as_deref(), partition.mode.as_deref()].into_iter().flatten().collect();
+                out.push_str(&format!("  Bron {} ({}):\n", partition.name, kind.join(", ")));
+                let lines: Vec<&str> = partition.expression.as_deref().unwrap_or_default().lines().collect();
+                for line in lines.iter().take(SOURCE_LINE_LIMIT) {
+                    out.push_str(&format!("      {line}\n"));
+                }
+                if lines.len() > SOURCE_LINE_LIMIT {
+                    out.push_str(&format!("      … ({} regels meer)\n", lines.len() - SOURCE_LINE_LIMIT));
+                }
+            }
+        }
+        if !self.relationships.is_empty() {
+            out.push_str("\nRelaties:\n");
+        }
+        for relationship in &self.relationships {
+            let cardinality = |c: &str| if c == "one" { "1" } else { "n" };
+            let mut notes = vec![format!("{}:{}", cardinality(&relationship.from_cardinality), cardinality(&relationship.to_cardinality))];
+            if relationship.both_directions {
+                notes.push("filtert in beide richtingen".to_string());
+            }
+            if !relationship.active {
+                notes.push("inactief".to_string());
+            }
+            out.push_str(&format!(
+                "- {}[{}] → {}[{}] ({})\n",
+                relationship.from.0,
+                relationship.from.1,
+                relationship.to.0,
+                relationship.to.1,
+                notes.join(", ")
+            ));
+        }
+        out
+    }
+}
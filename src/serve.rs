@@ -0,0 +1,738 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+#[cfg(feature = "serve")]
+use std::io::BufReader;
+#[cfg(feature = "serve")]
+use std::net::TcpListener;
+#[cfg(feature = "serve")]
+use std::thread;
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::html::{escape_html, render_html};
+use crate::manifest::Manifest;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const DEFAULT_HOST: &str = "127.0.0.1";
+pub const CHANGES_PATH: &str = "/__doxcer/changes";
+pub const POLL_INTERVAL_MS: u64 = 1000;
+pub const INDEX_DOC: &str = "index.md";
+// The longest request line and header line read; longer ones get a 414 or
+// 431 instead of being buffered without limit.
+pub const MAX_REQUEST_LINE_BYTES: u64 = 8 * 1024;
+pub const MAX_HEADER_BYTES: u64 = 16 * 1024;
+// Connections served at once; more get a 503. Each one has `REQUEST_TIMEOUT`
+// to send its request and again to take the response, so idle or slow
+// clients cannot hold a handler for long.
+pub const MAX_CONNECTIONS: usize = 64;
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocEntry {
+    // Path relative to the docs directory, with forward slashes.
+    pub path: String,
+    pub notebook: Option<String>,
+}
+
+// Counts the connections being handled, up to a maximum.
+#[derive(Debug, Clone)]
+pub struct ConnectionLimit {
+    open: Arc<AtomicUsize>,
+    max: usize,
+}
+
+// One connection counted by a [`ConnectionLimit`]; dropping it frees the slot.
+#[derive(Debug)]
+pub struct ConnectionSlot {
+    open: Arc<AtomicUsize>,
+}
+
+// A socket whose reads and writes all fail once `deadline` has passed, however
+// the other side paces them.
+pub struct DeadlineStream<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+
+    fn html(status: u16, body: String) -> Response {
+        Response { status, content_type: "text/html; charset=utf-8", body: body.into_bytes() }
+    }
+
+
+    #[cfg(any(feature = "serve", test))]
+    fn plain(status: u16, body: &str) -> Response {
+        Response { status, content_type: "text/plain; charset=utf-8", body: body.as_bytes().to_vec() }
+    }
+
+
+    fn not_found() -> Response {
+        Response { status: 404, content_type: "text/plain; charset=utf-8", body: b"not found\n".to_vec() }
+    }
+
+
+    pub fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            414 => "URI Too Long",
+            431 => "Request Header Fields Too Large",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+impl ConnectionLimit {
+
+    pub fn new(max: usize) -> ConnectionLimit {
+        ConnectionLimit { open: Arc::new(AtomicUsize::new(0)), max }
+    }
+
+
+    pub fn acquire(&self) -> Option<ConnectionSlot> {
+
+        // Takes a slot, or `None` when `max` connections are already open.
+
+        self.open
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |open| (open < self.max).then_some(open + 1))
+            .ok()
+            .map(|_| ConnectionSlot { open: Arc::clone(&self.open) })
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.open.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl DeadlineStream<'_> {
+
+    pub fn new(stream: &TcpStream, timeout: Duration) -> DeadlineStream<'_> {
+        DeadlineStream { stream, deadline: Instant::now() + timeout }
+    }
+
+
+    fn remaining(&self) -> io::Result<Duration> {
+        match self.deadline.saturating_duration_since(Instant::now()) {
+            left if left.is_zero() => Err(io::Error::new(io::ErrorKind::TimedOut, "connection deadline passed")),
+            left => Ok(left),
+        }
+    }
+}
+
+impl Read for DeadlineStream<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream.set_read_timeout(Some(self.remaining()?))?;
+        let mut stream = self.stream;
+        stream.read(buf)
+    }
+}
+
+impl Write for DeadlineStream<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.set_write_timeout(Some(self.remaining()?))?;
+        let mut stream = self.stream;
+        stream.write(buf)
+    }
+
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+
+// ====================================================
+//  Paths
+// ====================================================
+pub fn percent_decode(value: &str) -> Option<String> {
+
+    // Decodes `%XX` escapes in a request path; `None` for a malformed escape or
+    // a result that is not UTF-8.
+
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+
+pub fn percent_encode_path(path: &str) -> String {
+
+    // Encodes a relative path for use in a link, keeping the slashes.
+
+    let mut out = String::new();
+    for b in path.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'/' | b'-' | b'_' | b'.' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+
+pub fn resolve_request_path(root: &Path, target: &str) -> Option<PathBuf> {
+
+    // Maps a request target to a file inside the docs directory.
+    //
+    // # Description
+    // The query and fragment are dropped and the path is percent-decoded once.
+    // Any `..` segment, backslash or NUL byte is refused outright, so neither
+    // `/../x` nor `/%2e%2e/x` nor `/..%5cx` gets anywhere. As a second line of
+    // defence the existing path is canonicalized and must still lie inside the
+    // canonical docs directory, which also stops symlinks pointing out of it.
+    //
+    // # Returns
+    // * `Some(path)` of an existing file or directory inside `root`.
+    // * `None` for anything else.
+
+    let path = target.split(['?', '#']).next().unwrap_or_default();
+    let decoded = percent_decode(path)?;
+    if decoded.contains(['\\', '\0']) {
+        return None;
+    }
+    let mut relative = PathBuf::new();
+    for segment in decoded.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => return None,
+            segment => relative.push(segment),
+        }
+    }
+    let root = fs::canonicalize(root).ok()?;
+    let resolved = fs::canonicalize(root.join(relative)).ok()?;
+    resolved.starts_with(&root).then_some(resolved)
+}
+
+
+// ====================================================
+//  Requests
+// ====================================================
+pub fn read_limited_line(reader: &mut impl BufRead, limit: u64) -> Option<String> {
+
+    // Reads one line of at most `limit` bytes, including the line ending.
+    //
+    // # Returns
+    // * `Some(line)` for a complete line, or the last line before the end of
+    //   the stream.
+    // * `None` when the line is longer than `limit`, is not UTF-8 or cannot
+    //   be read.
+
+    let mut line = Vec::new();
+    let read = reader.take(limit).read_until(b'\n', &mut line).ok()?;
+    if read as u64 == limit && !line.ends_with(b"\n") {
+        return None;
+    }
+    String::from_utf8(line).ok()
+}
+
+
+// ====================================================
+//  Listing
+// ====================================================
+pub fn list_docs(root: &Path, manifest: &Manifest) -> Vec<DocEntry> {
+
+    // Lists the Markdown files under the docs directory for the sidebar, with
+    // the notebook each was generated from when the manifest knows it. Hidden
+    // files and directories are left out.
+
+    let canonical_root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let sources: Vec<(PathBuf, String)> = manifest
+        .entries
+        .iter()
+        .filter_map(|(notebook, entry)| fs::canonicalize(&entry.output).ok().map(|p| (p, notebook.clone())))
+        .collect();
+
+    let mut docs = Vec::new();
+    for path in walk(root) {
+        if path.extension().and_then(|e| e.to_str()) != Some("md") {
+            continue;
+        }
+        let canonical = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        let Ok(relative) = canonical.strip_prefix(&canonical_root) else {
+            continue;
+        };
+        docs.push(DocEntry {
+            path: relative.to_string_lossy().replace('\\', "/"),
+            notebook: sources.iter().find(|(p, _)| *p == canonical).map(|(_, n)| n.clone()),
+        });
+    }
+    docs.sort_by(|a, b| a.path.cmp(&b.path));
+    docs
+}
+
+
+pub fn latest_change(root: &Path) -> String {
+
+    // Fingerprints the docs directory for the auto-reload poll: the number of
+    // files and the latest modification time, so additions, edits and removals
+    // all change it.
+
+    let files = walk(root);
+    let newest = files
+        .iter()
+        .filter_map(|p| fs::metadata(p).ok()?.modified().ok()?.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis())
+        .max()
+        .unwrap_or(0);
+    format!("{}-{newest}", files.len())
+}
+
+
+fn walk(dir: &Path) -> Vec<PathBuf> {
+
+    // Lists the files below `dir`, skipping hidden entries. Symlinked
+    // directories are followed, but each real directory only once, so a
+    // symlink cycle ends.
+
+    let mut files = Vec::new();
+    walk_into(dir, &mut HashSet::new(), &mut files);
+    files
+}
+
+
+fn walk_into(dir: &Path, visited: &mut HashSet<PathBuf>, files: &mut Vec<PathBuf>) {
+    let Ok(canonical) = fs::canonicalize(dir) else {
+        return;
+    };
+    if !visited.insert(canonical) {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            walk_into(&path, visited, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+
+// ====================================================
+//  Responses
+// ====================================================
+pub fn respond(root: &Path, manifest: &Manifest, target: &str) -> Response {
+
+    // Answers one GET request.
+    //
+    // # Description
+    // `/` shows `index.md` when there is one, otherwise a list of all
+    // documents. Markdown files are rendered with [`render_html`] inside a page
+    // with the sidebar; other files (images) are sent as they are.
+    // `CHANGES_PATH` returns the [`latest_change`] fingerprint that the page
+    // polls to reload itself.
+
+    let path = target.split(['?', '#']).next().unwrap_or_default();
+    if path == CHANGES_PATH {
+        return Response { status: 200, content_type: "text/plain; charset=utf-8", body: latest_change(root).into_bytes() };
+    }
+    let docs = list_docs(root, manifest);
+    let Some(resolved) = resolve_request_path(root, target) else {
+        return Response::not_found();
+    };
+    let file = if resolved.is_dir() { resolved.join(INDEX_DOC) } else { resolved.clone() };
+
+    if !file.is_file() {
+        if path.trim_matches('/').is_empty() {
+            return Response::html(200, render_page("Documents", &docs, None, &overview(&docs)));
+        }
+        return Response::not_found();
+    }
+    let current = fs::canonicalize(root)
+        .ok()
+        .and_then(|root| file.strip_prefix(root).ok().map(|p| p.to_string_lossy().replace('\\', "/")));
+    if file.extension().and_then(|e| e.to_str()) == Some("md") {
+        return match fs::read_to_string(&file) {
+            Ok(markdown) => {
+                let title = current.clone().unwrap_or_default();
+                Response::html(200, render_page(&title, &docs, current.as_deref(), &render_html(&markdown)))
+            }
+            Err(_) => Response::not_found(),
+        };
+    }
+    match fs::read(&file) {
+        Ok(body) => Response { status: 200, content_type: content_type(&file), body },
+        Err(_) => Response::not_found(),
+    }
+}
+
+
+pub fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase).as_deref() {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        Some("css") => "text/css; charset=utf-8",
+        Some("txt" | "json" | "py" | "sql") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+
+fn overview(docs: &[DocEntry]) -> String {
+
+    // The start page when the docs directory has no `index.md`.
+
+    let mut out = format!("<h1>Documents</h1>\n<p>{} document(s).</p>\n<ul>\n", docs.len());
+    for doc in docs {
+        let source = doc.notebook.as_ref().map(|n| format!(" <small>{}</small>", escape_html(n))).unwrap_or_default();
+        out.push_str(&format!("<li><a href=\"/{}\">{}</a>{source}</li>\n", percent_encode_path(&doc.path), escape_html(&doc.path)));
+    }
+    out.push_str("</ul>\n");
+    out
+}
+
+
+pub fn render_page(title: &str, docs: &[DocEntry], current: Option<&str>, body: &str) -> String {
+
+    // Wraps a rendered document in the preview page: sidebar, content and the
+    // script that reloads the page when the docs directory changes.
+
+    let mut sidebar = String::from("<a href=\"/\">Overview</a>\n<ul>\n");
+    for doc in docs {
+        let class = if current == Some(doc.path.as_str()) { " class=\"current\"" } else { "" };
+        let title = doc.notebook.as_ref().map(|n| format!(" title=\"{}\"", escape_html(n))).unwrap_or_default();
+        sidebar.push_str(&format!(
+            "<li><a href=\"/{}\"{class}{title}>{}</a></li>\n",
+            percent_encode_path(&doc.path),
+            escape_html(&doc.path)
+        ));
+    }
+    sidebar.push_str("</ul>\n");
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title} – doxcer</title>\n<style>\n\
+         body {{ margin: 0; display: flex; font-family: system-ui, sans-serif; line-height: 1.5; }}\n\
+         nav {{ width: 16rem; padding: 1rem; background: #f4f4f4; min-height: 100vh; font-size: 0.9rem; }}\n\
+         nav ul {{ list-style: none; padding: 0; }} nav a.current {{ font-weight: bold; }}\n\
+         main {{ flex: 1; padding: 1rem 2rem; max-width: 60rem; }}\n\
+         pre {{ background: #f6f8fa; padding: 0.75rem; overflow-x: auto; }} pre.front-matter {{ color: #666; }}\n\
+         table {{ border-collapse: collapse; }} th, td {{ border: 1px solid #ccc; padding: 0.25rem 0.5rem; }}\n\
+         </style>\n</head>\n<body>\n<nav>\n{sidebar}</nav>\n<main>\n{body}</main>\n<script>\n\
+         let seen = null;\n\
+         setInterval(async () => {{\n  try {{\n    const stamp = await (await fetch('{CHANGES_PATH}')).text();\n\
+             if (seen !== null && stamp !== seen) location.reload();\n    seen = stamp;\n  }} catch (e) {{}}\n}}, {POLL_INTERVAL_MS});\n\
+         </script>\n</body>\n</html>\n",
+        title = escape_html(title),
+    )
+}
+
+
+// ====================================================
+//  Server
+// ====================================================
+#[cfg(feature = "serve")]
+pub fn serve(root: &Path, host: &str, port: u16, manifest_path: &Path, on_ready: impl FnOnce(&str)) -> Result<(), DoxcerError> {
+
+    // Serves the docs directory until the process is stopped.
+    //
+    // # Description
+    // Binds `host:port` (port 0 picks a free one) and calls `on_ready` with the
+    // URL. Every connection gets its own thread and one response, at most
+    // `MAX_CONNECTIONS` at a time; the manifest is re-read per request so the
+    // sidebar follows regeneration.
+
+    let listener = TcpListener::bind((host, port)).map_err(|e| DoxcerError::Config(format!("Failed to listen on {host}:{port}: {e}")))?;
+    let address = listener.local_addr().map_err(|e| DoxcerError::Config(e.to_string()))?;
+    on_ready(&format!("http://{address}/"));
+    let limit = ConnectionLimit::new(MAX_CONNECTIONS);
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let Some(slot) = limit.acquire() else {
+            let busy = Response::plain(503, "too many connections\n");
+            write_response(&mut DeadlineStream::new(&stream, Duration::from_secs(1)), &busy, true);
+            continue;
+        };
+        let root = root.to_path_buf();
+        let manifest_path = manifest_path.to_path_buf();
+        thread::spawn(move || {
+            handle(stream, &root, &manifest_path);
+            drop(slot);
+        });
+    }
+    Ok(())
+}
+
+
+#[cfg(not(feature = "serve"))]
+pub fn serve(_root: &Path, _host: &str, _port: u16, _manifest_path: &Path, _on_ready: impl FnOnce(&str)) -> Result<(), DoxcerError> {
+
+    // Fallback for builds without the `serve` feature.
+
+    Err(DoxcerError::Config("doxcer was built without the preview server (rebuild with `--features serve`)".to_string()))
+}
+
+
+#[cfg(feature = "serve")]
+fn handle(stream: TcpStream, root: &Path, manifest_path: &Path) {
+
+    // Reads one request line (and skips the headers) and writes the response.
+    // Neither is read past `MAX_REQUEST_LINE_BYTES` / `MAX_HEADER_BYTES`, and
+    // the client has `REQUEST_TIMEOUT` for each.
+
+    let mut reader = BufReader::new(DeadlineStream::new(&stream, REQUEST_TIMEOUT));
+    let request_line = read_limited_line(&mut reader, MAX_REQUEST_LINE_BYTES);
+    if request_line.as_deref() == Some("") {
+        return;
+    }
+    let mut headers_read = 0;
+    let mut headers_fit = true;
+    while request_line.is_some() {
+        let Some(header) = read_limited_line(&mut reader, MAX_HEADER_BYTES - headers_read) else {
+            headers_fit = false;
+            break;
+        };
+        headers_read += header.len() as u64;
+        if header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.as_deref().unwrap_or_default().split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or("/");
+    let response = match method {
+        _ if request_line.is_none() => Response::plain(414, "request line too long\n"),
+        _ if !headers_fit => Response::plain(431, "request headers too large\n"),
+        "GET" | "HEAD" => respond(root, &Manifest::load(manifest_path).unwrap_or_default(), target),
+        _ => Response::plain(405, "only GET is supported\n"),
+    };
+    write_response(&mut DeadlineStream::new(&stream, REQUEST_TIMEOUT), &response, method != "HEAD");
+}
+
+
+pub fn write_response(out: &mut impl Write, response: &Response, with_body: bool) {
+
+    // Writes a response and closes the exchange (`Connection: close`); a
+    // client that went away or stopped reading is not an error.
+
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.reason(),
+        response.content_type,
+        response.body.len()
+    );
+    let _ = out.write_all(head.as_bytes());
+    if with_body {
+        let _ = out.write_all(&response.body);
+    }
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::net::TcpListener;
+    use std::thread;
+
+    // A connected socket pair: the client end and the server end.
+    fn connection() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        (client, server)
+    }
+
+    // A docs directory `docs/` with a secret file next to it.
+    fn fixture() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let docs = dir.path().join("docs");
+        fs::create_dir_all(docs.join("gold")).unwrap();
+        fs::write(docs.join("index.md"), "# Index\n").unwrap();
+        fs::write(docs.join("gold").join("dim project.md"), "# Dim\n").unwrap();
+        fs::write(dir.path().join("secret.md"), "secret\n").unwrap();
+        (dir, docs)
+    }
+
+
+    #[test]
+    fn resolves_files_inside_the_docs_dir() {
+        let (_dir, docs) = fixture();
+        let root = fs::canonicalize(&docs).unwrap();
+        assert_eq!(resolve_request_path(&docs, "/index.md?reload=1#top"), Some(root.join("index.md")));
+        assert_eq!(resolve_request_path(&docs, "/gold/dim%20project.md"), Some(root.join("gold").join("dim project.md")));
+        assert_eq!(resolve_request_path(&docs, "/./gold//dim%20project.md"), Some(root.join("gold").join("dim project.md")));
+        assert_eq!(resolve_request_path(&docs, "/"), Some(root));
+    }
+
+
+    #[test]
+    fn refuses_dot_dot_segments() {
+        let (_dir, docs) = fixture();
+        for target in ["/../secret.md", "/gold/../../secret.md", "/gold/../index.md", "..", "/.."] {
+            assert_eq!(resolve_request_path(&docs, target), None, "{target}");
+        }
+    }
+
+
+    #[test]
+    fn refuses_encoded_dot_dot_segments() {
+        let (_dir, docs) = fixture();
+        for target in ["/%2e%2e/secret.md", "/%2E%2E/secret.md", "/.%2e/secret.md", "/%2e%2e%2fsecret.md", "/gold%2F..%2F..%2Fsecret.md"] {
+            assert_eq!(resolve_request_path(&docs, target), None, "{target}");
+        }
+        // Decoded once only: `%252e` is the literal name `%2e`, which does not exist.
+        assert_eq!(resolve_request_path(&docs, "/%252e%252e/secret.md"), None);
+    }
+
+
+    #[test]
+    fn refuses_backslashes_and_nul() {
+        let (_dir, docs) = fixture();
+        for target in ["/..\\secret.md", "/..%5csecret.md", "/..%5Csecret.md", "/gold\\dim%20project.md", "/index.md%00", "/index.md%00.html", "/\0"] {
+            assert_eq!(resolve_request_path(&docs, target), None, "{target:?}");
+        }
+    }
+
+
+    #[test]
+    fn refuses_malformed_escapes_and_missing_files() {
+        let (_dir, docs) = fixture();
+        for target in ["/index%2", "/index%zz.md", "/%ff.md", "/missing.md", "/etc/passwd"] {
+            assert_eq!(resolve_request_path(&docs, target), None, "{target}");
+        }
+    }
+
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_symlinks_out_of_the_docs_dir() {
+        let (dir, docs) = fixture();
+        std::os::unix::fs::symlink(dir.path().join("secret.md"), docs.join("link.md")).unwrap();
+        assert_eq!(resolve_request_path(&docs, "/link.md"), None);
+    }
+
+
+    #[test]
+    fn request_lines_are_read_up_to_the_limit() {
+        let mut reader = Cursor::new("GET / HTTP/1.1\r\nHost: x\r\n\r\n");
+        assert_eq!(read_limited_line(&mut reader, 64).as_deref(), Some("GET / HTTP/1.1\r\n"));
+        assert_eq!(read_limited_line(&mut reader, 64).as_deref(), Some("Host: x\r\n"));
+
+        let exact = "GET /abc\n";
+        assert_eq!(read_limited_line(&mut Cursor::new(exact), exact.len() as u64).as_deref(), Some(exact));
+        assert_eq!(read_limited_line(&mut Cursor::new(exact), exact.len() as u64 - 1), None);
+        assert_eq!(read_limited_line(&mut Cursor::new(format!("GET /{}", "a".repeat(10_000))), MAX_REQUEST_LINE_BYTES), None);
+        assert_eq!(read_limited_line(&mut Cursor::new(""), 64).as_deref(), Some(""));
+    }
+
+
+    #[test]
+    fn the_connection_limit_caps_and_frees_slots() {
+        let limit = ConnectionLimit::new(2);
+        let first = limit.acquire().unwrap();
+        let _second = limit.clone().acquire().unwrap();
+        assert!(limit.acquire().is_none());
+        drop(first);
+        assert!(limit.acquire().is_some());
+    }
+
+
+    #[test]
+    fn a_silent_client_is_given_up_on_at_the_deadline() {
+        let (_client, server) = connection();
+        let started = Instant::now();
+        let mut reader = std::io::BufReader::new(DeadlineStream::new(&server, Duration::from_millis(200)));
+        assert_eq!(read_limited_line(&mut reader, MAX_REQUEST_LINE_BYTES), None);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+
+    #[test]
+    fn a_slow_client_cannot_stretch_the_deadline() {
+        let (mut client, server) = connection();
+        thread::spawn(move || {
+            while client.write_all(b"G").is_ok() {
+                thread::sleep(Duration::from_millis(20));
+            }
+        });
+        let started = Instant::now();
+        let mut reader = std::io::BufReader::new(DeadlineStream::new(&server, Duration::from_millis(300)));
+        assert_eq!(read_limited_line(&mut reader, MAX_REQUEST_LINE_BYTES), None);
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(300) && elapsed < Duration::from_secs(5), "{elapsed:?}");
+    }
+
+
+    #[test]
+    fn responses_are_written_within_the_deadline() {
+        let (mut client, server) = connection();
+        write_response(&mut DeadlineStream::new(&server, REQUEST_TIMEOUT), &Response::plain(503, "too many connections\n"), true);
+        drop(server);
+        let mut text = String::new();
+        client.read_to_string(&mut text).unwrap();
+        assert!(text.starts_with("HTTP/1.1 503 Service Unavailable\r\n"), "{text}");
+        assert!(text.ends_with("\r\n\r\ntoo many connections\n"), "{text}");
+    }
+
+
+    #[cfg(unix)]
+    #[test]
+    fn a_symlink_cycle_is_walked_once() {
+        let (_dir, docs) = fixture();
+        std::os::unix::fs::symlink(&docs, docs.join("gold").join("loop")).unwrap();
+        std::os::unix::fs::symlink(docs.join("gold"), docs.join("gold alias")).unwrap();
+        let listed: Vec<String> = list_docs(&docs, &Manifest::default()).into_iter().map(|doc| doc.path).collect();
+        assert_eq!(listed, ["gold/dim project.md", "index.md"]);
+        assert!(latest_change(&docs).starts_with("2-"));
+    }
+}
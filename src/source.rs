@@ -0,0 +1,218 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::provenance::content_hash;
+use crate::semanticmodel::{is_tmdl_model_path, read_tmdl_definition};
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone)]
+pub struct SourceSnapshot {
+    pub path: PathBuf,
+    pub text: String,
+    pub hash: String,
+    pub url: Option<String>,
+    pub commit: Option<String>,
+    pub archive: Option<PathBuf>,
+}
+
+
+// ====================================================
+//  Reading
+// ====================================================
+pub fn read_source(path: &Path) -> io::Result<Vec<u8>> {
+
+    // Reads the bytes of a source file, the reader behind every snapshot.
+    // The `model.tmdl` of a TMDL semantic model is read with its whole
+    // `definition/` folder (see [`read_tmdl_definition`]).
+
+    match is_tmdl_model_path(path) {
+        true => read_tmdl_definition(path),
+        false => fs::read(path),
+    }
+}
+
+
+// ====================================================
+//  Snapshots
+// ====================================================
+impl SourceSnapshot {
+
+    pub fn read(path: &Path) -> Result<SourceSnapshot, DoxcerError> {
+
+        // Reads a notebook once and derives both its text and content hash. A
+        // TMDL semantic model is read with its whole folder (see [`read_source`]).
+
+        SourceSnapshot::read_with(path, read_source)
+    }
+
+
+    pub fn read_with<R>(path: &Path, read: R) -> Result<SourceSnapshot, DoxcerError>
+    where
+        R: FnOnce(&Path) -> io::Result<Vec<u8>>,
+    {
+
+        // Reads a notebook through the given reader.
+        //
+        // # Description
+        // The prompt text and the hash are derived from the same bytes, so the
+        // hash always describes exactly what was sent to the model.
+        //
+        // # Parameters
+        // * `path` – The notebook path.
+        // * `read` – Reader used to load the bytes (injectable for tests).
+        //
+        // # Returns
+        // * `Ok(SourceSnapshot)` holding the text and its `sha256:` hash.
+        // * `Err(DoxcerError)` if the file cannot be read or is not valid UTF-8.
+
+        let bytes = read(path)
+            .map_err(|e| DoxcerError::Input(format!("Failed to read notebook {}: {e}", path.display())))?;
+        SourceSnapshot::from_bytes(path, bytes, None)
+    }
+
+
+    pub fn from_bytes(path: &Path, bytes: Vec<u8>, url: Option<&str>) -> Result<SourceSnapshot, DoxcerError> {
+
+        // Builds a snapshot from bytes that were already loaded, such as a
+        // notebook downloaded from `url`; `path` then only carries its name.
+
+        let hash = content_hash(&bytes);
+        let label = url.map_or_else(|| path.display().to_string(), str::to_string);
+        let text = String::from_utf8(bytes)
+            .map_err(|_| DoxcerError::Input(format!("Notebook {label} is not valid UTF-8")))?;
+        Ok(SourceSnapshot { path: path.to_path_buf(), text, hash, url: url.map(str::to_string), commit: None, archive: None })
+    }
+
+
+    pub fn at_commit(path: &Path, bytes: Vec<u8>, commit: &str) -> Result<SourceSnapshot, DoxcerError> {
+
+        // Builds a snapshot of a notebook as it is at a git commit (`--at-ref`).
+
+        let snapshot = SourceSnapshot::from_bytes(path, bytes, None)?;
+        Ok(SourceSnapshot { commit: Some(commit.to_string()), ..snapshot })
+    }
+
+
+    pub fn in_archive(path: &Path, bytes: Vec<u8>, archive: &Path) -> Result<SourceSnapshot, DoxcerError> {
+
+        // Builds a snapshot of a ZIP archive member; `path` is its `archive.zip!member` name.
+
+        let snapshot = SourceSnapshot::from_bytes(path, bytes, None)?;
+        Ok(SourceSnapshot { archive: Some(archive.to_path_buf()), ..snapshot })
+    }
+
+
+    pub fn is_current(&self) -> Result<bool, DoxcerError> {
+
+        // Re-hashes the file on disk and checks it still matches the snapshot.
+
+        self.is_current_with(read_source)
+    }
+
+
+    pub fn is_current_with<R>(&self, read: R) -> Result<bool, DoxcerError>
+    where
+        R: FnOnce(&Path) -> io::Result<Vec<u8>>,
+    {
+
+        // Checks, through the given reader, whether the file still matches the snapshot.
+        //
+        // # Description
+        // Called just before an output or manifest entry is written. When the file
+        // changed since it was read, the generated document describes content that
+        // no longer exists and must not be recorded as up to date (`stale-input`).
+        // A downloaded notebook cannot change after the download, nor can one
+        // read from a commit or an archive (read into memory once), so those
+        // always are.
+
+        if self.url.is_some() || self.commit.is_some() || self.archive.is_some() {
+            return Ok(true);
+        }
+        let bytes = read(&self.path)
+            .map_err(|e| DoxcerError::Input(format!("Failed to re-read notebook {}: {e}", self.path.display())))?;
+        Ok(content_hash(&bytes) == self.hash)
+    }
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    const NOTEBOOK: &[u8] = b"df = spark.read.table('sales')\n";
+
+
+    #[test]
+    fn text_and_hash_come_from_the_same_read() {
+        let reads = RefCell::new(0);
+        let snapshot = SourceSnapshot::read_with(Path::new("nb.py"), |_| {
+            *reads.borrow_mut() += 1;
+            Ok(NOTEBOOK.to_vec())
+        })
+        .unwrap();
+        assert_eq!(*reads.borrow(), 1);
+        assert_eq!(snapshot.text.as_bytes(), NOTEBOOK);
+        assert_eq!(snapshot.hash, content_hash(NOTEBOOK));
+    }
+
+
+    #[test]
+    fn unchanged_file_is_current() {
+        let snapshot = SourceSnapshot::read_with(Path::new("nb.py"), |_| Ok(NOTEBOOK.to_vec())).unwrap();
+        assert!(snapshot.is_current_with(|_| Ok(NOTEBOOK.to_vec())).unwrap());
+    }
+
+
+    #[test]
+    fn file_swapped_between_read_and_write_is_stale() {
+        // The file on disk changes after the prompt was built from it, but
+        // before the output and manifest entry are written.
+        let disk = RefCell::new(NOTEBOOK.to_vec());
+        let snapshot = SourceSnapshot::read_with(Path::new("nb.py"), |_| Ok(disk.borrow().clone())).unwrap();
+        *disk.borrow_mut() = b"df = spark.read.table('orders')\n".to_vec();
+        assert!(!snapshot.is_current_with(|_| Ok(disk.borrow().clone())).unwrap());
+    }
+
+
+    #[test]
+    fn failed_re_read_is_an_error() {
+        let snapshot = SourceSnapshot::read_with(Path::new("nb.py"), |_| Ok(NOTEBOOK.to_vec())).unwrap();
+        let result = snapshot.is_current_with(|_| Err(io::Error::new(io::ErrorKind::NotFound, "gone")));
+        assert!(result.unwrap_err().to_string().contains("Failed to re-read notebook nb.py"));
+    }
+
+
+    #[test]
+    fn downloaded_and_committed_sources_are_always_current() {
+        let downloaded = SourceSnapshot::from_bytes(Path::new("nb.py"), NOTEBOOK.to_vec(), Some("https://example.com/nb.py")).unwrap();
+        let committed = SourceSnapshot::at_commit(Path::new("nb.py"), NOTEBOOK.to_vec(), "abc123").unwrap();
+        for snapshot in [downloaded, committed] {
+            assert!(snapshot.is_current_with(|_| panic!("must not re-read")).unwrap());
+        }
+    }
+
+
+    #[test]
+    fn invalid_utf8_is_rejected() {
+        let result = SourceSnapshot::read_with(Path::new("nb.py"), |_| Ok(vec![0xff, 0xfe]));
+        assert_eq!(result.unwrap_err().to_string(), "Notebook nb.py is not valid UTF-8");
+    }
+}
@@ -0,0 +1,521 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::frontmatter::{front_matter_value, split_front_matter, upsert_front_matter};
+use crate::html::slug;
+use crate::links::{LinkForm, extract_links};
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const PART_OF_KEY: &str = "doxcer_part_of";
+pub const CONTENTS_HEADING: &str = "## Onderdelen";
+pub const FALLBACK_SECTION: &str = "sectie";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitLimit {
+    Lines(usize),
+    Bytes(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitPage {
+    // Relative to the directory of the landing page.
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+struct Section<'a> {
+    title: &'a str,
+    // Byte offset of the heading in the body.
+    start: usize,
+    text: &'a str,
+}
+
+impl SplitLimit {
+
+    pub fn exceeded_by(&self, markdown: &str) -> bool {
+
+        // Returns whether a document is over the limit.
+
+        match self {
+            SplitLimit::Lines(max) => markdown.lines().count() > *max,
+            SplitLimit::Bytes(max) => markdown.len() > *max,
+        }
+    }
+}
+
+impl fmt::Display for SplitLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SplitLimit::Lines(max) => write!(f, "{max} lines"),
+            SplitLimit::Bytes(max) => write!(f, "{max} bytes"),
+        }
+    }
+}
+
+impl FromStr for SplitLimit {
+    type Err = DoxcerError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+
+        // Parses `400` (lines), `65536b` or `64kb` (bytes).
+
+        let lower = value.trim().to_ascii_lowercase();
+        let (digits, unit) = match lower.find(|c: char| !c.is_ascii_digit()) {
+            Some(i) => lower.split_at(i),
+            None => (lower.as_str(), ""),
+        };
+        let number: usize = digits
+            .parse()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| DoxcerError::Input(format!("expected a positive number of lines, or bytes with `b` or `kb`, got {value:?}")))?;
+        match unit {
+            "" => Ok(SplitLimit::Lines(number)),
+            "b" => Ok(SplitLimit::Bytes(number)),
+            "kb" => Ok(SplitLimit::Bytes(number * 1024)),
+            _ => Err(DoxcerError::Input(format!("expected a positive number of lines, or bytes with `b` or `kb`, got {value:?}"))),
+        }
+    }
+}
+
+
+// ====================================================
+//  Splitting
+// ====================================================
+pub fn split_document(markdown: &str, stem: &str) -> Vec<SplitPage> {
+
+    // Splits a document at its top-level `##` headings.
+    //
+    // # Description
+    // The landing page `<stem>.md` keeps the front-matter and the text before
+    // the first `##` heading, followed by a list of links to the sections; each
+    // section becomes `<stem>/<slug>.md`, named after its heading with the same
+    // slugger that gives the HTML headings their ids. Links to `#anchor` are
+    // rewritten to the page the heading ended up on, and other relative links
+    // in section pages get a `../`. The result only depends on the input, so
+    // regenerating the same document produces the same pages.
+    //
+    // # Returns
+    // * The landing page first, then the sections in document order. A
+    //   document without `##` headings comes back as a single page.
+
+    let landing = PathBuf::from(format!("{stem}.md"));
+    let (_, body) = split_front_matter(markdown);
+    let header = &markdown[..markdown.len() - body.len()];
+    let (intro, sections) = sections(body);
+    if sections.is_empty() {
+        return vec![SplitPage { path: landing, contents: markdown.to_string() }];
+    }
+
+    let mut used = BTreeSet::new();
+    let files: Vec<String> = sections
+        .iter()
+        .map(|section| {
+            let base = Some(slug(section.title)).filter(|s| !s.is_empty()).unwrap_or_else(|| FALLBACK_SECTION.to_string());
+            let mut name = base.clone();
+            let mut n = 1;
+            while !used.insert(name.clone()) {
+                n += 1;
+                name = format!("{base}-{n}");
+            }
+            format!("{name}.md")
+        })
+        .collect();
+
+    // Page 0 is the landing page, page i + 1 is section i.
+    let mut anchors: BTreeMap<String, usize> = BTreeMap::new();
+    for (page, text) in std::iter::once(intro).chain(sections.iter().map(|s| s.text)).enumerate() {
+        for anchor in heading_anchors(text) {
+            anchors.entry(anchor).or_insert(page);
+        }
+    }
+    let href = |from: usize, to: usize| -> String {
+        match (from, to) {
+            (0, 0) => String::new(),
+            (0, to) => format!("{stem}/{}", files[to - 1]),
+            (_, 0) => format!("../{stem}.md"),
+            (_, to) => files[to - 1].clone(),
+        }
+    };
+
+    let mut contents = format!("{header}{}", rewrite_links(intro, 0, &anchors, &href).trim_end());
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push_str("\n\n");
+    }
+    contents.push_str(&format!("{CONTENTS_HEADING}\n\n"));
+    for (section, file) in sections.iter().zip(&files) {
+        contents.push_str(&format!("- [{}]({stem}/{file})\n", section.title));
+    }
+
+    let mut pages = vec![SplitPage { path: landing, contents }];
+    for (i, (section, file)) in sections.iter().zip(&files).enumerate() {
+        let text = format!("{}\n", rewrite_links(section.text, i + 1, &anchors, &href).trim_end());
+        pages.push(SplitPage {
+            path: Path::new(stem).join(file),
+            contents: upsert_front_matter(&text, &[(PART_OF_KEY, format!("../{stem}.md"))]),
+        });
+    }
+    pages
+}
+
+
+fn sections(body: &str) -> (&str, Vec<Section<'_>>) {
+
+    // Cuts a body into the intro and one slice per `##` heading, skipping
+    // headings inside fenced code.
+
+    let mut starts: Vec<(usize, &str)> = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let trimmed = line.trim_end_matches(['\n', '\r']);
+        if let Some(marker) = fence {
+            if trimmed.trim_start().starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.trim_start().starts_with(m)) {
+            fence = Some(marker);
+            continue;
+        }
+        if let Some(title) = trimmed.strip_prefix("## ") {
+            starts.push((start, title.trim()));
+        }
+    }
+
+    let intro = &body[..starts.first().map_or(body.len(), |(start, _)| *start)];
+    let sections = starts
+        .iter()
+        .enumerate()
+        .map(|(i, (start, title))| Section {
+            title,
+            start: *start,
+            text: &body[*start..starts.get(i + 1).map_or(body.len(), |(next, _)| *next)],
+        })
+        .collect();
+    (intro, sections)
+}
+
+
+fn heading_anchors(text: &str) -> Vec<String> {
+
+    // Returns the ids of the headings in a page, as the HTML renderer assigns them.
+
+    let mut anchors = Vec::new();
+    let mut in_fence = false;
+    for line in text.lines().map(str::trim) {
+        if line.starts_with("```") || line.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        let level = line.chars().take_while(|c| *c == '#').count();
+        if !in_fence && (1..=6).contains(&level) && line[level..].starts_with(' ') {
+            anchors.push(slug(line[level..].trim()));
+        }
+    }
+    anchors
+}
+
+
+fn rewrite_links(text: &str, page: usize, anchors: &BTreeMap<String, usize>, href: &dyn Fn(usize, usize) -> String) -> String {
+
+    // Points `#anchor` links at the page that holds the heading, and moves the
+    // other relative links of a section page one directory up.
+
+    let mut replacements: Vec<(std::ops::Range<usize>, String)> = Vec::new();
+    for link in extract_links(text) {
+        if matches!(link.form, LinkForm::Reference(_)) {
+            continue;
+        }
+        let target = &text[link.target.clone()];
+        if let Some(anchor) = target.strip_prefix('#') {
+            if let Some(to) = anchors.get(anchor).copied().filter(|to| *to != page) {
+                replacements.push((link.target, format!("{}#{anchor}", href(page, to))));
+            }
+        } else if page > 0 && is_relative(target) {
+            replacements.push((link.target, format!("../{target}")));
+        }
+    }
+
+    let mut out = text.to_string();
+    for (range, replacement) in replacements.into_iter().rev() {
+        out.replace_range(range, &replacement);
+    }
+    out
+}
+
+
+fn is_relative(target: &str) -> bool {
+    !target.is_empty() && !target.starts_with(['/', '<']) && !target.contains(':')
+}
+
+
+// ====================================================
+//  Sections
+// ====================================================
+pub fn section_titles(markdown: &str) -> Vec<String> {
+
+    // Returns the titles of the `##` sections of a document, in order.
+
+    let (_, body) = split_front_matter(markdown);
+    sections(body).1.iter().map(|section| section.title.to_string()).collect()
+}
+
+
+pub fn find_section<'a>(markdown: &'a str, title: &str) -> Result<&'a str, DoxcerError> {
+
+    // Returns the text of the `## <title>` section, from its heading up to the
+    // next `##` heading, including its sub-headings. Titles match without
+    // regard to ASCII case; the first match wins.
+
+    locate_section(markdown, title).map(|(start, end)| &markdown[start..end])
+}
+
+
+fn locate_section(markdown: &str, title: &str) -> Result<(usize, usize), DoxcerError> {
+
+    // Returns the byte range of the `## <title>` section in the document.
+
+    let (_, body) = split_front_matter(markdown);
+    let offset = markdown.len() - body.len();
+    let (_, found) = sections(body);
+    if let Some(section) = found.iter().find(|section| section.title.eq_ignore_ascii_case(title.trim())) {
+        return Ok((offset + section.start, offset + section.start + section.text.len()));
+    }
+    if found.is_empty() {
+        return Err(DoxcerError::Input(format!("the document has no `## {title}` section, nor any other `##` section")));
+    }
+    let titles: Vec<&str> = found.iter().map(|section| section.title).collect();
+    Err(DoxcerError::Input(format!("the document has no `## {title}` section; its sections are: {}", titles.join(", "))))
+}
+
+
+pub fn replace_section(markdown: &str, title: &str, answer: &str) -> Result<String, DoxcerError> {
+
+    // Splices a rewritten section into a document.
+    //
+    // # Description
+    // `answer` is the model's version of the `## <title>` section: when it has
+    // `##` headings, only the section with that title is taken (anything
+    // around it is dropped); without any, the whole answer is the section
+    // body. The original heading line and the whitespace after the section
+    // are kept, and everything outside the section is left byte-for-byte as
+    // it was.
+    //
+    // # Returns
+    // * `Ok(String)` – the document with the section replaced.
+    // * `Err(DoxcerError)` – the document has no such section, listing those it
+    //   has, or the answer has `##` sections but not this one.
+
+    let (start, end) = locate_section(markdown, title)?;
+    let old = &markdown[start..end];
+    let heading = old.split_inclusive('\n').next().unwrap_or(old);
+
+    let (_, answer) = split_front_matter(answer);
+    let (_, rewritten) = sections(answer);
+    let content = match rewritten.iter().find(|section| section.title.eq_ignore_ascii_case(title.trim())) {
+        Some(section) => &section.text[section.text.split_inclusive('\n').next().map_or(0, str::len)..],
+        None if rewritten.is_empty() => answer,
+        None => {
+            let titles: Vec<&str> = rewritten.iter().map(|section| section.title).collect();
+            return Err(DoxcerError::Input(format!("the answer has no `## {title}` section, only: {}", titles.join(", "))));
+        }
+    };
+
+    let trailing = &old[old.trim_end().len()..];
+    let mut section = heading.to_string();
+    if !section.ends_with('\n') {
+        section.push('\n');
+    }
+    let content = content.trim_matches(['\n', '\r']).trim_end();
+    if !content.is_empty() {
+        section.push('\n');
+        section.push_str(content);
+    }
+    let section = format!("{}{trailing}", section.trim_end());
+    Ok(format!("{}{section}{}", &markdown[..start], &markdown[end..]))
+}
+
+
+// ====================================================
+//  Writing
+// ====================================================
+pub fn write_split(doc_path: &Path, markdown: &str, limit: SplitLimit) -> Result<Vec<PathBuf>, DoxcerError> {
+
+    // Writes a document, split into pages when it is over `limit`.
+    //
+    // # Description
+    // Every page is written atomically (temp file + rename). Section pages of
+    // an earlier split that are no longer produced are removed; only files
+    // whose `doxcer_part_of` points at this document are touched.
+    //
+    // # Returns
+    // * The paths written, the landing page first.
+
+    let stem = doc_path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let dir = doc_path.parent().unwrap_or(Path::new("."));
+    let pages = if limit.exceeded_by(markdown) {
+        split_document(markdown, &stem)
+    } else {
+        vec![SplitPage { path: PathBuf::from(format!("{stem}.md")), contents: markdown.to_string() }]
+    };
+
+    let mut written = Vec::new();
+    for page in &pages {
+        let path = dir.join(&page.path);
+        write_atomic(&path, &page.contents)?;
+        written.push(path);
+    }
+
+    let sections_dir = dir.join(&stem);
+    let part_of = format!("../{stem}.md");
+    if let Ok(entries) = fs::read_dir(&sections_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let ours = fs::read_to_string(&path)
+                .ok()
+                .and_then(|text| split_front_matter(&text).0.and_then(|f| front_matter_value(f, PART_OF_KEY)))
+                .is_some_and(|value| value == part_of);
+            if ours && !written.contains(&path) {
+                fs::remove_file(&path).map_err(|e| DoxcerError::Input(format!("Failed to remove {}: {e}", path.display())))?;
+            }
+        }
+        let _ = fs::remove_dir(&sections_dir);
+    }
+    Ok(written)
+}
+
+
+pub fn write_atomic(path: &Path, contents: &str) -> Result<(), DoxcerError> {
+
+    // Writes a file through a `.partial` sibling and a rename.
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| DoxcerError::Input(format!("Failed to create {}: {e}", parent.display())))?;
+    }
+    let tmp = path.with_extension("md.partial");
+    fs::write(&tmp, contents).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", tmp.display())))?;
+    fs::rename(&tmp, path).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", path.display())))
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "---\n\
+        doxcer_model: gpt-5-mini\n\
+        ---\n\
+        # dim_project\n\
+        \n\
+        Intro.\n\
+        \n\
+        ## Overview\n\
+        \n\
+        Loads projects.\n\
+        \n\
+        ## Parameters\n\
+        \n\
+        ### Required\n\
+        \n\
+        - `run_date`\n\
+        \n\
+        ```python\n\
+        ## not a heading\n\
+        ```\n\
+        \n\
+        ### Optional\n\
+        \n\
+        - `full_load`\n\
+        \n\
+        ## Output\n\
+        \n\
+        Writes `gold.dim_project`.\n";
+
+
+    #[test]
+    fn finds_a_section_with_its_sub_headings() {
+        let section = find_section(DOC, "parameters").unwrap();
+        assert!(section.starts_with("## Parameters\n"));
+        assert!(section.contains("### Required") && section.contains("## not a heading") && section.contains("### Optional"));
+        assert!(section.ends_with("- `full_load`\n\n"));
+    }
+
+
+    #[test]
+    fn splicing_a_section_back_is_a_round_trip() {
+        let section = find_section(DOC, "Parameters").unwrap();
+        assert_eq!(replace_section(DOC, "Parameters", section).unwrap(), DOC);
+    }
+
+
+    #[test]
+    fn splicing_keeps_everything_else_byte_for_byte() {
+        let answer = "## Parameters\n\n### Required\n\n- `run_date` (date)\n\n### Optional\n\n- `full_load` (bool)\n";
+        let spliced = replace_section(DOC, "Parameters", answer).unwrap();
+        let (start, end) = locate_section(DOC, "Parameters").unwrap();
+        let tail = &DOC[end..];
+        assert!(spliced.starts_with(&DOC[..start]));
+        assert!(spliced.ends_with(tail));
+        assert_eq!(&spliced[start..spliced.len() - tail.len()], "## Parameters\n\n### Required\n\n- `run_date` (date)\n\n### Optional\n\n- `full_load` (bool)\n\n");
+
+        // Splicing the old section back restores the original document.
+        assert_eq!(replace_section(&spliced, "Parameters", find_section(DOC, "Parameters").unwrap()).unwrap(), DOC);
+    }
+
+
+    #[test]
+    fn answer_without_headings_is_the_section_body() {
+        let spliced = replace_section(DOC, "Overview", "\nLoads and deduplicates projects.\n\n").unwrap();
+        assert!(spliced.contains("## Overview\n\nLoads and deduplicates projects.\n\n## Parameters\n"));
+        assert_eq!(find_section(&spliced, "Parameters").unwrap(), find_section(DOC, "Parameters").unwrap());
+    }
+
+
+    #[test]
+    fn only_the_target_section_of_the_answer_is_taken() {
+        let answer = "Here you go:\n\n## Output\n\nWrites `gold.dim_project` daily.\n\n## Notes\n\nDropped.\n";
+        let spliced = replace_section(DOC, "Output", answer).unwrap();
+        assert!(spliced.ends_with("## Output\n\nWrites `gold.dim_project` daily.\n"));
+        assert!(!spliced.contains("Dropped"));
+    }
+
+
+    #[test]
+    fn missing_sections_list_the_available_ones() {
+        assert_eq!(
+            find_section(DOC, "Lineage").unwrap_err().to_string(),
+            "the document has no `## Lineage` section; its sections are: Overview, Parameters, Output"
+        );
+        assert_eq!(
+            replace_section(DOC, "Output", "## Notes\n\nx\n").unwrap_err().to_string(),
+            "the answer has no `## Output` section, only: Notes"
+        );
+    }
+}
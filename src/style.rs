@@ -0,0 +1,242 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::env;
+use std::io::{self, IsTerminal};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Cyan,
+    Dim,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    pub enabled: bool,
+}
+
+static COLOR_CHOICE: OnceLock<ColorChoice> = OnceLock::new();
+
+impl FromStr for ColorChoice {
+    type Err = DoxcerError;
+
+    fn from_str(s: &str) -> Result<ColorChoice, DoxcerError> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            other => Err(DoxcerError::Usage(format!("--color must be auto, always or never, got {other:?}"))),
+        }
+    }
+}
+
+
+// ====================================================
+//  Color Resolution
+// ====================================================
+pub fn colors_enabled(choice: ColorChoice, is_tty: bool, no_color: bool) -> bool {
+
+    // Decides whether output should be colored.
+    //
+    // # Description
+    // `always` and `never` force the decision. `auto` colors only when the stream
+    // is a terminal and `NO_COLOR` is not set, so CI logs and pipes stay plain.
+
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => is_tty && !no_color,
+    }
+}
+
+
+pub fn set_color_choice(choice: ColorChoice) {
+
+    // Sets the process-wide `--color` choice. Only the first call has an effect.
+
+    let _ = COLOR_CHOICE.set(choice);
+}
+
+
+fn no_color_set() -> bool {
+
+    // Checks the `NO_COLOR` convention (any non-empty value disables color).
+
+    env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+
+// ====================================================
+//  Styling
+// ====================================================
+impl Style {
+
+    pub fn plain() -> Style {
+
+        // A style that never emits escape codes.
+
+        Style { enabled: false }
+    }
+
+
+    pub fn stderr() -> Style {
+
+        // The style for diagnostics written to stderr.
+
+        let choice = COLOR_CHOICE.get().copied().unwrap_or_default();
+        Style { enabled: colors_enabled(choice, io::stderr().is_terminal(), no_color_set()) }
+    }
+
+
+    pub fn stdout() -> Style {
+
+        // The style for tables and summaries written to stdout.
+
+        let choice = COLOR_CHOICE.get().copied().unwrap_or_default();
+        Style { enabled: colors_enabled(choice, io::stdout().is_terminal(), no_color_set()) }
+    }
+
+
+    pub fn paint(&self, text: &str, color: Color) -> String {
+
+        // Wraps `text` in the ANSI codes for `color` when styling is enabled.
+
+        if !self.enabled {
+            return text.to_string();
+        }
+        let code = match color {
+            Color::Red => "1;31",
+            Color::Green => "32",
+            Color::Yellow => "33",
+            Color::Cyan => "36",
+            Color::Dim => "2",
+        };
+        format!("\x1b[{code}m{text}\x1b[0m")
+    }
+
+
+    pub fn error_prefix(&self) -> String {
+
+        // The `error:` prefix for fatal diagnostics.
+
+        self.paint("error:", Color::Red)
+    }
+
+
+    pub fn warning_prefix(&self) -> String {
+
+        // The `warning:` prefix for non-fatal diagnostics.
+
+        self.paint("warning:", Color::Yellow)
+    }
+
+
+    pub fn mark(&self, pass: bool) -> String {
+
+        // A pass/fail mark for check lists.
+
+        if pass {
+            self.paint("✔", Color::Green)
+        } else {
+            self.paint("✘", Color::Red)
+        }
+    }
+
+
+    pub fn check_line(&self, pass: bool, detail: &str) -> String {
+
+        // One line of the `doctor` check list: the mark, then what was checked.
+
+        format!("{} {detail}", self.mark(pass))
+    }
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doctor_checks(style: Style) -> String {
+        [
+            (true, ".env loaded from /work/.env"),
+            (false, "OPENAI_API_KEY_ENC is not set"),
+            (true, "config loaded"),
+        ]
+        .iter()
+        .map(|(pass, detail)| style.check_line(*pass, detail) + "\n")
+        .collect()
+    }
+
+
+    #[test]
+    fn doctor_checks_render_plain() {
+        assert_eq!(
+            doctor_checks(Style::plain()),
+            "✔ .env loaded from /work/.env\n\
+             ✘ OPENAI_API_KEY_ENC is not set\n\
+             ✔ config loaded\n"
+        );
+    }
+
+
+    #[test]
+    fn doctor_checks_render_colored() {
+        assert_eq!(
+            doctor_checks(Style { enabled: true }),
+            "\x1b[32m✔\x1b[0m .env loaded from /work/.env\n\
+             \x1b[1;31m✘\x1b[0m OPENAI_API_KEY_ENC is not set\n\
+             \x1b[32m✔\x1b[0m config loaded\n"
+        );
+    }
+
+
+    #[test]
+    fn prefixes_are_painted_only_when_enabled() {
+        let colored = Style { enabled: true };
+        assert_eq!(colored.error_prefix(), "\x1b[1;31merror:\x1b[0m");
+        assert_eq!(colored.warning_prefix(), "\x1b[33mwarning:\x1b[0m");
+        assert_eq!(colored.paint("note", Color::Dim), "\x1b[2mnote\x1b[0m");
+        assert_eq!(Style::plain().error_prefix(), "error:");
+        assert_eq!(Style::plain().paint("note", Color::Cyan), "note");
+    }
+
+
+    #[test]
+    fn auto_colors_only_terminals_without_no_color() {
+        assert!(colors_enabled(ColorChoice::Auto, true, false));
+        assert!(!colors_enabled(ColorChoice::Auto, true, true));
+        assert!(!colors_enabled(ColorChoice::Auto, false, false));
+        assert!(colors_enabled(ColorChoice::Always, false, true));
+        assert!(!colors_enabled(ColorChoice::Never, true, false));
+        assert_eq!("always".parse::<ColorChoice>(), Ok(ColorChoice::Always));
+        assert!("yes".parse::<ColorChoice>().unwrap_err().to_string().contains("auto, always or never"));
+    }
+}
@@ -0,0 +1,108 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::frontmatter::split_front_matter;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const DEFAULT_SUMMARY_MAX_CHARS: usize = 600;
+pub const MODE_KEY: &str = "doxcer_mode";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocMode {
+    Full,
+    Summary,
+}
+
+impl DocMode {
+
+    pub fn name(&self) -> &'static str {
+
+        // Returns the name used by `--mode` and in the `doxcer_mode` front-matter key.
+
+        match self {
+            DocMode::Full => "full",
+            DocMode::Summary => "summary",
+        }
+    }
+}
+
+
+// ====================================================
+//  Parsing
+// ====================================================
+pub fn parse_modes(value: &str) -> Result<Vec<DocMode>, DoxcerError> {
+
+    // Parses a `--mode` value: `full`, `summary` or both, comma-separated.
+    //
+    // # Returns
+    // * `Ok(Vec<DocMode>)` in the given order, without duplicates.
+    // * `Err(DoxcerError)` for an unknown or empty mode.
+
+    let mut modes = Vec::new();
+    for item in value.split(',').map(str::trim) {
+        let mode = match item {
+            "full" => DocMode::Full,
+            "summary" => DocMode::Summary,
+            other => return Err(DoxcerError::Input(format!("unknown mode {other:?}; use full, summary or full,summary"))),
+        };
+        if !modes.contains(&mode) {
+            modes.push(mode);
+        }
+    }
+    Ok(modes)
+}
+
+
+// ====================================================
+//  Validation
+// ====================================================
+pub fn check_summary(text: &str, max_chars: usize) -> Result<(), DoxcerError> {
+
+    // Checks that a generated summary is one paragraph of at most `max_chars`
+    // characters.
+    //
+    // # Description
+    // Front-matter is ignored. Blank lines, headings, lists, tables and code
+    // fences all make the answer more than a single paragraph.
+
+    let (_, body) = split_front_matter(text);
+    let body = body.trim();
+    if body.is_empty() {
+        return Err(DoxcerError::Input("the summary is empty".to_string()));
+    }
+    if body.lines().any(|l| l.trim().is_empty()) {
+        return Err(DoxcerError::Input("the summary has more than one paragraph".to_string()));
+    }
+    if body.lines().map(str::trim_start).any(|l| l.starts_with(['#', '|', '>']) || l.starts_with("- ") || l.starts_with("* ") || l.starts_with("```")) {
+        return Err(DoxcerError::Input("the summary is not a plain paragraph".to_string()));
+    }
+    let chars = body.chars().count();
+    if chars > max_chars {
+        return Err(DoxcerError::Input(format!("the summary is {chars} characters, more than the limit of {max_chars}")));
+    }
+    Ok(())
+}
+
+
+pub fn shorter_instruction(max_chars: usize) -> String {
+
+    // Returns the instruction appended to the prompt when a summary is re-asked.
+
+    format!(
+        "Je vorige antwoord voldeed niet. Schrijf de samenvatting opnieuw, korter: \
+         precies één alinea platte tekst van hoogstens {max_chars} tekens, zonder kopjes, lijsten of tabellen."
+    )
+}
@@ -0,0 +1,561 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fmt::Display;
+use std::path::Path;
+
+#[cfg(feature = "otel")]
+use std::env;
+#[cfg(feature = "otel")]
+use std::process;
+#[cfg(feature = "otel")]
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+#[cfg(feature = "otel")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// External Libraries
+#[cfg(feature = "otel")]
+use serde_json::{Value, json};
+#[cfg(feature = "otel")]
+use sha2::{Digest, Sha256};
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const SERVICE_NAME: &str = "doxcer";
+#[cfg(feature = "otel")]
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttrValue {
+    Str(String),
+    Int(i64),
+    Bool(bool),
+}
+
+impl From<&str> for AttrValue {
+    fn from(value: &str) -> Self {
+        AttrValue::Str(value.to_string())
+    }
+}
+
+impl From<String> for AttrValue {
+    fn from(value: String) -> Self {
+        AttrValue::Str(value)
+    }
+}
+
+impl From<&String> for AttrValue {
+    fn from(value: &String) -> Self {
+        AttrValue::Str(value.clone())
+    }
+}
+
+impl From<&Path> for AttrValue {
+    fn from(value: &Path) -> Self {
+        AttrValue::Str(value.display().to_string())
+    }
+}
+
+impl From<u64> for AttrValue {
+    fn from(value: u64) -> Self {
+        AttrValue::Int(i64::try_from(value).unwrap_or(i64::MAX))
+    }
+}
+
+impl From<usize> for AttrValue {
+    fn from(value: usize) -> Self {
+        AttrValue::Int(i64::try_from(value).unwrap_or(i64::MAX))
+    }
+}
+
+impl From<u16> for AttrValue {
+    fn from(value: u16) -> Self {
+        AttrValue::Int(i64::from(value))
+    }
+}
+
+impl From<bool> for AttrValue {
+    fn from(value: bool) -> Self {
+        AttrValue::Bool(value)
+    }
+}
+
+// Traces of one run; clones share its spans, also across worker threads.
+// Without the `otel` feature this is an empty type and every method below
+// compiles to nothing.
+#[derive(Debug, Clone, Default)]
+pub struct Tracer {
+    #[cfg(feature = "otel")]
+    recorder: Option<Arc<Mutex<Recorder>>>,
+}
+
+// An open span; it ends when it is dropped.
+#[derive(Debug)]
+pub struct Span {
+    #[cfg(feature = "otel")]
+    recorder: Option<Arc<Mutex<Recorder>>>,
+    #[cfg(feature = "otel")]
+    index: usize,
+}
+
+#[cfg(feature = "otel")]
+#[derive(Debug)]
+struct Recorder {
+    endpoint: String,
+    headers: Vec<(String, String)>,
+    service: String,
+    trace_id: String,
+    spans: Vec<SpanRecord>,
+    open: Vec<usize>,
+}
+
+#[cfg(feature = "otel")]
+#[derive(Debug)]
+struct SpanRecord {
+    span_id: String,
+    parent: Option<String>,
+    name: &'static str,
+    start_ns: u128,
+    end_ns: Option<u128>,
+    attributes: Vec<(&'static str, AttrValue)>,
+    error: Option<String>,
+}
+
+
+// ====================================================
+//  Tracer
+// ====================================================
+impl Tracer {
+
+    #[cfg(feature = "otel")]
+    pub fn from_env() -> Result<Tracer, DoxcerError> {
+
+        // Sets up OTLP trace export from the standard OpenTelemetry variables.
+        //
+        // # Description
+        // `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` is used as-is; otherwise `/v1/traces`
+        // is appended to `OTEL_EXPORTER_OTLP_ENDPOINT`. Headers come from
+        // `OTEL_EXPORTER_OTLP_TRACES_HEADERS` / `OTEL_EXPORTER_OTLP_HEADERS`
+        // (`key=value,key=value`) and the service name from `OTEL_SERVICE_NAME`.
+        // Spans are sent as OTLP/HTTP JSON in one request when the run ends.
+        //
+        // # Returns
+        // * `Ok(Tracer)` – recording when an endpoint is set, a no-op otherwise.
+        // * `Err(DoxcerError)` – a protocol other than `http/json` was requested.
+
+        let var = |name: &str| env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let endpoint = match (var("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT"), var("OTEL_EXPORTER_OTLP_ENDPOINT")) {
+            (Some(endpoint), _) => endpoint,
+            (None, Some(base)) => format!("{}/v1/traces", base.trim_end_matches('/')),
+            (None, None) => return Ok(Tracer::default()),
+        };
+        let protocol = var("OTEL_EXPORTER_OTLP_TRACES_PROTOCOL").or_else(|| var("OTEL_EXPORTER_OTLP_PROTOCOL"));
+        if let Some(protocol) = protocol.filter(|p| p != "http/json") {
+            return Err(DoxcerError::Config(format!("OTLP protocol {protocol:?} is not supported, only http/json; traces are not exported")));
+        }
+        let headers = var("OTEL_EXPORTER_OTLP_TRACES_HEADERS")
+            .or_else(|| var("OTEL_EXPORTER_OTLP_HEADERS"))
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+            .collect();
+        let recorder = Recorder {
+            endpoint,
+            headers,
+            service: var("OTEL_SERVICE_NAME").unwrap_or_else(|| SERVICE_NAME.to_string()),
+            trace_id: new_id(32),
+            spans: Vec::new(),
+            open: Vec::new(),
+        };
+        Ok(Tracer { recorder: Some(Arc::new(Mutex::new(recorder))) })
+    }
+
+
+    #[cfg(not(feature = "otel"))]
+    #[inline(always)]
+    pub fn from_env() -> Result<Tracer, DoxcerError> {
+
+        // Builds without the `otel` feature never export traces.
+
+        Ok(Tracer::default())
+    }
+
+
+    #[inline(always)]
+    pub fn span(&self, name: &'static str) -> Span {
+
+        // Opens a span as a child of the innermost open span.
+
+        #[cfg(feature = "otel")]
+        if let Some(recorder) = &self.recorder {
+            let mut r = lock(recorder);
+            let parent = r.open.last().map(|i| r.spans[*i].span_id.clone());
+            let index = r.spans.len();
+            r.spans.push(SpanRecord {
+                span_id: new_id(16),
+                parent,
+                name,
+                start_ns: now_ns(),
+                end_ns: None,
+                attributes: Vec::new(),
+                error: None,
+            });
+            r.open.push(index);
+            return Span { recorder: Some(Arc::clone(recorder)), index };
+        }
+        let _ = name;
+        Span::default()
+    }
+
+
+    #[inline(always)]
+    pub fn set_current<V: Into<AttrValue>>(&self, key: &'static str, value: V) {
+
+        // Sets an attribute on the innermost open span, for code that has no
+        // handle to it (such as the API call inside the fallback loop).
+
+        #[cfg(feature = "otel")]
+        if let Some(recorder) = &self.recorder {
+            let mut r = lock(recorder);
+            if let Some(&index) = r.open.last() {
+                r.spans[index].attributes.push((key, value.into()));
+            }
+            return;
+        }
+        let _ = (key, value);
+    }
+
+
+    #[cfg(feature = "otel")]
+    pub fn export(&self) -> Result<(), DoxcerError> {
+
+        // Sends the finished spans to the OTLP endpoint. Spans that are still
+        // open are closed first.
+
+        let Some(recorder) = &self.recorder else {
+            return Ok(());
+        };
+        let body = {
+            let mut r = lock(recorder);
+            let now = now_ns();
+            for span in &mut r.spans {
+                span.end_ns.get_or_insert(now);
+            }
+            r.open.clear();
+            let body = r.payload();
+            r.spans.clear();
+            body
+        };
+        let r = lock(recorder);
+        let client = reqwest::blocking::Client::builder()
+            .timeout(EXPORT_TIMEOUT)
+            .build()
+            .map_err(|e| DoxcerError::Config(format!("Failed to build the OTLP client: {e}")))?;
+        let mut request = client.post(&r.endpoint).header("Content-Type", "application/json");
+        for (key, value) in &r.headers {
+            request = request.header(key, value);
+        }
+        let res = request
+            .body(body.to_string())
+            .send()
+            .map_err(|e| DoxcerError::Config(format!("Failed to export traces to {}: {e}", r.endpoint)))?;
+        if !res.status().is_success() {
+            return Err(DoxcerError::Config(format!("Failed to export traces to {}: HTTP {}", r.endpoint, res.status())));
+        }
+        Ok(())
+    }
+
+
+    #[cfg(not(feature = "otel"))]
+    #[inline(always)]
+    pub fn export(&self) -> Result<(), DoxcerError> {
+        Ok(())
+    }
+}
+
+
+// ====================================================
+//  Spans
+// ====================================================
+impl Default for Span {
+    #[inline(always)]
+    fn default() -> Self {
+        Span {
+            #[cfg(feature = "otel")]
+            recorder: None,
+            #[cfg(feature = "otel")]
+            index: 0,
+        }
+    }
+}
+
+impl Span {
+
+    #[inline(always)]
+    pub fn set<V: Into<AttrValue>>(&self, key: &'static str, value: V) {
+
+        // Sets an attribute on this span.
+
+        #[cfg(feature = "otel")]
+        if let Some(recorder) = &self.recorder {
+            lock(recorder).spans[self.index].attributes.push((key, value.into()));
+            return;
+        }
+        let _ = (key, value);
+    }
+
+
+    #[inline(always)]
+    pub fn fail(&self, error: impl Display) {
+
+        // Marks this span as failed, with the error as its status message.
+
+        #[cfg(feature = "otel")]
+        if let Some(recorder) = &self.recorder {
+            lock(recorder).spans[self.index].error = Some(error.to_string());
+            return;
+        }
+        let _ = error;
+    }
+}
+
+impl Drop for Span {
+    #[inline(always)]
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        if let Some(recorder) = &self.recorder {
+            let mut r = lock(recorder);
+            r.spans[self.index].end_ns.get_or_insert_with(now_ns);
+            r.open.retain(|i| *i != self.index);
+        }
+    }
+}
+
+
+// ====================================================
+//  OTLP Encoding
+// ====================================================
+#[cfg(feature = "otel")]
+impl Recorder {
+
+    fn payload(&self) -> Value {
+
+        // Encodes the spans as an OTLP/HTTP JSON `ExportTraceServiceRequest`.
+
+        let spans: Vec<Value> = self.spans.iter().map(|span| {
+            let mut encoded = json!({
+                "traceId": self.trace_id,
+                "spanId": span.span_id,
+                "name": span.name,
+                // SPAN_KIND_CLIENT for the API call, SPAN_KIND_INTERNAL otherwise.
+                "kind": if span.name == "api-call" { 3 } else { 1 },
+                "startTimeUnixNano": span.start_ns.to_string(),
+                "endTimeUnixNano": span.end_ns.unwrap_or(span.start_ns).to_string(),
+                "attributes": span.attributes.iter().map(|(k, v)| attribute(k, v)).collect::<Vec<_>>(),
+                "status": match &span.error {
+                    Some(message) => json!({ "code": 2, "message": message }),
+                    None => json!({ "code": 0 }),
+                },
+            });
+            if let Some(parent) = &span.parent {
+                encoded["parentSpanId"] = json!(parent);
+            }
+            encoded
+        }).collect();
+
+        json!({
+            "resourceSpans": [{
+                "resource": { "attributes": [
+                    attribute("service.name", &AttrValue::Str(self.service.clone())),
+                    attribute("service.version", &AttrValue::Str(crate::version().to_string())),
+                ] },
+                "scopeSpans": [{
+                    "scope": { "name": SERVICE_NAME, "version": crate::version() },
+                    "spans": spans,
+                }],
+            }],
+        })
+    }
+}
+
+
+#[cfg(feature = "otel")]
+fn attribute(key: &str, value: &AttrValue) -> Value {
+
+    // Encodes one attribute; OTLP JSON carries 64-bit integers as strings.
+
+    let value = match value {
+        AttrValue::Str(s) => json!({ "stringValue": s }),
+        AttrValue::Int(i) => json!({ "intValue": i.to_string() }),
+        AttrValue::Bool(b) => json!({ "boolValue": b }),
+    };
+    json!({ "key": key, "value": value })
+}
+
+
+#[cfg(feature = "otel")]
+fn new_id(hex_len: usize) -> String {
+
+    // Creates a trace or span id: hex digits from the clock, the process id and
+    // a counter, so ids differ per span and per run.
+
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let seed = format!("{}-{}-{}", now_ns(), process::id(), COUNTER.fetch_add(1, Ordering::Relaxed));
+    let digest = Sha256::digest(seed.as_bytes());
+    digest.iter().map(|b| format!("{b:02x}")).collect::<String>()[..hex_len].to_string()
+}
+
+
+#[cfg(feature = "otel")]
+fn now_ns() -> u128 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0)
+}
+
+
+#[cfg(feature = "otel")]
+fn lock(recorder: &Mutex<Recorder>) -> MutexGuard<'_, Recorder> {
+
+    // Locks the recorder; a panic while it was held leaves the spans usable.
+
+    recorder.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(all(test, feature = "otel"))]
+mod tests {
+    use super::*;
+
+    fn memory_tracer() -> (Tracer, Arc<Mutex<Recorder>>) {
+
+        // A recording tracer whose spans stay in memory; the test reads the
+        // OTLP payload from the shared recorder instead of exporting it.
+
+        let recorder = Arc::new(Mutex::new(Recorder {
+            endpoint: "memory".to_string(),
+            headers: Vec::new(),
+            service: SERVICE_NAME.to_string(),
+            trace_id: new_id(32),
+            spans: Vec::new(),
+            open: Vec::new(),
+        }));
+        (Tracer { recorder: Some(Arc::clone(&recorder)) }, recorder)
+    }
+
+    fn spans(recorder: &Mutex<Recorder>) -> Vec<Value> {
+        lock(recorder).payload()["resourceSpans"][0]["scopeSpans"][0]["spans"].as_array().unwrap().clone()
+    }
+
+    fn named<'a>(spans: &'a [Value], name: &str) -> &'a Value {
+        spans.iter().find(|s| s["name"] == name).unwrap()
+    }
+
+    fn attr<'a>(span: &'a Value, key: &str) -> &'a Value {
+        &span["attributes"].as_array().unwrap().iter().find(|a| a["key"] == key).unwrap()["value"]
+    }
+
+
+    #[test]
+    fn spans_nest_under_the_innermost_open_span() {
+        let (tracer, recorder) = memory_tracer();
+        {
+            let run = tracer.span("doxcer.run");
+            run.set("doxcer.command", "generate");
+            {
+                let file = tracer.span("doxcer.file");
+                file.set("doxcer.notebook", Path::new("nb/a.ipynb"));
+                drop(tracer.span("prompt-render"));
+                let call = tracer.span("api-call");
+                tracer.set_current("http.response.status_code", 200u16);
+                call.set("doxcer.cache_hit", false);
+            }
+            drop(tracer.span("doxcer.file"));
+            run.set("doxcer.files", 2usize);
+        }
+
+        let spans = spans(&recorder);
+        assert_eq!(spans.len(), 5);
+        let run = named(&spans, "doxcer.run");
+        let file = named(&spans, "doxcer.file");
+        assert!(run.get("parentSpanId").is_none());
+        assert_eq!(file["parentSpanId"], run["spanId"]);
+        assert_eq!(named(&spans, "prompt-render")["parentSpanId"], file["spanId"]);
+        assert_eq!(named(&spans, "api-call")["parentSpanId"], file["spanId"]);
+        assert_eq!(spans[4]["name"], "doxcer.file");
+        assert_eq!(spans[4]["parentSpanId"], run["spanId"]);
+        assert!(spans.iter().all(|s| s["traceId"] == run["traceId"]));
+        assert!(lock(&recorder).open.is_empty());
+    }
+
+
+    #[test]
+    fn encodes_kinds_attributes_and_status() {
+        let (tracer, recorder) = memory_tracer();
+        {
+            let run = tracer.span("doxcer.run");
+            run.set("doxcer.command", "verify");
+            run.set("doxcer.files", 3usize);
+            let call = tracer.span("api-call");
+            call.set("gen_ai.request.model", "gpt-4o");
+            call.set("doxcer.cache_hit", true);
+            call.fail("HTTP 429");
+        }
+
+        let spans = spans(&recorder);
+        let run = named(&spans, "doxcer.run");
+        let call = named(&spans, "api-call");
+        assert_eq!((run["kind"].as_i64(), call["kind"].as_i64()), (Some(1), Some(3)));
+        assert_eq!(attr(run, "doxcer.command"), &json!({ "stringValue": "verify" }));
+        assert_eq!(attr(run, "doxcer.files"), &json!({ "intValue": "3" }));
+        assert_eq!(attr(call, "gen_ai.request.model"), &json!({ "stringValue": "gpt-4o" }));
+        assert_eq!(attr(call, "doxcer.cache_hit"), &json!({ "boolValue": true }));
+        assert_eq!(run["status"], json!({ "code": 0 }));
+        assert_eq!(call["status"], json!({ "code": 2, "message": "HTTP 429" }));
+
+        let start: u128 = call["startTimeUnixNano"].as_str().unwrap().parse().unwrap();
+        let end: u128 = call["endTimeUnixNano"].as_str().unwrap().parse().unwrap();
+        assert!(end >= start);
+    }
+
+
+    #[test]
+    fn the_resource_names_the_service_and_version() {
+        let (tracer, recorder) = memory_tracer();
+        drop(tracer.span("doxcer.run"));
+        let payload = lock(&recorder).payload();
+        let resource = &payload["resourceSpans"][0]["resource"];
+        assert_eq!(attr(resource, "service.name"), &json!({ "stringValue": SERVICE_NAME }));
+        assert_eq!(attr(resource, "service.version"), &json!({ "stringValue": crate::version() }));
+        assert_eq!(payload["resourceSpans"][0]["scopeSpans"][0]["scope"]["name"], SERVICE_NAME);
+    }
+
+
+    #[test]
+    fn a_disabled_tracer_records_nothing() {
+        let tracer = Tracer::default();
+        let span = tracer.span("doxcer.run");
+        span.set("doxcer.files", 1usize);
+        tracer.set_current("doxcer.retry_count", 1usize);
+        assert!(span.recorder.is_none());
+        assert!(tracer.export().is_ok());
+    }
+}
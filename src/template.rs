@@ -0,0 +1,867 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Internal Modules
+use crate::config::{Settings, SettingsLayer};
+use crate::error::DoxcerError;
+use crate::frontmatter::{FrontMatterEntry, FrontMatterValue, parse_front_matter, split_front_matter};
+use crate::kind::SourceKind;
+use crate::language::{language_code, language_instruction};
+use crate::notebook::rendered_cells;
+use crate::provenance::content_hash;
+use crate::templating::{Record, Variables, render};
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const NOTEBOOK_HEADER: &str = "Hier is de Notebook.py:";
+pub const SQL_HEADER: &str = "Hier is het SQL-script:";
+pub const PIPELINE_HEADER: &str = "Hier is de Data Pipeline:";
+pub const SEMANTIC_MODEL_HEADER: &str = "Hier is het semantische model:";
+pub const MAX_INCLUDE_DEPTH: usize = 10;
+pub const REPETITION_WINDOW: usize = 200;
+pub const REPETITION_LIMIT: usize = 10;
+pub const CONTEXT_KEYS: [&str; 9] = ["notebook_name", "notebook_path", "model", "lang", "upstream_summaries", "child_notebooks", "run_stats", "context", "cells"];
+pub const FRONT_MATTER_KEYS: [&str; 11] = [
+    "model", "fallback_models", "max_output_tokens", "max_output_bytes",
+    "required_sections", "lang", "role_split_marker", "min_doxcer_version",
+    "context_files", "max_context_tokens", "optional_placeholders",
+];
+// Placeholder names announced for later versions; a template may list them in
+// `optional_placeholders` before this binary provides them.
+pub const RESERVED_PLACEHOLDERS: [&str; 1] = ["lineage"];
+// Older spellings that still render, as the placeholder they stand for.
+pub const DEPRECATED_PLACEHOLDERS: [(&str, &str); 3] = [
+    ("notebook", "notebook_name"),
+    ("path", "notebook_path"),
+    ("context_files", "context"),
+];
+pub const BUILTIN_PROMPT_TEMPLATE: &str = include_str!("../templates/prompt.md");
+pub const BUILTIN_SUMMARY_TEMPLATE: &str = include_str!("../templates/summary.md");
+pub const BUILTIN_NOTEBOOK_SUMMARY_TEMPLATE: &str = include_str!("../templates/notebook_summary.md");
+pub const BUILTIN_SQL_TEMPLATE: &str = include_str!("../templates/sql.md");
+pub const BUILTIN_PIPELINE_TEMPLATE: &str = include_str!("../templates/pipeline.md");
+pub const BUILTIN_SEMANTIC_MODEL_TEMPLATE: &str = include_str!("../templates/semantic_model.md");
+pub const BUILTIN_PARTIALS: [(&str, &str); 1] = [
+    ("markdown_tables.md", include_str!("../templates/partials/markdown_tables.md")),
+];
+// Every template compiled into doxcer, by the file name it overrides; see
+// `doxcer template show` and `doxcer template export`.
+pub const BUILTIN_TEMPLATES: [(&str, &str); 6] = [
+    ("prompt.md", BUILTIN_PROMPT_TEMPLATE),
+    ("summary.md", BUILTIN_SUMMARY_TEMPLATE),
+    ("notebook_summary.md", BUILTIN_NOTEBOOK_SUMMARY_TEMPLATE),
+    ("sql.md", BUILTIN_SQL_TEMPLATE),
+    ("pipeline.md", BUILTIN_PIPELINE_TEMPLATE),
+    ("semantic_model.md", BUILTIN_SEMANTIC_MODEL_TEMPLATE),
+];
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub path: PathBuf,
+    pub body: String,
+    pub settings: SettingsLayer,
+    pub min_doxcer_version: Option<String>,
+    pub optional_placeholders: Vec<String>,
+    pub warnings: Vec<String>,
+    pub includes: Vec<String>,
+    // The templates the body extends or imports macros from, by the name it
+    // gives them, with their own includes expanded.
+    pub layouts: Vec<(String, String)>,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceholderKind {
+    Provided,
+    Reserved,
+    Deprecated(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prompt {
+    pub instructions: Option<String>,
+    pub input: String,
+}
+
+
+// ====================================================
+//  Template Loading
+// ====================================================
+pub fn load_template(path: &Path) -> Result<Template, DoxcerError> {
+
+    // Reads and parses a prompt template from disk, expanding its includes.
+    //
+    // # Description
+    // After the front-matter is parsed, every `{{> name}}` in the body is replaced
+    // by the named partial and the templates it extends or imports are loaded
+    // (see [`expand_includes`]). The template hash covers the front-matter, the
+    // fully expanded body and those templates, so editing any of them changes it.
+
+    let raw = fs::read_to_string(path)
+        .map_err(|e| DoxcerError::Config(format!("Failed to read {}: {e}", path.display())))?;
+    let mut template = parse_template(path, &raw)?;
+
+    let template_dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let mut chain = vec![include_id(path)];
+    let mut includes = Vec::new();
+    let mut layouts = Vec::new();
+    template.body = expand_includes(&template.body, &template_dir, &template_dir, &mut chain, &mut includes, &mut layouts)?;
+    template.includes = includes;
+    template.layouts = layouts;
+
+    template.hash = expanded_hash(&raw, &template.body, &template.layouts);
+    Ok(template)
+}
+
+
+pub fn load_prompt_template(path: Option<&Path>) -> Result<Template, DoxcerError> {
+
+    // Loads the prompt template, falling back to the built-in one.
+    //
+    // # Description
+    // `path` is the template `resolve_template` found, if any; without one the
+    // prompt template compiled into doxcer is used, reported as
+    // `builtin:prompt.md`, so doxcer works without any setup. Its hash is
+    // computed as for a file, so exporting it unchanged (`doxcer template
+    // export`) does not make existing documents stale.
+
+    if let Some(path) = path {
+        return load_template(path);
+    }
+    let mut template = parse_template(Path::new("builtin:prompt.md"), BUILTIN_PROMPT_TEMPLATE)?;
+    template.hash = expanded_hash(BUILTIN_PROMPT_TEMPLATE, &template.body, &[]);
+    Ok(template)
+}
+
+
+pub fn load_summary_template(path: &Path) -> Result<Template, DoxcerError> {
+
+    // Loads the workspace summary template, falling back to the built-in one.
+    //
+    // # Description
+    // A `summary.md` next to the prompt template wins; without one the summary
+    // template compiled into doxcer is used, reported as `builtin:summary.md`.
+
+    if path.exists() {
+        return load_template(path);
+    }
+    parse_template(Path::new("builtin:summary.md"), BUILTIN_SUMMARY_TEMPLATE)
+}
+
+
+pub fn load_notebook_summary_template(path: &Path) -> Result<Template, DoxcerError> {
+
+    // Loads the template for `--mode summary`, falling back to the built-in one.
+    //
+    // # Description
+    // Like [`load_summary_template`]: a `notebook_summary.md` next to the prompt
+    // template wins, otherwise `builtin:notebook_summary.md` is used.
+
+    if path.exists() {
+        return load_template(path);
+    }
+    parse_template(Path::new("builtin:notebook_summary.md"), BUILTIN_NOTEBOOK_SUMMARY_TEMPLATE)
+}
+
+
+pub fn load_sql_template(path: &Path) -> Result<Template, DoxcerError> {
+
+    // Loads the template for SQL scripts (`--kind sql`), falling back to the
+    // built-in one.
+    //
+    // # Description
+    // Like [`load_summary_template`]: a `sql.md` next to the prompt template
+    // wins, otherwise `builtin:sql.md` is used.
+
+    if path.exists() {
+        return load_template(path);
+    }
+    parse_template(Path::new("builtin:sql.md"), BUILTIN_SQL_TEMPLATE)
+}
+
+
+pub fn load_pipeline_template(path: &Path) -> Result<Template, DoxcerError> {
+
+    // Loads the template for Data Pipelines (`--kind pipeline`): a
+    // `pipeline.md` next to the prompt template, else `builtin:pipeline.md`.
+
+    if path.exists() {
+        return load_template(path);
+    }
+    parse_template(Path::new("builtin:pipeline.md"), BUILTIN_PIPELINE_TEMPLATE)
+}
+
+
+pub fn load_semantic_model_template(path: &Path) -> Result<Template, DoxcerError> {
+
+    // Loads the template for semantic models (`--kind semantic-model`): a
+    // `semantic_model.md` next to the prompt template, else
+    // `builtin:semantic_model.md`.
+
+    if path.exists() {
+        return load_template(path);
+    }
+    parse_template(Path::new("builtin:semantic_model.md"), BUILTIN_SEMANTIC_MODEL_TEMPLATE)
+}
+
+
+pub fn language_variant(path: &Path, lang: Option<&str>) -> PathBuf {
+
+    // Returns the variant of a template for a `lang` setting, if it exists.
+    //
+    // # Description
+    // For `templates/prompt.md` and `lang = "en-GB"` this is
+    // `templates/prompt.en-gb.md`, else `templates/prompt.en.md`; without
+    // either (or without `lang`) the template itself.
+
+    let (Some(lang), Some(stem)) = (lang, path.file_stem().and_then(|s| s.to_str())) else {
+        return path.to_path_buf();
+    };
+    let lang = lang.trim().to_lowercase().replace('_', "-");
+    let mut tags = vec![lang.clone()];
+    tags.extend(language_code(&lang).map(str::to_string));
+    tags.into_iter()
+        .filter(|tag| !tag.is_empty())
+        .map(|tag| path.with_file_name(format!("{stem}.{tag}.md")))
+        .find(|variant| variant.is_file())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+
+pub fn builtin_template(name: &str) -> Option<(&'static str, &'static str)> {
+
+    // Returns `(file name, contents)` of a built-in template or partial, by its
+    // file name with or without `.md`.
+
+    let name = name.strip_suffix(".md").unwrap_or(name);
+    BUILTIN_TEMPLATES
+        .iter()
+        .chain(BUILTIN_PARTIALS.iter())
+        .find(|(file, _)| file.strip_suffix(".md") == Some(name))
+        .copied()
+}
+
+
+fn expanded_hash(raw: &str, body: &str, layouts: &[(String, String)]) -> String {
+
+    // The template hash: the front-matter, the fully expanded body and the
+    // templates it extends or imports.
+
+    let (front, _) = split_front_matter(raw);
+    let mut text = format!("{}\n{}", front.unwrap_or_default(), body);
+    for (name, layout) in layouts {
+        text.push_str(&format!("\n{name}\n{layout}"));
+    }
+    content_hash(text.as_bytes())
+}
+
+
+pub fn parse_template(path: &Path, raw: &str) -> Result<Template, DoxcerError> {
+
+    // Splits a template into its front-matter settings and its prompt body.
+    //
+    // # Description
+    // The optional front-matter may set `model`, `fallback_models`, `max_output_tokens`,
+    // `max_output_bytes`, `required_sections`, `lang`, `role_split_marker`,
+    // `context_files`, `max_context_tokens`, `min_doxcer_version` and
+    // `optional_placeholders`.
+    // Unknown keys are kept as warnings (naming the template and the key) rather
+    // than failing the run. A `min_doxcer_version` newer than this binary is a
+    // hard error. Optional placeholders this binary does not provide render as
+    // empty strings, with one warning naming all of them.
+    //
+    // # Parameters
+    // * `path` – The template path, used in messages.
+    // * `raw` – The template contents.
+    //
+    // # Returns
+    // * `Ok(Template)` with the body, settings layer and any warnings.
+    // * `Err(DoxcerError)` on malformed front-matter, wrongly typed values or a too-old binary.
+
+    let (front, body) = split_front_matter(raw);
+    let mut template = Template {
+        path: path.to_path_buf(),
+        body: body.to_string(),
+        settings: SettingsLayer::default(),
+        min_doxcer_version: None,
+        optional_placeholders: Vec::new(),
+        warnings: Vec::new(),
+        includes: Vec::new(),
+        layouts: Vec::new(),
+        hash: content_hash(raw.as_bytes()),
+    };
+
+    let Some(front) = front else {
+        return Ok(template);
+    };
+
+    let entries = parse_front_matter(front)
+        .map_err(|e| DoxcerError::Config(format!("{}: invalid front-matter, {e}", path.display())))?;
+
+    for FrontMatterEntry { key, value, line } in entries {
+        let known = apply_front_matter_entry(
+            &mut template.settings,
+            &mut template.min_doxcer_version,
+            &mut template.optional_placeholders,
+            &key,
+            value,
+        )
+        .map_err(|e| DoxcerError::Config(format!("{}:{line}: {e}", path.display())))?;
+        if !known {
+            template.warnings.push(format!(
+                "{}:{line}: unknown template front-matter key `{key}` (ignored)",
+                path.display()
+            ));
+        }
+    }
+
+    if let Some(required) = &template.min_doxcer_version {
+        let running = crate::version();
+        if !version_at_least(running, required)? {
+            return Err(DoxcerError::Config(format!(
+                "{} requires doxcer >= {required}, but this is doxcer {running}; please upgrade",
+                path.display()
+            )));
+        }
+    }
+
+    let unavailable = unavailable_placeholders(&template.optional_placeholders);
+    if !unavailable.is_empty() {
+        template.warnings.push(format!(
+            "{}: doxcer {} does not provide the optional placeholders {}; they render as empty text",
+            path.display(),
+            crate::version(),
+            unavailable.iter().map(|name| format!("`{{{{{name}}}}}`")).collect::<Vec<_>>().join(", ")
+        ));
+    }
+
+    Ok(template)
+}
+
+
+pub fn apply_front_matter_entry(
+    settings: &mut SettingsLayer,
+    min_doxcer_version: &mut Option<String>,
+    optional_placeholders: &mut Vec<String>,
+    key: &str,
+    value: FrontMatterValue,
+) -> Result<bool, DoxcerError> {
+
+    // Applies one template front-matter entry to the settings layer.
+    //
+    // # Returns
+    // * `Ok(true)` when the key is known, `Ok(false)` for an unknown key.
+    // * `Err(DoxcerError)` when the value has the wrong type.
+
+    let err = |expected: &str| DoxcerError::Config(format!("`{key}` must be {expected}"));
+    match (key, value) {
+        ("model", FrontMatterValue::Scalar(v)) => settings.model = Some(v),
+        ("lang", FrontMatterValue::Scalar(v)) => settings.lang = Some(v),
+        ("role_split_marker", FrontMatterValue::Scalar(v)) => settings.role_split_marker = Some(v),
+        ("min_doxcer_version", FrontMatterValue::Scalar(v)) => *min_doxcer_version = Some(v),
+        ("max_output_tokens", FrontMatterValue::Scalar(v)) => {
+            settings.max_output_tokens = Some(v.parse().map_err(|_| err("a positive integer"))?);
+        }
+        ("max_output_bytes", FrontMatterValue::Scalar(v)) => {
+            settings.max_output_bytes = Some(v.parse().map_err(|_| err("a positive integer"))?);
+        }
+        ("max_context_tokens", FrontMatterValue::Scalar(v)) => {
+            settings.max_context_tokens = Some(v.parse().map_err(|_| err("a positive integer"))?);
+        }
+        ("required_sections", FrontMatterValue::List(v)) => settings.required_sections = Some(v),
+        ("required_sections", _) => return Err(err("a list")),
+        ("fallback_models", FrontMatterValue::List(v)) => settings.fallback_models = Some(v),
+        ("fallback_models", _) => return Err(err("a list")),
+        ("context_files", FrontMatterValue::List(v)) => settings.context_files = Some(v),
+        ("context_files", _) => return Err(err("a list")),
+        ("optional_placeholders", FrontMatterValue::List(v)) => {
+            if let Some(bad) = v.iter().find(|name| !is_placeholder_name(name)) {
+                return Err(DoxcerError::Config(format!("`{key}` lists {bad:?}, which is not a placeholder name")));
+            }
+            *optional_placeholders = v;
+        }
+        ("optional_placeholders", _) => return Err(err("a list")),
+        ("model" | "lang" | "role_split_marker" | "min_doxcer_version" | "max_output_tokens" | "max_output_bytes" | "max_context_tokens", _) => {
+            return Err(err("a single value"));
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+
+pub fn version_at_least(running: &str, required: &str) -> Result<bool, DoxcerError> {
+
+    // Compares two dotted version numbers (`major.minor.patch`, missing parts are 0).
+
+    let parse = |v: &str| -> Result<Vec<u64>, DoxcerError> {
+        v.trim()
+            .trim_start_matches('v')
+            .split('.')
+            .map(|p| p.parse::<u64>().map_err(|_| DoxcerError::Config(format!("Invalid version number: {v:?}"))))
+            .collect()
+    };
+    let (mut a, mut b) = (parse(running)?, parse(required)?);
+    let len = a.len().max(b.len());
+    a.resize(len, 0);
+    b.resize(len, 0);
+    Ok(a >= b)
+}
+
+
+// ====================================================
+//  Includes
+// ====================================================
+pub fn expand_includes(
+    body: &str,
+    current_dir: &Path,
+    template_dir: &Path,
+    chain: &mut Vec<String>,
+    includes: &mut Vec<String>,
+    layouts: &mut Vec<(String, String)>,
+) -> Result<String, DoxcerError> {
+
+    // Recursively replaces `{{> name}}` and `{% include "name" %}` directives
+    // with the contents of the partial, and loads the templates named by
+    // `{% extends "name" %}` and `{% import "name" as ... %}`.
+    //
+    // # Description
+    // A partial is resolved relative to the directory of the including file, then
+    // relative to the root template directory, and finally against the built-in
+    // partials. Front-matter in a partial is ignored. An `extends` or `import`
+    // tag stays in the body for Tera; the template it names is resolved the same
+    // way, expanded in turn and added to `layouts`. Include cycles and chains
+    // deeper than `MAX_INCLUDE_DEPTH` are errors that show the include chain.
+    //
+    // # Parameters
+    // * `body` – The text to expand.
+    // * `current_dir` – Directory of the file `body` came from.
+    // * `template_dir` – Directory of the root template.
+    // * `chain` – The include chain so far (root template first).
+    // * `includes` – Collects every resolved partial, in order of inclusion.
+    // * `layouts` – Collects the extended and imported templates by name.
+    //
+    // # Returns
+    // * `Ok(String)` with all includes expanded.
+    // * `Err(DoxcerError)` if a partial is missing, cyclic or nested too deeply.
+
+    let mut out = String::with_capacity(body.len());
+    let mut rest = body;
+
+    while let Some(start) = [rest.find("{{"), rest.find("{%")].into_iter().flatten().min() {
+        let close = if rest[start..].starts_with("{{") { "}}" } else { "%}" };
+        let Some(len) = rest[start..].find(close) else {
+            break;
+        };
+        let inner = rest[start + 2..start + len].trim_matches('-').trim();
+        let quoted = |keyword: &str| {
+            let name = inner.strip_prefix(keyword)?.split_whitespace().next()?;
+            name.strip_prefix('"').and_then(|n| n.strip_suffix('"')).map(str::to_string)
+        };
+        let (name, layout) = match close {
+            "}}" => (inner.strip_prefix('>').map(|name| name.trim().to_string()), false),
+            _ => match quoted("include") {
+                Some(name) => (Some(name), false),
+                None => (quoted("extends").or_else(|| quoted("import")), true),
+            },
+        };
+        let Some(name) = name else {
+            out.push_str(&rest[..start + len + 2]);
+            rest = &rest[start + len + 2..];
+            continue;
+        };
+        let name = name.as_str();
+        out.push_str(&rest[..if layout { start + len + 2 } else { start }]);
+        rest = &rest[start + len + 2..];
+
+        let (id, dir, raw) = resolve_partial(name, current_dir, template_dir)?;
+        if chain.contains(&id) {
+            return Err(DoxcerError::Config(format!("Template include cycle: {} -> {id}", chain.join(" -> "))));
+        }
+        if chain.len() > MAX_INCLUDE_DEPTH {
+            return Err(DoxcerError::Config(format!(
+                "Template include depth limit ({MAX_INCLUDE_DEPTH}) exceeded: {} -> {id}",
+                chain.join(" -> ")
+            )));
+        }
+
+        chain.push(id.clone());
+        includes.push(id);
+        let (_, partial_body) = split_front_matter(&raw);
+        let expanded = expand_includes(partial_body.trim_end_matches('\n'), &dir, template_dir, chain, includes, layouts)?;
+        chain.pop();
+        match layout {
+            true if !layouts.iter().any(|(known, _)| known == name) => layouts.push((name.to_string(), expanded)),
+            true => {}
+            false => out.push_str(&expanded),
+        }
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+
+pub fn resolve_partial(name: &str, current_dir: &Path, template_dir: &Path) -> Result<(String, PathBuf, String), DoxcerError> {
+
+    // Locates a partial and returns `(identifier, directory, contents)`.
+
+    for candidate in [current_dir.join(name), template_dir.join(name)] {
+        if candidate.is_file() {
+            let raw = fs::read_to_string(&candidate)
+                .map_err(|e| DoxcerError::Config(format!("Failed to read partial {}: {e}", candidate.display())))?;
+            let dir = candidate.parent().unwrap_or(Path::new(".")).to_path_buf();
+            return Ok((include_id(&candidate), dir, raw));
+        }
+    }
+
+    BUILTIN_PARTIALS
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(builtin, raw)| (format!("builtin:{builtin}"), template_dir.to_path_buf(), raw.to_string()))
+        .ok_or_else(|| DoxcerError::Config(format!(
+            "Template include `{{{{> {name}}}}}` not found (looked in {} and {}, and the built-in partials)",
+            current_dir.display(),
+            template_dir.display()
+        )))
+}
+
+
+pub fn include_id(path: &Path) -> String {
+
+    // Identifies a template file in include chains, independent of how its path was spelled.
+
+    fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string()
+}
+
+
+// ====================================================
+//  Placeholders
+// ====================================================
+pub fn prompt_context(notebook_path: &Path, settings: &Settings, upstream_summaries: &str, child_notebooks: &str, run_stats: &str, context_files: &str) -> BTreeMap<String, String> {
+
+    // Builds the variables available to `{{placeholder}}` substitution; their
+    // names are listed in `CONTEXT_KEYS` for `doxcer template check`.
+    // `upstream_summaries` is only filled in batch runs (`verify --fix`);
+    // `child_notebooks` lists the notebooks this one runs (see `runrefs`);
+    // `run_stats` summarises the Fabric run history (`--fabric-runs`);
+    // `context` holds the context files (see `contextfiles`). Template
+    // variables (`--var`, `[vars]`) are added under their own names; the
+    // built-in placeholders win over a variable of the same name.
+
+    let mut context = settings.vars.value.clone();
+    context.insert(
+        "notebook_name".to_string(),
+        notebook_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+    );
+    context.insert("notebook_path".to_string(), notebook_path.display().to_string());
+    context.insert("model".to_string(), settings.model.value.clone());
+    context.insert("lang".to_string(), settings.lang.value.clone().unwrap_or_default());
+    context.insert("upstream_summaries".to_string(), upstream_summaries.to_string());
+    context.insert("child_notebooks".to_string(), child_notebooks.to_string());
+    context.insert("run_stats".to_string(), run_stats.to_string());
+    context.insert("context".to_string(), context_files.to_string());
+    context
+}
+
+
+pub fn placeholder_kind(name: &str) -> Option<PlaceholderKind> {
+
+    // Looks a placeholder name up in the registry: provided by this binary
+    // (`CONTEXT_KEYS`), reserved for a later version, or a deprecated alias.
+
+    if CONTEXT_KEYS.contains(&name) {
+        return Some(PlaceholderKind::Provided);
+    }
+    if RESERVED_PLACEHOLDERS.contains(&name) {
+        return Some(PlaceholderKind::Reserved);
+    }
+    DEPRECATED_PLACEHOLDERS
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map(|(_, current)| PlaceholderKind::Deprecated(current))
+}
+
+
+pub fn is_placeholder_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+
+pub fn unavailable_placeholders(optional: &[String]) -> Vec<&str> {
+
+    // Returns the optional placeholders this binary cannot fill in.
+
+    optional
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !matches!(placeholder_kind(name), Some(PlaceholderKind::Provided | PlaceholderKind::Deprecated(_))))
+        .collect()
+}
+
+
+pub fn render_placeholders(text: &str, context: &BTreeMap<String, String>) -> Result<String, DoxcerError> {
+
+    // Renders a template body with `context` and no lists, see
+    // `templating::render`. A deprecated alias (`DEPRECATED_PLACEHOLDERS`)
+    // renders as the placeholder it stands for.
+
+    render(text, &Variables { values: context, lists: &BTreeMap::new(), aliases: &DEPRECATED_PLACEHOLDERS, layouts: &[] })
+}
+
+
+pub fn cell_records(notebook: &str) -> Vec<Record> {
+
+    // Builds the `cells` template variable from the notebook as it is sent:
+    // per cell its `index`, `kind`, `language`, `title` (the first comment or
+    // heading) and `text`.
+
+    rendered_cells(notebook)
+        .into_iter()
+        .map(|cell| {
+            let title = cell
+                .source
+                .lines()
+                .map(str::trim)
+                .find(|l| !l.is_empty())
+                .and_then(|l| l.strip_prefix('#').or_else(|| l.strip_prefix("--")))
+                .map(|l| l.trim_start_matches(['#', ' ']).trim().to_string())
+                .unwrap_or_default();
+            Record::from([
+                ("index".to_string(), cell.index.to_string()),
+                ("kind".to_string(), cell.kind),
+                ("language".to_string(), cell.language.unwrap_or_default()),
+                ("title".to_string(), title),
+                ("text".to_string(), cell.source),
+            ])
+        })
+        .collect()
+}
+
+
+// ====================================================
+//  Prompt Rendering
+// ====================================================
+pub fn render_prompt(
+    template_body: &str,
+    layouts: &[(String, String)],
+    notebook: &str,
+    context: &BTreeMap<String, String>,
+    settings: &Settings,
+    kind: SourceKind,
+) -> Result<Prompt, DoxcerError> {
+
+    // Builds the request prompt from the template body and the notebook contents.
+    //
+    // # Description
+    // The (include-expanded) template body is rendered with Tera first, with
+    // `layouts` as the templates it extends or imports, so partials can use
+    // the same variables; `cells` lists the cells of
+    // `notebook` (see [`cell_records`]), after the prompt pipeline has
+    // redacted them.
+    // The template is sent as the `instructions` (system/developer role) and the
+    // notebook as user input, so text in a notebook cannot pose as part of the
+    // instructions. When a `role_split_marker` occurs in the template, only the
+    // part before it becomes the instructions and the part after it is sent
+    // together with the notebook. With `single_input` (`--single-input`) the
+    // whole template and the notebook are sent as one input, as doxcer used to.
+    // A configured `lang` adds an explicit language instruction (see
+    // [`language_instruction`]). `kind` and `lang` pick the header above the
+    // source.
+
+    let lists = BTreeMap::from([("cells".to_string(), cell_records(notebook))]);
+    let template_body = render(template_body, &Variables { values: context, lists: &lists, aliases: &DEPRECATED_PLACEHOLDERS, layouts })?;
+
+    let split = settings.role_split_marker.value.as_deref().and_then(|marker| template_body.split_once(marker));
+    let (instructions, user_part) = match (split, settings.single_input.value) {
+        (Some((before, after)), _) => (Some(before.trim().to_string()), after.trim().to_string()),
+        (None, true) => (None, template_body.clone()),
+        (None, false) => (Some(template_body.trim().to_string()), String::new()),
+    };
+
+    let language = settings.lang.value.as_deref().map(language_instruction);
+
+    let (instructions, user_part) = match (instructions, language) {
+        (Some(i), Some(l)) => (Some(format!("{i}\n\n{l}")), user_part),
+        (None, Some(l)) => (None, format!("{user_part}\n\n{l}")),
+        (i, None) => (i, user_part),
+    };
+
+    let header = kind.header(settings.lang.value.as_deref());
+    let input = match user_part.is_empty() {
+        true => format!("{header}\n\n{notebook}"),
+        false => format!("{user_part}\n\n{header}\n\n{notebook}"),
+    };
+    Ok(Prompt { instructions, input })
+}
+
+
+// ====================================================
+//  Output Validation
+// ====================================================
+pub fn missing_sections(markdown: &str, required: &[String]) -> Vec<String> {
+
+    // Returns the required sections that do not appear as a heading in the output.
+    //
+    // # Description
+    // Headings are compared case-insensitively with emphasis markers removed, so
+    // `## **Functioneel ontwerp**` satisfies `Functioneel ontwerp`.
+
+    let headings: Vec<String> = markdown
+        .lines()
+        .filter_map(|line| {
+            let trimmed = line.trim_start();
+            let text = trimmed.trim_start_matches('#');
+            (text.len() < trimmed.len() && text.starts_with(' ')).then(|| normalize_heading(text))
+        })
+        .collect();
+
+    required
+        .iter()
+        .filter(|section| !headings.contains(&normalize_heading(section)))
+        .cloned()
+        .collect()
+}
+
+
+pub fn repeated_window(markdown: &str, window: usize, limit: usize) -> Option<(String, usize)> {
+
+    // Detects pathological repetition in generated output.
+    //
+    // # Description
+    // Takes the `window`-character text starting at every line and counts how
+    // often each one occurs. Runaway outputs repeat whole sections verbatim, so
+    // their windows line up on line starts; normal documents never repeat a
+    // 200-character stretch more than a handful of times.
+    //
+    // # Returns
+    // * `Some((window, count))` for the most repeated window if it occurs more than `limit` times.
+    // * `None` otherwise.
+
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut start = 0;
+    for line in markdown.split_inclusive('\n') {
+        let rest = &markdown[start..];
+        start += line.len();
+        let Some((end, _)) = rest.char_indices().nth(window) else {
+            break;
+        };
+        let text = &rest[..end];
+        if !text.trim().is_empty() {
+            *counts.entry(text).or_default() += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .filter(|(_, count)| *count > limit)
+        .map(|(text, count)| (text.to_string(), count))
+}
+
+
+fn normalize_heading(text: &str) -> String {
+
+    // Normalizes heading text for comparison.
+
+    text.replace(['*', '_', '`'], "").trim().to_lowercase()
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn templates(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, text) in files {
+            fs::write(dir.path().join(name), text).unwrap();
+        }
+        dir
+    }
+
+
+    fn load_error(dir: &tempfile::TempDir) -> String {
+        let error = load_template(&dir.path().join("prompt.md")).unwrap_err();
+        assert!(matches!(error, DoxcerError::Config(_)), "{error}");
+        error.to_string()
+    }
+
+
+    #[test]
+    fn a_partial_that_includes_itself_is_a_cycle_error() {
+        let dir = templates(&[("prompt.md", "Start\n{{> self.md}}\n"), ("self.md", "Again {{> self.md}}\n")]);
+        let error = load_error(&dir);
+        let id = |name: &str| include_id(&dir.path().join(name));
+        assert_eq!(error, format!("Template include cycle: {} -> {} -> {}", id("prompt.md"), id("self.md"), id("self.md")));
+    }
+
+
+    #[test]
+    fn partials_that_include_each_other_are_a_cycle_error() {
+        let dir = templates(&[
+            ("prompt.md", "{% include \"a.md\" %}\n"),
+            ("a.md", "A {{> b.md}}\n"),
+            ("b.md", "B {% include \"a.md\" %}\n"),
+        ]);
+        let error = load_error(&dir);
+        let id = |name: &str| include_id(&dir.path().join(name));
+        assert_eq!(error, format!("Template include cycle: {} -> {} -> {} -> {}", id("prompt.md"), id("a.md"), id("b.md"), id("a.md")));
+    }
+
+
+    #[test]
+    fn a_template_that_extends_itself_is_a_cycle_error() {
+        let dir = templates(&[("prompt.md", "{% extends \"prompt.md\" %}\n")]);
+        assert!(load_error(&dir).starts_with("Template include cycle: "));
+    }
+
+
+    #[test]
+    fn a_chain_deeper_than_the_limit_is_an_error() {
+        let mut files: Vec<(String, String)> = (0..=MAX_INCLUDE_DEPTH).map(|i| (format!("p{i}.md"), format!("{{{{> p{}.md}}}}", i + 1))).collect();
+        files.push((format!("p{}.md", MAX_INCLUDE_DEPTH + 1), "end".to_string()));
+        files.push(("prompt.md".to_string(), "{{> p0.md}}".to_string()));
+        let files: Vec<(&str, &str)> = files.iter().map(|(name, text)| (name.as_str(), text.as_str())).collect();
+        let error = load_error(&templates(&files));
+        assert!(error.starts_with(&format!("Template include depth limit ({MAX_INCLUDE_DEPTH}) exceeded: ")), "{error}");
+    }
+
+
+    #[test]
+    fn a_partial_included_twice_is_not_a_cycle() {
+        let dir = templates(&[("prompt.md", "{{> part.md}} and {{> part.md}}\n"), ("part.md", "---\nmodel: x\n---\nPart\n")]);
+        let template = load_template(&dir.path().join("prompt.md")).unwrap();
+        assert_eq!(template.body.trim_end(), "Part and Part");
+        assert_eq!(template.includes.len(), 2);
+    }
+
+
+    #[test]
+    fn a_missing_partial_names_where_it_was_looked_for() {
+        let dir = templates(&[("prompt.md", "{{> missing.md}}\n")]);
+        let error = load_error(&dir);
+        assert!(error.starts_with("Template include `{{> missing.md}}` not found"), "{error}");
+    }
+}
@@ -0,0 +1,382 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::BTreeSet;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Internal Modules
+use crate::config::SettingsLayer;
+use crate::error::DoxcerError;
+use crate::frontmatter::{FrontMatterEntry, parse_front_matter, split_front_matter};
+use crate::template::{
+    CONTEXT_KEYS, FRONT_MATTER_KEYS, MAX_INCLUDE_DEPTH, PlaceholderKind, RESERVED_PLACEHOLDERS, apply_front_matter_entry,
+    include_id, placeholder_kind, resolve_partial, unavailable_placeholders, version_at_least,
+};
+use crate::templating::{Reference, check_syntax, references};
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub severity: Severity,
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}:{}:{}: {severity}: {}", self.file, self.line, self.column, self.message)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TemplateChecker {
+    pub problems: Vec<Problem>,
+    pub files: usize,
+    visited: BTreeSet<String>,
+    // Optional placeholders of the files on the current include chain.
+    optional: Vec<String>,
+    // Template variable names (`[vars]`, `--var`) that count as placeholders.
+    pub vars: Vec<String>,
+}
+
+
+// ====================================================
+//  Checking
+// ====================================================
+impl TemplateChecker {
+
+    pub fn check_dir(&mut self, dir: &Path) -> Result<(), DoxcerError> {
+
+        // Checks every `.md` file below a template directory, in path order.
+        //
+        // # Description
+        // Includes are resolved against `dir` as the root template directory, like
+        // they are for the prompt template in it. A partial that is reached through
+        // an include is only checked once.
+
+        for path in markdown_files(dir)? {
+            self.check_file(&path, dir)?;
+        }
+        Ok(())
+    }
+
+
+    pub fn check_file(&mut self, path: &Path, template_dir: &Path) -> Result<(), DoxcerError> {
+
+        // Checks one template file and every partial it includes.
+
+        let raw = fs::read_to_string(path)
+            .map_err(|e| DoxcerError::Input(format!("Failed to read {}: {e}", path.display())))?;
+        let current_dir = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new(".")).to_path_buf();
+        let mut chain = vec![include_id(path)];
+        self.check_text(&include_id(path), &path.display().to_string(), &raw, &current_dir, template_dir, &mut chain);
+        Ok(())
+    }
+
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.problems.iter().filter(|p| p.severity == severity).count()
+    }
+
+
+    fn check_text(&mut self, id: &str, file: &str, raw: &str, current_dir: &Path, template_dir: &Path, chain: &mut Vec<String>) {
+
+        // Checks the front-matter, the Tera syntax and the placeholders and
+        // includes of one file.
+
+        if !self.visited.insert(id.to_string()) {
+            return;
+        }
+        self.files += 1;
+
+        let (front, body) = split_front_matter(raw);
+        let body_offset = raw.len() - body.len();
+        let mut settings = SettingsLayer::default();
+        let inherited = self.optional.len();
+        if let Some(front) = front {
+            let mut optional = Vec::new();
+            self.check_front_matter(file, front, &mut settings, &mut optional);
+            self.optional.extend(optional);
+        } else if raw.starts_with("---\n") || raw.starts_with("---\r\n") {
+            self.report(Severity::Warning, file, 1, 1, "front-matter block is not closed with `---`; it is sent as part of the prompt");
+        }
+        if let Some(marker) = &settings.role_split_marker
+            && !body.contains(marker.as_str())
+        {
+            self.report(Severity::Warning, file, 1, 1, &format!("role_split_marker {marker:?} does not occur in the template body"));
+        }
+
+        if let Err((message, offset)) = check_syntax(body) {
+            let (line, column) = location(raw, body_offset + offset);
+            self.report(Severity::Error, file, line, column, &message);
+            self.optional.truncate(inherited);
+            return;
+        }
+        for reference in references(body) {
+            match reference {
+                Reference::Include { name, offset } => {
+                    let at = location(raw, body_offset + offset);
+                    self.check_include(&name, file, at, current_dir, template_dir, chain);
+                }
+                Reference::Placeholder { name, offset } => {
+                    let (line, column) = location(raw, body_offset + offset);
+                    self.check_placeholder(&name, file, line, column);
+                }
+            }
+        }
+        self.optional.truncate(inherited);
+    }
+
+
+    fn check_placeholder(&mut self, inner: &str, file: &str, line: usize, column: usize) {
+
+        // Checks one placeholder name against the registry, the template
+        // variables and the environment.
+
+        if let Some(var) = inner.strip_prefix("env:") {
+            if var.is_empty() || !var.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                self.report(Severity::Error, file, line, column, &format!("invalid environment variable name in `{{{{{inner}}}}}`"));
+            } else if env::var(var).is_err() {
+                self.report(Severity::Warning, file, line, column, &format!("`{{{{{inner}}}}}` refers to {var}, which is not set here"));
+            }
+            return;
+        }
+        match placeholder_kind(inner) {
+            Some(PlaceholderKind::Provided) => {}
+            Some(PlaceholderKind::Deprecated(current)) => self.report(Severity::Warning, file, line, column, &format!(
+                "`{{{{{inner}}}}}` is deprecated; use `{{{{{current}}}}}`"
+            )),
+            _ if self.optional.iter().any(|name| name == inner) => {}
+            _ if self.vars.iter().any(|name| name == inner) => {}
+            Some(PlaceholderKind::Reserved) => self.report(Severity::Error, file, line, column, &format!(
+                "`{{{{{inner}}}}}` is not provided by doxcer {}; list it in `optional_placeholders` to render it empty here",
+                crate::version()
+            )),
+            None => match closest(inner, &CONTEXT_KEYS) {
+                Some(k) => self.report(Severity::Error, file, line, column, &format!(
+                    "unknown placeholder `{{{{{inner}}}}}`; did you mean `{{{{{k}}}}}`? known placeholders: {}, env:NAME",
+                    CONTEXT_KEYS.join(", ")
+                )),
+                None => self.report(Severity::Warning, file, line, column, &format!(
+                    "`{{{{{inner}}}}}` is not a built-in placeholder; pass it with `--var {inner}=...` or set it under [vars] in doxcer.toml"
+                )),
+            },
+        }
+    }
+
+
+    fn check_front_matter(&mut self, file: &str, front: &str, settings: &mut SettingsLayer, optional: &mut Vec<String>) {
+
+        // Checks the keys and value types of a front-matter block.
+        //
+        // # Description
+        // Each optional placeholder is looked up in the placeholder registry:
+        // names this binary provides or has reserved pass, deprecated aliases
+        // and unregistered names are warnings. The ones that render empty here
+        // are reported together, the way `generate` warns about them.
+
+        let entries = match parse_front_matter(front) {
+            Ok(entries) => entries,
+            Err(e) => {
+                let e = e.to_string();
+                let line = e
+                    .strip_prefix("line ")
+                    .and_then(|rest| rest.split(':').next())
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(1);
+                self.report(Severity::Error, file, line, 1, &format!("invalid front-matter, {e}"));
+                return;
+            }
+        };
+
+        let mut min_version = None;
+        for FrontMatterEntry { key, value, line } in entries {
+            match apply_front_matter_entry(settings, &mut min_version, optional, &key, value) {
+                Ok(true) => {}
+                Ok(false) => {
+                    let hint = closest(&key, &FRONT_MATTER_KEYS).map(|k| format!("; did you mean `{k}`?")).unwrap_or_default();
+                    self.report(Severity::Warning, file, line, 1, &format!("unknown front-matter key `{key}` (ignored){hint}"));
+                }
+                Err(e) => self.report(Severity::Error, file, line, 1, &e.to_string()),
+            }
+            if key == "min_doxcer_version"
+                && let Some(required) = min_version.take()
+            {
+                let running = crate::version();
+                match version_at_least(running, &required) {
+                    Ok(true) => {}
+                    Ok(false) => self.report(Severity::Error, file, line, 1, &format!(
+                        "requires doxcer >= {required}, but this is doxcer {running}"
+                    )),
+                    Err(e) => self.report(Severity::Error, file, line, 1, &e.to_string()),
+                }
+            }
+            if key == "optional_placeholders" {
+                self.check_optional(file, line, optional);
+            }
+        }
+    }
+
+
+    fn check_optional(&mut self, file: &str, line: usize, optional: &[String]) {
+
+        // Validates an `optional_placeholders` list against the registry.
+
+        let registered: Vec<&str> = CONTEXT_KEYS.iter().chain(RESERVED_PLACEHOLDERS.iter()).copied().collect();
+        for name in optional {
+            match placeholder_kind(name) {
+                Some(PlaceholderKind::Provided | PlaceholderKind::Reserved) => {}
+                Some(PlaceholderKind::Deprecated(current)) => self.report(Severity::Warning, file, line, 1, &format!(
+                    "optional placeholder `{name}` is a deprecated alias of `{current}`; list `{current}` instead"
+                )),
+                None => {
+                    let hint = closest(name, &registered).map(|k| format!("; did you mean `{k}`?")).unwrap_or_default();
+                    self.report(Severity::Warning, file, line, 1, &format!(
+                        "optional placeholder `{name}` is neither provided nor reserved by doxcer {}{hint}",
+                        crate::version()
+                    ));
+                }
+            }
+        }
+        let unavailable = unavailable_placeholders(optional);
+        if !unavailable.is_empty() {
+            self.report(Severity::Warning, file, line, 1, &format!(
+                "doxcer {} renders the optional placeholders {} as empty text",
+                crate::version(),
+                unavailable.iter().map(|name| format!("`{{{{{name}}}}}`")).collect::<Vec<_>>().join(", ")
+            ));
+        }
+    }
+
+
+    fn check_include(&mut self, name: &str, file: &str, at: (usize, usize), current_dir: &Path, template_dir: &Path, chain: &mut Vec<String>) {
+
+        // Resolves one `{{> name}}`, include, extends or import the way the
+        // loader does and checks the template it names.
+
+        let (line, column) = at;
+        let (id, dir, raw) = match resolve_partial(name, current_dir, template_dir) {
+            Ok(found) => found,
+            Err(e) => return self.report(Severity::Error, file, line, column, &e.to_string()),
+        };
+        if chain.contains(&id) {
+            let cycle = format!("include cycle: {} -> {}", chain.iter().map(|c| display_id(c)).collect::<Vec<_>>().join(" -> "), display_id(&id));
+            return self.report(Severity::Error, file, line, column, &cycle);
+        }
+        if chain.len() > MAX_INCLUDE_DEPTH {
+            return self.report(Severity::Error, file, line, column, &format!("include depth limit ({MAX_INCLUDE_DEPTH}) exceeded"));
+        }
+        chain.push(id.clone());
+        self.check_text(&id, &display_id(&id), &raw, &dir, template_dir, chain);
+        chain.pop();
+    }
+
+
+    fn report(&mut self, severity: Severity, file: &str, line: usize, column: usize, message: &str) {
+        self.problems.push(Problem { severity, file: file.to_string(), line, column, message: message.to_string() });
+    }
+}
+
+
+// ====================================================
+//  Helpers
+// ====================================================
+fn markdown_files(dir: &Path) -> Result<Vec<PathBuf>, DoxcerError> {
+
+    // Lists the `.md` files below a directory, sorted.
+
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let entries = fs::read_dir(&current)
+            .map_err(|e| DoxcerError::Input(format!("Failed to read {}: {e}", current.display())))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|e| e == "md") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+
+fn location(text: &str, offset: usize) -> (usize, usize) {
+
+    // Returns the 1-based line and column of a byte offset.
+
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+    (line, column)
+}
+
+
+fn display_id(id: &str) -> String {
+
+    // Shows an include identifier relative to the working directory when possible.
+
+    env::current_dir()
+        .ok()
+        .and_then(|cwd| fs::canonicalize(cwd).ok())
+        .and_then(|cwd| Path::new(id).strip_prefix(cwd).ok().map(|p| p.display().to_string()))
+        .unwrap_or_else(|| id.to_string())
+}
+
+
+fn closest<'a>(name: &str, known: &[&'a str]) -> Option<&'a str> {
+
+    // Suggests the known name within two edits of a misspelled one.
+
+    known
+        .iter()
+        .map(|k| (edit_distance(name, k), *k))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, k)| k)
+}
+
+
+fn edit_distance(a: &str, b: &str) -> usize {
+
+    // Levenshtein distance between two short names.
+
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
@@ -0,0 +1,164 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// External Libraries
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::Deserialize;
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::kind::SourceKind;
+use crate::template::{BUILTIN_PARTIALS, BUILTIN_TEMPLATES};
+
+
+// ====================================================
+//  Constants
+// ====================================================
+// The `[[templates]]` tables of `doxcer.toml`.
+pub const TEMPLATE_ROUTE_TABLE: &str = "templates";
+// The built-in template names and the file each stands for in the template
+// directory; any other name `<name>` is the file `<name>.md`.
+pub const KIND_TEMPLATES: [(&str, &str); 4] = [
+    ("notebook", "prompt.md"),
+    ("sql", "sql.md"),
+    ("pipeline", "pipeline.md"),
+    ("semantic-model", "semantic_model.md"),
+];
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+// One `[[templates]]` entry: the files matching `pattern` (a `.gitignore`
+// pattern, relative to the working directory) use the template `template`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateRoute {
+    pub pattern: String,
+    pub template: String,
+}
+
+
+// ====================================================
+//  Names
+// ====================================================
+pub fn kind_template_name(kind: SourceKind) -> String {
+
+    // The name of the template a kind uses when nothing else is selected.
+
+    kind.to_string()
+}
+
+
+pub fn template_file_name(name: &str) -> Result<String, DoxcerError> {
+
+    // Returns the file a template name stands for: `prompt.md` for
+    // `notebook`, `sql.md` for `sql`, and `<name>.md` for any other name.
+
+    if let Some((_, file)) = KIND_TEMPLATES.iter().find(|(kind, _)| *kind == name) {
+        return Ok(file.to_string());
+    }
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    match valid {
+        true => Ok(format!("{name}.md")),
+        false => Err(DoxcerError::Config(format!("invalid template name {name:?}; use letters, digits, `-` and `_`, without `.md`"))),
+    }
+}
+
+
+pub fn available_templates(dir: &Path) -> Vec<String> {
+
+    // Lists the template names that can be selected: the built-in ones and
+    // every other `<name>.md` directly in the template directory, leaving out
+    // the summary templates and partials doxcer ships and language variants.
+
+    let mut names: Vec<String> = KIND_TEMPLATES.iter().map(|(name, _)| name.to_string()).collect();
+    let files = fs::read_dir(dir).into_iter().flatten().flatten().map(|entry| entry.path());
+    for path in files {
+        let is_markdown = path.extension().is_some_and(|ext| ext == "md");
+        // `prompt.en.md` is a language variant, not a template of its own.
+        let Some(stem) = path.file_stem().and_then(|s| s.to_str()).filter(|s| is_markdown && !s.contains('.')) else {
+            continue;
+        };
+        let builtin = BUILTIN_TEMPLATES.iter().chain(BUILTIN_PARTIALS.iter()).any(|(file, _)| file.strip_suffix(".md") == Some(stem));
+        if !builtin && !names.iter().any(|n| n == stem) {
+            names.push(stem.to_string());
+        }
+    }
+    names
+}
+
+
+// ====================================================
+//  Routing
+// ====================================================
+pub fn validate_template_routes(routes: &[TemplateRoute]) -> Result<(), DoxcerError> {
+
+    // Checks every `[[templates]]` entry: a valid pattern and template name.
+
+    for (i, route) in routes.iter().enumerate() {
+        template_file_name(&route.template).map_err(|e| DoxcerError::Config(format!("[[{TEMPLATE_ROUTE_TABLE}]] entry {}: {e}", i + 1)))?;
+        matcher(&route.pattern).map_err(|e| DoxcerError::Config(format!("[[{TEMPLATE_ROUTE_TABLE}]] entry {}: {e}", i + 1)))?;
+    }
+    Ok(())
+}
+
+
+pub fn route_template<'a>(routes: &'a [TemplateRoute], path: &Path) -> Option<&'a TemplateRoute> {
+
+    // Returns the first `[[templates]]` entry whose pattern matches `path`.
+    //
+    // # Description
+    // Patterns follow `.gitignore` rules, like `--include`: `*.sql` matches in
+    // every directory, `fabric/gold/**` only below that directory. Invalid
+    // patterns are reported by `validate_template_routes` and never match.
+
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    routes.iter().find(|route| {
+        matcher(&route.pattern).is_ok_and(|matcher| matches(&matcher, &path))
+    })
+}
+
+
+pub fn template_name_for(selected: Option<&str>, routes: &[TemplateRoute], path: &Path, kind: SourceKind) -> String {
+
+    // The template a file is documented with: the `--template-name` flag, else
+    // the first matching `[[templates]]` entry, else the one for its kind.
+
+    selected
+        .map(str::to_string)
+        .or_else(|| route_template(routes, path).map(|route| route.template.clone()))
+        .unwrap_or_else(|| kind_template_name(kind))
+}
+
+
+fn matcher(pattern: &str) -> Result<Gitignore, DoxcerError> {
+    let mut builder = GitignoreBuilder::new(working_dir());
+    builder.add_line(None, pattern).map_err(|e| DoxcerError::Config(format!("invalid pattern {pattern:?}: {e}")))?;
+    builder.build().map_err(|e| DoxcerError::Config(format!("invalid pattern {pattern:?}: {e}")))
+}
+
+
+fn working_dir() -> PathBuf {
+    std::path::absolute(".").unwrap_or_else(|_| PathBuf::from("."))
+}
+
+
+fn matches(matcher: &Gitignore, path: &Path) -> bool {
+
+    // `matched_path_or_any_parents` expects a path below the matcher's root;
+    // files elsewhere are matched on their own path only.
+
+    match path.starts_with(matcher.path()) {
+        true => matcher.matched_path_or_any_parents(path, false).is_ignore(),
+        false => matcher.matched(path, false).is_ignore(),
+    }
+}
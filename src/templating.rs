@@ -0,0 +1,640 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::error::Error;
+
+// External Libraries
+use serde_json::{Map, Value};
+use tera::{Context, Tera};
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+// The name of the rendered body inside its Tera instance.
+const BODY_NAME: &str = "__doxcer_template__";
+// Words in a Tera expression that are never placeholders.
+const KEYWORDS: [&str; 14] = ["and", "or", "not", "in", "is", "as", "if", "else", "true", "false", "True", "False", "loop", "self"];
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+// A list item, such as a cell: its fields by name (`cell.title`).
+pub type Record = BTreeMap<String, String>;
+
+// A syntax error and the byte offset it was found at.
+pub type SyntaxError = (String, usize);
+
+// A name a template refers to, outside the names it binds itself (loop
+// variables, `set`, macro arguments).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reference {
+    Placeholder { name: String, offset: usize },
+    // `{{> name}}`, or `{% include %}`, `{% extends %}` or `{% import %}`.
+    Include { name: String, offset: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variables<'a> {
+    pub values: &'a BTreeMap<String, String>,
+    pub lists: &'a BTreeMap<String, Vec<Record>>,
+    // Deprecated spellings, as the placeholder they stand for.
+    pub aliases: &'a [(&'a str, &'a str)],
+    // The templates the body extends or imports macros from, by the name it
+    // gives them, with their includes expanded.
+    pub layouts: &'a [(String, String)],
+}
+
+
+// ====================================================
+//  Parsing
+// ====================================================
+pub fn check_syntax(body: &str) -> Result<(), SyntaxError> {
+
+    // Parses a template body with Tera without rendering it.
+    //
+    // # Description
+    // The body is Tera: `{{ name }}`, `{{ cell.title | upper }}`, every Tera
+    // filter, test and function, `{% for %}` with `loop.*`, `{% if %}` /
+    // `{% elif %}`, `{% set %}`, macros, `{% extends %}` with blocks and
+    // `{# comments #}`. doxcer adds `{{> name}}` (an include, expanded when
+    // the template is loaded) and `{{ env:NAME }}` (an environment variable).
+    // The templates the body includes, extends or imports are stood in for by
+    // empty ones, so only the body itself is checked.
+    //
+    // # Returns
+    // * `Ok(())` – the body parses.
+    // * `Err((message, offset))` – a syntax error and its byte offset in `body`.
+
+    let mut templates: Vec<(String, String)> = references(body)
+        .into_iter()
+        .filter_map(|reference| match reference {
+            Reference::Include { name, .. } => Some((name, String::new())),
+            Reference::Placeholder { .. } => None,
+        })
+        .collect();
+    templates.push((BODY_NAME.to_string(), tera_source(body)));
+    Tera::default().add_raw_templates(templates).map_err(|e| syntax_error(body, &e))
+}
+
+
+fn tera_source(body: &str) -> String {
+
+    // Rewrites doxcer's additions into Tera without moving any byte, so
+    // Tera's line and column still point into `body`: `{{> name}}` becomes a
+    // comment (it is expanded before rendering) and `env:NAME` becomes
+    // `env.NAME`, a field of the `env` variable.
+
+    let mut out = String::with_capacity(body.len());
+    let mut pos = 0;
+    for (start, end, open) in tags(body) {
+        out.push_str(&body[pos..start]);
+        let tag = &body[start..end];
+        match open {
+            "{{" if tag[2..].trim_start().starts_with('>') => out.push_str(&format!("{{#{}#}}", &tag[2..tag.len() - 2])),
+            "{#" => out.push_str(tag),
+            _ => out.push_str(&env_fields(tag)),
+        }
+        pos = end;
+    }
+    out.push_str(&body[pos..]);
+    out
+}
+
+
+fn env_fields(tag: &str) -> String {
+
+    // Replaces `env:` before a name with `env.`, outside quoted text.
+
+    let mut out = String::with_capacity(tag.len());
+    let mut quote = None;
+    for (i, c) in tag.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if matches!(c, '"' | '\'' | '`') => quote = Some(c),
+            None if c == ':' && tag[..i].ends_with("env") && tag[i + 1..].starts_with(is_name_start) => {
+                let before = tag[..i - 3].chars().next_back();
+                if !before.is_some_and(is_name_char) {
+                    out.push('.');
+                    continue;
+                }
+            }
+            None => {}
+        }
+        out.push(c);
+    }
+    out
+}
+
+
+fn tags(body: &str) -> Vec<(usize, usize, &'static str)> {
+
+    // The `{{ }}`, `{% %}` and `{# #}` tags of a body as `(start, end,
+    // opening delimiter)`; an unclosed tag ends the list (Tera reports it).
+    // The text between `{% raw %}` and `{% endraw %}` holds no tags.
+
+    let mut found = Vec::new();
+    let mut pos = 0;
+    let mut raw = false;
+    while let Some((start, open)) = ["{{", "{%", "{#"].iter().filter_map(|open| body[pos..].find(open).map(|i| (pos + i, *open))).min_by_key(|(i, _)| *i) {
+        let close = match open {
+            "{{" => "}}",
+            "{%" => "%}",
+            _ => "#}",
+        };
+        let Some(len) = body[start + 2..].find(close) else {
+            break;
+        };
+        let end = start + 2 + len + 2;
+        let word = body[start + 2..end - 2].trim_matches('-').split_whitespace().next().unwrap_or_default();
+        match (raw, open, word) {
+            (false, "{%", "raw") => raw = true,
+            (true, "{%", "endraw") => raw = false,
+            (true, _, _) => {}
+            _ => found.push((start, end, open)),
+        }
+        pos = end;
+    }
+    found
+}
+
+
+fn syntax_error(body: &str, error: &tera::Error) -> SyntaxError {
+
+    // Turns a Tera parse error into its message and the byte offset of the
+    // `--> line:column` it points at.
+
+    let text = error_chain(error);
+    let position = text.split(" --> ").nth(1).and_then(|rest| {
+        let (line, column) = rest.lines().next()?.trim().split_once(':')?;
+        Some((line.parse::<usize>().ok()?, column.parse::<usize>().ok()?))
+    });
+    let message = text
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("= "))
+        .map_or_else(|| text.replace(&format!("'{BODY_NAME}'"), "the template"), str::to_string);
+    let Some((line, column)) = position else {
+        return (message, 0);
+    };
+    let line_start: usize = body.split_inclusive('\n').take(line.saturating_sub(1)).map(str::len).sum();
+    let offset = line_start + body[line_start..].chars().take(column.saturating_sub(1)).map(char::len_utf8).sum::<usize>();
+    (message, offset.min(body.len()))
+}
+
+
+fn error_chain(error: &tera::Error) -> String {
+
+    // A Tera error with the errors that caused it.
+
+    let mut text = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        text.push_str(": ");
+        text.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    text
+}
+
+
+fn is_name_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+
+fn is_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(is_name_char)
+}
+
+
+pub fn parse_var(text: &str) -> Result<(String, String), DoxcerError> {
+
+    // Parses one `name=value` template variable (`--var author=Stefan`); the
+    // value may contain `=` and is used as is.
+
+    let Some((name, value)) = text.split_once('=') else {
+        return Err(DoxcerError::Config(format!("expected name=value, got {text:?}")));
+    };
+    let name = name.trim();
+    if !is_name(name) {
+        return Err(DoxcerError::Config(format!("{name:?} is not a placeholder name (letters, digits and `_`)")));
+    }
+    Ok((name.to_string(), value.to_string()))
+}
+
+
+pub fn references(body: &str) -> Vec<Reference> {
+
+    // Lists the placeholders and includes a template uses, in order, each at
+    // the offset of its tag.
+    //
+    // # Description
+    // Names the template binds itself anywhere (`{% for x in ... %}`,
+    // `{% set x = ... %}`, macro arguments, `{% import ... as x %}`) are not
+    // placeholders, nor are fields, filters, tests, functions and keyword
+    // arguments. An expression with a `default` filter renders without its
+    // variables, so it lists none. `{{ env:NAME }}` is listed as `env:NAME`.
+
+    let mut bound: BTreeSet<String> = BTreeSet::new();
+    let mut found = Vec::new();
+    for (start, end, open) in tags(body) {
+        let inner = body[start + 2..end - 2].trim_matches('-').trim();
+        if open == "{#" {
+            continue;
+        }
+        if open == "{{" {
+            match inner.strip_prefix('>') {
+                Some(name) => found.push(Reference::Include { name: name.trim().to_string(), offset: start }),
+                None if inner.contains("default(") => {}
+                None => found.extend(expression_names(inner).into_iter().map(|name| Reference::Placeholder { name, offset: start })),
+            }
+            continue;
+        }
+        let (keyword, rest) = inner.split_once(char::is_whitespace).unwrap_or((inner, ""));
+        let rest = rest.trim();
+        let expression = match keyword {
+            "include" | "extends" | "import" => {
+                if let Some(name) = unquote(rest.split_whitespace().next().unwrap_or_default()) {
+                    found.push(Reference::Include { name, offset: start });
+                }
+                if let Some((_, alias)) = rest.rsplit_once(" as ") {
+                    bound.insert(alias.trim().to_string());
+                }
+                continue;
+            }
+            "for" => match rest.split_once(" in ") {
+                Some((vars, list)) => {
+                    bound.extend(vars.split(',').map(|var| var.trim().to_string()));
+                    list
+                }
+                None => continue,
+            },
+            "set" | "set_global" => match rest.split_once('=') {
+                Some((var, value)) => {
+                    bound.insert(var.trim().to_string());
+                    value
+                }
+                None => continue,
+            },
+            "macro" => {
+                let args = rest.split_once('(').map_or("", |(_, args)| args.trim_end_matches(')'));
+                bound.extend(args.split(',').filter_map(|arg| arg.split('=').next()).map(|arg| arg.trim().to_string()));
+                continue;
+            }
+            "if" | "elif" => rest,
+            _ => continue,
+        };
+        if !expression.contains("default(") {
+            found.extend(expression_names(expression).into_iter().map(|name| Reference::Placeholder { name, offset: start }));
+        }
+    }
+    found.retain(|reference| !matches!(reference, Reference::Placeholder { name, .. } if bound.contains(name)));
+    found
+}
+
+
+fn expression_names(expression: &str) -> Vec<String> {
+
+    // The variables a Tera expression reads: names that are not keywords and
+    // do not follow `.`, `|`, `::` or a test's `is`, nor come before `(`, `=`
+    // or `::`. `env:NAME` is kept whole.
+
+    let chars: Vec<char> = expression.chars().collect();
+    let mut names = Vec::new();
+    let mut previous: [String; 2] = Default::default();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if matches!(c, '"' | '\'' | '`') {
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                i += 1;
+            }
+            i += 1;
+            continue;
+        }
+        if !is_name_start(c) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && is_name_char(chars[i]) {
+            i += 1;
+        }
+        let word: String = chars[start..i].iter().collect();
+        let before: String = chars[..start].iter().rev().skip_while(|c| c.is_whitespace()).take(2).collect();
+        let after: String = chars[i..].iter().skip_while(|c| c.is_whitespace()).take(2).collect();
+        let is_test = previous[1] == "is" || (previous[0] == "is" && previous[1] == "not");
+        if word == "env" && after.starts_with(':') && !after.starts_with("::") {
+            let name_start = i + 1;
+            let mut end = name_start;
+            while end < chars.len() && is_name_char(chars[end]) {
+                end += 1;
+            }
+            names.push(format!("env:{}", chars[name_start..end].iter().collect::<String>()));
+            i = end;
+        } else if !(KEYWORDS.contains(&word.as_str())
+            || before.starts_with('.')
+            || before.starts_with('|')
+            || before == "::"
+            || is_test
+            || after.starts_with('(')
+            || after.starts_with("::")
+            || (after.starts_with('=') && after != "=="))
+        {
+            names.push(word.clone());
+        }
+        previous = [std::mem::take(&mut previous[1]), word];
+    }
+    names
+}
+
+
+fn unquote(text: &str) -> Option<String> {
+    text.strip_prefix('"').and_then(|t| t.strip_suffix('"'))
+        .or_else(|| text.strip_prefix('\'').and_then(|t| t.strip_suffix('\'')))
+        .map(str::to_string)
+}
+
+
+// ====================================================
+//  Rendering
+// ====================================================
+pub fn render(body: &str, variables: &Variables) -> Result<String, DoxcerError> {
+
+    // Renders a template body with Tera (see `check_syntax` for the syntax).
+    //
+    // # Description
+    // Text values are strings and lists are lists of records, so
+    // `{{ cells | length }}` and `{% for cell in cells %}` work as in any Tera
+    // template; output is not HTML-escaped. A block tag or comment alone on
+    // its line takes the line with it, so loops do not leave blank lines
+    // behind. `{{ env:NAME }}` reads the environment variable `NAME`. An
+    // unknown placeholder or an unset env var is an error, unless a `default`
+    // filter gives the text to use instead.
+    //
+    // # Returns
+    // * `Ok(String)` – the rendered text.
+    // * `Err(DoxcerError)` – a syntax error (with its line) or a failed lookup.
+
+    check_syntax(body).map_err(|(message, offset)| {
+        let line = body[..offset].matches('\n').count() + 1;
+        DoxcerError::Config(format!("Invalid template at line {line}: {message}"))
+    })?;
+    let mut templates: Vec<(String, String)> = variables
+        .layouts
+        .iter()
+        .map(|(name, layout)| (name.clone(), trim_tag_lines(&tera_source(layout))))
+        .collect();
+    templates.push((BODY_NAME.to_string(), trim_tag_lines(&tera_source(body))));
+
+    let mut tera = Tera::default();
+    tera.autoescape_on(Vec::new());
+    tera.add_raw_templates(templates)
+        .map_err(|e| DoxcerError::Config(format!("Invalid template: {}", error_chain(&e).replace(&format!("'{BODY_NAME}'"), "the template"))))?;
+    let texts = std::iter::once(body).chain(variables.layouts.iter().map(|(_, layout)| layout.as_str()));
+    tera.render(BODY_NAME, &context(variables, texts)).map_err(|e| render_error(&e))
+}
+
+
+fn trim_tag_lines(body: &str) -> String {
+
+    // Drops the indentation and line break around a `{% %}` tag or `{# #}`
+    // comment that is alone on its line.
+
+    body.split_inclusive('\n')
+        .map(|line| {
+            let tag = line.trim();
+            let alone = [("{%", "%}"), ("{#", "#}")]
+                .iter()
+                .any(|(open, close)| tag.starts_with(open) && tag.ends_with(close) && tag.matches(open).count() == 1);
+            if alone { tag } else { line }
+        })
+        .collect()
+}
+
+
+fn context<'a>(variables: &Variables, texts: impl Iterator<Item = &'a str>) -> Context {
+
+    // The Tera context: the values (with their deprecated aliases), the lists
+    // and, as `env`, the environment variables the templates name.
+
+    let mut context = Context::new();
+    for (alias, current) in variables.aliases {
+        if let Some(value) = variables.values.get(*current) {
+            context.insert(*alias, value);
+        }
+    }
+    for (name, value) in variables.values {
+        context.insert(name, value);
+    }
+    for (name, items) in variables.lists {
+        context.insert(name, items);
+    }
+    let mut vars = Map::new();
+    for text in texts {
+        for (start, _) in text.match_indices("env:") {
+            let name: String = text[start + 4..].chars().take_while(|c| is_name_char(*c)).collect();
+            if let Ok(value) = env::var(&name) {
+                vars.insert(name, Value::String(value));
+            }
+        }
+    }
+    context.insert("env", &vars);
+    context
+}
+
+
+fn render_error(error: &tera::Error) -> DoxcerError {
+
+    // Explains a failed render; an unknown variable gets the ways to set it.
+
+    let text = error_chain(error).replace(&format!("'{BODY_NAME}'"), "the template");
+    let missing = text.split("Variable `").nth(1).and_then(|rest| rest.split_once("` not found")).map(|(name, _)| name);
+    match missing {
+        Some(name) if name.starts_with("env.") => {
+            let var = &name["env.".len()..];
+            DoxcerError::Config(format!("Template placeholder `{{{{env:{var}}}}}` refers to unset env var {var}"))
+        }
+        Some(name) if is_name(name) => DoxcerError::Config(format!(
+            "Unknown template placeholder `{{{{{name}}}}}`; a template variable is set with `--var {name}=...` or under [vars] in doxcer.toml"
+        )),
+        _ => DoxcerError::Config(format!("Template error: {}", text.strip_prefix("Failed to render the template: ").unwrap_or(&text))),
+    }
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use crate::config::{Settings, SettingsLayer, Source};
+    use crate::template::prompt_context;
+
+    fn record(fields: &[(&str, &str)]) -> Record {
+        fields.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn rendered(body: &str, values: &[(&str, &str)], lists: &[(&str, Vec<Record>)]) -> Result<String, String> {
+        let values = values.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let lists = lists.iter().map(|(k, v)| (k.to_string(), v.clone())).collect();
+        render(body, &Variables { values: &values, lists: &lists, aliases: &[], layouts: &[] }).map_err(|e| e.to_string())
+    }
+
+
+    #[test]
+    fn reports_a_syntax_error_at_its_line() {
+        let err = rendered("# Title\n\nIntro {{ name }}\n{{ bad name }}\n", &[("name", "x")], &[]).unwrap_err();
+        assert!(err.starts_with("Invalid template at line 4: expected"), "{err}");
+
+        let err = rendered("a\n{% endif %}", &[], &[]).unwrap_err();
+        assert!(err.starts_with("Invalid template at line 2: unexpected tag"), "{err}");
+
+        let err = rendered("x\n\n{{ name", &[], &[]).unwrap_err();
+        assert!(err.starts_with("Invalid template at line 3:"), "{err}");
+
+        let err = rendered("{{ name | shout }}", &[("name", "x")], &[]).unwrap_err();
+        assert!(err.contains("Filter 'shout' not found"), "{err}");
+    }
+
+
+    #[test]
+    fn renders_tera_filters_tests_and_tags() {
+        let cells = vec![record(&[("kind", "code"), ("title", "load")]), record(&[("kind", "markdown"), ("title", "intro")])];
+        let body = "{% set code = cells | filter(attribute=\"kind\", value=\"code\") %}\
+                    {{ code | length }} of {{ cells | length }}: {{ cells | map(attribute=\"title\") | join(sep=\", \") | title }}\
+                    {% if team is defined and team | length > 2 %} by {{ team | replace(from=\"-\", to=\" \") }}{% elif team %} by ?{% endif %}";
+        assert_eq!(rendered(body, &[("team", "data-bi")], &[("cells", cells)]).unwrap(), "1 of 2: Load, Intro by data bi");
+    }
+
+
+    #[test]
+    fn an_extended_layout_and_imported_macros_render() {
+        let layouts = [
+            ("base.md".to_string(), "# {% block title %}Doc{% endblock title %}\n{% block body %}{% endblock body %}".to_string()),
+            ("macros.md".to_string(), "{% macro bullet(text) %}- {{ text }}{% endmacro bullet %}".to_string()),
+        ];
+        let body = "{% extends \"base.md\" %}{% import \"macros.md\" as m %}\
+                    {% block title %}{{ notebook_name | upper }}{% endblock title %}\
+                    {% block body %}{{ m::bullet(text=notebook_name) }}{% endblock body %}";
+        let values = BTreeMap::from([("notebook_name".to_string(), "load.py".to_string())]);
+        let variables = Variables { values: &values, lists: &BTreeMap::new(), aliases: &[], layouts: &layouts };
+        assert_eq!(render(body, &variables).unwrap(), "# LOAD.PY\n- load.py");
+    }
+
+
+    #[test]
+    fn an_unclosed_for_is_a_syntax_error() {
+        let err = rendered("# Cells\n{% for cell in cells %}\n- {{ cell.title }}\n", &[], &[("cells", Vec::new())]).unwrap_err();
+        assert!(err.starts_with("Invalid template at line"), "{err}");
+
+        let err = rendered("{% for a in list %}{% if a.x %}{% endfor %}", &[], &[]).unwrap_err();
+        assert!(err.starts_with("Invalid template at line 1:"), "{err}");
+    }
+
+
+    #[test]
+    fn nested_loops_each_get_their_own_loop_variable() {
+        let tables = vec![record(&[("name", "orders")]), record(&[("name", "lines")])];
+        let columns = vec![record(&[("name", "id")]), record(&[("name", "qty")]), record(&[("name", "day")])];
+        let body = "{% for t in tables %}\n\
+                    {{ loop.index }}. {{ t.name }}{% if loop.first %} (first){% endif %}:\
+                    {% for c in columns %} {{ t.name }}.{{ c.name }}#{{ loop.index0 }}{% if not loop.last %},{% endif %}{% endfor %}\
+                    {% if loop.last %} (last){% endif %}\n\
+                    {% endfor %}\n";
+        assert_eq!(
+            rendered(body, &[], &[("tables", tables), ("columns", columns)]).unwrap(),
+            "1. orders (first): orders.id#0, orders.qty#1, orders.day#2\n\
+             2. lines: lines.id#0, lines.qty#1, lines.day#2 (last)\n"
+        );
+    }
+
+
+    #[test]
+    fn loop_is_unbound_outside_its_loop() {
+        let err = rendered("{% for t in tables %}{% endfor %}{{ loop.index }}", &[], &[("tables", Vec::new())]).unwrap_err();
+        assert!(err.contains("Variable `loop.index` not found"), "{err}");
+    }
+
+
+    #[test]
+    fn default_covers_missing_values() {
+        let body = "{{ author | default(value=\"onbekend\") }}|{{ team | default(value='-') }}|{{ owner | default(value=\"x\") | upper }}";
+        assert_eq!(rendered(body, &[("team", "")], &[]).unwrap(), "onbekend||X");
+        assert_eq!(rendered(body, &[("author", "Stefan"), ("team", "data"), ("owner", "bi")], &[]).unwrap(), "Stefan|data|BI");
+        assert_eq!(rendered("{{ env:DOXCER_TEMPLATING_TEST_UNSET | default(value=\"-\") }}", &[], &[]).unwrap(), "-");
+
+        let err = rendered("{{ author }}", &[], &[]).unwrap_err();
+        assert!(err.starts_with("Unknown template placeholder `{{author}}`; a template variable is set with `--var author=...`"), "{err}");
+        let err = rendered("{{ env:DOXCER_TEMPLATING_TEST_UNSET }}", &[], &[]).unwrap_err();
+        assert_eq!(err, "Template placeholder `{{env:DOXCER_TEMPLATING_TEST_UNSET}}` refers to unset env var DOXCER_TEMPLATING_TEST_UNSET");
+    }
+
+
+    #[test]
+    fn references_leave_out_bound_names_fields_and_filters() {
+        let body = "{{ author | default(value=\"-\") }}{{ title }}\n\
+                    {% set n = cells | length %}{% for cell in cells %}{{ cell.title | upper }}{{ loop.index }}{% endfor %}\n\
+                    {% if team is not defined %}{{ env:HOME }}{% endif %}{{> rules.md}}{% include \"more.md\" %}{{ n }}";
+        let names: Vec<String> = references(body)
+            .into_iter()
+            .map(|reference| match reference {
+                Reference::Placeholder { name, .. } => name,
+                Reference::Include { name, .. } => format!("> {name}"),
+            })
+            .collect();
+        assert_eq!(names, ["title", "cells", "cells", "team", "env:HOME", "> rules.md", "> more.md"]);
+        assert_eq!(references("x {{ title }}")[0], Reference::Placeholder { name: "title".to_string(), offset: 2 });
+        assert_eq!(check_syntax("{{> rules.md}}\n{% include \"more.md\" %}"), Ok(()));
+    }
+
+
+    #[test]
+    fn builtin_variables_win_over_var() {
+        let layer = SettingsLayer {
+            model: Some("gpt-4o".to_string()),
+            vars: Some(BTreeMap::from([
+                ("author".to_string(), "Stefan".to_string()),
+                ("model".to_string(), "from --var".to_string()),
+                ("notebook_name".to_string(), "from --var".to_string()),
+            ])),
+            ..Default::default()
+        };
+        let settings = Settings::resolve(&[(Source::Cli, &layer)]);
+        let context = prompt_context(Path::new("nb/load.py"), &settings, "", "", "", "");
+        let variables = Variables { values: &context, lists: &BTreeMap::new(), aliases: &[], layouts: &[] };
+        assert_eq!(
+            render("{{ author }}: {{ notebook_name }} ({{ notebook_path }}) with {{ model }}", &variables).unwrap(),
+            "Stefan: load.py (nb/load.py) with gpt-4o"
+        );
+    }
+
+
+    #[test]
+    fn parses_var_flags() {
+        assert_eq!(parse_var("author=Stefan = B").unwrap(), ("author".to_string(), "Stefan = B".to_string()));
+        assert_eq!(parse_var(" team =").unwrap(), ("team".to_string(), String::new()));
+        assert!(parse_var("author").unwrap_err().to_string().starts_with("expected name=value"));
+        assert!(parse_var("my-name=x").unwrap_err().to_string().contains("is not a placeholder name"));
+    }
+}
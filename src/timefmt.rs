@@ -0,0 +1,145 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::env;
+use std::str::FromStr;
+use std::time::Duration;
+
+// External Libraries
+use chrono::{DateTime, Local, NaiveDate, SecondsFormat, Utc};
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const GENERATED_AT_KEY: &str = "generated_at";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampTz {
+    #[default]
+    Utc,
+    Local,
+}
+
+impl FromStr for TimestampTz {
+    type Err = DoxcerError;
+
+    fn from_str(s: &str) -> Result<TimestampTz, DoxcerError> {
+        match s {
+            "utc" | "UTC" => Ok(TimestampTz::Utc),
+            "local" => Ok(TimestampTz::Local),
+            other => Err(DoxcerError::Config(format!("timestamp timezone must be utc or local, got {other:?}"))),
+        }
+    }
+}
+
+
+// ====================================================
+//  Timestamps
+// ====================================================
+pub fn now_rfc3339() -> String {
+
+    // Returns the current time for machine-facing records (manifest, run log, cache).
+    //
+    // # Description
+    // Always RFC 3339 in UTC with second precision (`2026-10-14T09:30:00Z`), so
+    // records are identical in format on every machine and sort as strings.
+
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+
+pub fn now_in(tz: TimestampTz) -> String {
+
+    // Returns the current time for human-facing fields such as `generated_at`.
+    //
+    // # Description
+    // RFC 3339 in the requested zone; local times carry their UTC offset
+    // (`2026-10-14T11:30:00+02:00`) so they stay unambiguous.
+
+    match tz {
+        TimestampTz::Utc => now_rfc3339(),
+        TimestampTz::Local => Local::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+    }
+}
+
+
+pub fn age_of(timestamp: &str) -> Option<Duration> {
+
+    // Returns how long ago an RFC 3339 timestamp was, or `None` if it cannot be
+    // parsed or lies in the future.
+
+    let then = DateTime::parse_from_rfc3339(timestamp).ok()?.with_timezone(&Utc);
+    (Utc::now() - then).to_std().ok()
+}
+
+
+pub fn parse_age(text: &str) -> Result<Duration, DoxcerError> {
+
+    // Parses a human age such as `30m`, `12h` or `7d` (a bare number means seconds).
+
+    let text = text.trim();
+    let (number, unit) = text
+        .find(|c: char| !c.is_ascii_digit())
+        .map(|i| text.split_at(i))
+        .unwrap_or((text, "s"));
+    let secs_per_unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        "w" => 7 * 86_400,
+        _ => return Err(DoxcerError::Config(format!("invalid age {text:?}, expected e.g. 30m, 12h or 7d"))),
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|_| DoxcerError::Config(format!("invalid age {text:?}, expected e.g. 30m, 12h or 7d")))?;
+    Ok(Duration::from_secs(number * secs_per_unit))
+}
+
+
+pub fn parse_instant(text: &str) -> Result<DateTime<Utc>, DoxcerError> {
+
+    // Parses a point in time for `--since`/`--until`: an RFC 3339 timestamp,
+    // a date (`2026-10-01`, midnight UTC) or an age back from now (`30d`).
+
+    let text = text.trim();
+    if let Ok(at) = DateTime::parse_from_rfc3339(text) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    let age = parse_age(text).map_err(|_| DoxcerError::Config(format!("invalid time {text:?}, expected e.g. 2026-10-01, 2026-10-01T09:30:00Z or 30d")))?;
+    chrono::Duration::from_std(age)
+        .ok()
+        .and_then(|age| Utc::now().checked_sub_signed(age))
+        .ok_or_else(|| DoxcerError::Config(format!("time {text:?} is too far back")))
+}
+
+
+pub fn tz_from_env() -> Result<Option<TimestampTz>, DoxcerError> {
+
+    // Reads the `generated_at` timezone from `DOXCER_TZ`, if set.
+
+    match env::var("DOXCER_TZ") {
+        Ok(v) if !v.trim().is_empty() => v
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|e| DoxcerError::Config(format!("DOXCER_TZ: {e}"))),
+        _ => Ok(None),
+    }
+}
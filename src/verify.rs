@@ -0,0 +1,301 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Internal Modules
+use crate::archive::{member_doc_path, split_member_path};
+use crate::error::DoxcerError;
+use crate::manifest::{Manifest, ManifestEntry, manifest_key};
+use crate::provenance::read_provenance;
+use crate::source::SourceSnapshot;
+use crate::style::{Color, Style};
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const DEFAULT_DOCS_DIR: &str = "docs";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocStatus {
+    Ok,
+    Stale,
+    Missing,
+}
+
+#[derive(Debug, Clone)]
+pub struct VerifyResult {
+    pub notebook: PathBuf,
+    pub doc_path: PathBuf,
+    pub source_hash: String,
+    pub status: DocStatus,
+}
+
+impl fmt::Display for DocStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DocStatus::Ok => write!(f, "ok"),
+            DocStatus::Stale => write!(f, "stale"),
+            DocStatus::Missing => write!(f, "missing"),
+        }
+    }
+}
+
+
+// ====================================================
+//  Output Paths
+// ====================================================
+pub fn expected_doc_path(notebook: &Path, docs_dir: &Path) -> PathBuf {
+
+    // Returns the path where the documentation for a notebook is expected.
+    //
+    // # Description
+    // Mirrors the documented convention `docs/<notebook_stem>.md`. A member of
+    // a ZIP archive (`export.zip!path/in/archive.py`) keeps its folders, see
+    // [`member_doc_path`].
+
+    if let Some((_, member)) = split_member_path(notebook) {
+        return member_doc_path(&member, docs_dir);
+    }
+    let stem = notebook
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "notebook".to_string());
+    docs_dir.join(format!("{stem}.md"))
+}
+
+
+pub fn check_doc_paths(notebooks: &[PathBuf], docs_dir: &Path) -> Result<(), DoxcerError> {
+
+    // Refuses notebooks that share a document path.
+    //
+    // # Description
+    // Loose files keep only their stem, so `a/load.py` and `b/load.py` both
+    // map to `docs/load.md`; one document would then stand in for the other.
+    //
+    // # Returns
+    // * `Ok(())` when every notebook has its own document path.
+    // * `Err(DoxcerError::Usage)` naming the first two notebooks that collide.
+
+    let mut seen: BTreeMap<PathBuf, &PathBuf> = BTreeMap::new();
+    for notebook in notebooks {
+        let path = expected_doc_path(notebook, docs_dir);
+        if let Some(other) = seen.insert(path.clone(), notebook) {
+            return Err(DoxcerError::Usage(format!(
+                "{} and {} are both documented in {}; rename one of them",
+                other.display(),
+                notebook.display(),
+                path.display()
+            )));
+        }
+    }
+    Ok(())
+}
+
+
+// ====================================================
+//  Staleness Decision
+// ====================================================
+pub fn doc_status(notebook: &Path, source_hash: &str, doc: Option<&str>, entry: Option<&ManifestEntry>) -> DocStatus {
+
+    // Decides whether a notebook's documentation is up to date.
+    //
+    // # Description
+    // A missing document is `Missing`. When the document carries front-matter
+    // provenance, its `doxcer_source_hash` is authoritative, and its
+    // `doxcer_source` must name the notebook (compared as manifest keys), so a
+    // document written for another notebook is `Stale`. Documents without
+    // provenance fall back to the manifest entry; if neither is available the
+    // document cannot be trusted and is reported as `Stale`.
+    //
+    // # Parameters
+    // * `notebook` – The notebook being verified.
+    // * `source_hash` – Content hash of the notebook as it is now.
+    // * `doc` – The current document contents, if the file exists.
+    // * `entry` – The manifest entry for the notebook, if any.
+    //
+    // # Returns
+    // * The resulting `DocStatus`.
+
+    let Some(doc) = doc else {
+        return DocStatus::Missing;
+    };
+
+    let recorded = match read_provenance(doc) {
+        Some(p) if manifest_key(Path::new(&p.source)) != manifest_key(notebook) => return DocStatus::Stale,
+        Some(p) => Some(p.source_hash),
+        None => entry.map(|e| e.source_hash.clone()),
+    };
+
+    match recorded {
+        Some(hash) if hash == source_hash => DocStatus::Ok,
+        _ => DocStatus::Stale,
+    }
+}
+
+
+pub fn verify_notebook(notebook: &Path, docs_dir: &Path, manifest: &Manifest) -> Result<VerifyResult, DoxcerError> {
+
+    // Checks a single notebook against its documentation on disk.
+    //
+    // # Returns
+    // * `Ok(VerifyResult)` describing the notebook, its doc path and status.
+    // * `Err(DoxcerError)` if the notebook or an existing document cannot be read.
+
+    verify_notebook_with(notebook, docs_dir, manifest, |path| {
+        if !path.exists() {
+            return Ok(None);
+        }
+        fs::read(path).map(Some).map_err(|e| DoxcerError::Input(format!("Failed to read {}: {e}", path.display())))
+    })
+}
+
+
+pub fn verify_notebook_with<R>(notebook: &Path, docs_dir: &Path, manifest: &Manifest, read: R) -> Result<VerifyResult, DoxcerError>
+where
+    R: Fn(&Path) -> Result<Option<Vec<u8>>, DoxcerError>,
+{
+
+    // Checks a single notebook against its documentation through the given reader.
+    //
+    // # Description
+    // `read` returns `None` for a file that does not exist. `verify --staged`
+    // passes a reader for the git index, so the notebook and the document are
+    // compared as they will be committed.
+
+    let bytes = read(notebook)?.ok_or_else(|| DoxcerError::Input(format!("Failed to read notebook {}: not found", notebook.display())))?;
+    let source_hash = SourceSnapshot::from_bytes(notebook, bytes, None)?.hash;
+    let doc_path = expected_doc_path(notebook, docs_dir);
+
+    let doc = match read(&doc_path)? {
+        Some(bytes) => Some(String::from_utf8(bytes).map_err(|_| DoxcerError::Input(format!("{} is not valid UTF-8", doc_path.display())))?),
+        None => None,
+    };
+
+    let status = doc_status(notebook, &source_hash, doc.as_deref(), manifest.get(notebook));
+    Ok(VerifyResult { notebook: notebook.to_path_buf(), doc_path, source_hash, status })
+}
+
+
+// ====================================================
+//  Reporting
+// ====================================================
+pub fn render_table(results: &[VerifyResult], style: &Style) -> String {
+
+    // Renders verification results as an aligned table.
+    //
+    // # Description
+    // Statuses are colored (`ok` green, `stale` yellow, `missing` red) when the
+    // given style is enabled; padding is applied before coloring so columns line
+    // up either way.
+
+    let width = results
+        .iter()
+        .map(|r| r.notebook.display().to_string().len())
+        .chain(std::iter::once("NOTEBOOK".len()))
+        .max()
+        .unwrap_or(0);
+
+    let mut out = format!("{:<8} {:<width$} DOC\n", "STATUS", "NOTEBOOK");
+    for r in results {
+        let color = match r.status {
+            DocStatus::Ok => Color::Green,
+            DocStatus::Stale => Color::Yellow,
+            DocStatus::Missing => Color::Red,
+        };
+        out.push_str(&format!(
+            "{} {:<width$} {}\n",
+            style.paint(&format!("{:<8}", r.status.to_string()), color),
+            r.notebook.display().to_string(),
+            r.doc_path.display()
+        ));
+    }
+    out
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provenance::{Provenance, stamp_provenance};
+
+    const HASH: &str = "sha256:3f2a";
+
+
+    fn doc_for(source: &str, source_hash: &str) -> String {
+        let provenance = Provenance { source: source.to_string(), source_hash: source_hash.to_string(), model: None, generated_at: None };
+        stamp_provenance("# load\n", &provenance)
+    }
+
+    fn entry(source_hash: &str) -> ManifestEntry {
+        ManifestEntry {
+            source_hash: source_hash.to_string(),
+            output: "docs/load.md".to_string(),
+            snapshot: None,
+            git_ref: None,
+            git_commit: None,
+            generated_at: None,
+        }
+    }
+
+
+    #[test]
+    fn matching_provenance_is_ok() {
+        let doc = doc_for("./a/load.py", HASH);
+        assert_eq!(doc_status(Path::new("a/load.py"), HASH, Some(&doc), None), DocStatus::Ok);
+    }
+
+
+    #[test]
+    fn a_changed_notebook_is_stale() {
+        let doc = doc_for("a/load.py", HASH);
+        assert_eq!(doc_status(Path::new("a/load.py"), "sha256:9c1d", Some(&doc), Some(&entry("sha256:9c1d"))), DocStatus::Stale);
+    }
+
+
+    #[test]
+    fn a_missing_document_is_missing() {
+        assert_eq!(doc_status(Path::new("a/load.py"), HASH, None, Some(&entry(HASH))), DocStatus::Missing);
+    }
+
+
+    #[test]
+    fn without_provenance_the_manifest_entry_decides() {
+        assert_eq!(doc_status(Path::new("a/load.py"), HASH, Some("# load\n"), Some(&entry(HASH))), DocStatus::Ok);
+        assert_eq!(doc_status(Path::new("a/load.py"), HASH, Some("# load\n"), Some(&entry("sha256:9c1d"))), DocStatus::Stale);
+        assert_eq!(doc_status(Path::new("a/load.py"), HASH, Some("# load\n"), None), DocStatus::Stale);
+    }
+
+
+    #[test]
+    fn a_document_for_another_source_is_stale() {
+        // Same content hash, but written for the notebook in another folder.
+        let doc = doc_for("b/load.py", HASH);
+        assert_eq!(doc_status(Path::new("a/load.py"), HASH, Some(&doc), Some(&entry(HASH))), DocStatus::Stale);
+    }
+
+
+    #[test]
+    fn notebooks_sharing_a_doc_path_are_refused() {
+        let docs = Path::new("docs");
+        let error = check_doc_paths(&[PathBuf::from("a/load.py"), PathBuf::from("b/load.py")], docs).unwrap_err();
+        assert_eq!(error.to_string(), "a/load.py and b/load.py are both documented in docs/load.md; rename one of them");
+        assert!(check_doc_paths(&[PathBuf::from("a/load.py"), PathBuf::from("export.zip!b/load.py")], docs).is_ok());
+    }
+}
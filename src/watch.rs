@@ -0,0 +1,215 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+// External Libraries
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+
+// Internal Modules
+use crate::error::DoxcerError;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+// How often the watch loop checks settled changes and finished runs.
+pub const DEFAULT_TICK_MS: u64 = 500;
+pub const DEFAULT_DEBOUNCE_MS: u64 = 300;
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+// Follows file changes below a set of paths with the operating system's file
+// notifications (the `notify` crate) and holds each change back until it
+// has settled.
+pub struct Watcher {
+    debounce: Duration,
+    events: Receiver<notify::Result<Event>>,
+    // Keeps the notifications coming; they stop when it is dropped.
+    _notifier: RecommendedWatcher,
+    // Files that changed and are waiting to settle, with their last change.
+    pending: BTreeMap<PathBuf, Instant>,
+}
+
+
+// ====================================================
+//  Watching
+// ====================================================
+impl Watcher {
+
+    pub fn new(paths: &[PathBuf], debounce: Duration) -> Result<Watcher, DoxcerError> {
+
+        // Starts watching `paths`, directories recursively; only later changes
+        // count. Changed files are reported by their canonical path.
+
+        let (sender, events) = mpsc::channel();
+        let mut notifier = notify::recommended_watcher(sender)
+            .map_err(|e| DoxcerError::Config(format!("Failed to start the file watcher: {e}")))?;
+        for path in paths {
+            let canonical = fs::canonicalize(path).map_err(|e| DoxcerError::Input(format!("Failed to watch {}: {e}", path.display())))?;
+            notifier
+                .watch(&canonical, RecursiveMode::Recursive)
+                .map_err(|e| DoxcerError::Input(format!("Failed to watch {}: {e}", path.display())))?;
+        }
+        Ok(Watcher { debounce, events, _notifier: notifier, pending: BTreeMap::new() })
+    }
+
+
+    pub fn wait(&mut self, timeout: Duration) -> Result<Vec<PathBuf>, DoxcerError> {
+
+        // Collects the file notifications that arrive within `timeout` and
+        // returns the files whose changes have settled (see [`Watcher::settled`]).
+        //
+        // # Returns
+        // * `Ok(Vec<PathBuf>)` with the settled files, often empty.
+        // * `Err(DoxcerError)` when the notifier reported an error; the changes
+        //   seen so far stay pending.
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            match self.events.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(Ok(event)) => self.record(&event, Instant::now()),
+                Ok(Err(e)) => return Err(DoxcerError::Input(format!("File watcher error: {e}"))),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return Err(DoxcerError::Input("The file watcher stopped".to_string())),
+            }
+        }
+        Ok(self.settled(Instant::now()))
+    }
+
+
+    pub(crate) fn record(&mut self, event: &Event, now: Instant) {
+
+        // Marks the files of an event as changed at `now`; reads (access
+        // events) are not changes.
+
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        for path in &event.paths {
+            self.pending.insert(path.clone(), now);
+        }
+    }
+
+
+    pub(crate) fn settled(&mut self, now: Instant) -> Vec<PathBuf> {
+
+        // Returns the changed files that have not changed for the debounce
+        // time, so an editor that saves in several writes triggers one run.
+        // Files that are gone by then are forgotten.
+
+        let ready: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, changed)| now.duration_since(**changed) >= self.debounce)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &ready {
+            self.pending.remove(path);
+        }
+        ready.into_iter().filter(|path| path.is_file()).collect()
+    }
+}
+
+
+pub fn canonical_paths(files: &[PathBuf]) -> BTreeMap<PathBuf, PathBuf> {
+
+    // Maps the canonical path of each file to the path it was found under, to
+    // look up the files the watcher reports.
+
+    files.iter().filter_map(|path| Some((fs::canonicalize(path).ok()?, path.clone()))).collect()
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, ModifyKind};
+    use std::thread;
+
+    fn watcher(dir: &tempfile::TempDir, debounce: Duration) -> Watcher {
+        Watcher::new(&[dir.path().to_path_buf()], debounce).unwrap()
+    }
+
+
+    fn event(kind: EventKind, path: &std::path::Path) -> Event {
+        Event::new(kind).add_path(path.to_path_buf())
+    }
+
+
+    #[test]
+    fn a_change_is_held_back_until_it_settles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = fs::canonicalize(dir.path()).unwrap().join("orders.py");
+        fs::write(&path, "x = 1\n").unwrap();
+        let mut watcher = watcher(&dir, Duration::from_millis(300));
+        let start = Instant::now();
+
+        watcher.record(&event(EventKind::Create(CreateKind::File), &path), start);
+        watcher.record(&event(EventKind::Modify(ModifyKind::Any), &path), start + Duration::from_millis(200));
+        assert!(watcher.settled(start + Duration::from_millis(400)).is_empty());
+        assert_eq!(watcher.settled(start + Duration::from_millis(500)), vec![path.clone()]);
+        assert!(watcher.settled(start + Duration::from_secs(5)).is_empty());
+    }
+
+
+    #[test]
+    fn reads_and_removed_files_are_not_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        let kept = root.join("orders.py");
+        fs::write(&kept, "x = 1\n").unwrap();
+        let mut watcher = watcher(&dir, Duration::ZERO);
+        let now = Instant::now();
+
+        watcher.record(&event(EventKind::Access(AccessKind::Any), &kept), now);
+        watcher.record(&event(EventKind::Modify(ModifyKind::Any), &root.join("gone.py")), now);
+        assert!(watcher.settled(now).is_empty());
+        assert!(watcher.pending.is_empty());
+    }
+
+
+    #[test]
+    fn a_written_file_is_reported_once_by_its_canonical_path() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sales")).unwrap();
+        let mut watcher = watcher(&dir, Duration::from_millis(50));
+        let path = dir.path().join("sales/orders.py");
+        for part in ["x = 1\n", "x = 1\ny = 2\n"] {
+            fs::write(&path, part).unwrap();
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let mut reported = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while reported.is_empty() && Instant::now() < deadline {
+            reported = watcher.wait(Duration::from_millis(100)).unwrap();
+        }
+        assert_eq!(reported, vec![fs::canonicalize(&path).unwrap()]);
+        assert!(watcher.wait(Duration::from_millis(200)).unwrap().is_empty());
+        assert_eq!(canonical_paths(std::slice::from_ref(&path)).get(&reported[0]), Some(&path));
+    }
+
+
+    #[test]
+    fn watching_a_missing_path_is_an_input_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let error = Watcher::new(&[dir.path().join("missing")], Duration::ZERO).err().unwrap();
+        assert!(matches!(error, DoxcerError::Input(_)));
+        assert!(error.to_string().starts_with("Failed to watch"), "{error}");
+    }
+}
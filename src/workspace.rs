@@ -0,0 +1,252 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Internal Modules
+use crate::error::DoxcerError;
+use crate::provenance::content_hash;
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const DEFAULT_CACHE_DIR: &str = ".doxcer/cache";
+pub const WORKSPACES_DIR: &str = "workspaces";
+pub const DEFAULT_MAX_AGE_HOURS: u64 = 24 * 7;
+pub const ATTEMPT_GROUP_ARTIFACT: &str = "attempt_group";
+
+
+// ====================================================
+//  Data Structures
+// ====================================================
+#[derive(Debug, Clone)]
+pub struct RunWorkspace {
+    pub base: PathBuf,
+    pub run_id: String,
+    pub root: PathBuf,
+    pub keep: bool,
+}
+
+
+// ====================================================
+//  Workspace Lifecycle
+// ====================================================
+pub fn default_workspace_base() -> PathBuf {
+
+    // Returns the directory that holds all run workspaces (`.doxcer/cache/workspaces`).
+
+    Path::new(DEFAULT_CACHE_DIR).join(WORKSPACES_DIR)
+}
+
+
+pub fn new_run_id() -> String {
+
+    // Creates a run id that sorts chronologically and is unique per process.
+
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format!("run-{secs}-{}", process::id())
+}
+
+
+pub fn max_age_from_env() -> Result<Duration, DoxcerError> {
+
+    // Reads the workspace garbage-collection age from `DOXCER_WORKDIR_MAX_AGE_HOURS`.
+
+    match env::var("DOXCER_WORKDIR_MAX_AGE_HOURS") {
+        Ok(v) => v
+            .trim()
+            .parse::<u64>()
+            .map(|h| Duration::from_secs(h * 3600))
+            .map_err(|_| DoxcerError::Input(format!("DOXCER_WORKDIR_MAX_AGE_HOURS must be a whole number of hours, got {v:?}"))),
+        Err(_) => Ok(Duration::from_secs(DEFAULT_MAX_AGE_HOURS * 3600)),
+    }
+}
+
+
+pub fn idempotency_key(attempt_group: &str, source_hash: &str, model: &str) -> String {
+
+    // Derives the `Idempotency-Key` of a logical request.
+    //
+    // # Description
+    // Deterministic in the run that first attempted the notebook, the notebook
+    // hash and the model, so every retry of the same request (and a `--resume`
+    // of it) sends the same key, while a fallback model gets a key of its own.
+
+    let hash = content_hash(format!("{attempt_group}\n{source_hash}\n{model}").as_bytes());
+    let hex = hash.split_once(':').map(|(_, h)| h).unwrap_or(&hash);
+    format!("doxcer-{}", &hex[..32.min(hex.len())])
+}
+
+
+impl RunWorkspace {
+
+    pub fn create(base: &Path, run_id: &str, keep: bool) -> Result<RunWorkspace, DoxcerError> {
+
+        // Creates the per-run directory `<base>/<run_id>`.
+        //
+        // # Parameters
+        // * `base` – The workspace base (cache dir or `--workdir`).
+        // * `run_id` – Identifier of this run, see [`new_run_id`].
+        // * `keep` – Keep the directory after a successful run (`--keep-workdir`).
+
+        let root = base.join(run_id);
+        fs::create_dir_all(&root)
+            .map_err(|e| DoxcerError::Input(format!("Failed to create workspace {}: {e}", root.display())))?;
+        Ok(RunWorkspace { base: base.to_path_buf(), run_id: run_id.to_string(), root, keep })
+    }
+
+
+    pub fn artifact_path(&self, source_hash: &str, name: &str) -> PathBuf {
+
+        // Returns the deterministic path of an artifact for a notebook.
+        //
+        // # Description
+        // Artifacts live in `<root>/<hash>/<name>`, where `<hash>` is the notebook's
+        // content hash without its `sha256:` prefix, so a later run can find them
+        // again for the same content.
+
+        self.root.join(hash_dir(source_hash)).join(name)
+    }
+
+
+    pub fn write_artifact(&self, source_hash: &str, name: &str, contents: &str) -> Result<PathBuf, DoxcerError> {
+
+        // Writes an artifact atomically (temp file + rename), so a crash never
+        // leaves a half-written file that a later `--resume` would pick up.
+
+        let path = self.artifact_path(source_hash, name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| DoxcerError::Input(format!("Failed to create {}: {e}", parent.display())))?;
+        }
+        let tmp = path.with_extension("partial");
+        fs::write(&tmp, contents).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", tmp.display())))?;
+        fs::rename(&tmp, &path).map_err(|e| DoxcerError::Input(format!("Failed to write {}: {e}", path.display())))?;
+        Ok(path)
+    }
+
+
+    pub fn previous_artifacts(&self, source_hash: &str, name: &str) -> Vec<PathBuf> {
+
+        // Lists artifacts produced by earlier runs for the same notebook content.
+        //
+        // # Description
+        // Used by `--resume`: scans the other workspaces under the same base and
+        // returns every completed artifact with this name, newest run first.
+
+        let Ok(entries) = fs::read_dir(&self.base) else {
+            return Vec::new();
+        };
+        let mut runs: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.is_dir() && *p != self.root)
+            .collect();
+        runs.sort();
+        runs.into_iter()
+            .rev()
+            .map(|run| run.join(hash_dir(source_hash)).join(name))
+            .filter(|p| p.is_file())
+            .collect()
+    }
+
+
+    pub fn attempt_group(&self, source_hash: &str, resume: bool) -> Result<String, DoxcerError> {
+
+        // Returns the attempt group of a notebook: the run that first attempted it.
+        //
+        // # Description
+        // Normally this run. With `resume`, the group recorded by the newest
+        // earlier run for the same content is inherited, so a resumed request keeps
+        // its idempotency key. The group is stored as an artifact the first time.
+
+        let path = self.artifact_path(source_hash, ATTEMPT_GROUP_ARTIFACT);
+        if let Ok(group) = fs::read_to_string(&path) {
+            return Ok(group);
+        }
+        let inherited = resume
+            .then(|| self.previous_artifacts(source_hash, ATTEMPT_GROUP_ARTIFACT))
+            .and_then(|previous| previous.first().and_then(|p| fs::read_to_string(p).ok()));
+        let group = inherited.unwrap_or_else(|| self.run_id.clone());
+        self.write_artifact(source_hash, ATTEMPT_GROUP_ARTIFACT, &group)?;
+        Ok(group)
+    }
+
+
+    pub fn finish(self, success: bool) -> Result<Option<PathBuf>, DoxcerError> {
+
+        // Ends the run: removes the workspace after success unless it should be kept.
+        //
+        // # Returns
+        // * `Ok(None)` when the workspace was removed.
+        // * `Ok(Some(path))` when the workspace was kept (failure or `--keep-workdir`).
+
+        if success && !self.keep {
+            fs::remove_dir_all(&self.root)
+                .map_err(|e| DoxcerError::Input(format!("Failed to remove workspace {}: {e}", self.root.display())))?;
+            return Ok(None);
+        }
+        Ok(Some(self.root))
+    }
+}
+
+
+// ====================================================
+//  Garbage Collection
+// ====================================================
+pub fn gc_workspaces(base: &Path, max_age: Duration, current: Option<&str>) -> Result<Vec<PathBuf>, DoxcerError> {
+
+    // Removes run workspaces that were last modified longer than `max_age` ago.
+    //
+    // # Description
+    // Called on startup so workspaces left behind by crashed or failed runs do not
+    // pile up. The current run (if given) is never removed.
+    //
+    // # Returns
+    // * `Ok(Vec<PathBuf>)` with the removed workspaces.
+    // * `Err(DoxcerError)` if a workspace could not be removed.
+
+    let Ok(entries) = fs::read_dir(base) else {
+        return Ok(Vec::new());
+    };
+
+    let now = SystemTime::now();
+    let mut removed = Vec::new();
+    for path in entries.filter_map(|e| e.ok().map(|e| e.path())) {
+        let is_current = current.is_some_and(|c| path.file_name().is_some_and(|n| n == c));
+        if !path.is_dir() || is_current {
+            continue;
+        }
+        let age = fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|m| now.duration_since(m).ok())
+            .unwrap_or_default();
+        if age > max_age {
+            fs::remove_dir_all(&path)
+                .map_err(|e| DoxcerError::Input(format!("Failed to remove old workspace {}: {e}", path.display())))?;
+            removed.push(path);
+        }
+    }
+    Ok(removed)
+}
+
+
+fn hash_dir(source_hash: &str) -> &str {
+
+    // Strips the algorithm prefix from a content hash for use as a directory name.
+
+    source_hash.split_once(':').map(|(_, h)| h).unwrap_or(source_hash)
+}
@@ -0,0 +1,262 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::env;
+use std::io::{self, IsTerminal};
+
+
+// ====================================================
+//  Constants
+// ====================================================
+pub const DEFAULT_WRAP_WIDTH: usize = 80;
+pub const MIN_WRAP_WIDTH: usize = 20;
+
+
+// ====================================================
+//  Width
+// ====================================================
+pub fn display_width(requested: Option<usize>) -> Option<usize> {
+
+    // Decides the soft-wrap width for stdout.
+    //
+    // # Description
+    // Wrapping is for display only, so it applies only when stdout is a
+    // terminal; files and pipes always get the Markdown unchanged. Without
+    // `--wrap`, the terminal width from `COLUMNS` is used (80 if unset), and
+    // `--wrap 0` turns wrapping off.
+    //
+    // # Returns
+    // * `Some(width)` when output should be wrapped, `None` otherwise.
+
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    let width = requested.unwrap_or_else(|| {
+        env::var("COLUMNS")
+            .ok()
+            .and_then(|c| c.trim().parse().ok())
+            .unwrap_or(DEFAULT_WRAP_WIDTH)
+    });
+    (width > 0).then_some(width.max(MIN_WRAP_WIDTH))
+}
+
+
+// ====================================================
+//  Soft Wrapping
+// ====================================================
+pub fn soft_wrap(markdown: &str, width: usize) -> String {
+
+    // Wraps the prose lines of a Markdown document at word boundaries.
+    //
+    // # Description
+    // Fenced code blocks, tables, indented code, headings and the front-matter
+    // block are left as they are. List items and block quotes are continued
+    // under their text. A line never breaks inside an inline code span or a
+    // link target, so a long URL stays on one (overlong) line.
+
+    let mut out = String::new();
+    let mut fence: Option<&str> = None;
+    let mut in_front_matter = markdown.starts_with("---\n");
+
+    for (i, line) in markdown.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if in_front_matter {
+            in_front_matter = i == 0 || trimmed != "---";
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        }
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+        }
+        let verbatim = fence.is_some()
+            || trimmed.starts_with('|')
+            || trimmed.starts_with('#')
+            || line.starts_with("    ")
+            || line.starts_with('\t')
+            || line.chars().count() <= width;
+        if verbatim {
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        }
+        wrap_line(line, width, &mut out);
+    }
+    out
+}
+
+
+fn wrap_line(line: &str, width: usize, out: &mut String) {
+
+    // Wraps one prose line, continuing under the text of a list item or quote.
+
+    let prefix_len = line.len() - line.trim_start().len() + marker_len(line.trim_start());
+    let (first_prefix, text) = line.split_at(prefix_len);
+    let indent = " ".repeat(first_prefix.chars().count());
+    let continuation = if first_prefix.trim_start().starts_with('>') { first_prefix.to_string() } else { indent };
+
+    let mut current = first_prefix.to_string();
+    let mut has_word = false;
+    for word in words(text) {
+        let fits = current.chars().count() + 1 + word.chars().count() <= width;
+        if has_word && !fits {
+            out.push_str(current.trim_end());
+            out.push('\n');
+            current = continuation.clone();
+            has_word = false;
+        }
+        if has_word {
+            current.push(' ');
+        }
+        current.push_str(word);
+        has_word = true;
+    }
+    out.push_str(current.trim_end());
+    out.push('\n');
+}
+
+
+fn marker_len(text: &str) -> usize {
+
+    // Returns the length of a leading list marker (`- `, `* `, `1. `) or quote (`> `).
+
+    let bytes = text.as_bytes();
+    if let [b'-' | b'*' | b'+' | b'>', b' ', ..] = bytes {
+        return 2;
+    }
+    let digits = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+    if digits > 0 && matches!(bytes.get(digits..digits + 2), Some([b'.' | b')', b' '])) {
+        return digits + 2;
+    }
+    0
+}
+
+
+fn words(text: &str) -> Vec<&str> {
+
+    // Splits text at spaces, except inside inline code spans and link targets.
+
+    let mut words = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut code_ticks = 0;
+    let mut in_target = false;
+    let mut prev = ' ';
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let (pos, c) = chars[i];
+        if c == '`' {
+            let run = chars[i..].iter().take_while(|(_, c)| *c == '`').count();
+            if code_ticks == 0 {
+                code_ticks = run;
+            } else if code_ticks == run {
+                code_ticks = 0;
+            }
+            start.get_or_insert(pos);
+            i += run;
+            prev = '`';
+            continue;
+        }
+        if code_ticks == 0 {
+            if c == '(' && prev == ']' {
+                in_target = true;
+            } else if c == ')' && in_target {
+                in_target = false;
+            }
+        }
+        let breakable = c == ' ' && code_ticks == 0 && !in_target;
+        match (breakable, start) {
+            (true, Some(s)) => {
+                words.push(&text[s..pos]);
+                start = None;
+            }
+            (false, None) => start = Some(pos),
+            _ => {}
+        }
+        prev = c;
+        i += 1;
+    }
+    if let Some(s) = start {
+        words.push(&text[s..]);
+    }
+    words
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn widest(text: &str) -> usize {
+        text.lines().map(|l| l.chars().count()).max().unwrap_or(0)
+    }
+
+
+    #[test]
+    fn wraps_prose_at_word_boundaries() {
+        let wrapped = soft_wrap("De notebook laadt projecten uit bronze en schrijft ze naar de gold laag.\n", 30);
+        assert_eq!(wrapped, "De notebook laadt projecten\nuit bronze en schrijft ze naar\nde gold laag.\n");
+        assert!(widest(&wrapped) <= 30);
+    }
+
+
+    #[test]
+    fn never_breaks_inside_inline_code() {
+        let wrapped = soft_wrap("Roep `spark.read.table(\"bronze projects\")` aan voor de bron.\n", 20);
+        assert!(wrapped.lines().any(|l| l == "`spark.read.table(\"bronze projects\")`"), "{wrapped}");
+        let wrapped = soft_wrap("Gebruik ``code met ` erin`` hier en daar.\n", 12);
+        assert!(wrapped.contains("``code met ` erin``"), "{wrapped}");
+    }
+
+
+    #[test]
+    fn never_breaks_inside_link_targets() {
+        let wrapped = soft_wrap("Zie [de config](<./config/my settings.json>) voor alle opties van de run.\n", 24);
+        // The link text is prose and may wrap; the target may not.
+        assert!(wrapped.contains("config](<./config/my settings.json>)"), "{wrapped}");
+        let url = "https://example.com/a/very/long/path/that/does/not/fit";
+        let wrapped = soft_wrap(&format!("Lees [dit]({url}) eerst.\n"), 20);
+        assert!(wrapped.lines().any(|l| l == format!("[dit]({url})")), "{wrapped}");
+    }
+
+
+    #[test]
+    fn leaves_code_tables_headings_and_front_matter_alone() {
+        let long = "woord ".repeat(20);
+        let markdown = format!(
+            "---\ndoxcer_source: {long}\n---\n# {long}\n```python\nx = '{long}'\n```\n| a | {long} |\n    indented {long}\n"
+        );
+        assert_eq!(soft_wrap(&markdown, 30), markdown);
+    }
+
+
+    #[test]
+    fn continues_list_items_and_quotes_under_their_text() {
+        let wrapped = soft_wrap("- eerste punt met veel te veel woorden erin\n> een citaat met veel te veel woorden\n", 20);
+        assert_eq!(wrapped, "- eerste punt met\n  veel te veel\n  woorden erin\n> een citaat met\n> veel te veel\n> woorden\n");
+        let wrapped = soft_wrap("12. genummerd punt met te veel woorden\n", 20);
+        assert_eq!(wrapped, "12. genummerd punt\n    met te veel\n    woorden\n");
+    }
+
+
+    #[test]
+    fn short_lines_are_unchanged() {
+        assert_eq!(soft_wrap("kort\n\nook kort\n", 20), "kort\n\nook kort\n");
+    }
+}
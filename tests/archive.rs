@@ -0,0 +1,160 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-15
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::process::{Command, Output};
+use std::thread;
+
+// External Libraries
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+
+// ====================================================
+//  Fixture
+// ====================================================
+const MEMBERS: [(&str, &str); 4] = [
+    ("ws/Orders.Notebook/notebook-content.py", "df = spark.read.table('orders')\n"),
+    ("ws/sales/customers.py", "df = spark.read.table('customers')\n"),
+    ("ws/sales/old/refunds.py", "df = spark.read.table('refunds')\n"),
+    ("ws/readme.md", "# Workspace\n"),
+];
+
+fn provider() -> String {
+
+    // Starts a provider that answers every request with a document naming the
+    // table the notebook reads.
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            thread::spawn(move || answer(stream));
+        }
+    });
+    url
+}
+
+fn answer(mut stream: TcpStream) {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 65536];
+    while let Ok(n) = stream.read(&mut buffer) {
+        request.extend_from_slice(&buffer[..n]);
+        if n == 0 || request.ends_with(b"}") {
+            break;
+        }
+    }
+    let request = String::from_utf8_lossy(&request);
+    let table = ["orders", "customers", "refunds"].into_iter().find(|table| request.contains(&format!("table('{table}')"))).unwrap_or("unknown");
+    let body = format!(r##"{{"response": "# {table}\n\nReads the {table} table.\n", "prompt_eval_count": 10, "eval_count": 5}}"##);
+    let response = format!("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len());
+    let _ = stream.write_all(response.as_bytes());
+}
+
+fn project() -> tempfile::TempDir {
+
+    // A project holding `export.zip`, a real archive written with the zip
+    // crate, with stored and deflated members.
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join(".env"), "").unwrap();
+    let mut zip = ZipWriter::new(File::create(dir.path().join("export.zip")).unwrap());
+    for (index, (name, text)) in MEMBERS.iter().enumerate() {
+        let method = if index % 2 == 0 { CompressionMethod::Deflated } else { CompressionMethod::Stored };
+        zip.start_file(*name, SimpleFileOptions::default().compression_method(method)).unwrap();
+        zip.write_all(text.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+    dir
+}
+
+fn generate(dir: &Path, url: &str, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_doxcer"))
+        .arg("generate")
+        .args(args)
+        .args(["--provider", "ollama", "--model", "m", "--no-cache", "--no-timestamps"])
+        .current_dir(dir)
+        .env("OLLAMA_HOST", url)
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap()
+}
+
+fn written(dir: &Path) -> Vec<String> {
+
+    // The documents below `docs`, as paths relative to it.
+
+    let mut found = Vec::new();
+    let mut pending = vec![dir.join("docs")];
+    while let Some(folder) = pending.pop() {
+        for entry in fs::read_dir(&folder).into_iter().flatten().flatten() {
+            let path = entry.path();
+            match path.is_dir() {
+                true => pending.push(path),
+                false => found.push(path.strip_prefix(dir.join("docs")).unwrap().to_string_lossy().replace('\\', "/")),
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[test]
+fn a_plain_archive_documents_every_notebook_in_it() {
+    let url = provider();
+    let dir = project();
+    let output = generate(dir.path(), &url, &["export.zip", "--output", "docs"]);
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(written(dir.path()), ["ws/Orders.md", "ws/sales/customers.md", "ws/sales/old/refunds.md"]);
+    let customers = fs::read_to_string(dir.path().join("docs/ws/sales/customers.md")).unwrap();
+    assert!(customers.contains("Reads the customers table."), "{customers}");
+    assert!(customers.contains("export.zip!ws/sales/customers.py"), "{customers}");
+}
+
+
+#[test]
+fn a_folder_in_an_archive_documents_the_notebooks_below_it() {
+    let url = provider();
+    let dir = project();
+    let output = generate(dir.path(), &url, &["export.zip!ws/sales", "--output", "docs", "--exclude", "old/"]);
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(written(dir.path()), ["ws/sales/customers.md"]);
+}
+
+
+#[test]
+fn a_member_of_an_archive_is_a_single_notebook() {
+    let url = provider();
+    let dir = project();
+    let output = generate(dir.path(), &url, &["export.zip!ws/Orders.Notebook/notebook-content.py"]);
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("# orders\n\nReads the orders table."), "{stdout}");
+
+    let missing = generate(dir.path(), &url, &["export.zip!ws/missing.py"]);
+    assert_eq!(missing.status.code(), Some(4));
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("ws/missing.py does not exist in export.zip"));
+}
+
+
+#[test]
+fn an_archive_cannot_join_other_paths_in_a_batch() {
+    let url = provider();
+    let dir = project();
+    fs::write(dir.path().join("orders.py"), "x = 1\n").unwrap();
+    let output = generate(dir.path(), &url, &["export.zip", "orders.py"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("got export.zip"));
+}
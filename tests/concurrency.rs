@@ -0,0 +1,145 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::process::{Command, Output};
+use std::thread;
+use std::time::Duration;
+
+
+// ====================================================
+//  Fixture
+// ====================================================
+const TABLES: [&str; 6] = ["orders", "customers", "broken_refunds", "products", "broken_stock", "invoices"];
+
+fn provider() -> String {
+
+    // Starts a provider that answers requests in parallel, each after a short
+    // delay so that a concurrent batch overlaps them. A notebook reading a
+    // `broken_*` table gets a 500, any other a document naming its table.
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            thread::spawn(move || answer(stream));
+        }
+    });
+    url
+}
+
+fn answer(mut stream: TcpStream) {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 65536];
+    while let Ok(n) = stream.read(&mut buffer) {
+        request.extend_from_slice(&buffer[..n]);
+        if n == 0 || request.ends_with(b"}") {
+            break;
+        }
+    }
+    let request = String::from_utf8_lossy(&request);
+    let table = TABLES.iter().find(|table| request.contains(&format!("table('{table}')"))).copied().unwrap_or("unknown");
+    thread::sleep(Duration::from_millis(150));
+    let (status, body) = match table.starts_with("broken") {
+        true => ("500 Internal Server Error", "boom".to_string()),
+        false => ("200 OK", format!(r##"{{"response": "# {table}\n\nReads the {table} table.\n", "prompt_eval_count": 10, "eval_count": 5}}"##)),
+    };
+    let response = format!("HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len());
+    let _ = stream.write_all(response.as_bytes());
+}
+
+fn project() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join(".env"), "").unwrap();
+    for table in TABLES {
+        fs::write(dir.path().join(format!("{table}.py")), format!("df = spark.read.table('{table}')\n")).unwrap();
+    }
+    dir
+}
+
+fn batch(dir: &Path, url: &str, extra: &[&str]) -> Output {
+    let notebooks: Vec<String> = TABLES.iter().map(|table| format!("{table}.py")).collect();
+    Command::new(env!("CARGO_BIN_EXE_doxcer"))
+        .arg("generate")
+        .args(&notebooks)
+        .args(["--provider", "ollama", "--model", "m", "--no-cache", "--no-timestamps", "--retry-max-attempts", "1"])
+        .args(extra)
+        .current_dir(dir)
+        .env("OLLAMA_HOST", url)
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap()
+}
+
+fn docs(dir: &Path) -> Vec<(String, String)> {
+
+    // The written documents by name, without their front matter (which holds
+    // the run id).
+
+    let mut docs: Vec<(String, String)> = fs::read_dir(dir.join("docs"))
+        .unwrap()
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "md"))
+        .map(|entry| {
+            let text = fs::read_to_string(entry.path()).unwrap();
+            let body = text.rsplit_once("\n---\n").map_or(text.clone(), |(_, body)| body.to_string());
+            (entry.file_name().to_string_lossy().to_string(), body)
+        })
+        .collect();
+    docs.sort();
+    docs
+}
+
+fn failures(output: &Output) -> Vec<String> {
+
+    // The stderr lines naming a failed notebook, in the order printed.
+
+    String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter(|line| line.contains("broken_"))
+        .map(str::to_string)
+        .collect()
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[test]
+fn a_concurrent_batch_writes_the_same_documents_and_exit_code() {
+    let url = provider();
+    let (serial, parallel) = (project(), project());
+    let one = batch(serial.path(), &url, &["--output", "docs", "--concurrency", "1"]);
+    let four = batch(parallel.path(), &url, &["--output", "docs", "--concurrency", "4"]);
+
+    assert_eq!(one.status.code(), Some(1), "{}", String::from_utf8_lossy(&one.stderr));
+    assert_eq!(four.status.code(), one.status.code(), "{}", String::from_utf8_lossy(&four.stderr));
+    let written = docs(serial.path());
+    assert_eq!(written.len(), 4);
+    assert_eq!(docs(parallel.path()), written);
+    assert!(!failures(&one).is_empty(), "{}", String::from_utf8_lossy(&one.stderr));
+    assert_eq!(failures(&four), failures(&one));
+}
+
+
+#[test]
+fn a_concurrent_batch_prints_to_stdout_in_input_order() {
+    let url = provider();
+    let dir = project();
+    let one = batch(dir.path(), &url, &["--concurrency", "1"]);
+    let four = batch(dir.path(), &url, &["--concurrency", "4"]);
+
+    assert_eq!(four.status.code(), one.status.code());
+    let stdout = String::from_utf8_lossy(&four.stdout);
+    assert_eq!(stdout, String::from_utf8_lossy(&one.stdout));
+    let headings: Vec<&str> = stdout.lines().filter(|line| line.starts_with("# ")).collect();
+    assert_eq!(headings, ["# orders", "# customers", "# products", "# invoices"]);
+}
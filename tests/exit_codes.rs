@@ -0,0 +1,321 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Internal Modules
+use doxcer::provenance::content_hash;
+
+
+// ====================================================
+//  Fixture
+// ====================================================
+fn project() -> tempfile::TempDir {
+
+    // An empty project with a `.env` and one small notebook.
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join(".env"), "").unwrap();
+    fs::write(dir.path().join("orders.py"), "df = spark.read.table('orders')\n").unwrap();
+    dir
+}
+
+fn doxcer(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_doxcer"))
+        .args(args)
+        .current_dir(dir)
+        .env_remove("OLLAMA_HOST")
+        .output()
+        .unwrap()
+}
+
+fn failing_provider() -> String {
+
+    // Starts a provider that answers every request with a 500 and returns its
+    // URL, for use as `OLLAMA_HOST`.
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = [0u8; 65536];
+            let _ = stream.read(&mut request);
+            let _ = stream.write_all(b"HTTP/1.1 500 Internal Server Error\r\nContent-Length: 4\r\nConnection: close\r\n\r\nboom");
+        }
+    });
+    url
+}
+
+fn rate_limited_provider(retry_after: &'static str) -> (String, mpsc::Receiver<()>) {
+
+    // Starts a provider that answers every request with a 429 asking to retry
+    // after `retry_after`, and signals each request it has received.
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (received, requests) = mpsc::channel();
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = [0u8; 65536];
+            let _ = stream.read(&mut request);
+            let _ = received.send(());
+            let response = format!("HTTP/1.1 429 Too Many Requests\r\nRetry-After: {retry_after}\r\nContent-Length: 4\r\nConnection: close\r\n\r\nslow");
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    (url, requests)
+}
+
+fn silent_provider() -> (String, mpsc::Receiver<()>) {
+
+    // Starts a provider that reads every request but never answers, and
+    // signals each request it has received.
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (received, requests) = mpsc::channel();
+    thread::spawn(move || {
+        let mut open = Vec::new();
+        for mut stream in listener.incoming().flatten() {
+            let mut request = [0u8; 65536];
+            let _ = stream.read(&mut request);
+            let _ = received.send(());
+            open.push(stream);
+        }
+    });
+    (url, requests)
+}
+
+fn write_doc(dir: &Path, source_hash: &str) {
+
+    // Writes `docs/orders.md` with provenance claiming `source_hash`.
+
+    fs::create_dir_all(dir.join("docs")).unwrap();
+    let doc = format!("---\ndoxcer_source: orders.py\ndoxcer_source_hash: {source_hash}\n---\n# Orders\n");
+    fs::write(dir.join("docs").join("orders.md"), doc).unwrap();
+}
+
+fn assert_exit(output: &Output, code: i32) {
+    assert_eq!(output.status.code(), Some(code), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[test]
+fn explaining_the_exit_codes_succeeds() {
+    let dir = project();
+    let output = doxcer(dir.path(), &["--explain-exit-codes"]);
+    assert_exit(&output, 0);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("130  interrupted"));
+}
+
+
+#[test]
+fn stale_documents_are_incomplete() {
+    let dir = project();
+    let output = doxcer(dir.path(), &["verify", "orders.py"]);
+    assert_exit(&output, 1);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("stale or missing"));
+}
+
+
+#[test]
+fn check_passes_on_an_up_to_date_document() {
+    let dir = project();
+    let source = fs::read(dir.path().join("orders.py")).unwrap();
+    write_doc(dir.path(), &content_hash(&source));
+    let output = doxcer(dir.path(), &["check", "orders.py"]);
+    assert_exit(&output, 0);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("ok"));
+}
+
+
+#[test]
+fn check_fails_on_a_stale_document() {
+    let dir = project();
+    write_doc(dir.path(), "sha256:0000");
+    let output = doxcer(dir.path(), &["check", "orders.py"]);
+    assert_exit(&output, 1);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("stale"));
+}
+
+
+#[test]
+fn check_fails_on_a_missing_document() {
+    let dir = project();
+    let output = doxcer(dir.path(), &["check", "orders.py"]);
+    assert_exit(&output, 1);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("missing"));
+}
+
+
+#[test]
+fn check_refuses_to_fix() {
+    let dir = project();
+    for flag in ["--fix", "--retry-failed"] {
+        let output = doxcer(dir.path(), &["check", flag, "orders.py"]);
+        assert_exit(&output, 2);
+        assert!(String::from_utf8_lossy(&output.stderr).contains(&format!("doxcer verify {flag}")));
+    }
+    assert!(!dir.path().join("docs").exists());
+}
+
+
+#[test]
+fn an_unknown_flag_is_a_usage_error() {
+    let dir = project();
+    assert_exit(&doxcer(dir.path(), &["--bogus"]), 2);
+}
+
+
+#[test]
+fn copy_with_more_than_one_notebook_is_a_usage_error() {
+    let dir = project();
+    fs::write(dir.path().join("customers.py"), "df = spark.read.table('customers')\n").unwrap();
+    for args in [&["generate", "orders.py", "customers.py", "--copy"][..], &["generate", ".", "--copy"]] {
+        let output = doxcer(dir.path(), &[args, &["--provider", "ollama", "--model", "m"]].concat());
+        assert_exit(&output, 2);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("--copy puts a single document on the clipboard"));
+    }
+}
+
+
+#[test]
+fn a_broken_config_file_is_a_config_error() {
+    let dir = project();
+    fs::write(dir.path().join("doxcer.toml"), "provider = [\n").unwrap();
+    assert_exit(&doxcer(dir.path(), &["generate", "orders.py"]), 3);
+}
+
+
+#[test]
+fn a_missing_notebook_is_an_input_error() {
+    let dir = project();
+    let output = doxcer(dir.path(), &["generate", "missing.py", "--provider", "ollama", "--model", "m"]);
+    assert_exit(&output, 4);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("missing.py"));
+}
+
+
+#[test]
+fn a_failing_provider_is_a_provider_error() {
+    let dir = project();
+    let output = Command::new(env!("CARGO_BIN_EXE_doxcer"))
+        .args(["generate", "orders.py", "--provider", "ollama", "--model", "m", "--retry-max-attempts", "1", "--no-cache"])
+        .current_dir(dir.path())
+        .env("OLLAMA_HOST", failing_provider())
+        .output()
+        .unwrap();
+    assert_exit(&output, 5);
+    assert!(!dir.path().join("docs/orders.md").exists());
+}
+
+
+#[test]
+fn a_missing_env_file_only_matters_for_a_provider_with_a_key() {
+    let dir = project();
+    fs::remove_file(dir.path().join(".env")).unwrap();
+    let run = |provider: &str| {
+        Command::new(env!("CARGO_BIN_EXE_doxcer"))
+            .args(["generate", "orders.py", "--provider", provider, "--model", "m", "--retry-max-attempts", "1", "--no-cache"])
+            .current_dir(dir.path())
+            .env("OLLAMA_HOST", failing_provider())
+            .env_remove("DOXCER_ENV_PATH")
+            .env_remove("ANTHROPIC_API_KEY")
+            .env_remove("ANTHROPIC_API_KEY_ENC")
+            .output()
+            .unwrap()
+    };
+
+    let ollama = run("ollama");
+    assert_exit(&ollama, 5);
+    assert!(!String::from_utf8_lossy(&ollama.stderr).contains(".env"), "{}", String::from_utf8_lossy(&ollama.stderr));
+
+    let anthropic = run("anthropic");
+    assert_exit(&anthropic, 3);
+    assert!(String::from_utf8_lossy(&anthropic.stderr).contains("Could not find a .env file"));
+}
+
+
+#[test]
+fn a_retry_after_over_a_minute_fails_without_waiting() {
+    let dir = project();
+    let (url, requests) = rate_limited_provider("600");
+    let started = Instant::now();
+    let output = Command::new(env!("CARGO_BIN_EXE_doxcer"))
+        .args(["generate", "orders.py", "--provider", "ollama", "--model", "m", "--no-cache"])
+        .current_dir(dir.path())
+        .env("OLLAMA_HOST", url)
+        .output()
+        .unwrap();
+    assert_exit(&output, 5);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("the API asked to retry after 600s (Retry-After), longer than the 60s doxcer waits"), "{stderr}");
+    assert_eq!(requests.try_iter().count(), 1);
+    assert!(started.elapsed() < Duration::from_secs(20));
+}
+
+
+#[test]
+fn a_prompt_over_the_token_limit_is_a_limit_error() {
+    let dir = project();
+    let output = doxcer(dir.path(), &["generate", "orders.py", "--provider", "ollama", "--model", "m", "--max-total-tokens", "1"]);
+    assert_exit(&output, 6);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--max-total-tokens"));
+}
+
+
+#[test]
+fn a_file_over_its_timeout_is_a_provider_error() {
+    let dir = project();
+    let (url, _requests) = silent_provider();
+    let started = Instant::now();
+    let output = Command::new(env!("CARGO_BIN_EXE_doxcer"))
+        .args(["generate", "orders.py", "--provider", "ollama", "--model", "m", "--no-cache", "--file-timeout", "1"])
+        .current_dir(dir.path())
+        .env("OLLAMA_HOST", url)
+        .output()
+        .unwrap();
+    assert_exit(&output, 5);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("timed out after 1s (--file-timeout)"));
+    assert!(started.elapsed() < Duration::from_secs(20));
+}
+
+
+#[cfg(unix)]
+#[test]
+fn ctrl_c_during_a_request_is_interrupted() {
+    let dir = project();
+    let (url, requests) = silent_provider();
+    let child = Command::new(env!("CARGO_BIN_EXE_doxcer"))
+        .args(["generate", "orders.py", "--provider", "ollama", "--model", "m", "--no-cache"])
+        .current_dir(dir.path())
+        .env("OLLAMA_HOST", url)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    requests.recv_timeout(Duration::from_secs(20)).unwrap();
+    let started = Instant::now();
+    // SAFETY: signals the child spawned above, which has not been waited on.
+    assert_eq!(unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGINT) }, 0);
+    let output = child.wait_with_output().unwrap();
+    assert_exit(&output, 130);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("interrupted"), "{stderr}");
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
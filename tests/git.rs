@@ -0,0 +1,271 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, MutexGuard};
+
+// Internal Modules
+use doxcer::discovery::{DiscoveryOptions, discover_listed};
+use doxcer::git;
+use doxcer::hook::{HOOK_BEGIN, HOOK_NAME, HookChange, install_hook};
+use doxcer::source::SourceSnapshot;
+
+
+// ====================================================
+//  Fixture
+// ====================================================
+// The git helpers work on the current directory, which is shared by every
+// test in this binary; each test holds this lock while it is inside its repo.
+static CWD: Mutex<()> = Mutex::new(());
+
+struct Repo {
+    dir: tempfile::TempDir,
+    _cwd: MutexGuard<'static, ()>,
+}
+
+impl Repo {
+
+    fn new() -> Repo {
+
+        // Creates an empty repository and makes it the working directory.
+
+        let cwd = CWD.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        env::set_current_dir(dir.path()).unwrap();
+        let repo = Repo { dir, _cwd: cwd };
+        repo.git(&["init", "-q", "-b", "main"]);
+        repo
+    }
+
+
+    fn git(&self, args: &[&str]) -> String {
+
+        // Runs git in the repository with a fixed identity and no user config.
+
+        let output = Command::new("git")
+            .args(["-c", "user.name=doxcer", "-c", "user.email=doxcer@example.com", "-c", "commit.gpgsign=false"])
+            .args(args)
+            .current_dir(self.dir.path())
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?}: {}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
+
+
+    fn write(&self, path: &str, content: &str) {
+        let path = self.dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+
+    fn commit(&self, message: &str) {
+        self.git(&["add", "-A"]);
+        self.git(&["commit", "-q", "-m", message]);
+    }
+
+
+    fn root(&self) -> PathBuf {
+        fs::canonicalize(self.dir.path()).unwrap()
+    }
+}
+
+
+fn canonical(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    paths.into_iter().map(|p| fs::canonicalize(p).unwrap()).collect()
+}
+
+
+// ====================================================
+//  Hooks
+// ====================================================
+#[test]
+fn hooks_dir_defaults_to_the_git_directory() {
+    let repo = Repo::new();
+    assert_eq!(git::hooks_dir().unwrap(), Path::new(".git/hooks"));
+    assert_eq!(fs::canonicalize(git::repo_root().unwrap()).unwrap(), repo.root());
+}
+
+
+#[test]
+fn hooks_dir_follows_core_hooks_path() {
+    let repo = Repo::new();
+    repo.git(&["config", "core.hooksPath", "tools/hooks"]);
+    fs::create_dir_all(repo.dir.path().join("tools/hooks")).unwrap();
+
+    let hooks = git::hooks_dir().unwrap();
+    assert_eq!(hooks, Path::new("tools/hooks"));
+
+    let hook = hooks.join(HOOK_NAME);
+    assert_eq!(install_hook(&hook).unwrap(), HookChange::Created);
+    assert!(fs::read_to_string(repo.dir.path().join("tools/hooks").join(HOOK_NAME)).unwrap().contains(HOOK_BEGIN));
+    assert!(!repo.dir.path().join(".git/hooks").join(HOOK_NAME).exists());
+}
+
+
+// ====================================================
+//  Changed Files
+// ====================================================
+#[test]
+fn changed_files_lists_staged_or_all_changes() {
+    let repo = Repo::new();
+    repo.write("a.ipynb", "{}");
+    repo.write("b.ipynb", "{}");
+    repo.commit("initial");
+
+    repo.write("a.ipynb", "{\"cells\": []}");
+    repo.git(&["add", "a.ipynb"]);
+    repo.write("b.ipynb", "{\"cells\": []}");
+    repo.write("new/c.ipynb", "{}");
+
+    let root = repo.root();
+    assert_eq!(canonical(git::changed_files(true).unwrap()), vec![root.join("a.ipynb")]);
+    assert_eq!(
+        canonical(git::changed_files(false).unwrap()),
+        vec![root.join("a.ipynb"), root.join("b.ipynb"), root.join("new/c.ipynb")]
+    );
+}
+
+
+#[test]
+fn diff_files_lists_the_files_of_a_range() {
+    let repo = Repo::new();
+    repo.write("a.ipynb", "{}");
+    repo.commit("initial");
+    repo.git(&["checkout", "-q", "-b", "feature"]);
+    repo.write("b.ipynb", "{}");
+    repo.commit("add b");
+
+    assert_eq!(canonical(git::diff_files("main...HEAD").unwrap()), vec![repo.root().join("b.ipynb")]);
+}
+
+
+#[test]
+fn diff_files_refuses_ranges_that_look_like_options() {
+    let repo = Repo::new();
+    repo.write("a.ipynb", "{}");
+    repo.commit("initial");
+    let marker = repo.dir.path().join("written");
+
+    for range in ["-p", "--output=written", "--no-index", "", "  "] {
+        let err = git::diff_files(range).unwrap_err().to_string();
+        assert!(err.starts_with("invalid diff range"), "{range:?}: {err}");
+    }
+    assert!(!marker.exists());
+}
+
+
+// ====================================================
+//  At Ref
+// ====================================================
+fn tagged_repo() -> Repo {
+
+    // A repo whose `v1` tag differs from the working tree: `a` was edited,
+    // `gone` was deleted and `new` was added afterwards.
+
+    let repo = Repo::new();
+    repo.write("nb/a.ipynb", "{\"v\": 1}");
+    repo.write("nb/gone.ipynb", "{}");
+    repo.write("other/skip.ipynb", "{}");
+    repo.commit("v1");
+    repo.git(&["tag", "v1"]);
+    repo.write("nb/a.ipynb", "{\"v\": 2}");
+    fs::remove_file(repo.dir.path().join("nb/gone.ipynb")).unwrap();
+    repo.write("nb/new.ipynb", "{}");
+    repo.commit("v2");
+    repo.write("nb/a.ipynb", "{\"v\": 3}");
+    repo
+}
+
+
+#[test]
+fn resolve_ref_gives_the_tagged_commit() {
+    let repo = tagged_repo();
+    let v1 = git::resolve_ref("v1").unwrap();
+    assert_eq!(v1.reference, "v1");
+    assert_eq!(v1.commit, repo.git(&["rev-parse", "v1^{commit}"]));
+    assert_eq!(v1.commit.len(), 40);
+    assert_ne!(git::resolve_ref("HEAD").unwrap().commit, v1.commit);
+    assert!(git::resolve_ref("v9").unwrap_err().to_string().contains("\"v9\" is not a commit"));
+}
+
+
+#[test]
+fn ref_content_reads_the_tagged_version_not_the_working_tree() {
+    let _repo = tagged_repo();
+    let v1 = git::resolve_ref("v1").unwrap();
+
+    assert_eq!(git::ref_content(&v1.commit, Path::new("nb/a.ipynb")).unwrap().unwrap(), b"{\"v\": 1}");
+    assert_eq!(git::ref_content(&v1.commit, Path::new("./nb/gone.ipynb")).unwrap().unwrap(), b"{}");
+    assert_eq!(git::ref_content(&v1.commit, Path::new("nb/new.ipynb")).unwrap(), None);
+    assert_eq!(fs::read("nb/a.ipynb").unwrap(), b"{\"v\": 3}");
+
+    let absolute = git::repo_root().unwrap().join("nb/a.ipynb");
+    assert_eq!(git::ref_content(&v1.commit, &absolute).unwrap().unwrap(), b"{\"v\": 1}");
+}
+
+
+#[test]
+fn staged_content_reads_the_index() {
+    let repo = tagged_repo();
+    assert_eq!(git::staged_content(Path::new("nb/a.ipynb")).unwrap().unwrap(), b"{\"v\": 2}");
+    repo.git(&["add", "nb/a.ipynb"]);
+    assert_eq!(git::staged_content(Path::new("nb/a.ipynb")).unwrap().unwrap(), b"{\"v\": 3}");
+    assert_eq!(git::staged_content(Path::new("nb/untracked.ipynb")).unwrap(), None);
+}
+
+
+#[test]
+fn ref_files_lists_the_tree_of_the_tag_below_the_inputs() {
+    let _repo = tagged_repo();
+    let v1 = git::resolve_ref("v1").unwrap();
+
+    let files = git::ref_files(&v1.commit, &[PathBuf::from("nb")]).unwrap();
+    assert_eq!(files, vec![PathBuf::from("nb/a.ipynb"), PathBuf::from("nb/gone.ipynb")]);
+    assert_eq!(git::ref_files(&v1.commit, &[]).unwrap().len(), 3);
+
+    let head = git::resolve_ref("HEAD").unwrap();
+    let files = git::ref_files(&head.commit, &[PathBuf::from("nb")]).unwrap();
+    assert_eq!(files, vec![PathBuf::from("nb/a.ipynb"), PathBuf::from("nb/new.ipynb")]);
+}
+
+
+#[test]
+fn at_commit_snapshots_hash_the_tagged_bytes() {
+    let _repo = tagged_repo();
+    let v1 = git::resolve_ref("v1").unwrap();
+    let path = Path::new("nb/a.ipynb");
+
+    let tagged = SourceSnapshot::at_commit(path, git::ref_content(&v1.commit, path).unwrap().unwrap(), &v1.commit).unwrap();
+    let working = SourceSnapshot::read(path).unwrap();
+    assert_eq!(tagged.text, "{\"v\": 1}");
+    assert_eq!(tagged.commit.as_deref(), Some(v1.commit.as_str()));
+    assert_ne!(tagged.hash, working.hash);
+}
+
+
+#[test]
+fn discover_listed_finds_notebooks_deleted_since_the_tag() {
+    let _repo = tagged_repo();
+    let v1 = git::resolve_ref("v1").unwrap();
+    let inputs = [PathBuf::from("nb")];
+    let files = git::ref_files(&v1.commit, &inputs).unwrap();
+
+    let found = discover_listed(&inputs, &DiscoveryOptions::default(), &files, |path| {
+        git::ref_content(&v1.commit, path).ok().flatten().and_then(|bytes| String::from_utf8(bytes).ok())
+    })
+    .unwrap();
+    let names: Vec<_> = found.notebooks.iter().map(|p| p.file_name().unwrap().to_string_lossy().to_string()).collect();
+    assert_eq!(names, ["a.ipynb", "gone.ipynb"]);
+}
@@ -0,0 +1,139 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+
+// ====================================================
+//  Fixture
+// ====================================================
+const COMMANDS: [&str; 18] = [
+    "generate", "verify", "check", "init", "config", "coverage", "doctor", "selftest", "cache", "runs",
+    "template", "hook", "keygen", "encrypt", "decrypt", "serve", "watch", "dbt",
+];
+
+fn project() -> tempfile::TempDir {
+
+    // An empty project with one small notebook, which help must never touch.
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("orders.py"), "df = spark.read.table('orders')\n").unwrap();
+    dir
+}
+
+fn doxcer(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_doxcer")).args(args).current_dir(dir).output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[test]
+fn help_lists_every_command_and_exits_zero() {
+    let dir = project();
+    let help = doxcer(dir.path(), &["--help"]);
+    assert_eq!(help.status.code(), Some(0));
+    let text = stdout(&help);
+    assert!(text.contains("Usage: doxcer <command> [options]"), "{text}");
+    for command in COMMANDS {
+        assert!(text.lines().any(|line| line.trim_start().starts_with(command) || line.contains(&format!("|{command}"))), "{command} missing:\n{text}");
+    }
+    assert!(text.contains("-V, --version"), "{text}");
+}
+
+
+#[test]
+fn short_flag_and_help_command_print_the_same_help() {
+    let dir = project();
+    let long = stdout(&doxcer(dir.path(), &["--help"]));
+    for args in [["-h"], ["help"]] {
+        let output = doxcer(dir.path(), &args);
+        assert_eq!(output.status.code(), Some(0), "{args:?}");
+        assert_eq!(stdout(&output), long, "{args:?}");
+    }
+}
+
+
+#[test]
+fn every_command_prints_its_own_usage() {
+    let dir = project();
+    for command in COMMANDS {
+        for flag in ["--help", "-h"] {
+            let output = doxcer(dir.path(), &[command, flag]);
+            assert_eq!(output.status.code(), Some(0), "{command} {flag}: {}", String::from_utf8_lossy(&output.stderr));
+            let text = stdout(&output);
+            let expected = match command {
+                "generate" => "Usage: doxcer [generate] ".to_string(),
+                _ => format!("Usage: doxcer {command} "),
+            };
+            assert!(text.starts_with(&expected), "{command} {flag}:\n{text}");
+            assert!(!text.contains("Usage: doxcer <command>"), "{command} {flag} printed the top-level help");
+        }
+    }
+}
+
+
+#[test]
+fn help_wins_over_the_other_arguments() {
+    let dir = project();
+    let output = doxcer(dir.path(), &["generate", "orders.py", "--output", "docs", "--help"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).starts_with("Usage: doxcer [generate] "));
+    assert!(!dir.path().join("docs").exists());
+}
+
+
+#[test]
+fn an_unknown_option_is_a_usage_error_with_the_usage() {
+    let dir = project();
+    let output = doxcer(dir.path(), &["verify", "--bogus", "orders.py"]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--bogus"), "{stderr}");
+    assert!(stderr.contains("Usage: doxcer verify "), "{stderr}");
+}
+
+
+#[test]
+fn an_unknown_command_is_a_usage_error_not_a_notebook() {
+    let dir = project();
+    let output = doxcer(dir.path(), &["genrate", "orders.py"]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unrecognized subcommand 'genrate'"), "{stderr}");
+    assert!(stderr.contains("'generate'"), "{stderr}");
+}
+
+
+#[test]
+fn init_sets_up_a_project_and_keeps_existing_files() {
+    let dir = project();
+    let output = doxcer(dir.path(), &["init", "--provider", "anthropic"]);
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    let config = fs::read_to_string(dir.path().join("doxcer.toml")).unwrap();
+    assert!(config.contains("provider = \"anthropic\""), "{config}");
+    assert!(dir.path().join("templates/prompt.md").is_file());
+    let env = fs::read_to_string(dir.path().join(".env")).unwrap();
+    assert!(env.starts_with("ENCRYPTION_PASSWORD="), "{env}");
+    assert_eq!(fs::read_to_string(dir.path().join(".gitignore")).unwrap(), ".env\n.doxcer/\n");
+    assert!(stdout(&output).contains("--name ANTHROPIC_API_KEY"));
+
+    fs::write(dir.path().join("doxcer.toml"), "model = \"mine\"\n").unwrap();
+    let again = doxcer(dir.path(), &["init", "--provider", "ollama"]);
+    assert_eq!(again.status.code(), Some(0));
+    assert_eq!(fs::read_to_string(dir.path().join("doxcer.toml")).unwrap(), "model = \"mine\"\n");
+    assert_eq!(fs::read_to_string(dir.path().join(".env")).unwrap(), env);
+    assert_eq!(fs::read_to_string(dir.path().join(".gitignore")).unwrap(), ".env\n.doxcer/\n");
+}
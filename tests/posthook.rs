@@ -0,0 +1,148 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+#![cfg(unix)]
+
+
+// Internal Libraries
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Internal Modules
+use doxcer::posthook::{HookContext, run_post_hook};
+
+
+// ====================================================
+//  Fixture
+// ====================================================
+// Checks the variables doxcer passes, then appends a line to the document.
+const CHECKING_HOOK: &str = r#"#!/bin/sh
+fail() { echo "$1" >&2; exit 3; }
+[ "$1" = "$DOXCER_HOOK_OUTPUT" ] || fail "argument $1 is not DOXCER_HOOK_OUTPUT"
+[ "$DOXCER_HOOK_SOURCE" = "nb/orders.ipynb" ] || fail "source $DOXCER_HOOK_SOURCE"
+[ "$DOXCER_HOOK_SOURCE_HASH" = "sha256:abc" ] || fail "hash $DOXCER_HOOK_SOURCE_HASH"
+[ "$DOXCER_HOOK_MODEL" = "gpt-4o" ] || fail "model $DOXCER_HOOK_MODEL"
+[ "$DOXCER_HOOK_STATUS" = "ok" ] || fail "status $DOXCER_HOOK_STATUS"
+[ "$DOXCER_HOOK_CACHED" = "false" ] || fail "cached $DOXCER_HOOK_CACHED"
+[ "$DOXCER_HOOK_INPUT_TOKENS" = "1200" ] || fail "input tokens $DOXCER_HOOK_INPUT_TOKENS"
+[ "$DOXCER_HOOK_OUTPUT_TOKENS" = "340" ] || fail "output tokens $DOXCER_HOOK_OUTPUT_TOKENS"
+[ -z "$POSTHOOK_TEST_API_KEY" ] || fail "a secret variable was passed on"
+[ "$POSTHOOK_TEST_VISIBLE" = "yes" ] || fail "a plain variable was not passed on"
+echo "<!-- checked by hook -->" >> "$1"
+"#;
+
+fn script(dir: &Path, name: &str, body: &str) -> String {
+
+    // Writes an executable script and returns it quoted as a hook command.
+
+    let path = dir.join(name);
+    fs::write(&path, body).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    format!("'{}'", path.display())
+}
+
+fn context(output: PathBuf) -> HookContext {
+    HookContext {
+        output,
+        source: "nb/orders.ipynb".to_string(),
+        source_hash: "sha256:abc".to_string(),
+        model: "gpt-4o".to_string(),
+        status: "ok".to_string(),
+        cached: false,
+        input_tokens: Some(1200),
+        output_tokens: Some(340),
+    }
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[test]
+fn a_hook_sees_the_document_env_and_can_mutate_the_output() {
+    // SAFETY: no other test in this binary reads or writes these variables.
+    unsafe {
+        env::set_var("POSTHOOK_TEST_API_KEY", "sk-should-not-leak");
+        env::set_var("POSTHOOK_TEST_VISIBLE", "yes");
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("docs with space").join("orders.md");
+    fs::create_dir_all(output.parent().unwrap()).unwrap();
+    fs::write(&output, "# Orders\n").unwrap();
+
+    let hook = script(dir.path(), "check.sh", CHECKING_HOOK);
+    run_post_hook(&hook, &context(output.clone()), Duration::from_secs(10)).unwrap();
+    assert_eq!(fs::read_to_string(&output).unwrap(), "# Orders\n<!-- checked by hook -->\n");
+}
+
+
+#[test]
+fn cached_answers_leave_out_the_token_counts() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("orders.md");
+    fs::write(&output, "# Orders\n").unwrap();
+    let hook = script(
+        dir.path(),
+        "cached.sh",
+        "#!/bin/sh\n[ \"$DOXCER_HOOK_CACHED\" = true ] && [ -z \"${DOXCER_HOOK_INPUT_TOKENS+set}\" ] || exit 4\n",
+    );
+    let context = HookContext { cached: true, input_tokens: None, output_tokens: None, ..context(output) };
+    run_post_hook(&hook, &context, Duration::from_secs(10)).unwrap();
+}
+
+
+#[test]
+fn a_failing_hook_reports_its_exit_code_and_stderr() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("orders.md");
+    fs::write(&output, "# Orders\n").unwrap();
+    let hook = script(dir.path(), "lint.sh", "#!/bin/sh\necho \"MD041 first line\" >&2\nexit 2\n");
+    let err = run_post_hook(&hook, &context(output), Duration::from_secs(10)).unwrap_err().to_string();
+    assert!(err.ends_with("failed with exit code 2: MD041 first line"), "{err}");
+}
+
+
+#[test]
+fn a_hung_hook_is_killed_at_the_timeout() {
+    let dir = tempfile::tempdir().unwrap();
+    let hook = script(dir.path(), "hang.sh", "#!/bin/sh\nexec sleep 30\n");
+    let started = Instant::now();
+    let err = run_post_hook(&hook, &context(dir.path().join("orders.md")), Duration::from_secs(1)).unwrap_err().to_string();
+    assert!(err.ends_with("timed out after 1s"), "{err}");
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+
+#[test]
+fn a_hook_that_backgrounds_a_process_still_times_out() {
+    let dir = tempfile::tempdir().unwrap();
+    let hook = script(dir.path(), "spawn.sh", "#!/bin/sh\nsleep 30 &\nexec sleep 30\n");
+    let started = Instant::now();
+    let err = run_post_hook(&hook, &context(dir.path().join("orders.md")), Duration::from_secs(1)).unwrap_err().to_string();
+    assert!(err.ends_with("timed out after 1s"), "{err}");
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+
+#[test]
+fn a_background_process_left_by_a_hook_is_killed_at_the_timeout() {
+    let dir = tempfile::tempdir().unwrap();
+    let pid_file = dir.path().join("sleep.pid");
+    let body = format!("#!/bin/sh\nsleep 30 &\necho $! > '{}'\nexit 0\n", pid_file.display());
+    let hook = script(dir.path(), "daemon.sh", &body);
+    let started = Instant::now();
+    run_post_hook(&hook, &context(dir.path().join("orders.md")), Duration::from_secs(1)).unwrap();
+    assert!(started.elapsed() < Duration::from_secs(10));
+
+    // The left-over `sleep` is gone, or at most a zombie waiting to be reaped.
+    let pid = fs::read_to_string(&pid_file).unwrap().trim().to_string();
+    thread::sleep(Duration::from_millis(200));
+    let state = fs::read_to_string(format!("/proc/{pid}/stat")).unwrap_or_default();
+    assert!(state.is_empty() || state.contains(") Z "), "{state}");
+}
@@ -0,0 +1,188 @@
+////////////////////////////////////////////////////////
+// AUTHOR   : Stefan B. J. Meeuwessen
+// CREATION : 2026-10-14
+// VERSION  : 0.1.0
+////////////////////////////////////////////////////////
+
+
+// Internal Libraries
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+
+
+// ====================================================
+//  Fixture
+// ====================================================
+const ANSWER: &str = r##"{"response": "# Orders\n\nReads the orders table.\n", "prompt_eval_count": 10, "eval_count": 5}"##;
+
+fn provider() -> String {
+
+    // Starts a provider that answers every request with the same document and
+    // returns its URL, for use as `OLLAMA_HOST`.
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = [0u8; 65536];
+            let _ = stream.read(&mut request);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{ANSWER}",
+                ANSWER.len()
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    url
+}
+
+fn run_with_closed_stdout(dir: &Path, args: &[&str]) -> (Option<i32>, String) {
+
+    // Runs doxcer with a stdout pipe whose reader is gone before the first
+    // write, like `doxcer ... | head` after `head` exited.
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_doxcer"))
+        .args(args)
+        .args(["--provider", "ollama", "--model", "m", "--no-cache"])
+        .current_dir(dir)
+        .env("OLLAMA_HOST", provider())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    drop(child.stdout.take());
+    let output = child.wait_with_output().unwrap();
+    (output.status.code(), String::from_utf8_lossy(&output.stderr).to_string())
+}
+
+fn run_with_closed_stderr(dir: &Path, args: &[&str]) -> (Option<i32>, String) {
+
+    // Runs doxcer with a stderr pipe whose reader is gone, like
+    // `doxcer ... 2>&1 | head -1` once `head` printed its line.
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_doxcer"))
+        .args(args)
+        .args(["--provider", "ollama", "--model", "m", "--no-cache"])
+        .current_dir(dir)
+        .env("OLLAMA_HOST", provider())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    drop(child.stderr.take());
+    let output = child.wait_with_output().unwrap();
+    (output.status.code(), String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn generate(dir: &Path, url: &str, args: &[&str]) -> String {
+
+    // Runs `generate --changed-only` and returns its stderr.
+
+    let output = Command::new(env!("CARGO_BIN_EXE_doxcer"))
+        .arg("generate")
+        .args(args)
+        .args(["--changed-only", "--provider", "ollama", "--model", "m", "--no-cache"])
+        .current_dir(dir)
+        .env("OLLAMA_HOST", url)
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+fn project() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join(".env"), "").unwrap();
+    fs::write(dir.path().join("orders.py"), "df = spark.read.table('orders')\n").unwrap();
+    fs::write(dir.path().join("customers.py"), "df = spark.read.table('customers')\n").unwrap();
+    dir
+}
+
+
+// ====================================================
+//  Tests
+// ====================================================
+#[test]
+fn a_batch_with_a_closed_stdout_still_writes_every_document() {
+    let dir = project();
+    let (code, stderr) = run_with_closed_stdout(dir.path(), &["generate", "orders.py", "customers.py", "--output", "docs"]);
+    assert_eq!(code, Some(0), "{stderr}");
+    assert!(!stderr.contains("panicked"), "{stderr}");
+    for doc in ["docs/orders.md", "docs/customers.md"] {
+        assert!(fs::read_to_string(dir.path().join(doc)).unwrap().contains("Reads the orders table."), "{doc}");
+    }
+}
+
+
+#[test]
+fn a_single_file_to_a_closed_stdout_exits_quietly() {
+    let dir = project();
+    let (code, stderr) = run_with_closed_stdout(dir.path(), &["generate", "orders.py"]);
+    assert_eq!(code, Some(0), "{stderr}");
+    assert!(!stderr.contains("panicked"), "{stderr}");
+    assert!(!stderr.contains("no longer writable"), "{stderr}");
+}
+
+
+#[test]
+fn a_closed_stderr_does_not_panic() {
+    let dir = project();
+    let (code, stdout) = run_with_closed_stderr(dir.path(), &["generate", "orders.py"]);
+    assert_eq!(code, Some(0));
+    assert!(stdout.contains("Reads the orders table."), "{stdout}");
+
+    let (code, _) = run_with_closed_stderr(dir.path(), &["generate", "missing.py"]);
+    assert_eq!(code, Some(4));
+}
+
+
+#[test]
+fn changed_only_skips_a_notebook_only_for_the_output_it_was_written_to() {
+    let dir = project();
+    let url = provider();
+    generate(dir.path(), &url, &["orders.py"]);
+    assert!(generate(dir.path(), &url, &["orders.py"]).contains("unchanged"));
+
+    let stderr = generate(dir.path(), &url, &["orders.py", "--output", "docs"]);
+    assert!(!stderr.contains("unchanged"), "{stderr}");
+    assert!(dir.path().join("docs/orders.md").is_file());
+    assert!(generate(dir.path(), &url, &["orders.py", "--output", "docs", "--overwrite"]).contains("unchanged"));
+
+    let stderr = generate(dir.path(), &url, &["orders.py", "--output", "other"]);
+    assert!(!stderr.contains("unchanged"), "{stderr}");
+    assert!(dir.path().join("other/orders.md").is_file());
+}
+
+
+#[test]
+fn stdout_holds_only_the_document() {
+    let dir = project();
+    let (code, stdout) = run_with_closed_stderr(dir.path(), &["generate", "orders.py", "--no-timestamps"]);
+    assert_eq!(code, Some(0));
+    assert!(!stdout.contains("Preview"), "{stdout}");
+    assert!(stdout.starts_with("---\n"), "{stdout}");
+}
+
+
+#[test]
+fn a_bare_notebook_is_documented_with_ollama_without_any_setup() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("orders.py"), "df = spark.read.table('orders')\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_doxcer"))
+        .args(["orders.py", "--no-cache", "--no-timestamps"])
+        .current_dir(dir.path())
+        .env("DOXCER_PROVIDER", "ollama")
+        .env("DOXCER_MODEL", "m")
+        .env("OLLAMA_HOST", provider())
+        .env_remove("DOXCER_ENV_PATH")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Reads the orders table."));
+    assert!(!String::from_utf8_lossy(&output.stderr).contains(".env"));
+}